@@ -71,13 +71,14 @@ impl Atom {
 
     /// Try the return a reference to the body.
     ///
-    /// This function fails if a) the type URID in the atom does not match with A's URID or b)
-    /// the internal casting function tells that the data is malformed.
+    /// This function fails if a) the type URID in the atom is not accepted by `A`, which usually
+    /// means that it does not match with A's URID, or b) the internal casting function tells that
+    /// the data is malformed.
     pub fn get_body<A: AtomBody + ?Sized>(
         &self,
         urids: &mut urid::CachedMap,
     ) -> Result<&A, GetBodyError> {
-        if self.atom_type != urids.map(A::get_uri()) {
+        if !A::accepts_type(self.atom_type, urids) {
             return Err(GetBodyError::WrongURID);
         }
         let raw_body = self.get_raw_body();
@@ -85,6 +86,10 @@ impl Atom {
     }
 }
 
+unsafe impl lv2rs_core::raw::RawType for Atom {
+    const C_NAME: &'static str = "LV2_Atom";
+}
+
 /// Errors that may occur when calling [`Atom::get_body`](trait.Atom.html#method.get_body).
 #[derive(Debug)]
 pub enum GetBodyError {
@@ -122,6 +127,14 @@ pub trait AtomBody {
     /// Return the URI of the atom type.
     fn get_uri() -> &'static CStr;
 
+    /// Return `true` if atoms of the given type can be read as this type.
+    ///
+    /// By default, only atoms of this type are accepted, but types that are compatible to others
+    /// may accept them too, like booleans accept integers.
+    fn accepts_type(atom_type: URID, urids: &mut urid::CachedMap) -> bool {
+        atom_type == urids.map(Self::get_uri())
+    }
+
     /// Write out a basic but valid atom body.
     ///
     /// Implementors should use the writing frame to write out general information about the atom,
@@ -178,6 +191,16 @@ impl<'a, H: 'static + Sized> AtomIterator<'a, H> {
     }
 }
 
+impl<'a, H: 'static + Sized> Clone for AtomIterator<'a, H> {
+    fn clone(&self) -> Self {
+        AtomIterator {
+            data: self.data,
+            position: self.position,
+            phantom: PhantomData,
+        }
+    }
+}
+
 impl<'a, H: 'static + Sized> Iterator for AtomIterator<'a, H> {
     type Item = (&'a H, &'a Atom);
 
@@ -202,10 +225,16 @@ impl<'a, H: 'static + Sized> Iterator for AtomIterator<'a, H> {
         let atom_ptr = unsafe { pre_header_ptr.add(1) } as *const Atom;
         let atom = unsafe { atom_ptr.as_ref() }?;
 
-        // Apply the package of pre-header, atom and data to our position in the array.
-        self.position += size_of::<H>() + size_of::<Atom>() + atom.size as usize;
+        // Apply the package of pre-header, atom and data to our position in the array. Negative
+        // or huge sizes end the iteration.
+        self.position = match (self.position + size_of::<H>() + size_of::<Atom>())
+            .checked_add(atom.size as usize)
+        {
+            Some(position) => position,
+            None => self.data.len(),
+        };
 
-        if self.position <= self.data.len() {
+        if self.position <= self.data.len() && atom.size >= 0 {
             Some((pre_header, atom))
         } else {
             None
@@ -433,5 +462,30 @@ pub mod array {
             writer.write_raw(data)?;
             Ok(())
         }
+
+        /// Append the items of an iterator to the data.
+        ///
+        /// The space for all items is written at once and the items are written into it
+        /// directly, without collecting them first. If the iterator returns fewer items than its
+        /// length promised, the rest of the space is left zeroed. In case of insufficient memory or
+        /// if the size of the items overflows, an `Err` is returned and nothing is written.
+        ///
+        /// This method assumes that the atom was already initialized, but since can't be checked,
+        /// this method is unsafe.
+        pub unsafe fn extend<'a, W, I>(writer: &mut W, items: I) -> Result<(), ()>
+        where
+            W: WritingFrame<'a> + WritingFrameExt<'a, Self>,
+            I: IntoIterator<Item = T>,
+            I::IntoIter: ExactSizeIterator,
+        {
+            let items = items.into_iter();
+            let n_items = items.len();
+            let data = writer.write_zeroed(n_items.checked_mul(size_of::<T>()).ok_or(())?)?;
+            let data = data.as_mut_ptr() as *mut T;
+            for (index, item) in items.take(n_items).enumerate() {
+                data.add(index).write_unaligned(item);
+            }
+            Ok(())
+        }
     }
 }
@@ -0,0 +1,312 @@
+//! Reusable benchmark scenarios.
+//!
+//! This module is only available with the `bench` feature. It contains typical workloads of the
+//! reading and writing code of this crate, which are used by the benchmarks of this crate and can
+//! be used to measure the performance of your own setup. Every scenario prepares its buffers
+//! when it's created and does the measured work in [`run`](trait.Scenario.html#tymethod.run),
+//! which fits the iteration closure of a benchmark harness like
+//! [`criterion`](https://docs.rs/criterion/):
+//!
+//! ```ignore
+//! #[macro_use]
+//! extern crate criterion;
+//! extern crate lv2rs_atom as atom;
+//!
+//! use criterion::Criterion;
+//!
+//! fn bench_scenarios(c: &mut Criterion) {
+//!     for mut scenario in atom::bench::scenarios() {
+//!         c.bench_function(scenario.name(), |b| b.iter(|| scenario.run()));
+//!     }
+//! }
+//!
+//! criterion_group!(benches, bench_scenarios);
+//! criterion_main!(benches);
+//! ```
+//!
+//! The benchmarks of this crate are run with `cargo bench --features bench`.
+use crate::atom::Atom;
+use crate::object::ObjectWritingFrame;
+use crate::ports::AtomOutputPort;
+use crate::prelude::*;
+use crate::vector::VectorWritingFrame;
+use lv2rs_core::uris::Uri;
+use std::mem::size_of_val;
+use urid::debug::DebugMap;
+use urid::{CachedMap, URID};
+
+/// The URI of the class of the objects in the [`CopyObjects`](struct.CopyObjects.html) scenario.
+pub const NODE_CLASS_URI: Uri = Uri::new(b"http://lv2rs.org/bench#Node\0");
+
+/// A benchmark workload.
+pub trait Scenario {
+    /// Return the name of the scenario, which can be used as the ID of the benchmark.
+    fn name(&self) -> &'static str;
+
+    /// Do the work of the scenario once.
+    ///
+    /// The returned value depends on the work, which keeps the optimizer from removing it when it
+    /// is passed to the benchmark harness.
+    fn run(&mut self) -> usize;
+}
+
+/// Return the scenarios of this crate with their default sizes.
+pub fn scenarios() -> Vec<Box<dyn Scenario>> {
+    vec![
+        Box::new(ReadScalars::new(10_000)),
+        Box::new(CopyObjects::new(8, 16)),
+        Box::new(WriteSpectrum::new(2048)),
+    ]
+}
+
+/// Prepare a buffer like a host and return an output port that writes to it.
+///
+/// The size noted in the header of the buffer is set to the length of the buffer, which has to be
+/// done before every call of
+/// [`write_atom_body`](../ports/struct.AtomOutputPort.html#method.write_atom_body).
+pub fn output_port<A: AtomBody + ?Sized>(space: &mut [u64]) -> AtomOutputPort<A> {
+    let atom = space.as_mut_ptr() as *mut Atom;
+    if let Some(atom) = unsafe { atom.as_mut() } {
+        *(atom.mut_size()) = size_of_val(space) as i32;
+    }
+    let mut port = AtomOutputPort::new();
+    port.connect_port(atom);
+    port
+}
+
+/// Return the atom at the beginning of a buffer.
+fn atom_of(space: &[u64]) -> &Atom {
+    unsafe { (space.as_ptr() as *const Atom).as_ref() }.unwrap()
+}
+
+/// Read a tuple of integer and float atoms.
+pub struct ReadScalars {
+    urids: CachedMap,
+    _map: Box<DebugMap>,
+    space: Vec<u64>,
+}
+
+impl ReadScalars {
+    /// Create the scenario with the number of atoms in the tuple.
+    pub fn new(n_atoms: usize) -> Self {
+        let mut map = DebugMap::new();
+        let mut urids = unsafe { map.create_cached_map() };
+        // The header of the tuple and an atom header and a padded body for every atom.
+        let mut space = vec![0u64; 1 + 2 * n_atoms];
+        let mut port = output_port::<Tuple>(&mut space);
+        {
+            let mut tuple = unsafe { port.write_atom_body(&(), &mut urids) }.unwrap();
+            for index in 0..n_atoms {
+                if index % 2 == 0 {
+                    tuple.push_atom::<i32>(&(index as i32), &mut urids).unwrap();
+                } else {
+                    tuple.push_atom::<f32>(&(index as f32), &mut urids).unwrap();
+                }
+            }
+        }
+        Self {
+            urids,
+            _map: map,
+            space,
+        }
+    }
+}
+
+impl Scenario for ReadScalars {
+    fn name(&self) -> &'static str {
+        "read_scalars"
+    }
+
+    fn run(&mut self) -> usize {
+        let urids = &mut self.urids;
+        let tuple = atom_of(&self.space).get_body::<Tuple>(urids).unwrap();
+        let mut sum = 0;
+        for atom in tuple.iter() {
+            if let Ok(value) = atom.get_body::<i32>(urids) {
+                sum += *value as usize;
+            } else if let Ok(value) = atom.get_body::<f32>(urids) {
+                sum += *value as usize;
+            }
+        }
+        sum
+    }
+}
+
+/// Copy a tree of objects to another buffer.
+///
+/// Every object of the tree has a number of float properties and, except for the innermost one,
+/// a property with the next object. The copy is done by writing new objects and copying the other
+/// properties as they are.
+pub struct CopyObjects {
+    urids: CachedMap,
+    _map: Box<DebugMap>,
+    source: Vec<u64>,
+    target: Vec<u64>,
+}
+
+impl CopyObjects {
+    /// Create the scenario with the number of nested objects and the number of float properties
+    /// per object.
+    pub fn new(depth: usize, width: usize) -> Self {
+        let mut map = DebugMap::new();
+        let mut urids = unsafe { map.create_cached_map() };
+        let class = urids.map(NODE_CLASS_URI);
+        // Every object needs two words for its headers, three words for every float property and
+        // two for the property header of the next object.
+        let mut source = vec![0u64; (depth + 1) * (4 + 3 * width)];
+        let target = source.clone();
+        let mut port = output_port::<Object>(&mut source);
+        {
+            let mut object = unsafe { port.write_atom_body(&(0, class), &mut urids) }.unwrap();
+            write_node(&mut object, class, depth, width, &mut urids).unwrap();
+        }
+        Self {
+            urids,
+            _map: map,
+            source,
+            target,
+        }
+    }
+}
+
+/// Write the properties of an object of the tree.
+fn write_node<'a, W: ObjectWritingFrame<'a>>(
+    frame: &mut W,
+    class: URID,
+    depth: usize,
+    width: usize,
+    urids: &mut CachedMap,
+) -> Result<(), ()> {
+    for index in 0..width {
+        frame.push_property::<f32>(index as URID + 1, 0, &(index as f32), urids)?;
+    }
+    if depth > 0 {
+        let mut child = frame.push_property::<Object>(0, 0, &(0, class), urids)?;
+        write_node(&mut child, class, depth - 1, width, urids)?;
+    }
+    Ok(())
+}
+
+/// Copy the properties of an object to an object frame.
+fn copy_node<'a, W: ObjectWritingFrame<'a>>(
+    frame: &mut W,
+    source: &Object,
+    urids: &mut CachedMap,
+) -> Result<(), ()> {
+    for (property, atom) in source.iter() {
+        if let Ok(object) = atom.get_body::<Object>(urids) {
+            let header = (object.header.id, object.header.otype);
+            let mut child =
+                frame.push_property::<Object>(property.key, property.context, &header, urids)?;
+            copy_node(&mut child, object, urids)?;
+        } else {
+            unsafe {
+                frame.write_sized(property)?;
+                frame.write_sized(atom)?;
+                frame.write_raw(atom.get_raw_body())?;
+                let padding = (8 - atom.size() % 8) % 8;
+                frame.write_raw(&[0; 8][..padding])?;
+            }
+        }
+    }
+    Ok(())
+}
+
+impl Scenario for CopyObjects {
+    fn name(&self) -> &'static str {
+        "copy_objects"
+    }
+
+    fn run(&mut self) -> usize {
+        let urids = &mut self.urids;
+        let source = atom_of(&self.source).get_body::<Object>(urids).unwrap();
+        let header = (source.header.id, source.header.otype);
+        let mut port = output_port::<Object>(&mut self.target);
+        let mut frame = unsafe { port.write_atom_body(&header, urids) }.unwrap();
+        copy_node(&mut frame, source, urids).unwrap();
+        frame.get_atom().size()
+    }
+}
+
+/// Write the bins of a spectrum to a vector.
+///
+/// The bins are computed while they are written, like the output of an analysis plugin that is
+/// written in every cycle.
+pub struct WriteSpectrum {
+    urids: CachedMap,
+    _map: Box<DebugMap>,
+    n_bins: usize,
+    space: Vec<u64>,
+}
+
+impl WriteSpectrum {
+    /// Create the scenario with the number of bins.
+    pub fn new(n_bins: usize) -> Self {
+        let mut map = DebugMap::new();
+        let urids = unsafe { map.create_cached_map() };
+        // The header of the atom and the vector and the padded bins.
+        let space = vec![0u64; 2 + n_bins.div_ceil(2)];
+        Self {
+            urids,
+            _map: map,
+            n_bins,
+            space,
+        }
+    }
+}
+
+impl Scenario for WriteSpectrum {
+    fn name(&self) -> &'static str {
+        "write_spectrum"
+    }
+
+    fn run(&mut self) -> usize {
+        let urids = &mut self.urids;
+        let n_bins = self.n_bins;
+        let mut port = output_port::<Vector<f32>>(&mut self.space);
+        let mut frame = unsafe { port.write_atom_body(&(), urids) }.unwrap();
+        let bins = (0..n_bins).map(|bin| 1.0 / (bin as f32 + 1.0));
+        frame.extend(bins).unwrap();
+        frame.get_atom().size()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bench::*;
+
+    #[test]
+    fn test_read_scalars() {
+        let mut scenario = ReadScalars::new(10);
+        assert_eq!("read_scalars", scenario.name());
+        assert_eq!(45, scenario.run());
+        assert_eq!(45, scenario.run());
+    }
+
+    #[test]
+    fn test_copy_objects() {
+        let mut scenario = CopyObjects::new(2, 3);
+        assert_eq!("copy_objects", scenario.name());
+        let size = atom_of(&scenario.source).size();
+        assert_eq!(size, scenario.run());
+        assert_eq!(size, scenario.run());
+        assert_eq!(scenario.source, scenario.target);
+    }
+
+    #[test]
+    fn test_write_spectrum() {
+        let mut scenario = WriteSpectrum::new(5);
+        assert_eq!("write_spectrum", scenario.name());
+        assert_eq!(8 + 4 * 5, scenario.run());
+        assert_eq!(8 + 4 * 5, scenario.run());
+        let vector = atom_of(&scenario.space)
+            .get_body::<Vector<f32>>(&mut scenario.urids)
+            .unwrap();
+        assert_eq!([1.0, 0.5, 1.0 / 3.0, 0.25, 0.2], vector.as_slice());
+
+        // The default scenarios fit into their buffers.
+        for mut scenario in scenarios() {
+            assert!(scenario.run() > 0);
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! Copying a sequence while filtering and rewriting its events.
+//!
+//! Many plugins that process events, like MIDI filters, transposers or velocity scalers, share the
+//! same skeleton: They iterate over the input sequence and, for every event, either drop it, copy
+//! it to the output sequence as it is or write a rewritten atom instead. The
+//! [`filter_sequence`](fn.filter_sequence.html) function implements this skeleton and calls a
+//! closure with a [`FilteredEvent`](struct.FilteredEvent.html) for every event, which decides what
+//! happens with it:
+//!
+//!     extern crate lv2rs_atom as atom;
+//!     extern crate lv2rs_core as core;
+//!     extern crate lv2rs_urid as urid;
+//!
+//!     use atom::filter::filter_sequence;
+//!     use atom::ports::*;
+//!     use atom::prelude::*;
+//!     use atom::sequence::{TimeStamp, TimeUnit};
+//!     use urid::debug::DebugMap;
+//!
+//!     let mut debug_map = DebugMap::new();
+//!     let mut urids = unsafe { debug_map.create_cached_map() };
+//!
+//!     // Creating the atom spaces and the ports.
+//!     let mut input_space = vec![0u64; 16];
+//!     let input_atom = unsafe { (input_space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+//!     *(input_atom.mut_size()) = 128 - 8;
+//!     let mut host_port: AtomOutputPort<Sequence> = AtomOutputPort::new();
+//!     host_port.connect_port(input_atom);
+//!     let mut in_port: AtomInputPort<Sequence> = AtomInputPort::new();
+//!     in_port.connect_port(input_atom as &Atom);
+//!
+//!     let mut output_space = vec![0u64; 16];
+//!     let output_atom = unsafe { (output_space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+//!     *(output_atom.mut_size()) = 128 - 8;
+//!     let mut out_port: AtomOutputPort<Sequence> = AtomOutputPort::new();
+//!     out_port.connect_port(output_atom);
+//!
+//!     // Writing the input sequence, like a host would do.
+//!     {
+//!         let mut frame =
+//!             unsafe { host_port.write_atom_body(&TimeUnit::Frames, &mut urids) }.unwrap();
+//!         frame.push_event::<f32>(TimeStamp::Frames(0), &0.25, &mut urids).unwrap();
+//!         frame.push_event::<i32>(TimeStamp::Frames(1), &42, &mut urids).unwrap();
+//!         frame.push_event::<f32>(TimeStamp::Frames(2), &0.5, &mut urids).unwrap();
+//!     }
+//!
+//!     // Simulated `run` method: Doubling all floats and passing everything else through.
+//!     {
+//!         let context = core::RunContext::new(3, 0, 44100.0, None);
+//!         let input = unsafe { in_port.read(&context, &mut urids) }.unwrap();
+//!         let mut output =
+//!             unsafe { out_port.write_atom_body(&TimeUnit::Frames, &mut urids) }.unwrap();
+//!         filter_sequence(input, &mut output, &mut urids, |event, urids| {
+//!             match unsafe { event.atom().get_body::<f32>(urids) } {
+//!                 Ok(value) => event.replace::<f32>(&(value * 2.0), urids).map(|_| ()),
+//!                 Err(_) => event.pass(urids),
+//!             }
+//!         })
+//!         .unwrap();
+//!     }
+//!
+//!     let output_atom = unsafe { (output_space.as_ptr() as *const Atom).as_ref() }.unwrap();
+//!     let output = unsafe { output_atom.get_body::<Sequence>(&mut urids) }.unwrap();
+//!     let mut events = output.iter(&mut urids);
+//!     let (_, first) = events.next().unwrap();
+//!     assert_eq!(0.5, *unsafe { first.get_body::<f32>(&mut urids) }.unwrap());
+//!     let (_, second) = events.next().unwrap();
+//!     assert_eq!(42, *unsafe { second.get_body::<i32>(&mut urids) }.unwrap());
+//!     let (time, third) = events.next().unwrap();
+//!     assert_eq!(TimeStamp::Frames(2), time);
+//!     assert_eq!(1.0, *unsafe { third.get_body::<f32>(&mut urids) }.unwrap());
+//!     assert!(events.next().is_none());
+//!
+//! Events are dropped unless the closure passes or replaces them. Passing an event copies its
+//! bytes without interpreting them, which means that events of unknown types survive the filter.
+use crate::atom::{Atom, AtomBody};
+use crate::frame::NestedFrame;
+use crate::sequence::{check_time_unit, PushEventError, Sequence, SequenceWritingFrame, TimeStamp};
+use urid::CachedMap;
+
+/// An event of the input sequence that is handled by the closure of
+/// [`filter_sequence`](fn.filter_sequence.html).
+///
+/// The event is dropped unless it is passed through or replaced. It is possible to write several
+/// events for one input event, for example to duplicate it.
+pub struct FilteredEvent<'i, 'w, W> {
+    time: TimeStamp,
+    atom: &'i Atom,
+    output: &'w mut W,
+}
+
+impl<'i, 'w, W> FilteredEvent<'i, 'w, W> {
+    /// Return the time stamp of the event.
+    pub fn time(&self) -> TimeStamp {
+        self.time.clone()
+    }
+
+    /// Return the atom of the event.
+    pub fn atom(&self) -> &'i Atom {
+        self.atom
+    }
+
+    /// Copy the event to the output sequence as it is.
+    ///
+    /// This fails if the output sequence is full or has another time unit than the input
+    /// sequence.
+    pub fn pass<'a>(&mut self, urids: &mut CachedMap) -> Result<(), PushEventError>
+    where
+        W: SequenceWritingFrame<'a>,
+    {
+        self.pass_at(self.time.clone(), urids)
+    }
+
+    /// Copy the atom of the event to the output sequence, but with another time stamp.
+    pub fn pass_at<'a>(
+        &mut self,
+        time: TimeStamp,
+        urids: &mut CachedMap,
+    ) -> Result<(), PushEventError>
+    where
+        W: SequenceWritingFrame<'a>,
+    {
+        self.output.push_event_copy(time, self.atom, urids)
+    }
+
+    /// Write a new atom with the time stamp of the event to the output sequence.
+    ///
+    /// The returned frame can be used to write the rest of the atom, for example the properties
+    /// of an object.
+    /// Like [`pass`](#method.pass), this fails if the output sequence is full or has another time
+    /// unit.
+    pub fn replace<'a, A: AtomBody + ?Sized>(
+        &mut self,
+        parameter: &A::InitializationParameter,
+        urids: &mut CachedMap,
+    ) -> Result<NestedFrame<'_, 'a, A>, PushEventError>
+    where
+        W: SequenceWritingFrame<'a>,
+    {
+        self.replace_at::<A>(self.time.clone(), parameter, urids)
+    }
+
+    /// Write a new atom with another time stamp to the output sequence.
+    pub fn replace_at<'a, A: AtomBody + ?Sized>(
+        &mut self,
+        time: TimeStamp,
+        parameter: &A::InitializationParameter,
+        urids: &mut CachedMap,
+    ) -> Result<NestedFrame<'_, 'a, A>, PushEventError>
+    where
+        W: SequenceWritingFrame<'a>,
+    {
+        check_time_unit(self.output, &time, urids)?;
+        self.output
+            .push_event::<A>(time, parameter, urids)
+            .map_err(|_| PushEventError::InsufficientSpace)
+    }
+}
+
+/// Copy the events of a sequence to another one, while letting a closure decide what happens with
+/// every event.
+///
+/// The closure receives the events in the order of the input sequence, together with the URID
+/// map. If it returns an error, for example because the output sequence is full, the remaining
+/// events are skipped and the error is returned.
+pub fn filter_sequence<'a, W, F, E>(
+    input: &Sequence,
+    output: &mut W,
+    urids: &mut CachedMap,
+    mut filter: F,
+) -> Result<(), E>
+where
+    W: SequenceWritingFrame<'a>,
+    F: FnMut(&mut FilteredEvent<W>, &mut CachedMap) -> Result<(), E>,
+{
+    for (time, atom) in input.iter(urids) {
+        let mut event = FilteredEvent {
+            time,
+            atom,
+            output: &mut *output,
+        };
+        filter(&mut event, urids)?;
+    }
+    Ok(())
+}
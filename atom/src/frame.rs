@@ -30,6 +30,26 @@ pub trait WritingFrame<'a> {
     /// You have to know what you are doing!
     unsafe fn write_raw(&mut self, data: &[u8]) -> Result<&'a mut [u8], ()>;
 
+    /// Try to write a number of zero bytes into the atom space.
+    ///
+    /// This works like [`write_raw`](#tymethod.write_raw), but the written bytes are returned to
+    /// be filled afterwards, which avoids creating the data somewhere else first. The frames of
+    /// this crate write either all bytes or none of them. The default implementation writes the
+    /// bytes in small chunks and may therefore run out of space after writing some of them.
+    ///
+    /// This function is unsafe for the same reasons as `write_raw`.
+    unsafe fn write_zeroed(&mut self, size: usize) -> Result<&'a mut [u8], ()> {
+        const ZEROS: [u8; 64] = [0; 64];
+        let start = self.write_raw(&ZEROS[..size.min(64)])?.as_mut_ptr();
+        let mut written = size.min(64);
+        while written < size {
+            let chunk_size = (size - written).min(64);
+            self.write_raw(&ZEROS[..chunk_size])?;
+            written += chunk_size;
+        }
+        Ok(std::slice::from_raw_parts_mut(start, size))
+    }
+
     /// Return an immutable reference to the managed atom header.
     fn get_atom(&self) -> &Atom;
 }
@@ -154,6 +174,26 @@ impl<'a, A: AtomBody + ?Sized> WritingFrame<'a> for RootFrame<'a, A> {
         Ok(target_data)
     }
 
+    unsafe fn write_zeroed(&mut self, size: usize) -> Result<&'a mut [u8], ()> {
+        if size > self.free_data.len() {
+            return Err(());
+        }
+
+        let data_ptr = self.free_data.as_mut_ptr();
+        let n_free_bytes = self.free_data.len() - size;
+
+        let target_data = std::slice::from_raw_parts_mut(data_ptr, size);
+        let free_data = std::slice::from_raw_parts_mut(data_ptr.add(size), n_free_bytes);
+
+        for byte in target_data.iter_mut() {
+            *byte = 0;
+        }
+        self.free_data = free_data;
+        *(self.atom.mut_size()) += size as i32;
+
+        Ok(target_data)
+    }
+
     fn get_atom(&self) -> &Atom {
         self.atom
     }
@@ -209,6 +249,12 @@ where
         Ok(data)
     }
 
+    unsafe fn write_zeroed(&mut self, size: usize) -> Result<&'b mut [u8], ()> {
+        let data = self.parent.write_zeroed(size)?;
+        *(self.atom.mut_size()) += data.len() as i32;
+        Ok(data)
+    }
+
     fn get_atom(&self) -> &Atom {
         self.atom
     }
@@ -0,0 +1,221 @@
+//! Entry points for fuzzers.
+//!
+//! This module is only available with the `fuzzing` feature. Reading atoms involves a lot of
+//! unsafe pointer casts, which have to reject malformed data coming from the host or other
+//! plugins. [`parse_any_atom`](fn.parse_any_atom.html) throws arbitrary bytes at all of the
+//! reading functions of this crate, which makes it a good target for a fuzzer like
+//! [`cargo-fuzz`](https://github.com/rust-fuzz/cargo-fuzz). A fuzz target only needs to forward
+//! its input to [`fuzz_atom`](fn.fuzz_atom.html):
+//!
+//! ```ignore
+//! #![no_main]
+//! #[macro_use]
+//! extern crate libfuzzer_sys;
+//! extern crate lv2rs_atom as atom;
+//!
+//! fuzz_target!(|data: &[u8]| atom::fuzzing::fuzz_atom(data));
+//! ```
+//!
+//! A malformed atom must never lead to a panic or to undefined behaviour, which is best checked
+//! by running the fuzzer with a sanitizer.
+use crate::atom::Atom;
+use crate::prelude::*;
+use std::mem::size_of;
+use urid::{CachedMap, URID};
+
+/// The maximal nesting depth of containers that is followed.
+///
+/// Deeper atoms are ignored to keep the stack of the fuzzer small.
+pub const MAX_DEPTH: usize = 32;
+
+/// Try to read an atom with header from arbitrary bytes.
+///
+/// The data is copied to a 64-bit aligned buffer, as a host would provide it, and interpreted as
+/// every atom type of this crate. Containers like tuples, objects and sequences are traversed and
+/// their children are parsed the same way. The types are tried in a fixed order, which means
+/// that a fresh map assigns the same URIDs to them in every run.
+///
+/// `Err` is returned if the data is too short for the header or the atom it describes. Otherwise,
+/// the number of atoms that could be read as one of the types is returned.
+pub fn parse_any_atom(data: &[u8], urids: &mut CachedMap) -> Result<usize, ()> {
+    if data.len() < size_of::<Atom>() {
+        return Err(());
+    }
+    let mut buffer = vec![0u64; data.len().div_ceil(8)];
+    let bytes =
+        unsafe { std::slice::from_raw_parts_mut(buffer.as_mut_ptr() as *mut u8, data.len()) };
+    bytes.copy_from_slice(data);
+
+    let atom = unsafe { (bytes.as_ptr() as *const Atom).as_ref() }.ok_or(())?;
+    // Negative sizes are converted to huge ones, which overflow.
+    match size_of::<Atom>().checked_add(atom.size()) {
+        Some(length) if length <= data.len() => Ok(parse_atom(atom, urids, 0)),
+        _ => Err(()),
+    }
+}
+
+/// Parse arbitrary bytes as an atom with a fresh map.
+///
+/// This function is meant to be called by a fuzz target and ignores the result.
+pub fn fuzz_atom(data: &[u8]) {
+    let mut debug_map = urid::debug::DebugMap::new();
+    let mut urids = unsafe { debug_map.create_cached_map() };
+    let _ = parse_any_atom(data, &mut urids);
+}
+
+/// Interpret an atom as every known type and return the number of successful readings.
+fn parse_atom(atom: &Atom, urids: &mut CachedMap, depth: usize) -> usize {
+    if depth >= MAX_DEPTH {
+        return 0;
+    }
+    let mut n_atoms = 0;
+
+    if let Ok(value) = atom.get_body::<i32>(urids) {
+        n_atoms += 1;
+        let _ = value.checked_abs();
+    }
+    if let Ok(value) = atom.get_body::<i64>(urids) {
+        n_atoms += 1;
+        let _ = value.checked_abs();
+    }
+    if let Ok(value) = atom.get_body::<f32>(urids) {
+        n_atoms += 1;
+        let _ = value.is_nan();
+    }
+    if let Ok(value) = atom.get_body::<f64>(urids) {
+        n_atoms += 1;
+        let _ = value.is_nan();
+    }
+    if let Ok(value) = atom.get_body::<URID>(urids) {
+        n_atoms += 1;
+        let _ = value.count_ones();
+    }
+    if atom.get_body::<bool>(urids).is_ok() {
+        n_atoms += 1;
+    }
+    if let Ok(literal) = atom.get_body::<Literal>(urids) {
+        n_atoms += 1;
+        let _ = literal.as_str();
+        let _ = literal.lang();
+    }
+    if let Ok(string) = atom.get_body::<AtomString>(urids) {
+        n_atoms += 1;
+        let _ = string.as_cstr();
+    }
+    if let Ok(vector) = atom.get_body::<Vector<i32>>(urids) {
+        n_atoms += 1;
+        let _ = (vector.child_body_size(), vector.child_body_type());
+        let _ = vector
+            .as_slice()
+            .iter()
+            .fold(0i32, |a, b| a.wrapping_add(*b));
+    }
+    if let Ok(vector) = atom.get_body::<Vector<f64>>(urids) {
+        n_atoms += 1;
+        let _ = vector.as_slice().iter().sum::<f64>();
+    }
+    if let Ok(tuple) = atom.get_body::<Tuple>(urids) {
+        n_atoms += 1;
+        for child in tuple.iter() {
+            n_atoms += parse_atom(child, urids, depth + 1);
+        }
+    }
+    if let Ok(object) = atom.get_body::<Object>(urids) {
+        n_atoms += 1;
+        for (header, child) in object.iter() {
+            let _ = (header.key, header.context);
+            n_atoms += parse_atom(child, urids, depth + 1);
+        }
+    }
+    if let Ok(sequence) = atom.get_body::<Sequence>(urids) {
+        n_atoms += 1;
+        let _ = sequence.time_unit(urids);
+        let events: Vec<&Atom> = sequence.iter(urids).map(|(_, event)| event).collect();
+        for event in events {
+            n_atoms += parse_atom(event, urids, depth + 1);
+        }
+    }
+    n_atoms
+}
+
+#[cfg(test)]
+mod test {
+    use crate::fuzzing::*;
+    use crate::ports::AtomOutputPort;
+    use crate::sequence::{TimeStamp, TimeUnit};
+    use urid::debug::DebugMap;
+
+    #[test]
+    fn test_parse_any_atom() {
+        let mut debug_map = DebugMap::new();
+        let mut urids = unsafe { debug_map.create_cached_map() };
+
+        // A tuple with an integer and a sequence with a literal. The integer is read as a
+        // boolean too, which makes five readings for four atoms.
+        let mut space = vec![0u64; 16];
+        let atom = unsafe { (space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+        *(atom.mut_size()) = 128 - 8;
+        let mut port: AtomOutputPort<Tuple> = AtomOutputPort::new();
+        port.connect_port(atom);
+        {
+            let mut tuple = unsafe { port.write_atom_body(&(), &mut urids) }.unwrap();
+            tuple.push_atom::<i32>(&42, &mut urids).unwrap();
+            let mut sequence = tuple
+                .push_atom::<Sequence>(&TimeUnit::Frames, &mut urids)
+                .unwrap();
+            let mut literal = sequence
+                .push_event::<Literal>(TimeStamp::Frames(0), &0, &mut urids)
+                .unwrap();
+            literal.append_string("Hello").unwrap();
+        }
+        let atom = unsafe { (space.as_ptr() as *const Atom).as_ref() }.unwrap();
+        let length = 8 + atom.size();
+        let bytes = unsafe { std::slice::from_raw_parts(space.as_ptr() as *const u8, length) };
+        assert_eq!(Ok(5), parse_any_atom(bytes, &mut urids));
+
+        // The data is copied, so it doesn't need to be aligned.
+        let mut unaligned = vec![0u8];
+        unaligned.extend_from_slice(bytes);
+        assert_eq!(Ok(5), parse_any_atom(&unaligned[1..], &mut urids));
+
+        // Every prefix and every corrupted byte is handled gracefully.
+        for end in 0..length {
+            assert_eq!(Err(()), parse_any_atom(&bytes[..end], &mut urids));
+        }
+        let mut corrupted = bytes.to_owned();
+        for index in 0..length {
+            for value in [0x00, 0x01, 0x7f, 0x80, 0xff].iter() {
+                let original = corrupted[index];
+                corrupted[index] = *value;
+                let _ = parse_any_atom(&corrupted, &mut urids);
+                corrupted[index] = original;
+            }
+        }
+    }
+
+    #[test]
+    fn test_fuzz_atom() {
+        fuzz_atom(&[]);
+        // A tuple claiming to be longer than the data.
+        fuzz_atom(&[16, 0, 0, 0, 1, 0, 0, 0]);
+        // A child atom with a negative size.
+        fuzz_atom(&[
+            16, 0, 0, 0, 10, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ]);
+
+        // Pseudo-random data with a valid header.
+        let mut state = 4711u32;
+        for _ in 0..1000 {
+            let mut data = vec![0u8; 64];
+            for byte in data.iter_mut() {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                *byte = (state >> 16) as u8;
+            }
+            data[0] = 56;
+            data[1..4].copy_from_slice(&[0, 0, 0]);
+            data[4] = (data[4] % 16) + 1;
+            data[5..8].copy_from_slice(&[0, 0, 0]);
+            fuzz_atom(&data);
+        }
+    }
+}
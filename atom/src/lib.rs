@@ -58,19 +58,44 @@
 //! for meaningfulness. Instead, you should use the safe methods provided by the writing frame
 //! extensions, which are tailored for specific atoms and guarantee the consistency of the resulting
 //! output. You can read more about them in their specific module descriptions.
+//!
+//! ### Fuzzing and benchmarks
+//!
+//! With the `fuzzing` feature, the [`fuzzing`](fuzzing/index.html) module provides entry points
+//! for fuzzers that feed arbitrary bytes to the reading functions of this crate. With the `bench`
+//! feature, the [`bench`](bench/index.html) module provides benchmark scenarios for the reading
+//! and writing code.
+//!
+//! ### Compatibility
+//!
+//! Some hosts pad the bodies of scalar atoms to eight bytes. These atoms are rejected by default,
+//! since their size doesn't match, but they are accepted with the `padded-scalars` feature.
+extern crate lv2rs_core;
 extern crate lv2rs_urid as urid;
 
 mod atom;
+#[cfg(feature = "bench")]
+pub mod bench;
+pub mod filter;
 pub mod frame;
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing;
 pub mod literal;
 pub mod object;
+pub mod osc;
+pub mod patch;
 pub mod ports;
 pub mod scalar;
+pub mod schema;
 pub mod sequence;
+pub mod split;
 pub mod string;
+pub mod time;
 pub mod tuple;
+pub mod units;
 pub mod uris;
 pub mod vector;
+pub mod visit;
 
 pub use atom::*;
 
@@ -15,6 +15,7 @@
 //! An example:
 //!
 //!     extern crate lv2rs_atom as atom;
+//!     extern crate lv2rs_core as core;
 //!     extern crate lv2rs_urid as urid;
 //!
 //!     use atom::prelude::*;
@@ -30,7 +31,7 @@
 //!
 //!     impl Plugin {
 //!         /// Simulated `run` method.
-//!         fn run(&mut self) {
+//!         fn run(&mut self, context: &mut core::RunContext) {
 //!             // Writing
 //!             {
 //!                 let mut frame =
@@ -39,7 +40,7 @@
 //!             }
 //!
 //!             // Reading.
-//!             let literal = unsafe { self.in_port.get_atom_body(&mut self.urids) }.unwrap();
+//!             let literal = unsafe { self.in_port.read(context, &mut self.urids) }.unwrap();
 //!             let message = literal.as_str().unwrap();
 //!             assert_eq!("Hello World!", message);
 //!         }
@@ -66,7 +67,7 @@
 //!     plugin.out_port.connect_port(atom);
 //!
 //!     // Calling `run`.
-//!     plugin.run();
+//!     plugin.run(&mut core::RunContext::new(1, 0, 44100.0, None));
 use crate::atom::{array::*, *};
 use crate::frame::{WritingFrame, WritingFrameExt};
 use crate::uris;
@@ -114,7 +115,7 @@ impl AtomBody for Literal {
     type InitializationParameter = URID;
 
     fn get_uri() -> &'static CStr {
-        unsafe { CStr::from_bytes_with_nul_unchecked(uris::LITERAL_TYPE_URI) }
+        uris::LITERAL_TYPE_URI.as_cstr()
     }
 
     unsafe fn initialize_body<'a, W>(
@@ -16,6 +16,7 @@
 //! An example:
 //!
 //!     extern crate lv2rs_atom as atom;
+//!     extern crate lv2rs_core as core;
 //!     extern crate lv2rs_urid as urid;
 //!
 //!     use atom::prelude::*;
@@ -31,7 +32,7 @@
 //!
 //!     impl Plugin {
 //!         /// Simulated `run` method.
-//!         fn run(&mut self) {
+//!         fn run(&mut self, context: &mut core::RunContext) {
 //!             let my_class_urid = self.urids.map(
 //!                 CStr::from_bytes_with_nul(b"https://example.org#MyClass\0").unwrap()
 //!             );
@@ -62,7 +63,7 @@
 //!             }
 //!
 //!             // Reading
-//!             let object = unsafe { self.in_port.get_atom_body(&mut self.urids) }.unwrap();
+//!             let object = unsafe { self.in_port.read(context, &mut self.urids) }.unwrap();
 //!             // We're iterating through the properties. If a property matches our known key,
 //!             // We assert that it has the right value.
 //!             for (header, property_atom) in object.iter() {
@@ -100,7 +101,7 @@
 //!     plugin.out_port.connect_port(atom);
 //!
 //!     // Calling `run`.
-//!     plugin.run();
+//!     plugin.run(&mut core::RunContext::new(1, 0, 44100.0, None));
 use crate::atom::{array::*, *};
 use crate::frame::{NestedFrame, WritingFrame, WritingFrameExt};
 use crate::uris;
@@ -166,7 +167,7 @@ impl AtomBody for Object {
     type InitializationParameter = (URID, URID);
 
     fn get_uri() -> &'static CStr {
-        unsafe { CStr::from_bytes_with_nul_unchecked(uris::OBJECT_TYPE_URI) }
+        uris::OBJECT_TYPE_URI.as_cstr()
     }
 
     unsafe fn initialize_body<'a, W>(
@@ -0,0 +1,510 @@
+//! OSC messages and bundles.
+//!
+//! There is no official LV2 extension for Open Sound Control, but most plugins and hosts follow
+//! the conventions of the [OSC extension](http://open-music-kontrollers.ch/lv2/osc) by Open Music
+//! Kontrollers: A message is an object of the class `osc:Message` with the address pattern as a
+//! string and the arguments as a tuple, and a bundle is an object of the class `osc:Bundle` with
+//! a timetag object and a tuple of messages and bundles. These objects are usually sent in
+//! sequences, just like MIDI events.
+//!
+//! A packet is read with [`read_packet`](fn.read_packet.html), which returns either a
+//! [`Message`](struct.Message.html) or a [`Bundle`](struct.Bundle.html). The arguments of a
+//! message are read one after another with an [`ArgumentReader`](struct.ArgumentReader.html).
+//! For writing, the [`OscWritingFrame`](trait.OscWritingFrame.html) fills a message or bundle
+//! object and the [`ArgumentWritingFrame`](trait.ArgumentWritingFrame.html) pushes the arguments:
+//!
+//!     extern crate lv2rs_atom as atom;
+//!     extern crate lv2rs_urid as urid;
+//!
+//!     use atom::osc::*;
+//!     use atom::ports::*;
+//!     use atom::prelude::*;
+//!     use urid::debug::DebugMap;
+//!
+//!     let mut debug_map = DebugMap::new();
+//!     let mut urids = unsafe { debug_map.create_cached_map() };
+//!     let osc_urids = OscURIDs::new(&mut urids);
+//!
+//!     // Creating the atom space and the ports.
+//!     let mut atom_space = vec![0u64; 32];
+//!     let atom = unsafe { (atom_space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+//!     *(atom.mut_size()) = 256;
+//!     let mut out_port: AtomOutputPort<Object> = AtomOutputPort::new();
+//!     out_port.connect_port(atom);
+//!
+//!     // Writing a message.
+//!     {
+//!         let mut object =
+//!             unsafe { out_port.write_atom_body(&(0, osc_urids.message), &mut urids) }.unwrap();
+//!         let mut arguments = object.write_message("/mixer/gain", &mut urids).unwrap();
+//!         arguments.push_argument(&Argument::Int(2), &mut urids).unwrap();
+//!         arguments.push_argument(&Argument::Float(0.5), &mut urids).unwrap();
+//!     }
+//!
+//!     // Reading the message.
+//!     let atom = unsafe { (atom_space.as_ptr() as *const Atom).as_ref() }.unwrap();
+//!     match read_packet(atom, &mut urids) {
+//!         Some(Packet::Message(message)) => {
+//!             assert_eq!("/mixer/gain", message.path);
+//!             let mut arguments = message.arguments();
+//!             assert_eq!(Some(2), arguments.read_int(&mut urids));
+//!             assert_eq!(None, arguments.read_int(&mut urids));
+//!             assert_eq!(Some(Argument::Float(0.5)), arguments.read(&mut urids));
+//!             assert_eq!(None, arguments.read(&mut urids));
+//!         }
+//!         _ => panic!("Not a message"),
+//!     }
+//!
+//! Arguments of the types `b` (blob), `N` (nil), `I` (impulse), `c` (char), `r` (RGBA color) and
+//! `m` (MIDI message) have no representation in this crate and are read as
+//! [`Argument::Unsupported`](enum.Argument.html#variant.Unsupported).
+use crate::atom::{Atom, AtomIterator};
+use crate::frame::{NestedFrame, WritingFrame, WritingFrameExt};
+use crate::object::{Object, ObjectWritingFrame, PropertyHeader};
+use crate::string::AtomString;
+use crate::tuple::{Tuple, TupleWritingFrame};
+use lv2rs_core::uris::Uri;
+use urid::{CachedMap, URID};
+
+pub const URI: Uri = Uri::new(b"http://open-music-kontrollers.ch/lv2/osc\0");
+
+pub const EVENT_URI: Uri = Uri::new(b"http://open-music-kontrollers.ch/lv2/osc#Event\0");
+pub const BUNDLE_CLASS_URI: Uri = Uri::new(b"http://open-music-kontrollers.ch/lv2/osc#Bundle\0");
+pub const MESSAGE_CLASS_URI: Uri = Uri::new(b"http://open-music-kontrollers.ch/lv2/osc#Message\0");
+pub const TIMETAG_CLASS_URI: Uri = Uri::new(b"http://open-music-kontrollers.ch/lv2/osc#Timetag\0");
+pub const BUNDLE_ITEMS_URI: Uri =
+    Uri::new(b"http://open-music-kontrollers.ch/lv2/osc#bundleItems\0");
+pub const BUNDLE_TIMETAG_URI: Uri =
+    Uri::new(b"http://open-music-kontrollers.ch/lv2/osc#bundleTimetag\0");
+pub const MESSAGE_ARGUMENTS_URI: Uri =
+    Uri::new(b"http://open-music-kontrollers.ch/lv2/osc#messageArguments\0");
+pub const MESSAGE_PATH_URI: Uri =
+    Uri::new(b"http://open-music-kontrollers.ch/lv2/osc#messagePath\0");
+pub const TIMETAG_FRACTION_URI: Uri =
+    Uri::new(b"http://open-music-kontrollers.ch/lv2/osc#timetagFraction\0");
+pub const TIMETAG_INTEGRAL_URI: Uri =
+    Uri::new(b"http://open-music-kontrollers.ch/lv2/osc#timetagIntegral\0");
+
+urid::urid_cache! {
+    /// The URIDs of the OSC vocabulary.
+    pub struct OscURIDs {
+        pub event: EVENT_URI,
+        pub bundle: BUNDLE_CLASS_URI,
+        pub message: MESSAGE_CLASS_URI,
+        pub timetag: TIMETAG_CLASS_URI,
+        pub bundle_items: BUNDLE_ITEMS_URI,
+        pub bundle_timetag: BUNDLE_TIMETAG_URI,
+        pub message_arguments: MESSAGE_ARGUMENTS_URI,
+        pub message_path: MESSAGE_PATH_URI,
+        pub timetag_fraction: TIMETAG_FRACTION_URI,
+        pub timetag_integral: TIMETAG_INTEGRAL_URI,
+    }
+}
+
+/// An NTP timestamp, which tells when a bundle should be executed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Timetag {
+    /// The seconds since the first of January 1900.
+    pub integral: u32,
+    /// The fractional part of the seconds, in units of 2^-32 seconds.
+    pub fraction: u32,
+}
+
+impl Timetag {
+    /// The special timetag that requests an immediate execution.
+    pub const IMMEDIATE: Timetag = Timetag {
+        integral: 0,
+        fraction: 1,
+    };
+}
+
+/// The argument of an OSC message.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Argument<'a> {
+    /// `i`, written as an `Int` atom.
+    Int(i32),
+    /// `f`, written as a `Float` atom.
+    Float(f32),
+    /// `s`, written as a `String` atom.
+    String(&'a str),
+    /// `h`, written as a `Long` atom.
+    Long(i64),
+    /// `d`, written as a `Double` atom.
+    Double(f64),
+    /// `S`, written as a `URID` atom.
+    Symbol(URID),
+    /// `T` or `F`, written as a `Bool` atom.
+    Bool(bool),
+    /// `t`, written as an `osc:Timetag` object.
+    Timetag(Timetag),
+    /// An atom of another type, which is identified by its type URID.
+    ///
+    /// Unsupported arguments can not be written.
+    Unsupported(URID),
+}
+
+impl<'a> From<bool> for Argument<'a> {
+    fn from(value: bool) -> Self {
+        Argument::Bool(value)
+    }
+}
+
+/// Read a timetag from an `osc:Timetag` object.
+fn read_timetag(atom: &Atom, urids: &mut CachedMap) -> Option<Timetag> {
+    let object = atom.get_body::<Object>(urids).ok()?;
+    if object.header.otype != urids.map(TIMETAG_CLASS_URI) {
+        return None;
+    }
+    let integral_urid = urids.map(TIMETAG_INTEGRAL_URI);
+    let fraction_urid = urids.map(TIMETAG_FRACTION_URI);
+    let (mut integral, mut fraction) = (None, None);
+    for (header, atom) in object.iter() {
+        let value = match atom.get_body::<i64>(urids) {
+            Ok(value) => *value as u32,
+            Err(_) => continue,
+        };
+        if header.key == integral_urid {
+            integral = Some(value);
+        } else if header.key == fraction_urid {
+            fraction = Some(value);
+        }
+    }
+    Some(Timetag {
+        integral: integral?,
+        fraction: fraction?,
+    })
+}
+
+/// Read a string atom as a `str`.
+fn read_string<'a>(atom: &'a Atom, urids: &mut CachedMap) -> Option<&'a str> {
+    let string = atom.get_body::<AtomString>(urids).ok()?;
+    string.as_cstr().ok()?.to_str().ok()
+}
+
+/// Read an atom as an argument.
+pub fn read_argument<'a>(atom: &'a Atom, urids: &mut CachedMap) -> Argument<'a> {
+    if let Ok(value) = atom.get_body::<i32>(urids) {
+        Argument::Int(*value)
+    } else if let Ok(value) = atom.get_body::<f32>(urids) {
+        Argument::Float(*value)
+    } else if let Some(value) = read_string(atom, urids) {
+        Argument::String(value)
+    } else if let Ok(value) = atom.get_body::<i64>(urids) {
+        Argument::Long(*value)
+    } else if let Ok(value) = atom.get_body::<f64>(urids) {
+        Argument::Double(*value)
+    } else if let Ok(value) = atom.get_body::<URID>(urids) {
+        Argument::Symbol(*value)
+    } else if let Ok(value) = atom.get_body::<bool>(urids) {
+        Argument::Bool(*value)
+    } else if let Some(value) = read_timetag(atom, urids) {
+        Argument::Timetag(value)
+    } else {
+        Argument::Unsupported(atom.atom_type())
+    }
+}
+
+/// Reader for the arguments of a message.
+///
+/// The arguments are read in order, either with [`read`](#method.read), which accepts every
+/// argument, or with one of the typed methods, which only advance the reader if the next
+/// argument has the requested type.
+#[derive(Clone)]
+pub struct ArgumentReader<'a> {
+    atoms: AtomIterator<'a, ()>,
+}
+
+impl<'a> ArgumentReader<'a> {
+    /// Read the next argument.
+    ///
+    /// `None` is returned if there are no arguments left.
+    pub fn read(&mut self, urids: &mut CachedMap) -> Option<Argument<'a>> {
+        let (_, atom) = self.atoms.next()?;
+        Some(read_argument(atom, urids))
+    }
+
+    /// Read the next argument if the function accepts it.
+    fn read_as<T, F>(&mut self, urids: &mut CachedMap, f: F) -> Option<T>
+    where
+        F: FnOnce(Argument<'a>) -> Option<T>,
+    {
+        let mut atoms = self.atoms.clone();
+        let (_, atom) = atoms.next()?;
+        let value = f(read_argument(atom, urids))?;
+        self.atoms = atoms;
+        Some(value)
+    }
+
+    /// Read the next argument if it's an `Int`.
+    pub fn read_int(&mut self, urids: &mut CachedMap) -> Option<i32> {
+        self.read_as(urids, |argument| match argument {
+            Argument::Int(value) => Some(value),
+            _ => None,
+        })
+    }
+
+    /// Read the next argument if it's a `Float`.
+    pub fn read_float(&mut self, urids: &mut CachedMap) -> Option<f32> {
+        self.read_as(urids, |argument| match argument {
+            Argument::Float(value) => Some(value),
+            _ => None,
+        })
+    }
+
+    /// Read the next argument if it's a `String`.
+    pub fn read_string(&mut self, urids: &mut CachedMap) -> Option<&'a str> {
+        self.read_as(urids, |argument| match argument {
+            Argument::String(value) => Some(value),
+            _ => None,
+        })
+    }
+
+    /// Read the next argument if it's a `Long`.
+    pub fn read_long(&mut self, urids: &mut CachedMap) -> Option<i64> {
+        self.read_as(urids, |argument| match argument {
+            Argument::Long(value) => Some(value),
+            _ => None,
+        })
+    }
+
+    /// Read the next argument if it's a `Double`.
+    pub fn read_double(&mut self, urids: &mut CachedMap) -> Option<f64> {
+        self.read_as(urids, |argument| match argument {
+            Argument::Double(value) => Some(value),
+            _ => None,
+        })
+    }
+
+    /// Read the next argument if it's a `Symbol`.
+    pub fn read_symbol(&mut self, urids: &mut CachedMap) -> Option<URID> {
+        self.read_as(urids, |argument| match argument {
+            Argument::Symbol(value) => Some(value),
+            _ => None,
+        })
+    }
+
+    /// Read the next argument if it's a `Bool`.
+    pub fn read_bool(&mut self, urids: &mut CachedMap) -> Option<bool> {
+        self.read_as(urids, |argument| match argument {
+            Argument::Bool(value) => Some(value),
+            _ => None,
+        })
+    }
+
+    /// Read the next argument if it's a `Timetag`.
+    pub fn read_timetag(&mut self, urids: &mut CachedMap) -> Option<Timetag> {
+        self.read_as(urids, |argument| match argument {
+            Argument::Timetag(value) => Some(value),
+            _ => None,
+        })
+    }
+}
+
+/// An OSC message, read from an `osc:Message` object.
+#[derive(Clone, Copy)]
+pub struct Message<'a> {
+    /// The address pattern of the message.
+    pub path: &'a str,
+    arguments: &'a Tuple,
+}
+
+impl<'a> Message<'a> {
+    /// Create a reader for the arguments of the message.
+    pub fn arguments(&self) -> ArgumentReader<'a> {
+        ArgumentReader {
+            atoms: AtomIterator::new(&self.arguments.data),
+        }
+    }
+}
+
+/// An OSC bundle, read from an `osc:Bundle` object.
+#[derive(Clone, Copy)]
+pub struct Bundle<'a> {
+    /// The time at which the items of the bundle should be executed.
+    pub timetag: Timetag,
+    items: &'a Tuple,
+}
+
+impl<'a> Bundle<'a> {
+    /// Create an iterator over the atoms of the items.
+    ///
+    /// Every item is a packet which can be read with [`read_packet`](fn.read_packet.html).
+    pub fn items(&self) -> impl Iterator<Item = &'a Atom> {
+        self.items.iter()
+    }
+}
+
+/// An OSC packet.
+#[derive(Clone, Copy)]
+pub enum Packet<'a> {
+    Message(Message<'a>),
+    Bundle(Bundle<'a>),
+}
+
+/// Read an OSC packet from an atom, like an event of a sequence.
+///
+/// `None` is returned if the atom is neither an `osc:Message` nor an `osc:Bundle` object or if
+/// one of the required properties is missing. Properties with unknown keys are ignored.
+pub fn read_packet<'a>(atom: &'a Atom, urids: &mut CachedMap) -> Option<Packet<'a>> {
+    let object = atom.get_body::<Object>(urids).ok()?;
+    if object.header.otype == urids.map(MESSAGE_CLASS_URI) {
+        let path_urid = urids.map(MESSAGE_PATH_URI);
+        let arguments_urid = urids.map(MESSAGE_ARGUMENTS_URI);
+        let (mut path, mut arguments) = (None, None);
+        for (header, atom) in object.iter() {
+            if header.key == path_urid {
+                path = read_string(atom, urids);
+            } else if header.key == arguments_urid {
+                arguments = atom.get_body::<Tuple>(urids).ok();
+            }
+        }
+        Some(Packet::Message(Message {
+            path: path?,
+            arguments: arguments?,
+        }))
+    } else if object.header.otype == urids.map(BUNDLE_CLASS_URI) {
+        let timetag_urid = urids.map(BUNDLE_TIMETAG_URI);
+        let items_urid = urids.map(BUNDLE_ITEMS_URI);
+        let (mut timetag, mut items) = (None, None);
+        for (header, atom) in object.iter() {
+            if header.key == timetag_urid {
+                timetag = read_timetag(atom, urids);
+            } else if header.key == items_urid {
+                items = atom.get_body::<Tuple>(urids).ok();
+            }
+        }
+        Some(Packet::Bundle(Bundle {
+            timetag: timetag?,
+            items: items?,
+        }))
+    } else {
+        None
+    }
+}
+
+/// Write the body of a string atom.
+fn write_string<'a, W>(frame: &mut W, string: &str) -> Result<(), ()>
+where
+    W: WritingFrame<'a> + WritingFrameExt<'a, AtomString>,
+{
+    if string.as_bytes().contains(&0) {
+        return Err(());
+    }
+    unsafe {
+        frame.write_raw(string.as_bytes())?;
+        frame.write_sized(&0u8)?;
+    }
+    Ok(())
+}
+
+/// Write the properties of an `osc:Timetag` object.
+fn write_timetag<'a, W>(frame: &mut W, timetag: Timetag, urids: &mut CachedMap) -> Result<(), ()>
+where
+    W: ObjectWritingFrame<'a>,
+{
+    let integral_urid = urids.map(TIMETAG_INTEGRAL_URI);
+    let fraction_urid = urids.map(TIMETAG_FRACTION_URI);
+    frame.push_property::<i64>(integral_urid, 0, &(timetag.integral as i64), urids)?;
+    frame.push_property::<i64>(fraction_urid, 0, &(timetag.fraction as i64), urids)?;
+    Ok(())
+}
+
+/// Extension for [`WritingFrame`](../frame/trait.WritingFrame.html) and
+/// [`WritingFrameExt`](../frame/trait.WritingFrameExt.html) for OSC messages and bundles.
+///
+/// The frame has to manage an empty object of the class `osc:Message` or `osc:Bundle`, for
+/// example an event of a sequence or an item of a bundle. See the
+/// [module documentation](index.html) for more information.
+pub trait OscWritingFrame<'a>: WritingFrame<'a> + WritingFrameExt<'a, Object> {
+    /// Write the address pattern of a message and return a frame for its arguments.
+    ///
+    /// An `Err` is returned if the object isn't an `osc:Message`, if the path contains a null
+    /// byte or if there isn't enough space.
+    fn write_message<'b>(
+        &'b mut self,
+        path: &str,
+        urids: &mut CachedMap,
+    ) -> Result<NestedFrame<'b, 'a, Tuple>, ()> {
+        let otype = unsafe { self.get_atom_body(urids) }
+            .map_err(|_| ())?
+            .header
+            .otype;
+        if otype != urids.map(MESSAGE_CLASS_URI) {
+            return Err(());
+        }
+
+        let header = PropertyHeader {
+            key: urids.map(MESSAGE_PATH_URI),
+            context: 0,
+        };
+        unsafe {
+            self.write_sized(&header)?;
+            let mut string = self.create_nested_frame::<AtomString>(urids)?;
+            write_string(&mut string, path)?;
+        }
+        self.push_property::<Tuple>(urids.map(MESSAGE_ARGUMENTS_URI), 0, &(), urids)
+    }
+
+    /// Write the timetag of a bundle and return a frame for its items.
+    ///
+    /// Every item is an object that is written with
+    /// [`push_atom`](../tuple/trait.TupleWritingFrame.html#method.push_atom) and filled
+    /// with [`write_message`](#method.write_message) or [`write_bundle`](#method.write_bundle).
+    /// An `Err` is returned if the object isn't an `osc:Bundle` or if there isn't enough space.
+    fn write_bundle<'b>(
+        &'b mut self,
+        timetag: Timetag,
+        urids: &mut CachedMap,
+    ) -> Result<NestedFrame<'b, 'a, Tuple>, ()> {
+        let otype = unsafe { self.get_atom_body(urids) }
+            .map_err(|_| ())?
+            .header
+            .otype;
+        if otype != urids.map(BUNDLE_CLASS_URI) {
+            return Err(());
+        }
+
+        let timetag_class = urids.map(TIMETAG_CLASS_URI);
+        {
+            let key = urids.map(BUNDLE_TIMETAG_URI);
+            let mut object = self.push_property::<Object>(key, 0, &(0, timetag_class), urids)?;
+            write_timetag(&mut object, timetag, urids)?;
+        }
+        self.push_property::<Tuple>(urids.map(BUNDLE_ITEMS_URI), 0, &(), urids)
+    }
+}
+
+impl<'a, W> OscWritingFrame<'a> for W where W: WritingFrame<'a> + WritingFrameExt<'a, Object> {}
+
+/// Extension for [`WritingFrame`](../frame/trait.WritingFrame.html) and
+/// [`WritingFrameExt`](../frame/trait.WritingFrameExt.html) for the arguments of OSC messages.
+///
+/// See the [module documentation](index.html) for more information.
+pub trait ArgumentWritingFrame<'a>: WritingFrame<'a> + WritingFrameExt<'a, Tuple> {
+    /// Append an argument to the message.
+    ///
+    /// An `Err` is returned if the argument is unsupported, if a string contains a null byte or
+    /// if there isn't enough space.
+    fn push_argument(&mut self, argument: &Argument, urids: &mut CachedMap) -> Result<(), ()> {
+        match argument {
+            Argument::Int(value) => self.push_atom::<i32>(value, urids).map(|_| ()),
+            Argument::Float(value) => self.push_atom::<f32>(value, urids).map(|_| ()),
+            Argument::String(value) => {
+                let mut string = unsafe { self.create_nested_frame::<AtomString>(urids) }?;
+                write_string(&mut string, value)
+            }
+            Argument::Long(value) => self.push_atom::<i64>(value, urids).map(|_| ()),
+            Argument::Double(value) => self.push_atom::<f64>(value, urids).map(|_| ()),
+            Argument::Symbol(value) => self.push_atom::<URID>(value, urids).map(|_| ()),
+            Argument::Bool(value) => self.push_atom::<bool>(value, urids).map(|_| ()),
+            Argument::Timetag(value) => {
+                let timetag_class = urids.map(TIMETAG_CLASS_URI);
+                let mut object = self.push_atom::<Object>(&(0, timetag_class), urids)?;
+                write_timetag(&mut object, *value, urids)
+            }
+            Argument::Unsupported(_) => Err(()),
+        }
+    }
+}
+
+impl<'a, W> ArgumentWritingFrame<'a> for W where W: WritingFrame<'a> + WritingFrameExt<'a, Tuple> {}
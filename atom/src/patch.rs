@@ -0,0 +1,74 @@
+//! URIs of the LV2 patch extension.
+//!
+//! The patch extension defines messages to get and set properties of a plugin, for example by its
+//! user interface. These messages are sent as atom objects, which use the class URIs as their
+//! types and the property URIs as their keys.
+use lv2rs_core::uris::Uri;
+
+pub const URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/patch\0");
+
+pub const ACK_CLASS_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/patch#Ack\0");
+pub const COPY_CLASS_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/patch#Copy\0");
+pub const DELETE_CLASS_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/patch#Delete\0");
+pub const ERROR_CLASS_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/patch#Error\0");
+pub const GET_CLASS_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/patch#Get\0");
+pub const INSERT_CLASS_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/patch#Insert\0");
+pub const MESSAGE_CLASS_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/patch#Message\0");
+pub const MOVE_CLASS_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/patch#Move\0");
+pub const PATCH_CLASS_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/patch#Patch\0");
+pub const POST_CLASS_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/patch#Post\0");
+pub const PUT_CLASS_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/patch#Put\0");
+pub const REQUEST_CLASS_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/patch#Request\0");
+pub const RESPONSE_CLASS_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/patch#Response\0");
+pub const SET_CLASS_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/patch#Set\0");
+pub const ACCEPT_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/patch#accept\0");
+pub const ADD_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/patch#add\0");
+pub const BODY_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/patch#body\0");
+pub const CONTEXT_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/patch#context\0");
+pub const DESTINATION_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/patch#destination\0");
+pub const PROPERTY_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/patch#property\0");
+pub const READABLE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/patch#readable\0");
+pub const REMOVE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/patch#remove\0");
+pub const REQUEST_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/patch#request\0");
+pub const SEQUENCE_NUMBER_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/patch#sequenceNumber\0");
+pub const SUBJECT_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/patch#subject\0");
+pub const VALUE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/patch#value\0");
+pub const WILDCARD_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/patch#wildcard\0");
+pub const WRITABLE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/patch#writable\0");
+
+urid::urid_cache! {
+    /// The URIDs of the patch vocabulary.
+    ///
+    /// The `request_property` field holds the URID of the `patch:request` property, in contrast
+    /// to the `request` field, which holds the one of the `patch:Request` class.
+    pub struct PatchURIDs {
+        pub ack: ACK_CLASS_URI,
+        pub copy: COPY_CLASS_URI,
+        pub delete: DELETE_CLASS_URI,
+        pub error: ERROR_CLASS_URI,
+        pub get: GET_CLASS_URI,
+        pub insert: INSERT_CLASS_URI,
+        pub message: MESSAGE_CLASS_URI,
+        pub r#move: MOVE_CLASS_URI,
+        pub patch: PATCH_CLASS_URI,
+        pub post: POST_CLASS_URI,
+        pub put: PUT_CLASS_URI,
+        pub request: REQUEST_CLASS_URI,
+        pub response: RESPONSE_CLASS_URI,
+        pub set: SET_CLASS_URI,
+        pub accept: ACCEPT_URI,
+        pub add: ADD_URI,
+        pub body: BODY_URI,
+        pub context: CONTEXT_URI,
+        pub destination: DESTINATION_URI,
+        pub property: PROPERTY_URI,
+        pub readable: READABLE_URI,
+        pub remove: REMOVE_URI,
+        pub request_property: REQUEST_URI,
+        pub sequence_number: SEQUENCE_NUMBER_URI,
+        pub subject: SUBJECT_URI,
+        pub value: VALUE_URI,
+        pub wildcard: WILDCARD_URI,
+        pub writable: WRITABLE_URI,
+    }
+}
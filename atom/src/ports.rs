@@ -1,8 +1,100 @@
 //! Wrappers for raw atom IO.
 //!
 //! The wrappers provided by this module increase the safety and usability of atom IO.
+//!
+//! The host may reuse or free the buffers of the ports after a call to `run`. Therefore, atoms
+//! should be read with [`AtomInputPort::read`](struct.AtomInputPort.html#method.read), which
+//! borrows the [`RunContext`](../../lv2rs_core/struct.RunContext.html) of the cycle. The
+//! returned references can't outlive the cycle, which means that they can't be stored in the
+//! plugin and accidentally used in the next cycle:
+//!
+//! ```compile_fail
+//! extern crate lv2rs_atom as atom;
+//! extern crate lv2rs_core as core;
+//! extern crate lv2rs_urid as urid;
+//!
+//! use atom::ports::AtomInputPort;
+//! use urid::CachedMap;
+//!
+//! struct Plugin {
+//!     input: AtomInputPort<f32>,
+//!     last_input: Option<&'static f32>,
+//!     urids: CachedMap,
+//! }
+//!
+//! impl Plugin {
+//!     fn run(&mut self, context: &mut core::RunContext) {
+//!         // The reference only lives as long as the context.
+//!         self.last_input = unsafe { self.input.read(context, &mut self.urids) }.ok();
+//!     }
+//! }
+//! ```
+//!
+//! The port is borrowed too, which means that it can't be reconnected while the atom is in use:
+//!
+//! ```compile_fail
+//! extern crate lv2rs_atom as atom;
+//! extern crate lv2rs_core as core;
+//! extern crate lv2rs_urid as urid;
+//!
+//! use atom::ports::AtomInputPort;
+//! use urid::debug::DebugMap;
+//!
+//! let mut debug_map = DebugMap::new();
+//! let mut urids = unsafe { debug_map.create_cached_map() };
+//! let context = core::RunContext::new(64, 0, 44100.0, None);
+//! let mut port: AtomInputPort<f32> = AtomInputPort::new();
+//!
+//! let value = unsafe { port.read(&context, &mut urids) }.unwrap();
+//! port.connect_port(std::ptr::null());
+//! assert_eq!(0.0, *value);
+//! ```
+//!
+//! Plugins with many atom ports of the same type, like a MIDI processor with an input for every
+//! channel, declare them as an [`AtomInputPortArray`](type.AtomInputPortArray.html) or an
+//! [`AtomOutputPortArray`](type.AtomOutputPortArray.html), which are
+//! [`PortArray`](../../lv2rs_core/ports/struct.PortArray.html)s of atom ports:
+//!
+//!     extern crate lv2rs_atom as atom;
+//!     extern crate lv2rs_core as core;
+//!     extern crate lv2rs_urid as urid;
+//!
+//!     use atom::ports::*;
+//!     use atom::prelude::*;
+//!     use core::ports::*;
+//!     use urid::debug::DebugMap;
+//!
+//!     #[derive(Ports)]
+//!     struct MidiPorts {
+//!         #[port(name = "MIDI In", buffer_type = "Sequence", supports = "midi:MidiEvent")]
+//!         midi_in: AtomInputPortArray<Sequence, 16>,
+//!         #[port(name = "MIDI Out", buffer_type = "Sequence", supports = "midi:MidiEvent")]
+//!         midi_out: AtomOutputPort<Sequence>,
+//!     }
+//!
+//!     let mut ports = MidiPorts {
+//!         midi_in: PortArray::from_fn(|_| AtomInputPort::new()),
+//!         midi_out: AtomOutputPort::new(),
+//!     };
+//!     assert_eq!(17, MidiPorts::PORT_COUNT);
+//!     assert_eq!(16, MidiPorts::MIDI_OUT);
+//!     assert_eq!("midi_in_15", MidiPorts::PORTS[15].symbol);
+//!
+//!     // Simulated `run` method, with none of the inputs connected.
+//!     let mut debug_map = DebugMap::new();
+//!     let mut urids = unsafe { debug_map.create_cached_map() };
+//!     let context = core::RunContext::new(64, 0, 44100.0, None);
+//!     for port in ports.midi_in.iter() {
+//!         if let Ok(sequence) = unsafe { port.read(&context, &mut urids) } {
+//!             for (_time, _event) in sequence.iter(&mut urids) {
+//!                 // Handle the events of the port's channel.
+//!             }
+//!         }
+//!     }
 use crate::atom::*;
 use crate::frame::RootFrame;
+use lv2rs_core::ports::PortArray;
+use lv2rs_core::RunContext;
 use std::marker::PhantomData;
 use std::ptr::{null, null_mut};
 
@@ -25,7 +117,8 @@ pub enum WriteAtomError {
 }
 
 #[derive(Debug)]
-/// Error that may occur when calling [`AtomInputPort::get_atom_body`](struct.AtomInputPort.html#method.get_atom_body).
+/// Error that may occur when calling
+/// [`AtomInputPort::read`](struct.AtomInputPort.html#method.read).
 pub enum GetAtomError {
     /// The internal pointer points to zero.
     ///
@@ -82,6 +175,11 @@ impl<A: AtomBody + ?Sized> AtomOutputPort<A> {
     }
 }
 
+/// A fixed number of atom output ports of the same type.
+///
+/// See the [module documentation](index.html) for more information.
+pub type AtomOutputPortArray<A, const N: usize> = PortArray<AtomOutputPort<A>, N>;
+
 /// Wrapper for atom reading operations.
 pub struct AtomInputPort<A: AtomBody + ?Sized> {
     atom: *const Atom,
@@ -110,9 +208,13 @@ impl<A: AtomBody + ?Sized> AtomInputPort<A> {
 
     /// Dereference the internal raw pointer to an atom body reference.
     ///
+    /// The returned reference is only bound to the port, which means that it may be kept after
+    /// `run` returns, when the host may already have reused the buffer.
+    ///
     /// This method is unsafe since it dereferences the raw, internal pointer and therefore could
     /// yield undefined behaviour. Make sure that your plugin's `connect_port` method calls this
     /// port's [`connect_port`](#method.connect_port) method correctly!
+    #[deprecated(note = "use `read`, which binds the atom to the cycle")]
     pub unsafe fn get_atom_body(&self, urids: &mut urid::CachedMap) -> Result<&A, GetAtomError> {
         let atom = match self.atom.as_ref() {
             Some(atom) => atom,
@@ -121,4 +223,45 @@ impl<A: AtomBody + ?Sized> AtomInputPort<A> {
         atom.get_body(urids)
             .map_err(|err| GetAtomError::GetBody(err))
     }
+
+    /// Read the atom body of the current cycle.
+    ///
+    /// The returned reference is bound to both the port and the context of the cycle, which means
+    /// that it can't be kept after `run` returns and that the port can't be reconnected while the
+    /// reference is in use. `GetAtomError::NullPointer` is returned if the port isn't connected
+    /// and `GetAtomError::GetBody` if the atom has another type or is malformed.
+    ///
+    /// # Safety
+    ///
+    /// This method dereferences the raw, internal pointer, which has to be null or the one the host
+    /// passed to the plugin's `connect_port` method for the current cycle.
+    pub unsafe fn read<'c>(
+        &'c self,
+        _context: &'c RunContext,
+        urids: &mut urid::CachedMap,
+    ) -> Result<&'c A, GetAtomError> {
+        let atom = self.atom.as_ref().ok_or(GetAtomError::NullPointer)?;
+        atom.get_body(urids).map_err(GetAtomError::GetBody)
+    }
+}
+
+/// A fixed number of atom input ports of the same type.
+///
+/// See the [module documentation](index.html) for more information.
+pub type AtomInputPortArray<A, const N: usize> = PortArray<AtomInputPort<A>, N>;
+
+impl<A: AtomBody + ?Sized> lv2rs_core::ports::PortHandle for AtomOutputPort<A> {
+    const KIND: lv2rs_core::ports::PortKind = lv2rs_core::ports::PortKind::AtomOutput;
+
+    fn connect_raw(&mut self, data: *mut ()) {
+        self.connect_port(data as *mut Atom);
+    }
+}
+
+impl<A: AtomBody + ?Sized> lv2rs_core::ports::PortHandle for AtomInputPort<A> {
+    const KIND: lv2rs_core::ports::PortKind = lv2rs_core::ports::PortKind::AtomInput;
+
+    fn connect_raw(&mut self, data: *mut ()) {
+        self.connect_port(data as *const Atom);
+    }
 }
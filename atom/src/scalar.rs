@@ -8,6 +8,13 @@
 //! * `bool`
 //! * `URID`
 //!
+//! Booleans are written as integers, which is their canonical representation, and integer atoms
+//! can be read as booleans too.
+//!
+//! The body of a scalar atom has to have the exact size of the scalar. However, some hosts pad
+//! the bodies of smaller scalars to eight bytes. With the `padded-scalars` feature, such bodies
+//! are accepted too, as long as the type URID of the atom matches.
+//!
 //! They all have in common that they are statically sized (which is something special among atoms)
 //! and that they can be written in one piece; Once they are initialized, they are completed and
 //! need no further amendments. Therefore, their behaviour is abstracted to another trait,
@@ -16,6 +23,7 @@
 //! and reading scalar atoms is pretty straight foreward:
 //!
 //!     extern crate lv2rs_atom as atom;
+//!     extern crate lv2rs_core as core;
 //!     extern crate lv2rs_urid as urid;
 //!
 //!     use atom::prelude::*;
@@ -31,12 +39,12 @@
 //!
 //!     impl Plugin {
 //!         /// Simulated `run` method.
-//!         fn run(&mut self) {
+//!         fn run(&mut self, context: &mut core::RunContext) {
 //!             // Writing.
 //!             unsafe { self.out_port.write_atom_body(&42.0f32, &mut self.urids) }.unwrap();
 //!
 //!             // Reading.
-//!             let float = unsafe { self.in_port.get_atom_body(&mut self.urids) }.unwrap();
+//!             let float = unsafe { self.in_port.read(context, &mut self.urids) }.unwrap();
 //!             assert_eq!(42.0, *float);
 //!         }
 //!     }
@@ -62,7 +70,7 @@
 //!     plugin.out_port.connect_port(atom);
 //!
 //!     // Calling `run`.
-//!     plugin.run();
+//!     plugin.run(&mut core::RunContext::new(1, 0, 44100.0, None));
 use crate::atom::*;
 use crate::frame::{WritingFrame, WritingFrameExt};
 use crate::uris;
@@ -71,8 +79,48 @@ use std::ffi::CStr;
 /// Abstraction over scalar (number-like) atoms.
 ///
 /// See the [module documentation](index.html) for more information.
-pub trait ScalarAtomBody {
+pub trait ScalarAtomBody: Sized {
     fn get_uri() -> &'static CStr;
+
+    /// Return `true` if atoms of the given type can be read as this type.
+    ///
+    /// By default, only atoms of this type are accepted.
+    fn accepts_type(atom_type: URID, urids: &mut urid::CachedMap) -> bool {
+        atom_type == urids.map(Self::get_uri())
+    }
+
+    /// Return the canonical representation of the value, which is written to atoms.
+    ///
+    /// By default, this is the value's memory.
+    fn as_bytes(&self) -> &[u8] {
+        unsafe {
+            std::slice::from_raw_parts(
+                self as *const Self as *const u8,
+                std::mem::size_of::<Self>(),
+            )
+        }
+    }
+
+    /// Interpret the body of an atom.
+    ///
+    /// By default, the body has to have the size of the type and is used as it is. With the
+    /// `padded-scalars` feature, longer bodies are accepted too and only their start is used.
+    fn from_body(raw_body: &[u8]) -> Result<&Self, ()> {
+        if body_fits(raw_body.len(), std::mem::size_of::<Self>()) {
+            let ptr = raw_body.as_ptr() as *const Self;
+            Ok(unsafe { ptr.as_ref() }.unwrap())
+        } else {
+            Err(())
+        }
+    }
+}
+
+/// Return `true` if a body with the given length holds a scalar of the given size.
+///
+/// Some hosts pad the bodies of scalars to eight bytes, which is allowed for the bodies of
+/// scalars with the `padded-scalars` feature.
+fn body_fits(length: usize, size: usize) -> bool {
+    length == size || (cfg!(feature = "padded-scalars") && length > size)
 }
 
 impl<T> AtomBody for T
@@ -85,6 +133,10 @@ where
         T::get_uri()
     }
 
+    fn accepts_type(atom_type: URID, urids: &mut urid::CachedMap) -> bool {
+        T::accepts_type(atom_type, urids)
+    }
+
     unsafe fn initialize_body<'a, W>(
         writer: &mut W,
         parameter: &Self,
@@ -93,41 +145,36 @@ where
     where
         W: WritingFrame<'a> + WritingFrameExt<'a, Self>,
     {
-        writer.write_sized(parameter)?;
+        writer.write_raw(parameter.as_bytes())?;
         Ok(())
     }
 
     fn create_ref<'a>(raw_body: &'a [u8]) -> Result<&'a Self, ()> {
-        if raw_body.len() == std::mem::size_of::<Self>() {
-            let ptr = raw_body.as_ptr() as *const Self;
-            Ok(unsafe { ptr.as_ref() }.unwrap())
-        } else {
-            Err(())
-        }
+        T::from_body(raw_body)
     }
 }
 
 impl ScalarAtomBody for i32 {
     fn get_uri() -> &'static CStr {
-        unsafe { CStr::from_bytes_with_nul_unchecked(uris::INT_TYPE_URI) }
+        uris::INT_TYPE_URI.as_cstr()
     }
 }
 
 impl ScalarAtomBody for i64 {
     fn get_uri() -> &'static CStr {
-        unsafe { CStr::from_bytes_with_nul_unchecked(uris::LONG_TYPE_URI) }
+        uris::LONG_TYPE_URI.as_cstr()
     }
 }
 
 impl ScalarAtomBody for f32 {
     fn get_uri() -> &'static CStr {
-        unsafe { CStr::from_bytes_with_nul_unchecked(uris::FLOAT_TYPE_URI) }
+        uris::FLOAT_TYPE_URI.as_cstr()
     }
 }
 
 impl ScalarAtomBody for f64 {
     fn get_uri() -> &'static CStr {
-        unsafe { CStr::from_bytes_with_nul_unchecked(uris::DOUBLE_TYPE_URI) }
+        uris::DOUBLE_TYPE_URI.as_cstr()
     }
 }
 
@@ -135,12 +182,44 @@ pub use urid::URID;
 
 impl ScalarAtomBody for URID {
     fn get_uri() -> &'static CStr {
-        unsafe { CStr::from_bytes_with_nul_unchecked(uris::URID_TYPE_URI) }
+        uris::URID_TYPE_URI.as_cstr()
     }
 }
 
+/// Booleans are written as `Int`s where `0` is false and `1` is true, just like the
+/// `LV2_Atom_Bool` of the C API.
+///
+/// Since the specification defines a boolean as an integer where every value but zero is true,
+/// `Int` atoms are read as booleans too, as are the single-byte bodies that older versions of this
+/// crate wrote.
 impl ScalarAtomBody for bool {
     fn get_uri() -> &'static CStr {
-        unsafe { CStr::from_bytes_with_nul_unchecked(uris::BOOL_TYPE_URI) }
+        uris::BOOL_TYPE_URI.as_cstr()
+    }
+
+    fn accepts_type(atom_type: URID, urids: &mut urid::CachedMap) -> bool {
+        atom_type == urids.map(<bool as ScalarAtomBody>::get_uri())
+            || atom_type == urids.map(<i32 as ScalarAtomBody>::get_uri())
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        const FALSE: [u8; 4] = 0i32.to_ne_bytes();
+        const TRUE: [u8; 4] = 1i32.to_ne_bytes();
+        if *self {
+            &TRUE
+        } else {
+            &FALSE
+        }
+    }
+
+    fn from_body(raw_body: &[u8]) -> Result<&Self, ()> {
+        let value = match raw_body {
+            [byte] => *byte != 0,
+            [a, b, c, d, ..] if body_fits(raw_body.len(), 4) => {
+                i32::from_ne_bytes([*a, *b, *c, *d]) != 0
+            }
+            _ => return Err(()),
+        };
+        Ok(if value { &true } else { &false })
     }
 }
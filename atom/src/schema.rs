@@ -0,0 +1,302 @@
+//! Schemas that objects are validated against.
+//!
+//! Objects are read by iterating over their properties, which means that a plugin silently
+//! ignores properties with the wrong type or misses required ones unless it checks every single
+//! property. An [`ObjectSchema`](struct.ObjectSchema.html) declares the class of an object, its
+//! required and optional properties and their atom types once. Incoming objects are then
+//! validated against it and the first violation is reported as a
+//! [`SchemaError`](enum.SchemaError.html), which contains the URIDs of the offending key and
+//! types:
+//!
+//!     extern crate lv2rs_atom as atom;
+//!     extern crate lv2rs_urid as urid;
+//!
+//!     use atom::ports::*;
+//!     use atom::prelude::*;
+//!     use atom::schema::*;
+//!     use std::ffi::CStr;
+//!     use urid::debug::DebugMap;
+//!
+//!     let mut debug_map = DebugMap::new();
+//!     let mut urids = unsafe { debug_map.create_cached_map() };
+//!     let note = urids.map(CStr::from_bytes_with_nul(b"http://example.org/Note\0").unwrap());
+//!     let pitch = urids.map(CStr::from_bytes_with_nul(b"http://example.org/pitch\0").unwrap());
+//!     let velocity =
+//!         urids.map(CStr::from_bytes_with_nul(b"http://example.org/velocity\0").unwrap());
+//!
+//!     let schema = ObjectSchema::new(Some(note))
+//!         .with_required::<i32>(pitch, &mut urids)
+//!         .with_optional::<f32>(velocity, &mut urids);
+//!
+//!     // Creating the atom space and the ports.
+//!     let mut atom_space = vec![0u64; 32];
+//!     let atom = unsafe { (atom_space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+//!     *(atom.mut_size()) = 256;
+//!     let mut out_port: AtomOutputPort<Object> = AtomOutputPort::new();
+//!     out_port.connect_port(atom);
+//!
+//!     // Writing a note with an integer velocity.
+//!     {
+//!         let mut object = unsafe { out_port.write_atom_body(&(0, note), &mut urids) }.unwrap();
+//!         object.push_property::<i32>(pitch, 0, &60, &mut urids).unwrap();
+//!         object.push_property::<i32>(velocity, 0, &100, &mut urids).unwrap();
+//!     }
+//!
+//!     // Validating the note.
+//!     let atom = unsafe { (atom_space.as_ptr() as *const Atom).as_ref() }.unwrap();
+//!     let int = urids.map(<i32 as AtomBody>::get_uri());
+//!     let float = urids.map(<f32 as AtomBody>::get_uri());
+//!     assert_eq!(
+//!         Err(SchemaError::WrongType {
+//!             key: velocity,
+//!             expected: float,
+//!             found: int,
+//!         }),
+//!         schema.validate_atom(atom, &mut urids).map(|_| ())
+//!     );
+//!
+//! Validation doesn't allocate, which means that it can be done in the audio thread.
+use crate::atom::{Atom, AtomBody};
+use crate::object::Object;
+use std::fmt;
+use urid::{CachedMap, URID};
+
+/// A violation of an [`ObjectSchema`](struct.ObjectSchema.html).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SchemaError {
+    /// The atom isn't an object, but has the given type.
+    NotAnObject { atom_type: URID },
+    /// The object belongs to another class than the schema.
+    WrongClass { expected: URID, found: URID },
+    /// A required property is missing.
+    MissingProperty { key: URID },
+    /// A property has an atom type that the declared type doesn't accept.
+    WrongType {
+        key: URID,
+        expected: URID,
+        found: URID,
+    },
+    /// A property has the right atom type, but its body is malformed.
+    MalformedProperty { key: URID },
+    /// A property appears more than once.
+    DuplicateProperty { key: URID },
+    /// A strict schema doesn't declare the property.
+    UnknownProperty { key: URID },
+}
+
+impl SchemaError {
+    /// Return the key of the property that violates the schema, if the error concerns a property.
+    pub fn key(&self) -> Option<URID> {
+        match self {
+            SchemaError::NotAnObject { .. } | SchemaError::WrongClass { .. } => None,
+            SchemaError::MissingProperty { key }
+            | SchemaError::WrongType { key, .. }
+            | SchemaError::MalformedProperty { key }
+            | SchemaError::DuplicateProperty { key }
+            | SchemaError::UnknownProperty { key } => Some(*key),
+        }
+    }
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SchemaError::NotAnObject { atom_type } => {
+                write!(f, "atom of type {} is not an object", atom_type)
+            }
+            SchemaError::WrongClass { expected, found } => {
+                write!(f, "object has class {} instead of {}", found, expected)
+            }
+            SchemaError::MissingProperty { key } => write!(f, "property {} is missing", key),
+            SchemaError::WrongType {
+                key,
+                expected,
+                found,
+            } => write!(
+                f,
+                "property {} has type {} instead of {}",
+                key, found, expected
+            ),
+            SchemaError::MalformedProperty { key } => write!(f, "property {} is malformed", key),
+            SchemaError::DuplicateProperty { key } => {
+                write!(f, "property {} appears more than once", key)
+            }
+            SchemaError::UnknownProperty { key } => write!(f, "property {} is unknown", key),
+        }
+    }
+}
+
+/// The declaration of a property.
+struct PropertySchema {
+    key: URID,
+    atom_type: URID,
+    required: bool,
+    accepts_type: fn(URID, &mut CachedMap) -> bool,
+    check_body: fn(&Atom, &mut CachedMap) -> bool,
+}
+
+fn check_body<A: AtomBody + ?Sized>(atom: &Atom, urids: &mut CachedMap) -> bool {
+    atom.get_body::<A>(urids).is_ok()
+}
+
+/// The expected class and properties of an object.
+///
+/// A schema is built once, usually when the plugin is instantiated, by declaring the properties
+/// with [`with_required`](#method.with_required) and [`with_optional`](#method.with_optional).
+/// Properties that aren't declared are allowed, unless the schema is [`strict`](#method.strict).
+pub struct ObjectSchema {
+    otype: Option<URID>,
+    properties: Vec<PropertySchema>,
+    strict: bool,
+}
+
+impl ObjectSchema {
+    /// Create a schema without properties.
+    ///
+    /// If a class is given, objects of other classes are rejected.
+    pub fn new(otype: Option<URID>) -> Self {
+        Self {
+            otype,
+            properties: Vec::new(),
+            strict: false,
+        }
+    }
+
+    fn with_property<A: AtomBody + ?Sized>(
+        mut self,
+        key: URID,
+        required: bool,
+        urids: &mut CachedMap,
+    ) -> Self {
+        let property = PropertySchema {
+            key,
+            atom_type: urids.map(A::get_uri()),
+            required,
+            accepts_type: A::accepts_type,
+            check_body: check_body::<A>,
+        };
+        self.properties.retain(|property| property.key != key);
+        self.properties.push(property);
+        self
+    }
+
+    /// Declare a property that every object has to contain, with an atom of type `A`.
+    ///
+    /// A previous declaration of the same key is replaced.
+    pub fn with_required<A: AtomBody + ?Sized>(self, key: URID, urids: &mut CachedMap) -> Self {
+        self.with_property::<A>(key, true, urids)
+    }
+
+    /// Declare a property that objects may contain, with an atom of type `A`.
+    ///
+    /// A previous declaration of the same key is replaced.
+    pub fn with_optional<A: AtomBody + ?Sized>(self, key: URID, urids: &mut CachedMap) -> Self {
+        self.with_property::<A>(key, false, urids)
+    }
+
+    /// Reject objects with properties the schema doesn't declare.
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    /// Return the class of the schema, if objects have to belong to one.
+    pub fn otype(&self) -> Option<URID> {
+        self.otype
+    }
+
+    /// Return `true` if the schema declares a property with the given key.
+    pub fn declares(&self, key: URID) -> bool {
+        self.properties.iter().any(|property| property.key == key)
+    }
+
+    /// Validate an atom and return it as an object.
+    pub fn validate_atom<'a>(
+        &self,
+        atom: &'a Atom,
+        urids: &mut CachedMap,
+    ) -> Result<&'a Object, SchemaError> {
+        let object = atom
+            .get_body::<Object>(urids)
+            .map_err(|_| SchemaError::NotAnObject {
+                atom_type: atom.atom_type(),
+            })?;
+        self.validate(object, urids)?;
+        Ok(object)
+    }
+
+    /// Validate an object and return the first violation of the schema.
+    ///
+    /// The class is checked first, then the properties in the order of the object, and missing
+    /// properties last.
+    pub fn validate(&self, object: &Object, urids: &mut CachedMap) -> Result<(), SchemaError> {
+        let mut result = Ok(());
+        self.visit_errors(object, urids, |error| {
+            result = Err(error);
+            false
+        });
+        result
+    }
+
+    /// Validate an object and return all violations of the schema, in the order of
+    /// [`validate`](#method.validate).
+    pub fn errors(&self, object: &Object, urids: &mut CachedMap) -> Vec<SchemaError> {
+        let mut errors = Vec::new();
+        self.visit_errors(object, urids, |error| {
+            errors.push(error);
+            true
+        });
+        errors
+    }
+
+    /// Call a function with every violation until it returns `false`.
+    fn visit_errors<F>(&self, object: &Object, urids: &mut CachedMap, mut visit: F)
+    where
+        F: FnMut(SchemaError) -> bool,
+    {
+        if let Some(expected) = self.otype {
+            if object.header.otype != expected {
+                let found = object.header.otype;
+                if !visit(SchemaError::WrongClass { expected, found }) {
+                    return;
+                }
+            }
+        }
+
+        for (index, (header, atom)) in object.iter().enumerate() {
+            let key = header.key;
+            let error = if object.iter().take(index).any(|(other, _)| other.key == key) {
+                Some(SchemaError::DuplicateProperty { key })
+            } else {
+                match self.properties.iter().find(|property| property.key == key) {
+                    Some(property) if !(property.accepts_type)(atom.atom_type(), urids) => {
+                        Some(SchemaError::WrongType {
+                            key,
+                            expected: property.atom_type,
+                            found: atom.atom_type(),
+                        })
+                    }
+                    Some(property) if !(property.check_body)(atom, urids) => {
+                        Some(SchemaError::MalformedProperty { key })
+                    }
+                    Some(_) => None,
+                    None if self.strict => Some(SchemaError::UnknownProperty { key }),
+                    None => None,
+                }
+            };
+            if let Some(error) = error {
+                if !visit(error) {
+                    return;
+                }
+            }
+        }
+
+        for property in self.properties.iter().filter(|property| property.required) {
+            if !object.iter().any(|(header, _)| header.key == property.key)
+                && !visit(SchemaError::MissingProperty { key: property.key })
+            {
+                return;
+            }
+        }
+    }
+}
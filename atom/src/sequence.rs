@@ -15,10 +15,14 @@
 //!
 //! Reading atoms is done by iterating through all atoms one by one. Iterators are produced by the
 //! [`iter`](type.Tuple.html#method.iter) method.
+//! If several parts of a plugin consume the events of the same sequence one after another, a
+//! [`SequenceCursor`](struct.SequenceCursor.html) keeps track of the events that were already
+//! consumed.
 //!
 //! An example:
 //!
 //!     extern crate lv2rs_atom as atom;
+//!     extern crate lv2rs_core as core;
 //!     extern crate lv2rs_urid as urid;
 //!
 //!     use atom::prelude::*;
@@ -34,7 +38,7 @@
 //!
 //!     impl Plugin {
 //!         /// Simulated `run` method.
-//!         fn run(&mut self) {
+//!         fn run(&mut self, context: &mut core::RunContext) {
 //!             // Writing
 //!             {
 //!                 let mut frame =
@@ -47,7 +51,7 @@
 //!             let f32_urid = self.urids.map(<f32 as AtomBody>::get_uri());
 //!
 //!             // Reading.
-//!             let tuple = unsafe { self.in_port.get_atom_body(&mut self.urids) }.unwrap();
+//!             let tuple = unsafe { self.in_port.read(context, &mut self.urids) }.unwrap();
 //!             for sub_atom in tuple.iter() {
 //!                 match unsafe { sub_atom.get_body::<i32>(&mut self.urids) } {
 //!                     Ok(integer) => {
@@ -89,11 +93,12 @@
 //!     plugin.out_port.connect_port(atom);
 //!
 //!     // Calling `run`.
-//!     plugin.run();
+//!     plugin.run(&mut core::RunContext::new(1, 0, 44100.0, None));
 use crate::atom::{array::*, *};
 use crate::frame::{NestedFrame, WritingFrame, WritingFrameExt};
 use crate::uris;
 use std::ffi::CStr;
+use std::fmt;
 use urid::URID;
 
 /// Nice handle for the time unit.
@@ -115,7 +120,7 @@ impl TimeUnit {
     /// [`uris::BEAT_TIME_URI`](../uris/constant.BEAT_TIME_URI.html), this method will return
     /// `TimeUnit::Beats`. Otherwise, it will return `Time::Frames`.
     pub fn from_urid(urid: URID, urids: &mut urid::CachedMap) -> TimeUnit {
-        if urid == urids.map(unsafe { CStr::from_bytes_with_nul_unchecked(uris::BEAT_TIME_URI) }) {
+        if urid == urids.map(uris::BEAT_TIME_URI) {
             TimeUnit::Beats
         } else {
             TimeUnit::Frames
@@ -125,12 +130,8 @@ impl TimeUnit {
     /// Return the corresponding URID of the time unit.
     pub fn into_urid(&self, urids: &mut urid::CachedMap) -> URID {
         match self {
-            TimeUnit::Frames => {
-                urids.map(unsafe { CStr::from_bytes_with_nul_unchecked(uris::FRAME_TIME_URI) })
-            }
-            TimeUnit::Beats => {
-                urids.map(unsafe { CStr::from_bytes_with_nul_unchecked(uris::BEAT_TIME_URI) })
-            }
+            TimeUnit::Frames => urids.map(uris::FRAME_TIME_URI),
+            TimeUnit::Beats => urids.map(uris::BEAT_TIME_URI),
         }
     }
 }
@@ -169,6 +170,16 @@ union RawTimeStamp {
     beats: f64,
 }
 
+impl RawTimeStamp {
+    /// Interpret the raw time stamp in the given unit.
+    fn to_stamp(self, unit: &TimeUnit) -> TimeStamp {
+        match unit {
+            TimeUnit::Frames => TimeStamp::Frames(unsafe { self.frames }),
+            TimeUnit::Beats => TimeStamp::Beats(unsafe { self.beats }),
+        }
+    }
+}
+
 impl From<TimeStamp> for RawTimeStamp {
     fn from(other: TimeStamp) -> RawTimeStamp {
         match other {
@@ -222,7 +233,7 @@ impl AtomBody for Sequence {
     type InitializationParameter = TimeUnit;
 
     fn get_uri() -> &'static CStr {
-        unsafe { CStr::from_bytes_with_nul_unchecked(uris::SEQUENCE_TYPE_URI) }
+        uris::SEQUENCE_TYPE_URI.as_cstr()
     }
 
     unsafe fn initialize_body<'a, W>(
@@ -249,18 +260,128 @@ impl Sequence {
     pub fn iter<'a>(
         &'a self,
         urids: &mut urid::CachedMap,
-    ) -> impl Iterator<Item = (TimeStamp, &'a Atom)> {
+    ) -> impl Iterator<Item = (TimeStamp, &'a Atom)> + Clone {
         let time_unit = TimeUnit::from_urid(self.header.unit, urids);
         AtomIterator::new(&self.data).map(
             move |(raw_stamp, chunk): (&'a RawTimeStamp, &'a Atom)| -> (TimeStamp, &'a Atom) {
-                let stamp = match time_unit {
-                    TimeUnit::Frames => TimeStamp::Frames(unsafe { raw_stamp.frames }),
-                    TimeUnit::Beats => TimeStamp::Beats(unsafe { raw_stamp.beats }),
-                };
-                (stamp, chunk)
+                (raw_stamp.to_stamp(&time_unit), chunk)
             },
         )
     }
+
+    /// Create a cursor that starts at the first event.
+    ///
+    /// See [`SequenceCursor`](struct.SequenceCursor.html) for more information.
+    pub fn cursor<'a>(&'a self, urids: &mut urid::CachedMap) -> SequenceCursor<'a> {
+        SequenceCursor {
+            data: &self.data,
+            events: AtomIterator::new(&self.data),
+            unit: self.time_unit(urids),
+            position: 0,
+        }
+    }
+}
+
+/// A reading position in a sequence that is kept between calls.
+///
+/// The iterator of a sequence always starts at the first event. A cursor, which is created with
+/// [`Sequence::cursor`](type.Sequence.html#method.cursor), remembers how many events were
+/// consumed instead. It can therefore be handed to several parts of a plugin, for example a MIDI
+/// handler and a patch handler, which each consume the events they are responsible for and leave
+/// the remaining ones to the next part, without collecting the events first.
+///
+/// A cursor is an iterator over the remaining events. Events are only consumed conditionally
+/// with [`next_if`](#method.next_if) and [`until`](#method.until). Cloning a cursor is cheap and
+/// creates an independent cursor at the same position.
+#[derive(Clone)]
+pub struct SequenceCursor<'a> {
+    data: &'a [u8],
+    events: AtomIterator<'a, RawTimeStamp>,
+    unit: TimeUnit,
+    position: usize,
+}
+
+impl<'a> SequenceCursor<'a> {
+    /// Return the time unit of the sequence.
+    pub fn time_unit(&self) -> TimeUnit {
+        self.unit.clone()
+    }
+
+    /// Return the number of events that were consumed.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Return `true` if all events were consumed.
+    pub fn is_finished(&self) -> bool {
+        self.peek().is_none()
+    }
+
+    /// Return the next event without consuming it.
+    pub fn peek(&self) -> Option<(TimeStamp, &'a Atom)> {
+        self.clone().next()
+    }
+
+    /// Consume the next event if the predicate accepts it.
+    pub fn next_if<F>(&mut self, predicate: F) -> Option<(TimeStamp, &'a Atom)>
+    where
+        F: FnOnce(&TimeStamp, &'a Atom) -> bool,
+    {
+        let mut events = self.events.clone();
+        let (raw_stamp, atom) = events.next()?;
+        let stamp = raw_stamp.to_stamp(&self.unit);
+        if predicate(&stamp, atom) {
+            self.events = events;
+            self.position += 1;
+            Some((stamp, atom))
+        } else {
+            None
+        }
+    }
+
+    /// Consume the events before the given time stamp.
+    ///
+    /// The returned iterator stops at the first event that isn't before the end, which remains
+    /// the next event of the cursor. If the end has another unit than the sequence, no events
+    /// are consumed.
+    pub fn until<'b>(&'b mut self, end: TimeStamp) -> EventsUntil<'b, 'a> {
+        EventsUntil { cursor: self, end }
+    }
+
+    /// Go back to the first event.
+    pub fn rewind(&mut self) {
+        self.events = AtomIterator::new(self.data);
+        self.position = 0;
+    }
+}
+
+impl<'a> Iterator for SequenceCursor<'a> {
+    type Item = (TimeStamp, &'a Atom);
+
+    fn next(&mut self) -> Option<(TimeStamp, &'a Atom)> {
+        self.next_if(|_, _| true)
+    }
+}
+
+/// Iterator over the events of a cursor before a time stamp.
+///
+/// It is created by [`SequenceCursor::until`](struct.SequenceCursor.html#method.until).
+pub struct EventsUntil<'b, 'a> {
+    cursor: &'b mut SequenceCursor<'a>,
+    end: TimeStamp,
+}
+
+impl<'b, 'a> Iterator for EventsUntil<'b, 'a> {
+    type Item = (TimeStamp, &'a Atom);
+
+    fn next(&mut self) -> Option<(TimeStamp, &'a Atom)> {
+        let end = &self.end;
+        self.cursor.next_if(|stamp, _| match (stamp, end) {
+            (TimeStamp::Frames(stamp), TimeStamp::Frames(end)) => stamp < end,
+            (TimeStamp::Beats(stamp), TimeStamp::Beats(end)) => stamp < end,
+            _ => false,
+        })
+    }
 }
 
 /// Extension for [`WritingFrame`](../frame/trait.WritingFrame.html) and
@@ -291,6 +412,76 @@ pub trait SequenceWritingFrame<'a>: WritingFrame<'a> + WritingFrameExt<'a, Seque
             Ok(frame)
         }
     }
+
+    /// Copy an atom into the sequence as a new event, for example to pass an event from an
+    /// input sequence through.
+    ///
+    /// The atom is copied together with its header, which means that its type doesn't have to be
+    /// known. Like [`push_event`](#method.push_event), this method fails if the time stamp doesn't
+    /// have the time unit of the sequence. If the sequence is full, nothing is written.
+    fn push_event_copy(
+        &mut self,
+        time: TimeStamp,
+        atom: &Atom,
+        urids: &mut urid::CachedMap,
+    ) -> Result<(), PushEventError> {
+        check_time_unit(self, &time, urids)?;
+
+        let stamp_size = std::mem::size_of::<RawTimeStamp>();
+        let header_size = std::mem::size_of::<Atom>();
+        let body = atom.get_raw_body();
+        let padding = (8 - body.len() % 8) % 8;
+        let data = unsafe { self.write_zeroed(stamp_size + header_size + body.len() + padding) }
+            .map_err(|_| PushEventError::InsufficientSpace)?;
+
+        let stamp = RawTimeStamp::from(time);
+        let stamp = unsafe {
+            std::slice::from_raw_parts(&stamp as *const RawTimeStamp as *const u8, stamp_size)
+        };
+        let header =
+            unsafe { std::slice::from_raw_parts(atom as *const Atom as *const u8, header_size) };
+        data[..stamp_size].copy_from_slice(stamp);
+        data[stamp_size..stamp_size + header_size].copy_from_slice(header);
+        data[stamp_size + header_size..stamp_size + header_size + body.len()].copy_from_slice(body);
+        Ok(())
+    }
 }
 
 impl<'a, W> SequenceWritingFrame<'a> for W where W: WritingFrame<'a> + WritingFrameExt<'a, Sequence> {}
+
+/// Check that a time stamp has the time unit of the sequence that is written by the frame.
+pub(crate) fn check_time_unit<'a, W: SequenceWritingFrame<'a>>(
+    frame: &W,
+    time: &TimeStamp,
+    urids: &mut urid::CachedMap,
+) -> Result<(), PushEventError> {
+    let header_unit: TimeUnit = {
+        let atom_body = unsafe { frame.get_atom_body(urids) }.unwrap();
+        TimeUnit::from_urid(atom_body.header.unit, urids)
+    };
+    if header_unit == time.get_unit() {
+        Ok(())
+    } else {
+        Err(PushEventError::WrongTimeUnit)
+    }
+}
+
+/// Errors that may occur when an event is copied into a sequence.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PushEventError {
+    /// The time stamp has another time unit than the sequence.
+    WrongTimeUnit,
+    /// The sequence doesn't have enough space left for the event.
+    InsufficientSpace,
+}
+
+impl fmt::Display for PushEventError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PushEventError::WrongTimeUnit => {
+                write!(f, "time stamp has another unit than the sequence")
+            }
+            PushEventError::InsufficientSpace => write!(f, "sequence is full"),
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! Sample-accurate splitting of a run at sequence events.
+//!
+//! Events in a [`Sequence`](../sequence/type.Sequence.html) have time stamps that refer to frames
+//! in the current audio block. In order to handle them sample-accurately, a plugin has to process
+//! the audio up to the time stamp of an event, handle the event and then continue with the rest of
+//! the block. The [`split_run`](fn.split_run.html) function does this bookkeeping: It splits the
+//! block at the time stamp of every event and calls a closure with the range of frames of every
+//! segment and the events that are due at its start.
+//!
+//! An example:
+//!
+//!     extern crate lv2rs_atom as atom;
+//!     extern crate lv2rs_core as core;
+//!     extern crate lv2rs_urid as urid;
+//!
+//!     use atom::prelude::*;
+//!     use atom::ports::*;
+//!     use atom::sequence::{TimeStamp, TimeUnit};
+//!     use atom::split::split_run;
+//!     use urid::debug::DebugMap;
+//!
+//!     let mut debug_map = DebugMap::new();
+//!     let mut urids = unsafe { debug_map.create_cached_map() };
+//!
+//!     // Creating the atom space and the ports.
+//!     let mut atom_space = vec![0u8; 256];
+//!     let atom = unsafe { (atom_space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+//!     *(atom.mut_size()) = 256 - 8;
+//!     let mut out_port: AtomOutputPort<Sequence> = AtomOutputPort::new();
+//!     out_port.connect_port(atom);
+//!     let mut in_port: AtomInputPort<Sequence> = AtomInputPort::new();
+//!     in_port.connect_port(atom as &Atom);
+//!
+//!     // Writing two gain changes, like a host would do.
+//!     {
+//!         let mut frame =
+//!             unsafe { out_port.write_atom_body(&TimeUnit::Frames, &mut urids) }.unwrap();
+//!         frame.push_event::<f32>(TimeStamp::Frames(2), &0.5, &mut urids).unwrap();
+//!         frame.push_event::<f32>(TimeStamp::Frames(5), &0.0, &mut urids).unwrap();
+//!     }
+//!
+//!     // Simulated `run` method.
+//!     let input = [1.0f32; 8];
+//!     let mut output = [0.0f32; 8];
+//!     let mut gain = 1.0;
+//!     let context = core::RunContext::new(8, 0, 44100.0, None);
+//!
+//!     let sequence = unsafe { in_port.read(&context, &mut urids) }.unwrap();
+//!     let events = sequence.iter(&mut urids);
+//!     split_run(8, events, |range, due_events| {
+//!         for (_, event) in due_events {
+//!             if let Ok(new_gain) = unsafe { event.get_body::<f32>(&mut urids) } {
+//!                 gain = *new_gain;
+//!             }
+//!         }
+//!         for (o, i) in output[range.clone()].iter_mut().zip(input[range].iter()) {
+//!             *o = *i * gain;
+//!         }
+//!     });
+//!
+//!     assert_eq!([1.0, 1.0, 0.5, 0.5, 0.5, 0.0, 0.0, 0.0], output);
+use crate::atom::Atom;
+use crate::sequence::TimeStamp;
+use std::ops::Range;
+
+/// Iterator over the events that are due at the start of a segment.
+///
+/// It is created by [`split_run`](fn.split_run.html) and yields the time stamps and atoms of the
+/// events, just like the iterator of the sequence.
+pub struct DueEvents<I> {
+    events: I,
+    remaining: usize,
+}
+
+impl<'a, I> Iterator for DueEvents<I>
+where
+    I: Iterator<Item = (TimeStamp, &'a Atom)>,
+{
+    type Item = (TimeStamp, &'a Atom);
+
+    fn next(&mut self) -> Option<(TimeStamp, &'a Atom)> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        self.events.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining))
+    }
+}
+
+/// Split a run at the time stamps of the events.
+///
+/// The block of `n_samples` frames is split into segments that start at the time stamp of an
+/// event and end at the time stamp of the next one. For every segment, `process` is called with
+/// the range of frames of the segment and the events that are due at its start. The segments are
+/// processed in order and cover the whole block, which means that `process` is called with the
+/// range `0..n` with no events if there aren't any.
+///
+/// The events are expected to be sorted by their time stamps, as they are in a sequence. Time
+/// stamps after the block are treated as if they were at the last frame of the block and time
+/// stamps before the current segment as if they were at its start. Since beats can not be
+/// converted to frames without knowing the tempo, events with beat time stamps are always due at
+/// the start of the current segment.
+pub fn split_run<'a, I, F>(n_samples: u32, events: I, mut process: F)
+where
+    I: Iterator<Item = (TimeStamp, &'a Atom)> + Clone,
+    F: FnMut(Range<usize>, DueEvents<I>),
+{
+    let n_samples = n_samples as usize;
+    let last_frame = n_samples.saturating_sub(1);
+    let mut events = events;
+    let mut start: usize = 0;
+
+    loop {
+        // Look ahead to count the events due at the start and to find the end of the segment.
+        let mut due: usize = 0;
+        let mut end = n_samples;
+        for (stamp, _) in events.clone() {
+            let frame = match stamp {
+                TimeStamp::Frames(frame) if frame > 0 => (frame as usize).min(last_frame),
+                TimeStamp::Frames(_) => 0,
+                TimeStamp::Beats(_) => start,
+            };
+            if frame <= start {
+                due += 1;
+            } else {
+                end = frame;
+                break;
+            }
+        }
+
+        process(
+            start..end,
+            DueEvents {
+                events: events.clone(),
+                remaining: due,
+            },
+        );
+
+        if end >= n_samples {
+            break;
+        }
+        for _ in 0..due {
+            events.next();
+        }
+        start = end;
+    }
+}
@@ -9,6 +9,7 @@
 //! An example:
 //!
 //!     extern crate lv2rs_atom as atom;
+//!     extern crate lv2rs_core as core;
 //!     extern crate lv2rs_urid as urid;
 //!
 //!     use atom::prelude::*;
@@ -24,7 +25,7 @@
 //!
 //!     impl Plugin {
 //!         /// Simulated `run` method.
-//!         fn run(&mut self) {
+//!         fn run(&mut self, context: &mut core::RunContext) {
 //!             let message: &str = "Hello World!\0";
 //!             let c_message = CStr::from_bytes_with_nul(message.as_bytes()).unwrap();
 //!
@@ -32,7 +33,7 @@
 //!             unsafe { self.out_port.write_atom_body(c_message, &mut self.urids).unwrap() };
 //!
 //!             // Reading.
-//!             let string = unsafe { self.in_port.get_atom_body(&mut self.urids) }.unwrap();
+//!             let string = unsafe { self.in_port.read(context, &mut self.urids) }.unwrap();
 //!             let str = string.as_cstr().unwrap().to_str().unwrap();
 //!             assert_eq!("Hello World!", str);
 //!         }
@@ -59,7 +60,7 @@
 //!     plugin.out_port.connect_port(atom);
 //!
 //!     // Calling `run`.
-//!     plugin.run();
+//!     plugin.run(&mut core::RunContext::new(1, 0, 44100.0, None));
 use crate::atom::{array::*, *};
 use crate::frame::{WritingFrame, WritingFrameExt};
 use crate::uris;
@@ -74,7 +75,7 @@ impl AtomBody for AtomString {
     type InitializationParameter = CStr;
 
     fn get_uri() -> &'static CStr {
-        unsafe { CStr::from_bytes_with_nul_unchecked(uris::STRING_TYPE_URI) }
+        uris::STRING_TYPE_URI.as_cstr()
     }
 
     unsafe fn initialize_body<'a, W>(
@@ -0,0 +1,152 @@
+//! Transport information of the LV2 time extension.
+//!
+//! Hosts inform plugins about the current transport state, like the tempo or the position in the
+//! song, by sending `time:Position` objects. The [`Position`](struct.Position.html) struct of
+//! `lv2rs-core` can be read from such an object with [`read_position`](fn.read_position.html)
+//! and contains all properties the host has sent.
+//!
+//! Hosts usually only send a position if something has changed and they don't have to send all
+//! properties every time. Therefore, a plugin should pass every new position to the
+//! `update_position` method of its `RunContext`, which keeps the last position around and
+//! advances it after every cycle.
+//!
+//! An example:
+//!
+//!     extern crate lv2rs_atom as atom;
+//!     extern crate lv2rs_core as core;
+//!     extern crate lv2rs_urid as urid;
+//!
+//!     use atom::prelude::*;
+//!     use atom::ports::*;
+//!     use atom::time;
+//!     use urid::debug::DebugMap;
+//!     use std::ffi::CStr;
+//!
+//!     let mut debug_map = DebugMap::new();
+//!     let mut urids = unsafe { debug_map.create_cached_map() };
+//!
+//!     // Creating the atom space and the ports.
+//!     let mut atom_space = vec![0u8; 256];
+//!     let atom = unsafe { (atom_space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+//!     *(atom.mut_size()) = 256 - 8;
+//!     let mut out_port: AtomOutputPort<Object> = AtomOutputPort::new();
+//!     out_port.connect_port(atom);
+//!     let mut in_port: AtomInputPort<Object> = AtomInputPort::new();
+//!     in_port.connect_port(atom as &Atom);
+//!
+//!     // Writing a position, like a host would do.
+//!     let position_urid =
+//!         urids.map(time::POSITION_URI);
+//!     let bpm_urid =
+//!         urids.map(time::BEATS_PER_MINUTE_URI);
+//!     {
+//!         let mut frame =
+//!             unsafe { out_port.write_atom_body(&(0, position_urid), &mut urids) }.unwrap();
+//!         frame.push_property::<f32>(bpm_urid, 0, &140.0, &mut urids).unwrap();
+//!     }
+//!
+//!     // Reading the position in a simulated `run` method.
+//!     let context = core::RunContext::new(1, 0, 44100.0, None);
+//!     let object = unsafe { in_port.read(&context, &mut urids) }.unwrap();
+//!     let position = time::read_position(object, &mut urids).unwrap();
+//!     assert_eq!(Some(140.0), position.beats_per_minute);
+//!     assert_eq!(None, position.speed);
+use crate::atom::Atom;
+use crate::object::Object;
+use lv2rs_core::uris::Uri;
+use urid::CachedMap;
+
+pub use lv2rs_core::time::Position;
+
+pub const URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/time\0");
+
+pub const POSITION_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/time#Position\0");
+pub const BAR_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/time#bar\0");
+pub const BAR_BEAT_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/time#barBeat\0");
+pub const BEAT_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/time#beat\0");
+pub const BEAT_UNIT_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/time#beatUnit\0");
+pub const BEATS_PER_BAR_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/time#beatsPerBar\0");
+pub const BEATS_PER_MINUTE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/time#beatsPerMinute\0");
+pub const FRAME_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/time#frame\0");
+pub const FRAMES_PER_SECOND_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/time#framesPerSecond\0");
+pub const SPEED_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/time#speed\0");
+
+urid::urid_cache! {
+    /// The URIDs of the time vocabulary.
+    pub struct TimeURIDs {
+        pub position: POSITION_URI,
+        pub bar: BAR_URI,
+        pub bar_beat: BAR_BEAT_URI,
+        pub beat: BEAT_URI,
+        pub beat_unit: BEAT_UNIT_URI,
+        pub beats_per_bar: BEATS_PER_BAR_URI,
+        pub beats_per_minute: BEATS_PER_MINUTE_URI,
+        pub frame: FRAME_URI,
+        pub frames_per_second: FRAMES_PER_SECOND_URI,
+        pub speed: SPEED_URI,
+    }
+}
+
+/// Read a number from an atom, regardless of its numeric type.
+fn read_number(atom: &Atom, urids: &mut CachedMap) -> Option<f64> {
+    if let Ok(value) = atom.get_body::<f32>(urids) {
+        Some(*value as f64)
+    } else if let Ok(value) = atom.get_body::<f64>(urids) {
+        Some(*value)
+    } else if let Ok(value) = atom.get_body::<i32>(urids) {
+        Some(*value as f64)
+    } else if let Ok(value) = atom.get_body::<i64>(urids) {
+        Some(*value as f64)
+    } else {
+        None
+    }
+}
+
+/// Read a position from an object.
+///
+/// If the object is not a `time:Position`, `None` is returned. Properties with unknown keys
+/// or non-numeric values are ignored. Numeric values are accepted regardless of their actual
+/// type, since not all hosts use the types suggested by the specification.
+pub fn read_position(object: &Object, urids: &mut CachedMap) -> Option<Position> {
+    if object.header.otype != urids.map(POSITION_URI) {
+        return None;
+    }
+
+    let bar_urid = urids.map(BAR_URI);
+    let bar_beat_urid = urids.map(BAR_BEAT_URI);
+    let beat_urid = urids.map(BEAT_URI);
+    let beat_unit_urid = urids.map(BEAT_UNIT_URI);
+    let beats_per_bar_urid = urids.map(BEATS_PER_BAR_URI);
+    let beats_per_minute_urid = urids.map(BEATS_PER_MINUTE_URI);
+    let frame_urid = urids.map(FRAME_URI);
+    let frames_per_second_urid = urids.map(FRAMES_PER_SECOND_URI);
+    let speed_urid = urids.map(SPEED_URI);
+
+    let mut position = Position::default();
+    for (header, atom) in object.iter() {
+        let value = match read_number(atom, urids) {
+            Some(value) => value,
+            None => continue,
+        };
+        if header.key == bar_urid {
+            position.bar = Some(value as i64);
+        } else if header.key == bar_beat_urid {
+            position.bar_beat = Some(value as f32);
+        } else if header.key == beat_urid {
+            position.beat = Some(value);
+        } else if header.key == beat_unit_urid {
+            position.beat_unit = Some(value as i32);
+        } else if header.key == beats_per_bar_urid {
+            position.beats_per_bar = Some(value as f32);
+        } else if header.key == beats_per_minute_urid {
+            position.beats_per_minute = Some(value as f32);
+        } else if header.key == frame_urid {
+            position.frame = Some(value as i64);
+        } else if header.key == frames_per_second_urid {
+            position.frames_per_second = Some(value as f32);
+        } else if header.key == speed_urid {
+            position.speed = Some(value as f32);
+        }
+    }
+    Some(position)
+}
@@ -15,6 +15,7 @@
 //! An example:
 //!
 //!     extern crate lv2rs_atom as atom;
+//!     extern crate lv2rs_core as core;
 //!     extern crate lv2rs_urid as urid;
 //!
 //!     use atom::prelude::*;
@@ -30,7 +31,7 @@
 //!
 //!     impl Plugin {
 //!         /// Simulated `run` method.
-//!         fn run(&mut self) {
+//!         fn run(&mut self, context: &mut core::RunContext) {
 //!             // Writing
 //!             {
 //!                 let mut frame =
@@ -43,7 +44,7 @@
 //!             let f32_urid = self.urids.map(<f32 as AtomBody>::get_uri());
 //!
 //!             // Reading.
-//!             let tuple = unsafe { self.in_port.get_atom_body(&mut self.urids) }.unwrap();
+//!             let tuple = unsafe { self.in_port.read(context, &mut self.urids) }.unwrap();
 //!             for sub_atom in tuple.iter() {
 //!                 match unsafe { sub_atom.get_body::<i32>(&mut self.urids) } {
 //!                     Ok(integer) => {
@@ -85,7 +86,7 @@
 //!     plugin.out_port.connect_port(atom);
 //!
 //!     // Calling `run`.
-//!     plugin.run();
+//!     plugin.run(&mut core::RunContext::new(1, 0, 44100.0, None));
 use crate::atom::{array::*, *};
 use crate::frame::{NestedFrame, WritingFrame, WritingFrameExt};
 use crate::uris;
@@ -100,7 +101,7 @@ impl AtomBody for Tuple {
     type InitializationParameter = ();
 
     fn get_uri() -> &'static CStr {
-        unsafe { CStr::from_bytes_with_nul_unchecked(uris::TUPLE_TYPE_URI) }
+        uris::TUPLE_TYPE_URI.as_cstr()
     }
 
     unsafe fn initialize_body<'a, W>(
@@ -0,0 +1,80 @@
+//! URIs of the LV2 units extension.
+//!
+//! The units extension defines units for the values of ports and properties, like hertz or
+//! decibels. Besides its use in plugin descriptions, a unit can be sent along with a value, for
+//! example in a patch message.
+use lv2rs_core::uris::Uri;
+
+pub const URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/units\0");
+
+pub const UNIT_CLASS_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/units#Unit\0");
+pub const CONVERSION_CLASS_URI: Uri =
+    Uri::new(b"http://lv2plug.in/ns/extensions/units#Conversion\0");
+pub const UNIT_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/units#unit\0");
+pub const CONVERSION_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/units#conversion\0");
+pub const FACTOR_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/units#factor\0");
+pub const SYMBOL_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/units#symbol\0");
+pub const RENDER_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/units#render\0");
+
+pub const BAR_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/units#bar\0");
+pub const BEAT_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/units#beat\0");
+pub const BPM_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/units#bpm\0");
+pub const CENT_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/units#cent\0");
+pub const CM_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/units#cm\0");
+pub const COEF_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/units#coef\0");
+pub const DB_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/units#db\0");
+pub const DEGREE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/units#degree\0");
+pub const FRAME_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/units#frame\0");
+pub const HZ_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/units#hz\0");
+pub const INCH_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/units#inch\0");
+pub const KHZ_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/units#khz\0");
+pub const KM_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/units#km\0");
+pub const M_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/units#m\0");
+pub const MHZ_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/units#mhz\0");
+pub const MIDI_NOTE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/units#midiNote\0");
+pub const MILE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/units#mile\0");
+pub const MIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/units#min\0");
+pub const MM_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/units#mm\0");
+pub const MS_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/units#ms\0");
+pub const OCT_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/units#oct\0");
+pub const PC_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/units#pc\0");
+pub const S_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/units#s\0");
+pub const SEMITONE_12TET_URI: Uri =
+    Uri::new(b"http://lv2plug.in/ns/extensions/units#semitone12TET\0");
+
+urid::urid_cache! {
+    /// The URIDs of the units vocabulary.
+    pub struct UnitsURIDs {
+        pub unit_class: UNIT_CLASS_URI,
+        pub conversion_class: CONVERSION_CLASS_URI,
+        pub unit: UNIT_URI,
+        pub conversion: CONVERSION_URI,
+        pub factor: FACTOR_URI,
+        pub symbol: SYMBOL_URI,
+        pub render: RENDER_URI,
+        pub bar: BAR_URI,
+        pub beat: BEAT_URI,
+        pub bpm: BPM_URI,
+        pub cent: CENT_URI,
+        pub cm: CM_URI,
+        pub coef: COEF_URI,
+        pub db: DB_URI,
+        pub degree: DEGREE_URI,
+        pub frame: FRAME_URI,
+        pub hz: HZ_URI,
+        pub inch: INCH_URI,
+        pub khz: KHZ_URI,
+        pub km: KM_URI,
+        pub m: M_URI,
+        pub mhz: MHZ_URI,
+        pub midi_note: MIDI_NOTE_URI,
+        pub mile: MILE_URI,
+        pub min: MIN_URI,
+        pub mm: MM_URI,
+        pub ms: MS_URI,
+        pub oct: OCT_URI,
+        pub pc: PC_URI,
+        pub s: S_URI,
+        pub semitone_12tet: SEMITONE_12TET_URI,
+    }
+}
@@ -3,8 +3,8 @@
 //! A [vector](type.Vector.html) is the LV2 equivalent of a slice: It has a variable length, but it
 //! does only contain one type of item, which has to be sized.
 //!
-//! When initialized, a vector does not contain any items. These items have to be pushed or appended
-//! to the vector using the [`VectorWritingFrame`](trait.VectorWritingFrame.html) trait. Every
+//! When initialized, a vector does not contain any items. Items are added one by one, as a slice
+//! or from an iterator using the [`VectorWritingFrame`](trait.VectorWritingFrame.html) trait. Every
 //! writing frame implements this trait via a blanket implementation and the trait is included in
 //! the crate's prelude. You can, therefore, act as if the extended methods were normal methods of a
 //! writing frame.
@@ -17,6 +17,7 @@
 //! An example:
 //!
 //!     extern crate lv2rs_atom as atom;
+//!     extern crate lv2rs_core as core;
 //!     extern crate lv2rs_urid as urid;
 //!
 //!     use atom::prelude::*;
@@ -32,18 +33,19 @@
 //!
 //!     impl Plugin {
 //!         /// Simulated `run` method.
-//!         fn run(&mut self) {
+//!         fn run(&mut self, context: &mut core::RunContext) {
 //!             // Writing
 //!             {
 //!                 let mut frame =
 //!                     unsafe { self.out_port.write_atom_body(&(), &mut self.urids) }.unwrap();
 //!                 frame.push(0.0).unwrap();
 //!                 frame.append(&[1.0, 2.0, 3.0, 4.0]).unwrap();
+//!                 frame.extend((5..8).map(|i| i as f32)).unwrap();
 //!             }
 //!
 //!             // Reading.
-//!             let vector = unsafe { self.in_port.get_atom_body(&mut self.urids) }.unwrap();
-//!             assert_eq!([0.0, 1.0, 2.0, 3.0, 4.0], vector.as_slice());
+//!             let vector = unsafe { self.in_port.read(context, &mut self.urids) }.unwrap();
+//!             assert_eq!([0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0], vector.as_slice());
 //!         }
 //!     }
 //!
@@ -68,7 +70,7 @@
 //!     plugin.out_port.connect_port(atom);
 //!
 //!     // Calling `run`.
-//!     plugin.run();
+//!     plugin.run(&mut core::RunContext::new(1, 0, 44100.0, None));
 use crate::atom::{array::*, *};
 use crate::frame::{WritingFrame, WritingFrameExt};
 use crate::uris;
@@ -121,7 +123,7 @@ where
     type InitializationParameter = ();
 
     fn get_uri() -> &'static CStr {
-        unsafe { CStr::from_bytes_with_nul_unchecked(uris::VECTOR_TYPE_URI) }
+        uris::VECTOR_TYPE_URI.as_cstr()
     }
 
     unsafe fn initialize_body<'a, W>(
@@ -180,6 +182,19 @@ where
     fn append(&mut self, slice: &[T]) -> Result<(), ()> {
         unsafe { Vector::<T>::append(self, slice) }
     }
+
+    /// Append the items of an iterator to the end of the vector.
+    ///
+    /// The items are written directly into the atom space, without collecting them first, which
+    /// makes this method suitable for large vectors that are computed in every cycle, like the
+    /// bins of a spectrum. Either all items are written or, if the space isn't sufficient, none.
+    fn extend<I>(&mut self, items: I) -> Result<(), ()>
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        unsafe { Vector::<T>::extend(self, items) }
+    }
 }
 
 impl<'a, T, F> VectorWritingFrame<'a, T> for F
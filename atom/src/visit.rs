@@ -0,0 +1,232 @@
+//! Walking through nested atoms.
+//!
+//! Tuples, objects and sequences may contain any atom, including other containers. Code that
+//! handles arbitrary atoms, like a logger, a converter or a validator, therefore has to recurse
+//! through these containers and identify the type of every atom it finds. An
+//! [`AtomVisitor`](trait.AtomVisitor.html) only implements the methods for the atoms it is
+//! interested in and [`walk`](fn.walk.html) calls them in the order the atoms appear:
+//!
+//!     extern crate lv2rs_atom as atom;
+//!     extern crate lv2rs_urid as urid;
+//!
+//!     use atom::ports::*;
+//!     use atom::prelude::*;
+//!     use atom::visit::*;
+//!     use urid::debug::DebugMap;
+//!
+//!     /// A visitor that sums up all integers.
+//!     struct IntSum(i64);
+//!
+//!     impl AtomVisitor for IntSum {
+//!         fn visit_scalar(&mut self, value: Scalar) {
+//!             match value {
+//!                 Scalar::Int(value) => self.0 += i64::from(value),
+//!                 Scalar::Long(value) => self.0 += value,
+//!                 _ => (),
+//!             }
+//!         }
+//!     }
+//!
+//!     let mut debug_map = DebugMap::new();
+//!     let mut urids = unsafe { debug_map.create_cached_map() };
+//!
+//!     // Creating the atom space and the port.
+//!     let mut atom_space = vec![0u64; 32];
+//!     let atom = unsafe { (atom_space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+//!     *(atom.mut_size()) = 256;
+//!     let mut out_port: AtomOutputPort<Tuple> = AtomOutputPort::new();
+//!     out_port.connect_port(atom);
+//!
+//!     // Writing a tuple that contains another tuple.
+//!     {
+//!         let mut tuple = unsafe { out_port.write_atom_body(&(), &mut urids) }.unwrap();
+//!         tuple.push_atom::<i32>(&1, &mut urids).unwrap();
+//!         tuple.push_atom::<f32>(&2.0, &mut urids).unwrap();
+//!         let mut inner = tuple.push_atom::<Tuple>(&(), &mut urids).unwrap();
+//!         inner.push_atom::<i64>(&3, &mut urids).unwrap();
+//!     }
+//!
+//!     // Summing up the integers.
+//!     let atom = unsafe { (atom_space.as_ptr() as *const Atom).as_ref() }.unwrap();
+//!     let mut sum = IntSum(0);
+//!     walk(atom, &mut sum, &mut urids);
+//!     assert_eq!(4, sum.0);
+//!
+//! Containers are entered with a `visit_` method, which may return `false` to skip the children,
+//! and left with a `leave_` method. Atoms of unknown types and malformed atoms are passed to
+//! [`visit_other`](trait.AtomVisitor.html#method.visit_other).
+use crate::atom::Atom;
+use crate::object::{ObjectHeader, PropertyHeader};
+use crate::prelude::*;
+use crate::sequence::{TimeStamp, TimeUnit};
+use crate::uris;
+use crate::vector::VectorHeader;
+use std::mem::size_of;
+use urid::{CachedMap, URID};
+
+/// The maximal nesting depth of containers that is followed.
+///
+/// Deeper containers are passed to [`visit_other`](trait.AtomVisitor.html#method.visit_other),
+/// which keeps the stack small even if the atom is malicious.
+pub const MAX_DEPTH: usize = 32;
+
+/// The value of a scalar atom.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Scalar {
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    Bool(bool),
+    URID(URID),
+}
+
+/// Callbacks for the atoms that are found by [`walk`](fn.walk.html).
+///
+/// Every method has a default implementation that does nothing and enters all containers, which
+/// means that a visitor only has to implement the methods it needs.
+pub trait AtomVisitor {
+    /// Visit an `Int`, `Long`, `Float`, `Double`, `Bool` or `URID` atom.
+    fn visit_scalar(&mut self, _value: Scalar) {}
+
+    /// Visit a string atom.
+    fn visit_string(&mut self, _string: &AtomString) {}
+
+    /// Visit a literal atom.
+    fn visit_literal(&mut self, _literal: &Literal) {}
+
+    /// Visit a vector atom.
+    ///
+    /// The items are read by getting the body of the atom as a vector of the child type.
+    fn visit_vector(&mut self, _header: &VectorHeader, _atom: &Atom) {}
+
+    /// Enter an object and return `true` if its properties should be visited.
+    fn visit_object(&mut self, _header: &ObjectHeader) -> bool {
+        true
+    }
+
+    /// Visit the header of a property and return `true` if its value should be visited.
+    fn visit_property(&mut self, _header: &PropertyHeader) -> bool {
+        true
+    }
+
+    /// Leave an object that was entered.
+    fn leave_object(&mut self, _header: &ObjectHeader) {}
+
+    /// Enter a tuple and return `true` if its elements should be visited.
+    fn visit_tuple(&mut self) -> bool {
+        true
+    }
+
+    /// Visit the index of a tuple element and return `true` if the element should be visited.
+    fn visit_tuple_element(&mut self, _index: usize) -> bool {
+        true
+    }
+
+    /// Leave a tuple that was entered.
+    fn leave_tuple(&mut self) {}
+
+    /// Enter a sequence and return `true` if its events should be visited.
+    fn visit_sequence(&mut self, _unit: TimeUnit) -> bool {
+        true
+    }
+
+    /// Visit the time stamp of an event and return `true` if the event should be visited.
+    fn visit_event(&mut self, _time: TimeStamp) -> bool {
+        true
+    }
+
+    /// Leave a sequence that was entered.
+    fn leave_sequence(&mut self) {}
+
+    /// Visit an atom of another type, a malformed atom or a container that is nested deeper than
+    /// [`MAX_DEPTH`](constant.MAX_DEPTH.html).
+    fn visit_other(&mut self, _atom: &Atom) {}
+}
+
+/// Walk through an atom and all atoms it contains and pass them to the visitor.
+///
+/// The atom has to be valid, as it is for example when it was read from a port.
+pub fn walk<V: AtomVisitor + ?Sized>(atom: &Atom, visitor: &mut V, urids: &mut CachedMap) {
+    walk_nested(atom, visitor, urids, 0);
+}
+
+/// Identify an atom and pass it to the visitor.
+fn walk_nested<V: AtomVisitor + ?Sized>(
+    atom: &Atom,
+    visitor: &mut V,
+    urids: &mut CachedMap,
+    depth: usize,
+) {
+    if let Some(value) = read_scalar(atom, urids) {
+        visitor.visit_scalar(value);
+    } else if let Ok(string) = atom.get_body::<AtomString>(urids) {
+        visitor.visit_string(string);
+    } else if let Ok(literal) = atom.get_body::<Literal>(urids) {
+        visitor.visit_literal(literal);
+    } else if let Some(header) = read_vector_header(atom, urids) {
+        visitor.visit_vector(header, atom);
+    } else if depth >= MAX_DEPTH {
+        visitor.visit_other(atom);
+    } else if let Ok(object) = atom.get_body::<Object>(urids) {
+        if visitor.visit_object(&object.header) {
+            for (header, value) in object.iter() {
+                if visitor.visit_property(header) {
+                    walk_nested(value, visitor, urids, depth + 1);
+                }
+            }
+            visitor.leave_object(&object.header);
+        }
+    } else if let Ok(tuple) = atom.get_body::<Tuple>(urids) {
+        if visitor.visit_tuple() {
+            for (index, element) in tuple.iter().enumerate() {
+                if visitor.visit_tuple_element(index) {
+                    walk_nested(element, visitor, urids, depth + 1);
+                }
+            }
+            visitor.leave_tuple();
+        }
+    } else if let Ok(sequence) = atom.get_body::<Sequence>(urids) {
+        if visitor.visit_sequence(sequence.time_unit(urids)) {
+            for (time, event) in sequence.iter(urids) {
+                if visitor.visit_event(time) {
+                    walk_nested(event, visitor, urids, depth + 1);
+                }
+            }
+            visitor.leave_sequence();
+        }
+    } else {
+        visitor.visit_other(atom);
+    }
+}
+
+/// Read the value of a scalar atom.
+///
+/// Integers are read before booleans, since booleans accept integer atoms too.
+fn read_scalar(atom: &Atom, urids: &mut CachedMap) -> Option<Scalar> {
+    if let Ok(value) = atom.get_body::<i32>(urids) {
+        Some(Scalar::Int(*value))
+    } else if let Ok(value) = atom.get_body::<i64>(urids) {
+        Some(Scalar::Long(*value))
+    } else if let Ok(value) = atom.get_body::<f32>(urids) {
+        Some(Scalar::Float(*value))
+    } else if let Ok(value) = atom.get_body::<f64>(urids) {
+        Some(Scalar::Double(*value))
+    } else if let Ok(value) = atom.get_body::<URID>(urids) {
+        Some(Scalar::URID(*value))
+    } else if let Ok(value) = atom.get_body::<bool>(urids) {
+        Some(Scalar::Bool(*value))
+    } else {
+        None
+    }
+}
+
+/// Read the body header of a vector atom, regardless of its child type.
+fn read_vector_header<'a>(atom: &'a Atom, urids: &mut CachedMap) -> Option<&'a VectorHeader> {
+    let vector_urid = urids.map(uris::VECTOR_TYPE_URI);
+    let raw_body = atom.get_raw_body();
+    if atom.atom_type() != vector_urid || raw_body.len() < size_of::<VectorHeader>() {
+        return None;
+    }
+    unsafe { (raw_body.as_ptr() as *const VectorHeader).as_ref() }
+}
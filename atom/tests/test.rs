@@ -1,8 +1,10 @@
 extern crate lv2rs_atom as atom;
+extern crate lv2rs_core;
 extern crate lv2rs_urid as urid;
 
 use atom::ports::*;
 use atom::prelude::*;
+use lv2rs_core::ports::{AudioInputPort, PortArray, PortCollection, PortKind, Ports};
 use std::ffi::CStr;
 use urid::debug::DebugMap;
 
@@ -26,7 +28,8 @@ fn test_scalar() {
     unsafe { out_port.write_atom_body(&42.0f32, &mut urids) }.unwrap();
 
     // Reading.
-    let float = unsafe { in_port.get_atom_body(&mut urids) }.unwrap();
+    let context = lv2rs_core::RunContext::new(1, 0, 44100.0, None);
+    let float = unsafe { in_port.read(&context, &mut urids) }.unwrap();
     let header = unsafe {
         (float as *const f32 as *const Atom)
             .sub(1)
@@ -36,10 +39,7 @@ fn test_scalar() {
 
     // Asserting.
     assert_eq!(4, header.size());
-    assert_eq!(
-        urids.map(CStr::from_bytes_with_nul(atom::uris::FLOAT_TYPE_URI).unwrap()),
-        header.atom_type()
-    );
+    assert_eq!(urids.map(atom::uris::FLOAT_TYPE_URI), header.atom_type());
     assert_eq!(42.0, *float);
 }
 
@@ -66,7 +66,8 @@ fn test_literal() {
         .is_ok());
 
     // Reading.
-    let literal = unsafe { in_port.get_atom_body(&mut urids) }.unwrap();
+    let context = lv2rs_core::RunContext::new(1, 0, 44100.0, None);
+    let literal = unsafe { in_port.read(&context, &mut urids) }.unwrap();
     let header = unsafe {
         (literal as *const Literal as *const Atom)
             .sub(1)
@@ -76,10 +77,7 @@ fn test_literal() {
 
     // Asserting.
     assert_eq!(20, header.size());
-    assert_eq!(
-        urids.map(CStr::from_bytes_with_nul(atom::uris::LITERAL_TYPE_URI).unwrap()),
-        header.atom_type()
-    );
+    assert_eq!(urids.map(atom::uris::LITERAL_TYPE_URI), header.atom_type());
     assert_eq!(0, literal.lang());
     assert_eq!("Hello World!", literal.as_str().unwrap());
 }
@@ -107,7 +105,8 @@ fn test_string() {
     unsafe { out_port.write_atom_body(message, &mut urids) }.unwrap();
 
     // Reading.
-    let string = unsafe { in_port.get_atom_body(&mut urids) }.unwrap();
+    let context = lv2rs_core::RunContext::new(1, 0, 44100.0, None);
+    let string = unsafe { in_port.read(&context, &mut urids) }.unwrap();
     let header = unsafe {
         (string as *const AtomString as *const Atom)
             .sub(1)
@@ -117,10 +116,7 @@ fn test_string() {
 
     // Asserting.
     assert_eq!(13, header.size());
-    assert_eq!(
-        urids.map(CStr::from_bytes_with_nul(atom::uris::STRING_TYPE_URI).unwrap()),
-        atom.atom_type()
-    );
+    assert_eq!(urids.map(atom::uris::STRING_TYPE_URI), atom.atom_type());
     assert_eq!("Hello World!", string.as_cstr().unwrap().to_str().unwrap());
 }
 
@@ -148,7 +144,8 @@ fn test_vector() {
     }
 
     // Reading.
-    let vector = unsafe { in_port.get_atom_body(&mut urids) }.unwrap();
+    let context = lv2rs_core::RunContext::new(1, 0, 44100.0, None);
+    let vector = unsafe { in_port.read(&context, &mut urids) }.unwrap();
     let header = unsafe {
         (vector as *const Vector<f32> as *const Atom)
             .sub(1)
@@ -158,18 +155,58 @@ fn test_vector() {
 
     // Asserting.
     assert_eq!(8 + 4 * 5, header.size());
-    assert_eq!(
-        urids.map(CStr::from_bytes_with_nul(atom::uris::VECTOR_TYPE_URI).unwrap()),
-        header.atom_type()
-    );
+    assert_eq!(urids.map(atom::uris::VECTOR_TYPE_URI), header.atom_type());
     assert_eq!(4, vector.child_body_size());
     assert_eq!(
-        urids.map(CStr::from_bytes_with_nul(atom::uris::FLOAT_TYPE_URI).unwrap()),
+        urids.map(atom::uris::FLOAT_TYPE_URI),
         vector.child_body_type()
     );
     assert_eq!([0.0, 1.0, 2.0, 3.0, 4.0], vector.as_slice());
 }
 
+#[test]
+fn test_vector_extend() {
+    let mut debug_map = DebugMap::new();
+    let mut urids = unsafe { debug_map.create_cached_map() };
+
+    // Creating the atom space.
+    let mut atom_space = vec![0u64; 32];
+    let atom = unsafe { (atom_space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+    *(atom.mut_size()) = 256 - 8;
+
+    // Creating the ports and connecting them.
+    let mut out_port: AtomOutputPort<Tuple> = AtomOutputPort::new();
+    out_port.connect_port(atom);
+    let mut in_port: AtomInputPort<Tuple> = AtomInputPort::new();
+    in_port.connect_port(atom as &Atom);
+
+    // Writing a vector within a tuple.
+    {
+        let mut frame = unsafe { out_port.write_atom_body(&(), &mut urids) }.unwrap();
+        let mut vector = frame.push_atom::<Vector<i32>>(&(), &mut urids).unwrap();
+        vector.push(-1).unwrap();
+        vector.extend((0..40).map(|i| i * i)).unwrap();
+        // The space isn't sufficient for all items, so none are written.
+        assert!(vector.extend(0..100).is_err());
+        // The size of the items overflows.
+        assert!(vector.extend((0..usize::MAX / 2).map(|_| 0)).is_err());
+        vector.extend(Vec::new()).unwrap();
+        vector.extend(vec![7, 8]).unwrap();
+    }
+
+    // Reading.
+    let context = lv2rs_core::RunContext::new(1, 0, 44100.0, None);
+    let tuple = unsafe { in_port.read(&context, &mut urids) }.unwrap();
+    let atom = tuple.iter().next().unwrap();
+    assert_eq!(8 + 4 * 43, atom.size());
+    let vector = atom.get_body::<Vector<i32>>(&mut urids).unwrap();
+    let expected: Vec<i32> = std::iter::once(-1)
+        .chain((0..40).map(|i| i * i))
+        .chain(vec![7, 8])
+        .collect();
+    assert_eq!(expected.as_slice(), vector.as_slice());
+}
+
 #[test]
 fn test_tuple() {
     let mut debug_map = DebugMap::new();
@@ -207,7 +244,8 @@ fn test_tuple() {
     }
 
     // Reading.
-    let tuple = unsafe { in_port.get_atom_body(&mut urids) }.unwrap();
+    let context = lv2rs_core::RunContext::new(1, 0, 44100.0, None);
+    let tuple = unsafe { in_port.read(&context, &mut urids) }.unwrap();
     let header = unsafe {
         (tuple as *const Tuple as *const Atom)
             .sub(1)
@@ -222,10 +260,7 @@ fn test_tuple() {
     // Literal: AtomHeader, LiteralHeader, string, pad.
     assumed_size += 8 + 8 + 13 + 3;
     assert_eq!(assumed_size, header.size());
-    assert_eq!(
-        urids.map(CStr::from_bytes_with_nul(atom::uris::TUPLE_TYPE_URI).unwrap()),
-        atom.atom_type()
-    );
+    assert_eq!(urids.map(atom::uris::TUPLE_TYPE_URI), atom.atom_type());
 
     let mut iter = tuple.iter();
     let integer = iter.next().unwrap().get_body::<i32>(&mut urids).unwrap();
@@ -290,7 +325,8 @@ fn test_sequence() {
     }
 
     // Reading.
-    let atom = unsafe { in_port.get_atom_body(&mut urids) }.unwrap();
+    let context = lv2rs_core::RunContext::new(1, 0, 44100.0, None);
+    let atom = unsafe { in_port.read(&context, &mut urids) }.unwrap();
     let mut sequence_iter = atom.iter(&mut urids);
 
     let (time_stamp, integer) = sequence_iter.next().unwrap();
@@ -309,3 +345,813 @@ fn test_sequence() {
         assert_eq!(2, *integer);
     }
 }
+
+#[test]
+fn test_split_run() {
+    use atom::sequence::{TimeStamp, TimeUnit};
+    use atom::split::split_run;
+    let mut debug_map = DebugMap::new();
+    let mut urids = unsafe { debug_map.create_cached_map() };
+
+    // Creating the atom space.
+    let mut atom_space = vec![0u8; 256];
+    let atom = unsafe { (atom_space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+    *(atom.mut_size()) = 256 - 8;
+
+    // Creating the ports and connecting them.
+    let mut out_port: AtomOutputPort<Sequence> = AtomOutputPort::new();
+    out_port.connect_port(atom);
+    let mut in_port: AtomInputPort<Sequence> = AtomInputPort::new();
+    in_port.connect_port(atom as &Atom);
+
+    // Writing.
+    {
+        let mut frame = unsafe { out_port.write_atom_body(&TimeUnit::Frames, &mut urids) }.unwrap();
+        for (time, value) in [(0, 0), (3, 1), (3, 2), (20, 3)].iter() {
+            frame
+                .push_event::<i32>(TimeStamp::Frames(*time), value, &mut urids)
+                .unwrap();
+        }
+    }
+
+    // Reading.
+    let context = lv2rs_core::RunContext::new(1, 0, 44100.0, None);
+    let sequence = unsafe { in_port.read(&context, &mut urids) }.unwrap();
+    let mut segments: Vec<(std::ops::Range<usize>, Vec<i32>)> = Vec::new();
+    split_run(8, sequence.iter(&mut urids), |range, events| {
+        let values = events
+            .map(|(_, event)| *event.get_body::<i32>(&mut urids).unwrap())
+            .collect();
+        segments.push((range, values));
+    });
+    assert_eq!(
+        vec![(0..3, vec![0]), (3..7, vec![1, 2]), (7..8, vec![3])],
+        segments
+    );
+
+    // Without events, the whole block is a single segment.
+    let mut segments: Vec<(std::ops::Range<usize>, usize)> = Vec::new();
+    split_run(
+        8,
+        std::iter::empty::<(TimeStamp, &Atom)>(),
+        |range, events| segments.push((range, events.count())),
+    );
+    assert_eq!(vec![(0..8, 0)], segments);
+}
+
+#[test]
+fn test_vocabularies() {
+    use atom::patch::PatchURIDs;
+    use atom::time::TimeURIDs;
+    use atom::units::UnitsURIDs;
+    use atom::uris::AtomURIDs;
+
+    let mut debug_map = DebugMap::new();
+    let mut urids = unsafe { debug_map.create_cached_map() };
+
+    let atom_urids = AtomURIDs::new(&mut urids);
+    let patch_urids = PatchURIDs::new(&mut urids);
+    let time_urids = TimeURIDs::new(&mut urids);
+    let units_urids = UnitsURIDs::new(&mut urids);
+
+    assert_eq!(atom_urids.float, urids.map(atom::uris::FLOAT_TYPE_URI));
+    assert_ne!(patch_urids.request, patch_urids.request_property);
+    assert_ne!(time_urids.frame, units_urids.frame);
+    assert_eq!(units_urids.db, urids.map(atom::units::DB_URI));
+}
+
+#[test]
+fn test_osc() {
+    use atom::osc::*;
+
+    let mut debug_map = DebugMap::new();
+    let mut urids = unsafe { debug_map.create_cached_map() };
+    let osc_urids = OscURIDs::new(&mut urids);
+    let symbol = urids.map(CStr::from_bytes_with_nul(b"https://example.org/symbol\0").unwrap());
+    let timetag = Timetag {
+        integral: 3_900_000_000,
+        fraction: 1 << 31,
+    };
+    let arguments = [
+        Argument::Int(-4),
+        Argument::Float(0.25),
+        Argument::String("Hello"),
+        Argument::Long(1 << 40),
+        Argument::Double(0.125),
+        Argument::Symbol(symbol),
+        Argument::Bool(true),
+        Argument::Timetag(Timetag::IMMEDIATE),
+    ];
+
+    let mut atom_space = vec![0u64; 128];
+    let atom = unsafe { (atom_space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+    *(atom.mut_size()) = 128 * 8;
+    let mut out_port: AtomOutputPort<Object> = AtomOutputPort::new();
+    out_port.connect_port(atom);
+
+    // Writing a bundle with a message and an empty bundle.
+    {
+        let mut object =
+            unsafe { out_port.write_atom_body(&(0, osc_urids.bundle), &mut urids) }.unwrap();
+        assert!(object.write_message("/wrong", &mut urids).is_err());
+        let mut items = object.write_bundle(timetag, &mut urids).unwrap();
+        {
+            let mut message = items
+                .push_atom::<Object>(&(0, osc_urids.message), &mut urids)
+                .unwrap();
+            assert!(message.write_bundle(timetag, &mut urids).is_err());
+            let mut frame = message.write_message("/synth/1", &mut urids).unwrap();
+            for argument in arguments.iter() {
+                frame.push_argument(argument, &mut urids).unwrap();
+            }
+            assert!(frame
+                .push_argument(&Argument::Unsupported(0), &mut urids)
+                .is_err());
+        }
+        let mut bundle = items
+            .push_atom::<Object>(&(0, osc_urids.bundle), &mut urids)
+            .unwrap();
+        bundle.write_bundle(Timetag::IMMEDIATE, &mut urids).unwrap();
+    }
+
+    // Reading it again.
+    let atom = unsafe { (atom_space.as_ptr() as *const Atom).as_ref() }.unwrap();
+    let bundle = match read_packet(atom, &mut urids) {
+        Some(Packet::Bundle(bundle)) => bundle,
+        _ => panic!("Not a bundle"),
+    };
+    assert_eq!(timetag, bundle.timetag);
+    let items: Vec<&Atom> = bundle.items().collect();
+    assert_eq!(2, items.len());
+
+    let message = match read_packet(items[0], &mut urids) {
+        Some(Packet::Message(message)) => message,
+        _ => panic!("Not a message"),
+    };
+    assert_eq!("/synth/1", message.path);
+    let mut reader = message.arguments();
+    for argument in arguments.iter() {
+        assert_eq!(Some(*argument), reader.read(&mut urids));
+    }
+    assert_eq!(None, reader.read(&mut urids));
+
+    // The typed readers only advance if the type matches.
+    let mut reader = message.arguments();
+    assert_eq!(None, reader.read_float(&mut urids));
+    assert_eq!(Some(-4), reader.read_int(&mut urids));
+    assert_eq!(Some(0.25), reader.read_float(&mut urids));
+    assert_eq!(Some("Hello"), reader.read_string(&mut urids));
+    assert_eq!(Some(1 << 40), reader.read_long(&mut urids));
+    assert_eq!(Some(0.125), reader.read_double(&mut urids));
+    assert_eq!(Some(symbol), reader.read_symbol(&mut urids));
+    assert_eq!(Some(true), reader.read_bool(&mut urids));
+    assert_eq!(Some(Timetag::IMMEDIATE), reader.read_timetag(&mut urids));
+    assert_eq!(None, reader.read_int(&mut urids));
+
+    match read_packet(items[1], &mut urids) {
+        Some(Packet::Bundle(bundle)) => {
+            assert_eq!(Timetag::IMMEDIATE, bundle.timetag);
+            assert_eq!(0, bundle.items().count());
+        }
+        _ => panic!("Not a bundle"),
+    }
+
+    // Other objects and atoms aren't packets.
+    let time_position = urids.map(atom::time::POSITION_URI);
+    {
+        let mut frame =
+            unsafe { out_port.write_atom_body(&(0, time_position), &mut urids) }.unwrap();
+        assert!(frame.write_message("/synth/1", &mut urids).is_err());
+    }
+    assert!(read_packet(atom, &mut urids).is_none());
+    unsafe { *(*(atom_space.as_mut_ptr() as *mut Atom)).mut_size() = 128 * 8 };
+    {
+        let mut object =
+            unsafe { out_port.write_atom_body(&(0, osc_urids.message), &mut urids) }.unwrap();
+        assert!(object.write_message("/nul\0", &mut urids).is_err());
+    }
+    assert!(read_packet(atom, &mut urids).is_none());
+}
+
+#[derive(Ports)]
+struct TestPorts {
+    audio_in: AudioInputPort,
+    #[port(buffer_type = "Sequence", supports = "Float")]
+    atom_in: AtomInputPort<f32>,
+    atom_out: AtomOutputPort<f32>,
+}
+
+#[test]
+fn test_derived_ports() {
+    let mut debug_map = DebugMap::new();
+    let mut urids = unsafe { debug_map.create_cached_map() };
+
+    let mut atom_space = vec![0u8; 256];
+    let atom = unsafe { (atom_space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+    *(atom.mut_size()) = 256 - 8;
+    let atom = atom as *mut Atom as *mut ();
+
+    let mut ports = TestPorts {
+        audio_in: AudioInputPort::new(),
+        atom_in: AtomInputPort::new(),
+        atom_out: AtomOutputPort::new(),
+    };
+    assert_eq!(3, TestPorts::PORT_COUNT);
+    assert_eq!(0, TestPorts::AUDIO_IN);
+    assert_eq!(PortKind::AtomInput, TestPorts::PORTS[1].kind);
+    assert_eq!(Some("Sequence"), TestPorts::PORTS[1].buffer_type);
+    assert_eq!(&["Float"], TestPorts::PORTS[1].supports);
+    assert!(ports.connect_port(TestPorts::ATOM_IN, atom));
+    assert!(ports.connect_port(TestPorts::ATOM_OUT, atom));
+    assert!(!ports.connect_port(TestPorts::PORT_COUNT, atom));
+
+    unsafe { ports.atom_out.write_atom_body(&42.0f32, &mut urids) }.unwrap();
+    let context = lv2rs_core::RunContext::new(1, 0, 44100.0, None);
+    let float = unsafe { ports.atom_in.read(&context, &mut urids) }.unwrap();
+    assert_eq!(42.0, *float);
+    assert!(unsafe { ports.audio_in.as_slice(1) }.is_none());
+}
+
+#[test]
+fn test_foreign_atom() {
+    #[repr(C)]
+    #[allow(non_camel_case_types)]
+    struct LV2_Atom {
+        size: u32,
+        type_: u32,
+    }
+
+    let mut debug_map = DebugMap::new();
+    let mut urids = unsafe { debug_map.create_cached_map() };
+
+    let mut atom_space = vec![0u8; 256];
+    let atom = unsafe { (atom_space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+    *(atom.mut_size()) = 256 - 8;
+    let mut port = AtomOutputPort::<i32>::new();
+    port.connect_port(atom as *mut Atom);
+    unsafe { port.write_atom_body(&42, &mut urids) }.unwrap();
+
+    let foreign: &LV2_Atom = unsafe { lv2rs_core::raw::to_foreign(&*atom) };
+    assert_eq!(4, foreign.size);
+    assert_eq!(urids.map(atom::uris::INT_TYPE_URI), foreign.type_);
+
+    let atom: &Atom = unsafe { lv2rs_core::raw::from_foreign(foreign) };
+    assert_eq!(Ok(&42), atom.get_body::<i32>(&mut urids).map_err(|_| ()));
+}
+
+#[test]
+fn test_schema() {
+    use atom::schema::*;
+    use urid::{CachedMap, URID};
+
+    let mut debug_map = DebugMap::new();
+    let mut urids = unsafe { debug_map.create_cached_map() };
+    let [note, chord, pitch, velocity, channel] = [
+        b"http://example.org/Note\0" as &[u8],
+        b"http://example.org/Chord\0",
+        b"http://example.org/pitch\0",
+        b"http://example.org/velocity\0",
+        b"http://example.org/channel\0",
+    ]
+    .map(|uri| urids.map(CStr::from_bytes_with_nul(uri).unwrap()));
+    let int = urids.map(<i32 as AtomBody>::get_uri());
+    let float = urids.map(<f32 as AtomBody>::get_uri());
+
+    let schema = ObjectSchema::new(Some(note))
+        .with_required::<i32>(pitch, &mut urids)
+        .with_optional::<f32>(velocity, &mut urids);
+    let strict_schema = ObjectSchema::new(None)
+        .with_required::<i32>(pitch, &mut urids)
+        .with_required::<f32>(velocity, &mut urids)
+        .strict();
+    assert_eq!(Some(note), schema.otype());
+    assert_eq!(None, strict_schema.otype());
+    assert!(schema.declares(velocity));
+    assert!(!schema.declares(channel));
+
+    fn write_object(
+        atom_space: &mut [u64],
+        urids: &mut CachedMap,
+        otype: URID,
+        properties: &[(URID, i32)],
+    ) {
+        let atom = unsafe { (atom_space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+        *(atom.mut_size()) = 256 - 8;
+        let mut out_port: AtomOutputPort<Object> = AtomOutputPort::new();
+        out_port.connect_port(atom);
+        let mut object = unsafe { out_port.write_atom_body(&(0, otype), urids) }.unwrap();
+        for (key, value) in properties {
+            object.push_property::<i32>(*key, 0, value, urids).unwrap();
+        }
+    }
+    let mut atom_space = vec![0u64; 32];
+
+    // A valid object with an undeclared property.
+    write_object(
+        &mut atom_space,
+        &mut urids,
+        note,
+        &[(pitch, 60), (channel, 1)],
+    );
+    let atom = unsafe { (atom_space.as_ptr() as *const Atom).as_ref() }.unwrap();
+    let object = schema.validate_atom(atom, &mut urids).unwrap();
+    assert!(schema.errors(object, &mut urids).is_empty());
+    assert_eq!(
+        vec![
+            SchemaError::UnknownProperty { key: channel },
+            SchemaError::MissingProperty { key: velocity },
+        ],
+        strict_schema.errors(object, &mut urids)
+    );
+
+    // A property with the wrong type.
+    write_object(
+        &mut atom_space,
+        &mut urids,
+        note,
+        &[(pitch, 60), (velocity, 100)],
+    );
+    let atom = unsafe { (atom_space.as_ptr() as *const Atom).as_ref() }.unwrap();
+    assert_eq!(
+        Some(SchemaError::WrongType {
+            key: velocity,
+            expected: float,
+            found: int
+        }),
+        schema.validate_atom(atom, &mut urids).err()
+    );
+
+    // An object of the wrong class with a duplicate and a missing property.
+    write_object(
+        &mut atom_space,
+        &mut urids,
+        chord,
+        &[(channel, 1), (channel, 2)],
+    );
+    let atom = unsafe { (atom_space.as_ptr() as *const Atom).as_ref() }.unwrap();
+    let object = atom.get_body::<Object>(&mut urids).unwrap();
+    let errors = schema.errors(object, &mut urids);
+    assert_eq!(
+        vec![
+            SchemaError::WrongClass {
+                expected: note,
+                found: chord
+            },
+            SchemaError::DuplicateProperty { key: channel },
+            SchemaError::MissingProperty { key: pitch },
+        ],
+        errors
+    );
+    assert_eq!(Err(errors[0]), schema.validate(object, &mut urids));
+    assert_eq!(None, errors[0].key());
+    assert_eq!(Some(pitch), errors[2].key());
+    assert_eq!(
+        format!("property {} is missing", pitch),
+        errors[2].to_string()
+    );
+
+    // Other atoms aren't objects.
+    let mut atom_space = vec![0u64; 4];
+    let atom = unsafe { (atom_space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+    *(atom.mut_size()) = 24;
+    let mut out_port: AtomOutputPort<i32> = AtomOutputPort::new();
+    out_port.connect_port(atom);
+    unsafe { out_port.write_atom_body(&42, &mut urids) }.unwrap();
+    let atom = unsafe { (atom_space.as_ptr() as *const Atom).as_ref() }.unwrap();
+    assert_eq!(
+        Some(SchemaError::NotAnObject { atom_type: int }),
+        schema.validate_atom(atom, &mut urids).err()
+    );
+}
+
+#[test]
+fn test_bool() {
+    let mut debug_map = DebugMap::new();
+    let mut urids = unsafe { debug_map.create_cached_map() };
+    let bool_urid = urids.map(atom::uris::BOOL_TYPE_URI);
+
+    let mut atom_space = vec![0u64; 4];
+    let atom = unsafe { (atom_space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+    *(atom.mut_size()) = 24;
+
+    // Booleans are written as 32-bit integers.
+    {
+        let mut port = AtomOutputPort::<bool>::new();
+        port.connect_port(atom as *mut Atom);
+        unsafe { port.write_atom_body(&true, &mut urids) }.unwrap();
+    }
+    assert_eq!(4, atom.size());
+    assert_eq!(bool_urid, atom.atom_type());
+    assert_eq!(1, atom_space[1] as u32);
+    let atom = unsafe { (atom_space.as_ptr() as *const Atom).as_ref() }.unwrap();
+    assert_eq!(Ok(&true), atom.get_body::<bool>(&mut urids).map_err(|_| ()));
+
+    // Integers are read as booleans, but not the other way around.
+    for (value, expected) in [(0, false), (1, true), (-3, true)].iter() {
+        let mut atom_space = vec![0u64; 4];
+        let atom = unsafe { (atom_space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+        *(atom.mut_size()) = 24;
+        let mut port = AtomOutputPort::<i32>::new();
+        port.connect_port(atom as *mut Atom);
+        unsafe { port.write_atom_body(value, &mut urids) }.unwrap();
+        assert_eq!(
+            Ok(expected),
+            atom.get_body::<bool>(&mut urids).map_err(|_| ())
+        );
+    }
+    assert!(atom.get_body::<i32>(&mut urids).is_err());
+
+    // Bodies that aren't four bytes long are only accepted if they contain a single byte.
+    atom_space[0] = u64::from(bool_urid) << 32 | 1;
+    atom_space[1] = 0;
+    let atom = unsafe { (atom_space.as_ptr() as *const Atom).as_ref() }.unwrap();
+    assert_eq!(
+        Ok(&false),
+        atom.get_body::<bool>(&mut urids).map_err(|_| ())
+    );
+    atom_space[0] = u64::from(bool_urid) << 32 | 2;
+    let atom = unsafe { (atom_space.as_ptr() as *const Atom).as_ref() }.unwrap();
+    assert!(atom.get_body::<bool>(&mut urids).is_err());
+
+    assert_eq!(atom::osc::Argument::Bool(true), true.into());
+}
+
+#[test]
+fn test_visit() {
+    use atom::object::{ObjectHeader, PropertyHeader};
+    use atom::vector::VectorHeader;
+    use atom::visit::*;
+
+    /// A visitor that logs every atom, but skips the property with the key 3.
+    struct Logger(Vec<String>);
+
+    impl AtomVisitor for Logger {
+        fn visit_scalar(&mut self, value: Scalar) {
+            self.0.push(format!("{:?}", value));
+        }
+
+        fn visit_string(&mut self, string: &AtomString) {
+            self.0.push(format!("{:?}", string.as_cstr().unwrap()));
+        }
+
+        fn visit_vector(&mut self, header: &VectorHeader, atom: &Atom) {
+            self.0
+                .push(format!("vector {} {}", header.child_size, atom.size()));
+        }
+
+        fn visit_object(&mut self, header: &ObjectHeader) -> bool {
+            self.0.push(format!("object {}", header.otype));
+            true
+        }
+
+        fn visit_property(&mut self, header: &PropertyHeader) -> bool {
+            self.0.push(format!("key {}", header.key));
+            header.key != 3
+        }
+
+        fn leave_object(&mut self, _header: &ObjectHeader) {
+            self.0.push("end object".to_owned());
+        }
+
+        fn visit_tuple_element(&mut self, index: usize) -> bool {
+            self.0.push(format!("element {}", index));
+            true
+        }
+
+        fn leave_tuple(&mut self) {
+            self.0.push("end tuple".to_owned());
+        }
+
+        fn visit_other(&mut self, atom: &Atom) {
+            self.0.push(format!("other {}", atom.size()));
+        }
+    }
+
+    let mut debug_map = DebugMap::new();
+    let mut urids = unsafe { debug_map.create_cached_map() };
+
+    let mut atom_space = vec![0u64; 32];
+    let atom = unsafe { (atom_space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+    *(atom.mut_size()) = 256;
+    let mut port = AtomOutputPort::<Object>::new();
+    port.connect_port(atom as *mut Atom);
+    {
+        let mut object = unsafe { port.write_atom_body(&(0, 100), &mut urids) }.unwrap();
+        object.push_property::<i32>(1, 0, &42, &mut urids).unwrap();
+        {
+            let mut tuple = object
+                .push_property::<Tuple>(2, 0, &(), &mut urids)
+                .unwrap();
+            tuple.push_atom::<bool>(&true, &mut urids).unwrap();
+            let hi = CStr::from_bytes_with_nul(b"hi\0").unwrap();
+            tuple.push_atom::<AtomString>(hi, &mut urids).unwrap();
+        }
+        object.push_property::<f64>(3, 0, &1.0, &mut urids).unwrap();
+        {
+            let mut vector = object
+                .push_property::<Vector<f32>>(4, 0, &(), &mut urids)
+                .unwrap();
+            vector.append(&[1.0, 2.0]).unwrap();
+        }
+        object
+            .push_property::<Literal>(5, 0, &0, &mut urids)
+            .unwrap();
+    }
+
+    let atom = unsafe { (atom_space.as_ptr() as *const Atom).as_ref() }.unwrap();
+    let mut logger = Logger(Vec::new());
+    walk(atom, &mut logger, &mut urids);
+    assert_eq!(
+        vec![
+            "object 100",
+            "key 1",
+            "Int(42)",
+            "key 2",
+            "element 0",
+            "Bool(true)",
+            "element 1",
+            "\"hi\"",
+            "end tuple",
+            "key 3",
+            "key 4",
+            "vector 4 16",
+            "key 5",
+            "end object",
+        ],
+        logger.0
+    );
+}
+
+#[test]
+fn test_sequence_cursor() {
+    use atom::sequence::*;
+
+    /// A handler that consumes all integers at the start of the remaining events.
+    fn handle_ints(cursor: &mut SequenceCursor, urids: &mut urid::CachedMap) -> Vec<i32> {
+        let mut values = Vec::new();
+        while let Some((_, atom)) = cursor.next_if(|_, atom| atom.get_body::<i32>(urids).is_ok()) {
+            values.push(*atom.get_body::<i32>(urids).unwrap());
+        }
+        values
+    }
+
+    let mut debug_map = DebugMap::new();
+    let mut urids = unsafe { debug_map.create_cached_map() };
+
+    let mut atom_space = vec![0u64; 32];
+    let atom = unsafe { (atom_space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+    *(atom.mut_size()) = 256;
+    let mut port = AtomOutputPort::<Sequence>::new();
+    port.connect_port(atom as *mut Atom);
+    {
+        let mut frame = unsafe { port.write_atom_body(&TimeUnit::Frames, &mut urids) }.unwrap();
+        frame
+            .push_event::<i32>(TimeStamp::Frames(0), &1, &mut urids)
+            .unwrap();
+        frame
+            .push_event::<i32>(TimeStamp::Frames(0), &2, &mut urids)
+            .unwrap();
+        frame
+            .push_event::<f32>(TimeStamp::Frames(2), &3.0, &mut urids)
+            .unwrap();
+        frame
+            .push_event::<i32>(TimeStamp::Frames(4), &4, &mut urids)
+            .unwrap();
+    }
+
+    let atom = unsafe { (atom_space.as_ptr() as *const Atom).as_ref() }.unwrap();
+    let sequence = atom.get_body::<Sequence>(&mut urids).unwrap();
+    let mut cursor = sequence.cursor(&mut urids);
+    assert_eq!(TimeUnit::Frames, cursor.time_unit());
+
+    // The handler stops at the float, which is left to the next one.
+    assert_eq!(vec![1, 2], handle_ints(&mut cursor, &mut urids));
+    assert_eq!(2, cursor.position());
+    assert_eq!(
+        Some(TimeStamp::Frames(2)),
+        cursor.peek().map(|(time, _)| time)
+    );
+    assert_eq!(0, cursor.until(TimeStamp::Beats(4.0)).count());
+    assert_eq!(1, cursor.until(TimeStamp::Frames(4)).count());
+    assert_eq!(3, cursor.position());
+    assert_eq!(vec![4], handle_ints(&mut cursor, &mut urids));
+    assert!(cursor.is_finished());
+    assert!(cursor.next().is_none());
+
+    cursor.rewind();
+    assert_eq!(0, cursor.position());
+    assert_eq!(4, cursor.count());
+}
+
+#[test]
+fn test_filter_sequence() {
+    use atom::filter::filter_sequence;
+    use atom::sequence::*;
+
+    let mut debug_map = DebugMap::new();
+    let mut urids = unsafe { debug_map.create_cached_map() };
+
+    let mut input_space = vec![0u64; 32];
+    let atom = unsafe { (input_space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+    *(atom.mut_size()) = 256 - 8;
+    let mut port = AtomOutputPort::<Sequence>::new();
+    port.connect_port(atom as *mut Atom);
+    {
+        let mut frame = unsafe { port.write_atom_body(&TimeUnit::Frames, &mut urids) }.unwrap();
+        frame
+            .push_event::<i32>(TimeStamp::Frames(0), &1, &mut urids)
+            .unwrap();
+        frame
+            .push_event::<f32>(TimeStamp::Frames(1), &0.5, &mut urids)
+            .unwrap();
+        let string = CStr::from_bytes_with_nul(b"Hello\0").unwrap();
+        frame
+            .push_event::<AtomString>(TimeStamp::Frames(2), string, &mut urids)
+            .unwrap();
+        frame
+            .push_event::<i32>(TimeStamp::Frames(3), &2, &mut urids)
+            .unwrap();
+    }
+    let atom = unsafe { (input_space.as_ptr() as *const Atom).as_ref() }.unwrap();
+    let input = atom.get_body::<Sequence>(&mut urids).unwrap();
+
+    // Integers are dropped, floats are doubled and everything else is passed through.
+    let mut output_space = vec![0u64; 32];
+    let atom = unsafe { (output_space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+    *(atom.mut_size()) = 256 - 8;
+    let mut port = AtomOutputPort::<Sequence>::new();
+    port.connect_port(atom as *mut Atom);
+    {
+        let mut frame = unsafe { port.write_atom_body(&TimeUnit::Frames, &mut urids) }.unwrap();
+        filter_sequence(input, &mut frame, &mut urids, |event, urids| {
+            if event.atom().get_body::<i32>(urids).is_ok() {
+                Ok(())
+            } else if let Ok(value) = event.atom().get_body::<f32>(urids) {
+                event.replace::<f32>(&(value * 2.0), urids).map(|_| ())
+            } else {
+                event.pass(urids)
+            }
+        })
+        .unwrap();
+    }
+
+    let atom = unsafe { (output_space.as_ptr() as *const Atom).as_ref() }.unwrap();
+    let output = atom.get_body::<Sequence>(&mut urids).unwrap();
+    let events: Vec<(TimeStamp, &Atom)> = output.iter(&mut urids).collect();
+    assert_eq!(2, events.len());
+    assert_eq!(TimeStamp::Frames(1), events[0].0);
+    assert_eq!(1.0, *events[0].1.get_body::<f32>(&mut urids).unwrap());
+    assert_eq!(TimeStamp::Frames(2), events[1].0);
+    assert_eq!(
+        "Hello",
+        events[1]
+            .1
+            .get_body::<AtomString>(&mut urids)
+            .unwrap()
+            .as_cstr()
+            .unwrap()
+            .to_str()
+            .unwrap()
+    );
+
+    // The time unit of the output has to match and a full output stops the filter.
+    {
+        let mut frame = unsafe { port.write_atom_body(&TimeUnit::Beats, &mut urids) }.unwrap();
+        assert_eq!(
+            Err(PushEventError::WrongTimeUnit),
+            filter_sequence(input, &mut frame, &mut urids, |event, urids| event
+                .pass(urids))
+        );
+    }
+    let mut small_space = vec![0u64; 6];
+    let atom = unsafe { (small_space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+    *(atom.mut_size()) = 48 - 8;
+    port.connect_port(atom as *mut Atom);
+    {
+        let mut frame = unsafe { port.write_atom_body(&TimeUnit::Frames, &mut urids) }.unwrap();
+        assert_eq!(
+            Err(PushEventError::InsufficientSpace),
+            filter_sequence(input, &mut frame, &mut urids, |event, urids| event
+                .pass(urids))
+        );
+    }
+    let atom = unsafe { (small_space.as_ptr() as *const Atom).as_ref() }.unwrap();
+    let output = atom.get_body::<Sequence>(&mut urids).unwrap();
+    assert_eq!(1, output.iter(&mut urids).count());
+}
+
+#[test]
+fn test_padded_scalars() {
+    let mut debug_map = DebugMap::new();
+    let mut urids = unsafe { debug_map.create_cached_map() };
+    let int_urid = urids.map(<i32 as AtomBody>::get_uri());
+    let bool_urid = urids.map(<bool as AtomBody>::get_uri());
+
+    // An int and a bool with bodies that are padded to eight bytes.
+    let mut atom_space = vec![0u64; 4];
+    {
+        let atom = unsafe { (atom_space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+        *(atom.mut_size()) = 8;
+        *(atom.mut_atom_type()) = int_urid;
+        let atom = unsafe { (atom_space.as_mut_ptr().add(2) as *mut Atom).as_mut() }.unwrap();
+        *(atom.mut_size()) = 8;
+        *(atom.mut_atom_type()) = bool_urid;
+    }
+    atom_space[1] = 42;
+    atom_space[3] = 1;
+    let int = unsafe { (atom_space.as_ptr() as *const Atom).as_ref() }.unwrap();
+    let boolean = unsafe { (atom_space.as_ptr().add(2) as *const Atom).as_ref() }.unwrap();
+
+    if cfg!(feature = "padded-scalars") {
+        assert_eq!(Ok(&42), int.get_body::<i32>(&mut urids).map_err(|_| ()));
+        assert_eq!(
+            Ok(&true),
+            boolean.get_body::<bool>(&mut urids).map_err(|_| ())
+        );
+    } else {
+        assert!(int.get_body::<i32>(&mut urids).is_err());
+        assert!(boolean.get_body::<bool>(&mut urids).is_err());
+    }
+    // The type is still checked.
+    assert!(int.get_body::<f32>(&mut urids).is_err());
+    assert!(boolean.get_body::<i32>(&mut urids).is_err());
+}
+
+#[test]
+fn test_read_port() {
+    let mut debug_map = DebugMap::new();
+    let mut urids = unsafe { debug_map.create_cached_map() };
+
+    let mut atom_space = vec![0u64; 4];
+    let atom = unsafe { (atom_space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+    *(atom.mut_size()) = 24;
+    let mut out_port = AtomOutputPort::<f32>::new();
+    out_port.connect_port(atom as *mut Atom);
+    unsafe { out_port.write_atom_body(&42.0, &mut urids) }.unwrap();
+
+    let context = lv2rs_core::RunContext::new(1, 0, 44100.0, None);
+    let mut in_port = AtomInputPort::<f32>::new();
+    match unsafe { in_port.read(&context, &mut urids) } {
+        Err(GetAtomError::NullPointer) => (),
+        _ => panic!("an unconnected port was read"),
+    }
+    in_port.connect_port(atom as *const Atom);
+    assert_eq!(
+        42.0,
+        *unsafe { in_port.read(&context, &mut urids) }.unwrap()
+    );
+    let mut int_port = AtomInputPort::<i32>::new();
+    int_port.connect_port(atom as *const Atom);
+    match unsafe { int_port.read(&context, &mut urids) } {
+        Err(GetAtomError::GetBody(_)) => (),
+        _ => panic!("a float was read as an int"),
+    }
+}
+
+#[derive(Ports)]
+struct ChannelPorts {
+    #[port(buffer_type = "Sequence", supports = "Float")]
+    inputs: AtomInputPortArray<f32, 3>,
+    outputs: AtomOutputPortArray<f32, 2>,
+}
+
+#[test]
+fn test_port_arrays() {
+    let mut debug_map = DebugMap::new();
+    let mut urids = unsafe { debug_map.create_cached_map() };
+
+    let mut atom_space = vec![0u64; 4];
+    let atom = unsafe { (atom_space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+    *(atom.mut_size()) = 24;
+    let atom = atom as *mut Atom as *mut ();
+
+    let mut ports = ChannelPorts {
+        inputs: PortArray::from_fn(|_| AtomInputPort::new()),
+        outputs: PortArray::from_fn(|_| AtomOutputPort::new()),
+    };
+    assert_eq!(5, ChannelPorts::PORT_COUNT);
+    assert_eq!(3, ChannelPorts::OUTPUTS);
+    assert_eq!(3, ports.inputs.len());
+    let symbols: Vec<&str> = ChannelPorts::PORTS.iter().map(|info| info.symbol).collect();
+    assert_eq!(
+        vec!["inputs_0", "inputs_1", "inputs_2", "outputs_0", "outputs_1"],
+        symbols
+    );
+    assert_eq!(4, ChannelPorts::PORTS[4].index);
+    assert_eq!(PortKind::AtomOutput, ChannelPorts::PORTS[4].kind);
+    assert_eq!(Some("Sequence"), ChannelPorts::PORTS[2].buffer_type);
+    assert_eq!(None, ChannelPorts::PORTS[3].buffer_type);
+
+    assert!(ports.connect_port(1, atom));
+    assert!(ports.connect_port(4, atom));
+    assert!(!ports.connect_port(5, atom));
+    unsafe { ports.outputs[1].write_atom_body(&42.0f32, &mut urids) }.unwrap();
+
+    let context = lv2rs_core::RunContext::new(1, 0, 44100.0, None);
+    let values: Vec<Option<f32>> = ports
+        .inputs
+        .iter()
+        .map(|port| unsafe { port.read(&context, &mut urids) }.ok().cloned())
+        .collect();
+    assert_eq!(vec![None, Some(42.0), None], values);
+}
@@ -0,0 +1,343 @@
+//! Derive macros for [`lv2rs-core`](https://docs.rs/lv2rs-core).
+//!
+//! This crate is re-exported by `lv2rs-core` and should not be used directly.
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{
+    parse_macro_input, Data, DeriveInput, Expr, ExprLit, Field, Fields, GenericArgument, Ident,
+    Lit, LitStr, Path, PathArguments, Type,
+};
+
+/// Derive `PortCollection` for a struct of ports.
+///
+/// See the documentation of `lv2rs_core::ports` for more information.
+#[proc_macro_derive(Ports, attributes(ports, port))]
+pub fn derive_ports(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match ports_impl(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn ports_impl(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let mut core_path: Path = syn::parse_quote!(::lv2rs_core);
+    for attribute in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("ports"))
+    {
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("core") {
+                core_path = meta.value()?.parse::<LitStr>()?.parse()?;
+                Ok(())
+            } else {
+                Err(meta.error("expected `core = \"path\"`"))
+            }
+        })?;
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    input,
+                    "Ports can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "Ports can only be derived for structs",
+            ))
+        }
+    };
+
+    let names: Vec<&Ident> = fields
+        .iter()
+        .map(|field| field.ident.as_ref().unwrap())
+        .collect();
+    let constants: Vec<Ident> = names
+        .iter()
+        .map(|name| {
+            let name = name.to_string();
+            let name = name.trim_start_matches("r#").to_uppercase();
+            Ident::new(&name, Span::call_site())
+        })
+        .collect();
+    let docs: Vec<String> = names
+        .iter()
+        .map(|name| format!("The index of the `{}` port.", name))
+        .collect();
+    let lengths = fields
+        .iter()
+        .map(array_length)
+        .collect::<syn::Result<Vec<_>>>()?;
+    let counts: Vec<u32> = lengths.iter().map(|length| length.unwrap_or(1)).collect();
+    let indices: Vec<u32> = counts
+        .iter()
+        .scan(0, |next, count| {
+            let index = *next;
+            *next += count;
+            Some(index)
+        })
+        .collect();
+    let port_count: u32 = counts.iter().sum();
+    let mut infos = Vec::new();
+    for ((field, index), length) in fields.iter().zip(indices.iter()).zip(lengths.iter()) {
+        infos.extend(port_info(field, *index, *length, &core_path)?);
+    }
+
+    // Arrays without ports have neither an index to connect nor metadata to apply.
+    let types: Vec<&Type> = fields.iter().map(|field| &field.ty).collect();
+    let connected = counts.iter().map(|count| *count > 0);
+    let (connected_names, connected_indices): (Vec<&Ident>, Vec<u32>) = names
+        .iter()
+        .zip(indices.iter())
+        .zip(connected)
+        .filter(|(_, connected)| *connected)
+        .map(|((name, index), _)| (*name, *index))
+        .unzip();
+    let connected_ends: Vec<u32> = indices
+        .iter()
+        .zip(counts.iter())
+        .filter(|(_, count)| **count > 0)
+        .map(|(index, count)| index + count)
+        .collect();
+
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #name #type_generics #where_clause {
+            #(
+                #[doc = #docs]
+                pub const #constants: u32 = #indices;
+            )*
+        }
+
+        impl #impl_generics #core_path::ports::PortCollection for #name #type_generics
+        #where_clause
+        {
+            const PORT_COUNT: u32 = #port_count;
+
+            const PORTS: &'static [#core_path::ttl::PortInfo] = &[#(#infos),*];
+
+            fn connect_port(&mut self, port: u32, data: *mut ()) -> bool {
+                #(
+                    const {
+                        assert!(
+                            <#types as #core_path::ports::PortHandle>::COUNT == #counts,
+                            "the length of a port array has to be an integer literal",
+                        )
+                    };
+                )*
+                match port {
+                    #(
+                        #connected_indices..#connected_ends => {
+                            #core_path::ports::PortHandle::connect_raw_at(
+                                &mut self.#connected_names,
+                                port - #connected_indices,
+                                data,
+                            )
+                        }
+                    )*
+                    _ => false,
+                }
+            }
+
+            fn apply_metadata(&mut self) {
+                #(
+                    #core_path::ports::PortHandle::apply_metadata(
+                        &mut self.#connected_names,
+                        &<Self as #core_path::ports::PortCollection>::PORTS
+                            [#connected_indices as usize],
+                    );
+                )*
+            }
+        }
+    })
+}
+
+/// Return the length of a port array, which is the last generic argument of the field's type if
+/// it's an integer literal, like in `PortArray<AudioInputPort, 4>`.
+fn array_length(field: &Field) -> syn::Result<Option<u32>> {
+    let segment = match &field.ty {
+        Type::Path(path) => path.path.segments.last(),
+        _ => None,
+    };
+    let last_argument = match segment.map(|segment| &segment.arguments) {
+        Some(PathArguments::AngleBracketed(arguments)) => arguments.args.last(),
+        _ => None,
+    };
+    match last_argument {
+        Some(GenericArgument::Const(Expr::Lit(ExprLit {
+            lit: Lit::Int(length),
+            ..
+        }))) => length.base10_parse().map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// Generate the `PortInfo`s of a field from its `port` attributes.
+///
+/// A port array with the given length generates one `PortInfo` for every port, with numbered
+/// symbols and names.
+fn port_info(
+    field: &Field,
+    index: u32,
+    length: Option<u32>,
+    core_path: &Path,
+) -> syn::Result<Vec<proc_macro2::TokenStream>> {
+    let mut symbol = field
+        .ident
+        .as_ref()
+        .unwrap()
+        .to_string()
+        .trim_start_matches("r#")
+        .to_owned();
+    let mut name: Option<String> = None;
+    let mut default: Option<Expr> = None;
+    let mut minimum: Option<Expr> = None;
+    let mut maximum: Option<Expr> = None;
+    let mut unit: Option<String> = None;
+    let mut buffer_type: Option<String> = None;
+    let mut designation: Option<String> = None;
+    let mut group: Option<String> = None;
+    let mut channel: Option<LitStr> = None;
+    let mut properties: Vec<String> = Vec::new();
+    let mut supports: Vec<String> = Vec::new();
+    let mut scale_point_labels: Vec<LitStr> = Vec::new();
+    let mut scale_point_values: Vec<Expr> = Vec::new();
+
+    for attribute in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("port"))
+    {
+        attribute.parse_nested_meta(|meta| {
+            let string = || -> syn::Result<String> { Ok(meta.value()?.parse::<LitStr>()?.value()) };
+            if meta.path.is_ident("name") {
+                name = Some(string()?);
+            } else if meta.path.is_ident("symbol") {
+                symbol = string()?;
+            } else if meta.path.is_ident("default") {
+                default = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("minimum") {
+                minimum = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("maximum") {
+                maximum = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("unit") {
+                unit = Some(string()?);
+            } else if meta.path.is_ident("buffer_type") {
+                buffer_type = Some(string()?);
+            } else if meta.path.is_ident("designation") {
+                designation = Some(string()?);
+            } else if meta.path.is_ident("group") {
+                group = Some(string()?);
+            } else if meta.path.is_ident("channel") {
+                channel = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("property") {
+                properties.push(string()?);
+            } else if meta.path.is_ident("supports") {
+                supports.push(string()?);
+            } else if meta.path.is_ident("scale_point") {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                scale_point_labels.push(content.parse()?);
+                content.parse::<syn::Token![,]>()?;
+                scale_point_values.push(content.parse()?);
+            } else {
+                return Err(meta.error("unknown port attribute"));
+            }
+            Ok(())
+        })?;
+    }
+
+    let name = name.unwrap_or_else(|| symbol.clone());
+    let optional_value = |value: Option<Expr>| match value {
+        Some(value) => quote!(Some((#value) as f32)),
+        None => quote!(None),
+    };
+    let optional_string = |value: Option<String>| match value {
+        Some(value) => quote!(Some(#value)),
+        None => quote!(None),
+    };
+    let default = optional_value(default);
+    let minimum = optional_value(minimum);
+    let maximum = optional_value(maximum);
+    let unit = optional_string(unit);
+    let buffer_type = optional_string(buffer_type);
+    let ty = &field.ty;
+    if length.is_some() && (designation.is_some() || channel.is_some()) {
+        return Err(syn::Error::new_spanned(
+            ty,
+            "a port array can't have a designation or a channel",
+        ));
+    }
+    let designation = match (designation, channel) {
+        (Some(_), Some(channel)) => {
+            return Err(syn::Error::new(
+                channel.span(),
+                "a port can't have both a designation and a channel",
+            ))
+        }
+        (Some(designation), None) => quote!(Some(#designation)),
+        (None, Some(channel)) => {
+            // Channels are named like the URIs of the port groups extension, in camel case.
+            let mut variant = channel.value();
+            if !variant.starts_with(|c: char| c.is_ascii_lowercase())
+                || !variant.chars().all(|c| c.is_ascii_alphanumeric())
+            {
+                return Err(syn::Error::new(channel.span(), "unknown channel"));
+            }
+            variant[0..1].make_ascii_uppercase();
+            let variant = Ident::new(&variant, channel.span());
+            quote!(Some(#core_path::groups::Channel::#variant.designation()))
+        }
+        (None, None) => quote!(<#ty as #core_path::ports::PortHandle>::DESIGNATION),
+    };
+    let group = optional_string(group);
+
+    let ports: Vec<(u32, String, String)> = match length {
+        Some(length) => (0..length)
+            .map(|number| {
+                let symbol = format!("{}_{}", symbol, number);
+                let name = format!("{} {}", name, number);
+                (index + number, symbol, name)
+            })
+            .collect(),
+        None => vec![(index, symbol, name)],
+    };
+    let infos = ports.iter().map(|(index, symbol, name)| {
+        quote! {
+            #core_path::ttl::PortInfo {
+                index: #index,
+                symbol: #symbol,
+                name: #name,
+                kind: <#ty as #core_path::ports::PortHandle>::KIND,
+                default: #default,
+                minimum: #minimum,
+                maximum: #maximum,
+                unit: #unit,
+                scale_points: &[#(
+                    #core_path::ttl::ScalePoint {
+                        label: #scale_point_labels,
+                        value: (#scale_point_values) as f32,
+                    }
+                ),*],
+                properties: &[#(#properties),*],
+                buffer_type: #buffer_type,
+                supports: &[#(#supports),*],
+                designation: #designation,
+                group: #group,
+            }
+        }
+    });
+    Ok(infos.collect())
+}
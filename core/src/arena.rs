@@ -0,0 +1,278 @@
+//! Memory for real-time data that is reserved when the plugin is instantiated.
+//!
+//! Allocating memory in `run` is not real-time safe, which is why plugins allocate their buffers,
+//! like delay lines or FFT buffers, in advance. However, freshly allocated memory may still cause
+//! page faults when it's touched for the first time, which may also happen in the audio thread.
+//! An [`Arena`](struct.Arena.html) avoids both: It reserves one region of memory, from which
+//! buffers are taken, touches every page of it before any buffer is taken and
+//! [prepares](struct.Arena.html#method.prepare) it by locking it in RAM with `mlock`, where
+//! available.
+//!
+//! Plugins return their arena in [`Plugin::arena`](../trait.Plugin.html#method.arena), which
+//! makes the framework prepare it after the plugin was activated and therefore, before `run` is
+//! called for the first time:
+//!
+//!     extern crate lv2rs_core as lv2core;
+//!
+//!     use lv2core::arena::*;
+//!
+//!     struct Delay {
+//!         arena: Arena,
+//!         line: ArenaBuffer<f32>,
+//!     }
+//!
+//!     impl lv2core::Plugin for Delay {
+//!         fn instantiate(
+//!             context: &lv2core::InstantiationContext,
+//!             _features: Option<&lv2core::FeaturesList>,
+//!         ) -> Result<Self, lv2core::InstantiateError> {
+//!             let length = context.sample_rate() as usize;
+//!             let out_of_memory = || lv2core::InstantiateError::Other("Out of memory".into());
+//!             let mut arena = Arena::size_of::<f32>(length)
+//!                 .and_then(Arena::new)
+//!                 .ok_or_else(out_of_memory)?;
+//!             let line = arena.alloc(length, 0.0).ok_or_else(out_of_memory)?;
+//!             Ok(Self { arena, line })
+//!         }
+//!
+//!         fn arena(&mut self) -> Option<&mut Arena> {
+//!             Some(&mut self.arena)
+//!         }
+//!
+//!         fn connect_port(&mut self, _port: u32, _data: *mut ()) {}
+//!
+//!         fn run(&mut self, _context: &mut lv2core::RunContext) {
+//!             for sample in self.line.iter_mut() {
+//!                 *sample *= 0.5;
+//!             }
+//!         }
+//!     }
+//!
+//! The buffers keep the region alive, so they may outlive the arena.
+use alloc::alloc::{alloc_zeroed, dealloc, Layout};
+use alloc::sync::Arc;
+use core::ops::{Deref, DerefMut};
+use core::ptr::NonNull;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// The alignment of the region and of every buffer, which is enough for SIMD vectors.
+const ALIGNMENT: usize = 64;
+
+/// The page size that is assumed when the region is touched.
+const PAGE_SIZE: usize = 4096;
+
+#[cfg(all(unix, feature = "std"))]
+extern "C" {
+    fn mlock(addr: *const core::ffi::c_void, len: usize) -> core::ffi::c_int;
+    fn munlock(addr: *const core::ffi::c_void, len: usize) -> core::ffi::c_int;
+}
+
+/// The memory of an arena, which is freed when the arena and all buffers are dropped.
+struct Region {
+    data: NonNull<u8>,
+    layout: Layout,
+    locked: AtomicBool,
+}
+
+// The region is only accessed through disjoint buffers.
+unsafe impl Send for Region {}
+unsafe impl Sync for Region {}
+
+impl Region {
+    fn new(size: usize) -> Option<Self> {
+        let layout = Layout::from_size_align(size.max(1), ALIGNMENT).ok()?;
+        let data = NonNull::new(unsafe { alloc_zeroed(layout) })?;
+        // Touch every page while no buffer exists, so that the writes can't race with the
+        // buffers' owners and only overwrite zeroes.
+        for offset in (0..layout.size()).step_by(PAGE_SIZE) {
+            unsafe { data.as_ptr().add(offset).write_volatile(0) };
+        }
+        Some(Self {
+            data,
+            layout,
+            locked: AtomicBool::new(false),
+        })
+    }
+
+    /// Lock the region in RAM and return whether it is locked.
+    fn lock(&self) -> bool {
+        if self.locked.load(Ordering::Acquire) {
+            return true;
+        }
+        #[cfg(all(unix, feature = "std"))]
+        {
+            let data = self.data.as_ptr() as *const core::ffi::c_void;
+            if unsafe { mlock(data, self.layout.size()) } == 0 {
+                self.locked.store(true, Ordering::Release);
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl Drop for Region {
+    fn drop(&mut self) {
+        #[cfg(all(unix, feature = "std"))]
+        {
+            if *self.locked.get_mut() {
+                let data = self.data.as_ptr() as *const core::ffi::c_void;
+                unsafe { munlock(data, self.layout.size()) };
+            }
+        }
+        unsafe { dealloc(self.data.as_ptr(), self.layout) };
+    }
+}
+
+/// A region of memory that buffers for real-time data are taken from.
+///
+/// See the [module documentation](index.html) for more information.
+pub struct Arena {
+    region: Arc<Region>,
+    used: usize,
+    prepared: bool,
+}
+
+impl Arena {
+    /// Reserve a region with the given size in bytes.
+    ///
+    /// `None` is returned if the memory can't be allocated.
+    pub fn new(size: usize) -> Option<Self> {
+        Some(Self {
+            region: Arc::new(Region::new(size)?),
+            used: 0,
+            prepared: false,
+        })
+    }
+
+    /// Return the size an arena needs to hold a buffer of `len` values of type `T`.
+    ///
+    /// Since every buffer is aligned, the sizes of several buffers may be added up to get the
+    /// size of an arena for all of them. `None` is returned if the size overflows.
+    pub fn size_of<T>(len: usize) -> Option<usize> {
+        let size = core::mem::size_of::<T>().checked_mul(len)?;
+        size.checked_next_multiple_of(ALIGNMENT)
+    }
+
+    /// Return the size of the region in bytes.
+    pub fn capacity(&self) -> usize {
+        self.region.layout.size()
+    }
+
+    /// Return the number of bytes that are still free.
+    pub fn remaining(&self) -> usize {
+        self.capacity() - self.used
+    }
+
+    /// Take a buffer of `len` values from the region and fill it with `value`.
+    ///
+    /// `None` is returned if the region doesn't have enough space left, the size of the buffer
+    /// overflows or the alignment of `T` is bigger than the alignment of the arena, which is 64
+    /// bytes.
+    ///
+    /// Taking a buffer doesn't allocate and is therefore real-time safe, but filling it takes
+    /// time proportional to its length.
+    pub fn alloc<T: Copy>(&mut self, len: usize, value: T) -> Option<ArenaBuffer<T>> {
+        if core::mem::align_of::<T>() > ALIGNMENT {
+            return None;
+        }
+        let size = Self::size_of::<T>(len)?;
+        if size > self.remaining() {
+            return None;
+        }
+        let data = unsafe { self.region.data.as_ptr().add(self.used) } as *mut T;
+        for index in 0..len {
+            unsafe { data.add(index).write(value) };
+        }
+        self.used += size;
+        Some(ArenaBuffer {
+            _region: self.region.clone(),
+            data: NonNull::new(data)?,
+            len,
+        })
+    }
+
+    /// Try to lock the region in RAM.
+    ///
+    /// The pages of the region were already touched when it was reserved. After it's locked, they
+    /// can't be swapped out anymore, which means that accessing the buffers doesn't cause page
+    /// faults. Locking the region requires the `std` feature and a Unix system and may fail if the
+    /// memory limit of the process is exceeded, which is why its success is returned.
+    ///
+    /// This method is called by the framework after the plugin was activated.
+    pub fn prepare(&mut self) -> bool {
+        self.prepared = true;
+        self.region.lock()
+    }
+
+    /// Return whether the region was prepared.
+    pub fn is_prepared(&self) -> bool {
+        self.prepared
+    }
+
+    /// Return whether the region is locked in RAM.
+    pub fn is_locked(&self) -> bool {
+        self.region.locked.load(Ordering::Acquire)
+    }
+}
+
+/// A buffer that was taken from an [`Arena`](struct.Arena.html).
+///
+/// It dereferences to a slice of its values.
+pub struct ArenaBuffer<T> {
+    _region: Arc<Region>,
+    data: NonNull<T>,
+    len: usize,
+}
+
+unsafe impl<T: Send> Send for ArenaBuffer<T> {}
+unsafe impl<T: Sync> Sync for ArenaBuffer<T> {}
+
+impl<T> Deref for ArenaBuffer<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        unsafe { core::slice::from_raw_parts(self.data.as_ptr(), self.len) }
+    }
+}
+
+impl<T> DerefMut for ArenaBuffer<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        unsafe { core::slice::from_raw_parts_mut(self.data.as_ptr(), self.len) }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::arena::*;
+
+    #[test]
+    fn test_arena() {
+        assert_eq!(Some(64), Arena::size_of::<f32>(3));
+        assert_eq!(Some(128), Arena::size_of::<f64>(9));
+        assert_eq!(None, Arena::size_of::<u32>(usize::MAX / 4 + 2));
+        assert_eq!(None, Arena::size_of::<u8>(usize::MAX));
+
+        let size = Arena::size_of::<f32>(100).unwrap() + Arena::size_of::<u8>(1).unwrap();
+        let mut arena = Arena::new(size).unwrap();
+        assert_eq!(512, arena.capacity());
+        let mut first = arena.alloc(100, 1.0f32).unwrap();
+        let second = arena.alloc(1, 2u8).unwrap();
+        assert_eq!(0, arena.remaining());
+        assert!(arena.alloc(1, 0u8).is_none());
+        assert!(arena.alloc(usize::MAX / 4 + 2, 7u32).is_none());
+        assert_eq!(0, second.as_ptr() as usize % ALIGNMENT);
+
+        first[99] = 3.0;
+        arena.prepare();
+        assert!(arena.is_prepared());
+        assert_eq!(1.0, first[0]);
+        assert_eq!(3.0, first[99]);
+        assert_eq!([2], *second);
+
+        // The buffers keep the region alive.
+        drop(arena);
+        first[0] = 4.0;
+        assert_eq!(4.0, first[0]);
+    }
+}
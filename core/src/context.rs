@@ -0,0 +1,302 @@
+//! Information about the environment a plugin is instantiated and run in.
+use crate::time::Position;
+use crate::Descriptor;
+use core::ffi::CStr;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::path::PathBuf;
+
+/// The information the host passes to a plugin when it's instantiated.
+///
+/// It contains the descriptor of the plugin, the sample rate of the session and the path of the
+/// bundle the plugin was loaded from. The raw C strings of the host are validated when they're
+/// accessed.
+pub struct InstantiationContext<'a> {
+    descriptor: &'a Descriptor,
+    sample_rate: f64,
+    bundle_path: &'a CStr,
+}
+
+impl<'a> InstantiationContext<'a> {
+    /// Create a new context.
+    ///
+    /// Plugins don't need to call this function, the context is created by
+    /// [`instantiate`](fn.instantiate.html). It's useful to instantiate plugins in tests.
+    pub fn new(descriptor: &'a Descriptor, sample_rate: f64, bundle_path: &'a CStr) -> Self {
+        Self {
+            descriptor,
+            sample_rate,
+            bundle_path,
+        }
+    }
+
+    /// Return the descriptor of the plugin.
+    pub fn descriptor(&self) -> &'a Descriptor {
+        self.descriptor
+    }
+
+    /// Return the sample rate of the session, in Hertz.
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// Return the URI of the plugin, which is stored in its descriptor.
+    ///
+    /// An error is returned if the URI is missing or not valid UTF-8.
+    pub fn plugin_uri(&self) -> Result<&'a str, PluginUriError> {
+        if self.descriptor.uri.is_null() {
+            return Err(PluginUriError::Missing);
+        }
+        let uri = unsafe { CStr::from_ptr(self.descriptor.uri) };
+        uri.to_str().map_err(|_| PluginUriError::NotUtf8)
+    }
+
+    /// Return the bundle path as it was passed by the host.
+    pub fn bundle_path_raw(&self) -> &'a CStr {
+        self.bundle_path
+    }
+
+    /// Return the bundle path as a string.
+    ///
+    /// An error is returned if the path is not valid UTF-8.
+    pub fn bundle_path_str(&self) -> Result<&'a str, BundlePathError> {
+        self.bundle_path
+            .to_str()
+            .map_err(|_| BundlePathError::NotUtf8)
+    }
+
+    /// Return the bundle path as a path of the standard library.
+    ///
+    /// On Unix systems, the path may contain arbitrary bytes. On Windows, hosts pass UTF-8
+    /// strings, which may contain forward slashes or a slash in front of the drive letter; Both
+    /// are handled. On other platforms, like WebAssembly, the path is taken as it is. An error is
+    /// returned if the path is empty or, outside of Unix, not valid UTF-8.
+    #[cfg(feature = "std")]
+    pub fn bundle_path(&self) -> Result<PathBuf, BundlePathError> {
+        if self.bundle_path.to_bytes().is_empty() {
+            return Err(BundlePathError::Empty);
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+            Ok(PathBuf::from(std::ffi::OsStr::from_bytes(
+                self.bundle_path.to_bytes(),
+            )))
+        }
+        #[cfg(windows)]
+        {
+            Ok(PathBuf::from(windows_path(self.bundle_path_str()?)))
+        }
+        #[cfg(not(any(unix, windows)))]
+        {
+            Ok(PathBuf::from(self.bundle_path_str()?))
+        }
+    }
+
+    /// Return the path of a file in the bundle.
+    ///
+    /// An error is returned if the bundle path is invalid, see
+    /// [`bundle_path`](#method.bundle_path).
+    #[cfg(feature = "std")]
+    pub fn bundle_file(&self, name: &str) -> Result<PathBuf, BundlePathError> {
+        Ok(self.bundle_path()?.join(name))
+    }
+}
+
+/// Errors that may occur when reading the plugin URI of an instantiation context.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PluginUriError {
+    /// The descriptor doesn't contain a URI.
+    Missing,
+    /// The URI is not valid UTF-8.
+    NotUtf8,
+}
+
+impl fmt::Display for PluginUriError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PluginUriError::Missing => write!(f, "The descriptor doesn't contain a plugin URI"),
+            PluginUriError::NotUtf8 => write!(f, "The plugin URI is not valid UTF-8"),
+        }
+    }
+}
+
+/// Errors that may occur when reading the bundle path of an instantiation context.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BundlePathError {
+    /// The host passed an empty bundle path.
+    Empty,
+    /// The bundle path is not valid UTF-8.
+    NotUtf8,
+}
+
+impl fmt::Display for BundlePathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BundlePathError::Empty => write!(f, "The bundle path is empty"),
+            BundlePathError::NotUtf8 => write!(f, "The bundle path is not valid UTF-8"),
+        }
+    }
+}
+
+/// The information about the current cycle that is passed to [`run`](trait.Plugin.html#tymethod.run).
+///
+/// Besides the number of samples of the cycle, it contains a frame counter and the transport
+/// position, which are maintained by the framework: The frame counter starts at zero when the
+/// plugin is instantiated and is increased by the number of samples after every cycle. The
+/// position is unknown until the plugin passes one it has received from the host to
+/// [`update_position`](#method.update_position). It's kept between cycles and advanced
+/// after every cycle, which means that it's still valid if the host only sends changes.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct RunContext {
+    n_samples: u32,
+    frame: u64,
+    sample_rate: f64,
+    position: Option<Position>,
+}
+
+impl RunContext {
+    /// Create a new context.
+    ///
+    /// Plugins don't need to call this function, the context is created by
+    /// [`run`](fn.run.html). It's useful to run plugins in tests.
+    pub fn new(n_samples: u32, frame: u64, sample_rate: f64, position: Option<Position>) -> Self {
+        Self {
+            n_samples,
+            frame,
+            sample_rate,
+            position,
+        }
+    }
+
+    /// Return the number of samples of this cycle.
+    ///
+    /// Audio buffers and atom sequences have a length of this number of samples. It may change
+    /// during the life time of the plugin and therefore, you should not store it somewhere.
+    pub fn n_samples(&self) -> u32 {
+        self.n_samples
+    }
+
+    /// Return the number of frames that were processed before this cycle.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Return the sample rate of the session, in Hertz.
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// Return the transport position at the start of this cycle, if it's known.
+    pub fn position(&self) -> Option<&Position> {
+        self.position.as_ref()
+    }
+
+    /// Merge a position the host has sent into the current position.
+    ///
+    /// Hosts send positions at the start of a cycle, which is why the position is treated as the
+    /// position at the start of this cycle.
+    pub fn update_position(&mut self, position: &Position) {
+        self.position
+            .get_or_insert_with(Position::default)
+            .update(position);
+    }
+
+    /// Return the context of the next cycle.
+    pub(crate) fn next(mut self, n_samples: u32) -> Self {
+        if let Some(position) = &mut self.position {
+            position.advance(self.n_samples, self.sample_rate);
+        }
+        self.frame += u64::from(self.n_samples);
+        self.n_samples = n_samples;
+        self
+    }
+}
+
+/// Remove the slash in front of the drive letter of a Windows path, like in `/C:/Plugins/`.
+#[cfg(any(test, all(feature = "std", windows)))]
+fn windows_path(path: &str) -> &str {
+    let bytes = path.as_bytes();
+    if bytes.len() >= 3 && bytes[0] == b'/' && bytes[1].is_ascii_alphabetic() && bytes[2] == b':' {
+        &path[1..]
+    } else {
+        path
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::context::*;
+    use crate::Plugin;
+
+    struct Dummy;
+
+    impl Plugin for Dummy {
+        fn instantiate(
+            _context: &InstantiationContext,
+            _features: Option<&crate::FeaturesList>,
+        ) -> Result<Self, crate::InstantiateError> {
+            Ok(Dummy)
+        }
+
+        fn connect_port(&mut self, _port: u32, _data: *mut ()) {}
+
+        fn run(&mut self, _context: &mut RunContext) {}
+    }
+
+    #[test]
+    fn test_context() {
+        let descriptor = Descriptor::new::<Dummy>(b"http://example.org/Dummy\0");
+        let bundle_path = CStr::from_bytes_with_nul(b"/usr/lib/lv2/dummy.lv2/\0").unwrap();
+        let context = InstantiationContext::new(&descriptor, 48000.0, bundle_path);
+
+        assert_eq!(48000.0, context.sample_rate());
+        assert_eq!(Ok("http://example.org/Dummy"), context.plugin_uri());
+        assert_eq!(Ok("/usr/lib/lv2/dummy.lv2/"), context.bundle_path_str());
+        #[cfg(unix)]
+        assert_eq!(
+            Ok(PathBuf::from("/usr/lib/lv2/dummy.lv2/samples.wav")),
+            context.bundle_file("samples.wav")
+        );
+
+        let invalid = CStr::from_bytes_with_nul(b"/\xff/\0").unwrap();
+        let context = InstantiationContext::new(&descriptor, 48000.0, invalid);
+        assert_eq!(Err(BundlePathError::NotUtf8), context.bundle_path_str());
+        #[cfg(unix)]
+        assert!(context.bundle_path().is_ok());
+
+        let empty = CStr::from_bytes_with_nul(b"\0").unwrap();
+        let context = InstantiationContext::new(&descriptor, 48000.0, empty);
+        assert_eq!(Err(BundlePathError::Empty), context.bundle_path());
+    }
+
+    #[test]
+    fn test_run_context() {
+        let mut context = RunContext::new(100, 0, 100.0, None);
+        assert_eq!(None, context.position());
+        context.update_position(&Position {
+            beats_per_minute: Some(60.0),
+            ..Position::default()
+        });
+        context.update_position(&Position {
+            beat: Some(1.0),
+            speed: Some(1.0),
+            ..Position::default()
+        });
+
+        let context = context.next(50);
+        assert_eq!(50, context.n_samples());
+        assert_eq!(100, context.frame());
+        let position = context.position().unwrap();
+        assert_eq!(Some(2.0), position.beat);
+        assert_eq!(Some(60.0), position.beats_per_minute);
+    }
+
+    #[test]
+    fn test_windows_path() {
+        assert_eq!("C:/Plugins/", windows_path("/C:/Plugins/"));
+        assert_eq!("C:\\Plugins\\", windows_path("C:\\Plugins\\"));
+        assert_eq!("/usr/lib/", windows_path("/usr/lib/"));
+        assert_eq!("/", windows_path("/"));
+    }
+}
@@ -0,0 +1,173 @@
+//! Protection against denormal numbers.
+//!
+//! Denormal numbers are tiny floating point numbers, which are created by decaying signals, for
+//! example in the feedback paths of filters and reverbs. Many CPUs process them much slower than
+//! normal numbers, which may cause drop-outs. Setting the flush-to-zero and denormals-are-zero
+//! flags of the CPU makes it treat them as zero instead.
+//!
+//! Plugins opt in by setting [`Plugin::FLUSH_DENORMALS`](../trait.Plugin.html#associatedconstant.FLUSH_DENORMALS)
+//! to `true`, which makes the framework create a [`DenormalGuard`](struct.DenormalGuard.html)
+//! around every call to `run`. Since the flags belong to the thread, which is owned by the host,
+//! the guard restores them afterwards.
+//!
+//! The flags are supported on x86-64 and on x86 with SSE2, where both flags are set, and on
+//! AArch64, where the flush-to-zero flag covers inputs and outputs. Some of the early CPUs with
+//! SSE, but without SSE2, don't have the denormals-are-zero flag and raise an exception if it's
+//! set, so x86 targets without SSE2 are treated like other architectures, where the guard does
+//! nothing.
+
+#[cfg(any(
+    all(target_arch = "x86", target_feature = "sse2"),
+    target_arch = "x86_64"
+))]
+mod arch {
+    /// The content of the MXCSR register.
+    pub type State = u32;
+
+    pub const SUPPORTED: bool = true;
+
+    pub fn get() -> State {
+        let mut state: State = 0;
+        unsafe {
+            core::arch::asm!(
+                "stmxcsr [{}]",
+                in(reg) &mut state as *mut State,
+                options(nostack, preserves_flags)
+            )
+        };
+        state
+    }
+
+    pub fn set(state: State) {
+        unsafe {
+            core::arch::asm!(
+                "ldmxcsr [{}]",
+                in(reg) &state as *const State,
+                options(nostack, preserves_flags, readonly)
+            )
+        };
+    }
+
+    /// Set the flush-to-zero and the denormals-are-zero bits.
+    pub fn flush_denormals(state: State) -> State {
+        state | 0x8040
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod arch {
+    /// The content of the FPCR register.
+    pub type State = u64;
+
+    pub const SUPPORTED: bool = true;
+
+    pub fn get() -> State {
+        let state: State;
+        unsafe {
+            core::arch::asm!(
+                "mrs {}, fpcr",
+                out(reg) state,
+                options(nomem, nostack, preserves_flags)
+            )
+        };
+        state
+    }
+
+    pub fn set(state: State) {
+        unsafe {
+            core::arch::asm!(
+                "msr fpcr, {}",
+                in(reg) state,
+                options(nomem, nostack, preserves_flags)
+            )
+        };
+    }
+
+    /// Set the flush-to-zero bit.
+    pub fn flush_denormals(state: State) -> State {
+        state | 1 << 24
+    }
+}
+
+#[cfg(not(any(
+    all(target_arch = "x86", target_feature = "sse2"),
+    target_arch = "x86_64",
+    target_arch = "aarch64"
+)))]
+mod arch {
+    pub type State = ();
+
+    pub const SUPPORTED: bool = false;
+
+    pub fn get() -> State {}
+
+    pub fn set(_state: State) {}
+
+    pub fn flush_denormals(state: State) -> State {
+        state
+    }
+}
+
+/// Treat denormal numbers as zero until the guard is dropped.
+///
+/// The guard saves the floating point flags of the current thread when it's created and restores
+/// them when it's dropped, so guards may be nested.
+pub struct DenormalGuard {
+    state: arch::State,
+}
+
+impl DenormalGuard {
+    /// Set the flush-to-zero and denormals-are-zero flags of the current thread.
+    pub fn new() -> Self {
+        let state = arch::get();
+        arch::set(arch::flush_denormals(state));
+        Self { state }
+    }
+
+    /// Return whether denormal numbers are treated as zero on this architecture.
+    pub fn is_supported() -> bool {
+        arch::SUPPORTED
+    }
+}
+
+impl Default for DenormalGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for DenormalGuard {
+    fn drop(&mut self) {
+        arch::set(self.state);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::denormals::*;
+
+    /// Halve a number in a way the compiler can't evaluate in advance.
+    fn halve(value: f32) -> f32 {
+        core::hint::black_box(value) * 0.5
+    }
+
+    #[test]
+    fn test_denormal_guard() {
+        let smallest = f32::MIN_POSITIVE;
+        assert!(halve(smallest).is_subnormal());
+        {
+            let _guard = DenormalGuard::new();
+            if DenormalGuard::is_supported() {
+                assert_eq!(0.0, halve(smallest));
+            }
+            {
+                let _inner = DenormalGuard::new();
+            }
+            // The inner guard restores the flags of the outer guard.
+            if DenormalGuard::is_supported() {
+                assert_eq!(0.0, halve(smallest));
+            }
+        }
+        assert!(halve(smallest).is_subnormal());
+    }
+}
@@ -1,5 +1,6 @@
-use std::ffi::CStr;
-use std::os::raw::*;
+use crate::uris::Uri;
+use core::ffi::CStr;
+use core::ffi::*;
 
 /**
    Feature.
@@ -31,6 +32,14 @@ pub struct Feature {
 pub type FeaturesList = [&'static Feature];
 
 impl Feature {
+    /// Create a new feature.
+    ///
+    /// This is only needed by hosts and test code, since plugins receive their features from the
+    /// host. The URI has to point to a null-terminated string that outlives the feature.
+    pub fn new(uri: *const c_char, data: *mut c_void) -> Self {
+        Self { uri, data }
+    }
+
     /// Try to get the URI of the feature.
     ///
     /// None if the URI is pointing to null.
@@ -75,38 +84,187 @@ impl Feature {
     }
 }
 
+/// Data of a feature with a known URI.
+///
+/// Implementing this trait connects a type to the URI of the feature that provides it. This is
+/// used by [`Features::get`](struct.Features.html#method.get) to look up and cast features
+/// without naming the URI every time.
+///
+/// # Safety
+///
+/// The data of the feature with the given URI is cast to the implementing type. It's your
+/// responsibility to ensure that the type has the layout of the data defined by the feature's
+/// specification.
+pub unsafe trait FeatureData {
+    /// The URI of the feature.
+    const URI: Uri;
+}
+
+/// The features supported by the host.
+///
+/// This is a thin wrapper around a [`FeaturesList`](type.FeaturesList.html) that provides
+/// typed access to the features. Features defined by other crates, like the URID map, can be
+/// retrieved with [`get`](#method.get), which is usually wrapped by a safe constructor of the
+/// feature's crate, like `CachedMap::try_from_features`.
+#[derive(Clone, Copy)]
+pub struct Features<'a> {
+    list: &'a FeaturesList,
+}
+
+impl<'a> Features<'a> {
+    /// Wrap a list of features.
+    pub fn new(list: &'a FeaturesList) -> Self {
+        Self { list }
+    }
+
+    /// Read the null-terminated feature array passed by the host.
+    ///
+    /// `None` is returned if the array pointer itself is null.
+    ///
+    /// # Safety
+    ///
+    /// The array can not be checked: It has to be terminated by a null pointer and all features in
+    /// it have to live as long as the plugin instance.
+    pub unsafe fn from_raw(features: *const *const Feature) -> Option<Features<'static>> {
+        if features.is_null() {
+            return None;
+        }
+        let mut length = 0;
+        while !(*features.add(length)).is_null() {
+            length += 1;
+        }
+        Some(Features {
+            list: core::slice::from_raw_parts(features as *const &'static Feature, length),
+        })
+    }
+
+    /// Return the wrapped list of features.
+    pub fn as_list(&self) -> &'a FeaturesList {
+        self.list
+    }
+
+    /// Iterate over all features.
+    pub fn iter(&self) -> impl Iterator<Item = &'a Feature> {
+        self.list.iter().cloned()
+    }
+
+    /// Return the number of features.
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Return `true` if the host doesn't support any features.
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Return `true` if the host supports the feature with the given URI.
+    ///
+    /// This is useful for features without data, like `lv2:isLive`.
+    pub fn contains(&self, uri: &CStr) -> bool {
+        self.iter().any(|feature| feature.uri() == Some(uri))
+    }
+
+    /// Return the first of the given feature URIs that is not supported by the host.
+    ///
+    /// Plugins can use this to check for all required features at once and refuse to instantiate
+    /// if one is missing.
+    pub fn first_missing<'b>(&self, required: &[&'b CStr]) -> Option<&'b CStr> {
+        required.iter().find(|uri| !self.contains(uri)).cloned()
+    }
+
+    /// Try to find the data of a feature by its URI.
+    ///
+    /// # Safety
+    ///
+    /// The data of the feature is cast to `T`, which has to be the type defined by the feature's
+    /// specification. Additionally, the requirements of [`get`](#method.get) apply.
+    pub unsafe fn get_by_uri<T>(&self, uri: &CStr) -> Option<&'static mut T> {
+        Feature::get_feature(self.list, uri)
+    }
+
+    /// Try to find the data of a feature by its type.
+    ///
+    /// `None` is returned if the host doesn't support the feature or its data pointer is null.
+    ///
+    /// # Safety
+    ///
+    /// The lifetime of the returned reference is not bound to the list: The data of the feature
+    /// has to be valid for as long as the reference is used, which is the case for the features
+    /// the host passes to `instantiate`. Since every call creates a new mutable reference to the
+    /// same data, the caller also has to ensure that these references are not used at the same
+    /// time.
+    pub unsafe fn get<T: FeatureData>(&self) -> Option<&'static mut T> {
+        self.get_by_uri(T::URI.as_cstr())
+    }
+}
+
+impl<'a> From<&'a FeaturesList> for Features<'a> {
+    fn from(list: &'a FeaturesList) -> Self {
+        Self::new(list)
+    }
+}
+
 #[cfg(test)]
 #[test]
 fn test_map_features() {
-    const FEATURE_0_URI: &[u8] = b"http://example.org/Feature0\0";
+    const FEATURE_0_URI: Uri = Uri::new(b"http://example.org/Feature0\0");
     const FEATURE_0_DATA: f64 = 42.0;
     const FEATURE_0: Feature = Feature {
-        uri: FEATURE_0_URI.as_ptr() as *const c_char,
+        uri: FEATURE_0_URI.as_ptr(),
         data: &FEATURE_0_DATA as *const f64 as *mut f64 as *mut c_void,
     };
 
-    const FEATURE_1_URI: &[u8] = b"http://example.org/Feature1\0";
+    const FEATURE_1_URI: Uri = Uri::new(b"http://example.org/Feature1\0");
     const FEATURE_1_DATA: f64 = 17.0;
     const FEATURE_1: Feature = Feature {
-        uri: FEATURE_1_URI.as_ptr() as *const c_char,
+        uri: FEATURE_1_URI.as_ptr(),
         data: &FEATURE_1_DATA as *const f64 as *mut f64 as *mut c_void,
     };
 
     const FEATURES: [&Feature; 2] = [&FEATURE_0, &FEATURE_1];
 
     unsafe {
-        let feature_0_data = Feature::get_feature::<f64>(
-            &FEATURES,
-            CStr::from_bytes_with_nul(FEATURE_0_URI).unwrap(),
-        )
-        .unwrap();
+        let feature_0_data =
+            Feature::get_feature::<f64>(&FEATURES, FEATURE_0_URI.as_cstr()).unwrap();
         assert_eq!(42.0, *feature_0_data);
 
-        let feature_1_data = Feature::get_feature::<f64>(
-            &FEATURES,
-            CStr::from_bytes_with_nul(FEATURE_1_URI).unwrap(),
-        )
-        .unwrap();
+        let feature_1_data =
+            Feature::get_feature::<f64>(&FEATURES, FEATURE_1_URI.as_cstr()).unwrap();
         assert_eq!(17.0, *feature_1_data);
     }
 }
+
+#[cfg(test)]
+#[test]
+fn test_features() {
+    const FEATURE_0_URI: Uri = Uri::new(b"http://example.org/Feature0\0");
+    const FEATURE_1_URI: Uri = Uri::new(b"http://example.org/Feature1\0");
+    const MISSING_URI: Uri = Uri::new(b"http://example.org/Missing\0");
+
+    struct Feature1Data(f64);
+    unsafe impl FeatureData for Feature1Data {
+        const URI: Uri = FEATURE_1_URI;
+    }
+
+    let mut data = Feature1Data(17.0);
+    let feature_0 = Feature::new(FEATURE_0_URI.as_ptr(), core::ptr::null_mut());
+    let feature_1 = Feature::new(
+        FEATURE_1_URI.as_ptr(),
+        &mut data as *mut Feature1Data as *mut c_void,
+    );
+    let raw: [*const Feature; 3] = [&feature_0, &feature_1, core::ptr::null()];
+
+    let features = unsafe { Features::from_raw(raw.as_ptr()) }.unwrap();
+    assert_eq!(2, features.len());
+    let feature_0_uri = FEATURE_0_URI.as_cstr();
+    let missing_uri = MISSING_URI.as_cstr();
+    assert!(features.contains(feature_0_uri));
+    assert_eq!(
+        Some(missing_uri),
+        features.first_missing(&[feature_0_uri, missing_uri])
+    );
+    assert_eq!(17.0, unsafe { features.get::<Feature1Data>() }.unwrap().0);
+
+    assert!(unsafe { Features::from_raw(core::ptr::null()) }.is_none());
+}
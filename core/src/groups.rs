@@ -0,0 +1,363 @@
+//! Port groups and channel layouts.
+//!
+//! Multichannel plugins have one audio port per channel. With the
+//! [port groups extension](http://lv2plug.in/ns/ext/port-groups), the plugin declares which ports
+//! belong together, for example as a stereo input, and which channel every port carries. Hosts
+//! use this information to connect the plugin's ports to their busses.
+//!
+//! A group is declared with a [`GroupInfo`](../ttl/struct.GroupInfo.html) in the plugin's
+//! metadata and its ports name it with the `group` and `channel` attributes of
+//! [`Ports`](../ports/derive.Ports.html). In `run`, the audio ports of a group can be accessed
+//! by channel with [`InputChannels`](struct.InputChannels.html) and
+//! [`OutputChannels`](struct.OutputChannels.html):
+//!
+//!     extern crate lv2rs_core as lv2core;
+//!
+//!     use lv2core::groups::*;
+//!     use lv2core::ports::*;
+//!
+//!     #[derive(Ports)]
+//!     struct BalancePorts {
+//!         #[port(group = "in", channel = "left")]
+//!         in_left: AudioInputPort,
+//!         #[port(group = "in", channel = "right")]
+//!         in_right: AudioInputPort,
+//!         #[port(group = "out", channel = "left")]
+//!         out_left: AudioOutputPort,
+//!         #[port(group = "out", channel = "right")]
+//!         out_right: AudioOutputPort,
+//!     }
+//!
+//!     let mut ports = BalancePorts {
+//!         in_left: AudioInputPort::new(),
+//!         in_right: AudioInputPort::new(),
+//!         out_left: AudioOutputPort::new(),
+//!         out_right: AudioOutputPort::new(),
+//!     };
+//!     let mut buffers = [[1.0f32; 2], [2.0; 2], [0.0; 2], [0.0; 2]];
+//!     for (index, buffer) in buffers.iter_mut().enumerate() {
+//!         ports.connect_port(index as u32, buffer.as_mut_ptr() as *mut ());
+//!     }
+//!
+//!     let inputs = unsafe {
+//!         InputChannels::new(ChannelLayout::Stereo, [&ports.in_left, &ports.in_right], 2)
+//!     }
+//!     .unwrap();
+//!     let mut outputs = unsafe {
+//!         OutputChannels::new(
+//!             ChannelLayout::Stereo,
+//!             [&mut ports.out_left, &mut ports.out_right],
+//!             2,
+//!         )
+//!     }
+//!     .unwrap();
+//!     for (input, output) in inputs.buffers().iter().zip(outputs.buffers_mut()) {
+//!         output.copy_from_slice(input);
+//!     }
+//!     outputs.channel(Channel::Right).unwrap()[0] *= 0.5;
+//!
+//!     assert_eq!([[1.0, 1.0], [1.0, 2.0]], [buffers[2], buffers[3]]);
+//!     assert_eq!(Some(&[2.0f32, 2.0][..]), inputs.channel(Channel::Right));
+//!     assert_eq!(None, inputs.channel(Channel::Center));
+use crate::ports::{AudioInputPort, AudioOutputPort};
+use core::fmt;
+
+/// The maximal number of channels of a [`ChannelLayout`](enum.ChannelLayout.html).
+pub const MAX_CHANNELS: usize = 6;
+
+/// A channel of a port group, which is the role of a port in the group.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Channel {
+    Center,
+    Left,
+    Right,
+    LowFrequencyEffects,
+    RearLeft,
+    RearRight,
+}
+
+impl Channel {
+    /// Return the URI of the channel, which is used as the designation of its port.
+    pub const fn designation(self) -> &'static str {
+        match self {
+            Channel::Center => "http://lv2plug.in/ns/ext/port-groups#center",
+            Channel::Left => "http://lv2plug.in/ns/ext/port-groups#left",
+            Channel::Right => "http://lv2plug.in/ns/ext/port-groups#right",
+            Channel::LowFrequencyEffects => {
+                "http://lv2plug.in/ns/ext/port-groups#lowFrequencyEffects"
+            }
+            Channel::RearLeft => "http://lv2plug.in/ns/ext/port-groups#rearLeft",
+            Channel::RearRight => "http://lv2plug.in/ns/ext/port-groups#rearRight",
+        }
+    }
+}
+
+/// The channels of a port group.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ChannelLayout {
+    Mono,
+    Stereo,
+    /// 5.1 surround sound, with the channels in the order left, right, center, low frequency
+    /// effects, rear left and rear right.
+    FiveDotOne,
+}
+
+impl ChannelLayout {
+    /// Return the channels of the layout in the order of their ports.
+    pub const fn channels(self) -> &'static [Channel] {
+        match self {
+            ChannelLayout::Mono => &[Channel::Center],
+            ChannelLayout::Stereo => &[Channel::Left, Channel::Right],
+            ChannelLayout::FiveDotOne => &[
+                Channel::Left,
+                Channel::Right,
+                Channel::Center,
+                Channel::LowFrequencyEffects,
+                Channel::RearLeft,
+                Channel::RearRight,
+            ],
+        }
+    }
+
+    /// Return the number of channels.
+    pub const fn channel_count(self) -> usize {
+        self.channels().len()
+    }
+
+    /// Return the position of a channel in the layout, if the layout contains it.
+    pub fn position(self, channel: Channel) -> Option<usize> {
+        self.channels().iter().position(|other| *other == channel)
+    }
+
+    /// Return the URI of the group class for the layout.
+    pub const fn group_class(self) -> &'static str {
+        match self {
+            ChannelLayout::Mono => "http://lv2plug.in/ns/ext/port-groups#MonoGroup",
+            ChannelLayout::Stereo => "http://lv2plug.in/ns/ext/port-groups#StereoGroup",
+            ChannelLayout::FiveDotOne => "http://lv2plug.in/ns/ext/port-groups#FiveDotOneGroup",
+        }
+    }
+}
+
+/// The direction of a port group.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GroupKind {
+    Input,
+    Output,
+}
+
+/// Errors that may occur when the channels of a port group are accessed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ChannelsError {
+    /// The number of ports doesn't match the channel count of the layout.
+    ChannelCount,
+    /// The port at the given index can't create a slice, because it isn't connected or the sample
+    /// count exceeds its maximal block length.
+    Unavailable(usize),
+}
+
+impl fmt::Display for ChannelsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ChannelsError::ChannelCount => {
+                write!(f, "The number of ports doesn't match the channel layout")
+            }
+            ChannelsError::Unavailable(index) => write!(f, "Port {} is not available", index),
+        }
+    }
+}
+
+/// The audio input buffers of a port group, accessible by channel.
+///
+/// This is a view for a single `run` call, since the buffers may change between calls.
+pub struct InputChannels<'a> {
+    layout: ChannelLayout,
+    buffers: [&'a [f32]; MAX_CHANNELS],
+}
+
+impl<'a> InputChannels<'a> {
+    /// Create the view from the ports of the group, in the order of the layout's channels.
+    ///
+    /// An error is returned if the number of ports doesn't match the layout or one of the ports
+    /// can't create a slice, because it isn't connected or the sample count exceeds its maximal
+    /// block length.
+    ///
+    /// # Safety
+    ///
+    /// The ports have to be connected to buffers that hold at least `n_samples` samples and stay
+    /// valid for `'a`. This is the case for the sample count the plugin's `run` function receives
+    /// from the host, see
+    /// [`AudioInputPort::as_slice`](../ports/struct.AudioInputPort.html#method.as_slice).
+    pub unsafe fn new<I>(
+        layout: ChannelLayout,
+        ports: I,
+        n_samples: u32,
+    ) -> Result<Self, ChannelsError>
+    where
+        I: IntoIterator<Item = &'a AudioInputPort>,
+    {
+        let mut buffers: [&'a [f32]; MAX_CHANNELS] = Default::default();
+        let mut count = 0;
+        for port in ports {
+            let buffer = buffers.get_mut(count).ok_or(ChannelsError::ChannelCount)?;
+            *buffer = port
+                .as_slice(n_samples)
+                .ok_or(ChannelsError::Unavailable(count))?;
+            count += 1;
+        }
+        if count == layout.channel_count() {
+            Ok(Self { layout, buffers })
+        } else {
+            Err(ChannelsError::ChannelCount)
+        }
+    }
+
+    /// Return the layout of the group.
+    pub fn layout(&self) -> ChannelLayout {
+        self.layout
+    }
+
+    /// Return the buffer of a channel, if the layout contains it.
+    pub fn channel(&self, channel: Channel) -> Option<&'a [f32]> {
+        self.layout
+            .position(channel)
+            .map(|position| self.buffers[position])
+    }
+
+    /// Return the buffers in the order of the layout's channels.
+    pub fn buffers(&self) -> &[&'a [f32]] {
+        &self.buffers[..self.layout.channel_count()]
+    }
+}
+
+/// The audio output buffers of a port group, accessible by channel.
+///
+/// This is a view for a single `run` call, since the buffers may change between calls.
+pub struct OutputChannels<'a> {
+    layout: ChannelLayout,
+    buffers: [&'a mut [f32]; MAX_CHANNELS],
+}
+
+impl<'a> OutputChannels<'a> {
+    /// Create the view from the ports of the group, in the order of the layout's channels.
+    ///
+    /// An error is returned if the number of ports doesn't match the layout or one of the ports
+    /// can't create a slice, because it isn't connected or the sample count exceeds its maximal
+    /// block length.
+    ///
+    /// # Safety
+    ///
+    /// The ports have to be connected to buffers that hold at least `n_samples` samples and stay
+    /// valid for `'a`. This is the case for the sample count the plugin's `run` function receives
+    /// from the host, see
+    /// [`AudioOutputPort::as_slice`](../ports/struct.AudioOutputPort.html#method.as_slice).
+    pub unsafe fn new<I>(
+        layout: ChannelLayout,
+        ports: I,
+        n_samples: u32,
+    ) -> Result<Self, ChannelsError>
+    where
+        I: IntoIterator<Item = &'a mut AudioOutputPort>,
+    {
+        let mut buffers: [&'a mut [f32]; MAX_CHANNELS] = Default::default();
+        let mut count = 0;
+        for port in ports {
+            let buffer = buffers.get_mut(count).ok_or(ChannelsError::ChannelCount)?;
+            *buffer = port
+                .as_slice(n_samples)
+                .ok_or(ChannelsError::Unavailable(count))?;
+            count += 1;
+        }
+        if count == layout.channel_count() {
+            Ok(Self { layout, buffers })
+        } else {
+            Err(ChannelsError::ChannelCount)
+        }
+    }
+
+    /// Return the layout of the group.
+    pub fn layout(&self) -> ChannelLayout {
+        self.layout
+    }
+
+    /// Return the buffer of a channel, if the layout contains it.
+    pub fn channel(&mut self, channel: Channel) -> Option<&mut [f32]> {
+        let position = self.layout.position(channel)?;
+        Some(&mut *self.buffers[position])
+    }
+
+    /// Return the buffers in the order of the layout's channels.
+    pub fn buffers_mut(&mut self) -> &mut [&'a mut [f32]] {
+        &mut self.buffers[..self.layout.channel_count()]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::groups::*;
+
+    #[test]
+    fn test_layouts() {
+        assert_eq!(1, ChannelLayout::Mono.channel_count());
+        assert_eq!(2, ChannelLayout::Stereo.channel_count());
+        assert_eq!(MAX_CHANNELS, ChannelLayout::FiveDotOne.channel_count());
+        assert_eq!(
+            Some(3),
+            ChannelLayout::FiveDotOne.position(Channel::LowFrequencyEffects)
+        );
+        assert_eq!(None, ChannelLayout::Stereo.position(Channel::Center));
+        assert_eq!(
+            "http://lv2plug.in/ns/ext/port-groups#rearLeft",
+            Channel::RearLeft.designation()
+        );
+    }
+
+    #[test]
+    fn test_channels() {
+        let samples = [0.5f32; 4];
+        let mut left = AudioInputPort::new();
+        left.connect(samples.as_ptr());
+        let mut right = AudioInputPort::new();
+
+        // The right channel isn't connected.
+        assert_eq!(
+            Some(ChannelsError::Unavailable(1)),
+            unsafe { InputChannels::new(ChannelLayout::Stereo, [&left, &right], 4) }.err()
+        );
+        right.connect(samples.as_ptr());
+        // Too few or too many ports.
+        assert_eq!(
+            Some(ChannelsError::ChannelCount),
+            unsafe { InputChannels::new(ChannelLayout::Stereo, [&left], 4) }.err()
+        );
+        assert_eq!(
+            Some(ChannelsError::ChannelCount),
+            unsafe { InputChannels::new(ChannelLayout::Mono, [&left, &right], 4) }.err()
+        );
+
+        let channels =
+            unsafe { InputChannels::new(ChannelLayout::Stereo, [&left, &right], 4) }.unwrap();
+        assert_eq!(ChannelLayout::Stereo, channels.layout());
+        assert_eq!(2, channels.buffers().len());
+        assert_eq!(Some(&samples[..]), channels.channel(Channel::Left));
+
+        let mut buffer = [0.0f32; 4];
+        let mut output = AudioOutputPort::new();
+        output.connect(buffer.as_mut_ptr());
+        output.set_max_block_length(Some(2));
+        assert_eq!(
+            Some(ChannelsError::Unavailable(0)),
+            unsafe { OutputChannels::new(ChannelLayout::Mono, [&mut output], 4) }.err()
+        );
+        {
+            let mut channels =
+                unsafe { OutputChannels::new(ChannelLayout::Mono, [&mut output], 2) }.unwrap();
+            channels
+                .channel(Channel::Center)
+                .unwrap()
+                .copy_from_slice(&[1.0, 2.0]);
+            assert!(channels.channel(Channel::Left).is_none());
+        }
+        assert_eq!([1.0, 2.0, 0.0, 0.0], buffer);
+    }
+}
@@ -4,12 +4,34 @@
 //! 
 //! This is a frozen prototype and therefore, development of this crate will not continue here. Further
 //! development continues as [rust-lv2](https://github.com/rust-dsp/rust-lv2).
+//!
+//! Without the `std` feature, this crate does not depend on the standard library, only on `core`
+//! and `alloc`. The `std` feature is enabled by default and required by the
+//! [`rt_check`](rt_check/index.html) module, which is enabled by the `rt-check` feature, by the
+//! `catch-panics` feature, which is enabled by default and keeps panics of plugins from unwinding
+//! into the host, by the conversion of the bundle path to a `PathBuf` and by locking the memory
+//! of an [`Arena`](arena/struct.Arena.html).
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
+extern crate alloc;
+
+pub mod arena;
+mod context;
+pub mod denormals;
 mod feature;
+pub mod groups;
 mod plugin;
 pub mod ports;
+pub mod raw;
+#[cfg(feature = "rt-check")]
+pub mod rt_check;
+pub mod time;
+pub mod ttl;
+pub mod units;
+mod unwind;
 pub mod uris;
 
-pub use feature::{Feature, FeaturesList};
+pub use context::{BundlePathError, InstantiationContext, PluginUriError, RunContext};
+pub use feature::{Feature, FeatureData, Features, FeaturesList};
 pub use plugin::*;
 
 /// Create lv2 export functions.
@@ -32,82 +54,213 @@ pub use plugin::*;
 ///
 ///       impl lv2core::Plugin for MyPlugin {
 ///           fn instantiate(
-///               _descriptor: &lv2core::Descriptor,
-///               _rate: f64,
-///               _bundle_path: &CStr,
+///               _context: &lv2core::InstantiationContext,
 ///               _features: Option<&lv2core::FeaturesList>
-///           ) -> Option<Self> {
-///               Some(Self {})
+///           ) -> Result<Self, lv2core::InstantiateError> {
+///               Ok(Self {})
 ///           }
 ///
 ///           fn connect_port(&mut self, _port: u32, _data: *mut ()) {}
 ///
-///           fn run(&mut self, _n_samples: u32) {}
+///           fn run(&mut self, _context: &mut lv2core::RunContext) {}
 ///       }
 ///
 ///       lv2core::lv2_main!(lv2core, MyPlugin, b"http://example.org/Dummy\0");
 ///
+/// Libraries that contain several plugins should use the
+/// [`lv2_descriptors`](macro.lv2_descriptors.html) macro instead.
 #[macro_export]
 macro_rules! lv2_main {
     ($c:ident, $s:ty, $u:expr) => {
-        const PLUGIN_URI: &'static [u8] = $u;
-        const PLUGIN_DESCRIPTOR: $c::Descriptor = $c::Descriptor {
-            uri: PLUGIN_URI.as_ptr() as *const std::os::raw::c_char,
-            instantiate: instantiate,
-            connect_port: connect_port,
-            activate: activate,
-            run: run,
-            deactivate: deactivate,
-            cleanup: cleanup,
-            extension_data: extension_data,
-        };
-
-        unsafe extern "C" fn instantiate(
-            descriptor: *const $c::Descriptor,
-            rate: f64,
-            bundle_path: *const std::os::raw::c_char,
-            features: *const *const $c::Feature,
-        ) -> $c::Handle {
-            $c::instantiate::<$s>(descriptor, rate, bundle_path, features)
-        }
-
-        unsafe extern "C" fn connect_port(
-            instance: $c::Handle,
-            port: u32,
-            data: *mut std::os::raw::c_void,
-        ) {
-            $c::connect_port::<$s>(instance, port, data);
-        }
+        $crate::lv2_descriptors!($c, [$s: $u]);
+    };
+}
 
-        unsafe extern "C" fn activate(instance: $c::Handle) {
-            $c::activate::<$s>(instance);
+/// Create lv2 export functions for several plugins.
+///
+/// This macro works like [`lv2_main`](macro.lv2_main.html), but takes a list of plugin types and
+/// their URIs: The exported `lv2_descriptor` function returns the descriptors of the plugins in
+/// the order of the list. Optionally, the list may be followed by `lib`, which additionally exports
+/// the `lv2_lib_descriptor` function that is used by plugin suites.
+///
+///     extern crate lv2rs_core as lv2core;
+///     use std::ffi::CStr;
+///
+///     struct Amp {}
+///     struct Delay {}
+///
+///     # macro_rules! impl_plugin {
+///     #     ($p:ty) => {
+///     #         impl lv2core::Plugin for $p {
+///     #             fn instantiate(
+///     #                 _context: &lv2core::InstantiationContext,
+///     #                 _features: Option<&lv2core::FeaturesList>
+///     #             ) -> Result<Self, lv2core::InstantiateError> {
+///     #                 Ok(Self {})
+///     #             }
+///     #
+///     #             fn connect_port(&mut self, _port: u32, _data: *mut ()) {}
+///     #
+///     #             fn run(&mut self, _context: &mut lv2core::RunContext) {}
+///     #         }
+///     #     };
+///     # }
+///     # impl_plugin!(Amp);
+///     # impl_plugin!(Delay);
+///     // Implementing `lv2core::Plugin` for `Amp` and `Delay`...
+///
+///     lv2core::lv2_descriptors!(lv2core, [
+///         Amp: b"http://example.org/Amp\0",
+///         Delay: b"http://example.org/Delay\0",
+///     ], lib);
+///
+///     let descriptor = unsafe { lv2_descriptor(1).as_ref() }.unwrap();
+///     let uri = unsafe { CStr::from_ptr(descriptor.uri) };
+///     assert_eq!(b"http://example.org/Delay", uri.to_bytes());
+///     assert!(unsafe { lv2_descriptor(2) }.is_null());
+///
+///     let lib = unsafe { lv2_lib_descriptor(std::ptr::null(), std::ptr::null()).as_ref() }.unwrap();
+///     assert_eq!(unsafe { lv2_descriptor(0) }, unsafe { (lib.get_plugin)(lib.handle, 0) });
+#[macro_export]
+macro_rules! lv2_descriptors {
+    ($c:ident, [$($s:ty : $u:expr),* $(,)*]) => {
+        #[no_mangle]
+        pub unsafe extern "C" fn lv2_descriptor(index: u32) -> *const $c::Descriptor {
+            const DESCRIPTORS: &[$c::Descriptor] = &[$($c::Descriptor::new::<$s>($u)),*];
+            match DESCRIPTORS.get(index as usize) {
+                Some(descriptor) => descriptor,
+                None => std::ptr::null(),
+            }
         }
+    };
+    ($c:ident, [$($s:ty : $u:expr),* $(,)*], lib) => {
+        $crate::lv2_descriptors!($c, [$($s: $u),*]);
 
-        unsafe extern "C" fn run(instance: $c::Handle, n_samples: u32) {
-            $c::run::<$s>(instance, n_samples);
+        unsafe extern "C" fn lv2_lib_get_plugin(
+            _handle: $c::Handle,
+            index: u32,
+        ) -> *const $c::Descriptor {
+            lv2_descriptor(index)
         }
 
-        unsafe extern "C" fn deactivate(instance: $c::Handle) {
-            $c::deactivate::<$s>(instance);
+        #[no_mangle]
+        pub unsafe extern "C" fn lv2_lib_descriptor(
+            _bundle_path: *const std::os::raw::c_char,
+            _features: *const *const $c::Feature,
+        ) -> *const $c::LibDescriptor {
+            const LIB_DESCRIPTOR: $c::LibDescriptor = $c::LibDescriptor::new(lv2_lib_get_plugin);
+            &LIB_DESCRIPTOR
         }
+    };
+}
 
-        unsafe extern "C" fn cleanup(instance: $c::Handle) {
-            $c::cleanup::<$s>(instance);
-        }
+/// Create lv2 export functions and the metadata of a plugin suite.
+///
+/// Crates that ship many small plugins register them with this macro instead of
+/// [`lv2_descriptors`](macro.lv2_descriptors.html). It takes the namespace of the `lv2rs-core`
+/// crate, a URI prefix that is shared by all plugins, a Turtle prologue and a list of plugin types
+/// with the rest of their URIs. Just like `lv2_descriptors` with `lib`, it exports
+/// `lv2_descriptor` and `lv2_lib_descriptor`. Additionally, it creates the constant
+/// `PLUGIN_SUITE`, a [`Suite`](ttl/struct.Suite.html) with the URIs of the plugins, which maps
+/// URIs to descriptor indices and generates the combined manifest of the suite.
+///
+///     extern crate lv2rs_core as lv2core;
+///     use lv2core::ttl::PluginInfo;
+///     use std::ffi::CStr;
+///
+///     struct Amp {}
+///     struct Delay {}
+///
+///     # macro_rules! impl_plugin {
+///     #     ($p:ty) => {
+///     #         impl lv2core::Plugin for $p {
+///     #             fn instantiate(
+///     #                 _context: &lv2core::InstantiationContext,
+///     #                 _features: Option<&lv2core::FeaturesList>
+///     #             ) -> Result<Self, lv2core::InstantiateError> {
+///     #                 Ok(Self {})
+///     #             }
+///     #
+///     #             fn connect_port(&mut self, _port: u32, _data: *mut ()) {}
+///     #
+///     #             fn run(&mut self, _context: &mut lv2core::RunContext) {}
+///     #         }
+///     #     };
+///     # }
+///     # impl_plugin!(Amp);
+///     # impl_plugin!(Delay);
+///     // Implementing `lv2core::Plugin` for `Amp` and `Delay`...
+///
+///     lv2core::plugin_suite!(lv2core, "http://example.org/suite#", "", [
+///         Amp: "amp",
+///         Delay: "delay",
+///     ]);
+///
+///     let descriptor = unsafe { lv2_descriptor(1).as_ref() }.unwrap();
+///     let uri = unsafe { CStr::from_ptr(descriptor.uri) };
+///     assert_eq!(b"http://example.org/suite#delay", uri.to_bytes());
+///     assert_eq!(Some(1), PLUGIN_SUITE.index("http://example.org/suite#delay"));
+///
+///     let manifest = PLUGIN_SUITE.manifest("suite.so", &[
+///         PluginInfo::new("http://example.org/suite#delay", "Delay", &[]),
+///         PluginInfo::new("http://example.org/suite#amp", "Amp", &[]),
+///     ]).unwrap();
+///     assert!(manifest.contains("<http://example.org/suite#amp> lv2:binary <suite.so> ."));
+#[macro_export]
+macro_rules! plugin_suite {
+    ($c:ident, $prefix:literal, $prologue:expr, [$($s:ty : $name:literal),* $(,)*]) => {
+        $crate::lv2_descriptors!($c, [$($s: concat!($prefix, $name, "\0").as_bytes()),*], lib);
 
-        unsafe extern "C" fn extension_data(
-            uri: *const std::os::raw::c_char,
-        ) -> *const std::os::raw::c_void {
-            $c::extension_data::<$s>(uri)
-        }
+        /// The plugins of this suite.
+        pub const PLUGIN_SUITE: $c::ttl::Suite =
+            $c::ttl::Suite::new($prologue, &[$(concat!($prefix, $name)),*]);
+    };
+}
 
-        #[no_mangle]
-        pub unsafe extern "C" fn lv2_descriptor(index: u32) -> *const $c::Descriptor {
-            if index == 0 {
-                &PLUGIN_DESCRIPTOR
-            } else {
-                std::ptr::null()
-            }
-        }
+/// Look up the data of an extension interface.
+///
+/// This macro implements [`Plugin::extension_data`](trait.Plugin.html#method.extension_data): It
+/// takes the requested URI and a list of `(URI, data)` entries, where the URIs are
+/// [`Uri`s](uris/struct.Uri.html) and the data are static references to the interface structs of
+/// the extensions, which have to implement [`ExtensionData`](trait.ExtensionData.html). The data
+/// of the entry with the requested URI is returned, or `None` if the plugin doesn't support the
+/// extension.
+///
+///     extern crate lv2rs_core as lv2core;
+///
+///     use lv2core::uris::Uri;
+///     use lv2core::ExtensionData;
+///     use std::ffi::CStr;
+///
+///     const IDLE_INTERFACE_URI: Uri =
+///         Uri::new(b"http://lv2plug.in/ns/extensions/ui#idleInterface\0");
+///
+///     #[repr(C)]
+///     struct IdleInterface {
+///         idle: extern "C" fn() -> i32,
+///     }
+///
+///     impl ExtensionData for IdleInterface {}
+///
+///     extern "C" fn idle() -> i32 {
+///         0
+///     }
+///
+///     static IDLE_INTERFACE: IdleInterface = IdleInterface { idle };
+///
+///     fn extension_data(uri: &CStr) -> Option<&'static dyn ExtensionData> {
+///         lv2core::extension_data!(uri, [(IDLE_INTERFACE_URI, &IDLE_INTERFACE)])
+///     }
+///
+///     assert!(extension_data(&IDLE_INTERFACE_URI).is_some());
+///     let uri = CStr::from_bytes_with_nul(b"http://lv2plug.in/ns/ext/state#interface\0");
+///     assert!(extension_data(uri.unwrap()).is_none());
+#[macro_export]
+macro_rules! extension_data {
+    ($uri:expr, [$(($extension_uri:expr, $data:expr)),* $(,)*]) => {
+        $crate::find_extension(
+            &[$(($extension_uri, $data as &'static dyn $crate::ExtensionData)),*],
+            $uri,
+        )
     };
 }
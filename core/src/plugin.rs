@@ -1,8 +1,18 @@
 //! General Plugin-related traits and functions.
-use crate::{Feature, FeaturesList};
-
-use std::ffi::CStr;
-use std::os::raw::*;
+use crate::arena::Arena;
+use crate::ttl::PluginInfo;
+use crate::unwind::catch;
+use crate::uris::Uri;
+use crate::{Feature, FeatureData, Features, FeaturesList, InstantiationContext, RunContext};
+
+use alloc::boxed::Box;
+use alloc::ffi::CString;
+use alloc::format;
+use alloc::string::String;
+use core::ffi::CStr;
+use core::ffi::*;
+use core::fmt;
+use core::sync::atomic::{AtomicBool, Ordering};
 
 /**
    Plugin Instance Handle.
@@ -194,6 +204,185 @@ pub struct Descriptor {
     pub extension_data: unsafe extern "C" fn(uri: *const c_char) -> *const c_void,
 }
 
+impl Descriptor {
+    /// Create the descriptor of a plugin type.
+    ///
+    /// The URI has to be a null-terminated byte string. The functions of the descriptor call the
+    /// helper functions of this module, like [`instantiate`](fn.instantiate.html), for the plugin
+    /// type. This function is used by the [`lv2_descriptors`](macro.lv2_descriptors.html) macro.
+    pub const fn new<P: Plugin>(uri: &'static [u8]) -> Self {
+        assert!(
+            !uri.is_empty() && uri[uri.len() - 1] == 0,
+            "plugin URIs have to be null-terminated"
+        );
+        Self {
+            uri: uri.as_ptr() as *const c_char,
+            instantiate: raw_instantiate::<P>,
+            connect_port: raw_connect_port::<P>,
+            activate: raw_activate::<P>,
+            run: raw_run::<P>,
+            deactivate: raw_deactivate::<P>,
+            cleanup: raw_cleanup::<P>,
+            extension_data: raw_extension_data::<P>,
+        }
+    }
+
+    /// Return the metadata of the plugin, if it provides it.
+    ///
+    /// The metadata is the [`PluginInfo`](ttl/struct.PluginInfo.html) that is returned by
+    /// [`Plugin::info`](trait.Plugin.html#method.info). It's queried via `extension_data` with
+    /// the [`INTROSPECTION_URI`](uris/constant.INTROSPECTION_URI.html), which other plugins don't
+    /// know. Therefore, `None` is returned for plugins that weren't written with this crate.
+    pub fn describe(&self) -> Option<&'static PluginInfo> {
+        let uri = crate::uris::INTROSPECTION_URI.as_ptr();
+        unsafe { ((self.extension_data)(uri) as *const PluginInfo).as_ref() }
+    }
+}
+
+/**
+   Plugin Library Descriptor.
+
+   This structure provides an alternative entry point for plugin libraries, which is exported as
+   `lv2_lib_descriptor`. Hosts that support it use it instead of `lv2_descriptor`.
+*/
+#[repr(C)]
+pub struct LibDescriptor {
+    /// Opaque library data which must be passed as the first parameter to all the methods of this
+    /// struct.
+    pub handle: Handle,
+
+    /// The total size of this struct. This allows for this struct to be expanded in the future if
+    /// necessary. This MUST be set by the library to `size_of::<LibDescriptor>()`.
+    pub size: u32,
+
+    /// Destroy this library descriptor and free all related resources.
+    pub cleanup: unsafe extern "C" fn(handle: Handle),
+
+    /// Plugin accessor.
+    ///
+    /// Plugins are accessed by index using values from 0 upwards. Out of range indices MUST result
+    /// in this function returning NULL, so the host can enumerate plugins by increasing index
+    /// until NULL is returned.
+    pub get_plugin: unsafe extern "C" fn(handle: Handle, index: u32) -> *const Descriptor,
+}
+
+impl LibDescriptor {
+    /// Create a library descriptor that returns the plugins of the `lv2_descriptor` function.
+    ///
+    /// This function is used by the [`lv2_descriptors`](macro.lv2_descriptors.html) macro.
+    pub const fn new(
+        get_plugin: unsafe extern "C" fn(handle: Handle, index: u32) -> *const Descriptor,
+    ) -> Self {
+        Self {
+            handle: core::ptr::null_mut(),
+            size: core::mem::size_of::<Self>() as u32,
+            cleanup: raw_lib_cleanup,
+            get_plugin,
+        }
+    }
+}
+
+/// The real-time and threading properties of a plugin.
+///
+/// These properties are features of the LV2 core. Hosts use them to decide in which thread a
+/// plugin is run and how its buffers are connected.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Threading {
+    /// The plugin doesn't allocate memory, lock mutexes or block otherwise in `run`
+    /// (`lv2:hardRTCapable`). Work that may block has to be done with the worker extension.
+    pub hard_rt_capable: bool,
+    /// The plugin depends on real time, for example because it streams data from the network, and
+    /// its output must not be cached or delayed (`lv2:isLive`).
+    pub is_live: bool,
+    /// The plugin doesn't work if an input and an output are connected to the same buffer
+    /// (`lv2:inPlaceBroken`).
+    pub in_place_broken: bool,
+}
+
+impl Threading {
+    /// Create a declaration without properties.
+    pub const fn new() -> Self {
+        Self {
+            hard_rt_capable: false,
+            is_live: false,
+            in_place_broken: false,
+        }
+    }
+
+    /// Create a declaration of a hard real-time capable plugin.
+    pub const fn hard_rt_capable() -> Self {
+        Self {
+            hard_rt_capable: true,
+            ..Self::new()
+        }
+    }
+
+    /// Return the URIs of the features the host has to support, in the format of
+    /// [`PluginInfo::required_features`](ttl/struct.PluginInfo.html#structfield.required_features).
+    pub fn required_features(&self) -> impl Iterator<Item = &'static str> {
+        let is_live = if self.is_live { Some("isLive") } else { None };
+        let in_place_broken = if self.in_place_broken {
+            Some("inPlaceBroken")
+        } else {
+            None
+        };
+        is_live.into_iter().chain(in_place_broken)
+    }
+
+    /// Return the URIs of the features the host may support, in the format of
+    /// [`PluginInfo::optional_features`](ttl/struct.PluginInfo.html#structfield.optional_features).
+    pub fn optional_features(&self) -> impl Iterator<Item = &'static str> {
+        if self.hard_rt_capable {
+            Some("hardRTCapable")
+        } else {
+            None
+        }
+        .into_iter()
+    }
+}
+
+impl Default for Threading {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The reason why a plugin couldn't be instantiated.
+///
+/// It's returned by [`Plugin::instantiate`](trait.Plugin.html#tymethod.instantiate) and written to
+/// the log of the host, if it provides one.
+#[derive(Clone, PartialEq, Debug)]
+pub enum InstantiateError {
+    /// The host doesn't support a feature, given by its URI.
+    MissingFeature(Uri),
+    /// The plugin doesn't support the sample rate of the session.
+    BadSampleRate(f64),
+    /// A file, like a sample or an impulse response, couldn't be loaded.
+    ResourceLoadFailed { path: String },
+    /// Any other reason.
+    Other(String),
+}
+
+impl InstantiateError {
+    /// Create the error for a missing feature type.
+    pub fn missing_feature<F: FeatureData>() -> Self {
+        InstantiateError::MissingFeature(F::URI)
+    }
+}
+
+impl fmt::Display for InstantiateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InstantiateError::MissingFeature(uri) => {
+                write!(f, "Missing required feature {}", uri)
+            }
+            InstantiateError::BadSampleRate(rate) => write!(f, "Unsupported sample rate {}", rate),
+            InstantiateError::ResourceLoadFailed { path } => write!(f, "Failed to load {}", path),
+            InstantiateError::Other(message) => f.write_str(message),
+        }
+    }
+}
+
 /// LV2 plugin trait.
 ///
 /// This trait helps you implementing plugins, since it requires you to implement all
@@ -202,22 +391,82 @@ pub struct Descriptor {
 /// Almost every plugin function call from the host will be checked and "safed" before these trait
 /// functions are called. Therefore, all of them are safe.
 pub trait Plugin {
+    /// The real-time and threading properties of the plugin.
+    ///
+    /// They are written to the plugin's Turtle description with
+    /// [`PluginInfo::threading`](ttl/struct.PluginInfo.html#structfield.threading). With the
+    /// `rt-check` feature, every call to [`run`](#tymethod.run) of a plugin that is declared as
+    /// hard real-time capable is checked for real-time safety violations.
+    ///
+    /// The default declares no properties.
+    const THREADING: Threading = Threading::new();
+
+    /// Whether denormal numbers are treated as zero in [`run`](#tymethod.run).
+    ///
+    /// If it's `true`, the flush-to-zero and denormals-are-zero flags of the CPU are set while
+    /// `run` or [`bypass`](#method.bypass) is called and restored afterwards. See the
+    /// [`denormals`](denormals/index.html) module for details.
+    ///
+    /// The default is `false`.
+    const FLUSH_DENORMALS: bool = false;
+
     /// Create a new instance of the plugin.
     ///
-    /// Here, you should instantiate the plugin and supply it with general information. You can look
-    /// at the plugin descriptor, the audio frame rate of the current session, the path from which
-    /// the host has loaded the plugin and an slice of references to the features supported by the
-    /// host. If, for one reason or another, you find yourself in a situation where you can't
-    /// create a plugin instance, you can return `None`.
+    /// Here, you should instantiate the plugin and supply it with general information. The
+    /// [`InstantiationContext`](struct.InstantiationContext.html) contains the plugin descriptor,
+    /// the sample rate of the current session and the path from which the host has loaded the
+    /// plugin. The features are a slice of references to the features supported by the host. If,
+    /// for one reason or another, you find yourself in a situation where you can't create a plugin
+    /// instance, you can return an [`InstantiateError`](enum.InstantiateError.html), which is
+    /// written to the log of the host.
     fn instantiate(
-        descriptor: &Descriptor,
-        rate: f64,
-        bundle_path: &CStr,
+        context: &InstantiationContext,
         features: Option<&FeaturesList>,
-    ) -> Option<Self>
+    ) -> Result<Self, InstantiateError>
     where
         Self: Sized;
 
+    /// The URIs of the features the plugin requires.
+    ///
+    /// The URIs are given like the [`URI`](trait.FeatureData.html#associatedconstant.URI) of a
+    /// feature type. If the host doesn't support one of them, the instantiation fails before
+    /// [`instantiate`](#tymethod.instantiate) is called and an error is printed to the log of the
+    /// host, if it provides one. Therefore, `instantiate` may expect the required features to be
+    /// present.
+    fn required_features() -> &'static [Uri]
+    where
+        Self: Sized,
+    {
+        &[]
+    }
+
+    /// The URIs of the features the plugin uses if the host supports them.
+    ///
+    /// Optional features aren't checked; Together with the
+    /// [required features](#method.required_features), they document which features the plugin
+    /// looks for in `instantiate`.
+    fn optional_features() -> &'static [Uri]
+    where
+        Self: Sized,
+    {
+        &[]
+    }
+
+    /// The metadata of the plugin, like its ports and their ranges.
+    ///
+    /// Plugins that declare their metadata in a [`PluginInfo`](ttl/struct.PluginInfo.html)
+    /// return it here, which makes it available at runtime via
+    /// [`Descriptor::describe`](struct.Descriptor.html#method.describe). Rust hosts that embed
+    /// the plugin can then build generic UIs and routings without parsing its Turtle description.
+    ///
+    /// The default returns `None`.
+    fn info() -> Option<&'static PluginInfo>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
     /// Set internal data pointers.
     ///
     /// This function will be called by the host when the location of a port has changed and the
@@ -232,9 +481,29 @@ pub trait Plugin {
     /// If your plugin can be turned on or off, you should override this function and set the plugin
     /// up for active use.
     ///
+    /// The host calls this function before `run` is called for the first time and after every
+    /// [`deactivate`](#method.deactivate) call, before the plugin is used again. This is the place
+    /// to reset all state that depends on the history of the instance, like filter memories or
+    /// delay lines, and to allocate and initialize scratch buffers: In contrast to `run`, this
+    /// function is not called in the audio thread.
+    ///
+    /// Calls of this function are paired with calls of `deactivate`: If a misbehaving host calls
+    /// `activate` again while the plugin is already active, the call is ignored.
+    ///
     /// The default implementation does nothing.
     fn activate(&mut self) {}
 
+    /// Return the arena the plugin's real-time data is allocated from.
+    ///
+    /// If the plugin returns an [`Arena`](arena/struct.Arena.html), it is
+    /// [prepared](arena/struct.Arena.html#method.prepare) after every call to
+    /// [`activate`](#method.activate) and therefore, before `run` is called for the first time.
+    ///
+    /// The default implementation returns `None`.
+    fn arena(&mut self) -> Option<&mut Arena> {
+        None
+    }
+
     /// Run plugin specific operations.
     ///
     /// This is where the action happens! Here, you should execute the actions that make your plugin
@@ -242,16 +511,35 @@ pub trait Plugin {
     ///
     /// Pointers, which were previously set by the [`connect_port`](#tyfunction.connect_port)
     /// function are guaranteed to be valid now. If they aren't, it's the host's fault, not yours.
-    /// Also, sample arrays or atom sequence will have a length  of `n_samples` elements. This
-    /// number may change during the life time of the plugin and therefore, you should not store
-    /// it somewhere.
-    fn run(&mut self, n_samples: u32);
+    /// Also, sample arrays or atom sequence will have a length of
+    /// [`n_samples`](struct.RunContext.html#method.n_samples) elements. The
+    /// [`RunContext`](struct.RunContext.html) also contains a frame counter and the transport
+    /// position, which are maintained for you.
+    fn run(&mut self, context: &mut RunContext);
+
+    /// Bypass the plugin after it panicked.
+    ///
+    /// With the `catch-panics` feature, which is enabled by default, panics are caught before they
+    /// reach the host, which would otherwise be aborted. The panic is written to the host's log
+    /// and from then on, this function is called instead of [`run`](#tymethod.run), since the
+    /// state of the plugin may be broken. It should silence the outputs or copy the inputs to
+    /// them.
+    ///
+    /// The default implementation does nothing.
+    fn bypass(&mut self, _context: &mut RunContext) {}
 
     /// Deactivate the plugin.
     ///
     /// If your plugin can be turned on or off, you should override this function and destroy the
     /// plugins active state.
     ///
+    /// Deactivation is not the same as pausing: The plugin will be re-initialized by
+    /// [`activate`](#method.activate) before it is run again. However, the state that the host may
+    /// want to save, like the values of parameters, must be kept.
+    ///
+    /// This function is only called if the plugin is active. If the host cleans an active plugin
+    /// up, it is deactivated first.
+    ///
     /// The default implementation does nothing.
     fn deactivate(&mut self) {}
 
@@ -266,6 +554,9 @@ pub trait Plugin {
     /// alive; as stated in the [LV2 header](http://lv2plug.in/doc/html/group__core.html#ae907a7668d6579f099ac08c134b2e634),
     /// the host is not responsible for freeing the returned value. Therefore, the referenced data
     /// need to live for the entirety of the program.
+    ///
+    /// Use the [`extension_data`](macro.extension_data.html) macro to implement this function for
+    /// all extensions your plugin supports.
     fn extension_data(_uri: &CStr) -> Option<&'static ExtensionData> {
         None
     }
@@ -278,6 +569,96 @@ pub trait Plugin {
 /// Apart from that, it has absolutely no meaning.
 pub trait ExtensionData {}
 
+/// Find the data of an extension in a list of extensions.
+///
+/// Every entry of the list contains the URI of an extension and the data that should be returned
+/// for it. This function is used by the [`extension_data`](macro.extension_data.html) macro,
+/// which is the preferred way to implement
+/// [`Plugin::extension_data`](trait.Plugin.html#method.extension_data).
+pub fn find_extension(
+    extensions: &[(Uri, &'static dyn ExtensionData)],
+    uri: &CStr,
+) -> Option<&'static dyn ExtensionData> {
+    extensions
+        .iter()
+        .find(|(extension_uri, _)| *extension_uri == uri)
+        .map(|(_, data)| *data)
+}
+
+/// A plugin instance, as it is referenced by the handle.
+struct Instance<P: Plugin> {
+    plugin: P,
+    active: bool,
+    log: Option<ErrorLog>,
+    panicked: AtomicBool,
+    /// The context of the last cycle.
+    cycle: RunContext,
+}
+
+impl<P: Plugin> Instance<P> {
+    unsafe fn from_handle<'a>(handle: Handle) -> &'a mut Self {
+        (handle as *mut Self).as_mut().unwrap()
+    }
+
+    /// Call a function with the plugin and contain a panic.
+    ///
+    /// If the function panics, the panic is logged, the instance is bypassed from now on and
+    /// `fallback` is returned.
+    fn contain<'a, R>(
+        &'a mut self,
+        context: &str,
+        fallback: R,
+        function: impl FnOnce(&'a mut P) -> R,
+    ) -> R {
+        let Instance {
+            plugin,
+            log,
+            panicked,
+            ..
+        } = self;
+        match catch(move || function(plugin)) {
+            Ok(result) => result,
+            Err(message) => {
+                panicked.store(true, Ordering::Relaxed);
+                if let Some(log) = log {
+                    log.print(&format!("Plugin panicked in {}: {}\n", context, message));
+                }
+                fallback
+            }
+        }
+    }
+}
+
+/// Call a function with the plugin of an instance handle and contain a panic.
+///
+/// The functions of extension interfaces, like the worker or the state interface, use this
+/// function instead of [`get_instance`](fn.get_instance.html) to keep panics from unwinding into
+/// the host: If the function panics and the `catch-panics` feature is enabled, the panic is
+/// written to the host's log, the plugin is [bypassed](trait.Plugin.html#method.bypass) from now
+/// on and `fallback` is returned. The context names the function in the log entry.
+///
+/// This function is unsafe for the same reasons as [`get_instance`](fn.get_instance.html).
+pub unsafe fn guard<'a, P: Plugin + 'a, R>(
+    instance: Handle,
+    context: &str,
+    fallback: R,
+    function: impl FnOnce(&'a mut P) -> R,
+) -> R {
+    Instance::<P>::from_handle(instance).contain(context, fallback, function)
+}
+
+/// Return the plugin of an instance handle.
+///
+/// Extension interfaces, like the state interface, receive the instance handle that was created by
+/// [`instantiate`](fn.instantiate.html) and use this function to retrieve the plugin.
+///
+/// This function is unsafe since the handle has to be created by `instantiate` with the same
+/// plugin type and must not be cleaned up yet. Also, the caller has to make sure that the plugin
+/// isn't borrowed mutably elsewhere, which depends on the threading rules of the extension.
+pub unsafe fn get_instance<'a, P: Plugin>(instance: Handle) -> &'a mut P {
+    &mut Instance::<P>::from_handle(instance).plugin
+}
+
 /// Helper function for the `instantiate` plugin call.
 ///
 /// This function takes the raw parameters provided by the C API and turns them into safe Rust data
@@ -290,46 +671,115 @@ pub unsafe fn instantiate<P: Plugin>(
 ) -> Handle {
     let descriptor = match descriptor.as_ref() {
         Some(desc) => desc,
-        None => return std::ptr::null_mut(),
+        None => return core::ptr::null_mut(),
     };
     let bundle_path = if bundle_path.is_null() {
-        return std::ptr::null_mut();
+        return core::ptr::null_mut();
     } else {
         CStr::from_ptr(bundle_path as *const c_char)
     };
 
-    let features = {
-        if features.is_null() {
-            None
-        } else {
-            let mut length = 0;
-            let mut features_cp = features;
-            loop {
-                match features_cp.as_ref() {
-                    Some(feature) => {
-                        if feature.is_null() {
-                            break;
-                        } else {
-                            length += 1;
-                            features_cp = features_cp.add(1);
-                        }
-                    }
-                    None => return std::ptr::null_mut(),
-                }
-            }
-            Some(std::slice::from_raw_parts(
-                features as *const &'static Feature,
-                length,
-            ))
+    let features = Features::from_raw(features).map(|features| features.as_list());
+
+    let supported = Features::new(features.unwrap_or(&[]));
+    let log = ErrorLog::from_features(supported);
+    let missing = P::required_features()
+        .iter()
+        .find(|uri| !supported.contains(uri.as_cstr()));
+    if let Some(missing) = missing {
+        if let Some(log) = &log {
+            log.print(&format!("{}\n", InstantiateError::MissingFeature(*missing)));
         }
-    };
+        return core::ptr::null_mut();
+    }
 
-    match P::instantiate(descriptor, rate, bundle_path, features) {
-        Some(instance) => {
-            let instance = Box::new(instance);
-            Box::leak(instance) as *const P as Handle
+    let context = InstantiationContext::new(descriptor, rate, bundle_path);
+    match catch(|| P::instantiate(&context, features)) {
+        Ok(Ok(plugin)) => {
+            let instance = Box::new(Instance {
+                plugin,
+                active: false,
+                log,
+                panicked: AtomicBool::new(false),
+                cycle: RunContext::new(0, 0, rate, None),
+            });
+            Box::into_raw(instance) as Handle
+        }
+        Ok(Err(error)) => {
+            if let Some(log) = &log {
+                log.print(&format!("{}\n", error));
+            }
+            core::ptr::null_mut()
+        }
+        Err(message) => {
+            if let Some(log) = &log {
+                log.print(&format!("Plugin panicked in instantiate: {}\n", message));
+            }
+            core::ptr::null_mut()
         }
-        None => std::ptr::null_mut(),
+    }
+}
+
+/// The URI of the log feature.
+const LOG_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/log#log\0");
+
+/// The URI of error log entries.
+const LOG_ERROR_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/log#Error\0");
+
+/// The URI of the URID map feature.
+const URID_MAP_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/urid#map\0");
+
+/// The layout of the log feature, which is wrapped by the `lv2rs-log` crate.
+#[repr(C)]
+struct RawLog {
+    handle: *mut c_void,
+    printf: unsafe extern "C" fn(
+        handle: *mut c_void,
+        entry_type: u32,
+        format: *const c_char,
+        ...
+    ) -> c_int,
+}
+
+/// The layout of the URID map feature, which is wrapped by the `lv2rs-urid` crate.
+#[repr(C)]
+struct RawMap {
+    handle: *mut c_void,
+    map: unsafe extern "C" fn(handle: *mut c_void, uri: *const c_char) -> u32,
+}
+
+/// The log of the host and the URID of error entries.
+///
+/// Log entries are typed by URIDs, so the log can only be used if the host supports both the log
+/// and the URID map feature.
+#[derive(Clone, Copy)]
+struct ErrorLog {
+    log: &'static RawLog,
+    error: u32,
+}
+
+impl ErrorLog {
+    unsafe fn from_features(features: Features) -> Option<Self> {
+        let log = features.get_by_uri::<RawLog>(LOG_URI.as_cstr())?;
+        let map = features.get_by_uri::<RawMap>(URID_MAP_URI.as_cstr())?;
+        let error = (map.map)(map.handle, LOG_ERROR_URI.as_ptr());
+        Some(Self { log, error })
+    }
+
+    /// Print an error message.
+    fn print(&self, message: &str) {
+        let message = match CString::new(message) {
+            Ok(message) => message,
+            Err(_) => return,
+        };
+        unsafe {
+            (self.log.printf)(
+                self.log.handle,
+                self.error,
+                b"%s\0".as_ptr() as *const c_char,
+                message.as_ptr(),
+            )
+        };
     }
 }
 
@@ -338,8 +788,9 @@ pub unsafe fn instantiate<P: Plugin>(
 /// This function takes the raw parameters provided by the C API and turns them into safe Rust data
 /// types. Only functions generated by the `lv2_main` should call the function any other should not.
 pub unsafe fn connect_port<P: Plugin>(instance: Handle, port: u32, data: *mut c_void) {
-    let instance = (instance as *mut P).as_mut().unwrap();
-    instance.connect_port(port, data as *mut ());
+    Instance::<P>::from_handle(instance).contain("connect_port", (), |plugin| {
+        plugin.connect_port(port, data as *mut ())
+    });
 }
 
 /// Helper function for the `activate` plugin call.
@@ -348,8 +799,16 @@ pub unsafe fn connect_port<P: Plugin>(instance: Handle, port: u32, data: *mut c_
 /// types, and calls the trait's function. Only functions generated by the `lv2_main` should call
 /// this function, any other must not.
 pub unsafe fn activate<P: Plugin>(instance: Handle) {
-    let instance = (instance as *mut P).as_mut().unwrap();
-    instance.activate();
+    let instance = Instance::<P>::from_handle(instance);
+    if !instance.active {
+        instance.contain("activate", (), |plugin| {
+            plugin.activate();
+            if let Some(arena) = plugin.arena() {
+                arena.prepare();
+            }
+        });
+        instance.active = true;
+    }
 }
 
 /// Helper function for the `run` plugin call.
@@ -358,8 +817,25 @@ pub unsafe fn activate<P: Plugin>(instance: Handle) {
 /// types, and calls the trait's function. Only functions generated by the `lv2_main` should call
 /// this function, any other must not.
 pub unsafe fn run<P: Plugin>(instance: Handle, n_samples: u32) {
-    let instance = (instance as *mut P).as_mut().unwrap();
-    instance.run(n_samples);
+    let instance = Instance::<P>::from_handle(instance);
+    #[cfg(feature = "rt-check")]
+    let _section = if P::THREADING.hard_rt_capable {
+        Some(crate::rt_check::RealtimeSection::enter())
+    } else {
+        None
+    };
+    let _denormals = if P::FLUSH_DENORMALS {
+        Some(crate::denormals::DenormalGuard::new())
+    } else {
+        None
+    };
+    let mut context = instance.cycle.next(n_samples);
+    if instance.panicked.load(Ordering::Relaxed) {
+        instance.contain("bypass", (), |plugin| plugin.bypass(&mut context));
+    } else {
+        instance.contain("run", (), |plugin| plugin.run(&mut context));
+    }
+    instance.cycle = context;
 }
 
 /// Helper function for the `deactivate` plugin call.
@@ -368,8 +844,11 @@ pub unsafe fn run<P: Plugin>(instance: Handle, n_samples: u32) {
 /// types, and calls the trait's function. Only functions generated by the `lv2_main` should call
 /// this function, any other must not.
 pub unsafe fn deactivate<P: Plugin>(instance: Handle) {
-    let instance = (instance as *mut P).as_mut().unwrap();
-    instance.deactivate();
+    let instance = Instance::<P>::from_handle(instance);
+    if instance.active {
+        instance.contain("deactivate", (), P::deactivate);
+        instance.active = false;
+    }
 }
 
 /// Helper function for the `cleanup` plugin call.
@@ -378,7 +857,14 @@ pub unsafe fn deactivate<P: Plugin>(instance: Handle) {
 /// types, and calls the trait's function. Only functions generated by the `lv2_main` should call
 /// this function, any other must not.
 pub unsafe fn cleanup<P: Plugin>(instance: Handle) {
-    core::ptr::drop_in_place(instance as *mut P);
+    deactivate::<P>(instance);
+    let instance = Box::from_raw(instance as *mut Instance<P>);
+    let log = instance.log;
+    if let Err(message) = catch(move || drop(instance)) {
+        if let Some(log) = &log {
+            log.print(&format!("Plugin panicked in cleanup: {}\n", message));
+        }
+    }
 }
 
 /// Helper function for the `extension_data` plugin call.
@@ -388,10 +874,51 @@ pub unsafe fn cleanup<P: Plugin>(instance: Handle) {
 /// this function, any other must not.
 pub unsafe fn extension_data<P: Plugin>(uri: *const c_char) -> *const c_void {
     let uri = CStr::from_ptr(uri);
-    let result = P::extension_data(uri);
-    std::mem::forget(uri);
+    if uri == crate::uris::INTROSPECTION_URI {
+        return match catch(P::info).unwrap_or(None) {
+            Some(info) => info as *const PluginInfo as *const c_void,
+            None => core::ptr::null(),
+        };
+    }
+    let result = catch(|| P::extension_data(uri)).unwrap_or(None);
+    core::mem::forget(uri);
     match result {
         Some(ext_data) => ext_data as *const ExtensionData as *const c_void,
-        None => std::ptr::null(),
+        None => core::ptr::null(),
     }
 }
+
+unsafe extern "C" fn raw_instantiate<P: Plugin>(
+    descriptor: *const Descriptor,
+    rate: f64,
+    bundle_path: *const c_char,
+    features: *const *const Feature,
+) -> Handle {
+    instantiate::<P>(descriptor, rate, bundle_path, features)
+}
+
+unsafe extern "C" fn raw_connect_port<P: Plugin>(instance: Handle, port: u32, data: *mut c_void) {
+    connect_port::<P>(instance, port, data);
+}
+
+unsafe extern "C" fn raw_activate<P: Plugin>(instance: Handle) {
+    activate::<P>(instance);
+}
+
+unsafe extern "C" fn raw_run<P: Plugin>(instance: Handle, n_samples: u32) {
+    run::<P>(instance, n_samples);
+}
+
+unsafe extern "C" fn raw_deactivate<P: Plugin>(instance: Handle) {
+    deactivate::<P>(instance);
+}
+
+unsafe extern "C" fn raw_cleanup<P: Plugin>(instance: Handle) {
+    cleanup::<P>(instance);
+}
+
+unsafe extern "C" fn raw_extension_data<P: Plugin>(uri: *const c_char) -> *const c_void {
+    extension_data::<P>(uri)
+}
+
+unsafe extern "C" fn raw_lib_cleanup(_handle: Handle) {}
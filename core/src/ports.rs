@@ -7,17 +7,144 @@
 //! You should use these wrappers in your plugin struct, since they clearly communicate what type of
 //! data they contain. If you only store raw pointers to the ports, you can not tell an
 //! audio port from a parameter port only looking at the type, for example.
+//!
+//! Matching the port indices in `connect_port` by hand is error-prone. Instead, the ports can be
+//! collected in a struct that derives [`Ports`](derive.Ports.html), which requires the `derive`
+//! feature. The derive macro implements [`PortCollection`](trait.PortCollection.html), which
+//! connects the ports in the order of the fields, and creates a constant with the index of every
+//! port, named like the field in upper case. Every field has to implement
+//! [`PortHandle`](trait.PortHandle.html), which is the case for all port wrappers of this module
+//! and the atom ports of `lv2rs-atom`.
+//!
+//!     extern crate lv2rs_core as lv2core;
+//!
+//!     use lv2core::ports::*;
+//!
+//!     #[derive(Ports)]
+//!     struct AmpPorts {
+//!         gain: ParameterInputPort,
+//!         audio_in: AudioInputPort,
+//!         audio_out: AudioOutputPort,
+//!     }
+//!
+//!     let mut ports = AmpPorts {
+//!         gain: ParameterInputPort::new(),
+//!         audio_in: AudioInputPort::new(),
+//!         audio_out: AudioOutputPort::new(),
+//!     };
+//!     assert_eq!(1, AmpPorts::AUDIO_IN);
+//!     assert_eq!(3, AmpPorts::PORT_COUNT);
+//!
+//!     let gain: f32 = 0.5;
+//!     assert!(ports.connect_port(AmpPorts::GAIN, &gain as *const f32 as *mut ()));
+//!     assert!(!ports.connect_port(3, std::ptr::null_mut()));
+//!     assert_eq!(Some(&0.5), unsafe { ports.gain.get() });
+//!
+//! The fields may also carry `#[port(...)]` attributes with the metadata of the port, like its
+//! name, range and unit. The derive macro collects it in
+//! [`PortCollection::PORTS`](trait.PortCollection.html#associatedconstant.PORTS), which is used
+//! to generate the plugin's Turtle description. See the [`ttl`](../ttl/index.html) module for the
+//! supported attributes. Some ports also use their metadata at runtime: After
+//! [`PortCollection::apply_metadata`](trait.PortCollection.html#method.apply_metadata) was
+//! called, CV ports know the range they were declared with.
+//!
+//! Many ports of the same type, like the inputs of a mixer, are declared as a single
+//! [`PortArray`](struct.PortArray.html) field, which occupies one index per port.
+//!
+//! If the `lv2rs-core` crate is used under another path, for example via the `lv2rs` crate, the
+//! path has to be given with the `ports` attribute, like `#[ports(core = "lv2rs::core")]`.
+
+use core::fmt;
+
+#[cfg(feature = "derive")]
+pub use lv2rs_core_derive::Ports;
+
+/// A collection of ports, which is usually created by deriving [`Ports`](derive.Ports.html).
+pub trait PortCollection {
+    /// The number of ports in the collection.
+    const PORT_COUNT: u32;
+
+    /// The metadata of the ports, in the order of their indices.
+    ///
+    /// It is used to generate the plugin's Turtle description, see the
+    /// [`ttl`](../ttl/index.html) module.
+    const PORTS: &'static [crate::ttl::PortInfo];
+
+    /// Connect the port with the given index to the data location.
+    ///
+    /// `false` is returned if there is no port with this index.
+    fn connect_port(&mut self, port: u32, data: *mut ()) -> bool;
+
+    /// Pass the metadata of every port to the port, see
+    /// [`PortHandle::apply_metadata`](trait.PortHandle.html#method.apply_metadata).
+    ///
+    /// This method should be called once, after the ports were created.
+    fn apply_metadata(&mut self) {}
+}
+
+/// The type and the direction of a port, as it is declared in the plugin's Turtle description.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PortKind {
+    AtomInput,
+    AtomOutput,
+    AudioInput,
+    AudioOutput,
+    CVInput,
+    CVOutput,
+    ControlInput,
+    ControlOutput,
+}
+
+/// A port that can be connected to an untyped data location.
+pub trait PortHandle {
+    /// The kind of the port.
+    const KIND: PortKind;
+
+    /// The designation of the port, like `"latency"` for `lv2:latency`.
+    ///
+    /// It is written to the Turtle description if the port doesn't have a `designation`
+    /// attribute.
+    const DESIGNATION: Option<&'static str> = None;
+
+    /// The number of ports the handle stands for, which is more than one for a
+    /// [`PortArray`](struct.PortArray.html).
+    const COUNT: u32 = 1;
+
+    /// Cast the data pointer to the type of the port and connect it.
+    fn connect_raw(&mut self, data: *mut ());
+
+    /// Connect the port with the given index within the handle.
+    ///
+    /// The default implementation connects the handle itself to index zero. `false` is returned
+    /// if the index is out of range.
+    fn connect_raw_at(&mut self, index: u32, data: *mut ()) -> bool {
+        if index == 0 {
+            self.connect_raw(data);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Configure the port with the metadata it was declared with.
+    ///
+    /// Most ports don't need their metadata, which is why the default implementation does
+    /// nothing.
+    fn apply_metadata(&mut self, _info: &crate::ttl::PortInfo) {}
+}
 
 /// Wrapper for raw audio input lists.
 pub struct AudioInputPort {
     raw: *const f32,
+    max_block_length: Option<u32>,
 }
 
 impl AudioInputPort {
     /// Create a new instance that points to null.
     pub fn new() -> Self {
         Self {
-            raw: std::ptr::null(),
+            raw: core::ptr::null(),
+            max_block_length: None,
         }
     }
 
@@ -25,19 +152,35 @@ impl AudioInputPort {
     ///
     /// This function should only be called by a plugin's `connect_port` function.
     pub fn connect(&mut self, raw: *const f32) {
-        self.raw = raw
+        self.raw = raw;
+    }
+
+    /// Set the maximal number of samples the host passes to `run`.
+    ///
+    /// If it is set, [`as_slice`](#method.as_slice) refuses to create longer slices. The maximal
+    /// block length is usually provided by the host via the options extension.
+    pub fn set_max_block_length(&mut self, max_block_length: Option<u32>) {
+        self.max_block_length = max_block_length;
+    }
+
+    /// Return the maximal number of samples the host passes to `run`, if it is known.
+    pub fn max_block_length(&self) -> Option<u32> {
+        self.max_block_length
     }
 
     /// Try to create an immutable slice of the audio data with the given length.
     ///
+    /// `None` is returned if the port isn't connected or the sample count exceeds the
+    /// [maximal block length](#method.set_max_block_length).
+    ///
     /// This function is unsafe since invalid slices can be created by passing an invalid sample
     /// count. Therefore, only a plugin's `run` function should use this function and must pass
     /// the sample count it received from the host.
     pub unsafe fn as_slice(&self, n_samples: u32) -> Option<&[f32]> {
-        if self.raw.is_null() {
+        if self.raw.is_null() || self.max_block_length.map_or(false, |max| n_samples > max) {
             None
         } else {
-            Some(std::slice::from_raw_parts(self.raw, n_samples as usize))
+            Some(core::slice::from_raw_parts(self.raw, n_samples as usize))
         }
     }
 }
@@ -45,13 +188,15 @@ impl AudioInputPort {
 /// Wrapper for raw audio output lists.
 pub struct AudioOutputPort {
     raw: *mut f32,
+    max_block_length: Option<u32>,
 }
 
 impl AudioOutputPort {
     /// Create a new instance that points to null.
     pub fn new() -> Self {
         Self {
-            raw: std::ptr::null_mut(),
+            raw: core::ptr::null_mut(),
+            max_block_length: None,
         }
     }
 
@@ -62,16 +207,283 @@ impl AudioOutputPort {
         self.raw = raw;
     }
 
+    /// Set the maximal number of samples the host passes to `run`.
+    ///
+    /// If it is set, [`as_slice`](#method.as_slice) refuses to create longer slices. The maximal
+    /// block length is usually provided by the host via the options extension.
+    pub fn set_max_block_length(&mut self, max_block_length: Option<u32>) {
+        self.max_block_length = max_block_length;
+    }
+
+    /// Return the maximal number of samples the host passes to `run`, if it is known.
+    pub fn max_block_length(&self) -> Option<u32> {
+        self.max_block_length
+    }
+
     /// Try to create a mutable slice of the audio data with the given length.
     ///
+    /// `None` is returned if the port isn't connected or the sample count exceeds the
+    /// [maximal block length](#method.set_max_block_length).
+    ///
     /// This function is unsafe since invalid slices can be created by passing an invalid sample
     /// count. Therefore, only a plugin's `run` function should use this function and must pass
     /// the sample count it receives from the host.
     pub unsafe fn as_slice(&mut self, n_samples: u32) -> Option<&mut [f32]> {
-        if self.raw.is_null() {
+        if self.raw.is_null() || self.max_block_length.map_or(false, |max| n_samples > max) {
             None
         } else {
-            Some(std::slice::from_raw_parts_mut(self.raw, n_samples as usize))
+            Some(core::slice::from_raw_parts_mut(
+                self.raw,
+                n_samples as usize,
+            ))
+        }
+    }
+}
+
+/// The range of the values of a CV port.
+///
+/// Unlike audio signals, which are always within -1 and 1, CV signals may have any range, like
+/// 0 to 10 for a pitch in volts. Modular hosts read the range from the `lv2:minimum` and
+/// `lv2:maximum` of the port to scale the signals they connect.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct CVRange {
+    pub minimum: f32,
+    pub maximum: f32,
+}
+
+impl CVRange {
+    /// Create a new range.
+    pub const fn new(minimum: f32, maximum: f32) -> Self {
+        Self { minimum, maximum }
+    }
+
+    /// Return the range of a port's metadata, if both its minimum and maximum are declared.
+    pub fn from_info(info: &crate::ttl::PortInfo) -> Option<Self> {
+        Some(Self::new(info.minimum?, info.maximum?))
+    }
+
+    /// Clamp a value to the range.
+    pub fn clamp(&self, value: f32) -> f32 {
+        value.max(self.minimum).min(self.maximum)
+    }
+
+    /// Map a value of the range to a value between 0 and 1.
+    ///
+    /// Values outside of the range are clamped and empty ranges are mapped to 0.
+    pub fn normalize(&self, value: f32) -> f32 {
+        let span = self.maximum - self.minimum;
+        if span > 0.0 {
+            (self.clamp(value) - self.minimum) / span
+        } else {
+            0.0
+        }
+    }
+
+    /// Map a value between 0 and 1 to a value of the range.
+    ///
+    /// Values outside of 0 and 1 are clamped.
+    pub fn denormalize(&self, value: f32) -> f32 {
+        self.minimum + value.clamp(0.0, 1.0) * (self.maximum - self.minimum)
+    }
+}
+
+/// Wrapper for raw CV input lists.
+///
+/// CV ports carry control signals, like envelopes or modulation, at audio rate. Their buffers have
+/// the same layout as the buffers of audio ports. The port may have a [range](struct.CVRange.html),
+/// which is set from the port's metadata by
+/// [`apply_metadata`](trait.PortHandle.html#method.apply_metadata).
+pub struct CVInputPort {
+    raw: *const f32,
+    max_block_length: Option<u32>,
+    range: Option<CVRange>,
+}
+
+impl CVInputPort {
+    /// Create a new instance that points to null.
+    pub fn new() -> Self {
+        Self {
+            raw: core::ptr::null(),
+            max_block_length: None,
+            range: None,
+        }
+    }
+
+    /// Set the range of the port's values.
+    pub fn set_range(&mut self, range: Option<CVRange>) {
+        self.range = range;
+    }
+
+    /// Return the range of the port's values, if it is known.
+    pub fn range(&self) -> Option<CVRange> {
+        self.range
+    }
+
+    /// Set the internal data pointer.
+    ///
+    /// This function should only be called by a plugin's `connect_port` function.
+    pub fn connect(&mut self, raw: *const f32) {
+        self.raw = raw;
+    }
+
+    /// Set the maximal number of samples the host passes to `run`.
+    ///
+    /// If it is set, [`as_slice`](#method.as_slice) refuses to create longer slices. The maximal
+    /// block length is usually provided by the host via the options extension.
+    pub fn set_max_block_length(&mut self, max_block_length: Option<u32>) {
+        self.max_block_length = max_block_length;
+    }
+
+    /// Return the maximal number of samples the host passes to `run`, if it is known.
+    pub fn max_block_length(&self) -> Option<u32> {
+        self.max_block_length
+    }
+
+    /// Try to create an immutable slice of the CV data with the given length.
+    ///
+    /// `None` is returned if the port isn't connected or the sample count exceeds the
+    /// [maximal block length](#method.set_max_block_length).
+    ///
+    /// # Safety
+    ///
+    /// Invalid slices can be created by passing an invalid sample count. Therefore, only a
+    /// plugin's `run` function should use this function and must pass the sample count it
+    /// received from the host.
+    pub unsafe fn as_slice(&self, n_samples: u32) -> Option<&[f32]> {
+        if self.raw.is_null() || self.max_block_length.is_some_and(|max| n_samples > max) {
+            None
+        } else {
+            Some(core::slice::from_raw_parts(self.raw, n_samples as usize))
+        }
+    }
+
+    /// Iterate over the CV data, mapped from the port's range to values between 0 and 1.
+    ///
+    /// `None` is returned if the port has no range or [`as_slice`](#method.as_slice) fails.
+    ///
+    /// # Safety
+    ///
+    /// The same requirements as for [`as_slice`](#method.as_slice) apply: `n_samples` has to be
+    /// the sample count the plugin's `run` function received from the host.
+    pub unsafe fn normalized(&self, n_samples: u32) -> Option<impl Iterator<Item = f32> + '_> {
+        let range = self.range?;
+        let data = self.as_slice(n_samples)?;
+        Some(data.iter().map(move |value| range.normalize(*value)))
+    }
+}
+
+/// Wrapper for raw CV output lists.
+///
+/// CV ports carry control signals, like envelopes or modulation, at audio rate. Their buffers have
+/// the same layout as the buffers of audio ports. The port may have a [range](struct.CVRange.html),
+/// which is set from the port's metadata by
+/// [`apply_metadata`](trait.PortHandle.html#method.apply_metadata).
+pub struct CVOutputPort {
+    raw: *mut f32,
+    max_block_length: Option<u32>,
+    range: Option<CVRange>,
+}
+
+impl CVOutputPort {
+    /// Create a new instance that points to null.
+    pub fn new() -> Self {
+        Self {
+            raw: core::ptr::null_mut(),
+            max_block_length: None,
+            range: None,
+        }
+    }
+
+    /// Set the range of the port's values.
+    pub fn set_range(&mut self, range: Option<CVRange>) {
+        self.range = range;
+    }
+
+    /// Return the range of the port's values, if it is known.
+    pub fn range(&self) -> Option<CVRange> {
+        self.range
+    }
+
+    /// Set the internal data pointer.
+    ///
+    /// This function should only be called by a plugin's `connect_port` function.
+    pub fn connect(&mut self, raw: *mut f32) {
+        self.raw = raw;
+    }
+
+    /// Set the maximal number of samples the host passes to `run`.
+    ///
+    /// If it is set, [`as_slice`](#method.as_slice) refuses to create longer slices. The maximal
+    /// block length is usually provided by the host via the options extension.
+    pub fn set_max_block_length(&mut self, max_block_length: Option<u32>) {
+        self.max_block_length = max_block_length;
+    }
+
+    /// Return the maximal number of samples the host passes to `run`, if it is known.
+    pub fn max_block_length(&self) -> Option<u32> {
+        self.max_block_length
+    }
+
+    /// Try to create a mutable slice of the CV data with the given length.
+    ///
+    /// `None` is returned if the port isn't connected or the sample count exceeds the
+    /// [maximal block length](#method.set_max_block_length).
+    ///
+    /// # Safety
+    ///
+    /// Invalid slices can be created by passing an invalid sample count. Therefore, only a
+    /// plugin's `run` function should use this function and must pass the sample count it
+    /// receives from the host.
+    pub unsafe fn as_slice(&mut self, n_samples: u32) -> Option<&mut [f32]> {
+        if self.raw.is_null() || self.max_block_length.is_some_and(|max| n_samples > max) {
+            None
+        } else {
+            Some(core::slice::from_raw_parts_mut(
+                self.raw,
+                n_samples as usize,
+            ))
+        }
+    }
+
+    /// Write values between 0 and 1 to the CV data, mapped to the port's range.
+    ///
+    /// At most `n_samples` values are written. An error is returned if the port has no range or
+    /// [`as_slice`](#method.as_slice) fails.
+    ///
+    /// # Safety
+    ///
+    /// The same requirements as for [`as_slice`](#method.as_slice) apply: `n_samples` has to be
+    /// the sample count the plugin's `run` function received from the host.
+    pub unsafe fn write_normalized(
+        &mut self,
+        n_samples: u32,
+        values: impl IntoIterator<Item = f32>,
+    ) -> Result<(), WriteNormalizedError> {
+        let range = self.range.ok_or(WriteNormalizedError::MissingRange)?;
+        let data = self
+            .as_slice(n_samples)
+            .ok_or(WriteNormalizedError::Unavailable)?;
+        for (sample, value) in data.iter_mut().zip(values) {
+            *sample = range.denormalize(value);
+        }
+        Ok(())
+    }
+}
+
+/// Errors that may occur when normalized values are written to a CV output port.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WriteNormalizedError {
+    /// The port has no range to map the values to.
+    MissingRange,
+    /// The port isn't connected or the sample count exceeds its maximal block length.
+    Unavailable,
+}
+
+impl fmt::Display for WriteNormalizedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WriteNormalizedError::MissingRange => write!(f, "The port has no range"),
+            WriteNormalizedError::Unavailable => write!(f, "The port's buffer is not available"),
         }
     }
 }
@@ -85,7 +497,7 @@ impl ParameterInputPort {
     /// Create a new instance that points to null.
     pub fn new() -> Self {
         Self {
-            raw: std::ptr::null(),
+            raw: core::ptr::null(),
         }
     }
 
@@ -113,7 +525,7 @@ impl ParameterOutputPort {
     /// Create a new instance that points to null.
     pub fn new() -> Self {
         Self {
-            raw: std::ptr::null_mut(),
+            raw: core::ptr::null_mut(),
         }
     }
 
@@ -131,3 +543,732 @@ impl ParameterOutputPort {
         self.raw.as_mut()
     }
 }
+
+/// Wrapper for raw control inputs with change detection and smoothing.
+///
+/// In contrast to the [`ParameterInputPort`](struct.ParameterInputPort.html), this port keeps
+/// track of the value: [`update`](#method.update) has to be called once at the beginning of every
+/// `run` call, which reads the new value from the host and detects whether it has changed.
+///
+/// Abrupt changes of parameters like the gain cause audible clicks. Therefore, the port can
+/// smooth the value linearly over a number of samples, which is enabled with
+/// [`set_smoothing`](#method.set_smoothing). The smoothed value is then retrieved sample by sample
+/// with [`next_value`](#method.next_value).
+pub struct ControlInputPort {
+    raw: *const f32,
+    value: Option<f32>,
+    changed: bool,
+    smoothing: Option<u32>,
+    current: f32,
+    step: f32,
+    remaining: u32,
+}
+
+impl ControlInputPort {
+    /// Create a new instance that points to null.
+    pub fn new() -> Self {
+        Self {
+            raw: core::ptr::null(),
+            value: None,
+            changed: false,
+            smoothing: None,
+            current: 0.0,
+            step: 0.0,
+            remaining: 0,
+        }
+    }
+
+    /// Set the internal data pointer.
+    ///
+    /// This function should only be called by a plugin's `connect_port` function.
+    pub fn connect(&mut self, raw: *const f32) {
+        self.raw = raw;
+    }
+
+    /// Set the number of samples over which changes of the value are smoothed.
+    ///
+    /// `None` or `Some(0)` disables smoothing.
+    pub fn set_smoothing(&mut self, n_samples: Option<u32>) {
+        self.smoothing = n_samples.filter(|n_samples| *n_samples > 0);
+        if self.smoothing.is_none() {
+            self.finish_ramp();
+        }
+    }
+
+    /// Read the current value from the host.
+    ///
+    /// This method should be called once at the beginning of every `run` call. The first value
+    /// that is read is never smoothed. `None` is returned if the port isn't connected.
+    ///
+    /// This method is unsafe since it dereferences the raw, internal pointer.
+    pub unsafe fn update(&mut self) -> Option<f32> {
+        let new_value = *self.raw.as_ref()?;
+        self.changed = self.value != Some(new_value);
+        if self.changed {
+            match (self.value, self.smoothing) {
+                (Some(_), Some(n_samples)) => {
+                    self.step = (new_value - self.current) / n_samples as f32;
+                    self.remaining = n_samples;
+                }
+                _ => {
+                    self.current = new_value;
+                    self.remaining = 0;
+                }
+            }
+            self.value = Some(new_value);
+        }
+        self.value
+    }
+
+    /// Return the last value that was read from the host.
+    pub fn value(&self) -> Option<f32> {
+        self.value
+    }
+
+    /// Return `true` if the value changed in the last [`update`](#method.update).
+    pub fn changed_since_last_run(&self) -> bool {
+        self.changed
+    }
+
+    /// Return `true` if the smoothed value hasn't reached the value yet.
+    pub fn is_smoothing(&self) -> bool {
+        self.remaining > 0
+    }
+
+    /// Advance the smoothing by one sample and return the smoothed value.
+    ///
+    /// Without smoothing, this is the last value that was read from the host.
+    pub fn next_value(&mut self) -> f32 {
+        if self.remaining > 0 {
+            self.remaining -= 1;
+            if self.remaining == 0 {
+                self.finish_ramp();
+            } else {
+                self.current += self.step;
+            }
+        }
+        self.current
+    }
+
+    /// Jump to the end of the current smoothing ramp.
+    fn finish_ramp(&mut self) {
+        if let Some(value) = self.value {
+            self.current = value;
+        }
+        self.remaining = 0;
+    }
+}
+
+/// Wrapper for raw control outputs with change detection.
+///
+/// The port remembers the last value that was written, so plugins can check whether they
+/// actually changed their output, for example to notify a user interface.
+pub struct ControlOutputPort {
+    raw: *mut f32,
+    value: Option<f32>,
+    changed: bool,
+}
+
+impl ControlOutputPort {
+    /// Create a new instance that points to null.
+    pub fn new() -> Self {
+        Self {
+            raw: core::ptr::null_mut(),
+            value: None,
+            changed: false,
+        }
+    }
+
+    /// Set the internal data pointer.
+    ///
+    /// This function should only be called by a plugin's `connect_port` function.
+    pub fn connect(&mut self, raw: *mut f32) {
+        self.raw = raw;
+    }
+
+    /// Write a value to the host.
+    ///
+    /// `Err` is returned if the port isn't connected.
+    ///
+    /// This method is unsafe since it dereferences the raw, internal pointer.
+    pub unsafe fn set(&mut self, value: f32) -> Result<(), ()> {
+        let raw = self.raw.as_mut().ok_or(())?;
+        *raw = value;
+        self.changed = self.value != Some(value);
+        self.value = Some(value);
+        Ok(())
+    }
+
+    /// Return the last value that was written.
+    pub fn value(&self) -> Option<f32> {
+        self.value
+    }
+
+    /// Return `true` if the last [`set`](#method.set) changed the value.
+    pub fn changed_since_last_run(&self) -> bool {
+        self.changed
+    }
+}
+
+/// Wrapper for the latency output of a plugin.
+///
+/// Plugins that delay their signal, like lookahead limiters or linear-phase filters, report the
+/// delay in samples with a control output that is designated as `lv2:latency`. The host then
+/// compensates the delay. The designation is declared automatically if the port is part of a
+/// collection that derives [`Ports`](derive.Ports.html).
+///
+/// The latency is set with [`set_latency`](#method.set_latency), usually when it is known in
+/// `instantiate` or `activate`, and has to be written to the host with
+/// [`report`](#method.report) in every `run` call.
+pub struct LatencyPort {
+    raw: *mut f32,
+    latency: u32,
+}
+
+impl LatencyPort {
+    /// Create a new instance that points to null and reports no latency.
+    pub fn new() -> Self {
+        Self {
+            raw: core::ptr::null_mut(),
+            latency: 0,
+        }
+    }
+
+    /// Set the internal data pointer.
+    ///
+    /// This function should only be called by a plugin's `connect_port` function.
+    pub fn connect(&mut self, raw: *mut f32) {
+        self.raw = raw;
+    }
+
+    /// Set the latency in samples.
+    pub fn set_latency(&mut self, latency: u32) {
+        self.latency = latency;
+    }
+
+    /// Return the latency in samples.
+    pub fn latency(&self) -> u32 {
+        self.latency
+    }
+
+    /// Write the latency to the host.
+    ///
+    /// An error is returned if the port isn't connected.
+    ///
+    /// # Safety
+    ///
+    /// The raw, internal pointer is dereferenced. It has to be null or point to the port's value,
+    /// which is the case if it was connected by the plugin's `connect_port` function.
+    pub unsafe fn report(&mut self) -> Result<(), NotConnectedError> {
+        *self.raw.as_mut().ok_or(NotConnectedError)? = self.latency as f32;
+        Ok(())
+    }
+}
+
+impl Default for LatencyPort {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The error that is returned if a port is accessed before it was connected.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct NotConnectedError;
+
+impl fmt::Display for NotConnectedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "The port is not connected")
+    }
+}
+
+/// Wrapper for the enabled input of a plugin, which implements a click-free bypass.
+///
+/// Hosts bypass plugins by setting a toggled control input, which is designated as
+/// `lv2:enabled`, to zero. The designation, the range and the `toggled` property are declared
+/// automatically if the port is part of a collection that derives [`Ports`](derive.Ports.html).
+///
+/// Instead of switching between the processed and the dry signal abruptly, the plugin wraps its
+/// processing in [`process`](#method.process), which crossfades between them over
+/// [`fade_length`](#method.fade_length) samples. While the plugin is bypassed completely, the
+/// processing isn't called at all and the inputs are copied to the outputs.
+///
+/// Since the dry signal is read after the processing, the plugin must not process in place and
+/// has to declare [`in_place_broken`](../struct.Threading.html#structfield.in_place_broken).
+pub struct EnabledPort {
+    raw: *const f32,
+    fade_length: u32,
+    wet: Option<f32>,
+}
+
+impl EnabledPort {
+    /// The default length of a crossfade, in samples.
+    pub const DEFAULT_FADE_LENGTH: u32 = 256;
+
+    /// Create a new instance that points to null.
+    pub fn new() -> Self {
+        Self {
+            raw: core::ptr::null(),
+            fade_length: Self::DEFAULT_FADE_LENGTH,
+            wet: None,
+        }
+    }
+
+    /// Set the internal data pointer.
+    ///
+    /// This function should only be called by a plugin's `connect_port` function.
+    pub fn connect(&mut self, raw: *const f32) {
+        self.raw = raw;
+    }
+
+    /// Set the length of a crossfade, in samples.
+    ///
+    /// A length of zero switches immediately.
+    pub fn set_fade_length(&mut self, fade_length: u32) {
+        self.fade_length = fade_length;
+    }
+
+    /// Return the length of a crossfade, in samples.
+    pub fn fade_length(&self) -> u32 {
+        self.fade_length
+    }
+
+    /// Return whether the host has enabled the plugin.
+    ///
+    /// A plugin is enabled if the port isn't connected.
+    ///
+    /// This method is unsafe since it dereferences the raw, internal pointer.
+    pub unsafe fn is_enabled(&self) -> bool {
+        self.raw.as_ref().map(|value| *value > 0.0).unwrap_or(true)
+    }
+
+    /// Return whether the plugin was bypassed completely in the last cycle.
+    pub fn is_bypassed(&self) -> bool {
+        self.wet == Some(0.0)
+    }
+
+    /// Process a cycle and crossfade the result with the dry signal.
+    ///
+    /// The inputs and outputs are the channels of the signal that is bypassed, which are paired
+    /// in their order. `process` receives the outputs and should write the processed signal to
+    /// them. It isn't called if the plugin is bypassed completely. The first cycle never fades,
+    /// it starts in the state the host has requested.
+    ///
+    /// This method is unsafe since it dereferences the raw, internal pointer.
+    pub unsafe fn process<F>(&mut self, inputs: &[&[f32]], outputs: &mut [&mut [f32]], process: F)
+    where
+        F: FnOnce(&mut [&mut [f32]]),
+    {
+        let target = if self.is_enabled() { 1.0 } else { 0.0 };
+        let wet = self.wet.unwrap_or(target);
+        if wet == 0.0 && target == 0.0 {
+            for (output, input) in outputs.iter_mut().zip(inputs) {
+                let length = output.len().min(input.len());
+                output[..length].copy_from_slice(&input[..length]);
+            }
+            self.wet = Some(0.0);
+            return;
+        }
+
+        process(outputs);
+        if wet == target {
+            self.wet = Some(wet);
+            return;
+        }
+
+        let step = if self.fade_length == 0 {
+            1.0
+        } else {
+            1.0 / self.fade_length as f32
+        };
+        let fade = |gain: f32, n_samples: usize| {
+            if target > gain {
+                (gain + step * n_samples as f32).min(target)
+            } else {
+                (gain - step * n_samples as f32).max(target)
+            }
+        };
+        for (output, input) in outputs.iter_mut().zip(inputs) {
+            for (index, (output, input)) in output.iter_mut().zip(input.iter()).enumerate() {
+                let gain = fade(wet, index + 1);
+                *output = *input * (1.0 - gain) + *output * gain;
+            }
+        }
+        let n_samples = outputs.iter().map(|output| output.len()).max();
+        self.wet = Some(fade(wet, n_samples.unwrap_or(0)));
+    }
+}
+
+/// A fixed number of ports of the same type, like the sixteen MIDI inputs of a multi-channel
+/// instrument.
+///
+/// In a struct that derives [`Ports`](derive.Ports.html), a port array occupies `N` consecutive
+/// indices and its constant contains the first one. The length has to be given as an integer
+/// literal, since the derive macro creates the metadata of every port: The symbols and names are
+/// numbered from zero, for example `midi_in_0` to `midi_in_15`, and all ports share the other
+/// attributes. Designations and channels can't be given for arrays.
+///
+///     extern crate lv2rs_core as lv2core;
+///
+///     use lv2core::ports::*;
+///
+///     #[derive(Ports)]
+///     struct MixerPorts {
+///         #[port(name = "Input")]
+///         inputs: PortArray<AudioInputPort, 4>,
+///         output: AudioOutputPort,
+///     }
+///
+///     let mut ports = MixerPorts {
+///         inputs: PortArray::from_fn(|_| AudioInputPort::new()),
+///         output: AudioOutputPort::new(),
+///     };
+///     assert_eq!(5, MixerPorts::PORT_COUNT);
+///     assert_eq!(4, MixerPorts::OUTPUT);
+///     assert_eq!("inputs_3", MixerPorts::PORTS[3].symbol);
+///     assert_eq!("Input 3", MixerPorts::PORTS[3].name);
+///
+///     let buffer = [1.0f32; 8];
+///     assert!(ports.connect_port(2, buffer.as_ptr() as *mut ()));
+///     assert!(unsafe { ports.inputs[2].as_slice(8) }.is_some());
+///     assert!(unsafe { ports.inputs[1].as_slice(8) }.is_none());
+pub struct PortArray<P, const N: usize> {
+    ports: [P; N],
+}
+
+impl<P, const N: usize> PortArray<P, N> {
+    /// Create an array from its ports.
+    pub fn new(ports: [P; N]) -> Self {
+        Self { ports }
+    }
+
+    /// Create an array by calling a function with the index of every port.
+    pub fn from_fn<F: FnMut(usize) -> P>(function: F) -> Self {
+        Self::new(core::array::from_fn(function))
+    }
+
+    /// Return the number of ports.
+    pub fn len(&self) -> usize {
+        N
+    }
+
+    /// Return `true` if the array contains no ports.
+    pub fn is_empty(&self) -> bool {
+        N == 0
+    }
+
+    /// Return the port with the given index.
+    pub fn get(&self, index: usize) -> Option<&P> {
+        self.ports.get(index)
+    }
+
+    /// Return the port with the given index mutably.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut P> {
+        self.ports.get_mut(index)
+    }
+
+    /// Iterate over the ports.
+    pub fn iter(&self) -> core::slice::Iter<'_, P> {
+        self.ports.iter()
+    }
+
+    /// Iterate over the ports mutably.
+    pub fn iter_mut(&mut self) -> core::slice::IterMut<'_, P> {
+        self.ports.iter_mut()
+    }
+}
+
+impl<P, const N: usize> core::ops::Index<usize> for PortArray<P, N> {
+    type Output = P;
+
+    fn index(&self, index: usize) -> &P {
+        &self.ports[index]
+    }
+}
+
+impl<P, const N: usize> core::ops::IndexMut<usize> for PortArray<P, N> {
+    fn index_mut(&mut self, index: usize) -> &mut P {
+        &mut self.ports[index]
+    }
+}
+
+impl<'a, P, const N: usize> IntoIterator for &'a PortArray<P, N> {
+    type Item = &'a P;
+    type IntoIter = core::slice::Iter<'a, P>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, P, const N: usize> IntoIterator for &'a mut PortArray<P, N> {
+    type Item = &'a mut P;
+    type IntoIter = core::slice::IterMut<'a, P>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// The ports are connected by their index within the array. The metadata is applied to all of
+/// them.
+impl<P: PortHandle, const N: usize> PortHandle for PortArray<P, N> {
+    const KIND: PortKind = P::KIND;
+    const DESIGNATION: Option<&'static str> = P::DESIGNATION;
+    const COUNT: u32 = N as u32;
+
+    fn connect_raw(&mut self, data: *mut ()) {
+        self.connect_raw_at(0, data);
+    }
+
+    fn connect_raw_at(&mut self, index: u32, data: *mut ()) -> bool {
+        match self.ports.get_mut(index as usize) {
+            Some(port) => {
+                port.connect_raw(data);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn apply_metadata(&mut self, info: &crate::ttl::PortInfo) {
+        for port in self.ports.iter_mut() {
+            port.apply_metadata(info);
+        }
+    }
+}
+
+impl PortHandle for AudioInputPort {
+    const KIND: PortKind = PortKind::AudioInput;
+
+    fn connect_raw(&mut self, data: *mut ()) {
+        self.connect(data as *const f32);
+    }
+}
+
+impl PortHandle for AudioOutputPort {
+    const KIND: PortKind = PortKind::AudioOutput;
+
+    fn connect_raw(&mut self, data: *mut ()) {
+        self.connect(data as *mut f32);
+    }
+}
+
+impl PortHandle for CVInputPort {
+    const KIND: PortKind = PortKind::CVInput;
+
+    fn connect_raw(&mut self, data: *mut ()) {
+        self.connect(data as *const f32);
+    }
+
+    fn apply_metadata(&mut self, info: &crate::ttl::PortInfo) {
+        self.set_range(CVRange::from_info(info));
+    }
+}
+
+impl PortHandle for CVOutputPort {
+    const KIND: PortKind = PortKind::CVOutput;
+
+    fn connect_raw(&mut self, data: *mut ()) {
+        self.connect(data as *mut f32);
+    }
+
+    fn apply_metadata(&mut self, info: &crate::ttl::PortInfo) {
+        self.set_range(CVRange::from_info(info));
+    }
+}
+
+impl PortHandle for ControlInputPort {
+    const KIND: PortKind = PortKind::ControlInput;
+
+    fn connect_raw(&mut self, data: *mut ()) {
+        self.connect(data as *const f32);
+    }
+}
+
+impl PortHandle for ControlOutputPort {
+    const KIND: PortKind = PortKind::ControlOutput;
+
+    fn connect_raw(&mut self, data: *mut ()) {
+        self.connect(data as *mut f32);
+    }
+}
+
+impl PortHandle for LatencyPort {
+    const KIND: PortKind = PortKind::ControlOutput;
+    const DESIGNATION: Option<&'static str> = Some("latency");
+
+    fn connect_raw(&mut self, data: *mut ()) {
+        self.connect(data as *mut f32);
+    }
+}
+
+impl PortHandle for EnabledPort {
+    const KIND: PortKind = PortKind::ControlInput;
+    const DESIGNATION: Option<&'static str> = Some("enabled");
+
+    fn connect_raw(&mut self, data: *mut ()) {
+        self.connect(data as *const f32);
+    }
+}
+
+impl PortHandle for ParameterInputPort {
+    const KIND: PortKind = PortKind::ControlInput;
+
+    fn connect_raw(&mut self, data: *mut ()) {
+        self.connect(data as *const f32);
+    }
+}
+
+impl PortHandle for ParameterOutputPort {
+    const KIND: PortKind = PortKind::ControlOutput;
+
+    fn connect_raw(&mut self, data: *mut ()) {
+        self.connect(data as *mut f32);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ports::*;
+
+    #[test]
+    fn test_checked_slices() {
+        let mut input_buffer = [1.0f32; 8];
+        let mut output_buffer = [0.0f32; 8];
+
+        let mut input = CVInputPort::new();
+        let mut output = AudioOutputPort::new();
+        assert!(unsafe { input.as_slice(8) }.is_none());
+        assert!(unsafe { output.as_slice(8) }.is_none());
+
+        input.connect_raw(input_buffer.as_mut_ptr() as *mut ());
+        output.connect_raw(output_buffer.as_mut_ptr() as *mut ());
+        input.set_max_block_length(Some(8));
+        output.set_max_block_length(Some(8));
+        assert_eq!(Some(8), input.max_block_length());
+        assert!(unsafe { input.as_slice(9) }.is_none());
+        assert!(unsafe { output.as_slice(9) }.is_none());
+
+        let input = unsafe { input.as_slice(8) }.unwrap();
+        let output = unsafe { output.as_slice(8) }.unwrap();
+        output.copy_from_slice(input);
+        assert_eq!([1.0; 8], output_buffer);
+    }
+
+    #[test]
+    fn test_cv_range() {
+        let range = CVRange::new(-5.0, 5.0);
+        assert_eq!(0.75, range.normalize(2.5));
+        assert_eq!(1.0, range.normalize(10.0));
+        assert_eq!(-5.0, range.denormalize(-1.0));
+        assert_eq!(0.0, CVRange::new(1.0, 1.0).normalize(1.0));
+
+        let mut input_buffer = [-5.0f32, 0.0, 5.0];
+        let mut output_buffer = [0.0f32; 3];
+        let mut input = CVInputPort::new();
+        let mut output = CVOutputPort::new();
+        input.connect_raw(input_buffer.as_mut_ptr() as *mut ());
+        output.connect_raw(output_buffer.as_mut_ptr() as *mut ());
+        assert!(unsafe { input.normalized(3) }.is_none());
+        assert_eq!(Err(WriteNormalizedError::MissingRange), unsafe {
+            output.write_normalized(3, Vec::new())
+        });
+
+        input.set_range(Some(range));
+        output.set_range(Some(CVRange::new(0.0, 10.0)));
+        let normalized = unsafe { input.normalized(3) }.unwrap();
+        unsafe { output.write_normalized(3, normalized) }.unwrap();
+        output.set_max_block_length(Some(2));
+        assert_eq!(Err(WriteNormalizedError::Unavailable), unsafe {
+            output.write_normalized(3, Vec::new())
+        });
+        assert_eq!([0.0, 5.0, 10.0], output_buffer);
+    }
+
+    #[test]
+    fn test_control_ports() {
+        let mut input_value = 1.0f32;
+        let input_ptr = &mut input_value as *mut f32;
+        let mut output_value = 0.0f32;
+
+        let mut input = ControlInputPort::new();
+        let mut output = ControlOutputPort::new();
+        assert_eq!(None, unsafe { input.update() });
+        assert!(unsafe { output.set(1.0) }.is_err());
+
+        input.connect(input_ptr);
+        output.connect(&mut output_value);
+        input.set_smoothing(Some(4));
+
+        // The first value is not smoothed.
+        assert_eq!(Some(1.0), unsafe { input.update() });
+        assert!(input.changed_since_last_run());
+        assert_eq!(1.0, input.next_value());
+
+        assert_eq!(Some(1.0), unsafe { input.update() });
+        assert!(!input.changed_since_last_run());
+
+        unsafe { *input_ptr = 3.0 };
+        assert_eq!(Some(3.0), unsafe { input.update() });
+        assert!(input.changed_since_last_run());
+        let ramp: Vec<f32> = (0..5).map(|_| input.next_value()).collect();
+        assert_eq!(vec![1.5, 2.0, 2.5, 3.0, 3.0], ramp);
+        assert!(!input.is_smoothing());
+
+        unsafe { output.set(2.0) }.unwrap();
+        assert!(output.changed_since_last_run());
+        unsafe { output.set(2.0) }.unwrap();
+        assert!(!output.changed_since_last_run());
+        assert_eq!(Some(2.0), output.value());
+        assert_eq!(2.0, output_value);
+    }
+
+    #[test]
+    fn test_latency_port() {
+        let mut value = -1.0f32;
+        let mut port = LatencyPort::new();
+        assert_eq!(Err(NotConnectedError), unsafe { port.report() });
+
+        port.connect_raw(&mut value as *mut f32 as *mut ());
+        port.set_latency(64);
+        assert_eq!(64, port.latency());
+        unsafe { port.report() }.unwrap();
+        assert_eq!(64.0, value);
+    }
+
+    #[test]
+    fn test_enabled_port() {
+        let input = [1.0f32; 4];
+        let mut output = [0.0f32; 4];
+        let mut enabled = 1.0f32;
+        let enabled_ptr = &mut enabled as *mut f32;
+        let mut port = EnabledPort::new();
+        assert!(unsafe { port.is_enabled() });
+        port.connect_raw(enabled_ptr as *mut ());
+        port.set_fade_length(2);
+
+        // The processing inverts the signal.
+        let invert = |outputs: &mut [&mut [f32]]| outputs[0].iter_mut().for_each(|x| *x = -1.0);
+        unsafe { port.process(&[&input], &mut [&mut output], invert) };
+        assert_eq!([-1.0; 4], output);
+
+        // Fading out.
+        unsafe { *enabled_ptr = 0.0 };
+        unsafe { port.process(&[&input], &mut [&mut output], invert) };
+        assert_eq!([0.0, 1.0, 1.0, 1.0], output);
+        assert!(port.is_bypassed());
+
+        // The processing is skipped while the plugin is bypassed.
+        output = [0.0; 4];
+        unsafe { port.process(&[&input], &mut [&mut output], |_| panic!("not bypassed")) };
+        assert_eq!([1.0; 4], output);
+
+        // Fading in.
+        unsafe { *enabled_ptr = 1.0 };
+        unsafe { port.process(&[&input[..1]], &mut [&mut output[..1]], invert) };
+        assert_eq!(0.0, output[0]);
+        assert!(!port.is_bypassed());
+        unsafe { port.process(&[&input], &mut [&mut output], invert) };
+        assert_eq!([-1.0; 4], output);
+    }
+}
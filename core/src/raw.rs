@@ -0,0 +1,183 @@
+//! Conversions to the types of other LV2 bindings.
+//!
+//! Crates like `lv2-sys` and `lv2_raw` contain their own bindings of the C structs of the LV2
+//! headers. Since the types of lv2rs that mirror these structs are `#[repr(C)]` too, a reference
+//! to one of them can be reinterpreted as a reference to the foreign struct, and vice versa,
+//! without copying anything. This allows a project to mix lv2rs with other bindings, for example
+//! by passing the features lv2rs received to a library that expects `lv2_sys::LV2_Feature`s.
+//!
+//! The types that mirror a C struct implement [`RawType`](trait.RawType.html), which names the
+//! struct. The conversion functions check that the size and the alignment of both types match,
+//! which is done at compile time in optimized builds:
+//!
+//!     extern crate lv2rs_core as core;
+//!
+//!     use std::os::raw::*;
+//!
+//!     // The binding of another crate.
+//!     #[repr(C)]
+//!     #[allow(non_camel_case_types, non_snake_case)]
+//!     struct LV2_Feature {
+//!         URI: *const c_char,
+//!         data: *mut c_void,
+//!     }
+//!
+//!     let uri = b"http://example.org/feature\0".as_ptr() as *const c_char;
+//!     let feature = core::Feature::new(uri, std::ptr::null_mut());
+//!     let foreign: &LV2_Feature = unsafe { core::raw::to_foreign(&feature) };
+//!     assert_eq!(feature.uri().unwrap().as_ptr(), foreign.URI);
+//!
+//!     let feature: &core::Feature = unsafe { core::raw::from_foreign(foreign) };
+//!     assert_eq!(b"http://example.org/feature", feature.uri().unwrap().to_bytes());
+use crate::{Descriptor, Feature, LibDescriptor};
+use core::mem::{align_of, size_of};
+
+/// A type of lv2rs with the same layout as a C struct of the LV2 headers.
+///
+/// Other lv2rs crates implement it for their own types, for example for the atom header.
+///
+/// # Safety
+///
+/// The conversions of this module rely on the layout of the type: It has to be `#[repr(C)]` and
+/// its fields have to match the fields of the C struct named by `C_NAME`, in the same order and
+/// with the same types.
+pub unsafe trait RawType: Sized {
+    /// The name of the C struct, like `LV2_Feature`.
+    const C_NAME: &'static str;
+}
+
+unsafe impl RawType for Descriptor {
+    const C_NAME: &'static str = "LV2_Descriptor";
+}
+
+unsafe impl RawType for Feature {
+    const C_NAME: &'static str = "LV2_Feature";
+}
+
+unsafe impl RawType for LibDescriptor {
+    const C_NAME: &'static str = "LV2_Lib_Descriptor";
+}
+
+/// Check that two types have the same size and alignment.
+///
+/// Since both are known at compile time, the check is removed by the optimizer if it succeeds.
+fn check_layout<T: RawType, U>() {
+    assert!(
+        size_of::<T>() == size_of::<U>() && align_of::<T>() == align_of::<U>(),
+        "The layout of the foreign type doesn't match {}",
+        T::C_NAME
+    );
+}
+
+/// Reinterpret a reference as a reference to the foreign binding of the same C struct.
+///
+/// # Safety
+///
+/// This function can only check the size and the alignment of the foreign type. It's your
+/// responsibility to ensure that `U` is a `#[repr(C)]` binding of the struct named by
+/// [`RawType::C_NAME`](trait.RawType.html#associatedconstant.C_NAME) and that every value of `T`
+/// is a valid value of `U`.
+///
+/// # Panics
+///
+/// This function panics if the size or the alignment of the types doesn't match.
+pub unsafe fn to_foreign<T: RawType, U>(value: &T) -> &U {
+    check_layout::<T, U>();
+    &*(value as *const T as *const U)
+}
+
+/// Reinterpret a mutable reference as a mutable reference to the foreign binding of the same C
+/// struct.
+///
+/// # Safety
+///
+/// The requirements of [`to_foreign`](fn.to_foreign.html) apply. In addition, every value of `U`
+/// that is written through the returned reference has to be a valid value of `T`.
+pub unsafe fn to_foreign_mut<T: RawType, U>(value: &mut T) -> &mut U {
+    check_layout::<T, U>();
+    &mut *(value as *mut T as *mut U)
+}
+
+/// Reinterpret a reference to a foreign binding as a reference to the type of lv2rs.
+///
+/// # Safety
+///
+/// `U` has to be a `#[repr(C)]` binding of the struct named by
+/// [`RawType::C_NAME`](trait.RawType.html#associatedconstant.C_NAME) and the value has to be a
+/// valid value of `T`, for example a feature with a null-terminated URI.
+pub unsafe fn from_foreign<U, T: RawType>(value: &U) -> &T {
+    check_layout::<T, U>();
+    &*(value as *const U as *const T)
+}
+
+/// Reinterpret a mutable reference to a foreign binding as a mutable reference to the type of
+/// lv2rs.
+///
+/// # Safety
+///
+/// The requirements of [`from_foreign`](fn.from_foreign.html) apply. In addition, every value of
+/// `T` that is written through the returned reference has to be a valid value of `U`.
+pub unsafe fn from_foreign_mut<U, T: RawType>(value: &mut U) -> &mut T {
+    check_layout::<T, U>();
+    &mut *(value as *mut U as *mut T)
+}
+
+/// Reinterpret a features list as a list of references to a foreign binding of `LV2_Feature`.
+///
+/// Hosts pass the features as a null-terminated array of pointers, which other bindings often
+/// expect as a slice of pointers or references.
+///
+/// # Safety
+///
+/// `U` has to be a binding of `LV2_Feature`, with the requirements of
+/// [`to_foreign`](fn.to_foreign.html).
+pub unsafe fn features_to_foreign<U>(features: &crate::FeaturesList) -> &[&'static U] {
+    check_layout::<Feature, U>();
+    &*(features as *const crate::FeaturesList as *const [&'static U])
+}
+
+#[cfg(test)]
+mod test {
+    use crate::raw::*;
+    use core::ffi::{c_char, c_void};
+
+    #[repr(C)]
+    #[allow(non_camel_case_types, non_snake_case)]
+    struct LV2_Feature {
+        URI: *const c_char,
+        data: *mut c_void,
+    }
+
+    #[test]
+    fn test_features() {
+        let mut value = 42u32;
+        let feature = Feature::new(
+            b"http://example.org/feature\0".as_ptr() as *const c_char,
+            &mut value as *mut u32 as *mut c_void,
+        );
+        let features: [&'static Feature; 1] = [unsafe { &*(&feature as *const Feature) }];
+
+        let foreign: &[&LV2_Feature] = unsafe { features_to_foreign(&features) };
+        assert_eq!(1, foreign.len());
+        assert_eq!(feature.uri().unwrap().as_ptr(), foreign[0].URI);
+        assert_eq!(&mut value as *mut u32 as *mut c_void, foreign[0].data);
+
+        let mut foreign = LV2_Feature {
+            URI: foreign[0].URI,
+            data: core::ptr::null_mut(),
+        };
+        let feature: &mut Feature = unsafe { from_foreign_mut(&mut foreign) };
+        assert_eq!(
+            b"http://example.org/feature",
+            feature.uri().unwrap().to_bytes()
+        );
+        assert!(unsafe { feature.data::<u32>() }.is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_layout_mismatch() {
+        let feature = Feature::new(core::ptr::null(), core::ptr::null_mut());
+        let _: &u8 = unsafe { to_foreign(&feature) };
+    }
+}
@@ -0,0 +1,209 @@
+//! Detection of real-time safety violations.
+//!
+//! Plugins that are `lv2:hardRTCapable` must not allocate memory, lock mutexes or do anything
+//! else that may block in their `run` method. This module, which is enabled by the `rt-check`
+//! feature, helps to find such violations while testing a plugin: When the feature is enabled,
+//! every call to `run` of a plugin that is declared as hard real-time capable by its
+//! [`THREADING`](../trait.Plugin.html#associatedconstant.THREADING) is a real-time section and
+//! the [`RtCheckAllocator`](struct.RtCheckAllocator.html) reports all allocations in such a
+//! section.
+//! Blocking operations can't be detected automatically, but code that wraps them may call
+//! [`check_blocking`](fn.check_blocking.html) to report them.
+//!
+//! Violations are collected during the section and handled according to the
+//! [`Reaction`](enum.Reaction.html) when the section ends: By default, the audio thread panics.
+//! Since the functions of the plugin descriptor can't unwind, this aborts the host with a
+//! description of the first violation.
+//!
+//!     extern crate lv2rs_core as lv2core;
+//!
+//!     use lv2core::rt_check::*;
+//!     use std::alloc::System;
+//!
+//!     #[global_allocator]
+//!     static ALLOCATOR: RtCheckAllocator<System> = RtCheckAllocator::new(System);
+//!
+//!     # fn main() {
+//!     set_reaction(Reaction::Ignore);
+//!     let violations = total_violations();
+//!     {
+//!         let _section = RealtimeSection::enter();
+//!         let _buffer: Vec<f32> = Vec::with_capacity(64);
+//!     }
+//!     assert_eq!(violations + 2, total_violations());
+//!     # }
+//!
+//! The checks only take place in the thread that entered the section and the allocator is only
+//! slightly slower outside of it. Still, this feature is meant for debugging and should not be
+//! enabled in releases.
+use std::alloc::{GlobalAlloc, Layout};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+
+/// An operation that is not real-time-safe.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Violation {
+    /// Memory was allocated or reallocated.
+    Allocation,
+    /// Memory was freed.
+    Deallocation,
+    /// A potentially blocking operation, reported with
+    /// [`check_blocking`](fn.check_blocking.html).
+    Blocking(&'static str),
+}
+
+/// What happens when a real-time section with violations ends.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Reaction {
+    /// Panic with a description of the first violation.
+    Panic,
+    /// Print a description of the violations to the standard error output.
+    Print,
+    /// Only count the violations, see [`total_violations`](fn.total_violations.html).
+    Ignore,
+}
+
+static REACTION: AtomicU8 = AtomicU8::new(Reaction::Panic as u8);
+
+static TOTAL_VIOLATIONS: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static IN_SECTION: Cell<bool> = const { Cell::new(false) };
+    static VIOLATIONS: Cell<usize> = const { Cell::new(0) };
+    static FIRST_VIOLATION: Cell<Option<Violation>> = const { Cell::new(None) };
+}
+
+/// Set the reaction to violations for all threads.
+pub fn set_reaction(reaction: Reaction) {
+    REACTION.store(reaction as u8, Ordering::Relaxed);
+}
+
+/// Return the current reaction to violations.
+pub fn reaction() -> Reaction {
+    match REACTION.load(Ordering::Relaxed) {
+        0 => Reaction::Panic,
+        1 => Reaction::Print,
+        _ => Reaction::Ignore,
+    }
+}
+
+/// Return the number of violations in all threads since the start of the program.
+pub fn total_violations() -> usize {
+    TOTAL_VIOLATIONS.load(Ordering::Relaxed)
+}
+
+/// Return `true` if the current thread is in a real-time section.
+pub fn in_realtime_section() -> bool {
+    IN_SECTION.with(Cell::get)
+}
+
+/// Report a violation if the current thread is in a real-time section.
+///
+/// This function doesn't allocate and can therefore be used by allocators.
+pub fn report(violation: Violation) {
+    if !in_realtime_section() {
+        return;
+    }
+    VIOLATIONS.with(|violations| violations.set(violations.get() + 1));
+    FIRST_VIOLATION.with(|first| {
+        if first.get().is_none() {
+            first.set(Some(violation));
+        }
+    });
+    TOTAL_VIOLATIONS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Report a potentially blocking operation, like locking a mutex or a system call.
+///
+/// The description should name the operation, for example `"Mutex::lock"`.
+pub fn check_blocking(description: &'static str) {
+    report(Violation::Blocking(description));
+}
+
+/// Guard of a real-time section.
+///
+/// The section starts when the guard is created with [`enter`](#method.enter) and ends when it is
+/// dropped, which is when the violations are handled. Sections may be nested; Only the outermost
+/// one handles the violations.
+pub struct RealtimeSection {
+    outermost: bool,
+}
+
+impl RealtimeSection {
+    /// Enter a real-time section in the current thread.
+    pub fn enter() -> Self {
+        let outermost = !IN_SECTION.with(|in_section| in_section.replace(true));
+        if outermost {
+            VIOLATIONS.with(|violations| violations.set(0));
+            FIRST_VIOLATION.with(|first| first.set(None));
+        }
+        Self { outermost }
+    }
+}
+
+impl Drop for RealtimeSection {
+    fn drop(&mut self) {
+        if !self.outermost {
+            return;
+        }
+        IN_SECTION.with(|in_section| in_section.set(false));
+        let violations = VIOLATIONS.with(Cell::get);
+        let first = match FIRST_VIOLATION.with(Cell::get) {
+            Some(first) => first,
+            None => return,
+        };
+        match reaction() {
+            Reaction::Panic => {
+                if !std::thread::panicking() {
+                    panic!(
+                        "{} real-time safety violation(s) in the audio thread, the first one was {:?}",
+                        violations, first
+                    );
+                }
+            }
+            Reaction::Print => eprintln!(
+                "{} real-time safety violation(s) in the audio thread, the first one was {:?}",
+                violations, first
+            ),
+            Reaction::Ignore => (),
+        }
+    }
+}
+
+/// A global allocator that reports allocations in real-time sections.
+///
+/// It wraps another allocator, usually `std::alloc::System`, and reports every allocation,
+/// reallocation and deallocation in a real-time section before passing it on. Install it with the
+/// `#[global_allocator]` attribute in the plugin library or in its tests.
+pub struct RtCheckAllocator<A: GlobalAlloc> {
+    inner: A,
+}
+
+impl<A: GlobalAlloc> RtCheckAllocator<A> {
+    /// Wrap an allocator.
+    pub const fn new(inner: A) -> Self {
+        Self { inner }
+    }
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for RtCheckAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        report(Violation::Allocation);
+        self.inner.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        report(Violation::Deallocation);
+        self.inner.dealloc(ptr, layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        report(Violation::Allocation);
+        self.inner.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        report(Violation::Allocation);
+        self.inner.realloc(ptr, layout, new_size)
+    }
+}
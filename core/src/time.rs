@@ -0,0 +1,493 @@
+//! The transport state of the host.
+//!
+//! Hosts inform plugins about the current transport state, like the tempo or the position in the
+//! song, by sending `time:Position` objects to an atom port. These objects are parsed by
+//! `lv2rs-atom`, which fills a [`Position`](struct.Position.html). Plugins pass it to
+//! [`RunContext::update_position`](../struct.RunContext.html#method.update_position), which
+//! keeps the position of the plugin and advances it after every cycle.
+//!
+//! Hosts usually only send a position if something has changed and they don't have to send all
+//! properties every time. Therefore, a new position is [merged](struct.Position.html#method.update)
+//! into the last one.
+//!
+//! Plugins that are synced to the host, like LFOs or step sequencers, need the musical time of
+//! every sample. A [`BeatClock`](struct.BeatClock.html) follows the positions of the host, even
+//! if they arrive in the middle of a cycle, and yields the [`BeatTime`](struct.BeatTime.html) of
+//! every frame:
+//!
+//!     extern crate lv2rs_core as core;
+//!
+//!     use core::time::*;
+//!
+//!     // A clock at 48 kHz, which is rolling at 120 BPM in 4/4.
+//!     let mut clock = BeatClock::new(48000.0);
+//!     clock.update(&Position {
+//!         bar: Some(0),
+//!         bar_beat: Some(0.0),
+//!         beat: Some(0.0),
+//!         beats_per_bar: Some(4.0),
+//!         beats_per_minute: Some(120.0),
+//!         speed: Some(1.0),
+//!         ..Position::default()
+//!     });
+//!
+//!     // Half a second later, the first beat is over.
+//!     let times: Vec<BeatTime> = clock.frames(24000).collect();
+//!     assert_eq!(0.0, times[0].beat);
+//!     assert_eq!(0.5, times[12000].beat_phase());
+//!     assert_eq!(1.0, clock.time().beat);
+//!     assert!(clock.is_rolling());
+//!
+//!     // The host jumps to the third bar and doubles the tempo.
+//!     clock.update(&Position {
+//!         bar: Some(2),
+//!         bar_beat: Some(0.0),
+//!         beat: Some(8.0),
+//!         beats_per_minute: Some(240.0),
+//!         ..Position::default()
+//!     });
+//!     let time = clock.frames(12000).last().unwrap();
+//!     assert_eq!(2, time.bar);
+//!     assert!(time.bar_beat > 0.99 && time.bar_beat < 1.0);
+//!     assert_eq!(0.25, clock.time().bar_phase());
+
+/// The transport state of the host.
+///
+/// Every property is optional since hosts don't have to send all of them.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Position {
+    /// The current bar, counted from zero.
+    pub bar: Option<i64>,
+    /// The beat within the current bar, counted from zero.
+    pub bar_beat: Option<f32>,
+    /// The current beat since the start of the song, counted from zero.
+    pub beat: Option<f64>,
+    /// The note value that counts as a beat, for example 4 for a quarter note.
+    pub beat_unit: Option<i32>,
+    /// The number of beats in a bar.
+    pub beats_per_bar: Option<f32>,
+    /// The tempo, in beats per minute.
+    pub beats_per_minute: Option<f32>,
+    /// The current frame since the start of the song.
+    pub frame: Option<i64>,
+    /// The number of frames in a second.
+    pub frames_per_second: Option<f32>,
+    /// The speed of the transport relative to the normal speed; Zero means that it is stopped.
+    pub speed: Option<f32>,
+}
+
+impl Position {
+    /// Overwrite all properties that are present in the other position.
+    pub fn update(&mut self, other: &Position) {
+        self.bar = other.bar.or(self.bar);
+        self.bar_beat = other.bar_beat.or(self.bar_beat);
+        self.beat = other.beat.or(self.beat);
+        self.beat_unit = other.beat_unit.or(self.beat_unit);
+        self.beats_per_bar = other.beats_per_bar.or(self.beats_per_bar);
+        self.beats_per_minute = other.beats_per_minute.or(self.beats_per_minute);
+        self.frame = other.frame.or(self.frame);
+        self.frames_per_second = other.frames_per_second.or(self.frames_per_second);
+        self.speed = other.speed.or(self.speed);
+    }
+
+    /// Move the position forward by a number of frames.
+    ///
+    /// The frames are scaled by the speed, which means that nothing changes if the speed is
+    /// unknown or zero. The beats are only advanced if the tempo is known. They are calculated with
+    /// the frames per second of the position, or with the sample rate if the host didn't send
+    /// them.
+    pub fn advance(&mut self, n_frames: u32, sample_rate: f64) {
+        let speed = match self.speed {
+            Some(speed) if speed != 0.0 => f64::from(speed),
+            _ => return,
+        };
+        let frames = f64::from(n_frames) * speed;
+        if let Some(frame) = &mut self.frame {
+            *frame += frames as i64;
+        }
+
+        let frames_per_second = self.frames_per_second.map(f64::from).unwrap_or(sample_rate);
+        let beats_per_minute = match self.beats_per_minute {
+            Some(beats_per_minute) if frames_per_second > 0.0 => f64::from(beats_per_minute),
+            _ => return,
+        };
+        let beats = frames * beats_per_minute / 60.0 / frames_per_second;
+        if let Some(beat) = &mut self.beat {
+            *beat += beats;
+        }
+
+        let beats_per_bar = match self.beats_per_bar {
+            Some(beats_per_bar) if beats_per_bar > 0.0 => f64::from(beats_per_bar),
+            _ => return,
+        };
+        if let Some(bar_beat) = &mut self.bar_beat {
+            let mut beat = f64::from(*bar_beat) + beats;
+            let mut bars = (beat / beats_per_bar) as i64;
+            beat -= bars as f64 * beats_per_bar;
+            if beat < 0.0 {
+                beat += beats_per_bar;
+                bars -= 1;
+            }
+            *bar_beat = beat as f32;
+            if let Some(bar) = &mut self.bar {
+                *bar += bars;
+            }
+        }
+    }
+}
+
+/// The musical time of a frame.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BeatTime {
+    /// The beat since the start of the song, counted from zero.
+    pub beat: f64,
+    /// The current bar, counted from zero.
+    pub bar: i64,
+    /// The beat within the current bar, counted from zero.
+    pub bar_beat: f64,
+    /// The number of beats in a bar.
+    pub beats_per_bar: f64,
+}
+
+impl BeatTime {
+    /// Return the position within the current beat, between `0.0` and `1.0`.
+    pub fn beat_phase(&self) -> f64 {
+        self.phase(1.0)
+    }
+
+    /// Return the position within the current bar, between `0.0` and `1.0`.
+    pub fn bar_phase(&self) -> f64 {
+        if self.beats_per_bar > 0.0 {
+            self.bar_beat / self.beats_per_bar
+        } else {
+            0.0
+        }
+    }
+
+    /// Return the position within a cycle of the given length in beats, between `0.0` and `1.0`.
+    ///
+    /// For example, an LFO that completes a cycle every two bars of 4/4 uses a length of 8 beats.
+    /// The cycles are aligned with the start of the song.
+    pub fn phase(&self, beats_per_cycle: f64) -> f64 {
+        if beats_per_cycle > 0.0 {
+            let cycles = self.beat / beats_per_cycle;
+            cycles - floor(cycles)
+        } else {
+            0.0
+        }
+    }
+
+    /// Return the index of the current step, if a step has the given length in beats.
+    ///
+    /// For example, the steps of a sequencer that plays sixteenth notes in 4/4 have a length of
+    /// a quarter beat. A new step starts when the index changes.
+    pub fn step(&self, beats_per_step: f64) -> i64 {
+        if beats_per_step > 0.0 {
+            floor(self.beat / beats_per_step) as i64
+        } else {
+            0
+        }
+    }
+}
+
+/// A clock that yields the musical time of every frame.
+///
+/// The clock is stopped and at the start of the song until the host sends a position, which is
+/// passed to [`update`](#method.update). Positions are applied at the current frame of the clock,
+/// which means that a position that arrives in the middle of a cycle has to be passed after the
+/// frames before it were taken from [`frames`](#method.frames). Tempo changes and transport jumps
+/// are therefore handled sample-accurately.
+///
+/// The times are calculated from the last position and the number of frames since then, so no
+/// rounding errors accumulate over long stretches with the same tempo. Until the host sends
+/// them, the clock assumes a tempo of 120 beats per minute and four beats per bar.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BeatClock {
+    anchor: BeatTime,
+    frames: u64,
+    beats_per_minute: f64,
+    frames_per_second: f64,
+    speed: f64,
+}
+
+impl BeatClock {
+    /// Create a stopped clock, which uses the sample rate until the host sends its frames per
+    /// second.
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            anchor: BeatTime {
+                beat: 0.0,
+                bar: 0,
+                bar_beat: 0.0,
+                beats_per_bar: 4.0,
+            },
+            frames: 0,
+            beats_per_minute: 120.0,
+            frames_per_second: sample_rate,
+            speed: 0.0,
+        }
+    }
+
+    /// Apply a position the host has sent at the current frame.
+    ///
+    /// Properties that aren't present are kept. If the position contains a beat, but no beat
+    /// within the bar, the bar and the beat within it are derived from the beat and vice versa.
+    pub fn update(&mut self, position: &Position) {
+        self.anchor = self.time();
+        self.frames = 0;
+
+        if let Some(beats_per_bar) = position.beats_per_bar {
+            if beats_per_bar > 0.0 {
+                self.anchor.beats_per_bar = f64::from(beats_per_bar);
+            }
+        }
+        if let Some(beats_per_minute) = position.beats_per_minute {
+            self.beats_per_minute = f64::from(beats_per_minute);
+        }
+        if let Some(frames_per_second) = position.frames_per_second {
+            if frames_per_second > 0.0 {
+                self.frames_per_second = f64::from(frames_per_second);
+            }
+        }
+        if let Some(speed) = position.speed {
+            self.speed = f64::from(speed);
+        }
+
+        let time = &mut self.anchor;
+        match (position.beat, position.bar_beat) {
+            (Some(beat), Some(bar_beat)) => {
+                time.beat = beat;
+                time.bar_beat = f64::from(bar_beat);
+                time.bar = position
+                    .bar
+                    .unwrap_or_else(|| floor(beat / time.beats_per_bar) as i64);
+            }
+            (Some(beat), None) => {
+                let bar = floor(beat / time.beats_per_bar);
+                time.beat = beat;
+                time.bar = bar as i64;
+                time.bar_beat = beat - bar * time.beats_per_bar;
+            }
+            (None, Some(bar_beat)) => {
+                if let Some(bar) = position.bar {
+                    time.bar = bar;
+                }
+                time.bar_beat = f64::from(bar_beat);
+                time.beat = time.bar as f64 * time.beats_per_bar + time.bar_beat;
+            }
+            (None, None) => {
+                if let Some(bar) = position.bar {
+                    time.bar = bar;
+                }
+            }
+        }
+    }
+
+    /// Return the time of the current frame.
+    pub fn time(&self) -> BeatTime {
+        self.time_at(self.frames)
+    }
+
+    /// Return the tempo, in beats per minute.
+    pub fn beats_per_minute(&self) -> f64 {
+        self.beats_per_minute
+    }
+
+    /// Return the speed of the transport; Zero means that it is stopped.
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    /// Return `true` if the transport is moving.
+    pub fn is_rolling(&self) -> bool {
+        self.speed != 0.0
+    }
+
+    /// Return the number of beats the clock moves per frame, including the speed.
+    pub fn beats_per_frame(&self) -> f64 {
+        if self.frames_per_second > 0.0 {
+            self.beats_per_minute * self.speed / 60.0 / self.frames_per_second
+        } else {
+            0.0
+        }
+    }
+
+    /// Move the clock forward by a number of frames.
+    pub fn advance(&mut self, n_frames: u32) {
+        self.frames += u64::from(n_frames);
+    }
+
+    /// Return an iterator over the times of the next frames, which advances the clock.
+    ///
+    /// The first item is the time of the current frame. When the iterator is dropped, the clock
+    /// is at the frame after the last one, even if not all items were taken.
+    pub fn frames(&mut self, n_frames: u32) -> BeatFrames<'_> {
+        BeatFrames {
+            clock: self,
+            remaining: n_frames,
+        }
+    }
+
+    /// Return the time a number of frames after the last position.
+    fn time_at(&self, frames: u64) -> BeatTime {
+        let mut time = self.anchor;
+        let beats = self.beats_per_frame() * frames as f64;
+        time.beat += beats;
+        time.bar_beat += beats;
+        if time.bar_beat >= time.beats_per_bar || time.bar_beat < 0.0 {
+            let bars = floor(time.bar_beat / time.beats_per_bar);
+            time.bar_beat -= bars * time.beats_per_bar;
+            time.bar += bars as i64;
+        }
+        time
+    }
+}
+
+/// An iterator over the times of consecutive frames.
+///
+/// It is created by [`BeatClock::frames`](struct.BeatClock.html#method.frames).
+pub struct BeatFrames<'a> {
+    clock: &'a mut BeatClock,
+    remaining: u32,
+}
+
+impl<'a> Iterator for BeatFrames<'a> {
+    type Item = BeatTime;
+
+    fn next(&mut self) -> Option<BeatTime> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let time = self.clock.time();
+        self.clock.frames += 1;
+        Some(time)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining as usize, Some(self.remaining as usize))
+    }
+}
+
+impl<'a> ExactSizeIterator for BeatFrames<'a> {}
+
+impl<'a> Drop for BeatFrames<'a> {
+    fn drop(&mut self) {
+        self.clock.advance(self.remaining);
+    }
+}
+
+/// Round down to the next integer, which `core` can't do for floats.
+fn floor(value: f64) -> f64 {
+    let truncated = value as i64 as f64;
+    if truncated > value {
+        truncated - 1.0
+    } else {
+        truncated
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::time::*;
+
+    #[test]
+    fn test_advance() {
+        let mut position = Position {
+            bar: Some(3),
+            bar_beat: Some(3.5),
+            beat: Some(15.5),
+            beats_per_bar: Some(4.0),
+            beats_per_minute: Some(120.0),
+            frame: Some(1000),
+            ..Position::default()
+        };
+
+        // The transport doesn't move without a speed.
+        position.advance(100, 100.0);
+        assert_eq!(Some(1000), position.frame);
+
+        // 100 frames at 100 Hz are one second or two beats.
+        position.speed = Some(1.0);
+        position.advance(100, 100.0);
+        assert_eq!(Some(1100), position.frame);
+        assert_eq!(Some(17.5), position.beat);
+        assert_eq!(Some(1.5), position.bar_beat);
+        assert_eq!(Some(4), position.bar);
+
+        // Playing backwards.
+        position.speed = Some(-1.0);
+        position.frames_per_second = Some(50.0);
+        position.advance(50, 100.0);
+        assert_eq!(Some(1050), position.frame);
+        assert_eq!(Some(15.5), position.beat);
+        assert_eq!(Some(3.5), position.bar_beat);
+        assert_eq!(Some(3), position.bar);
+    }
+
+    #[test]
+    fn test_beat_clock() {
+        // 64 frames per second at 120 BPM are 32 frames per beat.
+        let mut clock = BeatClock::new(64.0);
+        assert!(!clock.is_rolling());
+        clock.advance(100);
+        assert_eq!(0.0, clock.time().beat);
+
+        clock.update(&Position {
+            beats_per_bar: Some(3.0),
+            speed: Some(1.0),
+            ..Position::default()
+        });
+        let times: Vec<BeatTime> = clock.frames(136).collect();
+        assert_eq!(136, times.len());
+        assert_eq!(0.0, times[0].beat);
+        assert_eq!(1.0, times[32].beat);
+        assert_eq!(0.5, times[48].beat_phase());
+        assert_eq!(0, times[64].bar);
+        assert_eq!(2.0, times[64].bar_beat);
+        assert_eq!(1, times[96].bar);
+        assert_eq!(0.0, times[96].bar_beat);
+        assert_eq!(1, clock.time().bar);
+        assert_eq!(1.25, clock.time().bar_beat);
+
+        // The tempo doubles in the middle of a cycle.
+        clock.update(&Position {
+            beats_per_minute: Some(240.0),
+            ..Position::default()
+        });
+        clock.advance(4);
+        assert_eq!(4.5, clock.time().beat);
+        assert_eq!(4, clock.time().step(1.0));
+        assert_eq!(18, clock.time().step(0.25));
+
+        // The transport jumps to a bar and plays backwards.
+        clock.update(&Position {
+            bar: Some(2),
+            bar_beat: Some(1.0),
+            speed: Some(-1.0),
+            ..Position::default()
+        });
+        assert_eq!(7.0, clock.time().beat);
+        {
+            let mut frames = clock.frames(32);
+            assert_eq!(Some(7.0), frames.next().map(|time| time.beat));
+            assert_eq!(31, frames.len());
+        }
+        assert_eq!(5.0, clock.time().beat);
+        assert_eq!(1, clock.time().bar);
+        assert_eq!(2.0, clock.time().bar_beat);
+
+        // The bar is derived from the beat.
+        clock.update(&Position {
+            beat: Some(10.0),
+            speed: Some(0.0),
+            ..Position::default()
+        });
+        clock.advance(100);
+        let time = clock.time();
+        assert_eq!(3, time.bar);
+        assert_eq!(1.0, time.bar_beat);
+        assert_eq!(1.0 / 3.0, time.bar_phase());
+        assert_eq!(0.5, time.phase(4.0));
+    }
+}
@@ -0,0 +1,676 @@
+//! Generation of Turtle descriptions from plugin metadata.
+//!
+//! Hosts discover plugins by reading the `manifest.ttl` of a bundle and the Turtle description of
+//! every plugin, which lists its ports. Writing these files by hand means that every port is
+//! declared twice, once in Rust and once in Turtle, and both have to be kept in sync. Instead,
+//! the metadata of the ports can be declared with `#[port(...)]` attributes in a struct that
+//! derives [`Ports`](../ports/derive.Ports.html). The derive macro collects it in
+//! [`PortCollection::PORTS`](../ports/trait.PortCollection.html#associatedconstant.PORTS) and
+//! a [`PluginInfo`](struct.PluginInfo.html) combines it with the metadata of the plugin. A
+//! [`Bundle`](struct.Bundle.html) then generates the files:
+//!
+//!     extern crate lv2rs_core as lv2core;
+//!
+//!     use lv2core::ports::*;
+//!     use lv2core::ttl::*;
+//!
+//!     #[derive(Ports)]
+//!     struct AmpPorts {
+//!         #[port(name = "Gain", default = 0.0, minimum = -90.0, maximum = 24.0, unit = "db")]
+//!         gain: ControlInputPort,
+//!         #[port(name = "Input")]
+//!         input: AudioInputPort,
+//!         #[port(name = "Output")]
+//!         output: AudioOutputPort,
+//!     }
+//!
+//!     const AMP: PluginInfo = PluginInfo {
+//!         classes: &["AmplifierPlugin"],
+//!         ..PluginInfo::new("http://example.org/Amp", "Amp", AmpPorts::PORTS)
+//!     };
+//!
+//!     let bundle = Bundle::new("amp.so", &[AMP]);
+//!     let manifest = bundle.manifest();
+//!     assert!(manifest.contains("<http://example.org/Amp>"));
+//!     assert!(manifest.contains("lv2:binary <amp.so>"));
+//!
+//!     let (file_name, description) = &bundle.plugin_descriptions()[0];
+//!     assert_eq!("Amp.ttl", file_name);
+//!     assert!(description.contains("lv2:symbol \"gain\""));
+//!     assert!(description.contains("units:unit units:db"));
+//!
+//! The `port` attribute supports the following arguments, which correspond to the fields of
+//! [`PortInfo`](struct.PortInfo.html):
+//!
+//! * `name = "..."` and `symbol = "..."`: The symbol defaults to the name of the field and the
+//!   name defaults to the symbol.
+//! * `default = ...`, `minimum = ...` and `maximum = ...`: Constant expressions that are
+//!   converted to `f32`. The minimum and maximum of a CV port are also its
+//!   [`CVRange`](../ports/struct.CVRange.html).
+//! * `unit = "..."` and `buffer_type = "..."`: The units of the units extension are listed in
+//!   the [`units`](../units/index.html) module.
+//! * `property = "..."`, `supports = "..."` and `scale_point("Label", value)`: These may be given
+//!   several times. Besides the properties of the LV2 core, like `integer`, `enumeration` and
+//!   `toggled`, the properties of the port properties extension, like `logarithmic`, may be
+//!   given by name.
+//! * `designation = "..."`: Defaults to the designation of the port type, which is `latency` for
+//!   the [`LatencyPort`](../ports/struct.LatencyPort.html) and `enabled` for the
+//!   [`EnabledPort`](../ports/struct.EnabledPort.html). The enabled port is declared as a toggle
+//!   with a default of 1 automatically.
+//! * `group = "..."` and `channel = "..."`: The symbol of the port's group, which has to be
+//!   declared in [`PluginInfo::groups`](struct.PluginInfo.html#structfield.groups), and the
+//!   channel the port carries in the group, like `"left"` or `"lowFrequencyEffects"`. The channel
+//!   is declared as the designation of the port. See the [`groups`](../groups/index.html) module
+//!   for details.
+//!
+//! The attributes of a [`PortArray`](../ports/struct.PortArray.html) apply to all of its ports,
+//! whose symbols and names are numbered.
+//!
+//! Since the metadata is defined in the plugin crate, the files can't be generated by a build
+//! script. Instead, write them in a test or a small binary of the plugin crate, for example
+//! with `std::fs::write` for every file of [`Bundle::files`](struct.Bundle.html#method.files).
+//!
+//! Crates that ship many small plugins may register them with the
+//! [`plugin_suite!`](../macro.plugin_suite.html) macro instead, which creates a
+//! [`Suite`](struct.Suite.html). It describes all plugins in a single `manifest.ttl`.
+//!
+//! The metadata is useful at runtime, too: A plugin that returns its `PluginInfo` in
+//! [`Plugin::info`](../trait.Plugin.html#method.info) can be inspected by Rust hosts that embed
+//! it via [`Descriptor::describe`](../struct.Descriptor.html#method.describe).
+use crate::groups::{ChannelLayout, GroupKind};
+use crate::ports::PortKind;
+use crate::units::Unit;
+use crate::Threading;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+
+/// A labeled value of a control port.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ScalePoint {
+    pub label: &'static str,
+    pub value: f32,
+}
+
+/// The metadata of a port.
+///
+/// Properties, units, buffer types and supported types are URIs. Names without a colon are
+/// treated as names in the namespace of the LV2 core, the units extension or the atom extension,
+/// respectively. For example, the unit `"db"` is expanded to `units:db`. Properties of the port
+/// properties extension, like `"logarithmic"`, are expanded to its namespace.
+///
+/// Besides generating Turtle, the metadata can be used by generic UIs, for example to display a
+/// value with its unit or the label of its scale point.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PortInfo {
+    pub index: u32,
+    pub symbol: &'static str,
+    pub name: &'static str,
+    pub kind: PortKind,
+    pub default: Option<f32>,
+    pub minimum: Option<f32>,
+    pub maximum: Option<f32>,
+    pub unit: Option<&'static str>,
+    pub scale_points: &'static [ScalePoint],
+    pub properties: &'static [&'static str],
+    pub buffer_type: Option<&'static str>,
+    pub supports: &'static [&'static str],
+    pub designation: Option<&'static str>,
+    pub group: Option<&'static str>,
+}
+
+/// The metadata of a port group.
+///
+/// The URI of the group is the URI of its plugin, followed by `#` and the symbol of the group.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct GroupInfo {
+    pub symbol: &'static str,
+    pub name: &'static str,
+    pub kind: GroupKind,
+    pub layout: ChannelLayout,
+}
+
+/// The metadata of a plugin.
+///
+/// Create it with [`new`](#method.new) and use the struct update syntax to set the other fields.
+/// Plugin classes and features follow the same rules as the URIs of
+/// [`PortInfo`](struct.PortInfo.html), so `"AmplifierPlugin"` is expanded to
+/// `lv2:AmplifierPlugin`. The [`threading`](#structfield.threading) properties are written as
+/// features of the LV2 core and should be set to the
+/// [`THREADING`](../trait.Plugin.html#associatedconstant.THREADING) of the plugin type.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PluginInfo {
+    pub uri: &'static str,
+    pub name: &'static str,
+    pub classes: &'static [&'static str],
+    pub ports: &'static [PortInfo],
+    pub required_features: &'static [&'static str],
+    pub optional_features: &'static [&'static str],
+    pub extension_data: &'static [&'static str],
+    pub threading: Threading,
+    pub groups: &'static [GroupInfo],
+    pub main_input: Option<&'static str>,
+    pub main_output: Option<&'static str>,
+}
+
+impl PortInfo {
+    /// Return whether the port has a property, given by its name or its URI.
+    pub fn has_property(&self, property: &str) -> bool {
+        let property = split_property(property);
+        self.properties
+            .iter()
+            .any(|other| split_property(other) == property)
+    }
+
+    /// Return whether the port only takes integer values.
+    pub fn is_integer(&self) -> bool {
+        self.has_property("integer")
+    }
+
+    /// Return whether the port only takes the values of its scale points.
+    pub fn is_enumeration(&self) -> bool {
+        self.has_property("enumeration")
+    }
+
+    /// Return whether the port is a toggle, which is off for values below or equal to zero.
+    pub fn is_toggled(&self) -> bool {
+        self.has_property("toggled")
+    }
+
+    /// Return whether the port should be displayed on a logarithmic scale.
+    pub fn is_logarithmic(&self) -> bool {
+        self.has_property("logarithmic")
+    }
+
+    /// Return the unit of the port, if it is a unit of the units extension.
+    pub fn unit(&self) -> Option<Unit> {
+        self.unit.and_then(Unit::from_name)
+    }
+
+    /// Return the label of the scale point with the given value.
+    pub fn scale_point_label(&self, value: f32) -> Option<&'static str> {
+        self.scale_points
+            .iter()
+            .find(|scale_point| scale_point.value == value)
+            .map(|scale_point| scale_point.label)
+    }
+
+    /// Turn an arbitrary value into a valid value of the port.
+    ///
+    /// The value is snapped to the nearest scale point of an enumeration, rounded for integers and
+    /// toggles and then clamped to the range of the port.
+    pub fn clamp(&self, value: f32) -> f32 {
+        let mut value = value;
+        if self.is_enumeration() && !self.scale_points.is_empty() {
+            let distance = |scale_point: &ScalePoint| {
+                let distance = scale_point.value - value;
+                if distance < 0.0 {
+                    -distance
+                } else {
+                    distance
+                }
+            };
+            let mut nearest = &self.scale_points[0];
+            for scale_point in self.scale_points {
+                if distance(scale_point) < distance(nearest) {
+                    nearest = scale_point;
+                }
+            }
+            value = nearest.value;
+        } else if self.is_integer() || self.is_toggled() {
+            value = if value < 0.0 {
+                (value - 0.5) as i64 as f32
+            } else {
+                (value + 0.5) as i64 as f32
+            };
+        }
+        if let Some(minimum) = self.minimum {
+            value = value.max(minimum);
+        }
+        if let Some(maximum) = self.maximum {
+            value = value.min(maximum);
+        }
+        value
+    }
+}
+
+impl PluginInfo {
+    /// Create the metadata of a plugin without classes, features, threading properties,
+    /// extensions or groups.
+    pub const fn new(uri: &'static str, name: &'static str, ports: &'static [PortInfo]) -> Self {
+        Self {
+            uri,
+            name,
+            classes: &[],
+            ports,
+            required_features: &[],
+            optional_features: &[],
+            extension_data: &[],
+            threading: Threading::new(),
+            groups: &[],
+            main_input: None,
+            main_output: None,
+        }
+    }
+
+    /// Return the metadata of the port with the given index.
+    pub fn port(&self, index: u32) -> Option<&'static PortInfo> {
+        self.ports.iter().find(|port| port.index == index)
+    }
+
+    /// Return the metadata of the port with the given symbol.
+    pub fn port_by_symbol(&self, symbol: &str) -> Option<&'static PortInfo> {
+        self.ports.iter().find(|port| port.symbol == symbol)
+    }
+
+    /// Return the name of the plugin's Turtle file, see [`file_name`](fn.file_name.html).
+    pub fn file_name(&self) -> String {
+        file_name(self.name)
+    }
+
+    /// Generate the Turtle description of the plugin.
+    pub fn to_turtle(&self) -> String {
+        let mut ttl = String::new();
+        ttl.push_str(PREFIXES);
+        self.write_description(&mut ttl);
+        ttl
+    }
+
+    /// Write the description of the plugin without the prefixes.
+    fn write_description(&self, ttl: &mut String) {
+        let _ = writeln!(ttl, "\n<{}>", self.uri);
+        ttl.push_str("    a lv2:Plugin");
+        for class in self.classes {
+            ttl.push_str(", ");
+            ttl.push_str(&expand(class, "lv2"));
+        }
+        ttl.push_str(" ;\n");
+        let _ = write!(ttl, "    doap:name {}", literal(self.name));
+        let mut required_features = self.required_features.to_vec();
+        for feature in self.threading.required_features() {
+            if !required_features.contains(&feature) {
+                required_features.push(feature);
+            }
+        }
+        write_list(ttl, 4, "lv2:requiredFeature", &required_features, "lv2");
+        let mut optional_features = self.optional_features.to_vec();
+        for feature in self.threading.optional_features() {
+            if !optional_features.contains(&feature) {
+                optional_features.push(feature);
+            }
+        }
+        write_list(ttl, 4, "lv2:optionalFeature", &optional_features, "lv2");
+        write_list(ttl, 4, "lv2:extensionData", self.extension_data, "lv2");
+        if let Some(main_input) = self.main_input {
+            let _ = write!(ttl, " ;\n    pg:mainInput <{}#{}>", self.uri, main_input);
+        }
+        if let Some(main_output) = self.main_output {
+            let _ = write!(ttl, " ;\n    pg:mainOutput <{}#{}>", self.uri, main_output);
+        }
+        if !self.ports.is_empty() {
+            ttl.push_str(" ;\n    lv2:port");
+            for (i, port) in self.ports.iter().enumerate() {
+                if i > 0 {
+                    ttl.push_str(" ,");
+                }
+                ttl.push_str(" [\n");
+                write_port(ttl, port, self.uri);
+                ttl.push_str("    ]");
+            }
+        }
+        ttl.push_str(" .\n");
+        for group in self.groups {
+            write_group(ttl, group, self.uri);
+        }
+    }
+}
+
+/// The plugins of a bundle, which share a binary.
+pub struct Bundle<'a> {
+    binary: &'a str,
+    plugins: &'a [PluginInfo],
+}
+
+impl<'a> Bundle<'a> {
+    /// Create a bundle with the file name of the binary and the metadata of its plugins.
+    pub fn new(binary: &'a str, plugins: &'a [PluginInfo]) -> Self {
+        Self { binary, plugins }
+    }
+
+    /// Generate the `manifest.ttl` of the bundle.
+    pub fn manifest(&self) -> String {
+        let mut ttl = String::new();
+        ttl.push_str("@prefix lv2: <http://lv2plug.in/ns/lv2core#> .\n");
+        ttl.push_str("@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .\n");
+        for plugin in self.plugins {
+            let _ = write!(
+                ttl,
+                "\n<{}>\n    a lv2:Plugin ;\n    lv2:binary <{}> ;\n    rdfs:seeAlso <{}> .\n",
+                plugin.uri,
+                self.binary,
+                plugin.file_name()
+            );
+        }
+        ttl
+    }
+
+    /// Generate the Turtle descriptions of the plugins, together with their file names.
+    pub fn plugin_descriptions(&self) -> Vec<(String, String)> {
+        self.plugins
+            .iter()
+            .map(|plugin| (plugin.file_name(), plugin.to_turtle()))
+            .collect()
+    }
+
+    /// Generate all files of the bundle, together with their file names.
+    pub fn files(&self) -> Vec<(String, String)> {
+        let mut files = Vec::with_capacity(self.plugins.len() + 1);
+        files.push((String::from("manifest.ttl"), self.manifest()));
+        files.extend(self.plugin_descriptions());
+        files
+    }
+}
+
+/// The plugins of a suite, which share a binary and a manifest.
+///
+/// A suite is created by the [`plugin_suite!`](../macro.plugin_suite.html) macro, which
+/// registers the plugin types with their URIs. The index of a plugin is its position in the
+/// suite, which is also the index of its descriptor.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Suite {
+    /// Turtle statements that are written once at the start of the manifest, like prefixes or
+    /// the description of the project.
+    pub prologue: &'static str,
+    /// The URIs of the plugins.
+    pub uris: &'static [&'static str],
+}
+
+impl Suite {
+    /// Create a suite with a prologue and the URIs of its plugins.
+    pub const fn new(prologue: &'static str, uris: &'static [&'static str]) -> Self {
+        Self { prologue, uris }
+    }
+
+    /// Return the number of plugins in the suite.
+    pub fn len(&self) -> usize {
+        self.uris.len()
+    }
+
+    /// Return whether the suite has no plugins.
+    pub fn is_empty(&self) -> bool {
+        self.uris.is_empty()
+    }
+
+    /// Return the index of the plugin with the given URI.
+    pub fn index(&self, uri: &str) -> Option<u32> {
+        self.uris
+            .iter()
+            .position(|other| *other == uri)
+            .map(|index| index as u32)
+    }
+
+    /// Return the URI of the plugin with the given index.
+    pub fn uri(&self, index: u32) -> Option<&'static str> {
+        self.uris.get(index as usize).cloned()
+    }
+
+    /// Generate a `manifest.ttl` that contains the descriptions of all plugins.
+    ///
+    /// The metadata of the plugins may be given in any order, but there has to be exactly one
+    /// for every plugin of the suite; Otherwise, an error is returned. The plugins are described
+    /// in the order of the suite, after the prologue.
+    pub fn manifest(&self, binary: &str, plugins: &[PluginInfo]) -> Result<String, ManifestError> {
+        if plugins.len() != self.uris.len() {
+            return Err(ManifestError::PluginCount);
+        }
+        let mut ttl = String::new();
+        ttl.push_str(PREFIXES);
+        if !self.prologue.is_empty() {
+            ttl.push('\n');
+            ttl.push_str(self.prologue.trim_end());
+            ttl.push('\n');
+        }
+        for uri in self.uris {
+            let mut matching = plugins.iter().filter(|plugin| plugin.uri == *uri);
+            let plugin = matching.next().ok_or(ManifestError::Missing(uri))?;
+            if matching.next().is_some() {
+                return Err(ManifestError::Duplicate(uri));
+            }
+            plugin.write_description(&mut ttl);
+            let _ = writeln!(ttl, "\n<{}> lv2:binary <{}> .", uri, binary);
+        }
+        Ok(ttl)
+    }
+}
+
+/// Errors that may occur when the manifest of a [`Suite`](struct.Suite.html) is generated.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ManifestError {
+    /// The number of descriptions doesn't match the number of plugins in the suite.
+    PluginCount,
+    /// There is no description for the plugin with the given URI.
+    Missing(&'static str),
+    /// There are several descriptions for the plugin with the given URI.
+    Duplicate(&'static str),
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ManifestError::PluginCount => {
+                write!(f, "The number of descriptions doesn't match the suite")
+            }
+            ManifestError::Missing(uri) => write!(f, "Missing description of <{}>", uri),
+            ManifestError::Duplicate(uri) => write!(f, "Several descriptions of <{}>", uri),
+        }
+    }
+}
+
+const PREFIXES: &str = "@prefix atom: <http://lv2plug.in/ns/ext/atom#> .
+@prefix doap: <http://usefulinc.com/ns/doap#> .
+@prefix lv2: <http://lv2plug.in/ns/lv2core#> .
+@prefix pg: <http://lv2plug.in/ns/ext/port-groups#> .
+@prefix pprops: <http://lv2plug.in/ns/ext/port-props#> .
+@prefix rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#> .
+@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+@prefix units: <http://lv2plug.in/ns/extension/units#> .
+";
+
+/// The namespace of the port groups extension, whose URIs are abbreviated with the `pg` prefix.
+const PORT_GROUPS: &str = "http://lv2plug.in/ns/ext/port-groups#";
+
+const LV2: &str = "http://lv2plug.in/ns/lv2core#";
+
+/// The namespace of the port properties extension.
+const PORT_PROPS: &str = "http://lv2plug.in/ns/ext/port-props#";
+
+/// The port properties of the port properties extension.
+const PORT_PROPERTIES: &[&str] = &[
+    "causesArtifacts",
+    "continuousCV",
+    "discreteCV",
+    "expensive",
+    "hasStrictBounds",
+    "logarithmic",
+    "notAutomatic",
+    "notOnGUI",
+    "trigger",
+];
+
+/// Split a port property into its namespace and its name.
+///
+/// Unknown URIs are returned as the namespace with an empty name.
+fn split_property(property: &str) -> (&str, &str) {
+    for namespace in [LV2, PORT_PROPS].iter() {
+        if let Some(name) = property.strip_prefix(namespace) {
+            return (*namespace, name);
+        }
+    }
+    if property.contains(':') {
+        (property, "")
+    } else if PORT_PROPERTIES.contains(&property) {
+        (PORT_PROPS, property)
+    } else {
+        (LV2, property)
+    }
+}
+
+/// Expand a name to a prefixed name or enclose a URI in angle brackets.
+fn expand(name: &str, prefix: &str) -> String {
+    if let Some(local_name) = name.strip_prefix(PORT_GROUPS) {
+        format!("pg:{}", local_name)
+    } else if name.contains(':') {
+        format!("<{}>", name)
+    } else {
+        format!("{}:{}", prefix, name)
+    }
+}
+
+/// Return the name of a Turtle file for a resource with the given name.
+///
+/// It consists of the characters of the name that are allowed in file names and the `.ttl`
+/// extension.
+pub fn file_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect();
+    format!("{}.ttl", name)
+}
+
+/// Quote and escape a string literal.
+pub fn literal(value: &str) -> String {
+    let mut literal = String::with_capacity(value.len() + 2);
+    literal.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+/// Format a number as a Turtle decimal.
+fn number(value: f32) -> String {
+    format!("{:?}", value)
+}
+
+/// Write a predicate with a list of objects, if the list isn't empty.
+fn write_list(ttl: &mut String, indent: usize, predicate: &str, objects: &[&str], prefix: &str) {
+    if objects.is_empty() {
+        return;
+    }
+    let _ = write!(ttl, " ;\n{:indent$}{} ", "", predicate, indent = indent);
+    for (i, object) in objects.iter().enumerate() {
+        if i > 0 {
+            ttl.push_str(", ");
+        }
+        ttl.push_str(&expand(object, prefix));
+    }
+}
+
+fn write_port(ttl: &mut String, port: &PortInfo, plugin_uri: &str) {
+    let classes = match port.kind {
+        PortKind::AtomInput => "lv2:InputPort, atom:AtomPort",
+        PortKind::AtomOutput => "lv2:OutputPort, atom:AtomPort",
+        PortKind::AudioInput => "lv2:InputPort, lv2:AudioPort",
+        PortKind::AudioOutput => "lv2:OutputPort, lv2:AudioPort",
+        PortKind::CVInput => "lv2:InputPort, lv2:CVPort",
+        PortKind::CVOutput => "lv2:OutputPort, lv2:CVPort",
+        PortKind::ControlInput => "lv2:InputPort, lv2:ControlPort",
+        PortKind::ControlOutput => "lv2:OutputPort, lv2:ControlPort",
+    };
+    let _ = write!(
+        ttl,
+        "        a {} ;\n        lv2:index {} ;\n        lv2:symbol {} ;\n        lv2:name {}",
+        classes,
+        port.index,
+        literal(port.symbol),
+        literal(port.name)
+    );
+    // The enabled port of a plugin is a toggle that defaults to being enabled.
+    let enabled = port.designation == Some("enabled");
+    let fixed = |value: f32| if enabled { Some(value) } else { None };
+    let values = [
+        ("lv2:default", port.default.or(fixed(1.0))),
+        ("lv2:minimum", port.minimum.or(fixed(0.0))),
+        ("lv2:maximum", port.maximum.or(fixed(1.0))),
+    ];
+    for (predicate, value) in values.iter() {
+        if let Some(value) = value {
+            let _ = write!(ttl, " ;\n        {} {}", predicate, number(*value));
+        }
+    }
+    if let Some(group) = port.group {
+        let _ = write!(ttl, " ;\n        pg:group <{}#{}>", plugin_uri, group);
+    }
+    if let Some(designation) = port.designation {
+        let _ = write!(
+            ttl,
+            " ;\n        lv2:designation {}",
+            expand(designation, "lv2")
+        );
+    }
+    if let Some(unit) = port.unit {
+        let _ = write!(ttl, " ;\n        units:unit {}", expand(unit, "units"));
+    }
+    if let Some(buffer_type) = port.buffer_type {
+        let _ = write!(
+            ttl,
+            " ;\n        atom:bufferType {}",
+            expand(buffer_type, "atom")
+        );
+    }
+    let toggled = enabled && !port.is_toggled();
+    if !port.properties.is_empty() || toggled {
+        ttl.push_str(" ;\n        lv2:portProperty ");
+        for (i, property) in port.properties.iter().enumerate() {
+            if i > 0 {
+                ttl.push_str(", ");
+            }
+            match split_property(property) {
+                (LV2, name) => ttl.push_str(&expand(name, "lv2")),
+                (PORT_PROPS, name) => ttl.push_str(&expand(name, "pprops")),
+                _ => ttl.push_str(&expand(property, "lv2")),
+            }
+        }
+        if toggled {
+            if !port.properties.is_empty() {
+                ttl.push_str(", ");
+            }
+            ttl.push_str("lv2:toggled");
+        }
+    }
+    write_list(ttl, 8, "atom:supports", port.supports, "atom");
+    for scale_point in port.scale_points {
+        let _ = write!(
+            ttl,
+            " ;\n        lv2:scalePoint [ rdfs:label {} ; rdf:value {} ]",
+            literal(scale_point.label),
+            number(scale_point.value)
+        );
+    }
+    ttl.push('\n');
+}
+
+fn write_group(ttl: &mut String, group: &GroupInfo, plugin_uri: &str) {
+    let kind = match group.kind {
+        GroupKind::Input => "pg:InputGroup",
+        GroupKind::Output => "pg:OutputGroup",
+    };
+    let _ = write!(
+        ttl,
+        "\n<{}#{}>\n    a {}, {} ;\n    lv2:symbol {} ;\n    lv2:name {} .\n",
+        plugin_uri,
+        group.symbol,
+        expand(group.layout.group_class(), "pg"),
+        kind,
+        literal(group.symbol),
+        literal(group.name)
+    );
+}
@@ -0,0 +1,145 @@
+//! Units of control ports.
+//!
+//! The [units extension](http://lv2plug.in/ns/extension/units) defines units that hosts use to
+//! display the values of control ports. A port declares its unit with the `unit` attribute of
+//! [`Ports`](../ports/derive.Ports.html), using the name of the unit in the extension, like
+//! `"hz"` or `"semitone12TET"`. Generic UIs can then look the unit up in the port's
+//! [`PortInfo`](../ttl/struct.PortInfo.html#method.unit):
+//!
+//!     extern crate lv2rs_core as lv2core;
+//!
+//!     use lv2core::ports::*;
+//!     use lv2core::units::Unit;
+//!
+//!     #[derive(Ports)]
+//!     struct FilterPorts {
+//!         #[port(name = "Cutoff", default = 1000, minimum = 20, maximum = 20000, unit = "hz")]
+//!         #[port(property = "logarithmic")]
+//!         cutoff: ControlInputPort,
+//!     }
+//!
+//!     let cutoff = &FilterPorts::PORTS[0];
+//!     assert_eq!(Some(Unit::Hz), cutoff.unit());
+//!     assert_eq!("Hz", Unit::Hz.symbol());
+//!     assert!(cutoff.is_logarithmic());
+
+/// A unit of the units extension.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Unit {
+    Bar,
+    Beat,
+    Bpm,
+    Cent,
+    Coef,
+    Db,
+    Degree,
+    Frame,
+    Hz,
+    Khz,
+    Mhz,
+    MidiNote,
+    Min,
+    Ms,
+    Oct,
+    Pc,
+    S,
+    Semitone12TET,
+}
+
+const UNITS: [Unit; 18] = [
+    Unit::Bar,
+    Unit::Beat,
+    Unit::Bpm,
+    Unit::Cent,
+    Unit::Coef,
+    Unit::Db,
+    Unit::Degree,
+    Unit::Frame,
+    Unit::Hz,
+    Unit::Khz,
+    Unit::Mhz,
+    Unit::MidiNote,
+    Unit::Min,
+    Unit::Ms,
+    Unit::Oct,
+    Unit::Pc,
+    Unit::S,
+    Unit::Semitone12TET,
+];
+
+/// The namespace of the units extension.
+const NAMESPACE: &str = "http://lv2plug.in/ns/extension/units#";
+
+impl Unit {
+    /// Return the name of the unit in the units extension.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Unit::Bar => "bar",
+            Unit::Beat => "beat",
+            Unit::Bpm => "bpm",
+            Unit::Cent => "cent",
+            Unit::Coef => "coef",
+            Unit::Db => "db",
+            Unit::Degree => "degree",
+            Unit::Frame => "frame",
+            Unit::Hz => "hz",
+            Unit::Khz => "khz",
+            Unit::Mhz => "mhz",
+            Unit::MidiNote => "midiNote",
+            Unit::Min => "min",
+            Unit::Ms => "ms",
+            Unit::Oct => "oct",
+            Unit::Pc => "pc",
+            Unit::S => "s",
+            Unit::Semitone12TET => "semitone12TET",
+        }
+    }
+
+    /// Return the symbol that is displayed after a value, like `"dB"` or `"%"`.
+    pub const fn symbol(self) -> &'static str {
+        match self {
+            Unit::Bar => "bars",
+            Unit::Beat => "beats",
+            Unit::Bpm => "BPM",
+            Unit::Cent => "ct",
+            Unit::Coef => "",
+            Unit::Db => "dB",
+            Unit::Degree => "°",
+            Unit::Frame => "frames",
+            Unit::Hz => "Hz",
+            Unit::Khz => "kHz",
+            Unit::Mhz => "MHz",
+            Unit::MidiNote => "note",
+            Unit::Min => "min",
+            Unit::Ms => "ms",
+            Unit::Oct => "oct",
+            Unit::Pc => "%",
+            Unit::S => "s",
+            Unit::Semitone12TET => "semi",
+        }
+    }
+
+    /// Find a unit by its name or its URI.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.strip_prefix(NAMESPACE).unwrap_or(name);
+        UNITS.iter().cloned().find(|unit| unit.name() == name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::units::*;
+
+    #[test]
+    fn test_names() {
+        for unit in UNITS.iter() {
+            assert_eq!(Some(*unit), Unit::from_name(unit.name()));
+        }
+        assert_eq!(
+            Some(Unit::Semitone12TET),
+            Unit::from_name("http://lv2plug.in/ns/extension/units#semitone12TET")
+        );
+        assert_eq!(None, Unit::from_name("http://example.org/units#db"));
+        assert_eq!(None, Unit::from_name("decibel"));
+    }
+}
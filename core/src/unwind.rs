@@ -0,0 +1,25 @@
+//! Containment of panics at the boundary to the host.
+//!
+//! Panics must not unwind into the host, which is written in C. With the `catch-panics` feature,
+//! they are caught by the functions of the plugin descriptor. Without it, panics abort the host.
+use alloc::string::String;
+
+/// Call a function and return the message of its panic as an error.
+#[cfg(feature = "catch-panics")]
+pub(crate) fn catch<R>(function: impl FnOnce() -> R) -> Result<R, String> {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(function)).map_err(|payload| {
+        if let Some(message) = payload.downcast_ref::<&str>() {
+            String::from(*message)
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            String::from("unknown panic payload")
+        }
+    })
+}
+
+/// Call a function.
+#[cfg(not(feature = "catch-panics"))]
+pub(crate) fn catch<R>(function: impl FnOnce() -> R) -> Result<R, String> {
+    Ok(function())
+}
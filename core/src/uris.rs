@@ -1,87 +1,224 @@
 //! LV2 URIs
+//!
+//! Every URI is a [`Uri`](struct.Uri.html) constant, which is checked when the crate is compiled
+//! and converted to a `&CStr` or a `&str` without any unsafe code:
+//!
+//!     extern crate lv2rs_core as core;
+//!
+//!     use core::uris::{self, Uri};
+//!
+//!     const GAIN_URI: Uri = Uri::new(b"https://example.org/plugin#gain\0");
+//!
+//!     assert_eq!("https://example.org/plugin#gain", GAIN_URI.as_str());
+//!     assert_eq!(b"http://lv2plug.in/ns/lv2core#AudioPort", uris::AUDIO_PORT_URI.to_bytes());
+//!
+//! Byte strings without a terminating null byte are rejected by the compiler:
+//!
+//! ```compile_fail
+//! extern crate lv2rs_core as core;
+//!
+//! const GAIN_URI: core::uris::Uri = core::uris::Uri::new(b"https://example.org/plugin#gain");
+//! # fn main() {}
+//! ```
+use core::ffi::{c_char, CStr};
+use core::fmt;
+use core::ops::Deref;
 
-pub const URI: &[u8] = b"http://lv2plug.in/ns/lv2core\0";
-pub const ALLPASS_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#AllpassPlugin\0";
-pub const AMPLIFIER_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#AmplifierPlugin\0";
-pub const ANALYSER_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#AnalyserPlugin\0";
-pub const AUDIO_PORT_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#AudioPort\0";
-pub const BANDPASS_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#BandpassPlugin\0";
-pub const CV_PORT_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#CVPort\0";
-pub const CHORUS_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#ChorusPlugin\0";
-pub const COM_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#CombPlugin\0";
-pub const COMPRESSOR_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#CompressorPlugin\0";
-pub const CONSTANT_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#ConstantPlugin\0";
-pub const CONTROL_PORT_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#ControlPort\0";
-pub const CONVERTER_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#ConverterPlugin\0";
-pub const DELAY_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#DelayPlugin\0";
-pub const DISTORTION_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#DistortionPlugin\0";
-pub const DYNAMICS_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#DynamicsPlugin\0";
-pub const EQ_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#EQPlugin\0";
-pub const ENVELOPE_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#EnvelopePlugin\0";
-pub const EXPANDER_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#ExpanderPlugin\0";
-pub const EXTENSION_DATA: &[u8] = b"http://lv2plug.in/ns/lv2core#ExtensionData\0";
-pub const FEATURE_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#Feature\0";
-pub const FILER_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#FilterPlugin\0";
-pub const FLANGER_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#FlangerPlugin\0";
-pub const FUNCTION_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#FunctionPlugin\0";
-pub const GATE_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#GatePlugin\0";
-pub const GENERATOR_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#GeneratorPlugin\0";
-pub const HIGHPASS_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#HighpassPlugin\0";
-pub const INPUT_PORT_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#InputPort\0";
-pub const INSTRUMENT_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#InstrumentPlugin\0";
-pub const LIMITER_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#LimiterPlugin\0";
-pub const LOWPASS_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#LowpassPlugin\0";
-pub const MIXER_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#MixerPlugin\0";
-pub const MODULATOR_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#ModulatorPlugin\0";
-pub const MULTI_EQ_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#MultiEQPlugin\0";
-pub const OSCILLATOR_PLUGIN: &[u8] = b"http://lv2plug.in/ns/lv2core#OscillatorPlugin\0";
-pub const OUTPUT_PORT_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#OutputPort\0";
-pub const PARA_EQ_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#ParaEQPlugin\0";
-pub const PHASER_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#PhaserPlugin\0";
-pub const PITCH_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#PitchPlugin\0";
-pub const PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#Plugin\0";
-pub const PLUGIN_BASE_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#PluginBase\0";
-pub const POINT_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#Point\0";
-pub const UPPER_PORT_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#Port\0";
-pub const UPPER_PORT_PROPERTY_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#PortProperty\0";
-pub const RESOURCE_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#Resource\0";
-pub const REVERB_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#ReverbPlugin\0";
-pub const UPPER_SCALE_POINT_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#ScalePoint\0";
-pub const SIMULATOR_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#SimulatorPlugin\0";
-pub const SPATIAL_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#SpatialPlugin\0";
-pub const SPECIFICATION_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#Specification\0";
-pub const SPECTRAL_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#SpectralPlugin\0";
-pub const UTILITY_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#UtilityPlugin\0";
-pub const WAVESHAPER_PLUGIN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#WaveshaperPlugin\0";
-pub const APPLIES_TO_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#appliesTo\0";
-pub const BINARY_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#binary\0";
-pub const CONNECTION_OPTIONAL_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#connectionOptional\0";
-pub const CONTROL_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#control\0";
-pub const DEFAULT_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#default\0";
-pub const DESIGNATION_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#designation\0";
-pub const DOCUMENTATION_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#documentation\0";
-pub const ENUMERATION_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#enumeration\0";
-pub const EXTENSION_DATA_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#extensionData\0";
-pub const FREE_WHEELING_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#freeWheeling\0";
-pub const HARD_RT_CAPABLE_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#hardRTCapable\0";
-pub const IN_PLACE_BROKEN_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#inPlaceBroken\0";
-pub const INDEX_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#index\0";
-pub const INTEGER_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#integer\0";
-pub const IS_LIVE_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#isLive\0";
-pub const LATENCY_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#latency\0";
-pub const MAXIMUM_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#maximum\0";
-pub const MICRO_VERSION_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#microVersion\0";
-pub const MINIMUM_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#minimum\0";
-pub const MINOR_VERSION_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#minorVersion\0";
-pub const NAME_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#name\0";
-pub const OPTIONAL_FEATURE_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#optionalFeature\0";
-pub const LOWER_PORT_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#port\0";
-pub const LOWER_PORT_PROPERTY_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#portProperty\0";
-pub const PROJECT_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#project\0";
-pub const PROTOTYPE_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#prototype\0";
-pub const REPORTS_LATENCY_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#reportsLatency\0";
-pub const REQUIRED_FEATURE_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#requiredFeature\0";
-pub const SAMPLE_RATE_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#sampleRate\0";
-pub const LOWER_SCALE_POINT_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#scalePoint\0";
-pub const SYMBOL_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#symbol\0";
-pub const TOGGLED_URI: &[u8] = b"http://lv2plug.in/ns/lv2core#toggled\0";
+/// A null-terminated URI.
+///
+/// A URI is created from a byte string with [`new`](#method.new), which is a `const fn`. If the
+/// byte string isn't null-terminated, contains interior null bytes or isn't valid UTF-8, it panics,
+/// which turns into a compiler error when a constant is defined. The URI dereferences to a
+/// [`CStr`](https://doc.rust-lang.org/std/ffi/struct.CStr.html), which is what the URID map and
+/// the C API of LV2 expect.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Uri {
+    cstr: &'static CStr,
+    string: &'static str,
+}
+
+impl Uri {
+    /// Create a URI from a null-terminated byte string.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the byte string isn't null-terminated, contains interior null bytes
+    /// or isn't valid UTF-8.
+    pub const fn new(bytes: &'static [u8]) -> Self {
+        let cstr = match CStr::from_bytes_with_nul(bytes) {
+            Ok(cstr) => cstr,
+            Err(_) => panic!("URIs have to be null-terminated and must not contain null bytes"),
+        };
+        let string = match core::str::from_utf8(cstr.to_bytes()) {
+            Ok(string) => string,
+            Err(_) => panic!("URIs have to be valid UTF-8"),
+        };
+        Self { cstr, string }
+    }
+
+    /// Return the URI as a `CStr`.
+    pub const fn as_cstr(self) -> &'static CStr {
+        self.cstr
+    }
+
+    /// Return the URI as a string, without the terminating null byte.
+    pub const fn as_str(self) -> &'static str {
+        self.string
+    }
+
+    /// Return the bytes of the URI, including the terminating null byte.
+    pub const fn as_bytes_with_nul(self) -> &'static [u8] {
+        self.cstr.to_bytes_with_nul()
+    }
+
+    /// Return a pointer to the null-terminated URI, as it is passed to C functions.
+    pub const fn as_ptr(self) -> *const c_char {
+        self.cstr.as_ptr()
+    }
+}
+
+impl Deref for Uri {
+    type Target = CStr;
+
+    fn deref(&self) -> &CStr {
+        self.cstr
+    }
+}
+
+impl AsRef<CStr> for Uri {
+    fn as_ref(&self) -> &CStr {
+        self.cstr
+    }
+}
+
+impl From<Uri> for &'static CStr {
+    fn from(uri: Uri) -> Self {
+        uri.cstr
+    }
+}
+
+impl PartialEq<CStr> for Uri {
+    fn eq(&self, other: &CStr) -> bool {
+        self.cstr == other
+    }
+}
+
+impl PartialEq<&CStr> for Uri {
+    fn eq(&self, other: &&CStr) -> bool {
+        self.cstr == *other
+    }
+}
+
+impl PartialEq<Uri> for CStr {
+    fn eq(&self, other: &Uri) -> bool {
+        self == other.cstr
+    }
+}
+
+impl PartialEq<Uri> for &CStr {
+    fn eq(&self, other: &Uri) -> bool {
+        *self == other.cstr
+    }
+}
+
+impl fmt::Display for Uri {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.string)
+    }
+}
+
+pub const URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core\0");
+pub const ALLPASS_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#AllpassPlugin\0");
+pub const AMPLIFIER_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#AmplifierPlugin\0");
+pub const ANALYSER_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#AnalyserPlugin\0");
+pub const AUDIO_PORT_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#AudioPort\0");
+pub const BANDPASS_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#BandpassPlugin\0");
+pub const CV_PORT_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#CVPort\0");
+pub const CHORUS_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#ChorusPlugin\0");
+pub const COM_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#CombPlugin\0");
+pub const COMPRESSOR_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#CompressorPlugin\0");
+pub const CONSTANT_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#ConstantPlugin\0");
+pub const CONTROL_PORT_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#ControlPort\0");
+pub const CONVERTER_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#ConverterPlugin\0");
+pub const DELAY_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#DelayPlugin\0");
+pub const DISTORTION_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#DistortionPlugin\0");
+pub const DYNAMICS_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#DynamicsPlugin\0");
+pub const EQ_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#EQPlugin\0");
+pub const ENVELOPE_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#EnvelopePlugin\0");
+pub const EXPANDER_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#ExpanderPlugin\0");
+pub const EXTENSION_DATA: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#ExtensionData\0");
+pub const FEATURE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#Feature\0");
+pub const FILER_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#FilterPlugin\0");
+pub const FLANGER_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#FlangerPlugin\0");
+pub const FUNCTION_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#FunctionPlugin\0");
+pub const GATE_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#GatePlugin\0");
+pub const GENERATOR_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#GeneratorPlugin\0");
+pub const HIGHPASS_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#HighpassPlugin\0");
+pub const INPUT_PORT_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#InputPort\0");
+pub const INSTRUMENT_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#InstrumentPlugin\0");
+pub const LIMITER_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#LimiterPlugin\0");
+pub const LOWPASS_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#LowpassPlugin\0");
+pub const MIXER_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#MixerPlugin\0");
+pub const MODULATOR_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#ModulatorPlugin\0");
+pub const MULTI_EQ_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#MultiEQPlugin\0");
+pub const OSCILLATOR_PLUGIN: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#OscillatorPlugin\0");
+pub const OUTPUT_PORT_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#OutputPort\0");
+pub const PARA_EQ_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#ParaEQPlugin\0");
+pub const PHASER_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#PhaserPlugin\0");
+pub const PITCH_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#PitchPlugin\0");
+pub const PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#Plugin\0");
+pub const PLUGIN_BASE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#PluginBase\0");
+pub const POINT_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#Point\0");
+pub const UPPER_PORT_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#Port\0");
+pub const UPPER_PORT_PROPERTY_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#PortProperty\0");
+pub const RESOURCE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#Resource\0");
+pub const REVERB_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#ReverbPlugin\0");
+pub const UPPER_SCALE_POINT_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#ScalePoint\0");
+pub const SIMULATOR_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#SimulatorPlugin\0");
+pub const SPATIAL_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#SpatialPlugin\0");
+pub const SPECIFICATION_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#Specification\0");
+pub const SPECTRAL_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#SpectralPlugin\0");
+pub const UTILITY_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#UtilityPlugin\0");
+pub const WAVESHAPER_PLUGIN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#WaveshaperPlugin\0");
+pub const APPLIES_TO_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#appliesTo\0");
+pub const BINARY_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#binary\0");
+pub const CONNECTION_OPTIONAL_URI: Uri =
+    Uri::new(b"http://lv2plug.in/ns/lv2core#connectionOptional\0");
+pub const CONTROL_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#control\0");
+pub const DEFAULT_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#default\0");
+pub const DESIGNATION_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#designation\0");
+pub const DOCUMENTATION_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#documentation\0");
+pub const ENABLED_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#enabled\0");
+pub const ENUMERATION_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#enumeration\0");
+pub const EXTENSION_DATA_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#extensionData\0");
+pub const FREE_WHEELING_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#freeWheeling\0");
+pub const HARD_RT_CAPABLE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#hardRTCapable\0");
+pub const IN_PLACE_BROKEN_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#inPlaceBroken\0");
+pub const INDEX_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#index\0");
+pub const INTEGER_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#integer\0");
+pub const IS_LIVE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#isLive\0");
+pub const LATENCY_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#latency\0");
+pub const MAXIMUM_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#maximum\0");
+pub const MICRO_VERSION_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#microVersion\0");
+pub const MINIMUM_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#minimum\0");
+pub const MINOR_VERSION_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#minorVersion\0");
+pub const NAME_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#name\0");
+pub const OPTIONAL_FEATURE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#optionalFeature\0");
+pub const LOWER_PORT_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#port\0");
+pub const LOWER_PORT_PROPERTY_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#portProperty\0");
+pub const PROJECT_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#project\0");
+pub const PROTOTYPE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#prototype\0");
+pub const REPORTS_LATENCY_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#reportsLatency\0");
+pub const REQUIRED_FEATURE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#requiredFeature\0");
+pub const SAMPLE_RATE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#sampleRate\0");
+pub const LOWER_SCALE_POINT_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#scalePoint\0");
+pub const SYMBOL_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#symbol\0");
+pub const TOGGLED_URI: Uri = Uri::new(b"http://lv2plug.in/ns/lv2core#toggled\0");
+
+/// The URI that the metadata of plugins written with this crate is queried with.
+///
+/// It isn't part of LV2. Calling `extension_data` with it returns a pointer to the
+/// [`PluginInfo`](../ttl/struct.PluginInfo.html) of the plugin, or null. Use
+/// [`Descriptor::describe`](../struct.Descriptor.html#method.describe) instead of calling it
+/// directly.
+pub const INTROSPECTION_URI: Uri = Uri::new(b"https://github.com/Janonard/lv2rs#introspection\0");
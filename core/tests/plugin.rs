@@ -1,10 +1,11 @@
 extern crate lv2rs_core as core;
 
-use std::ffi::CStr;
-
 use core::ports::*;
+use core::uris::Uri;
+use std::ffi::CStr;
 use std::os::raw::*;
 use std::ptr::{null, null_mut};
+use std::sync::Mutex;
 
 /// A simple test plugin.
 ///
@@ -20,12 +21,10 @@ struct TestPlugin {
 
 impl core::Plugin for TestPlugin {
     fn instantiate(
-        _descriptor: &core::Descriptor,
-        _rate: f64,
-        _bundle_path: &CStr,
+        _context: &core::InstantiationContext,
         _features: Option<&core::FeaturesList>,
-    ) -> Option<Self> {
-        Some(Self {
+    ) -> Result<Self, core::InstantiateError> {
+        Ok(Self {
             audio_in: AudioInputPort::new(),
             audio_out: AudioOutputPort::new(),
             parameter_in: ParameterInputPort::new(),
@@ -43,9 +42,9 @@ impl core::Plugin for TestPlugin {
         }
     }
 
-    fn run(&mut self, n_samples: u32) {
-        let audio_in = unsafe { self.audio_in.as_slice(n_samples) }.unwrap();
-        let audio_out = unsafe { self.audio_out.as_slice(n_samples) }.unwrap();
+    fn run(&mut self, context: &mut core::RunContext) {
+        let audio_in = unsafe { self.audio_in.as_slice(context.n_samples()) }.unwrap();
+        let audio_out = unsafe { self.audio_out.as_slice(context.n_samples()) }.unwrap();
         let parameter_in = unsafe { self.parameter_in.get() }.unwrap();
         let parameter_out = unsafe { self.parameter_out.get_mut() }.unwrap();
 
@@ -54,11 +53,27 @@ impl core::Plugin for TestPlugin {
         }
 
         let sum: f32 = audio_out.iter().map(|sample| sample.powi(2)).sum();
-        let sum = (sum / n_samples as f32).sqrt();
+        let sum = (sum / context.n_samples() as f32).sqrt();
         *parameter_out = sum;
     }
+
+    fn extension_data(uri: &CStr) -> Option<&'static dyn core::ExtensionData> {
+        core::extension_data!(uri, [(TEST_EXTENSION_URI, &TEST_INTERFACE)])
+    }
 }
 
+const TEST_EXTENSION_URI: Uri = Uri::new(b"http://example.org/TestExtension\0");
+
+/// The interface of a test extension.
+#[repr(C)]
+struct TestInterface {
+    answer: u32,
+}
+
+impl core::ExtensionData for TestInterface {}
+
+static TEST_INTERFACE: TestInterface = TestInterface { answer: 42 };
+
 core::lv2_main!(core, TestPlugin, b"http://example.org/TestPlugin\0");
 
 struct TestHost {
@@ -154,6 +169,397 @@ fn test_plugin() {
     let rms: f32 = (rms / host.audio_output.len() as f32).sqrt();
     assert_eq!(host.parameter_output, rms);
 
+    // extension_data.
+    let interface = unsafe { (descriptor_ref.extension_data)(TEST_EXTENSION_URI.as_ptr()) };
+    let interface = unsafe { (interface as *const TestInterface).as_ref() }.unwrap();
+    assert_eq!(42, interface.answer);
+    let unknown_uri = b"http://example.org/UnknownExtension\0";
+    assert!(
+        unsafe { (descriptor_ref.extension_data)(unknown_uri.as_ptr() as *const c_char) }.is_null()
+    );
+
     // cleanup.
     unsafe { (descriptor_ref.cleanup)(host.handle) };
 }
+
+/// The lifecycle calls of the `LifecyclePlugin`.
+static LIFECYCLE_CALLS: Mutex<Vec<&str>> = Mutex::new(Vec::new());
+
+/// A plugin that records the calls of its lifecycle functions.
+struct LifecyclePlugin {}
+
+impl core::Plugin for LifecyclePlugin {
+    fn instantiate(
+        _context: &core::InstantiationContext,
+        _features: Option<&core::FeaturesList>,
+    ) -> Result<Self, core::InstantiateError> {
+        Ok(Self {})
+    }
+
+    fn connect_port(&mut self, _port: u32, _data: *mut ()) {}
+
+    fn activate(&mut self) {
+        LIFECYCLE_CALLS.lock().unwrap().push("activate");
+    }
+
+    fn run(&mut self, _context: &mut core::RunContext) {
+        LIFECYCLE_CALLS.lock().unwrap().push("run");
+    }
+
+    fn deactivate(&mut self) {
+        LIFECYCLE_CALLS.lock().unwrap().push("deactivate");
+    }
+}
+
+impl Drop for LifecyclePlugin {
+    fn drop(&mut self) {
+        LIFECYCLE_CALLS.lock().unwrap().push("drop");
+    }
+}
+
+#[test]
+fn test_lifecycle() {
+    let descriptor = unsafe { lv2_descriptor(0) };
+    let handle = unsafe {
+        core::instantiate::<LifecyclePlugin>(
+            descriptor,
+            44100.0,
+            BUNDLE_PATH.as_ptr() as *const c_char,
+            null(),
+        )
+    };
+    assert_ne!(handle, null_mut());
+
+    unsafe {
+        core::activate::<LifecyclePlugin>(handle);
+        // Activating an active plugin is ignored.
+        core::activate::<LifecyclePlugin>(handle);
+        core::run::<LifecyclePlugin>(handle, 64);
+        core::deactivate::<LifecyclePlugin>(handle);
+        // Deactivating an inactive plugin is ignored too.
+        core::deactivate::<LifecyclePlugin>(handle);
+        core::activate::<LifecyclePlugin>(handle);
+        // An active plugin is deactivated before it is cleaned up.
+        core::cleanup::<LifecyclePlugin>(handle);
+    }
+
+    assert_eq!(
+        vec![
+            "activate",
+            "run",
+            "deactivate",
+            "activate",
+            "deactivate",
+            "drop"
+        ],
+        *LIFECYCLE_CALLS.lock().unwrap()
+    );
+}
+
+const REQUIRED_FEATURE_URI: Uri = Uri::new(b"http://example.org/RequiredFeature\0");
+
+/// A plugin that requires a feature and a sample rate of at least 8 kHz.
+struct RequiringPlugin {}
+
+impl core::Plugin for RequiringPlugin {
+    fn instantiate(
+        context: &core::InstantiationContext,
+        features: Option<&core::FeaturesList>,
+    ) -> Result<Self, core::InstantiateError> {
+        let uri = REQUIRED_FEATURE_URI.as_cstr();
+        assert!(core::Features::new(features.unwrap()).contains(uri));
+        if context.sample_rate() < 8000.0 {
+            return Err(core::InstantiateError::BadSampleRate(context.sample_rate()));
+        }
+        Ok(Self {})
+    }
+
+    fn connect_port(&mut self, _port: u32, _data: *mut ()) {}
+
+    fn run(&mut self, _context: &mut core::RunContext) {}
+
+    fn required_features() -> &'static [Uri] {
+        &[REQUIRED_FEATURE_URI]
+    }
+}
+
+/// The messages printed by the test log.
+static LOG_MESSAGES: Mutex<Vec<(u32, String)>> = Mutex::new(Vec::new());
+
+/// The printf function of the test log.
+///
+/// Variadic functions can't be defined in Rust, but the log message of a missing feature has
+/// exactly one pointer argument.
+unsafe extern "C" fn log_printf(
+    _handle: *mut c_void,
+    entry_type: u32,
+    format: *const c_char,
+    uri: *const c_char,
+) -> c_int {
+    let format = CStr::from_ptr(format).to_str().unwrap();
+    let uri = CStr::from_ptr(uri).to_str().unwrap();
+    let message = format.replace("%s", uri);
+    LOG_MESSAGES.lock().unwrap().push((entry_type, message));
+    0
+}
+
+#[repr(C)]
+struct TestLog {
+    handle: *mut c_void,
+    printf: *const (),
+}
+
+#[repr(C)]
+struct TestMap {
+    handle: *mut c_void,
+    map: unsafe extern "C" fn(*mut c_void, *const c_char) -> u32,
+}
+
+unsafe extern "C" fn map_uri(_handle: *mut c_void, _uri: *const c_char) -> u32 {
+    7
+}
+
+#[test]
+fn test_required_features() {
+    let mut log = TestLog {
+        handle: null_mut(),
+        printf: log_printf as *const (),
+    };
+    let mut map = TestMap {
+        handle: null_mut(),
+        map: map_uri,
+    };
+    let log_feature = core::Feature::new(
+        b"http://lv2plug.in/ns/ext/log#log\0".as_ptr() as *const c_char,
+        &mut log as *mut TestLog as *mut c_void,
+    );
+    let map_feature = core::Feature::new(
+        b"http://lv2plug.in/ns/ext/urid#map\0".as_ptr() as *const c_char,
+        &mut map as *mut TestMap as *mut c_void,
+    );
+    let required_feature = core::Feature::new(REQUIRED_FEATURE_URI.as_ptr(), null_mut());
+    let instantiate_at = |rate: f64, features: &[*const core::Feature]| unsafe {
+        core::instantiate::<RequiringPlugin>(
+            lv2_descriptor(0),
+            rate,
+            BUNDLE_PATH.as_ptr() as *const c_char,
+            features.as_ptr(),
+        )
+    };
+    let instantiate = |features: &[*const core::Feature]| instantiate_at(44100.0, features);
+
+    // Without a log, the instantiation fails silently.
+    assert_eq!(null_mut(), instantiate(&[null()]));
+    assert!(LOG_MESSAGES.lock().unwrap().is_empty());
+
+    assert_eq!(
+        null_mut(),
+        instantiate(&[&log_feature, &map_feature, null()])
+    );
+    assert_eq!(
+        vec![(
+            7,
+            "Missing required feature http://example.org/RequiredFeature\n".to_owned()
+        )],
+        *LOG_MESSAGES.lock().unwrap()
+    );
+
+    let features: [*const core::Feature; 4] =
+        [&log_feature, &map_feature, &required_feature, null()];
+    let handle = instantiate(&features);
+    assert_ne!(null_mut(), handle);
+    unsafe { core::cleanup::<RequiringPlugin>(handle) };
+
+    // Errors of the plugin are logged, too.
+    LOG_MESSAGES.lock().unwrap().clear();
+    assert_eq!(null_mut(), instantiate_at(4000.0, &features));
+    assert_eq!(
+        vec![(7, "Unsupported sample rate 4000\n".to_owned())],
+        *LOG_MESSAGES.lock().unwrap()
+    );
+}
+
+/// A plugin that records the timing information of every cycle.
+struct ClockPlugin {
+    cycles: Vec<(u64, Option<f64>)>,
+}
+
+impl core::Plugin for ClockPlugin {
+    fn instantiate(
+        _context: &core::InstantiationContext,
+        _features: Option<&core::FeaturesList>,
+    ) -> Result<Self, core::InstantiateError> {
+        Ok(Self { cycles: Vec::new() })
+    }
+
+    fn connect_port(&mut self, _port: u32, _data: *mut ()) {}
+
+    fn run(&mut self, context: &mut core::RunContext) {
+        if context.frame() == 0 {
+            // The host sends the transport state once.
+            context.update_position(&core::time::Position {
+                beat: Some(0.0),
+                beats_per_minute: Some(120.0),
+                speed: Some(1.0),
+                ..Default::default()
+            });
+        }
+        let beat = context.position().and_then(|position| position.beat);
+        self.cycles.push((context.frame(), beat));
+    }
+}
+
+#[test]
+fn test_run_context() {
+    let descriptor = unsafe { lv2_descriptor(0) };
+    let handle = unsafe {
+        core::instantiate::<ClockPlugin>(
+            descriptor,
+            100.0,
+            BUNDLE_PATH.as_ptr() as *const c_char,
+            null(),
+        )
+    };
+    assert_ne!(handle, null_mut());
+
+    unsafe {
+        core::activate::<ClockPlugin>(handle);
+        core::run::<ClockPlugin>(handle, 50);
+        core::run::<ClockPlugin>(handle, 25);
+        core::run::<ClockPlugin>(handle, 25);
+    }
+
+    let plugin = unsafe { core::get_instance::<ClockPlugin>(handle) };
+    assert_eq!(
+        vec![(0, Some(0.0)), (50, Some(1.0)), (75, Some(1.5))],
+        plugin.cycles
+    );
+
+    unsafe { core::cleanup::<ClockPlugin>(handle) };
+}
+
+/// A plugin that allocates its buffer from an arena when it's activated.
+struct ArenaPlugin {
+    arena: core::arena::Arena,
+    buffer: Option<core::arena::ArenaBuffer<f32>>,
+    prepared_in_run: bool,
+}
+
+impl core::Plugin for ArenaPlugin {
+    fn instantiate(
+        context: &core::InstantiationContext,
+        _features: Option<&core::FeaturesList>,
+    ) -> Result<Self, core::InstantiateError> {
+        let length = context.sample_rate() as usize;
+        let arena = core::arena::Arena::size_of::<f32>(length)
+            .and_then(core::arena::Arena::new)
+            .ok_or_else(|| core::InstantiateError::Other("Out of memory".into()))?;
+        Ok(Self {
+            arena,
+            buffer: None,
+            prepared_in_run: false,
+        })
+    }
+
+    fn arena(&mut self) -> Option<&mut core::arena::Arena> {
+        Some(&mut self.arena)
+    }
+
+    fn connect_port(&mut self, _port: u32, _data: *mut ()) {}
+
+    fn activate(&mut self) {
+        if self.buffer.is_none() {
+            self.buffer = self.arena.alloc(self.arena.remaining() / 4, 0.0);
+        }
+    }
+
+    fn run(&mut self, context: &mut core::RunContext) {
+        self.prepared_in_run = self.arena.is_prepared();
+        let buffer = self.buffer.as_mut().unwrap();
+        for sample in buffer[..context.n_samples() as usize].iter_mut() {
+            *sample += 1.0;
+        }
+    }
+}
+
+#[test]
+fn test_arena() {
+    let descriptor = unsafe { lv2_descriptor(0) };
+    let handle = unsafe {
+        core::instantiate::<ArenaPlugin>(
+            descriptor,
+            48000.0,
+            BUNDLE_PATH.as_ptr() as *const c_char,
+            null(),
+        )
+    };
+    assert_ne!(handle, null_mut());
+    assert!(!unsafe { core::get_instance::<ArenaPlugin>(handle) }
+        .arena
+        .is_prepared());
+
+    unsafe {
+        core::activate::<ArenaPlugin>(handle);
+        core::run::<ArenaPlugin>(handle, 64);
+    }
+
+    let plugin = unsafe { core::get_instance::<ArenaPlugin>(handle) };
+    assert!(plugin.prepared_in_run);
+    let buffer = plugin.buffer.as_ref().unwrap();
+    assert_eq!(48000, buffer.len());
+    assert_eq!(1.0, buffer[63]);
+    assert_eq!(0.0, buffer[64]);
+
+    unsafe { core::cleanup::<ArenaPlugin>(handle) };
+}
+
+/// A plugin that flushes denormals and checks whether a decaying signal becomes zero.
+struct DecayPlugin {
+    flushed: bool,
+}
+
+impl core::Plugin for DecayPlugin {
+    const FLUSH_DENORMALS: bool = true;
+
+    fn instantiate(
+        _context: &core::InstantiationContext,
+        _features: Option<&core::FeaturesList>,
+    ) -> Result<Self, core::InstantiateError> {
+        Ok(Self { flushed: false })
+    }
+
+    fn connect_port(&mut self, _port: u32, _data: *mut ()) {}
+
+    fn run(&mut self, _context: &mut core::RunContext) {
+        self.flushed = std::hint::black_box(f32::MIN_POSITIVE) * 0.5 == 0.0;
+    }
+}
+
+#[test]
+fn test_flush_denormals() {
+    let descriptor = unsafe { lv2_descriptor(0) };
+    let handle = unsafe {
+        core::instantiate::<DecayPlugin>(
+            descriptor,
+            44100.0,
+            BUNDLE_PATH.as_ptr() as *const c_char,
+            null(),
+        )
+    };
+    assert_ne!(handle, null_mut());
+
+    unsafe {
+        core::activate::<DecayPlugin>(handle);
+        core::run::<DecayPlugin>(handle, 64);
+    }
+
+    let plugin = unsafe { core::get_instance::<DecayPlugin>(handle) };
+    assert_eq!(
+        core::denormals::DenormalGuard::is_supported(),
+        plugin.flushed
+    );
+    // The flags of the host's thread are restored.
+    assert!((std::hint::black_box(f32::MIN_POSITIVE) * 0.5).is_subnormal());
+
+    unsafe { core::cleanup::<DecayPlugin>(handle) };
+}
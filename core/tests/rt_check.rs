@@ -0,0 +1,129 @@
+extern crate lv2rs_core as core;
+
+use core::rt_check::*;
+use std::alloc::System;
+use std::os::raw::*;
+use std::panic::catch_unwind;
+use std::ptr::null;
+
+#[global_allocator]
+static ALLOCATOR: RtCheckAllocator<System> = RtCheckAllocator::new(System);
+
+/// A plugin that allocates in `run` if it's told to.
+struct AllocatingPlugin {
+    buffer: Vec<f32>,
+    allocate: bool,
+}
+
+impl core::Plugin for AllocatingPlugin {
+    const THREADING: core::Threading = core::Threading::hard_rt_capable();
+
+    fn instantiate(
+        _context: &core::InstantiationContext,
+        _features: Option<&core::FeaturesList>,
+    ) -> Result<Self, core::InstantiateError> {
+        // Allocating in `instantiate` is fine.
+        Ok(Self {
+            buffer: Vec::with_capacity(16),
+            allocate: false,
+        })
+    }
+
+    fn connect_port(&mut self, _port: u32, _data: *mut ()) {}
+
+    fn run(&mut self, context: &mut core::RunContext) {
+        if self.allocate {
+            self.buffer = vec![0.0; context.n_samples() as usize];
+        } else {
+            self.buffer.clear();
+            self.buffer.resize(context.n_samples() as usize, 0.0);
+        }
+    }
+}
+
+/// A plugin that allocates in `run`, which is fine since it isn't hard real-time capable.
+struct OfflinePlugin;
+
+impl core::Plugin for OfflinePlugin {
+    fn instantiate(
+        _context: &core::InstantiationContext,
+        _features: Option<&core::FeaturesList>,
+    ) -> Result<Self, core::InstantiateError> {
+        Ok(Self)
+    }
+
+    fn connect_port(&mut self, _port: u32, _data: *mut ()) {}
+
+    fn run(&mut self, context: &mut core::RunContext) {
+        drop(vec![0.0f32; context.n_samples() as usize]);
+    }
+}
+
+core::lv2_main!(
+    core,
+    AllocatingPlugin,
+    b"http://example.org/AllocatingPlugin\0"
+);
+
+#[test]
+fn test_rt_check() {
+    let features: [*const core::Feature; 1] = [null()];
+    let descriptor = unsafe { lv2_descriptor(0) };
+    let descriptor_ref = unsafe { descriptor.as_ref() }.unwrap();
+    let handle = unsafe {
+        (descriptor_ref.instantiate)(
+            descriptor,
+            44100.0,
+            b"/\0".as_ptr() as *const c_char,
+            features.as_ptr(),
+        )
+    };
+    assert!(!handle.is_null());
+    assert!(!in_realtime_section());
+
+    // Running without allocations.
+    let violations = total_violations();
+    unsafe { (descriptor_ref.run)(handle, 16) };
+    assert_eq!(violations, total_violations());
+
+    // Allocations are counted.
+    unsafe { core::get_instance::<AllocatingPlugin>(handle) }.allocate = true;
+    set_reaction(Reaction::Ignore);
+    unsafe { (descriptor_ref.run)(handle, 8) };
+    assert_eq!(violations + 2, total_violations());
+
+    // Allocations let the audio thread panic. The descriptor's functions can't unwind, so the
+    // panic is caught in the helper function.
+    set_reaction(Reaction::Panic);
+    let handle = handle as usize;
+    let result =
+        catch_unwind(|| unsafe { core::run::<AllocatingPlugin>(handle as core::Handle, 8) });
+    assert!(result.is_err());
+    assert!(!in_realtime_section());
+
+    // Blocking operations are reported explicitly.
+    {
+        let _section = RealtimeSection::enter();
+        set_reaction(Reaction::Ignore);
+        check_blocking("Mutex::lock");
+    }
+    check_blocking("Mutex::lock");
+    assert_eq!(violations + 5, total_violations());
+
+    unsafe { (descriptor_ref.cleanup)(handle as core::Handle) };
+
+    // Plugins that aren't hard real-time capable aren't checked.
+    set_reaction(Reaction::Panic);
+    let descriptor = core::Descriptor::new::<OfflinePlugin>(b"http://example.org/OfflinePlugin\0");
+    unsafe {
+        let handle = (descriptor.instantiate)(
+            &descriptor,
+            44100.0,
+            b"/\0".as_ptr() as *const c_char,
+            features.as_ptr(),
+        );
+        (descriptor.run)(handle, 8);
+        (descriptor.cleanup)(handle);
+    }
+    assert_eq!(violations + 5, total_violations());
+}
@@ -0,0 +1,133 @@
+extern crate lv2rs_core as core;
+
+use core::ports::*;
+use core::ttl::*;
+use std::ffi::CStr;
+use std::ptr::null;
+
+struct Gain {}
+struct Pan {}
+struct Mute {}
+
+macro_rules! impl_plugin {
+    ($p:ty, $info:expr) => {
+        impl core::Plugin for $p {
+            fn instantiate(
+                _context: &core::InstantiationContext,
+                _features: Option<&core::FeaturesList>,
+            ) -> Result<Self, core::InstantiateError> {
+                Ok(Self {})
+            }
+
+            fn info() -> Option<&'static PluginInfo> {
+                $info
+            }
+
+            fn connect_port(&mut self, _port: u32, _data: *mut ()) {}
+
+            fn run(&mut self, _context: &mut core::RunContext) {}
+        }
+    };
+}
+
+impl_plugin!(Gain, Some(&GAIN));
+impl_plugin!(Pan, Some(&PAN));
+impl_plugin!(Mute, None);
+
+const PROLOGUE: &str = "@prefix tools: <http://example.org/tools#> .
+
+<http://example.org/tools>
+    a doap:Project ;
+    doap:name \"Tools\" .
+";
+
+core::plugin_suite!(core, "http://example.org/tools#", PROLOGUE, [
+    Gain: "gain",
+    Pan: "pan",
+    Mute: "mute",
+]);
+
+#[derive(Ports)]
+struct GainPorts {
+    #[port(name = "Gain", default = 1.0, minimum = 0.0, maximum = 2.0)]
+    gain: ControlInputPort,
+}
+
+const GAIN: PluginInfo = PluginInfo::new("http://example.org/tools#gain", "Gain", GainPorts::PORTS);
+const PAN: PluginInfo = PluginInfo::new("http://example.org/tools#pan", "Pan", &[]);
+const MUTE: PluginInfo = PluginInfo::new("http://example.org/tools#mute", "Mute", &[]);
+
+#[test]
+fn test_descriptors() {
+    for (index, uri) in PLUGIN_SUITE.uris.iter().enumerate() {
+        let descriptor = unsafe { lv2_descriptor(index as u32).as_ref() }.unwrap();
+        let descriptor_uri = unsafe { CStr::from_ptr(descriptor.uri) };
+        assert_eq!(uri.as_bytes(), descriptor_uri.to_bytes());
+    }
+    assert!(unsafe { lv2_descriptor(3) }.is_null());
+
+    let lib = unsafe { lv2_lib_descriptor(null(), null()).as_ref() }.unwrap();
+    assert_eq!(unsafe { lv2_descriptor(2) }, unsafe {
+        (lib.get_plugin)(lib.handle, 2)
+    });
+}
+
+#[test]
+fn test_indices() {
+    assert_eq!(3, PLUGIN_SUITE.len());
+    assert_eq!(Some(0), PLUGIN_SUITE.index("http://example.org/tools#gain"));
+    assert_eq!(Some(2), PLUGIN_SUITE.index("http://example.org/tools#mute"));
+    assert_eq!(None, PLUGIN_SUITE.index("http://example.org/tools#"));
+    assert_eq!(Some("http://example.org/tools#pan"), PLUGIN_SUITE.uri(1));
+    assert_eq!(None, PLUGIN_SUITE.uri(3));
+}
+
+#[test]
+fn test_manifest() {
+    let manifest = PLUGIN_SUITE
+        .manifest("tools.so", &[MUTE, GAIN, PAN])
+        .unwrap();
+    assert!(manifest.starts_with("@prefix atom:"));
+    assert_eq!(1, manifest.matches("@prefix tools:").count());
+    assert_eq!(1, manifest.matches("@prefix lv2:").count());
+    assert!(manifest.contains("    doap:name \"Tools\" .\n\n<http://example.org/tools#gain>\n"));
+    assert!(manifest.contains("lv2:symbol \"gain\""));
+    assert!(manifest.contains("<http://example.org/tools#mute> lv2:binary <tools.so> .\n"));
+    assert!(!manifest.contains("rdfs:seeAlso"));
+
+    // The plugins are described in the order of the suite.
+    let gain = manifest.find("<http://example.org/tools#gain>\n").unwrap();
+    let pan = manifest.find("<http://example.org/tools#pan>\n").unwrap();
+    let mute = manifest.find("<http://example.org/tools#mute>\n").unwrap();
+    assert!(gain < pan && pan < mute);
+
+    // Every plugin needs exactly one description.
+    assert_eq!(
+        Err(ManifestError::PluginCount),
+        PLUGIN_SUITE.manifest("tools.so", &[GAIN, PAN])
+    );
+    assert_eq!(
+        Err(ManifestError::Duplicate("http://example.org/tools#pan")),
+        PLUGIN_SUITE.manifest("tools.so", &[GAIN, PAN, PAN])
+    );
+}
+
+#[test]
+fn test_describe() {
+    let descriptor = |index| unsafe { lv2_descriptor(index).as_ref() }.unwrap();
+    let gain = descriptor(0).describe().unwrap();
+    assert_eq!(GAIN, *gain);
+    let port = gain.port_by_symbol("gain").unwrap();
+    assert_eq!(0, port.index);
+    assert_eq!(PortKind::ControlInput, port.kind);
+    assert_eq!((Some(0.0), Some(2.0)), (port.minimum, port.maximum));
+    assert_eq!(Some(port), gain.port(0));
+    assert_eq!(None, gain.port(1));
+    assert_eq!(None, gain.port_by_symbol("pan"));
+
+    assert_eq!(
+        "http://example.org/tools#pan",
+        descriptor(1).describe().unwrap().uri
+    );
+    assert_eq!(None, descriptor(2).describe());
+}
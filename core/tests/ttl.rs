@@ -0,0 +1,329 @@
+extern crate lv2rs_core as core;
+
+use core::groups::*;
+use core::ports::*;
+use core::ttl::*;
+
+#[derive(Ports)]
+struct SwitchPorts {
+    #[port(name = "Mode", default = 1, minimum = 0, maximum = 2)]
+    #[port(property = "integer", property = "enumeration")]
+    #[port(scale_point("Off", 0), scale_point("On", 1), scale_point("Auto", 2))]
+    mode: ControlInputPort,
+    #[port(symbol = "in", name = "Input \"left\"")]
+    r#in: CVInputPort,
+    #[port(unit = "http://example.org/units#level")]
+    level: ControlOutputPort,
+}
+
+const SWITCH: PluginInfo = PluginInfo {
+    classes: &["UtilityPlugin"],
+    required_features: &["http://lv2plug.in/ns/ext/urid#map"],
+    optional_features: &["hardRTCapable"],
+    ..PluginInfo::new(
+        "http://example.org/Switch",
+        "Switch (CV)",
+        SwitchPorts::PORTS,
+    )
+};
+
+#[test]
+fn test_port_info() {
+    let ports = SwitchPorts::PORTS;
+    assert_eq!(3, ports.len());
+
+    let mode = &ports[0];
+    assert_eq!((0, "mode", "Mode"), (mode.index, mode.symbol, mode.name));
+    assert_eq!(PortKind::ControlInput, mode.kind);
+    assert_eq!(
+        (Some(1.0), Some(0.0), Some(2.0)),
+        (mode.default, mode.minimum, mode.maximum)
+    );
+    assert_eq!(&["integer", "enumeration"], mode.properties);
+    assert_eq!(
+        ScalePoint {
+            label: "Auto",
+            value: 2.0
+        },
+        mode.scale_points[2]
+    );
+
+    let input = &ports[1];
+    assert_eq!(
+        (1, "in", PortKind::CVInput),
+        (input.index, input.symbol, input.kind)
+    );
+    assert_eq!(None, input.default);
+
+    let level = &ports[2];
+    assert_eq!(("level", "level"), (level.symbol, level.name));
+    assert_eq!(PortKind::ControlOutput, level.kind);
+}
+
+#[test]
+fn test_turtle() {
+    assert_eq!("SwitchCV.ttl", SWITCH.file_name());
+
+    let turtle = SWITCH.to_turtle();
+    assert!(turtle.contains("<http://example.org/Switch>\n    a lv2:Plugin, lv2:UtilityPlugin ;"));
+    assert!(turtle.contains("doap:name \"Switch (CV)\""));
+    assert!(turtle.contains("lv2:requiredFeature <http://lv2plug.in/ns/ext/urid#map>"));
+    assert!(turtle.contains("lv2:optionalFeature lv2:hardRTCapable"));
+    assert!(turtle.contains("lv2:portProperty lv2:integer, lv2:enumeration"));
+    assert!(turtle.contains("lv2:scalePoint [ rdfs:label \"On\" ; rdf:value 1.0 ]"));
+    assert!(turtle.contains("a lv2:InputPort, lv2:CVPort"));
+    assert!(turtle.contains("lv2:name \"Input \\\"left\\\"\""));
+    assert!(turtle.contains("units:unit <http://example.org/units#level>"));
+    assert!(!turtle.contains("lv2:extensionData"));
+    assert!(turtle.ends_with("    ] .\n"));
+
+    let bundle = Bundle::new("switch.so", &[SWITCH]);
+    let files = bundle.files();
+    assert_eq!(2, files.len());
+    assert_eq!("manifest.ttl", files[0].0);
+    assert!(files[0].1.contains("rdfs:seeAlso <SwitchCV.ttl>"));
+    assert_eq!(("SwitchCV.ttl".to_string(), turtle), files[1]);
+}
+
+#[derive(Ports)]
+struct LimiterPorts {
+    #[port(name = "Enabled", property = "toggled", designation = "enabled")]
+    enabled: ControlInputPort,
+    #[port(name = "Latency", property = "integer")]
+    latency: LatencyPort,
+}
+
+#[test]
+fn test_designations() {
+    let ports = LimiterPorts::PORTS;
+    assert_eq!(Some("enabled"), ports[0].designation);
+    assert_eq!(Some("latency"), ports[1].designation);
+    assert_eq!(PortKind::ControlOutput, ports[1].kind);
+    assert_eq!(None, SwitchPorts::PORTS[0].designation);
+
+    let turtle = PluginInfo::new("http://example.org/Limiter", "Limiter", ports).to_turtle();
+    assert!(turtle.contains("lv2:designation lv2:enabled"));
+    assert!(turtle.contains(
+        "a lv2:OutputPort, lv2:ControlPort ;\n        lv2:index 1 ;\n        lv2:symbol \"latency\" ;\n        lv2:name \"Latency\" ;\n        lv2:designation lv2:latency"
+    ));
+}
+
+#[derive(Ports)]
+struct BypassPorts {
+    enabled: EnabledPort,
+    #[port(name = "Bypass", property = "toggled", designation = "enabled")]
+    custom: ControlInputPort,
+}
+
+#[test]
+fn test_enabled_port() {
+    let ports = BypassPorts::PORTS;
+    assert_eq!(Some("enabled"), ports[0].designation);
+    assert_eq!(PortKind::ControlInput, ports[0].kind);
+
+    let turtle = PluginInfo::new("http://example.org/Bypass", "Bypass", ports).to_turtle();
+    assert!(turtle.contains(
+        "lv2:name \"enabled\" ;\n        lv2:default 1.0 ;\n        lv2:minimum 0.0 ;\n        lv2:maximum 1.0 ;\n        lv2:designation lv2:enabled ;\n        lv2:portProperty lv2:toggled\n"
+    ));
+    // The toggled property isn't declared twice.
+    assert!(turtle
+        .contains("lv2:designation lv2:enabled ;\n        lv2:portProperty lv2:toggled\n    ] ."));
+}
+
+#[derive(Ports)]
+struct QuantizerPorts {
+    #[port(name = "Pitch", minimum = -5, maximum = 5)]
+    pitch: CVInputPort,
+    #[port(name = "Gate")]
+    gate: CVInputPort,
+    #[port(name = "Quantized", minimum = -5, maximum = 5)]
+    quantized: CVOutputPort,
+}
+
+#[test]
+fn test_cv_ports() {
+    let mut ports = QuantizerPorts {
+        pitch: CVInputPort::new(),
+        gate: CVInputPort::new(),
+        quantized: CVOutputPort::new(),
+    };
+    assert_eq!(None, ports.pitch.range());
+    ports.apply_metadata();
+    assert_eq!(Some(CVRange::new(-5.0, 5.0)), ports.pitch.range());
+    assert_eq!(None, ports.gate.range());
+    assert_eq!(Some(CVRange::new(-5.0, 5.0)), ports.quantized.range());
+
+    let turtle = PluginInfo::new(
+        "http://example.org/Quantizer",
+        "Quantizer",
+        QuantizerPorts::PORTS,
+    )
+    .to_turtle();
+    assert!(turtle.contains(
+        "a lv2:InputPort, lv2:CVPort ;\n        lv2:index 0 ;\n        lv2:symbol \"pitch\" ;\n        lv2:name \"Pitch\" ;\n        lv2:minimum -5.0 ;\n        lv2:maximum 5.0"
+    ));
+}
+
+#[derive(Ports)]
+struct UpmixPorts {
+    #[port(group = "in", channel = "left")]
+    in_left: AudioInputPort,
+    #[port(group = "in", channel = "right")]
+    in_right: AudioInputPort,
+    #[port(group = "out", channel = "left")]
+    out_left: AudioOutputPort,
+    #[port(group = "out", channel = "right")]
+    out_right: AudioOutputPort,
+    #[port(group = "out", channel = "center")]
+    out_center: AudioOutputPort,
+    #[port(group = "out", channel = "lowFrequencyEffects")]
+    out_lfe: AudioOutputPort,
+    #[port(group = "out", channel = "rearLeft")]
+    out_rear_left: AudioOutputPort,
+    #[port(group = "out", channel = "rearRight")]
+    out_rear_right: AudioOutputPort,
+}
+
+const UPMIX: PluginInfo = PluginInfo {
+    groups: &[
+        GroupInfo {
+            symbol: "in",
+            name: "Input",
+            kind: GroupKind::Input,
+            layout: ChannelLayout::Stereo,
+        },
+        GroupInfo {
+            symbol: "out",
+            name: "Output",
+            kind: GroupKind::Output,
+            layout: ChannelLayout::FiveDotOne,
+        },
+    ],
+    main_input: Some("in"),
+    main_output: Some("out"),
+    ..PluginInfo::new("http://example.org/Upmix", "Upmix", UpmixPorts::PORTS)
+};
+
+#[test]
+fn test_groups() {
+    let ports = UpmixPorts::PORTS;
+    for group in UPMIX.groups {
+        let channels: Vec<Option<&str>> = ports
+            .iter()
+            .filter(|port| port.group == Some(group.symbol))
+            .map(|port| port.designation)
+            .collect();
+        let expected: Vec<Option<&str>> = group
+            .layout
+            .channels()
+            .iter()
+            .map(|channel| Some(channel.designation()))
+            .collect();
+        assert_eq!(expected, channels);
+    }
+    assert_eq!(None, SwitchPorts::PORTS[0].group);
+
+    let turtle = UPMIX.to_turtle();
+    assert!(turtle.contains("pg:mainInput <http://example.org/Upmix#in> ;\n    pg:mainOutput <http://example.org/Upmix#out>"));
+    assert!(turtle.contains(
+        "lv2:symbol \"in_right\" ;\n        lv2:name \"in_right\" ;\n        pg:group <http://example.org/Upmix#in> ;\n        lv2:designation pg:right"
+    ));
+    assert!(turtle.contains("lv2:designation pg:lowFrequencyEffects"));
+    assert!(turtle.contains(
+        "\n<http://example.org/Upmix#in>\n    a pg:StereoGroup, pg:InputGroup ;\n    lv2:symbol \"in\" ;\n    lv2:name \"Input\" .\n"
+    ));
+    assert!(turtle.ends_with("    a pg:FiveDotOneGroup, pg:OutputGroup ;\n    lv2:symbol \"out\" ;\n    lv2:name \"Output\" .\n"));
+}
+
+#[derive(Ports)]
+struct FilterPorts {
+    #[port(
+        name = "Cutoff",
+        default = 1000,
+        minimum = 20,
+        maximum = 20000,
+        unit = "hz"
+    )]
+    #[port(
+        property = "logarithmic",
+        property = "http://lv2plug.in/ns/ext/port-props#expensive"
+    )]
+    cutoff: ControlInputPort,
+    #[port(name = "Transpose", default = 0, minimum = -24, maximum = 24)]
+    #[port(unit = "semitone12TET", property = "integer")]
+    transpose: ControlInputPort,
+    #[port(name = "Slope", minimum = 12, maximum = 48, property = "enumeration")]
+    #[port(
+        scale_point("12 dB", 12),
+        scale_point("24 dB", 24),
+        scale_point("48 dB", 48)
+    )]
+    slope: ControlInputPort,
+    #[port(name = "Bypass", property = "http://lv2plug.in/ns/lv2core#toggled")]
+    bypass: ControlInputPort,
+}
+
+#[test]
+fn test_introspection() {
+    let ports = FilterPorts::PORTS;
+    let (cutoff, transpose, slope, bypass) = (&ports[0], &ports[1], &ports[2], &ports[3]);
+
+    assert_eq!(Some(core::units::Unit::Hz), cutoff.unit());
+    assert!(cutoff.is_logarithmic());
+    assert!(cutoff.has_property("expensive"));
+    assert!(!cutoff.is_integer());
+    assert_eq!(20000.0, cutoff.clamp(30000.0));
+    assert_eq!(440.5, cutoff.clamp(440.5));
+
+    assert_eq!(Some(core::units::Unit::Semitone12TET), transpose.unit());
+    assert!(transpose.is_integer());
+    assert_eq!(-3.0, transpose.clamp(-2.6));
+    assert_eq!(-24.0, transpose.clamp(-30.0));
+
+    assert!(slope.is_enumeration());
+    assert_eq!(Some("24 dB"), slope.scale_point_label(24.0));
+    assert_eq!(None, slope.scale_point_label(36.0));
+    assert_eq!(48.0, slope.clamp(40.0));
+    assert_eq!(None, slope.unit());
+
+    assert!(bypass.is_toggled());
+    assert!(bypass.has_property("toggled"));
+    assert_eq!(1.0, bypass.clamp(0.7));
+    assert_eq!(None, SwitchPorts::PORTS[2].unit());
+
+    let turtle =
+        PluginInfo::new("http://example.org/Filter", "Filter", FilterPorts::PORTS).to_turtle();
+    assert!(turtle.contains("@prefix pprops: <http://lv2plug.in/ns/ext/port-props#> ."));
+    assert!(turtle.contains(
+        "units:unit units:hz ;\n        lv2:portProperty pprops:logarithmic, pprops:expensive"
+    ));
+    assert!(turtle.contains("units:unit units:semitone12TET"));
+    assert!(turtle.contains("lv2:portProperty lv2:toggled"));
+}
+
+#[test]
+fn test_threading() {
+    let turtle = PluginInfo {
+        optional_features: &["hardRTCapable"],
+        threading: core::Threading {
+            is_live: true,
+            ..core::Threading::hard_rt_capable()
+        },
+        ..PluginInfo::new("http://example.org/Stream", "Stream", &[])
+    }
+    .to_turtle();
+    assert!(turtle
+        .contains("lv2:requiredFeature lv2:isLive ;\n    lv2:optionalFeature lv2:hardRTCapable ."));
+
+    let turtle = PluginInfo {
+        required_features: &["http://lv2plug.in/ns/ext/urid#map"],
+        threading: core::Threading {
+            in_place_broken: true,
+            ..core::Threading::new()
+        },
+        ..SWITCH
+    }
+    .to_turtle();
+    assert!(turtle
+        .contains("lv2:requiredFeature <http://lv2plug.in/ns/ext/urid#map>, lv2:inPlaceBroken ;"));
+}
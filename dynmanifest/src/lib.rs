@@ -0,0 +1,103 @@
+//! Plugin descriptions that are generated at load time.
+//!
+//! This crate implements the
+//! [LV2 dynamic manifest extension](http://lv2plug.in/ns/ext/dynmanifest/dynmanifest.html):
+//! Usually, the plugins of a bundle are listed in its `manifest.ttl` and described by static
+//! Turtle files. Libraries that wrap a variable set of plugins, for example one plugin for every
+//! DSP definition in a directory, can't know their plugins in advance. Instead, their
+//! `manifest.ttl` only declares a dynamic manifest, which is generated by
+//! [`static_manifest`](fn.static_manifest.html), and the host asks the library for the Turtle
+//! data when it loads the bundle.
+//!
+//! The dynamic manifest implements the [`DynManifest`](trait.DynManifest.html) trait and is
+//! exported with the [`lv2_dyn_manifest`](macro.lv2_dyn_manifest.html) macro:
+//!
+//!     extern crate lv2rs_core as core;
+//!     extern crate lv2rs_dynmanifest as dynmanifest;
+//!
+//!     use core::ttl::PluginInfo;
+//!     use dynmanifest::*;
+//!     use std::ffi::CStr;
+//!
+//!     struct Gains {
+//!         plugins: Vec<PluginInfo>,
+//!     }
+//!
+//!     impl DynManifest for Gains {
+//!         fn open(_features: Option<&core::FeaturesList>) -> Option<Self> {
+//!             // Load the plugin definitions, for example from files.
+//!             let plugins = vec![
+//!                 PluginInfo::new("http://example.org/gains#half", "Half", &[]),
+//!                 PluginInfo::new("http://example.org/gains#double", "Double", &[]),
+//!             ];
+//!             Some(Self { plugins })
+//!         }
+//!
+//!         fn subjects(&mut self) -> String {
+//!             let uris: Vec<&str> = self.plugins.iter().map(|plugin| plugin.uri).collect();
+//!             plugin_subjects(&uris)
+//!         }
+//!
+//!         fn data(&mut self, uri: &CStr) -> Option<String> {
+//!             let uri = uri.to_str().ok()?;
+//!             let plugin = self.plugins.iter().find(|plugin| plugin.uri == uri)?;
+//!             Some(plugin.to_turtle())
+//!         }
+//!     }
+//!
+//!     dynmanifest::lv2_dyn_manifest!(dynmanifest, Gains);
+//!
+//!     let mut gains = Gains::open(None).unwrap();
+//!     assert!(gains.subjects().contains("<http://example.org/gains#double> a lv2:Plugin ."));
+//!     let uri = CStr::from_bytes_with_nul(b"http://example.org/gains#half\0").unwrap();
+//!     assert!(gains.data(uri).unwrap().contains("doap:name \"Half\""));
+//!
+//!     let manifest = static_manifest("http://example.org/gains", "gains.so");
+//!     assert!(manifest.contains("a dman:DynManifest ;\n    lv2:binary <gains.so> ."));
+extern crate lv2rs_core as core;
+
+mod manifest;
+pub mod uris;
+
+pub use manifest::*;
+
+/// Create the dynamic manifest export functions.
+///
+/// This macro works like the `lv2_main` macro of `lv2rs-core`: It takes the namespace of this
+/// crate and a type that implements [`DynManifest`](trait.DynManifest.html) and exports the
+/// `lv2_dyn_manifest_open`, `lv2_dyn_manifest_get_subjects`, `lv2_dyn_manifest_get_data` and
+/// `lv2_dyn_manifest_close` functions for it.
+#[macro_export]
+macro_rules! lv2_dyn_manifest {
+    ($c:ident, $m:ty) => {
+        #[no_mangle]
+        pub unsafe extern "C" fn lv2_dyn_manifest_open(
+            handle: *mut $c::DynManifestHandle,
+            features: *const *const std::os::raw::c_void,
+        ) -> std::os::raw::c_int {
+            $c::open::<$m>(handle, features as *const *const _)
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn lv2_dyn_manifest_get_subjects(
+            handle: $c::DynManifestHandle,
+            stream: *mut std::os::raw::c_void,
+        ) -> std::os::raw::c_int {
+            $c::get_subjects::<$m>(handle, stream)
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn lv2_dyn_manifest_get_data(
+            handle: $c::DynManifestHandle,
+            stream: *mut std::os::raw::c_void,
+            uri: *const std::os::raw::c_char,
+        ) -> std::os::raw::c_int {
+            $c::get_data::<$m>(handle, stream, uri)
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn lv2_dyn_manifest_close(handle: $c::DynManifestHandle) {
+            $c::close::<$m>(handle)
+        }
+    };
+}
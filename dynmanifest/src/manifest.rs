@@ -0,0 +1,140 @@
+//! The dynamic manifest trait and the functions the host calls.
+use std::ffi::{CStr, CString};
+use std::os::raw::*;
+
+/// Opaque handle of an open dynamic manifest.
+pub type DynManifestHandle = *mut c_void;
+
+/// A generator of Turtle data, which is exported with the
+/// [`lv2_dyn_manifest`](../macro.lv2_dyn_manifest.html) macro.
+///
+/// The host opens the manifest when it discovers the bundle, asks for the subjects it describes
+/// and then for the data of every subject. Afterwards, the manifest is closed by dropping it.
+pub trait DynManifest: Sized + 'static {
+    /// Open the manifest.
+    ///
+    /// The features are the features the host supports. If the manifest can't be opened,
+    /// `None` is returned.
+    fn open(features: Option<&core::FeaturesList>) -> Option<Self>;
+
+    /// Generate the Turtle data that lists the described subjects and their types.
+    ///
+    /// Plugins can be listed with [`plugin_subjects`](fn.plugin_subjects.html).
+    fn subjects(&mut self) -> String;
+
+    /// Generate the Turtle data of a subject, or `None` if the subject is unknown.
+    ///
+    /// The description of a plugin can be generated with the `to_turtle` method of
+    /// `lv2rs_core::ttl::PluginInfo`.
+    fn data(&mut self, uri: &CStr) -> Option<String>;
+}
+
+/// Generate the Turtle data that lists plugins as the subjects of a dynamic manifest.
+pub fn plugin_subjects(uris: &[&str]) -> String {
+    let mut ttl = String::from("@prefix lv2: <http://lv2plug.in/ns/lv2core#> .\n");
+    for uri in uris {
+        ttl.push_str(&format!("\n<{}> a lv2:Plugin .\n", uri));
+    }
+    ttl
+}
+
+/// Generate the static `manifest.ttl` of a bundle with a dynamic manifest.
+///
+/// The manifest declares the dynamic manifest with its URI and the file name of the binary that
+/// exports it.
+pub fn static_manifest(uri: &str, binary: &str) -> String {
+    format!(
+        "@prefix dman: <http://lv2plug.in/ns/ext/dynmanifest#> .\n\
+         @prefix lv2: <http://lv2plug.in/ns/lv2core#> .\n\
+         \n\
+         <{}>\n    a dman:DynManifest ;\n    lv2:binary <{}> .\n",
+        uri, binary
+    )
+}
+
+extern "C" {
+    fn fputs(string: *const c_char, stream: *mut c_void) -> c_int;
+}
+
+/// Write Turtle data to a C stream and return the status code for the host.
+unsafe fn write(stream: *mut c_void, data: String) -> c_int {
+    let data = match CString::new(data) {
+        Ok(data) => data,
+        Err(_) => return 1,
+    };
+    if stream.is_null() || fputs(data.as_ptr(), stream) < 0 {
+        1
+    } else {
+        0
+    }
+}
+
+/// Open a dynamic manifest and store its handle.
+///
+/// This function and the following ones implement the functions the extension's specification
+/// requires, which are exported by the [`lv2_dyn_manifest`](../macro.lv2_dyn_manifest.html)
+/// macro. They return zero on success and a non-zero value on failure.
+///
+/// This function is unsafe since it dereferences raw pointers, which have to be valid.
+pub unsafe fn open<M: DynManifest>(
+    handle: *mut DynManifestHandle,
+    features: *const *const core::Feature,
+) -> c_int {
+    if handle.is_null() {
+        return 1;
+    }
+    let features = core::Features::from_raw(features);
+    match M::open(features.map(|features| features.as_list())) {
+        Some(manifest) => {
+            *handle = Box::into_raw(Box::new(manifest)) as DynManifestHandle;
+            0
+        }
+        None => 1,
+    }
+}
+
+/// Write the subjects of a dynamic manifest to a C `FILE` stream.
+///
+/// This function is unsafe since it dereferences raw pointers, which have to be valid. The
+/// handle has to be created by [`open`](fn.open.html) with the same type.
+pub unsafe fn get_subjects<M: DynManifest>(
+    handle: DynManifestHandle,
+    stream: *mut c_void,
+) -> c_int {
+    match (handle as *mut M).as_mut() {
+        Some(manifest) => write(stream, manifest.subjects()),
+        None => 1,
+    }
+}
+
+/// Write the data of a subject to a C `FILE` stream.
+///
+/// This function is unsafe since it dereferences raw pointers, which have to be valid. The
+/// handle has to be created by [`open`](fn.open.html) with the same type.
+pub unsafe fn get_data<M: DynManifest>(
+    handle: DynManifestHandle,
+    stream: *mut c_void,
+    uri: *const c_char,
+) -> c_int {
+    let manifest = match (handle as *mut M).as_mut() {
+        Some(manifest) => manifest,
+        None => return 1,
+    };
+    if uri.is_null() {
+        return 1;
+    }
+    match manifest.data(CStr::from_ptr(uri)) {
+        Some(data) => write(stream, data),
+        None => 1,
+    }
+}
+
+/// Close a dynamic manifest and drop it.
+///
+/// This function is unsafe since the handle has to be created by [`open`](fn.open.html) with the
+/// same type and must not be used afterwards.
+pub unsafe fn close<M: DynManifest>(handle: DynManifestHandle) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle as *mut M));
+    }
+}
@@ -0,0 +1,5 @@
+//! URIs of the LV2 dynamic manifest extension.
+use core::uris::Uri;
+
+pub const URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/dynmanifest\0");
+pub const DYN_MANIFEST_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/dynmanifest#DynManifest\0");
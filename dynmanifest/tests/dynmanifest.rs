@@ -0,0 +1,118 @@
+extern crate lv2rs_core as core;
+extern crate lv2rs_dynmanifest as dynmanifest;
+
+use core::ttl::PluginInfo;
+use dynmanifest::*;
+use std::ffi::CStr;
+use std::os::raw::*;
+
+/// A manifest that describes one plugin for every name it was opened with.
+struct Generated {
+    names: Vec<&'static str>,
+}
+
+impl DynManifest for Generated {
+    fn open(features: Option<&core::FeaturesList>) -> Option<Self> {
+        // Plugins that are enabled by the host via a feature.
+        let names = features?
+            .iter()
+            .filter_map(|feature| feature.uri()?.to_str().ok())
+            .filter_map(|uri| uri.strip_prefix("http://example.org/enable#"))
+            .collect();
+        Some(Self { names })
+    }
+
+    fn subjects(&mut self) -> String {
+        let uris: Vec<String> = self
+            .names
+            .iter()
+            .map(|name| format!("http://example.org/{}", name))
+            .collect();
+        let uris: Vec<&str> = uris.iter().map(String::as_str).collect();
+        plugin_subjects(&uris)
+    }
+
+    fn data(&mut self, uri: &CStr) -> Option<String> {
+        let name = uri.to_str().ok()?.strip_prefix("http://example.org/")?;
+        let name = *self.names.iter().find(|other| **other == name)?;
+        Some(PluginInfo::new("http://example.org/plugin", name, &[]).to_turtle())
+    }
+}
+
+dynmanifest::lv2_dyn_manifest!(dynmanifest, Generated);
+
+extern "C" {
+    fn tmpfile() -> *mut c_void;
+    fn rewind(stream: *mut c_void);
+    fn fread(data: *mut c_void, size: usize, count: usize, stream: *mut c_void) -> usize;
+    fn fclose(stream: *mut c_void) -> c_int;
+}
+
+/// Call a function with a temporary C stream and return what it wrote.
+unsafe fn written(function: impl FnOnce(*mut c_void) -> c_int) -> Option<String> {
+    let stream = tmpfile();
+    assert!(!stream.is_null());
+    let status = function(stream);
+    rewind(stream);
+    let mut data = vec![0u8; 4096];
+    let length = fread(data.as_mut_ptr() as *mut c_void, 1, data.len(), stream);
+    fclose(stream);
+    data.truncate(length);
+    if status == 0 {
+        Some(String::from_utf8(data).unwrap())
+    } else {
+        assert!(data.is_empty());
+        None
+    }
+}
+
+#[test]
+fn test_dyn_manifest() {
+    let features = [
+        core::Feature::new(
+            b"http://example.org/enable#Bass\0".as_ptr() as *const c_char,
+            std::ptr::null_mut(),
+        ),
+        core::Feature::new(
+            b"http://example.org/enable#Treble\0".as_ptr() as *const c_char,
+            std::ptr::null_mut(),
+        ),
+    ];
+    let feature_list = [
+        &features[0] as *const core::Feature as *const c_void,
+        &features[1] as *const core::Feature as *const c_void,
+        std::ptr::null(),
+    ];
+
+    unsafe {
+        let mut handle: DynManifestHandle = std::ptr::null_mut();
+        assert_ne!(0, lv2_dyn_manifest_open(&mut handle, std::ptr::null()));
+        assert!(handle.is_null());
+        assert_eq!(0, lv2_dyn_manifest_open(&mut handle, feature_list.as_ptr()));
+        assert!(!handle.is_null());
+
+        let subjects = written(|stream| lv2_dyn_manifest_get_subjects(handle, stream)).unwrap();
+        assert_eq!(
+            "@prefix lv2: <http://lv2plug.in/ns/lv2core#> .\n\n\
+             <http://example.org/Bass> a lv2:Plugin .\n\n\
+             <http://example.org/Treble> a lv2:Plugin .\n",
+            subjects
+        );
+
+        let uri = b"http://example.org/Treble\0".as_ptr() as *const c_char;
+        let data = written(|stream| lv2_dyn_manifest_get_data(handle, stream, uri)).unwrap();
+        assert!(data.contains("doap:name \"Treble\""));
+
+        let uri = b"http://example.org/Mid\0".as_ptr() as *const c_char;
+        assert_eq!(
+            None,
+            written(|stream| lv2_dyn_manifest_get_data(handle, stream, uri))
+        );
+        assert_ne!(
+            0,
+            lv2_dyn_manifest_get_subjects(handle, std::ptr::null_mut())
+        );
+
+        lv2_dyn_manifest_close(handle);
+    }
+}
@@ -0,0 +1,91 @@
+//! The inline display interface, the queue draw feature and the trait to implement them.
+use crate::surface::*;
+use crate::uris;
+use core::uris::Uri;
+use std::os::raw::*;
+
+/// The queue draw feature.
+///
+/// The plugin uses it to tell the host that the display has changed and should be rendered
+/// again. It may be used in the audio thread.
+#[repr(C)]
+pub struct QueueDraw {
+    /// Pointer to a host-specific handle.
+    pub handle: *mut c_void,
+    /// Request the host to render the display again.
+    pub queue_draw: unsafe extern "C" fn(handle: *mut c_void),
+}
+
+unsafe impl core::FeatureData for QueueDraw {
+    const URI: Uri = uris::QUEUE_DRAW_URI;
+}
+
+impl QueueDraw {
+    /// Try to find the queue draw feature in the features.
+    pub fn try_from_features(features: &core::FeaturesList) -> Option<&'static mut Self> {
+        unsafe { core::Features::new(features).get::<Self>() }
+    }
+
+    /// Request the host to render the display again.
+    ///
+    /// The host calls [`render`](trait.InlineDisplay.html#tymethod.render) later, from a
+    /// non-real-time thread.
+    pub fn queue_draw(&self) {
+        unsafe { (self.queue_draw)(self.handle) }
+    }
+}
+
+/// A plugin with an inline display.
+pub trait InlineDisplay: core::Plugin {
+    /// Render the display.
+    ///
+    /// The surface has to be exactly `width` pixels wide and at most `max_height` pixels high.
+    /// Return `None` if there is nothing to display.
+    ///
+    /// This method is called in a non-real-time thread, but never concurrently with `run`. The
+    /// returned surface has to be owned by the plugin, since the host reads it after the call.
+    fn render(&mut self, width: u32, max_height: u32) -> Option<&mut Surface>;
+}
+
+/// The raw inline display interface.
+///
+/// An instance of this struct, created with [`new`](#method.new), has to be returned by the
+/// plugin's `extension_data` function for the [interface URI](uris/constant.INTERFACE_URI.html).
+#[repr(C)]
+pub struct InlineDisplayInterface {
+    /// Render the display of an instance.
+    pub render: unsafe extern "C" fn(
+        instance: core::Handle,
+        width: u32,
+        max_height: u32,
+    ) -> *mut ImageSurface,
+}
+
+impl core::ExtensionData for InlineDisplayInterface {}
+
+impl InlineDisplayInterface {
+    /// Create the inline display interface for a plugin type.
+    pub const fn new<P: InlineDisplay>() -> Self {
+        Self {
+            render: render::<P>,
+        }
+    }
+}
+
+unsafe extern "C" fn render<P: InlineDisplay>(
+    instance: core::Handle,
+    width: u32,
+    max_height: u32,
+) -> *mut ImageSurface {
+    core::guard(
+        instance,
+        "render",
+        std::ptr::null_mut(),
+        |plugin: &mut P| match plugin.render(width, max_height) {
+            Some(surface) if surface.width() == width && surface.height() <= max_height => {
+                surface.as_raw()
+            }
+            _ => std::ptr::null_mut(),
+        },
+    )
+}
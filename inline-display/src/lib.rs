@@ -0,0 +1,79 @@
+//! Small displays embedded in the host's GUI.
+//!
+//! This crate implements the
+//! [inline display extension](http://harrisonconsoles.com/lv2/inlinedisplay) of Ardour and
+//! Mixbus: Plugins draw previews, like waveforms or level meters, on an ARGB
+//! [`Surface`](struct.Surface.html) and the host displays them in its mixer strips. When the
+//! content changes, the plugin calls [`QueueDraw`](struct.QueueDraw.html) in its `run` method
+//! and the host later calls the plugin's [`render`](trait.InlineDisplay.html#tymethod.render)
+//! method in a non-real-time thread.
+//!
+//! The plugin has to return an [`InlineDisplayInterface`](struct.InlineDisplayInterface.html) for
+//! the [interface URI](uris/constant.INTERFACE_URI.html) in its `extension_data` function:
+//!
+//!     extern crate lv2rs_core as core;
+//!     extern crate lv2rs_inline_display as inline_display;
+//!
+//!     use inline_display::*;
+//!     use std::ffi::CStr;
+//!
+//!     struct Meter {
+//!         queue_draw: &'static QueueDraw,
+//!         level: f32,
+//!         surface: Surface,
+//!     }
+//!
+//!     impl core::Plugin for Meter {
+//!         fn instantiate(
+//!             _context: &core::InstantiationContext,
+//!             features: Option<&core::FeaturesList>,
+//!         ) -> Result<Self, core::InstantiateError> {
+//!             Ok(Self {
+//!                 queue_draw: features
+//!                     .and_then(QueueDraw::try_from_features)
+//!                     .ok_or_else(core::InstantiateError::missing_feature::<QueueDraw>)?,
+//!                 level: 0.0,
+//!                 surface: Surface::new(0, 0),
+//!             })
+//!         }
+//!
+//!         fn connect_port(&mut self, _port: u32, _data: *mut ()) {}
+//!
+//!         fn run(&mut self, _context: &mut core::RunContext) {
+//!             // Measure the level of the input and request a new display.
+//!             self.level = 0.5;
+//!             self.queue_draw.queue_draw();
+//!         }
+//!
+//!         fn extension_data(uri: &CStr) -> Option<&'static core::ExtensionData> {
+//!             core::extension_data!(uri, [(uris::INTERFACE_URI, &INLINE_DISPLAY_INTERFACE)])
+//!         }
+//!     }
+//!
+//!     impl InlineDisplay for Meter {
+//!         fn render(&mut self, width: u32, max_height: u32) -> Option<&mut Surface> {
+//!             let height = max_height.min(8);
+//!             let bar = (self.level * width as f32) as usize;
+//!             self.surface.resize(width, height);
+//!             self.surface.fill(argb(255, 0, 0, 0));
+//!             for y in 0..height {
+//!                 for pixel in self.surface.row_mut(y)?[..bar].iter_mut() {
+//!                     *pixel = argb(255, 0, 255, 0);
+//!                 }
+//!             }
+//!             Some(&mut self.surface)
+//!         }
+//!     }
+//!
+//!     static INLINE_DISPLAY_INTERFACE: InlineDisplayInterface =
+//!         InlineDisplayInterface::new::<Meter>();
+//!
+//!     # fn main() {}
+extern crate lv2rs_core as core;
+
+mod interface;
+mod surface;
+pub mod uris;
+
+pub use interface::*;
+pub use surface::*;
@@ -0,0 +1,120 @@
+//! Image surfaces the plugin draws on.
+use std::os::raw::*;
+
+/// The raw image surface that is returned to the host.
+///
+/// The pixels are stored row by row as 32-bit ARGB values with premultiplied alpha, like in a
+/// Cairo `ARGB32` surface. `stride` is the distance between two rows in bytes.
+#[repr(C)]
+pub struct ImageSurface {
+    pub data: *mut c_uchar,
+    pub width: c_int,
+    pub height: c_int,
+    pub stride: c_int,
+}
+
+/// Combine the components of a color to a pixel value.
+///
+/// The color components have to be premultiplied with the alpha value, which means that none of
+/// them may be greater than `alpha`.
+pub const fn argb(alpha: u8, red: u8, green: u8, blue: u8) -> u32 {
+    (alpha as u32) << 24 | (red as u32) << 16 | (green as u32) << 8 | blue as u32
+}
+
+/// An owned image surface.
+///
+/// The plugin keeps the surface between calls of
+/// [`render`](trait.InlineDisplay.html#tymethod.render), since the host reads the returned pixels
+/// after the call. Resizing the surface keeps its allocation if possible.
+pub struct Surface {
+    pixels: Vec<u32>,
+    width: u32,
+    height: u32,
+    raw: ImageSurface,
+}
+
+impl Surface {
+    /// Create a new, transparent surface with the given size.
+    pub fn new(width: u32, height: u32) -> Self {
+        let mut surface = Self {
+            pixels: Vec::new(),
+            width: 0,
+            height: 0,
+            raw: ImageSurface {
+                data: std::ptr::null_mut(),
+                width: 0,
+                height: 0,
+                stride: 0,
+            },
+        };
+        surface.resize(width, height);
+        surface
+    }
+
+    /// Return the width of the surface in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Return the height of the surface in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Change the size of the surface.
+    ///
+    /// The content of the surface is undefined afterwards.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.pixels.resize((width * height) as usize, 0);
+        self.width = width;
+        self.height = height;
+    }
+
+    /// Set all pixels to the given value.
+    pub fn fill(&mut self, pixel: u32) {
+        for value in self.pixels.iter_mut() {
+            *value = pixel;
+        }
+    }
+
+    /// Return the pixels of a row, or `None` if the row is out of bounds.
+    pub fn row(&self, y: u32) -> Option<&[u32]> {
+        if y >= self.height {
+            return None;
+        }
+        let start = (y * self.width) as usize;
+        Some(&self.pixels[start..start + self.width as usize])
+    }
+
+    /// Return the mutable pixels of a row, or `None` if the row is out of bounds.
+    pub fn row_mut(&mut self, y: u32) -> Option<&mut [u32]> {
+        if y >= self.height {
+            return None;
+        }
+        let start = (y * self.width) as usize;
+        Some(&mut self.pixels[start..start + self.width as usize])
+    }
+
+    /// Return all pixels, row by row.
+    pub fn pixels(&self) -> &[u32] {
+        &self.pixels
+    }
+
+    /// Return all pixels mutably, row by row.
+    pub fn pixels_mut(&mut self) -> &mut [u32] {
+        &mut self.pixels
+    }
+
+    /// Update the raw surface and return a pointer to it.
+    ///
+    /// The pointer is valid as long as the surface isn't changed or dropped.
+    pub fn as_raw(&mut self) -> *mut ImageSurface {
+        self.raw = ImageSurface {
+            data: self.pixels.as_mut_ptr() as *mut c_uchar,
+            width: self.width as c_int,
+            height: self.height as c_int,
+            stride: (self.width as usize * std::mem::size_of::<u32>()) as c_int,
+        };
+        &mut self.raw
+    }
+}
@@ -0,0 +1,9 @@
+//! URIs of the inline display extension.
+use core::uris::Uri;
+
+pub const URI: Uri = Uri::new(b"http://harrisonconsoles.com/lv2/inlinedisplay\0");
+pub const IN_GUI_URI: Uri = Uri::new(b"http://harrisonconsoles.com/lv2/inlinedisplay#in_gui\0");
+pub const INTERFACE_URI: Uri =
+    Uri::new(b"http://harrisonconsoles.com/lv2/inlinedisplay#interface\0");
+pub const QUEUE_DRAW_URI: Uri =
+    Uri::new(b"http://harrisonconsoles.com/lv2/inlinedisplay#queue_draw\0");
@@ -0,0 +1,124 @@
+extern crate lv2rs_core as core;
+extern crate lv2rs_inline_display as inline_display;
+
+use inline_display::*;
+use std::ffi::CStr;
+use std::os::raw::*;
+use std::ptr::null;
+
+const RED: u32 = argb(255, 255, 0, 0);
+const BLACK: u32 = argb(255, 0, 0, 0);
+
+/// A plugin that displays the number of processed blocks as a bar.
+struct BlockCounter {
+    queue_draw: &'static QueueDraw,
+    blocks: u32,
+    surface: Surface,
+}
+
+impl core::Plugin for BlockCounter {
+    fn instantiate(
+        _context: &core::InstantiationContext,
+        features: Option<&core::FeaturesList>,
+    ) -> Result<Self, core::InstantiateError> {
+        Ok(Self {
+            queue_draw: features
+                .and_then(QueueDraw::try_from_features)
+                .ok_or_else(core::InstantiateError::missing_feature::<QueueDraw>)?,
+            blocks: 0,
+            surface: Surface::new(0, 0),
+        })
+    }
+
+    fn connect_port(&mut self, _port: u32, _data: *mut ()) {}
+
+    fn run(&mut self, _context: &mut core::RunContext) {
+        self.blocks += 1;
+        self.queue_draw.queue_draw();
+    }
+
+    fn extension_data(uri: &CStr) -> Option<&'static dyn core::ExtensionData> {
+        core::extension_data!(uri, [(uris::INTERFACE_URI, &INLINE_DISPLAY_INTERFACE)])
+    }
+}
+
+impl InlineDisplay for BlockCounter {
+    fn render(&mut self, width: u32, max_height: u32) -> Option<&mut Surface> {
+        if self.blocks == 0 {
+            return None;
+        }
+        let bar = (self.blocks.min(width)) as usize;
+        self.surface.resize(width, max_height.min(2));
+        self.surface.fill(BLACK);
+        for y in 0..self.surface.height() {
+            for pixel in self.surface.row_mut(y)?[..bar].iter_mut() {
+                *pixel = RED;
+            }
+        }
+        Some(&mut self.surface)
+    }
+}
+
+static INLINE_DISPLAY_INTERFACE: InlineDisplayInterface =
+    InlineDisplayInterface::new::<BlockCounter>();
+
+core::lv2_main!(core, BlockCounter, b"http://example.org/BlockCounter\0");
+
+unsafe extern "C" fn count_draws(handle: *mut c_void) {
+    *(handle as *mut u32).as_mut().unwrap() += 1;
+}
+
+#[test]
+fn test_inline_display() {
+    let mut draws: u32 = 0;
+    let mut queue_draw = QueueDraw {
+        handle: &mut draws as *mut u32 as *mut c_void,
+        queue_draw: count_draws,
+    };
+    let queue_draw_feature = core::Feature::new(
+        uris::QUEUE_DRAW_URI.as_ptr(),
+        &mut queue_draw as *mut QueueDraw as *mut c_void,
+    );
+    let features: [*const core::Feature; 2] = [&queue_draw_feature, null()];
+
+    let descriptor = unsafe { lv2_descriptor(0) };
+    let descriptor_ref = unsafe { descriptor.as_ref() }.unwrap();
+    let bundle_path = b"/\0".as_ptr() as *const c_char;
+    let handle = unsafe {
+        (descriptor_ref.instantiate)(descriptor, 44100.0, bundle_path, features.as_ptr())
+    };
+    assert!(!handle.is_null());
+
+    let interface = unsafe { (descriptor_ref.extension_data)(uris::INTERFACE_URI.as_ptr()) };
+    let interface = unsafe { (interface as *const InlineDisplayInterface).as_ref() }.unwrap();
+
+    // Nothing to display yet.
+    assert!(unsafe { (interface.render)(handle, 4, 4) }.is_null());
+
+    unsafe {
+        (descriptor_ref.run)(handle, 64);
+        (descriptor_ref.run)(handle, 64);
+    }
+    assert_eq!(2, unsafe { *queue_draw.handle.cast::<u32>() });
+
+    let surface = unsafe { (interface.render)(handle, 4, 4).as_ref() }.unwrap();
+    assert_eq!((4, 2, 16), (surface.width, surface.height, surface.stride));
+    let pixels = unsafe { std::slice::from_raw_parts(surface.data as *const u32, 8) };
+    assert_eq!(&[RED, RED, BLACK, BLACK, RED, RED, BLACK, BLACK], pixels);
+
+    unsafe { (descriptor_ref.cleanup)(handle) };
+}
+
+#[test]
+fn test_surface() {
+    let mut surface = Surface::new(3, 2);
+    assert_eq!(&[0; 6], surface.pixels());
+    surface.row_mut(1).unwrap()[2] = RED;
+    assert_eq!(Some(&[0, 0, RED][..]), surface.row(1));
+    assert_eq!(None, surface.row(2));
+
+    surface.resize(2, 1);
+    surface.fill(BLACK);
+    assert_eq!(&[BLACK, BLACK], surface.pixels());
+    assert_eq!(0xff00_0000, BLACK);
+}
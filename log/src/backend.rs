@@ -0,0 +1,99 @@
+//! Integration with the `log` crate.
+//!
+//! A [`Logger`](../struct.Logger.html) can be installed as the backend of the
+//! [`log`](https://docs.rs/log/) crate with [`install`](../struct.Logger.html#method.install), so
+//! that the logging macros like `warn!` or `error!` print to the host's log. Since the `log` crate
+//! only supports one global backend but a library may contain several plugin instances, every
+//! instance installs its own logger and the messages are printed by the logger that was installed
+//! last. The returned [`LogGuard`](struct.LogGuard.html) uninstalls the logger when it is dropped,
+//! so it should be stored in the plugin.
+use crate::logger::*;
+use log::{Level, LevelFilter, Metadata, Record};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// The installed loggers, identified by the ID of their guard.
+static LOGGERS: Mutex<Vec<(usize, Logger<'static>)>> = Mutex::new(Vec::new());
+
+/// The ID of the next installed logger.
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Is the backend set as the logger of the `log` crate?
+static BACKEND_SET: AtomicBool = AtomicBool::new(false);
+
+static BACKEND: Backend = Backend {};
+
+/// The backend that forwards the records to the last installed logger.
+struct Backend {}
+
+impl log::Log for Backend {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        let entry_type = match record.level() {
+            Level::Error => EntryType::Error,
+            Level::Warn => EntryType::Warning,
+            Level::Info => EntryType::Note,
+            Level::Debug | Level::Trace => EntryType::Trace,
+        };
+        if let Ok(loggers) = LOGGERS.lock() {
+            if let Some((_, logger)) = loggers.last() {
+                let _ = logger.print(entry_type, &format!("{}\n", record.args()));
+            }
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// A guard that uninstalls a logger when it is dropped.
+pub struct LogGuard {
+    id: usize,
+}
+
+impl Drop for LogGuard {
+    fn drop(&mut self) {
+        if let Ok(mut loggers) = LOGGERS.lock() {
+            loggers.retain(|(id, _)| *id != self.id);
+        }
+    }
+}
+
+impl Logger<'static> {
+    /// Install the logger as the backend of the `log` crate.
+    ///
+    /// Records up to `max_level` are printed, which sets the maximal level of the `log` crate for
+    /// the whole library. This method fails if another backend was already set.
+    pub fn install(self, max_level: LevelFilter) -> Result<LogGuard, InstallError> {
+        let mut loggers = LOGGERS.lock().map_err(|_| InstallError::Poisoned)?;
+        if !BACKEND_SET.load(Ordering::Acquire) {
+            log::set_logger(&BACKEND).map_err(|_| InstallError::OtherBackend)?;
+            BACKEND_SET.store(true, Ordering::Release);
+        }
+        log::set_max_level(max_level);
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        loggers.push((id, self));
+        Ok(LogGuard { id })
+    }
+}
+
+/// Errors that may occur when a logger is installed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InstallError {
+    /// Another backend was already set for the `log` crate.
+    OtherBackend,
+    /// A thread panicked while it changed the installed loggers.
+    Poisoned,
+}
+
+impl fmt::Display for InstallError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InstallError::OtherBackend => write!(f, "Another backend was already set"),
+            InstallError::Poisoned => write!(f, "The list of installed loggers is poisoned"),
+        }
+    }
+}
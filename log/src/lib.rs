@@ -0,0 +1,73 @@
+//! Printing messages to the log of the host.
+//!
+//! This crate implements the [LV2 log extension](http://lv2plug.in/ns/ext/log/log.html): The host
+//! provides a log feature, which is wrapped by a [`Logger`](struct.Logger.html). Messages have one
+//! of four [entry types](enum.EntryType.html), which are mapped to URIDs when the logger is
+//! created.
+//!
+//! With the `log` feature, which is enabled by default, a logger can also be installed as the
+//! backend of the [`log`](https://docs.rs/log/) crate. See the [`backend`](backend/index.html)
+//! module for more information.
+//!
+//!     extern crate lv2rs_core as core;
+//!     extern crate lv2rs_log as lv2log;
+//!     extern crate lv2rs_urid as urid;
+//!     #[macro_use]
+//!     extern crate log;
+//!
+//!     use lv2log::{LogGuard, Logger};
+//!     use std::ffi::CStr;
+//!     use urid::CachedMap;
+//!
+//!     struct Plugin {
+//!         logger: Option<Logger<'static>>,
+//!         _log_guard: Option<LogGuard>,
+//!     }
+//!
+//!     impl core::Plugin for Plugin {
+//!         fn instantiate(
+//!             _context: &core::InstantiationContext,
+//!             features: Option<&core::FeaturesList>,
+//!         ) -> Result<Self, core::InstantiateError> {
+//!             let missing_map = core::InstantiateError::missing_feature::<urid::Map>;
+//!             let features = features.ok_or_else(missing_map)?;
+//!             let mut urids = CachedMap::try_from_features(features).ok_or_else(missing_map)?;
+//!             // The log is optional.
+//!             let logger = Logger::try_from_features(features, &mut urids);
+//!             let log_guard = logger
+//!                 .and_then(|logger| logger.install(log::LevelFilter::Warn).ok());
+//!             Ok(Self {
+//!                 logger,
+//!                 _log_guard: log_guard,
+//!             })
+//!         }
+//!
+//!         fn connect_port(&mut self, port: u32, _data: *mut ()) {
+//!             if port > 0 {
+//!                 warn!("Tried to connect unknown port {}", port);
+//!             }
+//!         }
+//!
+//!         fn run(&mut self, _context: &mut core::RunContext) {}
+//!
+//!         fn activate(&mut self) {
+//!             if let Some(logger) = self.logger {
+//!                 let _ = logger.note("Activated\n");
+//!             }
+//!         }
+//!     }
+//!
+//!     # fn main() {}
+#[cfg(feature = "log")]
+extern crate log;
+extern crate lv2rs_core as core;
+extern crate lv2rs_urid as urid;
+
+#[cfg(feature = "log")]
+pub mod backend;
+mod logger;
+pub mod uris;
+
+#[cfg(feature = "log")]
+pub use backend::{InstallError, LogGuard};
+pub use logger::*;
@@ -0,0 +1,142 @@
+//! The log feature and a safe wrapper around it.
+use crate::uris::{self, LogURIDs};
+use core::uris::Uri;
+use std::ffi::CString;
+use std::fmt;
+use std::os::raw::*;
+use urid::{CachedMap, URID};
+
+/// Type to describe pointers to the host's log handles.
+pub type LogHandle = *mut c_void;
+
+/// The host's function to print a formatted message.
+pub type PrintfFunction =
+    unsafe extern "C" fn(handle: LogHandle, entry_type: URID, format: *const c_char, ...) -> c_int;
+
+/// The log feature.
+#[repr(C)]
+pub struct Log {
+    /// Pointer to a host-specific handle.
+    pub handle: LogHandle,
+    /// Print a formatted message to the log.
+    pub printf: PrintfFunction,
+    /// Print a formatted message to the log, with the arguments given as a `va_list`.
+    ///
+    /// `va_list`s can't be created in Rust, so this function is never called.
+    pub vprintf: unsafe extern "C" fn(
+        handle: LogHandle,
+        entry_type: URID,
+        format: *const c_char,
+        arguments: *mut c_void,
+    ) -> c_int,
+}
+
+unsafe impl core::FeatureData for Log {
+    const URI: Uri = uris::LOG_URI;
+}
+
+/// The type of a log entry.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EntryType {
+    /// An error message.
+    Error,
+    /// An informative message.
+    Note,
+    /// A debugging message, which is usually only shown to developers.
+    Trace,
+    /// A warning message.
+    Warning,
+}
+
+/// A safe wrapper around the log feature.
+///
+/// According to the log extension, the log may be used from every thread, including the audio
+/// thread. However, formatting messages may allocate memory, so logging in `run` should still be
+/// rare.
+#[derive(Clone, Copy)]
+pub struct Logger<'a> {
+    log: &'a Log,
+    urids: LogURIDs,
+}
+
+unsafe impl<'a> Send for Logger<'a> {}
+unsafe impl<'a> Sync for Logger<'a> {}
+
+impl<'a> Logger<'a> {
+    /// Wrap a reference to the log feature.
+    pub fn new(log: &'a Log, urids: LogURIDs) -> Self {
+        Self { log, urids }
+    }
+
+    /// Try to find the log feature in the features list.
+    ///
+    /// The URIDs of the entry types are mapped with the `CachedMap`. `None` is returned if the
+    /// host does not provide a log.
+    pub fn try_from_features(
+        features: &core::FeaturesList,
+        map: &mut CachedMap,
+    ) -> Option<Logger<'static>> {
+        let log = unsafe { core::Features::new(features).get::<Log>() }?;
+        Some(Logger::new(log, LogURIDs::new(map)))
+    }
+
+    /// Print a message to the log.
+    ///
+    /// The message is printed as it is, so it should usually end with a newline. This method fails
+    /// if the message contains a null byte or the host failed to print it.
+    pub fn print(&self, entry_type: EntryType, message: &str) -> Result<(), PrintError> {
+        let entry_type = match entry_type {
+            EntryType::Error => self.urids.error,
+            EntryType::Note => self.urids.note,
+            EntryType::Trace => self.urids.trace,
+            EntryType::Warning => self.urids.warning,
+        };
+        let message = CString::new(message).map_err(|_| PrintError::NulByte)?;
+        let format = b"%s\0".as_ptr() as *const c_char;
+        let printed =
+            unsafe { (self.log.printf)(self.log.handle, entry_type, format, message.as_ptr()) };
+        if printed < 0 {
+            Err(PrintError::HostFailed)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Print an error message to the log.
+    pub fn error(&self, message: &str) -> Result<(), PrintError> {
+        self.print(EntryType::Error, message)
+    }
+
+    /// Print an informative message to the log.
+    pub fn note(&self, message: &str) -> Result<(), PrintError> {
+        self.print(EntryType::Note, message)
+    }
+
+    /// Print a debugging message to the log.
+    pub fn trace(&self, message: &str) -> Result<(), PrintError> {
+        self.print(EntryType::Trace, message)
+    }
+
+    /// Print a warning message to the log.
+    pub fn warning(&self, message: &str) -> Result<(), PrintError> {
+        self.print(EntryType::Warning, message)
+    }
+}
+
+/// Errors that may occur when printing a message to the log.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PrintError {
+    /// The message contains a null byte.
+    NulByte,
+    /// The host failed to print the message.
+    HostFailed,
+}
+
+impl fmt::Display for PrintError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PrintError::NulByte => write!(f, "The message contains a null byte"),
+            PrintError::HostFailed => write!(f, "The host failed to print the message"),
+        }
+    }
+}
@@ -0,0 +1,22 @@
+//! URIs of the LV2 log extension.
+use core::uris::Uri;
+
+pub const URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/log\0");
+pub const ENTRY_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/log#Entry\0");
+pub const ERROR_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/log#Error\0");
+pub const NOTE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/log#Note\0");
+pub const TRACE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/log#Trace\0");
+pub const WARNING_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/log#Warning\0");
+pub const LOG_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/log#log\0");
+
+urid::urid_cache! {
+    /// The URIDs of the log entry types.
+    ///
+    /// Map them with `LogURIDs::new(&mut cached_map)` in `instantiate`.
+    pub struct LogURIDs {
+        pub error: ERROR_URI,
+        pub note: NOTE_URI,
+        pub trace: TRACE_URI,
+        pub warning: WARNING_URI,
+    }
+}
@@ -0,0 +1,88 @@
+extern crate lv2rs_core as core;
+extern crate lv2rs_log as lv2log;
+extern crate lv2rs_urid as urid;
+#[macro_use]
+extern crate log;
+
+use lv2log::*;
+use std::ffi::CStr;
+use std::os::raw::*;
+use std::sync::Mutex;
+use urid::debug::DebugMap;
+use urid::URID;
+
+/// The entries printed by the test log.
+type Entries = Mutex<Vec<(URID, String)>>;
+
+/// The printf function of the test log.
+///
+/// Variadic functions can't be defined in Rust. Since the logger always passes the message as a
+/// single pointer argument, this function is called with a compatible signature instead.
+unsafe extern "C" fn printf(
+    handle: LogHandle,
+    entry_type: URID,
+    format: *const c_char,
+    message: *const c_char,
+) -> c_int {
+    assert_eq!(b"%s", CStr::from_ptr(format).to_bytes());
+    let message = CStr::from_ptr(message).to_str().unwrap().to_owned();
+    let length = message.len() as c_int;
+    let entries = (handle as *const Entries).as_ref().unwrap();
+    entries.lock().unwrap().push((entry_type, message));
+    length
+}
+
+unsafe extern "C" fn vprintf(
+    _handle: LogHandle,
+    _entry_type: URID,
+    _format: *const c_char,
+    _arguments: *mut c_void,
+) -> c_int {
+    -1
+}
+
+#[test]
+fn test_log() {
+    let entries = Entries::new(Vec::new());
+    let mut log = Log {
+        handle: &entries as *const Entries as *mut c_void,
+        printf: unsafe { std::mem::transmute::<*const (), PrintfFunction>(printf as *const ()) },
+        vprintf,
+    };
+    let log_feature = core::Feature::new(
+        lv2log::uris::LOG_URI.as_ptr(),
+        &mut log as *mut Log as *mut c_void,
+    );
+    let features: [&'static core::Feature; 1] = [unsafe { &*(&log_feature as *const _) }];
+
+    let mut debug_map = DebugMap::new();
+    let mut urids = unsafe { debug_map.create_cached_map() };
+    assert!(Logger::try_from_features(&[], &mut urids).is_none());
+    let logger = Logger::try_from_features(&features, &mut urids).unwrap();
+    let log_urids = lv2log::uris::LogURIDs::new(&mut urids);
+
+    // Printing directly.
+    logger.error("error\n").unwrap();
+    logger.note("note\n").unwrap();
+    assert_eq!(Err(PrintError::NulByte), logger.warning("invalid\0message"));
+
+    // Printing with the log crate.
+    {
+        let _guard = logger.install(log::LevelFilter::Info).unwrap();
+        warn!("Warning {}", 1);
+        info!("Info {}", 2);
+        debug!("Debug {}", 3);
+    }
+    // Without an installed logger, messages are dropped.
+    error!("Error {}", 4);
+
+    assert_eq!(
+        vec![
+            (log_urids.error, "error\n".to_owned()),
+            (log_urids.note, "note\n".to_owned()),
+            (log_urids.warning, "Warning 1\n".to_owned()),
+            (log_urids.note, "Info 2\n".to_owned()),
+        ],
+        *entries.lock().unwrap()
+    );
+}
@@ -0,0 +1,14 @@
+#[macro_use]
+extern crate criterion;
+extern crate lv2rs_midi as midi;
+
+use criterion::Criterion;
+
+fn bench_scenarios(c: &mut Criterion) {
+    for mut scenario in midi::bench::scenarios() {
+        c.bench_function(scenario.name(), |b| b.iter(|| scenario.run()));
+    }
+}
+
+criterion_group!(benches, bench_scenarios);
+criterion_main!(benches);
@@ -1,9 +1,8 @@
 /// Atom types for MIDI handling.
 use crate::message::*;
-use crate::prelude::*;
 use crate::status_bytes::*;
 use lv2rs_atom::prelude::*;
-use lv2rs_urid::CachedMap;
+use lv2rs_urid::{CachedMap, URID};
 use std::ffi::CStr;
 
 #[repr(C)]
@@ -28,13 +27,41 @@ impl RawMidiMessage {
     pub fn interpret(&self) -> Result<MidiMessage, TryFromError> {
         MidiMessage::try_from(&self.0)
     }
+
+    /// Return the raw bytes of the message, including the status byte.
+    ///
+    /// This is useful if you only want to pass the message through or copy it, since it doesn't
+    /// have the cost of [`interpret`](#method.interpret) and can not fail.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Return the length of the message in bytes, including the status byte.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Return `true` if the message doesn't contain any bytes.
+    ///
+    /// Since a valid message always contains a status byte, this is never the case for messages
+    /// read from an atom.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Return the status byte of the message.
+    ///
+    /// For channel messages, the status byte includes the channel number in its lower four bits.
+    pub fn status(&self) -> u8 {
+        self.0[0]
+    }
 }
 
 impl<'a> AtomBody for RawMidiMessage {
     type InitializationParameter = MidiMessage;
 
     fn get_uri() -> &'static CStr {
-        unsafe { CStr::from_bytes_with_nul_unchecked(crate::uris::EVENT_URI) }
+        crate::uris::EVENT_URI.as_cstr()
     }
 
     unsafe fn initialize_body<'b, W>(
@@ -45,93 +72,8 @@ impl<'a> AtomBody for RawMidiMessage {
     where
         W: WritingFrame<'b> + WritingFrameExt<'b, Self>,
     {
-        match message {
-            MidiMessage::NoteOff {
-                channel,
-                note,
-                velocity,
-            } => {
-                write_channel_status(writer, NOTE_OFF_STATUS, *channel)?;
-                write_data(writer, *note)?;
-                write_data(writer, *velocity)?;
-            }
-            MidiMessage::NoteOn {
-                channel,
-                note,
-                velocity,
-            } => {
-                write_channel_status(writer, NOTE_ON_STATUS, *channel)?;
-                write_data(writer, *note)?;
-                write_data(writer, *velocity)?;
-            }
-            MidiMessage::PolyKeyPressure { channel, pressure } => {
-                write_channel_status(writer, POLY_KEY_PRESSURE_STATUS, *channel)?;
-                write_data(writer, *pressure)?;
-            }
-            MidiMessage::ControlChange {
-                channel,
-                control_number,
-                control_value,
-            } => {
-                write_channel_status(writer, CONTROL_CHANGE_STATUS, *channel)?;
-                write_data(writer, *control_number)?;
-                write_data(writer, *control_value)?;
-            }
-            MidiMessage::ProgramChange {
-                channel,
-                program_number,
-            } => {
-                write_channel_status(writer, PROGRAM_CHANGE_STATUS, *channel)?;
-                write_data(writer, *program_number)?;
-            }
-            MidiMessage::ChannelPressure { channel, pressure } => {
-                write_channel_status(writer, CHANNEL_PRESSURE_STATUS, *channel)?;
-                write_data(writer, *pressure)?;
-            }
-            MidiMessage::PitchBendChange { channel, value } => {
-                write_channel_status(writer, PITCH_BEND_CHANGE_STATUS, *channel)?;
-                write_u14_data(writer, *value)?;
-            }
-            MidiMessage::TimeCodeQuarterFrame {
-                message_type,
-                value,
-            } => {
-                writer.write_sized(&TIME_CODE_QUARTER_FRAME_STATUS)?;
-                let message_type: u8 = (*message_type).into();
-                let value: u8 = (*value).into();
-                let byte: u8 = value + (message_type << 4);
-                writer.write_sized(&byte)?;
-            }
-            MidiMessage::SongPositionPointer { position } => {
-                writer.write_sized(&SONG_POSITION_POINTER_STATUS)?;
-                write_u14_data(writer, *position)?;
-            }
-            MidiMessage::SongSelect { song } => {
-                writer.write_sized(&SONG_SELECT_STATUS)?;
-                write_data(writer, *song)?;
-            }
-            MidiMessage::TuneRequest => {
-                writer.write_sized(&TUNE_REQUEST_STATUS)?;
-            }
-            MidiMessage::TimingClock => {
-                writer.write_sized(&TIMING_CLOCK_STATUS)?;
-            }
-            MidiMessage::Start => {
-                writer.write_sized(&START_STATUS)?;
-            }
-            MidiMessage::Continue => {
-                writer.write_sized(&CONTINUE_STATUS)?;
-            }
-            MidiMessage::Stop => {
-                writer.write_sized(&STOP_STATUS)?;
-            }
-            MidiMessage::ActiveSensing => {
-                writer.write_sized(&ACTIVE_SENSING_STATUS)?;
-            }
-            MidiMessage::SystemReset => {
-                writer.write_sized(&SYSTEM_RESET_STATUS)?;
-            }
-        }
+        let (bytes, len) = message.to_bytes();
+        writer.write_raw(&bytes[..len])?;
         Ok(())
     }
 
@@ -145,11 +87,11 @@ impl<'a> AtomBody for RawMidiMessage {
             return Err(());
         }
         // The second byte must not be a status byte.
-        if (raw_data.len() >= 2) & (raw_data[1] & 0b1000_0000 != 0) {
+        if (raw_data.len() >= 2) && (raw_data[1] & 0b1000_0000 != 0) {
             return Err(());
         }
         // The third byte must not be a status byte.
-        if (raw_data.len() == 3) & (raw_data[2] & 0b1000_0000 != 0) {
+        if (raw_data.len() == 3) && (raw_data[2] & 0b1000_0000 != 0) {
             return Err(());
         }
         // Construct and return the reference.
@@ -177,13 +119,26 @@ impl SystemExclusiveMessage {
         let len = data.len();
         &data[1..len - 1]
     }
+
+    /// Iterate over the data bytes in chunks of `chunk_size` bytes.
+    ///
+    /// The start and end status bytes aren't included and the last chunk may be shorter than
+    /// `chunk_size`. No data is copied, which makes this method useful to stream big dumps, like
+    /// patch banks or firmware, to a worker thread or to disk.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    pub fn chunks(&self, chunk_size: usize) -> std::slice::Chunks<'_, u8> {
+        self.get_data().chunks(chunk_size)
+    }
 }
 
 impl<'a> AtomBody for SystemExclusiveMessage {
     type InitializationParameter = [u8];
 
     fn get_uri() -> &'static CStr {
-        unsafe { CStr::from_bytes_with_nul_unchecked(crate::uris::EVENT_URI) }
+        crate::uris::EVENT_URI.as_cstr()
     }
 
     unsafe fn initialize_body<'b, W>(
@@ -231,34 +186,61 @@ impl<'a> AtomBody for SystemExclusiveMessage {
     }
 }
 
-unsafe fn write_channel_status<'a, W, A>(writer: &mut W, status: u8, channel: u4) -> Result<(), ()>
-where
-    W: WritingFrame<'a> + WritingFrameExt<'a, A>,
-    A: AtomBody + ?Sized,
-{
-    let channel: u8 = channel.into();
-    let status = status + channel;
-    writer.write_sized(&status).map(|_| ())
+/// A MIDI event of a sequence, either a "normal" message or a system-exclusive one.
+pub(crate) enum MidiEvent<'a> {
+    Message(MidiMessage),
+    SystemExclusive(&'a [u8]),
 }
 
-unsafe fn write_data<'a, W, A>(writer: &mut W, data: u7) -> Result<(), ()>
-where
-    W: WritingFrame<'a> + WritingFrameExt<'a, A>,
-    A: AtomBody + ?Sized,
-{
-    let data: u8 = data.into();
-    writer.write_sized(&data).map(|_| ())
+impl<'a> MidiEvent<'a> {
+    /// Try to read a MIDI event from an atom.
+    ///
+    /// `midi_urid` has to be the URID of the MIDI event type. `None` is returned if the atom isn't
+    /// a MIDI event or can't be interpreted.
+    pub(crate) fn read(atom: &'a Atom, midi_urid: URID) -> Option<Self> {
+        if atom.atom_type() != midi_urid {
+            return None;
+        }
+        let raw_body = atom.get_raw_body();
+        if let Ok(message) = RawMidiMessage::create_ref(raw_body) {
+            Some(MidiEvent::Message(message.interpret().ok()?))
+        } else {
+            let message = SystemExclusiveMessage::create_ref(raw_body).ok()?;
+            Some(MidiEvent::SystemExclusive(message.get_data()))
+        }
+    }
 }
 
-unsafe fn write_u14_data<'a, W, A>(writer: &mut W, data: u14) -> Result<(), ()>
-where
-    W: WritingFrame<'a> + WritingFrameExt<'a, A>,
-    A: AtomBody + ?Sized,
-{
-    let data: u16 = data.into();
-    let msb: u8 = ((data & 0b0011_1111_1000_0000) >> 7) as u8;
-    let lsb: u8 = (data & 0b0000_0000_0111_1111) as u8;
-    writer.write_sized(&lsb)?;
-    writer.write_sized(&msb)?;
-    Ok(())
+#[cfg(test)]
+mod test {
+    use crate::atom::*;
+
+    #[test]
+    fn test_sysex_chunks() {
+        let raw: [u8; 7] = [
+            START_OF_SYSTEM_EXCLUSIVE_STATUS,
+            1,
+            2,
+            3,
+            4,
+            5,
+            END_OF_SYSTEM_EXCLUSICE_STATUS,
+        ];
+        let message = SystemExclusiveMessage::create_ref(&raw).unwrap();
+        let chunks: Vec<&[u8]> = message.chunks(2).collect();
+        assert_eq!(vec![&[1, 2][..], &[3, 4][..], &[5][..]], chunks);
+    }
+
+    #[test]
+    fn test_raw_bytes() {
+        let raw: [u8; 3] = [NOTE_ON_STATUS + 2, 60, 100];
+        let message = RawMidiMessage::create_ref(&raw).unwrap();
+        assert_eq!(&raw, message.as_bytes());
+        assert_eq!(3, message.len());
+        assert_eq!(NOTE_ON_STATUS + 2, message.status());
+
+        let raw: [u8; 1] = [TIMING_CLOCK_STATUS];
+        let message = RawMidiMessage::create_ref(&raw).unwrap();
+        assert_eq!(1, message.len());
+    }
 }
@@ -0,0 +1,97 @@
+//! Reusable benchmark scenarios.
+//!
+//! This module is only available with the `bench` feature and extends the
+//! [scenarios of `lv2rs-atom`](https://docs.rs/lv2rs-atom/) with MIDI workloads. The benchmarks of
+//! this crate are run with `cargo bench --features bench`.
+use crate::atom::RawMidiMessage;
+use crate::message::MidiMessage;
+use crate::prelude::*;
+use lv2rs_atom::bench::output_port;
+use lv2rs_atom::prelude::*;
+use lv2rs_atom::sequence::{TimeStamp, TimeUnit};
+use lv2rs_urid::debug::DebugMap;
+use lv2rs_urid::CachedMap;
+
+pub use lv2rs_atom::bench::Scenario;
+
+/// Return the scenarios of this crate with their default sizes.
+pub fn scenarios() -> Vec<Box<dyn Scenario>> {
+    vec![Box::new(WriteMidiEvents::new(1000))]
+}
+
+/// Write a sequence of note on and note off messages.
+pub struct WriteMidiEvents {
+    urids: CachedMap,
+    _map: Box<DebugMap>,
+    space: Vec<u64>,
+    messages: Vec<MidiMessage>,
+}
+
+impl WriteMidiEvents {
+    /// Create the scenario with the number of events in the sequence.
+    pub fn new(n_events: usize) -> Self {
+        let mut map = DebugMap::new();
+        let urids = unsafe { map.create_cached_map() };
+        let messages = (0..n_events)
+            .map(|index| {
+                let channel = u4::new(0);
+                let note = u7::new(index as u8 / 2 % 128);
+                if index % 2 == 0 {
+                    MidiMessage::NoteOn {
+                        channel,
+                        note,
+                        velocity: u7::new(100),
+                    }
+                } else {
+                    MidiMessage::NoteOff {
+                        channel,
+                        note,
+                        velocity: u7::new(0),
+                    }
+                }
+            })
+            .collect();
+        Self {
+            urids,
+            _map: map,
+            // The headers of the sequence and a time stamp, an atom header and a padded message
+            // for every event.
+            space: vec![0u64; 2 + 3 * n_events],
+            messages,
+        }
+    }
+}
+
+impl Scenario for WriteMidiEvents {
+    fn name(&self) -> &'static str {
+        "write_midi_events"
+    }
+
+    fn run(&mut self) -> usize {
+        let urids = &mut self.urids;
+        let mut port = output_port::<Sequence>(&mut self.space);
+        let mut sequence = unsafe { port.write_atom_body(&TimeUnit::Frames, urids) }.unwrap();
+        for (index, message) in self.messages.iter().enumerate() {
+            sequence
+                .push_event::<RawMidiMessage>(TimeStamp::Frames(index as i64), message, urids)
+                .unwrap();
+        }
+        sequence.get_atom().size()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bench::*;
+
+    #[test]
+    fn test_write_midi_events() {
+        let mut scenario = WriteMidiEvents::new(10);
+        assert_eq!("write_midi_events", scenario.name());
+        assert_eq!(8 + 10 * 24, scenario.run());
+        assert_eq!(8 + 10 * 24, scenario.run());
+
+        let mut scenario = scenarios().pop().unwrap();
+        assert_eq!(8 + 1000 * 24, scenario.run());
+    }
+}
@@ -0,0 +1,568 @@
+//! MIDI Capability Inquiry messages.
+//!
+//! MIDI Capability Inquiry (MIDI-CI) is the part of MIDI 2.0 that lets devices find each other and
+//! negotiate what they can do. Its messages are universal non-real-time system-exclusive messages
+//! that are addressed with MIDI unique identifiers, or [MUIDs](struct.Muid.html): Every device
+//! picks a random MUID and uses it as the source of all of its messages, and replies are sent to
+//! the MUID of the inquiring device.
+//!
+//! This module supports discovery, which tells other devices who you are, and property exchange,
+//! which transfers JSON-encoded properties in chunks. Like the
+//! [universal messages](../universal/index.html), the types in this module work on the data bytes
+//! of system-exclusive messages, without the start and end status bytes. Parsing does not copy any
+//! data, which means that property data of a parsed message borrows from the system-exclusive
+//! message.
+//!
+//! Newer versions of MIDI-CI append fields to existing messages. When parsing, these additional
+//! fields are ignored, as the specification demands, and messages are always encoded in the
+//! layout of version 1.1.
+//!
+//!     extern crate lv2rs_midi as midi;
+//!
+//!     use midi::prelude::*;
+//!     use midi::capability_inquiry::*;
+//!
+//!     let own_muid = Muid::from_random(0x1234_5678);
+//!     let info = DeviceInfo {
+//!         manufacturer: [0x7d, 0x00, 0x00],
+//!         family: u14::new(1),
+//!         model: u14::new(2),
+//!         revision: [0, 1, 0, 0],
+//!         categories: PROPERTY_EXCHANGE_CATEGORY,
+//!         max_sysex_size: 512,
+//!     };
+//!
+//!     // Another device asks who is out there.
+//!     let discovery = CiMessage {
+//!         device_id: u7::new(WHOLE_PORT),
+//!         version: u7::new(VERSION),
+//!         source: Muid::from_random(42),
+//!         destination: Muid::BROADCAST,
+//!         body: CiBody::Discovery(info),
+//!     };
+//!     let mut buffer = [0u8; 64];
+//!     let data = discovery.encode(&mut buffer).unwrap();
+//!
+//!     // We parse the inquiry and answer it.
+//!     let inquiry = CiMessage::parse(data).unwrap();
+//!     assert!(inquiry.addresses(own_muid));
+//!     let reply = inquiry.reply(own_muid, CiBody::DiscoveryReply(info));
+//!     assert_eq!(inquiry.source, reply.destination);
+//!     assert_eq!(own_muid, reply.source);
+use crate::prelude::*;
+use crate::universal::{all_data_bytes, join_u14, split_u14, NON_REALTIME_ID};
+
+/// The sub-ID of all MIDI-CI messages.
+pub const CAPABILITY_INQUIRY_SUB_ID: u8 = 0x0d;
+
+/// The MIDI-CI version this module encodes, which is version 1.1.
+pub const VERSION: u8 = 0x01;
+
+/// The device ID that addresses the whole MIDI port instead of a single channel.
+pub const WHOLE_PORT: u8 = 0x7f;
+
+/// The category flag of devices that support protocol negotiation.
+pub const PROTOCOL_NEGOTIATION_CATEGORY: u8 = 0b0000_0010;
+/// The category flag of devices that support profile configuration.
+pub const PROFILE_CONFIGURATION_CATEGORY: u8 = 0b0000_0100;
+/// The category flag of devices that support property exchange.
+pub const PROPERTY_EXCHANGE_CATEGORY: u8 = 0b0000_1000;
+
+const PROPERTY_EXCHANGE_CAPABILITIES_ID: u8 = 0x30;
+const PROPERTY_EXCHANGE_CAPABILITIES_REPLY_ID: u8 = 0x31;
+const GET_PROPERTY_DATA_ID: u8 = 0x34;
+const GET_PROPERTY_DATA_REPLY_ID: u8 = 0x35;
+const SET_PROPERTY_DATA_ID: u8 = 0x36;
+const SET_PROPERTY_DATA_REPLY_ID: u8 = 0x37;
+const DISCOVERY_ID: u8 = 0x70;
+const DISCOVERY_REPLY_ID: u8 = 0x71;
+const INVALIDATE_MUID_ID: u8 = 0x7e;
+const NAK_ID: u8 = 0x7f;
+
+/// Split a 28-bit value into four 7-bit bytes, least significant first.
+fn split_u28(value: u32) -> [u8; 4] {
+    [
+        (value & 0x7f) as u8,
+        ((value >> 7) & 0x7f) as u8,
+        ((value >> 14) & 0x7f) as u8,
+        ((value >> 21) & 0x7f) as u8,
+    ]
+}
+
+/// Join four 7-bit bytes, least significant first, to a 28-bit value.
+fn join_u28(bytes: &[u8]) -> u32 {
+    bytes[0] as u32 | (bytes[1] as u32) << 7 | (bytes[2] as u32) << 14 | (bytes[3] as u32) << 21
+}
+
+/// A MIDI unique identifier.
+///
+/// MUIDs are 28-bit numbers. The highest 256 values are reserved: The very highest one is the
+/// broadcast MUID, which addresses all devices, and the others may not be used at all.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Muid(u32);
+
+impl Muid {
+    /// The MUID that addresses all devices.
+    pub const BROADCAST: Muid = Muid(0x0fff_ffff);
+
+    /// The first reserved MUID.
+    const FIRST_RESERVED: u32 = 0x0fff_ff00;
+
+    /// Create a MUID from a number.
+    ///
+    /// `None` is returned if the number has more than 28 bits or is reserved. The broadcast MUID
+    /// is accepted.
+    pub fn new(value: u32) -> Option<Self> {
+        if value == Self::BROADCAST.0 || value < Self::FIRST_RESERVED {
+            Some(Muid(value))
+        } else {
+            None
+        }
+    }
+
+    /// Create a device MUID from a random number.
+    ///
+    /// Since MUIDs should be picked randomly, but this crate does not depend on a random number
+    /// generator, you have to provide the random number yourself. Superfluous bits are dropped
+    /// and reserved values are moved into the valid range, which means that the returned MUID is
+    /// never reserved or the broadcast MUID.
+    pub fn from_random(random: u32) -> Self {
+        let value = random & 0x0fff_ffff;
+        if value >= Self::FIRST_RESERVED {
+            Muid(value - 0x100)
+        } else {
+            Muid(value)
+        }
+    }
+
+    /// Return the number of the MUID.
+    pub fn value(self) -> u32 {
+        self.0
+    }
+
+    /// Return `true` if this is the broadcast MUID.
+    pub fn is_broadcast(self) -> bool {
+        self == Self::BROADCAST
+    }
+
+    fn parse(bytes: &[u8]) -> Result<Self, ()> {
+        Self::new(join_u28(bytes)).ok_or(())
+    }
+}
+
+/// The identity and capabilities of a device, sent in discovery messages.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DeviceInfo {
+    /// The system-exclusive ID of the manufacturer.
+    ///
+    /// One-byte IDs are encoded as the ID, followed by two zeros.
+    pub manufacturer: [u8; 3],
+    /// The device family, as defined by the manufacturer.
+    pub family: u14,
+    /// The model of the device, as defined by the manufacturer.
+    pub model: u14,
+    /// The software revision, as defined by the manufacturer.
+    pub revision: [u8; 4],
+    /// The category flags of all supported MIDI-CI features.
+    pub categories: u8,
+    /// The length of the longest system-exclusive message the device can receive.
+    pub max_sysex_size: u32,
+}
+
+impl DeviceInfo {
+    const ENCODED_LEN: usize = 16;
+
+    fn parse(data: &[u8]) -> Result<Self, ()> {
+        if data.len() < Self::ENCODED_LEN {
+            return Err(());
+        }
+        let mut manufacturer = [0; 3];
+        manufacturer.copy_from_slice(&data[0..3]);
+        let mut revision = [0; 4];
+        revision.copy_from_slice(&data[7..11]);
+        Ok(Self {
+            manufacturer,
+            family: join_u14(data[3], data[4]),
+            model: join_u14(data[5], data[6]),
+            revision,
+            categories: data[11],
+            max_sysex_size: join_u28(&data[12..16]),
+        })
+    }
+
+    fn encode(&self, buffer: &mut [u8]) {
+        buffer[0..3].copy_from_slice(&self.manufacturer);
+        let (lsb, msb) = split_u14(self.family);
+        buffer[3] = lsb;
+        buffer[4] = msb;
+        let (lsb, msb) = split_u14(self.model);
+        buffer[5] = lsb;
+        buffer[6] = msb;
+        buffer[7..11].copy_from_slice(&self.revision);
+        buffer[11] = self.categories;
+        buffer[12..16].copy_from_slice(&split_u28(self.max_sysex_size));
+    }
+}
+
+/// A chunk of a property exchange.
+///
+/// Property exchange messages contain a JSON header, which names the property and may contain
+/// further parameters, and a part of the property data. Since the data may be larger than a single
+/// system-exclusive message, it may be split into several chunks. Chunks are numbered starting
+/// with one.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PropertyChunk<'a> {
+    /// The ID of the request, which is also used in the reply.
+    pub request_id: u7,
+    /// The JSON header.
+    pub header: &'a [u8],
+    /// The number of chunks of the property data.
+    pub n_chunks: u14,
+    /// The number of this chunk.
+    pub chunk: u14,
+    /// The property data of this chunk.
+    pub data: &'a [u8],
+}
+
+impl<'a> PropertyChunk<'a> {
+    fn parse(data: &'a [u8]) -> Result<Self, ()> {
+        let (request_id, header_len, rest) = match data {
+            [request_id, lsb, msb, rest @ ..] => (
+                u7::new(*request_id),
+                u16::from(join_u14(*lsb, *msb)) as usize,
+                rest,
+            ),
+            _ => return Err(()),
+        };
+        if rest.len() < header_len + 6 {
+            return Err(());
+        }
+        let (header, rest) = rest.split_at(header_len);
+        let data_len = u16::from(join_u14(rest[4], rest[5])) as usize;
+        if rest.len() != data_len + 6 {
+            return Err(());
+        }
+        Ok(Self {
+            request_id,
+            header,
+            n_chunks: join_u14(rest[0], rest[1]),
+            chunk: join_u14(rest[2], rest[3]),
+            data: &rest[6..],
+        })
+    }
+
+    fn encoded_len(&self) -> usize {
+        9 + self.header.len() + self.data.len()
+    }
+
+    fn encode(&self, buffer: &mut [u8]) -> Result<(), ()> {
+        if self.header.len() >= 1 << 14 || self.data.len() >= 1 << 14 {
+            return Err(());
+        }
+        if !all_data_bytes(self.header) || !all_data_bytes(self.data) {
+            return Err(());
+        }
+        buffer[0] = u8::from(self.request_id);
+        let (lsb, msb) = split_u14(u14::new(self.header.len() as u16));
+        buffer[1] = lsb;
+        buffer[2] = msb;
+        let rest = &mut buffer[3..];
+        rest[..self.header.len()].copy_from_slice(self.header);
+        let rest = &mut rest[self.header.len()..];
+        let fields = [self.n_chunks, self.chunk, u14::new(self.data.len() as u16)];
+        for (index, field) in fields.iter().enumerate() {
+            let (lsb, msb) = split_u14(*field);
+            rest[2 * index] = lsb;
+            rest[2 * index + 1] = msb;
+        }
+        rest[6..6 + self.data.len()].copy_from_slice(self.data);
+        Ok(())
+    }
+}
+
+/// The body of a MIDI-CI message.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CiBody<'a> {
+    /// Ask all devices to identify themselves.
+    Discovery(DeviceInfo),
+    /// Identify yourself to an inquiring device.
+    DiscoveryReply(DeviceInfo),
+    /// Tell all devices that the given MUID is not used anymore.
+    InvalidateMuid(Muid),
+    /// The last inquiry is not supported.
+    Nak,
+    /// Ask how many property exchange requests may be in flight at once.
+    PropertyExchangeCapabilities { simultaneous_requests: u7 },
+    /// Tell how many property exchange requests may be in flight at once.
+    PropertyExchangeCapabilitiesReply { simultaneous_requests: u7 },
+    /// Request the data of a property.
+    GetPropertyData(PropertyChunk<'a>),
+    /// Send the data of a requested property.
+    GetPropertyDataReply(PropertyChunk<'a>),
+    /// Set the data of a property.
+    SetPropertyData(PropertyChunk<'a>),
+    /// Acknowledge a set property request.
+    SetPropertyDataReply(PropertyChunk<'a>),
+}
+
+/// A MIDI-CI message.
+///
+/// See the [module documentation](index.html) for more information.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CiMessage<'a> {
+    /// The addressed channel, or [`WHOLE_PORT`](constant.WHOLE_PORT.html).
+    pub device_id: u7,
+    /// The MIDI-CI version of the message.
+    pub version: u7,
+    /// The MUID of the sending device.
+    pub source: Muid,
+    /// The MUID of the addressed device.
+    pub destination: Muid,
+    /// The content of the message.
+    pub body: CiBody<'a>,
+}
+
+impl<'a> CiMessage<'a> {
+    /// Try to parse the data of a system-exclusive message as a MIDI-CI message.
+    pub fn parse(data: &'a [u8]) -> Result<Self, ()> {
+        if !all_data_bytes(data) {
+            return Err(());
+        }
+        let (device_id, sub_id, version, source, destination, body) = match data {
+            [NON_REALTIME_ID, device_id, CAPABILITY_INQUIRY_SUB_ID, sub_id, version, rest @ ..]
+                if rest.len() >= 8 =>
+            {
+                (
+                    u7::new(*device_id),
+                    *sub_id,
+                    u7::new(*version),
+                    Muid::parse(&rest[0..4])?,
+                    Muid::parse(&rest[4..8])?,
+                    &rest[8..],
+                )
+            }
+            _ => return Err(()),
+        };
+        let body = match sub_id {
+            DISCOVERY_ID => CiBody::Discovery(DeviceInfo::parse(body)?),
+            DISCOVERY_REPLY_ID => CiBody::DiscoveryReply(DeviceInfo::parse(body)?),
+            INVALIDATE_MUID_ID if body.len() >= 4 => CiBody::InvalidateMuid(Muid::parse(body)?),
+            NAK_ID => CiBody::Nak,
+            PROPERTY_EXCHANGE_CAPABILITIES_ID if !body.is_empty() => {
+                CiBody::PropertyExchangeCapabilities {
+                    simultaneous_requests: u7::new(body[0]),
+                }
+            }
+            PROPERTY_EXCHANGE_CAPABILITIES_REPLY_ID if !body.is_empty() => {
+                CiBody::PropertyExchangeCapabilitiesReply {
+                    simultaneous_requests: u7::new(body[0]),
+                }
+            }
+            GET_PROPERTY_DATA_ID => CiBody::GetPropertyData(PropertyChunk::parse(body)?),
+            GET_PROPERTY_DATA_REPLY_ID => CiBody::GetPropertyDataReply(PropertyChunk::parse(body)?),
+            SET_PROPERTY_DATA_ID => CiBody::SetPropertyData(PropertyChunk::parse(body)?),
+            SET_PROPERTY_DATA_REPLY_ID => CiBody::SetPropertyDataReply(PropertyChunk::parse(body)?),
+            _ => return Err(()),
+        };
+        Ok(Self {
+            device_id,
+            version,
+            source,
+            destination,
+            body,
+        })
+    }
+
+    /// Return the length of the encoded data.
+    pub fn encoded_len(&self) -> usize {
+        13 + match &self.body {
+            CiBody::Discovery(_) | CiBody::DiscoveryReply(_) => DeviceInfo::ENCODED_LEN,
+            CiBody::InvalidateMuid(_) => 4,
+            CiBody::Nak => 0,
+            CiBody::PropertyExchangeCapabilities { .. }
+            | CiBody::PropertyExchangeCapabilitiesReply { .. } => 1,
+            CiBody::GetPropertyData(chunk)
+            | CiBody::GetPropertyDataReply(chunk)
+            | CiBody::SetPropertyData(chunk)
+            | CiBody::SetPropertyDataReply(chunk) => chunk.encoded_len(),
+        }
+    }
+
+    /// Write the data of the system-exclusive message to the buffer.
+    ///
+    /// The returned slice is the part of the buffer that contains the data. An error is returned
+    /// if the buffer is too small or if the header or data of a property chunk are too long or
+    /// contain status bytes.
+    pub fn encode<'b>(&self, buffer: &'b mut [u8]) -> Result<&'b [u8], ()> {
+        let len = self.encoded_len();
+        if buffer.len() < len {
+            return Err(());
+        }
+        let body = &mut buffer[13..];
+        let sub_id = match &self.body {
+            CiBody::Discovery(info) => {
+                info.encode(body);
+                DISCOVERY_ID
+            }
+            CiBody::DiscoveryReply(info) => {
+                info.encode(body);
+                DISCOVERY_REPLY_ID
+            }
+            CiBody::InvalidateMuid(muid) => {
+                body[0..4].copy_from_slice(&split_u28(muid.value()));
+                INVALIDATE_MUID_ID
+            }
+            CiBody::Nak => NAK_ID,
+            CiBody::PropertyExchangeCapabilities {
+                simultaneous_requests,
+            } => {
+                body[0] = u8::from(*simultaneous_requests);
+                PROPERTY_EXCHANGE_CAPABILITIES_ID
+            }
+            CiBody::PropertyExchangeCapabilitiesReply {
+                simultaneous_requests,
+            } => {
+                body[0] = u8::from(*simultaneous_requests);
+                PROPERTY_EXCHANGE_CAPABILITIES_REPLY_ID
+            }
+            CiBody::GetPropertyData(chunk) => {
+                chunk.encode(body)?;
+                GET_PROPERTY_DATA_ID
+            }
+            CiBody::GetPropertyDataReply(chunk) => {
+                chunk.encode(body)?;
+                GET_PROPERTY_DATA_REPLY_ID
+            }
+            CiBody::SetPropertyData(chunk) => {
+                chunk.encode(body)?;
+                SET_PROPERTY_DATA_ID
+            }
+            CiBody::SetPropertyDataReply(chunk) => {
+                chunk.encode(body)?;
+                SET_PROPERTY_DATA_REPLY_ID
+            }
+        };
+        buffer[0] = NON_REALTIME_ID;
+        buffer[1] = u8::from(self.device_id);
+        buffer[2] = CAPABILITY_INQUIRY_SUB_ID;
+        buffer[3] = sub_id;
+        buffer[4] = u8::from(self.version);
+        buffer[5..9].copy_from_slice(&split_u28(self.source.value()));
+        buffer[9..13].copy_from_slice(&split_u28(self.destination.value()));
+        Ok(&buffer[..len])
+    }
+
+    /// Return `true` if the message is addressed to the device with the given MUID.
+    pub fn addresses(&self, muid: Muid) -> bool {
+        self.destination == muid || self.destination.is_broadcast()
+    }
+
+    /// Create a reply to this message.
+    ///
+    /// The reply is sent from the given MUID to the source of this message, on the same device ID
+    /// and with the version of this module.
+    pub fn reply<'b>(&self, own_muid: Muid, body: CiBody<'b>) -> CiMessage<'b> {
+        CiMessage {
+            device_id: self.device_id,
+            version: u7::new(VERSION),
+            source: own_muid,
+            destination: self.source,
+            body,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::capability_inquiry::*;
+
+    #[test]
+    fn test_muid() {
+        assert_eq!(Some(Muid::BROADCAST), Muid::new(0x0fff_ffff));
+        assert_eq!(None, Muid::new(0x0fff_ff00));
+        assert_eq!(None, Muid::new(0x1000_0000));
+        assert_eq!(0x0fff_fe01, Muid::from_random(0xffff_ff01).value());
+        assert_eq!(0x0234_5678, Muid::from_random(0x1234_5678).value());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let info = DeviceInfo {
+            manufacturer: [0x00, 0x21, 0x09],
+            family: u14::new(300),
+            model: u14::new(7),
+            revision: [1, 2, 3, 4],
+            categories: PROPERTY_EXCHANGE_CATEGORY | PROFILE_CONFIGURATION_CATEGORY,
+            max_sysex_size: 100_000,
+        };
+        let chunk = PropertyChunk {
+            request_id: u7::new(3),
+            header: b"{\"resource\":\"DeviceInfo\"}",
+            n_chunks: u14::new(2),
+            chunk: u14::new(1),
+            data: b"{}",
+        };
+        let bodies = [
+            CiBody::Discovery(info),
+            CiBody::DiscoveryReply(info),
+            CiBody::InvalidateMuid(Muid::from_random(1234)),
+            CiBody::Nak,
+            CiBody::PropertyExchangeCapabilities {
+                simultaneous_requests: u7::new(4),
+            },
+            CiBody::PropertyExchangeCapabilitiesReply {
+                simultaneous_requests: u7::new(1),
+            },
+            CiBody::GetPropertyData(chunk),
+            CiBody::GetPropertyDataReply(chunk),
+            CiBody::SetPropertyData(chunk),
+            CiBody::SetPropertyDataReply(chunk),
+        ];
+        for body in bodies.iter() {
+            let message = CiMessage {
+                device_id: u7::new(WHOLE_PORT),
+                version: u7::new(VERSION),
+                source: Muid::from_random(5),
+                destination: Muid::BROADCAST,
+                body: *body,
+            };
+            let mut buffer = [0u8; 64];
+            let data = message.encode(&mut buffer).unwrap();
+            assert_eq!(message.encoded_len(), data.len());
+            assert_eq!(message, CiMessage::parse(data).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_invalid() {
+        // A discovery of version 1.2 has an additional output path ID, which is ignored.
+        let mut data = vec![0x7e, 0x7f, 0x0d, 0x70, 0x02, 1, 0, 0, 0];
+        data.extend_from_slice(&[0x7f; 4]);
+        data.extend_from_slice(&[0; 17]);
+        assert!(CiMessage::parse(&data).is_ok());
+        data.truncate(data.len() - 2);
+        assert!(CiMessage::parse(&data).is_err());
+
+        // Reserved MUIDs are rejected.
+        let data = [
+            0x7e, 0x7f, 0x0d, 0x7f, 0x01, 0, 0x7e, 0x7f, 0x7f, 0, 0, 0, 0,
+        ];
+        assert!(CiMessage::parse(&data).is_err());
+
+        let message = CiMessage {
+            device_id: u7::new(0),
+            version: u7::new(VERSION),
+            source: Muid::from_random(5),
+            destination: Muid::from_random(6),
+            body: CiBody::GetPropertyData(PropertyChunk {
+                request_id: u7::new(0),
+                header: b"\xff",
+                n_chunks: u14::new(1),
+                chunk: u14::new(1),
+                data: b"",
+            }),
+        };
+        assert!(message.encode(&mut [0u8; 64]).is_err());
+        assert!(!message.addresses(Muid::from_random(5)));
+    }
+}
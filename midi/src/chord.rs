@@ -0,0 +1,439 @@
+//! Interval and chord analysis.
+//!
+//! Chord-trigger or harmonizer plugins need to know which chord the player is holding down. This
+//! module contains a [`NoteSet`](struct.NoteSet.html), which tracks the held notes, and
+//! [`Chord::identify`](struct.Chord.html#method.identify), which names the chord formed by a set
+//! of notes. There are also helpers to transpose notes, sets and chords.
+//!
+//!     extern crate lv2rs_midi as midi;
+//!
+//!     use midi::chord::*;
+//!     use midi::prelude::*;
+//!     use midi::MidiMessage;
+//!
+//!     let mut notes = NoteSet::new();
+//!     // E, G and C: The first inversion of C major.
+//!     for note in [64, 67, 72].iter() {
+//!         notes.track(&MidiMessage::NoteOn {
+//!             channel: u4::new(0),
+//!             note: u7::new(*note),
+//!             velocity: u7::new(100),
+//!         });
+//!     }
+//!
+//!     let chord = Chord::identify(notes).unwrap();
+//!     assert_eq!(0, chord.root);
+//!     assert_eq!(ChordQuality::Major, chord.quality);
+//!     assert_eq!("C", chord.to_string());
+//!     assert_eq!("D", chord.transposed(2).to_string());
+//!
+//!     assert_eq!(Interval::MinorThird, Interval::between(u7::new(64), u7::new(67)));
+use crate::message::MidiMessage;
+use crate::prelude::*;
+use std::fmt;
+
+/// Names of the twelve pitch classes, starting with C.
+const PITCH_CLASS_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+/// Return the name of a pitch class, where 0 is C, 1 is C# and so on.
+///
+/// The pitch class is taken modulo 12.
+pub fn pitch_class_name(pitch_class: u8) -> &'static str {
+    PITCH_CLASS_NAMES[(pitch_class % 12) as usize]
+}
+
+/// Transpose a note by a number of semitones.
+///
+/// `None` is returned if the transposed note is out of range.
+pub fn transpose(note: u7, semitones: i8) -> Option<u7> {
+    let note = u8::from(note) as i16 + semitones as i16;
+    if (0..128).contains(&note) {
+        Some(u7::new(note as u8))
+    } else {
+        None
+    }
+}
+
+/// A set of notes, stored as a bit field where bit `n` represents note `n`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct NoteSet(pub u128);
+
+impl NoteSet {
+    /// Create a new, empty set.
+    pub fn new() -> Self {
+        NoteSet(0)
+    }
+
+    /// Add a note to the set.
+    pub fn insert(&mut self, note: u7) {
+        self.0 |= 1 << u8::from(note);
+    }
+
+    /// Remove a note from the set.
+    pub fn remove(&mut self, note: u7) {
+        self.0 &= !(1 << u8::from(note));
+    }
+
+    /// Return `true` if the note is in the set.
+    pub fn contains(&self, note: u7) -> bool {
+        self.0 & (1 << u8::from(note)) != 0
+    }
+
+    /// Return the number of notes in the set.
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Return `true` if the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Return the lowest note of the set.
+    pub fn lowest(&self) -> Option<u7> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(u7::new(self.0.trailing_zeros() as u8))
+        }
+    }
+
+    /// Return the highest note of the set.
+    pub fn highest(&self) -> Option<u7> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(u7::new(127 - self.0.leading_zeros() as u8))
+        }
+    }
+
+    /// Iterate over all notes of the set, from the lowest to the highest.
+    pub fn iter(&self) -> impl Iterator<Item = u7> {
+        let bits = self.0;
+        (0..128u8)
+            .filter(move |note| bits & (1 << note) != 0)
+            .map(u7::new)
+    }
+
+    /// Return the set of pitch classes, where bit `n` represents pitch class `n`.
+    pub fn pitch_classes(&self) -> u16 {
+        self.iter()
+            .fold(0, |classes, note| classes | 1 << (u8::from(note) % 12))
+    }
+
+    /// Return a copy of the set with all notes transposed by a number of semitones.
+    ///
+    /// Notes that would be out of range are dropped.
+    pub fn transposed(&self, semitones: i8) -> Self {
+        let distance = u32::from(semitones.unsigned_abs());
+        if semitones >= 0 {
+            NoteSet(self.0.checked_shl(distance).unwrap_or(0))
+        } else {
+            NoteSet(self.0.checked_shr(distance).unwrap_or(0))
+        }
+    }
+
+    /// Update the set with a message.
+    ///
+    /// Note ons add their note to the set and note offs, as well as note ons with a velocity of
+    /// zero, remove it. All other messages are ignored. The channel of the messages is ignored too,
+    /// so you have to filter the messages yourself if you want to track a single channel.
+    pub fn track(&mut self, message: &MidiMessage) {
+        match message {
+            MidiMessage::NoteOn { note, velocity, .. } if u8::from(*velocity) > 0 => {
+                self.insert(*note)
+            }
+            MidiMessage::NoteOn { note, .. } | MidiMessage::NoteOff { note, .. } => {
+                self.remove(*note)
+            }
+            _ => (),
+        }
+    }
+}
+
+/// An interval within an octave.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Interval {
+    Unison,
+    MinorSecond,
+    MajorSecond,
+    MinorThird,
+    MajorThird,
+    PerfectFourth,
+    Tritone,
+    PerfectFifth,
+    MinorSixth,
+    MajorSixth,
+    MinorSeventh,
+    MajorSeventh,
+}
+
+const INTERVALS: [Interval; 12] = [
+    Interval::Unison,
+    Interval::MinorSecond,
+    Interval::MajorSecond,
+    Interval::MinorThird,
+    Interval::MajorThird,
+    Interval::PerfectFourth,
+    Interval::Tritone,
+    Interval::PerfectFifth,
+    Interval::MinorSixth,
+    Interval::MajorSixth,
+    Interval::MinorSeventh,
+    Interval::MajorSeventh,
+];
+
+impl Interval {
+    /// Return the interval of a number of semitones.
+    ///
+    /// Compound intervals are reduced to a single octave, which means that an octave is a unison.
+    pub fn from_semitones(semitones: u8) -> Self {
+        INTERVALS[(semitones % 12) as usize]
+    }
+
+    /// Return the interval between two notes, regardless of their order.
+    pub fn between(a: u7, b: u7) -> Self {
+        let a = u8::from(a);
+        let b = u8::from(b);
+        Self::from_semitones(a.max(b) - a.min(b))
+    }
+
+    /// Return the number of semitones of the interval.
+    pub fn semitones(self) -> u8 {
+        self as u8
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Interval::Unison => "unison",
+            Interval::MinorSecond => "minor second",
+            Interval::MajorSecond => "major second",
+            Interval::MinorThird => "minor third",
+            Interval::MajorThird => "major third",
+            Interval::PerfectFourth => "perfect fourth",
+            Interval::Tritone => "tritone",
+            Interval::PerfectFifth => "perfect fifth",
+            Interval::MinorSixth => "minor sixth",
+            Interval::MajorSixth => "major sixth",
+            Interval::MinorSeventh => "minor seventh",
+            Interval::MajorSeventh => "major seventh",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The quality of a chord, which is defined by the intervals above its root.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ChordQuality {
+    /// Root and fifth.
+    Power,
+    Major,
+    Minor,
+    Diminished,
+    Augmented,
+    Suspended2,
+    Suspended4,
+    Major7,
+    Dominant7,
+    Minor7,
+    HalfDiminished7,
+    Diminished7,
+}
+
+const QUALITIES: [ChordQuality; 12] = [
+    ChordQuality::Power,
+    ChordQuality::Major,
+    ChordQuality::Minor,
+    ChordQuality::Diminished,
+    ChordQuality::Augmented,
+    ChordQuality::Suspended2,
+    ChordQuality::Suspended4,
+    ChordQuality::Major7,
+    ChordQuality::Dominant7,
+    ChordQuality::Minor7,
+    ChordQuality::HalfDiminished7,
+    ChordQuality::Diminished7,
+];
+
+impl ChordQuality {
+    /// Return the intervals of the chord tones above the root, in semitones.
+    pub fn intervals(self) -> &'static [u8] {
+        match self {
+            ChordQuality::Power => &[7],
+            ChordQuality::Major => &[4, 7],
+            ChordQuality::Minor => &[3, 7],
+            ChordQuality::Diminished => &[3, 6],
+            ChordQuality::Augmented => &[4, 8],
+            ChordQuality::Suspended2 => &[2, 7],
+            ChordQuality::Suspended4 => &[5, 7],
+            ChordQuality::Major7 => &[4, 7, 11],
+            ChordQuality::Dominant7 => &[4, 7, 10],
+            ChordQuality::Minor7 => &[3, 7, 10],
+            ChordQuality::HalfDiminished7 => &[3, 6, 10],
+            ChordQuality::Diminished7 => &[3, 6, 9],
+        }
+    }
+
+    /// Return the symbol of the quality, as it is written after the root.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            ChordQuality::Power => "5",
+            ChordQuality::Major => "",
+            ChordQuality::Minor => "m",
+            ChordQuality::Diminished => "dim",
+            ChordQuality::Augmented => "aug",
+            ChordQuality::Suspended2 => "sus2",
+            ChordQuality::Suspended4 => "sus4",
+            ChordQuality::Major7 => "maj7",
+            ChordQuality::Dominant7 => "7",
+            ChordQuality::Minor7 => "m7",
+            ChordQuality::HalfDiminished7 => "m7b5",
+            ChordQuality::Diminished7 => "dim7",
+        }
+    }
+
+    /// Return the pitch classes of the chord with a root of C, as a bit field.
+    fn pitch_classes(self) -> u16 {
+        self.intervals()
+            .iter()
+            .fold(1, |classes, interval| classes | 1 << interval)
+    }
+}
+
+/// A chord, consisting of a root and a quality.
+///
+/// See the [module documentation](index.html) for more information.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Chord {
+    /// The pitch class of the root, where 0 is C, 1 is C# and so on.
+    pub root: u8,
+    /// The quality of the chord.
+    pub quality: ChordQuality,
+}
+
+/// Rotate a set of pitch classes down, so that the given pitch class becomes 0.
+fn rotate_pitch_classes(classes: u16, root: u8) -> u16 {
+    let root = root % 12;
+    ((classes >> root) | (classes << (12 - root))) & 0x0fff
+}
+
+impl Chord {
+    /// Identify the chord formed by a set of notes.
+    ///
+    /// Only the pitch classes of the notes are considered, which means that doubled notes, the
+    /// voicing and the octave don't matter. The chord must consist of exactly the tones of one of
+    /// the known qualities. If several chords match, as for augmented or diminished seventh
+    /// chords, the chord with the lowest note as its root is preferred.
+    ///
+    /// `None` is returned if the notes don't form a known chord.
+    pub fn identify(notes: NoteSet) -> Option<Self> {
+        let classes = notes.pitch_classes();
+        let bass = u8::from(notes.lowest()?) % 12;
+        let roots = std::iter::once(bass).chain((0..12).filter(|root| *root != bass));
+        for root in roots {
+            if classes & (1 << root) == 0 {
+                continue;
+            }
+            let rotated = rotate_pitch_classes(classes, root);
+            if let Some(quality) = QUALITIES
+                .iter()
+                .find(|quality| quality.pitch_classes() == rotated)
+            {
+                return Some(Chord {
+                    root,
+                    quality: *quality,
+                });
+            }
+        }
+        None
+    }
+
+    /// Return the notes of the chord in root position, starting at the given root note.
+    ///
+    /// The pitch class of the given note replaces the root of the chord. Notes that would be out of
+    /// range are dropped.
+    pub fn notes(&self, root_note: u7) -> NoteSet {
+        let mut notes = NoteSet::new();
+        notes.insert(root_note);
+        for interval in self.quality.intervals() {
+            if let Some(note) = transpose(root_note, *interval as i8) {
+                notes.insert(note);
+            }
+        }
+        notes
+    }
+
+    /// Return a copy of the chord, transposed by a number of semitones.
+    pub fn transposed(&self, semitones: i8) -> Self {
+        Chord {
+            root: (self.root as i16 + semitones as i16).rem_euclid(12) as u8,
+            quality: self.quality,
+        }
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            pitch_class_name(self.root),
+            self.quality.symbol()
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::chord::*;
+
+    fn set(notes: &[u8]) -> NoteSet {
+        let mut set = NoteSet::new();
+        for note in notes {
+            set.insert(u7::new(*note));
+        }
+        set
+    }
+
+    #[test]
+    fn test_note_set() {
+        let mut notes = set(&[0, 60, 127]);
+        assert_eq!(3, notes.len());
+        assert_eq!(Some(u7::new(0)), notes.lowest());
+        assert_eq!(Some(u7::new(127)), notes.highest());
+        assert_eq!(set(&[12, 72]), notes.transposed(12));
+        assert_eq!(set(&[48, 115]), notes.transposed(-12));
+        assert_eq!(set(&[127]), notes.transposed(127));
+        assert_eq!(set(&[0]), notes.transposed(-127));
+        assert!(notes.transposed(-128).is_empty());
+
+        notes.track(&MidiMessage::NoteOn {
+            channel: u4::new(0),
+            note: u7::new(60),
+            velocity: u7::new(0),
+        });
+        assert!(!notes.contains(u7::new(60)));
+        assert_eq!(None, NoteSet::new().lowest());
+        assert_eq!(None, transpose(u7::new(120), 8));
+    }
+
+    #[test]
+    fn test_identify() {
+        let chord = |notes: &[u8]| Chord::identify(set(notes)).map(|chord| chord.to_string());
+        assert_eq!(Some("G7".to_string()), chord(&[43, 59, 62, 65]));
+        assert_eq!(Some("F#m7b5".to_string()), chord(&[54, 57, 60, 64]));
+        assert_eq!(Some("Dsus4".to_string()), chord(&[62, 67, 69, 74]));
+        assert_eq!(Some("E5".to_string()), chord(&[40, 47, 52]));
+        assert_eq!(Some("D#aug".to_string()), chord(&[63, 67, 71]));
+        assert_eq!(Some("Adim7".to_string()), chord(&[57, 60, 63, 66]));
+        assert_eq!(None, chord(&[60, 61, 62]));
+        assert_eq!(None, chord(&[]));
+
+        let chord = Chord::identify(set(&[57, 60, 64])).unwrap();
+        assert_eq!(set(&[62, 65, 69]), chord.transposed(5).notes(u7::new(62)));
+    }
+}
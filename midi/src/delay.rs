@@ -0,0 +1,257 @@
+//! Delay lines for MIDI messages.
+//!
+//! A [`MidiDelay`](struct.MidiDelay.html) shifts incoming messages by a fixed number of frames,
+//! which is the core of any MIDI delay or echo plugin. Since the delay may be longer than a
+//! block, the delayed messages are kept in a ring buffer and re-emitted in the block they fall
+//! into.
+//!
+//! The delay line keeps track of time itself: In every `run` call, you first
+//! [`push`](struct.MidiDelay.html#method.push) all incoming messages of the block, with time
+//! stamps relative to the beginning of the block, and then call
+//! [`pop_due`](struct.MidiDelay.html#method.pop_due) or
+//! [`flush`](struct.MidiDelay.html#method.flush) exactly once.
+//!
+//!     extern crate lv2rs_midi as midi;
+//!
+//!     use midi::delay::MidiDelay;
+//!     use midi::prelude::*;
+//!     use midi::{MidiMessage, TimedMidiMessage};
+//!
+//!     let mut delay = MidiDelay::new(100, 64);
+//!     let note = MidiMessage::NoteOn {
+//!         channel: u4::new(0),
+//!         note: u7::new(60),
+//!         velocity: u7::new(100),
+//!     };
+//!
+//!     // First block of 64 frames: The note comes in, but nothing goes out.
+//!     delay.push(TimedMidiMessage { frames: 10, message: note }).unwrap();
+//!     assert_eq!(0, delay.pop_due(64).count());
+//!
+//!     // Second block: The note is sent 110 frames after the start of the first block.
+//!     let due: Vec<TimedMidiMessage> = delay.pop_due(64).collect();
+//!     assert_eq!(1, due.len());
+//!     assert_eq!(46, due[0].frames);
+use crate::atom::RawMidiMessage;
+use crate::message::{MidiMessage, TimedMidiMessage};
+use lv2rs_atom::prelude::*;
+use lv2rs_atom::sequence::TimeStamp;
+use lv2rs_urid::CachedMap;
+use std::collections::VecDeque;
+
+struct DelayedMessage {
+    due: i64,
+    message: MidiMessage,
+}
+
+/// A delay line for MIDI messages with a fixed capacity.
+///
+/// The delay line never allocates memory after its creation, which makes it safe to use in a
+/// real-time context. If it is full, new messages are dropped and counted.
+///
+/// See the [module documentation](index.html) for more information.
+pub struct MidiDelay {
+    queue: VecDeque<DelayedMessage>,
+    capacity: usize,
+    delay: u32,
+    now: i64,
+    n_dropped: usize,
+}
+
+impl MidiDelay {
+    /// Create a new delay line that delays messages by `delay` frames and holds up to `capacity`
+    /// messages.
+    pub fn new(delay: u32, capacity: usize) -> Self {
+        Self {
+            queue: VecDeque::with_capacity(capacity),
+            capacity,
+            delay,
+            now: 0,
+            n_dropped: 0,
+        }
+    }
+
+    /// Return the delay in frames.
+    pub fn delay(&self) -> u32 {
+        self.delay
+    }
+
+    /// Set the delay in frames.
+    ///
+    /// The new delay only applies to messages pushed afterwards. If the delay is shortened,
+    /// new messages are held back until all older messages are sent, so that the order of the
+    /// messages is kept.
+    pub fn set_delay(&mut self, delay: u32) {
+        self.delay = delay;
+    }
+
+    /// Delay a message.
+    ///
+    /// The time stamp of the message is relative to the beginning of the current block. If the
+    /// delay line is full, the message is dropped and an `Err` is returned.
+    pub fn push(&mut self, message: TimedMidiMessage) -> Result<(), ()> {
+        if self.queue.len() >= self.capacity {
+            self.n_dropped += 1;
+            return Err(());
+        }
+        let mut due = self.now + message.frames.max(0) + self.delay as i64;
+        if let Some(last) = self.queue.back() {
+            due = due.max(last.due);
+        }
+        self.queue.push_back(DelayedMessage {
+            due,
+            message: message.message,
+        });
+        Ok(())
+    }
+
+    /// Return the number of delayed messages.
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Return `true` if there are no delayed messages.
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Return the maximal number of delayed messages.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Return the number of messages that were dropped because the delay line was full.
+    pub fn n_dropped(&self) -> usize {
+        self.n_dropped
+    }
+
+    /// Remove all delayed messages.
+    pub fn clear(&mut self) {
+        self.queue.clear();
+    }
+
+    /// Remove and return all messages that are due in a block of `n_samples` frames.
+    ///
+    /// The time stamps of the returned messages are relative to the beginning of the block.
+    /// Messages that were due in an earlier block, because the iterator wasn't consumed
+    /// completely, are returned with a time stamp of zero. This method advances the clock of the
+    /// delay line and therefore has to be called exactly once per block.
+    pub fn pop_due<'a>(
+        &'a mut self,
+        n_samples: u32,
+    ) -> impl Iterator<Item = TimedMidiMessage> + 'a {
+        let block_start = self.now;
+        self.now += n_samples as i64;
+        std::iter::from_fn(move || {
+            if self.queue.front()?.due >= self.now {
+                return None;
+            }
+            self.queue.pop_front().map(|delayed| TimedMidiMessage {
+                frames: (delayed.due - block_start).max(0),
+                message: delayed.message,
+            })
+        })
+    }
+
+    /// Write all messages that are due in a block of `n_samples` frames to a sequence.
+    ///
+    /// The sequence has to use frames as its time unit. Just like
+    /// [`pop_due`](#method.pop_due), this method advances the clock of the delay line and has to
+    /// be called exactly once per block.
+    ///
+    /// If the sequence runs out of space, an `Err` is returned. The messages that could not be
+    /// written are kept and will be sent at the beginning of the next block.
+    pub fn flush<'a, W>(
+        &mut self,
+        writer: &mut W,
+        n_samples: u32,
+        urids: &mut CachedMap,
+    ) -> Result<(), ()>
+    where
+        W: SequenceWritingFrame<'a>,
+    {
+        let block_start = self.now;
+        self.now += n_samples as i64;
+        while let Some(delayed) = self.queue.front() {
+            if delayed.due >= self.now {
+                break;
+            }
+            let time = TimeStamp::Frames((delayed.due - block_start).max(0));
+            writer.push_event::<RawMidiMessage>(time, &delayed.message, urids)?;
+            self.queue.pop_front();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::delay::*;
+    use crate::prelude::*;
+
+    fn note_on(note: u8) -> MidiMessage {
+        MidiMessage::NoteOn {
+            channel: u4::new(0),
+            note: u7::new(note),
+            velocity: u7::new(100),
+        }
+    }
+
+    #[test]
+    fn test_delay() {
+        let mut delay = MidiDelay::new(200, 2);
+        for note in 0..3 {
+            let message = TimedMidiMessage {
+                frames: note as i64 * 30,
+                message: note_on(note),
+            };
+            assert_eq!(note < 2, delay.push(message).is_ok());
+        }
+        assert_eq!(1, delay.n_dropped());
+
+        // Nothing is due within the first three blocks of 64 frames.
+        for _ in 0..3 {
+            assert_eq!(0, delay.pop_due(64).count());
+        }
+
+        let due: Vec<TimedMidiMessage> = delay.pop_due(64).collect();
+        assert_eq!(2, due.len());
+        assert_eq!(8, due[0].frames);
+        assert_eq!(note_on(0), due[0].message);
+        assert_eq!(38, due[1].frames);
+        assert!(delay.is_empty());
+    }
+
+    #[test]
+    fn test_set_delay() {
+        let mut delay = MidiDelay::new(100, 4);
+        delay
+            .push(TimedMidiMessage {
+                frames: 0,
+                message: note_on(0),
+            })
+            .unwrap();
+
+        // Shortening the delay holds new messages back to keep the order.
+        delay.set_delay(0);
+        delay
+            .push(TimedMidiMessage {
+                frames: 10,
+                message: note_on(1),
+            })
+            .unwrap();
+        let due: Vec<TimedMidiMessage> = delay.pop_due(128).collect();
+        assert_eq!(2, due.len());
+        assert_eq!(note_on(0), due[0].message);
+        assert_eq!(100, due[1].frames);
+
+        delay
+            .push(TimedMidiMessage {
+                frames: 5,
+                message: note_on(2),
+            })
+            .unwrap();
+        let due: Vec<TimedMidiMessage> = delay.pop_due(128).collect();
+        assert_eq!(5, due[0].frames);
+    }
+}
@@ -0,0 +1,91 @@
+//! Dispatching of MIDI events by message class.
+//!
+//! A plugin that handles many different MIDI messages often ends up with a giant `match` in its
+//! `run` method. Instead, you can implement the [`MidiHandler`](trait.MidiHandler.html) trait,
+//! which has a separate method for every [class of messages](../enum.MessageClass.html), and let
+//! [`dispatch`](fn.dispatch.html) call them for every MIDI event in a sequence.
+//!
+//! An example:
+//!
+//!     extern crate lv2rs_atom as atom;
+//!     extern crate lv2rs_midi as midi;
+//!
+//!     use atom::sequence::TimeStamp;
+//!     use midi::dispatch::MidiHandler;
+//!     use midi::MidiMessage;
+//!
+//!     /// Count the notes and the timing clock messages.
+//!     struct Counter {
+//!         notes: usize,
+//!         clocks: usize,
+//!     }
+//!
+//!     impl MidiHandler for Counter {
+//!         fn channel_voice(&mut self, _time: TimeStamp, message: MidiMessage) {
+//!             if let MidiMessage::NoteOn { .. } = message {
+//!                 self.notes += 1;
+//!             }
+//!         }
+//!
+//!         fn system_realtime(&mut self, _time: TimeStamp, message: MidiMessage) {
+//!             if message == MidiMessage::TimingClock {
+//!                 self.clocks += 1;
+//!             }
+//!         }
+//!     }
+//!
+//! In the plugin's `run` method, you would then call
+//! `midi::dispatch::dispatch(sequence, &mut counter, &mut urids)`.
+use crate::atom::{MidiEvent, RawMidiMessage};
+use crate::message::{MessageClass, MidiMessage};
+use lv2rs_atom::prelude::*;
+use lv2rs_atom::sequence::TimeStamp;
+use lv2rs_urid::CachedMap;
+
+/// Handler for the different classes of MIDI messages.
+///
+/// All methods have a default implementation that does nothing, so you only need to implement
+/// the ones you are interested in.
+///
+/// See the [module documentation](index.html) for more information.
+pub trait MidiHandler {
+    /// Handle a channel voice message, like a note or a controller.
+    fn channel_voice(&mut self, _time: TimeStamp, _message: MidiMessage) {}
+
+    /// Handle a system common message, like a song position.
+    fn system_common(&mut self, _time: TimeStamp, _message: MidiMessage) {}
+
+    /// Handle a system real-time message, like the timing clock.
+    fn system_realtime(&mut self, _time: TimeStamp, _message: MidiMessage) {}
+
+    /// Handle a system-exclusive message.
+    ///
+    /// The data is passed without the start and end status bytes.
+    fn system_exclusive(&mut self, _time: TimeStamp, _data: &[u8]) {}
+
+    /// Handle a message of any class.
+    ///
+    /// The default implementation calls the method for the class of the message. You usually don't
+    /// need to implement this method.
+    fn message(&mut self, time: TimeStamp, message: MidiMessage) {
+        match message.class() {
+            MessageClass::ChannelVoice => self.channel_voice(time, message),
+            MessageClass::SystemCommon => self.system_common(time, message),
+            MessageClass::SystemRealtime => self.system_realtime(time, message),
+        }
+    }
+}
+
+/// Call the handler for every MIDI event in the sequence.
+///
+/// Events that aren't MIDI events or that can't be interpreted are ignored.
+pub fn dispatch<H: MidiHandler>(sequence: &Sequence, handler: &mut H, urids: &mut CachedMap) {
+    let midi_urid = urids.map(<RawMidiMessage as AtomBody>::get_uri());
+    for (time, atom) in sequence.iter(urids) {
+        match MidiEvent::read(atom, midi_urid) {
+            Some(MidiEvent::Message(message)) => handler.message(time, message),
+            Some(MidiEvent::SystemExclusive(data)) => handler.system_exclusive(time, data),
+            None => (),
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! Conversion of MIDI dynamics to floating-point values.
+//!
+//! Velocities and pressures are transmitted as 7-bit numbers, but instrument plugins usually need
+//! them as a gain factor between zero and one. The [`DynamicsCurve`](enum.DynamicsCurve.html)
+//! does this conversion and lets you choose how the values are mapped.
+//!
+//!     extern crate lv2rs_midi as midi;
+//!
+//!     use midi::dynamics::DynamicsCurve;
+//!     use midi::prelude::*;
+//!
+//!     let curve = DynamicsCurve::Exponential(2.0);
+//!     let gain = curve.apply(u7::new(64));
+//!     assert!(gain > 0.0 && gain < 0.5);
+//!     assert_eq!(u7::new(64), curve.invert(gain));
+use crate::prelude::*;
+
+/// Normalize a 7-bit value to the range from zero to one.
+pub fn normalize(value: u7) -> f32 {
+    u8::from(value) as f32 / 127.0
+}
+
+/// Convert a value between zero and one to a 7-bit value.
+///
+/// The value is rounded to the next 7-bit value and values outside of the range are clamped.
+pub fn denormalize(value: f32) -> u7 {
+    let value = (value.clamp(0.0, 1.0) * 127.0).round();
+    u7::new(value as u8)
+}
+
+/// Mapping from MIDI velocities or pressures to values between zero and one.
+#[derive(Clone, Default)]
+pub enum DynamicsCurve {
+    /// Map the values linearly: 0 is mapped to 0.0 and 127 is mapped to 1.0.
+    #[default]
+    Linear,
+    /// Map the values exponentially with the given factor.
+    ///
+    /// A normalized value `x` is mapped to `(exp(k * x) - 1) / (exp(k) - 1)`, where `k` is the
+    /// factor. Positive factors make the curve softer, negative factors make it harder and a factor
+    /// of zero is the same as a linear curve.
+    Exponential(f32),
+    /// Map the values using a table.
+    ///
+    /// The table should be monotonically increasing, otherwise the
+    /// [`invert`](#method.invert) method can not work reliably. The table is boxed to keep the
+    /// other curves small.
+    Table(Box<[f32; 128]>),
+}
+
+impl DynamicsCurve {
+    /// Map a velocity or pressure to a value between zero and one.
+    pub fn apply(&self, value: u7) -> f32 {
+        let x = normalize(value);
+        match self {
+            DynamicsCurve::Linear => x,
+            DynamicsCurve::Exponential(factor) => {
+                if *factor == 0.0 {
+                    x
+                } else {
+                    (factor * x).exp_m1() / factor.exp_m1()
+                }
+            }
+            DynamicsCurve::Table(table) => table[u8::from(value) as usize],
+        }
+    }
+
+    /// Map a value between zero and one back to a velocity or pressure.
+    ///
+    /// This is the inverse of [`apply`](#method.apply): For tables, the entry that is closest to
+    /// the value is returned.
+    pub fn invert(&self, value: f32) -> u7 {
+        match self {
+            DynamicsCurve::Linear => denormalize(value),
+            DynamicsCurve::Exponential(factor) => {
+                if *factor == 0.0 {
+                    denormalize(value)
+                } else {
+                    let value = value.clamp(0.0, 1.0);
+                    denormalize((value * factor.exp_m1()).ln_1p() / factor)
+                }
+            }
+            DynamicsCurve::Table(table) => {
+                let mut best_index = 0;
+                for (index, entry) in table.iter().enumerate() {
+                    if (entry - value).abs() < (table[best_index] - value).abs() {
+                        best_index = index;
+                    }
+                }
+                u7::new(best_index as u8)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::dynamics::*;
+
+    #[test]
+    fn test_curves() {
+        let mut table = [0.0; 128];
+        for (index, entry) in table.iter_mut().enumerate() {
+            *entry = (index as f32 / 127.0).sqrt();
+        }
+        let curves = [
+            DynamicsCurve::Linear,
+            DynamicsCurve::Exponential(3.0),
+            DynamicsCurve::Exponential(-3.0),
+            DynamicsCurve::Exponential(0.0),
+            DynamicsCurve::Table(Box::new(table)),
+        ];
+        for curve in curves.iter() {
+            assert_eq!(0.0, curve.apply(u7::new(0)));
+            assert!((1.0 - curve.apply(u7::new(127))).abs() < 1e-6);
+            for value in 0..128 {
+                let value = u7::new(value);
+                assert_eq!(value, curve.invert(curve.apply(value)));
+            }
+        }
+        assert!(DynamicsCurve::Exponential(3.0).apply(u7::new(64)) < normalize(u7::new(64)));
+    }
+}
@@ -0,0 +1,85 @@
+//! Human-readable formatting of MIDI messages.
+//!
+//! [`MidiMessage`](../enum.MidiMessage.html) and
+//! [`TimedMidiMessage`](../struct.TimedMidiMessage.html) implement `Display` and can therefore be
+//! formatted directly. This module contains additional helpers for MIDI monitors and debugging
+//! output:
+//!
+//!     extern crate lv2rs_midi as midi;
+//!
+//!     use midi::format::{EventLog, NoteName};
+//!     use midi::prelude::*;
+//!     use midi::{MidiMessage, TimedMidiMessage};
+//!
+//!     assert_eq!("C#4", format!("{}", NoteName(u7::new(61))));
+//!
+//!     let events = [
+//!         TimedMidiMessage {
+//!             frames: 0,
+//!             message: MidiMessage::NoteOn {
+//!                 channel: u4::new(0),
+//!                 note: u7::new(60),
+//!                 velocity: u7::new(100),
+//!             },
+//!         },
+//!         TimedMidiMessage {
+//!             frames: 12,
+//!             message: MidiMessage::ControlChange {
+//!                 channel: u4::new(1),
+//!                 control_number: u7::new(74),
+//!                 control_value: u7::new(64),
+//!             },
+//!         },
+//!     ];
+//!     assert_eq!(
+//!         "     0 NoteOn ch1 C4 vel 100\n    12 CC ch2 #74=64\n",
+//!         format!("{}", EventLog(&events))
+//!     );
+use crate::message::TimedMidiMessage;
+use crate::prelude::*;
+use std::fmt;
+
+const NOTE_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+/// Display wrapper for note numbers.
+///
+/// Notes are formatted with their name and octave, where note 60 is `C4`. Accidentals are always
+/// written as sharps.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct NoteName(pub u7);
+
+impl fmt::Display for NoteName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let note = u8::from(self.0) as i32;
+        write!(f, "{}{}", NOTE_NAMES[(note % 12) as usize], note / 12 - 1)
+    }
+}
+
+/// Display wrapper for a list of timed messages.
+///
+/// Every message is written in its own line, preceded by its time stamp.
+pub struct EventLog<'a>(pub &'a [TimedMidiMessage]);
+
+impl<'a> fmt::Display for EventLog<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_event_log(f, self.0.iter().cloned())
+    }
+}
+
+/// Write a list of timed messages to a text buffer.
+///
+/// This does the same as formatting an [`EventLog`](struct.EventLog.html), but accepts any
+/// iterator of messages. If the buffer has enough capacity, for example a pre-allocated `String`,
+/// no memory is allocated.
+pub fn write_event_log<W, I>(writer: &mut W, events: I) -> fmt::Result
+where
+    W: fmt::Write,
+    I: IntoIterator<Item = TimedMidiMessage>,
+{
+    for event in events {
+        writeln!(writer, "{}", event)?;
+    }
+    Ok(())
+}
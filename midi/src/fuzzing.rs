@@ -0,0 +1,135 @@
+//! Entry points for fuzzers.
+//!
+//! This module is only available with the `fuzzing` feature. [`parse_midi`](fn.parse_midi.html)
+//! feeds arbitrary bytes to the MIDI parsers of this crate and checks that every message they
+//! accept is encoded to the same message again, which makes it a good target for a fuzzer like
+//! [`cargo-fuzz`](https://github.com/rust-fuzz/cargo-fuzz). A fuzz target only needs to forward
+//! its input to [`fuzz_midi`](fn.fuzz_midi.html):
+//!
+//! ```ignore
+//! #![no_main]
+//! #[macro_use]
+//! extern crate libfuzzer_sys;
+//! extern crate lv2rs_midi as midi;
+//!
+//! fuzz_target!(|data: &[u8]| midi::fuzzing::fuzz_midi(data));
+//! ```
+//!
+//! The MIDI atoms themselves are read by the parsers of `lv2rs-atom`, which have their own
+//! entry point with its `fuzzing` feature.
+use crate::capability_inquiry::CiMessage;
+use crate::message::*;
+use crate::sample_dump::SampleDumpMessage;
+use crate::show_control::ShowControlMessage;
+use crate::status_bytes::*;
+use crate::universal::{IdentityReply, IdentityRequest};
+use crate::validate::validate_message;
+
+/// Try to parse arbitrary bytes as a MIDI message.
+///
+/// The bytes are parsed with [`MidiMessage::try_from`](../enum.MidiMessage.html#method.try_from)
+/// and checked with [`validate_message`](../validate/fn.validate_message.html). If they form a
+/// system-exclusive message, its data is also parsed as every universal system-exclusive message
+/// of this crate.
+///
+/// # Panics
+///
+/// This function panics if a parsed message isn't encoded to the same message again or if a
+/// message is parsed that isn't valid. Such a panic is a bug that should be reported.
+pub fn parse_midi(data: &[u8]) -> Result<MidiMessage, TryFromError> {
+    let message = MidiMessage::try_from(data);
+    if let Ok(message) = message {
+        let (bytes, len) = message.to_bytes();
+        assert_eq!(data, &bytes[..len], "{:?} isn't encoded correctly", message);
+        assert_eq!(Ok(()), validate_message(data), "{:?} isn't valid", message);
+    }
+
+    if let [START_OF_SYSTEM_EXCLUSIVE_STATUS, data @ .., END_OF_SYSTEM_EXCLUSICE_STATUS] = data {
+        parse_system_exclusive(data);
+    }
+    message
+}
+
+/// Parse arbitrary bytes as a MIDI message.
+///
+/// This function is meant to be called by a fuzz target and ignores the result.
+pub fn fuzz_midi(data: &[u8]) {
+    let _ = parse_midi(data);
+    // The fuzzer should find the universal messages without guessing the framing bytes.
+    parse_system_exclusive(data);
+}
+
+/// Parse the data of a system-exclusive message as every universal message and check that the
+/// accepted messages are parsed the same way after encoding them.
+fn parse_system_exclusive(data: &[u8]) {
+    if let Ok(message) = IdentityRequest::parse(data) {
+        assert_eq!(Ok(message), IdentityRequest::parse(&message.encode()));
+    }
+    if let Ok(message) = IdentityReply::parse(data) {
+        let mut buffer = [0; IdentityReply::MAX_DATA_LEN];
+        assert_eq!(
+            Ok(message),
+            IdentityReply::parse(message.encode(&mut buffer))
+        );
+    }
+    if let Ok(message) = SampleDumpMessage::parse(data) {
+        let mut buffer = [0; SampleDumpMessage::MAX_DATA_LEN];
+        assert_eq!(
+            Ok(&message),
+            SampleDumpMessage::parse(message.encode(&mut buffer)).as_ref()
+        );
+    }
+    if let Ok(message) = CiMessage::parse(data) {
+        let mut buffer = vec![0; message.encoded_len()];
+        let encoded = message.encode(&mut buffer).unwrap();
+        assert_eq!(Ok(message), CiMessage::parse(encoded));
+    }
+    if let Ok(message) = ShowControlMessage::parse(data) {
+        let mut buffer = vec![0; message.encoded_len()];
+        let encoded = message.encode(&mut buffer).unwrap();
+        assert_eq!(Ok(message), ShowControlMessage::parse(encoded));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::fuzzing::*;
+
+    #[test]
+    fn test_parse_midi() {
+        assert!(parse_midi(&[]).is_err());
+        assert!(parse_midi(&[0x90, 60, 100]).is_ok());
+        assert!(parse_midi(&[0xf0, 0x7e, 0x7f, 0x06, 0x01, 0xf7]).is_err());
+
+        // All messages of up to three bytes, with a couple of data bytes.
+        let data_bytes = [0x00, 0x01, 0x3c, 0x7f, 0x80, 0xf7, 0xff];
+        for status in 0..=255u8 {
+            let _ = parse_midi(&[status]);
+            for first in data_bytes.iter() {
+                let _ = parse_midi(&[status, *first]);
+                for second in data_bytes.iter() {
+                    let _ = parse_midi(&[status, *first, *second]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fuzz_midi() {
+        // Pseudo-random universal messages.
+        let mut state = 4711u32;
+        for length in 0..256 {
+            let mut data = vec![0u8; length % 32 + 4];
+            for byte in data.iter_mut() {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                *byte = (state >> 16) as u8 & 0x7f;
+            }
+            data[0] = if length % 2 == 1 { 0x7e } else { 0x7f };
+            data[2] = [0x01, 0x02, 0x03, 0x06, 0x0d][length % 5];
+            fuzz_midi(&data);
+            data.insert(0, 0xf0);
+            data.push(0xf7);
+            fuzz_midi(&data);
+        }
+    }
+}
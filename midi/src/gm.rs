@@ -0,0 +1,264 @@
+//! Instrument names of General MIDI.
+//!
+//! General MIDI (GM) defines a standard set of 128 programs and a drum kit on channel 10, which
+//! means that a program or drum key can be given a name if the receiving synthesizer follows GM.
+//! These names are useful for user interfaces or for logging.
+//!
+//! This module is only available with the `gm` feature, which is enabled by default.
+//!
+//!     extern crate lv2rs_midi as midi;
+//!
+//!     use midi::gm;
+//!     use midi::prelude::*;
+//!
+//!     assert_eq!("Acoustic Grand Piano", gm::program_name(u7::new(0)));
+//!     assert_eq!("Pianos", gm::family_name(u7::new(0)));
+//!     assert_eq!(Some("Acoustic Snare"), gm::drum_name(u7::new(38)));
+//!     assert_eq!(None, gm::drum_name(u7::new(20)));
+use crate::prelude::*;
+
+/// The channel that plays the drum kit, counted from zero.
+pub const DRUM_CHANNEL: u8 = 9;
+
+const PROGRAM_NAMES: [&str; 128] = [
+    "Acoustic Grand Piano",
+    "Bright Acoustic Piano",
+    "Electric Grand Piano",
+    "Honky-tonk Piano",
+    "Electric Piano 1",
+    "Electric Piano 2",
+    "Harpsichord",
+    "Clavi",
+    "Celesta",
+    "Glockenspiel",
+    "Music Box",
+    "Vibraphone",
+    "Marimba",
+    "Xylophone",
+    "Tubular Bells",
+    "Dulcimer",
+    "Drawbar Organ",
+    "Percussive Organ",
+    "Rock Organ",
+    "Church Organ",
+    "Reed Organ",
+    "Accordion",
+    "Harmonica",
+    "Tango Accordion",
+    "Acoustic Guitar (nylon)",
+    "Acoustic Guitar (steel)",
+    "Electric Guitar (jazz)",
+    "Electric Guitar (clean)",
+    "Electric Guitar (muted)",
+    "Overdriven Guitar",
+    "Distortion Guitar",
+    "Guitar Harmonics",
+    "Acoustic Bass",
+    "Electric Bass (finger)",
+    "Electric Bass (pick)",
+    "Fretless Bass",
+    "Slap Bass 1",
+    "Slap Bass 2",
+    "Synth Bass 1",
+    "Synth Bass 2",
+    "Violin",
+    "Viola",
+    "Cello",
+    "Contrabass",
+    "Tremolo Strings",
+    "Pizzicato Strings",
+    "Orchestral Harp",
+    "Timpani",
+    "String Ensemble 1",
+    "String Ensemble 2",
+    "Synth Strings 1",
+    "Synth Strings 2",
+    "Choir Aahs",
+    "Voice Oohs",
+    "Synth Voice",
+    "Orchestra Hit",
+    "Trumpet",
+    "Trombone",
+    "Tuba",
+    "Muted Trumpet",
+    "French Horn",
+    "Brass Section",
+    "Synth Brass 1",
+    "Synth Brass 2",
+    "Soprano Sax",
+    "Alto Sax",
+    "Tenor Sax",
+    "Baritone Sax",
+    "Oboe",
+    "English Horn",
+    "Bassoon",
+    "Clarinet",
+    "Piccolo",
+    "Flute",
+    "Recorder",
+    "Pan Flute",
+    "Blown Bottle",
+    "Shakuhachi",
+    "Whistle",
+    "Ocarina",
+    "Lead 1 (square)",
+    "Lead 2 (sawtooth)",
+    "Lead 3 (calliope)",
+    "Lead 4 (chiff)",
+    "Lead 5 (charang)",
+    "Lead 6 (voice)",
+    "Lead 7 (fifths)",
+    "Lead 8 (bass + lead)",
+    "Pad 1 (new age)",
+    "Pad 2 (warm)",
+    "Pad 3 (polysynth)",
+    "Pad 4 (choir)",
+    "Pad 5 (bowed)",
+    "Pad 6 (metallic)",
+    "Pad 7 (halo)",
+    "Pad 8 (sweep)",
+    "FX 1 (rain)",
+    "FX 2 (soundtrack)",
+    "FX 3 (crystal)",
+    "FX 4 (atmosphere)",
+    "FX 5 (brightness)",
+    "FX 6 (goblins)",
+    "FX 7 (echoes)",
+    "FX 8 (sci-fi)",
+    "Sitar",
+    "Banjo",
+    "Shamisen",
+    "Koto",
+    "Kalimba",
+    "Bag pipe",
+    "Fiddle",
+    "Shanai",
+    "Tinkle Bell",
+    "Agogo",
+    "Steel Drums",
+    "Woodblock",
+    "Taiko Drum",
+    "Melodic Tom",
+    "Synth Drum",
+    "Reverse Cymbal",
+    "Guitar Fret Noise",
+    "Breath Noise",
+    "Seashore",
+    "Bird Tweet",
+    "Telephone Ring",
+    "Helicopter",
+    "Applause",
+    "Gunshot",
+];
+
+const FAMILY_NAMES: [&str; 16] = [
+    "Pianos",
+    "Chromatic Percussion",
+    "Organs",
+    "Guitars",
+    "Basses",
+    "Strings",
+    "Ensembles",
+    "Brass",
+    "Reeds",
+    "Pipes",
+    "Synth Leads",
+    "Synth Pads",
+    "Synth Effects",
+    "Ethnic",
+    "Percussive",
+    "Sound Effects",
+];
+
+/// The first key of the drum kit.
+const FIRST_DRUM: u8 = 35;
+
+const DRUM_NAMES: [&str; 47] = [
+    "Acoustic Bass Drum",
+    "Bass Drum 1",
+    "Side Stick",
+    "Acoustic Snare",
+    "Hand Clap",
+    "Electric Snare",
+    "Low Floor Tom",
+    "Closed Hi Hat",
+    "High Floor Tom",
+    "Pedal Hi-Hat",
+    "Low Tom",
+    "Open Hi-Hat",
+    "Low-Mid Tom",
+    "Hi-Mid Tom",
+    "Crash Cymbal 1",
+    "High Tom",
+    "Ride Cymbal 1",
+    "Chinese Cymbal",
+    "Ride Bell",
+    "Tambourine",
+    "Splash Cymbal",
+    "Cowbell",
+    "Crash Cymbal 2",
+    "Vibraslap",
+    "Ride Cymbal 2",
+    "Hi Bongo",
+    "Low Bongo",
+    "Mute Hi Conga",
+    "Open Hi Conga",
+    "Low Conga",
+    "High Timbale",
+    "Low Timbale",
+    "High Agogo",
+    "Low Agogo",
+    "Cabasa",
+    "Maracas",
+    "Short Whistle",
+    "Long Whistle",
+    "Short Guiro",
+    "Long Guiro",
+    "Claves",
+    "Hi Wood Block",
+    "Low Wood Block",
+    "Mute Cuica",
+    "Open Cuica",
+    "Mute Triangle",
+    "Open Triangle",
+];
+
+/// Return the name of a program, counted from zero.
+pub fn program_name(program: u7) -> &'static str {
+    PROGRAM_NAMES[u8::from(program) as usize]
+}
+
+/// Return the name of the family a program belongs to.
+///
+/// General MIDI groups the programs in 16 families of eight programs each, for example pianos or
+/// guitars.
+pub fn family_name(program: u7) -> &'static str {
+    FAMILY_NAMES[u8::from(program) as usize / 8]
+}
+
+/// Return the name of the drum played by a key on the drum channel.
+///
+/// Only the keys 35 to 81 have drums assigned, `None` is returned for all other keys.
+pub fn drum_name(key: u7) -> Option<&'static str> {
+    (u8::from(key) as usize)
+        .checked_sub(FIRST_DRUM as usize)
+        .and_then(|index| DRUM_NAMES.get(index))
+        .copied()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::gm::*;
+
+    #[test]
+    fn test_names() {
+        assert_eq!("Gunshot", program_name(u7::new(127)));
+        assert_eq!("Sound Effects", family_name(u7::new(127)));
+        assert_eq!("Violin", program_name(u7::new(40)));
+        assert_eq!("Strings", family_name(u7::new(40)));
+        assert_eq!(None, drum_name(u7::new(34)));
+        assert_eq!(Some("Acoustic Bass Drum"), drum_name(u7::new(35)));
+        assert_eq!(Some("Open Triangle"), drum_name(u7::new(81)));
+        assert_eq!(None, drum_name(u7::new(82)));
+    }
+}
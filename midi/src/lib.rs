@@ -5,20 +5,57 @@
 //! [`SystemExclusiveMessage`](atom/struct.SystemExclusiveMessage.html), as well as means to use
 //! them. Using these structs, one can read from external controlling devices or keyboards or
 //! controll synthesizers or even create a light show!
-//! 
+//!
 //! This is a frozen prototype and therefore, development of this crate will not continue here. Further
 //! development continues as [rust-lv2](https://github.com/rust-dsp/rust-lv2).
 //!
 //! This crate depends on the non-standard integer types introduced by the `ux` crate, but you don't
 //! need to depend on it too. The required types are exported too.
+//!
+//! The [`gm`](gm/index.html) module contains the instrument names of General MIDI. It is enabled by
+//! the `gm` feature, which is on by default.
+//!
+//! With the `testing` feature, the [`testing`](testing/index.html) module provides
+//! [`proptest`](https://docs.rs/proptest/) strategies for MIDI messages. With the `fuzzing`
+//! feature, the [`fuzzing`](fuzzing/index.html) module provides entry points for fuzzers that
+//! feed arbitrary bytes to the MIDI parsers. With the `bench` feature, the
+//! [`bench`](bench/index.html) module provides benchmark scenarios for writing MIDI events.
 extern crate lv2rs_atom;
+extern crate lv2rs_core;
 extern crate lv2rs_urid;
+#[cfg(feature = "testing")]
+extern crate proptest;
 extern crate ux;
 
 mod atom;
+#[cfg(feature = "bench")]
+pub mod bench;
+pub mod capability_inquiry;
+pub mod chord;
+pub mod delay;
+pub mod dispatch;
+pub mod dynamics;
+pub mod format;
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing;
+#[cfg(feature = "gm")]
+pub mod gm;
+pub mod merge;
 mod message;
+pub mod monitor;
+pub mod pedal;
+pub mod pressure;
+pub mod quantize;
+pub mod sample_dump;
+pub mod scheduler;
+pub mod show_control;
 pub mod status_bytes;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod universal;
 pub mod uris;
+pub mod validate;
+pub mod zone;
 
 /// Re-export module intended for wildcard use
 ///
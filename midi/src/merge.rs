@@ -0,0 +1,241 @@
+//! Merging of multiple MIDI sequences.
+//!
+//! A MIDI merge plugin has several input sequences and one output sequence, and all it does is
+//! copying the MIDI events of all inputs to the output in chronological order. This is exactly
+//! what the [`merge`](fn.merge.html) function does.
+//!
+//! If several events have the same time stamp, system real-time messages (like the timing clock)
+//! are written first, since they are timing-critical. All other events with the same time stamp
+//! are written in the order of their input sequences and, within a sequence, in their original
+//! order.
+//!
+//! Merging doesn't allocate and only looks up URIDs that are already cached, which means that
+//! it can be used in `run` as long as the atom and MIDI URIDs were mapped in `instantiate`. The
+//! number of inputs is a constant of the plugin, which lets the merge keep the state of every
+//! input on the stack. An example:
+//!
+//!     extern crate lv2rs_atom as atom;
+//!     extern crate lv2rs_core as core;
+//!     extern crate lv2rs_midi as midi;
+//!     extern crate lv2rs_urid as urid;
+//!
+//!     use atom::prelude::*;
+//!     use atom::ports::*;
+//!     use atom::sequence::TimeUnit;
+//!     use atom::uris::AtomURIDs;
+//!     use midi::uris::MidiURIDs;
+//!     use urid::CachedMap;
+//!
+//!     pub struct Plugin {
+//!         in_a: AtomInputPort<Sequence>,
+//!         in_b: AtomInputPort<Sequence>,
+//!         out: AtomOutputPort<Sequence>,
+//!         urids: CachedMap,
+//!         midi_urids: MidiURIDs,
+//!     }
+//!
+//!     impl Plugin {
+//!         /// Simulated `instantiate` method.
+//!         fn new(mut urids: CachedMap) -> Self {
+//!             AtomURIDs::new(&mut urids);
+//!             let midi_urids = MidiURIDs::new(&mut urids);
+//!             Self {
+//!                 in_a: AtomInputPort::new(),
+//!                 in_b: AtomInputPort::new(),
+//!                 out: AtomOutputPort::new(),
+//!                 urids,
+//!                 midi_urids,
+//!             }
+//!         }
+//!
+//!         /// Simulated `run` method.
+//!         fn run(&mut self, context: &mut core::RunContext) {
+//!             let in_a = unsafe { self.in_a.read(context, &mut self.urids) }.unwrap();
+//!             let in_b = unsafe { self.in_b.read(context, &mut self.urids) }.unwrap();
+//!             let mut frame =
+//!                 unsafe { self.out.write_atom_body(&TimeUnit::Frames, &mut self.urids) }.unwrap();
+//!             let midi_urid = self.midi_urids.event;
+//!             let inputs = [in_a, in_b];
+//!             midi::merge::merge(&inputs, &mut frame, midi_urid, &mut self.urids).unwrap();
+//!         }
+//!     }
+use crate::atom::{MidiEvent, RawMidiMessage, SystemExclusiveMessage};
+use lv2rs_atom::prelude::*;
+use lv2rs_atom::sequence::TimeStamp;
+use lv2rs_urid::{CachedMap, URID};
+
+/// Merge the MIDI events of several sequences into one output sequence.
+///
+/// The output sequence has to use frames as its time unit. Events from the inputs that aren't
+/// MIDI events, that can't be interpreted or that are time-stamped in beats are ignored. MIDI
+/// events are recognized by `midi_urid`, the URID of `midi:MidiEvent`, which is mapped in
+/// `instantiate`, for example as the `event` field of the
+/// [`MidiURIDs`](../uris/struct.MidiURIDs.html).
+///
+/// If the output sequence runs out of space, an `Err` is returned. All events written until then
+/// remain in the output.
+///
+/// See the [module documentation](index.html) for more information.
+pub fn merge<'a, W, const N: usize>(
+    inputs: &[&Sequence; N],
+    writer: &mut W,
+    midi_urid: URID,
+    urids: &mut CachedMap,
+) -> Result<(), ()>
+where
+    W: SequenceWritingFrame<'a>,
+{
+    let mut heads = core::array::from_fn::<_, N, _>(|index| {
+        inputs[index]
+            .iter(urids)
+            .filter_map(move |(time, atom)| match time {
+                TimeStamp::Frames(frames) => Some((frames, MidiEvent::read(atom, midi_urid)?)),
+                TimeStamp::Beats(_) => None,
+            })
+            .peekable()
+    });
+
+    loop {
+        // Find the input with the earliest event. `min_by_key` returns the first minimum, which
+        // keeps the merge stable.
+        let next_input = heads
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, head)| {
+                head.peek().map(|(frames, event)| {
+                    let realtime = match event {
+                        MidiEvent::Message(message) => message.is_system_realtime(),
+                        MidiEvent::SystemExclusive(_) => false,
+                    };
+                    ((*frames, !realtime), index)
+                })
+            })
+            .min_by_key(|(key, _)| *key)
+            .map(|(_, index)| index);
+        let (frames, event) = match next_input {
+            Some(index) => heads[index].next().unwrap(),
+            None => return Ok(()),
+        };
+
+        let time = TimeStamp::Frames(frames);
+        match event {
+            MidiEvent::Message(message) => {
+                writer.push_event::<RawMidiMessage>(time, &message, urids)?;
+            }
+            MidiEvent::SystemExclusive(data) => {
+                writer.push_event::<SystemExclusiveMessage>(time, data, urids)?;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::merge::*;
+    use crate::message::*;
+    use crate::prelude::*;
+    use lv2rs_atom::ports::*;
+    use lv2rs_atom::sequence::TimeUnit;
+    use lv2rs_urid::debug::DebugMap;
+
+    fn note_on(note: u8) -> MidiMessage {
+        MidiMessage::NoteOn {
+            channel: u4::new(0),
+            note: u7::new(note),
+            velocity: u7::new(100),
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut debug_map = DebugMap::new();
+        let mut urids = unsafe { debug_map.create_cached_map() };
+
+        // Creating the atom spaces.
+        let mut spaces: Vec<Vec<u8>> = (0..3).map(|_| vec![0u8; 256]).collect();
+        let atoms: Vec<&mut Atom> = spaces
+            .iter_mut()
+            .map(|space| {
+                let atom = unsafe { (space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+                *(atom.mut_size()) = 256 - 8;
+                atom
+            })
+            .collect();
+        let mut out_ports: Vec<AtomOutputPort<Sequence>> = Vec::new();
+        let mut in_ports: Vec<AtomInputPort<Sequence>> = Vec::new();
+        for atom in atoms {
+            let mut in_port = AtomInputPort::new();
+            in_port.connect_port(atom as &Atom);
+            in_ports.push(in_port);
+            let mut out_port = AtomOutputPort::new();
+            out_port.connect_port(atom);
+            out_ports.push(out_port);
+        }
+
+        // Writing the inputs.
+        {
+            let mut frame =
+                unsafe { out_ports[0].write_atom_body(&TimeUnit::Frames, &mut urids) }.unwrap();
+            frame
+                .push_event::<RawMidiMessage>(TimeStamp::Frames(0), &note_on(0), &mut urids)
+                .unwrap();
+            frame
+                .push_event::<RawMidiMessage>(TimeStamp::Frames(10), &note_on(1), &mut urids)
+                .unwrap();
+        }
+        {
+            let mut frame =
+                unsafe { out_ports[1].write_atom_body(&TimeUnit::Frames, &mut urids) }.unwrap();
+            frame
+                .push_event::<SystemExclusiveMessage>(TimeStamp::Frames(5), &[1, 2], &mut urids)
+                .unwrap();
+            frame
+                .push_event::<RawMidiMessage>(
+                    TimeStamp::Frames(10),
+                    &MidiMessage::TimingClock,
+                    &mut urids,
+                )
+                .unwrap();
+            frame
+                .push_event::<i32>(TimeStamp::Frames(11), &42, &mut urids)
+                .unwrap();
+        }
+
+        // Merging.
+        let context = lv2rs_core::RunContext::new(16, 0, 44100.0, None);
+        {
+            let midi_urid = urids.map(crate::uris::EVENT_URI);
+            let in_a = unsafe { in_ports[0].read(&context, &mut urids) }.unwrap();
+            let in_b = unsafe { in_ports[1].read(&context, &mut urids) }.unwrap();
+            let mut frame =
+                unsafe { out_ports[2].write_atom_body(&TimeUnit::Frames, &mut urids) }.unwrap();
+            merge(&[in_a, in_b], &mut frame, midi_urid, &mut urids).unwrap();
+        }
+
+        // Reading.
+        let output = unsafe { in_ports[2].read(&context, &mut urids) }.unwrap();
+        let mut iter = output.iter(&mut urids);
+
+        let (time, atom) = iter.next().unwrap();
+        assert_eq!(TimeStamp::Frames(0), time);
+        let message = atom.get_body::<RawMidiMessage>(&mut urids).unwrap();
+        assert_eq!(note_on(0), message.interpret().unwrap());
+
+        let (time, atom) = iter.next().unwrap();
+        assert_eq!(TimeStamp::Frames(5), time);
+        let message = atom.get_body::<SystemExclusiveMessage>(&mut urids).unwrap();
+        assert_eq!([1, 2], message.get_data());
+
+        let (time, atom) = iter.next().unwrap();
+        assert_eq!(TimeStamp::Frames(10), time);
+        let message = atom.get_body::<RawMidiMessage>(&mut urids).unwrap();
+        assert_eq!(MidiMessage::TimingClock, message.interpret().unwrap());
+
+        let (time, atom) = iter.next().unwrap();
+        assert_eq!(TimeStamp::Frames(10), time);
+        let message = atom.get_body::<RawMidiMessage>(&mut urids).unwrap();
+        assert_eq!(note_on(1), message.interpret().unwrap());
+
+        assert!(iter.next().is_none());
+    }
+}
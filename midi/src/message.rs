@@ -1,17 +1,20 @@
+use crate::format::NoteName;
 use crate::prelude::*;
 use crate::status_bytes::*;
+use std::fmt;
 
 /// A MIDI message.
 ///
 /// Please consult the [MIDI reference](https://www.midi.org/specifications) for information on how
 /// to use these messages.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum MidiMessage {
     /// Stop playing a note.
     NoteOff { channel: u4, note: u7, velocity: u7 },
     /// Start playing a note.
     NoteOn { channel: u4, note: u7, velocity: u7 },
     /// Change the pressure on a key.
-    PolyKeyPressure { channel: u4, pressure: u7 },
+    PolyKeyPressure { channel: u4, note: u7, pressure: u7 },
     /// Change the value of a controlled number.
     ControlChange {
         channel: u4,
@@ -46,6 +49,34 @@ pub enum MidiMessage {
     SystemReset,
 }
 
+/// A MIDI message with a time stamp.
+///
+/// The time stamp is given in frames and is, as long as not noted otherwise, relative to the
+/// beginning of the current `run` block. This is the type used by the
+/// [`MidiScheduler`](scheduler/struct.MidiScheduler.html) and other utilities that have to keep
+/// messages around for a while.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TimedMidiMessage {
+    /// The time stamp of the message, in frames.
+    pub frames: i64,
+    /// The message itself.
+    pub message: MidiMessage,
+}
+
+/// The classes of MIDI messages.
+///
+/// System-exclusive messages belong to the system common messages, but are handled by the
+/// [`SystemExclusiveMessage`](struct.SystemExclusiveMessage.html) struct.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MessageClass {
+    /// Messages that control a voice of a channel, like notes and controllers.
+    ChannelVoice,
+    /// Messages that are meant for all receivers, like the song position.
+    SystemCommon,
+    /// Timing-critical messages that are meant for all receivers, like the timing clock.
+    SystemRealtime,
+}
+
 /// Errors that may arise when using [`MidiMessage::try_from`](enum.MidiMessage.html#method.try_from)
 #[derive(Debug)]
 pub enum TryFromError {
@@ -82,6 +113,47 @@ fn data_to_u14(lsb: u7, msb: u7) -> u14 {
 }
 
 impl MidiMessage {
+    /// Return the class of the message.
+    pub fn class(&self) -> MessageClass {
+        match self {
+            MidiMessage::NoteOff { .. }
+            | MidiMessage::NoteOn { .. }
+            | MidiMessage::PolyKeyPressure { .. }
+            | MidiMessage::ControlChange { .. }
+            | MidiMessage::ProgramChange { .. }
+            | MidiMessage::ChannelPressure { .. }
+            | MidiMessage::PitchBendChange { .. } => MessageClass::ChannelVoice,
+            MidiMessage::TimeCodeQuarterFrame { .. }
+            | MidiMessage::SongPositionPointer { .. }
+            | MidiMessage::SongSelect { .. }
+            | MidiMessage::TuneRequest => MessageClass::SystemCommon,
+            MidiMessage::TimingClock
+            | MidiMessage::Start
+            | MidiMessage::Continue
+            | MidiMessage::Stop
+            | MidiMessage::ActiveSensing
+            | MidiMessage::SystemReset => MessageClass::SystemRealtime,
+        }
+    }
+
+    /// Return `true` if the message is a system real-time message.
+    pub fn is_system_realtime(&self) -> bool {
+        self.class() == MessageClass::SystemRealtime
+    }
+
+    /// Return the priority of the message.
+    ///
+    /// Messages with a higher priority are more timing-critical and should be handled first if
+    /// several messages arrive at the same time: System real-time messages have a priority of 2,
+    /// system common messages have a priority of 1 and channel voice messages have a priority of 0.
+    pub fn priority(&self) -> u8 {
+        match self.class() {
+            MessageClass::ChannelVoice => 0,
+            MessageClass::SystemCommon => 1,
+            MessageClass::SystemRealtime => 2,
+        }
+    }
+
     /// Try to create a `MidiMessage` from a one-byte-message.
     fn try_from_one_byte(status: u8) -> Result<Self, TryFromError> {
         match status {
@@ -101,12 +173,6 @@ impl MidiMessage {
         let (channel_status, channel) = split_to_channel_status(status);
 
         match channel_status {
-            POLY_KEY_PRESSURE_STATUS => {
-                return Ok(MidiMessage::PolyKeyPressure {
-                    channel: channel,
-                    pressure: data,
-                });
-            }
             PROGRAM_CHANGE_STATUS => {
                 return Ok(MidiMessage::ProgramChange {
                     channel: channel,
@@ -160,6 +226,13 @@ impl MidiMessage {
                     velocity: second_data,
                 });
             }
+            POLY_KEY_PRESSURE_STATUS => {
+                return Ok(MidiMessage::PolyKeyPressure {
+                    channel: channel,
+                    note: first_data,
+                    pressure: second_data,
+                });
+            }
             CONTROL_CHANGE_STATUS => {
                 return Ok(MidiMessage::ControlChange {
                     channel: channel,
@@ -221,4 +294,215 @@ impl MidiMessage {
             Err(TryFromError::UnknownMessage)
         }
     }
+
+    /// Return the raw bytes of the message.
+    ///
+    /// The message is written to the beginning of the array and the number of used bytes is
+    /// returned too. This is the inverse of [`try_from`](#method.try_from).
+    pub fn to_bytes(&self) -> ([u8; 3], usize) {
+        let channel_status = |status: u8, channel: &u4| status + u8::from(*channel);
+        let u14_to_data = |value: &u14| {
+            let value: u16 = (*value).into();
+            ((value & 0b0111_1111) as u8, (value >> 7) as u8)
+        };
+        match self {
+            MidiMessage::NoteOff {
+                channel,
+                note,
+                velocity,
+            } => (
+                [
+                    channel_status(NOTE_OFF_STATUS, channel),
+                    (*note).into(),
+                    (*velocity).into(),
+                ],
+                3,
+            ),
+            MidiMessage::NoteOn {
+                channel,
+                note,
+                velocity,
+            } => (
+                [
+                    channel_status(NOTE_ON_STATUS, channel),
+                    (*note).into(),
+                    (*velocity).into(),
+                ],
+                3,
+            ),
+            MidiMessage::PolyKeyPressure {
+                channel,
+                note,
+                pressure,
+            } => (
+                [
+                    channel_status(POLY_KEY_PRESSURE_STATUS, channel),
+                    (*note).into(),
+                    (*pressure).into(),
+                ],
+                3,
+            ),
+            MidiMessage::ControlChange {
+                channel,
+                control_number,
+                control_value,
+            } => (
+                [
+                    channel_status(CONTROL_CHANGE_STATUS, channel),
+                    (*control_number).into(),
+                    (*control_value).into(),
+                ],
+                3,
+            ),
+            MidiMessage::ProgramChange {
+                channel,
+                program_number,
+            } => (
+                [
+                    channel_status(PROGRAM_CHANGE_STATUS, channel),
+                    (*program_number).into(),
+                    0,
+                ],
+                2,
+            ),
+            MidiMessage::ChannelPressure { channel, pressure } => (
+                [
+                    channel_status(CHANNEL_PRESSURE_STATUS, channel),
+                    (*pressure).into(),
+                    0,
+                ],
+                2,
+            ),
+            MidiMessage::PitchBendChange { channel, value } => {
+                let (lsb, msb) = u14_to_data(value);
+                (
+                    [channel_status(PITCH_BEND_CHANGE_STATUS, channel), lsb, msb],
+                    3,
+                )
+            }
+            MidiMessage::TimeCodeQuarterFrame {
+                message_type,
+                value,
+            } => {
+                let message_type: u8 = (*message_type).into();
+                let value: u8 = (*value).into();
+                (
+                    [
+                        TIME_CODE_QUARTER_FRAME_STATUS,
+                        (message_type << 4) + value,
+                        0,
+                    ],
+                    2,
+                )
+            }
+            MidiMessage::SongPositionPointer { position } => {
+                let (lsb, msb) = u14_to_data(position);
+                ([SONG_POSITION_POINTER_STATUS, lsb, msb], 3)
+            }
+            MidiMessage::SongSelect { song } => ([SONG_SELECT_STATUS, (*song).into(), 0], 2),
+            MidiMessage::TuneRequest => ([TUNE_REQUEST_STATUS, 0, 0], 1),
+            MidiMessage::TimingClock => ([TIMING_CLOCK_STATUS, 0, 0], 1),
+            MidiMessage::Start => ([START_STATUS, 0, 0], 1),
+            MidiMessage::Continue => ([CONTINUE_STATUS, 0, 0], 1),
+            MidiMessage::Stop => ([STOP_STATUS, 0, 0], 1),
+            MidiMessage::ActiveSensing => ([ACTIVE_SENSING_STATUS, 0, 0], 1),
+            MidiMessage::SystemReset => ([SYSTEM_RESET_STATUS, 0, 0], 1),
+        }
+    }
+}
+
+impl fmt::Display for MidiMessage {
+    /// Format the message in a short, human-readable way.
+    ///
+    /// Channels are counted from one and notes are written by their name, for example
+    /// `NoteOn ch1 C4 vel 100` or `CC ch2 #74=64`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let channel_number = |channel: &u4| u8::from(*channel) + 1;
+        match self {
+            MidiMessage::NoteOff {
+                channel,
+                note,
+                velocity,
+            } => write!(
+                f,
+                "NoteOff ch{} {} vel {}",
+                channel_number(channel),
+                NoteName(*note),
+                velocity
+            ),
+            MidiMessage::NoteOn {
+                channel,
+                note,
+                velocity,
+            } => write!(
+                f,
+                "NoteOn ch{} {} vel {}",
+                channel_number(channel),
+                NoteName(*note),
+                velocity
+            ),
+            MidiMessage::PolyKeyPressure {
+                channel,
+                note,
+                pressure,
+            } => write!(
+                f,
+                "PolyKeyPressure ch{} {} {}",
+                channel_number(channel),
+                NoteName(*note),
+                pressure
+            ),
+            MidiMessage::ControlChange {
+                channel,
+                control_number,
+                control_value,
+            } => write!(
+                f,
+                "CC ch{} #{}={}",
+                channel_number(channel),
+                control_number,
+                control_value
+            ),
+            MidiMessage::ProgramChange {
+                channel,
+                program_number,
+            } => write!(
+                f,
+                "ProgramChange ch{} #{}",
+                channel_number(channel),
+                program_number
+            ),
+            MidiMessage::ChannelPressure { channel, pressure } => write!(
+                f,
+                "ChannelPressure ch{} {}",
+                channel_number(channel),
+                pressure
+            ),
+            MidiMessage::PitchBendChange { channel, value } => {
+                write!(f, "PitchBend ch{} {}", channel_number(channel), value)
+            }
+            MidiMessage::TimeCodeQuarterFrame {
+                message_type,
+                value,
+            } => write!(f, "QuarterFrame {}={}", message_type, value),
+            MidiMessage::SongPositionPointer { position } => {
+                write!(f, "SongPosition {}", position)
+            }
+            MidiMessage::SongSelect { song } => write!(f, "SongSelect {}", song),
+            MidiMessage::TuneRequest => write!(f, "TuneRequest"),
+            MidiMessage::TimingClock => write!(f, "TimingClock"),
+            MidiMessage::Start => write!(f, "Start"),
+            MidiMessage::Continue => write!(f, "Continue"),
+            MidiMessage::Stop => write!(f, "Stop"),
+            MidiMessage::ActiveSensing => write!(f, "ActiveSensing"),
+            MidiMessage::SystemReset => write!(f, "SystemReset"),
+        }
+    }
+}
+
+impl fmt::Display for TimedMidiMessage {
+    /// Format the message with its time stamp, for example `    12 NoteOn ch1 C4 vel 100`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:>6} {}", self.frames, self.message)
+    }
 }
@@ -0,0 +1,282 @@
+//! Recording of MIDI messages for monitors.
+//!
+//! A MIDI monitor plugin has to copy the messages it receives in the audio thread to its user
+//! interface, which runs in another thread. The [`channel`](fn.channel.html) function creates a
+//! [`MonitorRecorder`](struct.MonitorRecorder.html) for the audio thread and a
+//! [`MonitorReader`](struct.MonitorReader.html) for the interface thread, which share a
+//! fixed-capacity, lock-free ring buffer. Recording never blocks or allocates memory: If the
+//! buffer is full, new messages are dropped and counted, so that the interface can tell the user.
+//!
+//! The time stamps of recorded messages are counted in frames since the recorder was created. The
+//! recorder therefore has to be told when a block ends.
+//!
+//!     extern crate lv2rs_midi as midi;
+//!
+//!     use midi::monitor;
+//!     use midi::prelude::*;
+//!     use midi::{MidiMessage, TimedMidiMessage};
+//!     use std::thread;
+//!
+//!     let (mut recorder, mut reader) = monitor::channel(2);
+//!     let message = MidiMessage::Start;
+//!
+//!     // The audio thread records three messages in a block of 64 frames, but there is only space
+//!     // for two of them.
+//!     thread::spawn(move || {
+//!         for frames in 0..3 {
+//!             let _ = recorder.record(TimedMidiMessage { frames, message });
+//!         }
+//!         recorder.end_block(64);
+//!     })
+//!     .join()
+//!     .unwrap();
+//!
+//!     let recorded: Vec<TimedMidiMessage> = reader.drain().collect();
+//!     assert_eq!(2, recorded.len());
+//!     assert_eq!(1, reader.take_dropped());
+use crate::atom::RawMidiMessage;
+use crate::message::TimedMidiMessage;
+use lv2rs_atom::prelude::*;
+use lv2rs_atom::sequence::TimeStamp;
+use lv2rs_urid::CachedMap;
+use std::cell::UnsafeCell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// The ring buffer shared by the recorder and the reader.
+///
+/// The indices are never reset and wrap around; The slot of an index is the index modulo the
+/// capacity. Only the recorder writes to `write` and to the slots between `write` and `read`,
+/// and only the reader writes to `read`.
+struct Shared {
+    slots: Box<[UnsafeCell<TimedMidiMessage>]>,
+    write: AtomicUsize,
+    read: AtomicUsize,
+    n_dropped: AtomicUsize,
+}
+
+unsafe impl Sync for Shared {}
+
+/// Create a connected recorder and reader, which can store up to `capacity` messages.
+///
+/// # Panics
+///
+/// Panics if the capacity is zero.
+pub fn channel(capacity: usize) -> (MonitorRecorder, MonitorReader) {
+    assert!(capacity > 0);
+    let empty = TimedMidiMessage {
+        frames: 0,
+        message: crate::message::MidiMessage::TuneRequest,
+    };
+    let slots: Vec<UnsafeCell<TimedMidiMessage>> =
+        (0..capacity).map(|_| UnsafeCell::new(empty)).collect();
+    let shared = Arc::new(Shared {
+        slots: slots.into_boxed_slice(),
+        write: AtomicUsize::new(0),
+        read: AtomicUsize::new(0),
+        n_dropped: AtomicUsize::new(0),
+    });
+    (
+        MonitorRecorder {
+            shared: shared.clone(),
+            now: 0,
+        },
+        MonitorReader { shared },
+    )
+}
+
+/// The recording end of a monitor channel, to be used in the audio thread.
+///
+/// See the [module documentation](index.html) for more information.
+pub struct MonitorRecorder {
+    shared: Arc<Shared>,
+    now: i64,
+}
+
+impl MonitorRecorder {
+    /// Record a message.
+    ///
+    /// The time stamp of the message is relative to the beginning of the current block. If the
+    /// buffer is full, the message is dropped and an `Err` is returned.
+    pub fn record(&mut self, message: TimedMidiMessage) -> Result<(), ()> {
+        let write = self.shared.write.load(Ordering::Relaxed);
+        let read = self.shared.read.load(Ordering::Acquire);
+        if write.wrapping_sub(read) >= self.shared.slots.len() {
+            self.shared.n_dropped.fetch_add(1, Ordering::Relaxed);
+            return Err(());
+        }
+        let slot = &self.shared.slots[write % self.shared.slots.len()];
+        unsafe {
+            *slot.get() = TimedMidiMessage {
+                frames: self.now + message.frames,
+                message: message.message,
+            };
+        }
+        self.shared
+            .write
+            .store(write.wrapping_add(1), Ordering::Release);
+        Ok(())
+    }
+
+    /// Record all MIDI messages of a sequence.
+    ///
+    /// The sequence has to use frames as its time unit; Events with beat time stamps are recorded
+    /// at the beginning of the block. Events that aren't MIDI events or that can't be interpreted
+    /// are ignored. If the buffer runs full, the remaining messages are dropped and an `Err` is
+    /// returned.
+    pub fn record_sequence(
+        &mut self,
+        sequence: &Sequence,
+        urids: &mut CachedMap,
+    ) -> Result<(), ()> {
+        let mut result = Ok(());
+        for (time, atom) in sequence.iter(urids) {
+            let message = match atom
+                .get_body::<RawMidiMessage>(urids)
+                .ok()
+                .and_then(|message| message.interpret().ok())
+            {
+                Some(message) => message,
+                None => continue,
+            };
+            let frames = match time {
+                TimeStamp::Frames(frames) => frames,
+                TimeStamp::Beats(_) => 0,
+            };
+            if self.record(TimedMidiMessage { frames, message }).is_err() {
+                result = Err(());
+            }
+        }
+        result
+    }
+
+    /// Mark the end of a block with `n_samples` frames.
+    pub fn end_block(&mut self, n_samples: u32) {
+        self.now += n_samples as i64;
+    }
+}
+
+/// The reading end of a monitor channel, to be used in the interface thread.
+///
+/// See the [module documentation](index.html) for more information.
+pub struct MonitorReader {
+    shared: Arc<Shared>,
+}
+
+impl MonitorReader {
+    /// Remove and return the oldest recorded message.
+    pub fn pop(&mut self) -> Option<TimedMidiMessage> {
+        let read = self.shared.read.load(Ordering::Relaxed);
+        let write = self.shared.write.load(Ordering::Acquire);
+        if read == write {
+            return None;
+        }
+        let slot = &self.shared.slots[read % self.shared.slots.len()];
+        let message = unsafe { *slot.get() };
+        self.shared
+            .read
+            .store(read.wrapping_add(1), Ordering::Release);
+        Some(message)
+    }
+
+    /// Remove and return all recorded messages, from the oldest to the newest.
+    ///
+    /// Messages that are recorded while the iterator is consumed are returned too.
+    pub fn drain<'a>(&'a mut self) -> impl Iterator<Item = TimedMidiMessage> + 'a {
+        std::iter::from_fn(move || self.pop())
+    }
+
+    /// Return the number of messages that are waiting to be read.
+    pub fn len(&self) -> usize {
+        let read = self.shared.read.load(Ordering::Relaxed);
+        let write = self.shared.write.load(Ordering::Acquire);
+        write.wrapping_sub(read)
+    }
+
+    /// Return `true` if no messages are waiting to be read.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the maximal number of messages the buffer can hold.
+    pub fn capacity(&self) -> usize {
+        self.shared.slots.len()
+    }
+
+    /// Return the number of messages that were dropped since the last call of
+    /// [`take_dropped`](#method.take_dropped).
+    pub fn n_dropped(&self) -> usize {
+        self.shared.n_dropped.load(Ordering::Relaxed)
+    }
+
+    /// Return the number of dropped messages and reset the count.
+    pub fn take_dropped(&mut self) -> usize {
+        self.shared.n_dropped.swap(0, Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::message::MidiMessage;
+    use crate::monitor::*;
+    use crate::prelude::*;
+    use std::thread;
+
+    fn control(value: u8) -> MidiMessage {
+        MidiMessage::ControlChange {
+            channel: u4::new(0),
+            control_number: u7::new(1),
+            control_value: u7::new(value),
+        }
+    }
+
+    #[test]
+    fn test_ring() {
+        let (mut recorder, mut reader) = channel(3);
+        for round in 0..4 {
+            for value in 0..4 {
+                let message = TimedMidiMessage {
+                    frames: value as i64,
+                    message: control(value),
+                };
+                assert_eq!(value < 3, recorder.record(message).is_ok());
+            }
+            recorder.end_block(100);
+            assert_eq!(3, reader.len());
+            assert_eq!(1, reader.take_dropped());
+
+            let recorded: Vec<TimedMidiMessage> = reader.drain().collect();
+            assert_eq!(3, recorded.len());
+            assert_eq!(round * 100 + 2, recorded[2].frames);
+            assert_eq!(control(2), recorded[2].message);
+            assert!(reader.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_threads() {
+        let (mut recorder, mut reader) = channel(16);
+        let producer = thread::spawn(move || {
+            for value in 0..1000 {
+                let message = TimedMidiMessage {
+                    frames: value,
+                    message: control((value % 128) as u8),
+                };
+                while recorder.record(message).is_err() {
+                    thread::yield_now();
+                }
+            }
+        });
+
+        let mut expected = 0;
+        while expected < 1000 {
+            if let Some(message) = reader.pop() {
+                assert_eq!(expected, message.frames);
+                expected += 1;
+            } else {
+                thread::yield_now();
+            }
+        }
+        producer.join().unwrap();
+    }
+}
@@ -0,0 +1,304 @@
+//! Resolution of the sustain and sostenuto pedals.
+//!
+//! When the sustain pedal (controller 64) is pressed, released keys keep sounding until the pedal
+//! is released again. The sostenuto pedal (controller 66) does the same, but only for those keys
+//! that were held down at the moment the pedal was pressed. Every synthesizer has to implement this
+//! behaviour and since it is easy to get wrong, the [`PedalProcessor`](struct.PedalProcessor.html)
+//! does it for you: It consumes the pedal controllers and converts note ons and offs into the
+//! "effective" gates of the notes, meaning that a note off is only emitted when the note should
+//! really stop sounding.
+//!
+//! An example:
+//!
+//!     extern crate lv2rs_midi as midi;
+//!
+//!     use midi::pedal::PedalProcessor;
+//!     use midi::prelude::*;
+//!     use midi::MidiMessage;
+//!
+//!     let mut processor = PedalProcessor::new();
+//!     let mut output: Vec<MidiMessage> = Vec::new();
+//!
+//!     let channel = u4::new(0);
+//!     let note = u7::new(60);
+//!     let messages = [
+//!         MidiMessage::NoteOn { channel, note, velocity: u7::new(100) },
+//!         MidiMessage::ControlChange {
+//!             channel,
+//!             control_number: u7::new(64),
+//!             control_value: u7::new(127),
+//!         },
+//!         MidiMessage::NoteOff { channel, note, velocity: u7::new(0) },
+//!     ];
+//!     for message in messages.iter() {
+//!         processor.process(*message, |message| output.push(message));
+//!     }
+//!
+//!     // The note off is delayed since the sustain pedal is down.
+//!     assert_eq!(1, output.len());
+//!
+//!     let release = MidiMessage::ControlChange {
+//!         channel,
+//!         control_number: u7::new(64),
+//!         control_value: u7::new(0),
+//!     };
+//!     processor.process(release, |message| output.push(message));
+//!     assert_eq!(MidiMessage::NoteOff { channel, note, velocity: u7::new(0) }, output[1]);
+use crate::message::MidiMessage;
+use crate::prelude::*;
+
+/// Controller number of the sustain pedal.
+pub const SUSTAIN_CONTROLLER: u8 = 64;
+
+/// Controller number of the sostenuto pedal.
+pub const SOSTENUTO_CONTROLLER: u8 = 66;
+
+/// Pedal and key state of a single MIDI channel.
+///
+/// Every set of notes is stored as a bit field, where bit `n` represents note `n`.
+#[derive(Clone, Copy, Default)]
+struct ChannelState {
+    sustain: bool,
+    sostenuto: bool,
+    /// Keys that are physically held down.
+    held: u128,
+    /// Notes that are currently sounding.
+    sounding: u128,
+    /// Notes captured by the sostenuto pedal.
+    captured: u128,
+}
+
+impl ChannelState {
+    /// Return the set of sounding notes that are neither held down nor kept by a pedal.
+    fn releasable(&self) -> u128 {
+        let mut kept = self.held;
+        if self.sustain {
+            kept |= self.sounding;
+        }
+        if self.sostenuto {
+            kept |= self.captured;
+        }
+        self.sounding & !kept
+    }
+}
+
+/// Converter from raw note and pedal messages to effective note gates.
+///
+/// The processor tracks all 16 channels independently. Note ons are always passed through, but
+/// note offs are delayed as long as a pedal keeps the note sounding. If a note is struck again
+/// while it is kept sounding by a pedal, a note off is emitted before the new note on.
+///
+/// Sustain and sostenuto controller messages are consumed; All other messages are passed through
+/// unaltered.
+///
+/// See the [module documentation](index.html) for more information.
+pub struct PedalProcessor {
+    channels: [ChannelState; 16],
+}
+
+impl PedalProcessor {
+    /// Create a new processor with all pedals up and no sounding notes.
+    pub fn new() -> Self {
+        Self {
+            channels: [ChannelState::default(); 16],
+        }
+    }
+
+    /// Return `true` if the sustain pedal of the channel is down.
+    pub fn sustain(&self, channel: u4) -> bool {
+        self.channels[u8::from(channel) as usize].sustain
+    }
+
+    /// Return `true` if the sostenuto pedal of the channel is down.
+    pub fn sostenuto(&self, channel: u4) -> bool {
+        self.channels[u8::from(channel) as usize].sostenuto
+    }
+
+    /// Return `true` if the note is sounding, either because its key is held or because it is
+    /// kept by a pedal.
+    pub fn is_sounding(&self, channel: u4, note: u7) -> bool {
+        self.channels[u8::from(channel) as usize].sounding & (1 << u8::from(note)) != 0
+    }
+
+    /// Process a message.
+    ///
+    /// All messages that result from the given one are passed to `emit`, in the order they should
+    /// be handled.
+    pub fn process<F: FnMut(MidiMessage)>(&mut self, message: MidiMessage, mut emit: F) {
+        match message {
+            MidiMessage::NoteOn {
+                channel,
+                note,
+                velocity,
+            } if u8::from(velocity) != 0 => {
+                let state = &mut self.channels[u8::from(channel) as usize];
+                let bit = 1u128 << u8::from(note);
+                if state.sounding & bit != 0 {
+                    emit(MidiMessage::NoteOff {
+                        channel,
+                        note,
+                        velocity: u7::new(0),
+                    });
+                }
+                state.held |= bit;
+                state.sounding |= bit;
+                emit(message);
+            }
+            MidiMessage::NoteOn { channel, note, .. }
+            | MidiMessage::NoteOff { channel, note, .. } => {
+                let state = &mut self.channels[u8::from(channel) as usize];
+                let bit = 1u128 << u8::from(note);
+                state.held &= !bit;
+                if state.releasable() & bit != 0 {
+                    state.sounding &= !bit;
+                    emit(message);
+                }
+            }
+            MidiMessage::ControlChange {
+                channel,
+                control_number,
+                control_value,
+            } => {
+                let down = u8::from(control_value) >= 64;
+                let state = &mut self.channels[u8::from(channel) as usize];
+                match u8::from(control_number) {
+                    SUSTAIN_CONTROLLER => state.sustain = down,
+                    SOSTENUTO_CONTROLLER => {
+                        if down && !state.sostenuto {
+                            state.captured = state.held;
+                        }
+                        state.sostenuto = down;
+                    }
+                    _ => {
+                        emit(message);
+                        return;
+                    }
+                }
+                Self::release(state, channel, &mut emit);
+            }
+            _ => emit(message),
+        }
+    }
+
+    /// Release all sounding notes and reset the pedals.
+    ///
+    /// A note off is emitted for every sounding note. This is useful when the plugin is
+    /// deactivated or the transport stops.
+    pub fn release_all<F: FnMut(MidiMessage)>(&mut self, mut emit: F) {
+        for (channel, state) in self.channels.iter_mut().enumerate() {
+            *state = ChannelState {
+                sounding: state.sounding,
+                ..ChannelState::default()
+            };
+            Self::release(state, u4::new(channel as u8), &mut emit);
+        }
+    }
+
+    /// Emit note offs for all notes of the channel that aren't kept sounding anymore.
+    fn release<F: FnMut(MidiMessage)>(state: &mut ChannelState, channel: u4, emit: &mut F) {
+        let releasable = state.releasable();
+        for note in 0..128u8 {
+            if releasable & (1 << note) != 0 {
+                emit(MidiMessage::NoteOff {
+                    channel,
+                    note: u7::new(note),
+                    velocity: u7::new(0),
+                });
+            }
+        }
+        state.sounding &= !releasable;
+    }
+}
+
+impl Default for PedalProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pedal::*;
+
+    fn note_on(note: u8) -> MidiMessage {
+        MidiMessage::NoteOn {
+            channel: u4::new(0),
+            note: u7::new(note),
+            velocity: u7::new(100),
+        }
+    }
+
+    fn note_off(note: u8) -> MidiMessage {
+        MidiMessage::NoteOff {
+            channel: u4::new(0),
+            note: u7::new(note),
+            velocity: u7::new(0),
+        }
+    }
+
+    fn pedal(controller: u8, down: bool) -> MidiMessage {
+        MidiMessage::ControlChange {
+            channel: u4::new(0),
+            control_number: u7::new(controller),
+            control_value: u7::new(if down { 127 } else { 0 }),
+        }
+    }
+
+    fn process(processor: &mut PedalProcessor, message: MidiMessage) -> Vec<MidiMessage> {
+        let mut output = Vec::new();
+        processor.process(message, |message| output.push(message));
+        output
+    }
+
+    #[test]
+    fn test_sustain() {
+        let mut processor = PedalProcessor::new();
+        assert_eq!(vec![note_on(60)], process(&mut processor, note_on(60)));
+        assert!(process(&mut processor, pedal(SUSTAIN_CONTROLLER, true)).is_empty());
+        assert!(process(&mut processor, note_off(60)).is_empty());
+        assert!(processor.is_sounding(u4::new(0), u7::new(60)));
+
+        // Striking the note again retriggers it.
+        assert_eq!(
+            vec![note_off(60), note_on(60)],
+            process(&mut processor, note_on(60))
+        );
+        assert!(process(&mut processor, note_off(60)).is_empty());
+
+        assert_eq!(
+            vec![note_off(60)],
+            process(&mut processor, pedal(SUSTAIN_CONTROLLER, false))
+        );
+        assert!(!processor.is_sounding(u4::new(0), u7::new(60)));
+    }
+
+    #[test]
+    fn test_sostenuto() {
+        let mut processor = PedalProcessor::new();
+        process(&mut processor, note_on(60));
+        assert!(process(&mut processor, pedal(SOSTENUTO_CONTROLLER, true)).is_empty());
+
+        // Notes struck after pressing the pedal aren't captured.
+        process(&mut processor, note_on(64));
+        assert_eq!(vec![note_off(64)], process(&mut processor, note_off(64)));
+        assert!(process(&mut processor, note_off(60)).is_empty());
+
+        assert_eq!(
+            vec![note_off(60)],
+            process(&mut processor, pedal(SOSTENUTO_CONTROLLER, false))
+        );
+    }
+
+    #[test]
+    fn test_release_all() {
+        let mut processor = PedalProcessor::new();
+        process(&mut processor, pedal(SUSTAIN_CONTROLLER, true));
+        process(&mut processor, note_on(60));
+        process(&mut processor, note_off(60));
+
+        let mut output = Vec::new();
+        processor.release_all(|message| output.push(message));
+        assert_eq!(vec![note_off(60)], output);
+        assert!(!processor.sustain(u4::new(0)));
+    }
+}
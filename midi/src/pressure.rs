@@ -0,0 +1,334 @@
+//! Conversion between key pressure and channel pressure.
+//!
+//! MIDI knows two kinds of aftertouch: Polyphonic key pressure, which is sent for every held key,
+//! and channel pressure, which is sent once for the whole channel. Many keyboards and synthesizer
+//! engines only support one of them, and the [`PressureConverter`](struct.PressureConverter.html)
+//! translates between the two:
+//!
+//! * [`fold`](struct.PressureConverter.html#method.fold) converts key pressures into a channel
+//!   pressure, either by using the maximum of all key pressures or the pressure of the last struck
+//!   key, as defined by the [`PressurePolicy`](enum.PressurePolicy.html).
+//! * [`broadcast`](struct.PressureConverter.html#method.broadcast) converts a channel pressure into
+//!   key pressures for every held key.
+//!
+//! An example:
+//!
+//!     extern crate lv2rs_midi as midi;
+//!
+//!     use midi::prelude::*;
+//!     use midi::pressure::{PressureConverter, PressurePolicy};
+//!     use midi::MidiMessage;
+//!
+//!     let mut converter = PressureConverter::new(PressurePolicy::Max);
+//!     let mut output = Vec::new();
+//!
+//!     let channel = u4::new(0);
+//!     let messages = [
+//!         MidiMessage::NoteOn { channel, note: u7::new(60), velocity: u7::new(100) },
+//!         MidiMessage::NoteOn { channel, note: u7::new(64), velocity: u7::new(100) },
+//!         MidiMessage::PolyKeyPressure { channel, note: u7::new(60), pressure: u7::new(80) },
+//!         MidiMessage::PolyKeyPressure { channel, note: u7::new(64), pressure: u7::new(20) },
+//!     ];
+//!     for message in messages.iter() {
+//!         converter.fold(*message, |message| output.push(message));
+//!     }
+//!
+//!     // The pressure of the second key is lower and therefore doesn't change anything.
+//!     assert_eq!(3, output.len());
+//!     assert_eq!(MidiMessage::ChannelPressure { channel, pressure: u7::new(80) }, output[2]);
+use crate::message::MidiMessage;
+use crate::prelude::*;
+
+/// Policy to compute the channel pressure from the key pressures.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PressurePolicy {
+    /// Use the highest pressure of all held keys.
+    Max,
+    /// Use the pressure of the last struck key that is still held.
+    LastNote,
+}
+
+/// Key and pressure state of a single MIDI channel.
+#[derive(Clone, Copy)]
+struct ChannelState {
+    /// Keys that are held down, stored as a bit field where bit `n` represents note `n`.
+    held: u128,
+    /// The pressure of every key.
+    key_pressures: [u8; 128],
+    /// The order in which the keys were struck: Higher numbers were struck later.
+    strike_order: [u32; 128],
+    /// The number of the last strike.
+    strikes: u32,
+    /// The last channel pressure, either emitted or received.
+    channel_pressure: u8,
+}
+
+impl Default for ChannelState {
+    fn default() -> Self {
+        Self {
+            held: 0,
+            key_pressures: [0; 128],
+            strike_order: [0; 128],
+            strikes: 0,
+            channel_pressure: 0,
+        }
+    }
+}
+
+impl ChannelState {
+    fn press(&mut self, note: u7) {
+        let note = u8::from(note);
+        self.held |= 1 << note;
+        self.key_pressures[note as usize] = 0;
+        self.strikes = self.strikes.wrapping_add(1);
+        self.strike_order[note as usize] = self.strikes;
+    }
+
+    fn release(&mut self, note: u7) {
+        let note = u8::from(note);
+        self.held &= !(1 << note);
+        self.key_pressures[note as usize] = 0;
+    }
+
+    fn held_notes(&self) -> impl Iterator<Item = u8> {
+        let held = self.held;
+        (0..128u8).filter(move |note| held & (1 << note) != 0)
+    }
+
+    /// Compute the channel pressure from the key pressures.
+    fn folded_pressure(&self, policy: PressurePolicy) -> u8 {
+        let pressures = &self.key_pressures;
+        match policy {
+            PressurePolicy::Max => self
+                .held_notes()
+                .map(|note| pressures[note as usize])
+                .max()
+                .unwrap_or(0),
+            PressurePolicy::LastNote => self
+                .held_notes()
+                .max_by_key(|note| self.strike_order[*note as usize])
+                .map(|note| pressures[note as usize])
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// Converter between key pressure and channel pressure.
+///
+/// The converter tracks the held keys of all 16 channels independently. A converter should either
+/// be used for folding or for broadcasting, but not for both.
+///
+/// See the [module documentation](index.html) for more information.
+pub struct PressureConverter {
+    policy: PressurePolicy,
+    channels: [ChannelState; 16],
+}
+
+impl PressureConverter {
+    /// Create a new converter with no held keys.
+    pub fn new(policy: PressurePolicy) -> Self {
+        Self {
+            policy,
+            channels: [ChannelState::default(); 16],
+        }
+    }
+
+    /// Return the policy used for folding.
+    pub fn policy(&self) -> PressurePolicy {
+        self.policy
+    }
+
+    /// Set the policy used for folding.
+    pub fn set_policy(&mut self, policy: PressurePolicy) {
+        self.policy = policy;
+    }
+
+    /// Convert key pressures into channel pressures.
+    ///
+    /// Key pressure messages are consumed. Whenever the resulting channel pressure changes, either
+    /// because a key pressure changed or because a key was released, a channel pressure message is
+    /// emitted. All other messages are passed through unaltered.
+    pub fn fold<F: FnMut(MidiMessage)>(&mut self, message: MidiMessage, mut emit: F) {
+        let channel = match message {
+            MidiMessage::NoteOn {
+                channel,
+                note,
+                velocity,
+            } if u8::from(velocity) != 0 => {
+                self.channels[u8::from(channel) as usize].press(note);
+                emit(message);
+                channel
+            }
+            MidiMessage::NoteOn { channel, note, .. }
+            | MidiMessage::NoteOff { channel, note, .. } => {
+                self.channels[u8::from(channel) as usize].release(note);
+                emit(message);
+                channel
+            }
+            MidiMessage::PolyKeyPressure {
+                channel,
+                note,
+                pressure,
+            } => {
+                let state = &mut self.channels[u8::from(channel) as usize];
+                if state.held & (1 << u8::from(note)) != 0 {
+                    state.key_pressures[u8::from(note) as usize] = u8::from(pressure);
+                }
+                channel
+            }
+            _ => {
+                emit(message);
+                return;
+            }
+        };
+
+        let state = &mut self.channels[u8::from(channel) as usize];
+        let pressure = state.folded_pressure(self.policy);
+        if pressure != state.channel_pressure {
+            state.channel_pressure = pressure;
+            emit(MidiMessage::ChannelPressure {
+                channel,
+                pressure: u7::new(pressure),
+            });
+        }
+    }
+
+    /// Convert channel pressures into key pressures.
+    ///
+    /// Channel pressure messages are consumed and a key pressure message is emitted for every held
+    /// key of the channel. If a key is struck while the channel pressure isn't zero, a key
+    /// pressure message is emitted for it after the note on. All other messages are passed through
+    /// unaltered.
+    pub fn broadcast<F: FnMut(MidiMessage)>(&mut self, message: MidiMessage, mut emit: F) {
+        match message {
+            MidiMessage::NoteOn {
+                channel,
+                note,
+                velocity,
+            } if u8::from(velocity) != 0 => {
+                let state = &mut self.channels[u8::from(channel) as usize];
+                state.press(note);
+                emit(message);
+                if state.channel_pressure != 0 {
+                    emit(MidiMessage::PolyKeyPressure {
+                        channel,
+                        note,
+                        pressure: u7::new(state.channel_pressure),
+                    });
+                }
+            }
+            MidiMessage::NoteOn { channel, note, .. }
+            | MidiMessage::NoteOff { channel, note, .. } => {
+                self.channels[u8::from(channel) as usize].release(note);
+                emit(message);
+            }
+            MidiMessage::ChannelPressure { channel, pressure } => {
+                let state = &mut self.channels[u8::from(channel) as usize];
+                state.channel_pressure = u8::from(pressure);
+                for note in state.held_notes() {
+                    emit(MidiMessage::PolyKeyPressure {
+                        channel,
+                        note: u7::new(note),
+                        pressure,
+                    });
+                }
+            }
+            _ => emit(message),
+        }
+    }
+
+    /// Forget all held keys and pressures.
+    pub fn reset(&mut self) {
+        self.channels = [ChannelState::default(); 16];
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pressure::*;
+
+    fn note_on(note: u8) -> MidiMessage {
+        MidiMessage::NoteOn {
+            channel: u4::new(0),
+            note: u7::new(note),
+            velocity: u7::new(100),
+        }
+    }
+
+    fn note_off(note: u8) -> MidiMessage {
+        MidiMessage::NoteOff {
+            channel: u4::new(0),
+            note: u7::new(note),
+            velocity: u7::new(0),
+        }
+    }
+
+    fn key_pressure(note: u8, pressure: u8) -> MidiMessage {
+        MidiMessage::PolyKeyPressure {
+            channel: u4::new(0),
+            note: u7::new(note),
+            pressure: u7::new(pressure),
+        }
+    }
+
+    fn channel_pressure(pressure: u8) -> MidiMessage {
+        MidiMessage::ChannelPressure {
+            channel: u4::new(0),
+            pressure: u7::new(pressure),
+        }
+    }
+
+    fn fold(converter: &mut PressureConverter, message: MidiMessage) -> Vec<MidiMessage> {
+        let mut output = Vec::new();
+        converter.fold(message, |message| output.push(message));
+        output
+    }
+
+    fn broadcast(converter: &mut PressureConverter, message: MidiMessage) -> Vec<MidiMessage> {
+        let mut output = Vec::new();
+        converter.broadcast(message, |message| output.push(message));
+        output
+    }
+
+    #[test]
+    fn test_fold_last_note() {
+        let mut converter = PressureConverter::new(PressurePolicy::LastNote);
+        fold(&mut converter, note_on(60));
+        fold(&mut converter, note_on(64));
+        assert!(fold(&mut converter, key_pressure(60, 100)).is_empty());
+        assert_eq!(
+            vec![channel_pressure(30)],
+            fold(&mut converter, key_pressure(64, 30))
+        );
+
+        // Releasing the last note falls back to the previous one.
+        assert_eq!(
+            vec![note_off(64), channel_pressure(100)],
+            fold(&mut converter, note_off(64))
+        );
+        assert_eq!(
+            vec![note_off(60), channel_pressure(0)],
+            fold(&mut converter, note_off(60))
+        );
+    }
+
+    #[test]
+    fn test_broadcast() {
+        let mut converter = PressureConverter::new(PressurePolicy::Max);
+        broadcast(&mut converter, note_on(60));
+        broadcast(&mut converter, note_on(64));
+        assert_eq!(
+            vec![key_pressure(60, 50), key_pressure(64, 50)],
+            broadcast(&mut converter, channel_pressure(50))
+        );
+        assert_eq!(
+            vec![note_on(67), key_pressure(67, 50)],
+            broadcast(&mut converter, note_on(67))
+        );
+        broadcast(&mut converter, note_off(60));
+        assert_eq!(
+            vec![key_pressure(64, 0), key_pressure(67, 0)],
+            broadcast(&mut converter, channel_pressure(0))
+        );
+    }
+}
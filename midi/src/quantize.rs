@@ -0,0 +1,279 @@
+//! Quantization and humanization of time stamps.
+//!
+//! Quantizing moves messages to the nearest line of a rhythmic grid, for example every sixteenth
+//! note, and humanizing moves them randomly by a few frames to make them sound less mechanical.
+//! Both are implemented for [`TimedMidiMessage`s](../struct.TimedMidiMessage.html), which means
+//! that they fit well in front of a [`MidiScheduler`](../scheduler/struct.MidiScheduler.html).
+//!
+//! A [`Grid`](struct.Grid.html) is usually created from the tempo of the host, which is sent as a
+//! [`Position`](https://docs.rs/lv2rs-atom/*/lv2rs_atom/time/struct.Position.html). Since the grid
+//! lines are relative to the current block, the grid has to be
+//! [advanced](struct.Grid.html#method.advance) after every block.
+//!
+//! An example:
+//!
+//!     extern crate lv2rs_midi as midi;
+//!
+//!     use midi::prelude::*;
+//!     use midi::quantize::{Grid, Humanizer};
+//!     use midi::{MidiMessage, TimedMidiMessage};
+//!
+//!     // Sixteenth notes at 120 BPM and 48 kHz are 6000 frames long.
+//!     let grid = Grid::from_tempo(48000.0, 120.0, 4).unwrap();
+//!     assert_eq!(6000.0, grid.step());
+//!
+//!     let mut message = TimedMidiMessage {
+//!         frames: 6100,
+//!         message: MidiMessage::NoteOn {
+//!             channel: u4::new(0),
+//!             note: u7::new(60),
+//!             velocity: u7::new(100),
+//!         },
+//!     };
+//!     grid.quantize(&mut message, 1.0);
+//!     assert_eq!(6000, message.frames);
+//!
+//!     // Humanizing moves it by up to ten frames in either direction.
+//!     let mut humanizer = Humanizer::new(1, 10);
+//!     humanizer.humanize(&mut message);
+//!     assert!((message.frames - 6000).abs() <= 10);
+use crate::message::TimedMidiMessage;
+use lv2rs_atom::time::Position;
+
+/// A rhythmic grid with optional swing.
+///
+/// The grid consists of lines that are `step` frames apart. With swing, every odd line is delayed
+/// by a fraction of half a step. The position of the grid is given by the `offset`, which is the
+/// position of an even grid line relative to the beginning of the current block.
+///
+/// See the [module documentation](index.html) for more information.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Grid {
+    step: f64,
+    offset: f64,
+    swing: f64,
+}
+
+impl Grid {
+    /// Create a new grid without swing and the given distance between the lines, in frames.
+    ///
+    /// The first line of the grid is at the beginning of the current block.
+    pub fn new(step: f64) -> Self {
+        Self {
+            step: step.max(1.0),
+            offset: 0.0,
+            swing: 0.0,
+        }
+    }
+
+    /// Create a new grid with `steps_per_beat` lines in a beat of the given tempo.
+    ///
+    /// If the tempo or the sample rate is not a finite, positive number, `None` is returned.
+    pub fn from_tempo(
+        sample_rate: f64,
+        beats_per_minute: f32,
+        steps_per_beat: u32,
+    ) -> Option<Self> {
+        if !(beats_per_minute.is_finite() && beats_per_minute > 0.0) {
+            return None;
+        }
+        let frames_per_beat = sample_rate * 60.0 / beats_per_minute as f64;
+        let step = frames_per_beat / steps_per_beat.max(1) as f64;
+        if step.is_finite() && step > 0.0 {
+            Some(Self::new(step))
+        } else {
+            None
+        }
+    }
+
+    /// Create a new grid from the transport state of the host.
+    ///
+    /// The position should describe the beginning of the current block. If it contains the current
+    /// beat, the grid is aligned to the beats; Otherwise, the first line of the grid is at the
+    /// beginning of the block. If the position doesn't contain a valid tempo, `None` is returned.
+    pub fn from_position(
+        position: &Position,
+        sample_rate: f64,
+        steps_per_beat: u32,
+    ) -> Option<Self> {
+        let mut grid = Self::from_tempo(sample_rate, position.beats_per_minute?, steps_per_beat)?;
+        if let Some(beat) = position.beat {
+            // The position in steps, relative to the last even step.
+            let steps = (beat * steps_per_beat.max(1) as f64).rem_euclid(2.0);
+            grid.offset = -steps * grid.step;
+        }
+        Some(grid)
+    }
+
+    /// Set the swing of the grid.
+    ///
+    /// The swing is a value between zero and one: Zero means that the lines are evenly spaced and
+    /// one means that every odd line is delayed by half a step.
+    pub fn with_swing(mut self, swing: f32) -> Self {
+        self.swing = swing.clamp(0.0, 1.0) as f64;
+        self
+    }
+
+    /// Return the distance between two grid lines, in frames.
+    pub fn step(&self) -> f64 {
+        self.step
+    }
+
+    /// Return the swing of the grid.
+    pub fn swing(&self) -> f32 {
+        self.swing as f32
+    }
+
+    /// Move the grid to the next block.
+    ///
+    /// This has to be called once after every block with the length of the block, in frames.
+    pub fn advance(&mut self, n_samples: u32) {
+        let period = 2.0 * self.step;
+        self.offset = (self.offset - n_samples as f64).rem_euclid(period) - period;
+    }
+
+    /// Return the position of the grid line with the given index, relative to the offset.
+    fn line(&self, index: i64) -> f64 {
+        let mut position = self.offset + index as f64 * self.step;
+        if index.rem_euclid(2) == 1 {
+            position += self.swing * self.step / 2.0;
+        }
+        position
+    }
+
+    /// Return the position of the grid line that is closest to the given time stamp.
+    pub fn snap(&self, frames: i64) -> i64 {
+        let index = ((frames as f64 - self.offset) / self.step).floor() as i64;
+        let nearest = (index - 1..=index + 1)
+            .map(|index| self.line(index))
+            .min_by(|a, b| {
+                let a = (a - frames as f64).abs();
+                let b = (b - frames as f64).abs();
+                a.total_cmp(&b)
+            })
+            .unwrap();
+        nearest.round() as i64
+    }
+
+    /// Move the message towards the closest grid line.
+    ///
+    /// The strength is a value between zero and one: Zero means that the message isn't moved at
+    /// all, one means that it is moved exactly to the grid line and everything in between moves
+    /// it partially.
+    pub fn quantize(&self, message: &mut TimedMidiMessage, strength: f32) {
+        let strength = strength.clamp(0.0, 1.0) as f64;
+        let target = self.snap(message.frames);
+        let distance = (target - message.frames) as f64;
+        message.frames += (distance * strength).round() as i64;
+    }
+}
+
+/// Random displacement of time stamps.
+///
+/// The humanizer uses a simple, deterministic pseudo-random number generator, which does not
+/// allocate memory and can therefore be used in a real-time context.
+///
+/// See the [module documentation](index.html) for more information.
+pub struct Humanizer {
+    state: u32,
+    max_jitter: i64,
+}
+
+impl Humanizer {
+    /// Create a new humanizer that moves messages by up to `max_jitter` frames.
+    ///
+    /// The `seed` is used to initialize the random number generator; Two humanizers with the same
+    /// seed produce the same displacements.
+    pub fn new(seed: u32, max_jitter: u32) -> Self {
+        Self {
+            // The generator would only produce zeros with a zero state.
+            state: if seed == 0 { 0x9E37_79B9 } else { seed },
+            max_jitter: max_jitter as i64,
+        }
+    }
+
+    /// Return the next pseudo-random number.
+    fn next(&mut self) -> u32 {
+        // xorshift32
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        self.state
+    }
+
+    /// Return a random displacement between `-max_jitter` and `max_jitter` frames.
+    pub fn jitter(&mut self) -> i64 {
+        if self.max_jitter == 0 {
+            return 0;
+        }
+        let range = 2 * self.max_jitter + 1;
+        (self.next() as i64 % range) - self.max_jitter
+    }
+
+    /// Move the message by a random number of frames.
+    ///
+    /// The time stamp may become negative, in which case the message is late.
+    pub fn humanize(&mut self, message: &mut TimedMidiMessage) {
+        message.frames += self.jitter();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::quantize::*;
+
+    #[test]
+    fn test_swing() {
+        let grid = Grid::new(100.0).with_swing(0.5);
+        assert_eq!(0, grid.snap(20));
+        assert_eq!(125, grid.snap(90));
+        assert_eq!(200, grid.snap(170));
+        assert_eq!(-75, grid.snap(-60));
+    }
+
+    #[test]
+    fn test_position() {
+        let position = Position {
+            beats_per_minute: Some(60.0),
+            beat: Some(2.3125),
+            ..Position::default()
+        };
+        // One step is 100 frames long and the block starts a quarter step after a line.
+        let mut grid = Grid::from_position(&position, 400.0, 4).unwrap();
+        assert_eq!(75, grid.snap(70));
+
+        grid.advance(110);
+        assert_eq!(-35, grid.snap(-20));
+        assert_eq!(65, grid.snap(70));
+        assert!(Grid::from_position(&Position::default(), 400.0, 4).is_none());
+    }
+
+    #[test]
+    fn test_invalid_tempo() {
+        assert!(Grid::from_tempo(44100.0, 0.0, 4).is_none());
+        assert!(Grid::from_tempo(44100.0, -120.0, 4).is_none());
+        assert!(Grid::from_tempo(44100.0, f32::NAN, 4).is_none());
+        assert!(Grid::from_tempo(44100.0, f32::INFINITY, 4).is_none());
+        assert!(Grid::from_tempo(f64::NAN, 120.0, 4).is_none());
+        assert!(Grid::from_tempo(0.0, 120.0, 4).is_none());
+
+        let position = Position {
+            beats_per_minute: Some(0.0),
+            beat: Some(1.0),
+            ..Position::default()
+        };
+        assert!(Grid::from_position(&position, 44100.0, 4).is_none());
+    }
+
+    #[test]
+    fn test_humanizer() {
+        let mut a = Humanizer::new(42, 5);
+        let mut b = Humanizer::new(42, 5);
+        for _ in 0..100 {
+            let jitter = a.jitter();
+            assert!(jitter.abs() <= 5);
+            assert_eq!(jitter, b.jitter());
+        }
+        assert_eq!(0, Humanizer::new(42, 0).jitter());
+    }
+}
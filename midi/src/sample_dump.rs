@@ -0,0 +1,675 @@
+//! The MIDI Sample Dump Standard.
+//!
+//! The Sample Dump Standard (SDS) transfers audio samples between samplers using universal
+//! non-real-time system-exclusive messages. A transfer starts with a
+//! [`DumpHeader`](struct.DumpHeader.html) describing the sample, followed by data packets of 120
+//! bytes each. After every message, the receiver answers with an ACK, which lets the sender
+//! continue, or with a NAK, which makes the sender repeat the last packet.
+//!
+//! The [`SampleDumpSender`](struct.SampleDumpSender.html) and the
+//! [`SampleDumpReceiver`](struct.SampleDumpReceiver.html) implement both sides of this handshake:
+//! You pass them every incoming [`SampleDumpMessage`](enum.SampleDumpMessage.html) and send the
+//! messages they return. Both allocate memory for the sample data and should therefore be used
+//! outside of the audio thread, for example by a worker.
+//!
+//! Like all universal messages, the messages in this module work on the data bytes of
+//! system-exclusive messages, without the start and end status bytes.
+//!
+//!     extern crate lv2rs_midi as midi;
+//!
+//!     use midi::prelude::*;
+//!     use midi::sample_dump::*;
+//!
+//!     let header = DumpHeader {
+//!         sample_number: u14::new(3),
+//!         bits: 16,
+//!         period: 20833,
+//!         length: 200,
+//!         loop_start: 0,
+//!         loop_end: 199,
+//!         loop_type: LoopType::Off,
+//!     };
+//!     let samples: Vec<u32> = (0..200).map(|i| i * 300).collect();
+//!
+//!     let mut sender = SampleDumpSender::new(u7::new(0), header, &samples);
+//!     let mut receiver = SampleDumpReceiver::new(u7::new(0));
+//!
+//!     // Play the handshake until no one has anything to say anymore.
+//!     let mut message = Some(sender.start());
+//!     while let Some(to_receiver) = message {
+//!         message = receiver
+//!             .handle(&to_receiver)
+//!             .and_then(|to_sender| sender.handle(&to_sender));
+//!     }
+//!
+//!     assert!(sender.is_finished());
+//!     assert!(receiver.is_complete());
+//!     assert_eq!(&samples[..], receiver.samples());
+use crate::prelude::*;
+use crate::universal::{all_data_bytes, join_u14, split_u14, NON_REALTIME_ID};
+
+/// The sub-ID of dump headers.
+pub const HEADER_SUB_ID: u8 = 0x01;
+/// The sub-ID of data packets.
+pub const DATA_PACKET_SUB_ID: u8 = 0x02;
+/// The sub-ID of dump requests.
+pub const REQUEST_SUB_ID: u8 = 0x03;
+/// The sub-ID of wait messages.
+pub const WAIT_SUB_ID: u8 = 0x7c;
+/// The sub-ID of cancel messages.
+pub const CANCEL_SUB_ID: u8 = 0x7d;
+/// The sub-ID of negative acknowledgements.
+pub const NAK_SUB_ID: u8 = 0x7e;
+/// The sub-ID of acknowledgements.
+pub const ACK_SUB_ID: u8 = 0x7f;
+
+/// The number of sample data bytes in a data packet.
+pub const PACKET_DATA_LEN: usize = 120;
+
+/// Split a 21-bit value into three 7-bit bytes, least significant first.
+fn split_u21(value: u32) -> [u8; 3] {
+    [
+        (value & 0x7f) as u8,
+        ((value >> 7) & 0x7f) as u8,
+        ((value >> 14) & 0x7f) as u8,
+    ]
+}
+
+/// Join three 7-bit bytes, least significant first, to a 21-bit value.
+fn join_u21(bytes: &[u8]) -> u32 {
+    bytes[0] as u32 | (bytes[1] as u32) << 7 | (bytes[2] as u32) << 14
+}
+
+/// How a sample is looped.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LoopType {
+    /// Loop forwards.
+    Forward,
+    /// Loop forwards and backwards.
+    Alternating,
+    /// Don't loop.
+    Off,
+}
+
+impl LoopType {
+    fn from_byte(byte: u8) -> Result<Self, ()> {
+        match byte {
+            0x00 => Ok(LoopType::Forward),
+            0x01 => Ok(LoopType::Alternating),
+            0x7f => Ok(LoopType::Off),
+            _ => Err(()),
+        }
+    }
+
+    fn to_byte(self) -> u8 {
+        match self {
+            LoopType::Forward => 0x00,
+            LoopType::Alternating => 0x01,
+            LoopType::Off => 0x7f,
+        }
+    }
+}
+
+/// Description of a sample, sent at the beginning of a dump.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DumpHeader {
+    /// The number of the sample in the sampler's memory.
+    pub sample_number: u14,
+    /// The resolution of the sample, between 8 and 28 bits.
+    pub bits: u8,
+    /// The sample period, in nanoseconds.
+    pub period: u32,
+    /// The length of the sample, in words.
+    pub length: u32,
+    /// The first word of the sustain loop.
+    pub loop_start: u32,
+    /// The last word of the sustain loop.
+    pub loop_end: u32,
+    /// The type of the sustain loop.
+    pub loop_type: LoopType,
+}
+
+impl DumpHeader {
+    /// Return the number of bytes a word of sample data occupies in the data packets.
+    pub fn bytes_per_word(&self) -> usize {
+        (self.bits as usize).div_ceil(7)
+    }
+
+    /// Return the number of data packets needed to transfer the sample.
+    pub fn n_packets(&self) -> usize {
+        let n_bytes = self.length as usize * self.bytes_per_word();
+        n_bytes.div_ceil(PACKET_DATA_LEN)
+    }
+}
+
+/// A message of the Sample Dump Standard.
+///
+/// Every message carries the ID of the device it is sent to or from, which is called the channel
+/// in the standard.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum SampleDumpMessage {
+    /// Describe the sample that is about to be sent.
+    Header { device_id: u7, header: DumpHeader },
+    /// A packet of sample data.
+    ///
+    /// Packets are numbered, starting with zero, and the number wraps around after 127.
+    DataPacket {
+        device_id: u7,
+        number: u7,
+        data: [u8; PACKET_DATA_LEN],
+    },
+    /// Request a sample.
+    Request { device_id: u7, sample_number: u14 },
+    /// The last message was received correctly, send the next one.
+    Ack { device_id: u7, packet: u7 },
+    /// The last packet was corrupted, send it again.
+    Nak { device_id: u7, packet: u7 },
+    /// Abort the transfer.
+    Cancel { device_id: u7, packet: u7 },
+    /// Pause the transfer until the next ACK arrives.
+    Wait { device_id: u7, packet: u7 },
+}
+
+/// Compute the checksum of a data packet.
+fn checksum(device_id: u7, number: u7, data: &[u8]) -> u8 {
+    let mut checksum =
+        NON_REALTIME_ID ^ u8::from(device_id) ^ DATA_PACKET_SUB_ID ^ u8::from(number);
+    for byte in data {
+        checksum ^= byte;
+    }
+    checksum & 0x7f
+}
+
+impl SampleDumpMessage {
+    /// The maximal length of the encoded data.
+    pub const MAX_DATA_LEN: usize = 5 + PACKET_DATA_LEN;
+
+    /// Try to parse the data of a system-exclusive message as a sample dump message.
+    ///
+    /// Data packets with a wrong checksum are rejected.
+    pub fn parse(data: &[u8]) -> Result<Self, ()> {
+        if !all_data_bytes(data) {
+            return Err(());
+        }
+        let (device_id, sub_id, body) = match data {
+            [NON_REALTIME_ID, device_id, sub_id, body @ ..] => (u7::new(*device_id), *sub_id, body),
+            _ => return Err(()),
+        };
+        match (sub_id, body) {
+            (HEADER_SUB_ID, [sl, sh, bits, body @ ..]) if body.len() == 13 => {
+                let header = DumpHeader {
+                    sample_number: join_u14(*sl, *sh),
+                    bits: *bits,
+                    period: join_u21(&body[0..3]),
+                    length: join_u21(&body[3..6]),
+                    loop_start: join_u21(&body[6..9]),
+                    loop_end: join_u21(&body[9..12]),
+                    loop_type: LoopType::from_byte(body[12])?,
+                };
+                Ok(SampleDumpMessage::Header { device_id, header })
+            }
+            (DATA_PACKET_SUB_ID, [number, body @ ..]) if body.len() == PACKET_DATA_LEN + 1 => {
+                let number = u7::new(*number);
+                let (packet_data, packet_checksum) = body.split_at(PACKET_DATA_LEN);
+                if checksum(device_id, number, packet_data) != packet_checksum[0] {
+                    return Err(());
+                }
+                let mut data = [0; PACKET_DATA_LEN];
+                data.copy_from_slice(packet_data);
+                Ok(SampleDumpMessage::DataPacket {
+                    device_id,
+                    number,
+                    data,
+                })
+            }
+            (REQUEST_SUB_ID, [sl, sh]) => Ok(SampleDumpMessage::Request {
+                device_id,
+                sample_number: join_u14(*sl, *sh),
+            }),
+            (ACK_SUB_ID, [packet]) => Ok(SampleDumpMessage::Ack {
+                device_id,
+                packet: u7::new(*packet),
+            }),
+            (NAK_SUB_ID, [packet]) => Ok(SampleDumpMessage::Nak {
+                device_id,
+                packet: u7::new(*packet),
+            }),
+            (CANCEL_SUB_ID, [packet]) => Ok(SampleDumpMessage::Cancel {
+                device_id,
+                packet: u7::new(*packet),
+            }),
+            (WAIT_SUB_ID, [packet]) => Ok(SampleDumpMessage::Wait {
+                device_id,
+                packet: u7::new(*packet),
+            }),
+            _ => Err(()),
+        }
+    }
+
+    /// Return the ID of the device the message is sent to or from.
+    pub fn device_id(&self) -> u7 {
+        match self {
+            SampleDumpMessage::Header { device_id, .. }
+            | SampleDumpMessage::DataPacket { device_id, .. }
+            | SampleDumpMessage::Request { device_id, .. }
+            | SampleDumpMessage::Ack { device_id, .. }
+            | SampleDumpMessage::Nak { device_id, .. }
+            | SampleDumpMessage::Cancel { device_id, .. }
+            | SampleDumpMessage::Wait { device_id, .. } => *device_id,
+        }
+    }
+
+    /// Write the data of the system-exclusive message to the buffer.
+    ///
+    /// The returned slice is the part of the buffer that contains the data.
+    pub fn encode<'a>(&self, buffer: &'a mut [u8; Self::MAX_DATA_LEN]) -> &'a [u8] {
+        buffer[0] = NON_REALTIME_ID;
+        buffer[1] = u8::from(self.device_id());
+        let len = match self {
+            SampleDumpMessage::Header { header, .. } => {
+                buffer[2] = HEADER_SUB_ID;
+                let (sl, sh) = split_u14(header.sample_number);
+                buffer[3] = sl;
+                buffer[4] = sh;
+                buffer[5] = header.bits;
+                buffer[6..9].copy_from_slice(&split_u21(header.period));
+                buffer[9..12].copy_from_slice(&split_u21(header.length));
+                buffer[12..15].copy_from_slice(&split_u21(header.loop_start));
+                buffer[15..18].copy_from_slice(&split_u21(header.loop_end));
+                buffer[18] = header.loop_type.to_byte();
+                19
+            }
+            SampleDumpMessage::DataPacket {
+                device_id,
+                number,
+                data,
+            } => {
+                buffer[2] = DATA_PACKET_SUB_ID;
+                buffer[3] = u8::from(*number);
+                buffer[4..4 + PACKET_DATA_LEN].copy_from_slice(data);
+                buffer[4 + PACKET_DATA_LEN] = checksum(*device_id, *number, data);
+                5 + PACKET_DATA_LEN
+            }
+            SampleDumpMessage::Request { sample_number, .. } => {
+                buffer[2] = REQUEST_SUB_ID;
+                let (sl, sh) = split_u14(*sample_number);
+                buffer[3] = sl;
+                buffer[4] = sh;
+                5
+            }
+            SampleDumpMessage::Ack { packet, .. }
+            | SampleDumpMessage::Nak { packet, .. }
+            | SampleDumpMessage::Cancel { packet, .. }
+            | SampleDumpMessage::Wait { packet, .. } => {
+                buffer[2] = match self {
+                    SampleDumpMessage::Ack { .. } => ACK_SUB_ID,
+                    SampleDumpMessage::Nak { .. } => NAK_SUB_ID,
+                    SampleDumpMessage::Cancel { .. } => CANCEL_SUB_ID,
+                    _ => WAIT_SUB_ID,
+                };
+                buffer[3] = u8::from(*packet);
+                4
+            }
+        };
+        &buffer[..len]
+    }
+}
+
+/// Encode sample words into the byte stream of the data packets.
+///
+/// Every word is left-justified and split into 7-bit bytes, most significant first.
+fn encode_words(words: &[u32], bits: u8, bytes_per_word: usize) -> Vec<u8> {
+    let shift = 7 * bytes_per_word as u32 - bits as u32;
+    let mut stream = Vec::with_capacity(words.len() * bytes_per_word);
+    for word in words {
+        let word = (word & ((1 << bits) - 1)) << shift;
+        for index in (0..bytes_per_word).rev() {
+            stream.push(((word >> (7 * index)) & 0x7f) as u8);
+        }
+    }
+    stream
+}
+
+/// Decode a word from the byte stream of the data packets.
+fn decode_word(bytes: &[u8], bits: u8) -> u32 {
+    let shift = 7 * bytes.len() as u32 - bits as u32;
+    let word = bytes
+        .iter()
+        .fold(0u32, |word, byte| (word << 7) | *byte as u32);
+    word >> shift
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum SenderState {
+    Idle,
+    Header,
+    Packet(usize),
+    Waiting(usize),
+    Finished,
+    Cancelled,
+}
+
+/// The sending side of a sample dump.
+///
+/// See the [module documentation](index.html) for more information.
+pub struct SampleDumpSender {
+    device_id: u7,
+    header: DumpHeader,
+    stream: Vec<u8>,
+    state: SenderState,
+}
+
+impl SampleDumpSender {
+    /// Create a new sender.
+    ///
+    /// The samples are unsigned words with the resolution given in the header, where zero is the
+    /// most negative value. Only as many samples as noted in the header are sent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resolution of the header is not between 8 and 28 bits.
+    pub fn new(device_id: u7, header: DumpHeader, samples: &[u32]) -> Self {
+        assert!(header.bits >= 8 && header.bits <= 28);
+        let length = samples.len().min(header.length as usize);
+        let stream = encode_words(&samples[..length], header.bits, header.bytes_per_word());
+        Self {
+            device_id,
+            header,
+            stream,
+            state: SenderState::Idle,
+        }
+    }
+
+    /// Start the transfer and return the header message.
+    ///
+    /// This may also be used to restart a transfer.
+    pub fn start(&mut self) -> SampleDumpMessage {
+        self.state = SenderState::Header;
+        SampleDumpMessage::Header {
+            device_id: self.device_id,
+            header: self.header,
+        }
+    }
+
+    /// Return the data packet with the given index.
+    fn packet(&self, index: usize) -> SampleDumpMessage {
+        let mut data = [0; PACKET_DATA_LEN];
+        let start = (index * PACKET_DATA_LEN).min(self.stream.len());
+        let end = (start + PACKET_DATA_LEN).min(self.stream.len());
+        data[..end - start].copy_from_slice(&self.stream[start..end]);
+        SampleDumpMessage::DataPacket {
+            device_id: self.device_id,
+            number: u7::new((index % 128) as u8),
+            data,
+        }
+    }
+
+    /// Return the message that follows a successfully sent one, if there is any.
+    fn advance(&mut self, index: Option<usize>) -> Option<SampleDumpMessage> {
+        let next = index.map(|index| index + 1).unwrap_or(0);
+        if next < self.header.n_packets() {
+            self.state = SenderState::Packet(next);
+            Some(self.packet(next))
+        } else {
+            self.state = SenderState::Finished;
+            None
+        }
+    }
+
+    /// Handle a reply of the receiver.
+    ///
+    /// The returned message, if any, should be sent next. Replies from other devices or for other
+    /// packets are ignored.
+    pub fn handle(&mut self, reply: &SampleDumpMessage) -> Option<SampleDumpMessage> {
+        if reply.device_id() != self.device_id {
+            return None;
+        }
+        let current = match self.state {
+            SenderState::Header => None,
+            SenderState::Packet(index) | SenderState::Waiting(index) => Some(index),
+            SenderState::Idle | SenderState::Finished | SenderState::Cancelled => return None,
+        };
+        let expected_number = u7::new((current.unwrap_or(0) % 128) as u8);
+        match reply {
+            SampleDumpMessage::Ack { packet, .. } if *packet == expected_number => {
+                self.advance(current)
+            }
+            SampleDumpMessage::Nak { packet, .. } if *packet == expected_number => match current {
+                Some(index) => {
+                    self.state = SenderState::Packet(index);
+                    Some(self.packet(index))
+                }
+                None => Some(self.start()),
+            },
+            SampleDumpMessage::Wait { .. } => {
+                if let Some(index) = current {
+                    self.state = SenderState::Waiting(index);
+                }
+                None
+            }
+            SampleDumpMessage::Cancel { .. } => {
+                self.state = SenderState::Cancelled;
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Continue without waiting for a reply.
+    ///
+    /// The standard allows the sender to continue if the receiver doesn't answer within a
+    /// timeout, since not all receivers implement the handshake. Call this method when your
+    /// timeout has elapsed to get the next message, if there is any.
+    pub fn timeout(&mut self) -> Option<SampleDumpMessage> {
+        match self.state {
+            SenderState::Header => self.advance(None),
+            SenderState::Packet(index) => self.advance(Some(index)),
+            _ => None,
+        }
+    }
+
+    /// Return `true` if all packets were sent and acknowledged.
+    pub fn is_finished(&self) -> bool {
+        self.state == SenderState::Finished
+    }
+
+    /// Return `true` if the receiver cancelled the transfer.
+    pub fn is_cancelled(&self) -> bool {
+        self.state == SenderState::Cancelled
+    }
+}
+
+/// The receiving side of a sample dump.
+///
+/// See the [module documentation](index.html) for more information.
+pub struct SampleDumpReceiver {
+    device_id: u7,
+    header: Option<DumpHeader>,
+    stream: Vec<u8>,
+    next_packet: usize,
+}
+
+impl SampleDumpReceiver {
+    /// Create a new receiver listening to the given device ID.
+    pub fn new(device_id: u7) -> Self {
+        Self {
+            device_id,
+            header: None,
+            stream: Vec::new(),
+            next_packet: 0,
+        }
+    }
+
+    /// Handle a message of the sender.
+    ///
+    /// The returned message, if any, is the reply that should be sent back. A new header always
+    /// starts a new transfer. Packets that arrive out of order are answered with a NAK.
+    pub fn handle(&mut self, message: &SampleDumpMessage) -> Option<SampleDumpMessage> {
+        if message.device_id() != self.device_id {
+            return None;
+        }
+        match message {
+            SampleDumpMessage::Header { header, .. } => {
+                if header.bits < 8 || header.bits > 28 {
+                    return Some(SampleDumpMessage::Cancel {
+                        device_id: self.device_id,
+                        packet: u7::new(0),
+                    });
+                }
+                self.header = Some(*header);
+                self.stream.clear();
+                self.stream.reserve(header.n_packets() * PACKET_DATA_LEN);
+                self.next_packet = 0;
+                Some(SampleDumpMessage::Ack {
+                    device_id: self.device_id,
+                    packet: u7::new(0),
+                })
+            }
+            SampleDumpMessage::DataPacket { number, data, .. } => {
+                let header = self.header?;
+                if self.next_packet >= header.n_packets() {
+                    return None;
+                }
+                if usize::from(u8::from(*number)) != self.next_packet % 128 {
+                    return Some(SampleDumpMessage::Nak {
+                        device_id: self.device_id,
+                        packet: u7::new((self.next_packet % 128) as u8),
+                    });
+                }
+                self.stream.extend_from_slice(data);
+                self.next_packet += 1;
+                Some(SampleDumpMessage::Ack {
+                    device_id: self.device_id,
+                    packet: *number,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Return the header of the current transfer, if one was received.
+    pub fn header(&self) -> Option<&DumpHeader> {
+        self.header.as_ref()
+    }
+
+    /// Return `true` if all packets of the current transfer were received.
+    pub fn is_complete(&self) -> bool {
+        match self.header {
+            Some(header) => self.next_packet >= header.n_packets(),
+            None => false,
+        }
+    }
+
+    /// Return the number of completely received samples.
+    pub fn n_received(&self) -> usize {
+        match self.header {
+            Some(header) => {
+                (self.stream.len() / header.bytes_per_word()).min(header.length as usize)
+            }
+            None => 0,
+        }
+    }
+
+    /// Return the received samples.
+    ///
+    /// The samples are decoded on every call, which allocates memory. The samples are unsigned
+    /// words with the resolution given in the header, where zero is the most negative value.
+    pub fn samples(&self) -> Vec<u32> {
+        let header = match self.header {
+            Some(header) => header,
+            None => return Vec::new(),
+        };
+        self.stream
+            .chunks_exact(header.bytes_per_word())
+            .take(self.n_received())
+            .map(|bytes| decode_word(bytes, header.bits))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::sample_dump::*;
+
+    fn header(bits: u8, length: u32) -> DumpHeader {
+        DumpHeader {
+            sample_number: u14::new(300),
+            bits,
+            period: 22675,
+            length,
+            loop_start: 10,
+            loop_end: length - 1,
+            loop_type: LoopType::Forward,
+        }
+    }
+
+    #[test]
+    fn test_encoding() {
+        let header = header(12, 100);
+        let message = SampleDumpMessage::Header {
+            device_id: u7::new(2),
+            header,
+        };
+        let mut buffer = [0u8; SampleDumpMessage::MAX_DATA_LEN];
+        let data = message.encode(&mut buffer);
+        assert_eq!(19, data.len());
+        assert_eq!(message, SampleDumpMessage::parse(data).unwrap());
+
+        let mut packet = [0u8; PACKET_DATA_LEN];
+        packet[0] = 0x55;
+        let message = SampleDumpMessage::DataPacket {
+            device_id: u7::new(2),
+            number: u7::new(5),
+            data: packet,
+        };
+        let mut buffer = [0u8; SampleDumpMessage::MAX_DATA_LEN];
+        let data = message.encode(&mut buffer);
+        assert_eq!(message, SampleDumpMessage::parse(data).unwrap());
+        buffer[10] = 1;
+        assert!(SampleDumpMessage::parse(&buffer).is_err());
+
+        // 12 bits are left-justified in two bytes.
+        assert_eq!(vec![0x7f, 0x7c], encode_words(&[0xfff], 12, 2));
+        assert_eq!(0xfff, decode_word(&[0x7f, 0x7c], 12));
+    }
+
+    #[test]
+    fn test_handshake() {
+        let samples: Vec<u32> = (0..100).map(|i| i * 1000).collect();
+        let mut sender = SampleDumpSender::new(u7::new(1), header(20, 100), &samples);
+        let mut receiver = SampleDumpReceiver::new(u7::new(1));
+
+        let header_message = sender.start();
+        let ack = receiver.handle(&header_message).unwrap();
+        let first = sender.handle(&ack).unwrap();
+        let ack = receiver.handle(&first).unwrap();
+
+        // A NAK repeats the last packet.
+        let second = sender.handle(&ack).unwrap();
+        let nak = SampleDumpMessage::Nak {
+            device_id: u7::new(1),
+            packet: u7::new(1),
+        };
+        assert_eq!(second, sender.handle(&nak).unwrap());
+
+        // Out-of-order packets are rejected.
+        assert_eq!(Some(nak), receiver.handle(&first));
+
+        let mut message = receiver.handle(&second);
+        while let Some(reply) = message {
+            message = sender
+                .handle(&reply)
+                .and_then(|packet| receiver.handle(&packet));
+        }
+        assert!(sender.is_finished());
+        assert_eq!(samples, receiver.samples());
+
+        let cancel = SampleDumpMessage::Cancel {
+            device_id: u7::new(1),
+            packet: u7::new(0),
+        };
+        sender.start();
+        assert!(sender.handle(&cancel).is_none());
+        assert!(sender.is_cancelled());
+    }
+}
@@ -0,0 +1,263 @@
+//! Scheduling of future MIDI messages.
+//!
+//! Many MIDI effects, like arpeggiators or delays, don't only react to incoming messages but create
+//! messages that should be sent at some point in the future, possibly in a later `run` block. The
+//! [`MidiScheduler`](struct.MidiScheduler.html) keeps these messages around and writes them to an
+//! output sequence as soon as they are due.
+//!
+//! The time stamps of scheduled messages are always relative to the beginning of the current
+//! block. When a block is flushed, all remaining messages are moved back in time by the length of
+//! the block, so that their time stamps are relative to the next block.
+//!
+//! An example:
+//!
+//!     extern crate lv2rs_atom as atom;
+//!     extern crate lv2rs_midi as midi;
+//!     extern crate lv2rs_urid as urid;
+//!
+//!     use atom::prelude::*;
+//!     use atom::ports::*;
+//!     use atom::sequence::TimeUnit;
+//!     use midi::prelude::*;
+//!     use midi::scheduler::MidiScheduler;
+//!     use midi::{MidiMessage, TimedMidiMessage};
+//!     use urid::debug::DebugMap;
+//!
+//!     // Getting a debug URID map.
+//!     let mut debug_map = DebugMap::new();
+//!     let mut urids = unsafe { debug_map.create_cached_map() };
+//!
+//!     // Creating the atom space and the output port.
+//!     let mut atom_space = vec![0u8; 256];
+//!     let atom = unsafe { (atom_space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+//!     *(atom.mut_size()) = 256 - 8;
+//!     let mut out_port: AtomOutputPort<Sequence> = AtomOutputPort::new();
+//!     out_port.connect_port(atom);
+//!
+//!     // Schedule a note that starts now and ends in the next block.
+//!     let mut scheduler = MidiScheduler::with_capacity(16);
+//!     let note = MidiMessage::NoteOn {
+//!         channel: u4::new(0),
+//!         note: u7::new(60),
+//!         velocity: u7::new(100),
+//!     };
+//!     scheduler.schedule(TimedMidiMessage { frames: 0, message: note });
+//!     let note = MidiMessage::NoteOff {
+//!         channel: u4::new(0),
+//!         note: u7::new(60),
+//!         velocity: u7::new(0),
+//!     };
+//!     scheduler.schedule(TimedMidiMessage { frames: 300, message: note });
+//!
+//!     // Simulated `run` with a block size of 256 frames.
+//!     {
+//!         let mut frame =
+//!             unsafe { out_port.write_atom_body(&TimeUnit::Frames, &mut urids) }.unwrap();
+//!         scheduler.flush(&mut frame, 256, &mut urids).unwrap();
+//!     }
+//!
+//!     // Only the note off is left, now 44 frames into the next block.
+//!     assert_eq!(1, scheduler.len());
+//!     assert_eq!(44, scheduler.pending().next().unwrap().frames);
+use crate::atom::RawMidiMessage;
+use crate::message::TimedMidiMessage;
+use lv2rs_atom::prelude::*;
+use lv2rs_atom::sequence::TimeStamp;
+use lv2rs_urid::CachedMap;
+use std::collections::VecDeque;
+
+/// Buffer for MIDI messages that should be sent in the future.
+///
+/// Messages are kept in chronological order; Messages with the same time stamp are kept in the
+/// order they were scheduled in.
+///
+/// Scheduling may allocate memory if the internal buffer is full. If you want to use the scheduler
+/// in a real-time context, you should create it with [`with_capacity`](#method.with_capacity) and
+/// a capacity that is big enough for your use case.
+///
+/// See the [module documentation](index.html) for more information.
+pub struct MidiScheduler {
+    queue: VecDeque<TimedMidiMessage>,
+}
+
+impl MidiScheduler {
+    /// Create a new, empty scheduler.
+    pub fn new() -> Self {
+        Self {
+            queue: VecDeque::new(),
+        }
+    }
+
+    /// Create a new, empty scheduler with space for at least `capacity` messages.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            queue: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Add a message to the scheduler.
+    ///
+    /// The time stamp of the message is relative to the beginning of the current block. Messages
+    /// with a negative time stamp are considered late and will be sent at the beginning of the
+    /// next flushed block.
+    pub fn schedule(&mut self, message: TimedMidiMessage) {
+        let index = self
+            .queue
+            .iter()
+            .position(|pending| pending.frames > message.frames)
+            .unwrap_or(self.queue.len());
+        self.queue.insert(index, message);
+    }
+
+    /// Return the number of pending messages.
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Return `true` if there are no pending messages.
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Remove all pending messages.
+    pub fn clear(&mut self) {
+        self.queue.clear();
+    }
+
+    /// Iterate over all pending messages in chronological order.
+    pub fn pending(&self) -> impl Iterator<Item = &TimedMidiMessage> {
+        self.queue.iter()
+    }
+
+    /// Remove and return all messages that are due in a block of `n_samples` frames.
+    ///
+    /// Late messages will have their time stamp clamped to zero. All remaining messages are moved
+    /// back in time by `n_samples` frames, which means that this method should be called exactly
+    /// once per block.
+    ///
+    /// If you want to write the messages to a sequence, you should use
+    /// [`flush`](#method.flush) instead.
+    pub fn pop_due<'a>(
+        &'a mut self,
+        n_samples: u32,
+    ) -> impl Iterator<Item = TimedMidiMessage> + 'a {
+        let n_samples = n_samples as i64;
+        let n_due = self
+            .queue
+            .iter()
+            .take_while(|pending| pending.frames < n_samples)
+            .count();
+        for pending in self.queue.iter_mut().skip(n_due) {
+            pending.frames -= n_samples;
+        }
+        self.queue.drain(..n_due).map(|pending| TimedMidiMessage {
+            frames: pending.frames.max(0),
+            message: pending.message,
+        })
+    }
+
+    /// Write all messages that are due in a block of `n_samples` frames to a sequence.
+    ///
+    /// The sequence has to use frames as its time unit. Late messages are written at the beginning
+    /// of the block. Afterwards, all remaining messages are moved back in time by `n_samples`
+    /// frames, which means that this method should be called exactly once per block.
+    ///
+    /// If the sequence runs out of space, an `Err` is returned. The messages that could not be
+    /// written are kept and will be sent at the beginning of the next block.
+    pub fn flush<'a, W>(
+        &mut self,
+        writer: &mut W,
+        n_samples: u32,
+        urids: &mut CachedMap,
+    ) -> Result<(), ()>
+    where
+        W: SequenceWritingFrame<'a>,
+    {
+        let n_samples = n_samples as i64;
+        let mut result = Ok(());
+        while let Some(pending) = self.queue.front() {
+            if pending.frames >= n_samples {
+                break;
+            }
+            let time = TimeStamp::Frames(pending.frames.max(0));
+            if writer
+                .push_event::<RawMidiMessage>(time, &pending.message, urids)
+                .is_err()
+            {
+                result = Err(());
+                break;
+            }
+            self.queue.pop_front();
+        }
+        for pending in self.queue.iter_mut() {
+            pending.frames -= n_samples;
+        }
+        result
+    }
+}
+
+impl Default for MidiScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::message::*;
+    use crate::prelude::*;
+    use crate::scheduler::*;
+
+    fn note_on(note: u8) -> MidiMessage {
+        MidiMessage::NoteOn {
+            channel: u4::new(0),
+            note: u7::new(note),
+            velocity: u7::new(100),
+        }
+    }
+
+    #[test]
+    fn test_ordering() {
+        let mut scheduler = MidiScheduler::new();
+        scheduler.schedule(TimedMidiMessage {
+            frames: 20,
+            message: note_on(0),
+        });
+        scheduler.schedule(TimedMidiMessage {
+            frames: 10,
+            message: note_on(1),
+        });
+        scheduler.schedule(TimedMidiMessage {
+            frames: 20,
+            message: note_on(2),
+        });
+
+        let notes: Vec<MidiMessage> = scheduler.pending().map(|pending| pending.message).collect();
+        assert_eq!(vec![note_on(1), note_on(0), note_on(2)], notes);
+    }
+
+    #[test]
+    fn test_pop_due() {
+        let mut scheduler = MidiScheduler::new();
+        scheduler.schedule(TimedMidiMessage {
+            frames: -5,
+            message: note_on(0),
+        });
+        scheduler.schedule(TimedMidiMessage {
+            frames: 63,
+            message: note_on(1),
+        });
+        scheduler.schedule(TimedMidiMessage {
+            frames: 64,
+            message: note_on(2),
+        });
+
+        let due: Vec<TimedMidiMessage> = scheduler.pop_due(64).collect();
+        assert_eq!(2, due.len());
+        assert_eq!(0, due[0].frames);
+        assert_eq!(63, due[1].frames);
+
+        assert_eq!(1, scheduler.len());
+        assert_eq!(0, scheduler.pending().next().unwrap().frames);
+    }
+}
@@ -0,0 +1,347 @@
+//! MIDI Show Control commands.
+//!
+//! MIDI Show Control (MSC) is used to control lighting desks, sound playback, pyrotechnics and
+//! other theatrical equipment. Its commands are universal real-time system-exclusive messages,
+//! which address a device and a type of equipment, called the command format, and tell it to go
+//! to, stop or resume a cue, among other things.
+//!
+//! Just like the [universal messages](../universal/index.html), the types in this module work on
+//! the data bytes of system-exclusive messages, without the start and end status bytes. Parsing
+//! does not copy any data, which means that the cue numbers of a parsed message borrow from the
+//! system-exclusive message.
+//!
+//!     extern crate lv2rs_midi as midi;
+//!
+//!     use midi::prelude::*;
+//!     use midi::show_control::*;
+//!
+//!     // Tell all lighting desks to go to cue 12.5 of cue list 3.
+//!     let message = ShowControlMessage {
+//!         device_id: u7::new(ALL_CALL),
+//!         command_format: u7::new(LIGHTING_FORMAT),
+//!         command: ShowCommand::Go(Some(CueNumber::new("12.5", Some("3"), None).unwrap())),
+//!     };
+//!     let mut buffer = [0u8; 32];
+//!     let data = message.encode(&mut buffer).unwrap();
+//!     assert_eq!(b"\x7f\x7f\x02\x01\x0112.5\x003", data);
+//!
+//!     let parsed = ShowControlMessage::parse(data).unwrap();
+//!     if let ShowCommand::Go(Some(cue)) = parsed.command {
+//!         assert_eq!("12.5", cue.number());
+//!         assert_eq!(Some("3"), cue.list());
+//!     } else {
+//!         panic!("Unexpected command!");
+//!     }
+use crate::prelude::*;
+use crate::universal::{all_data_bytes, REALTIME_ID};
+
+pub use crate::universal::ALL_CALL;
+
+/// The sub-ID of MIDI Show Control messages.
+pub const SHOW_CONTROL_SUB_ID: u8 = 0x02;
+
+/// The command format of general lighting equipment.
+pub const LIGHTING_FORMAT: u8 = 0x01;
+/// The command format of general sound equipment.
+pub const SOUND_FORMAT: u8 = 0x10;
+/// The command format of general machinery.
+pub const MACHINERY_FORMAT: u8 = 0x20;
+/// The command format of general video equipment.
+pub const VIDEO_FORMAT: u8 = 0x30;
+/// The command format of general projection equipment.
+pub const PROJECTION_FORMAT: u8 = 0x40;
+/// The command format of general pyrotechnics.
+pub const PYRO_FORMAT: u8 = 0x60;
+/// The command format that addresses all types of equipment.
+pub const ALL_TYPES_FORMAT: u8 = 0x7f;
+
+const GO_COMMAND: u8 = 0x01;
+const STOP_COMMAND: u8 = 0x02;
+const RESUME_COMMAND: u8 = 0x03;
+const LOAD_COMMAND: u8 = 0x05;
+const FIRE_COMMAND: u8 = 0x07;
+const ALL_OFF_COMMAND: u8 = 0x08;
+const RESTORE_COMMAND: u8 = 0x09;
+const RESET_COMMAND: u8 = 0x0a;
+const GO_OFF_COMMAND: u8 = 0x0b;
+
+/// The number of a cue, optionally within a cue list and a cue path.
+///
+/// All parts are made of ASCII digits and decimal points, like `12.5`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CueNumber<'a> {
+    number: &'a str,
+    list: Option<&'a str>,
+    path: Option<&'a str>,
+}
+
+/// Check that the part of a cue number only contains digits and decimal points.
+fn is_cue_part(part: &[u8]) -> bool {
+    !part.is_empty()
+        && part
+            .iter()
+            .all(|byte| byte.is_ascii_digit() || *byte == b'.')
+}
+
+impl<'a> CueNumber<'a> {
+    /// Create a new cue number.
+    ///
+    /// `None` is returned if one of the parts is empty or contains something else than digits
+    /// and decimal points. A path can only be given together with a list.
+    pub fn new(number: &'a str, list: Option<&'a str>, path: Option<&'a str>) -> Option<Self> {
+        if !is_cue_part(number.as_bytes()) {
+            return None;
+        }
+        if let Some(list) = list {
+            if !is_cue_part(list.as_bytes()) {
+                return None;
+            }
+        }
+        if let Some(path) = path {
+            if list.is_none() || !is_cue_part(path.as_bytes()) {
+                return None;
+            }
+        }
+        Some(Self { number, list, path })
+    }
+
+    /// Return the number of the cue.
+    pub fn number(&self) -> &'a str {
+        self.number
+    }
+
+    /// Return the cue list, if present.
+    pub fn list(&self) -> Option<&'a str> {
+        self.list
+    }
+
+    /// Return the cue path, if present.
+    pub fn path(&self) -> Option<&'a str> {
+        self.path
+    }
+
+    /// Parse the parts of a cue number, which are separated by zero bytes.
+    fn parse(data: &'a [u8]) -> Result<Self, ()> {
+        let mut parts = data.split(|byte| *byte == 0).map(std::str::from_utf8);
+        let number = parts.next().ok_or(())?.map_err(|_| ())?;
+        let list = parts.next().transpose().map_err(|_| ())?;
+        let path = parts.next().transpose().map_err(|_| ())?;
+        if parts.next().is_some() {
+            return Err(());
+        }
+        Self::new(number, list, path).ok_or(())
+    }
+
+    /// Return the number of bytes the encoded cue number occupies.
+    fn encoded_len(&self) -> usize {
+        self.number.len()
+            + self.list.map(|list| list.len() + 1).unwrap_or(0)
+            + self.path.map(|path| path.len() + 1).unwrap_or(0)
+    }
+
+    /// Write the cue number to the beginning of the buffer.
+    fn encode(&self, buffer: &mut [u8]) {
+        let mut len = 0;
+        for (index, part) in [Some(self.number), self.list, self.path]
+            .iter()
+            .filter_map(|part| *part)
+            .enumerate()
+        {
+            if index > 0 {
+                buffer[len] = 0;
+                len += 1;
+            }
+            buffer[len..len + part.len()].copy_from_slice(part.as_bytes());
+            len += part.len();
+        }
+    }
+}
+
+/// A MIDI Show Control command.
+///
+/// Commands that take an optional cue number refer to the current cue if the number is omitted.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ShowCommand<'a> {
+    /// Start the transition to a cue.
+    Go(Option<CueNumber<'a>>),
+    /// Stop the transition to a cue.
+    Stop(Option<CueNumber<'a>>),
+    /// Resume a stopped transition.
+    Resume(Option<CueNumber<'a>>),
+    /// Prepare a cue, so that a following `Go` can start it immediately.
+    Load(CueNumber<'a>),
+    /// Trigger a macro.
+    Fire(u7),
+    /// Turn off all outputs, but remember their state.
+    AllOff,
+    /// Restore the state before the last `AllOff`.
+    Restore,
+    /// Stop all running cues and reset the equipment.
+    Reset,
+    /// Turn off a cue.
+    GoOff(Option<CueNumber<'a>>),
+}
+
+/// A MIDI Show Control message.
+///
+/// See the [module documentation](index.html) for more information.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ShowControlMessage<'a> {
+    /// The ID of the addressed device, or [`ALL_CALL`](constant.ALL_CALL.html).
+    pub device_id: u7,
+    /// The type of the addressed equipment, like [`LIGHTING_FORMAT`](constant.LIGHTING_FORMAT.html).
+    pub command_format: u7,
+    /// The command itself.
+    pub command: ShowCommand<'a>,
+}
+
+impl<'a> ShowControlMessage<'a> {
+    /// Try to parse the data of a system-exclusive message as a show control message.
+    ///
+    /// Commands that aren't supported by this module are rejected.
+    pub fn parse(data: &'a [u8]) -> Result<Self, ()> {
+        let (device_id, command_format, command, tail) = match data {
+            [REALTIME_ID, device_id, SHOW_CONTROL_SUB_ID, command_format, command, tail @ ..]
+                if all_data_bytes(&[*device_id, *command_format]) =>
+            {
+                (*device_id, *command_format, *command, tail)
+            }
+            _ => return Err(()),
+        };
+        if !all_data_bytes(tail) {
+            return Err(());
+        }
+
+        let optional_cue = |tail: &'a [u8]| {
+            if tail.is_empty() {
+                Ok(None)
+            } else {
+                CueNumber::parse(tail).map(Some)
+            }
+        };
+        let command = match (command, tail) {
+            (GO_COMMAND, _) => ShowCommand::Go(optional_cue(tail)?),
+            (STOP_COMMAND, _) => ShowCommand::Stop(optional_cue(tail)?),
+            (RESUME_COMMAND, _) => ShowCommand::Resume(optional_cue(tail)?),
+            (LOAD_COMMAND, _) => ShowCommand::Load(CueNumber::parse(tail)?),
+            (FIRE_COMMAND, [macro_number]) => ShowCommand::Fire(u7::new(*macro_number)),
+            (ALL_OFF_COMMAND, []) => ShowCommand::AllOff,
+            (RESTORE_COMMAND, []) => ShowCommand::Restore,
+            (RESET_COMMAND, []) => ShowCommand::Reset,
+            (GO_OFF_COMMAND, _) => ShowCommand::GoOff(optional_cue(tail)?),
+            _ => return Err(()),
+        };
+
+        Ok(Self {
+            device_id: u7::new(device_id),
+            command_format: u7::new(command_format),
+            command,
+        })
+    }
+
+    /// Return the number of bytes the encoded data occupies.
+    pub fn encoded_len(&self) -> usize {
+        let cue_len = |cue: &Option<CueNumber>| cue.map(|cue| cue.encoded_len()).unwrap_or(0);
+        5 + match &self.command {
+            ShowCommand::Go(cue)
+            | ShowCommand::Stop(cue)
+            | ShowCommand::Resume(cue)
+            | ShowCommand::GoOff(cue) => cue_len(cue),
+            ShowCommand::Load(cue) => cue.encoded_len(),
+            ShowCommand::Fire(_) => 1,
+            ShowCommand::AllOff | ShowCommand::Restore | ShowCommand::Reset => 0,
+        }
+    }
+
+    /// Write the data of the system-exclusive message to the buffer.
+    ///
+    /// The returned slice is the part of the buffer that contains the data. If the buffer is
+    /// shorter than [`encoded_len`](#method.encoded_len), `Err` is returned.
+    pub fn encode<'b>(&self, buffer: &'b mut [u8]) -> Result<&'b [u8], ()> {
+        let len = self.encoded_len();
+        if buffer.len() < len {
+            return Err(());
+        }
+        let (command, cue) = match &self.command {
+            ShowCommand::Go(cue) => (GO_COMMAND, *cue),
+            ShowCommand::Stop(cue) => (STOP_COMMAND, *cue),
+            ShowCommand::Resume(cue) => (RESUME_COMMAND, *cue),
+            ShowCommand::Load(cue) => (LOAD_COMMAND, Some(*cue)),
+            ShowCommand::Fire(macro_number) => {
+                buffer[5] = u8::from(*macro_number);
+                (FIRE_COMMAND, None)
+            }
+            ShowCommand::AllOff => (ALL_OFF_COMMAND, None),
+            ShowCommand::Restore => (RESTORE_COMMAND, None),
+            ShowCommand::Reset => (RESET_COMMAND, None),
+            ShowCommand::GoOff(cue) => (GO_OFF_COMMAND, *cue),
+        };
+        buffer[0] = REALTIME_ID;
+        buffer[1] = u8::from(self.device_id);
+        buffer[2] = SHOW_CONTROL_SUB_ID;
+        buffer[3] = u8::from(self.command_format);
+        buffer[4] = command;
+        if let Some(cue) = cue {
+            cue.encode(&mut buffer[5..]);
+        }
+        Ok(&buffer[..len])
+    }
+
+    /// Return `true` if a device with the given ID and command format should react to the
+    /// message.
+    pub fn addresses(&self, device_id: u7, command_format: u7) -> bool {
+        (u8::from(self.device_id) == ALL_CALL || self.device_id == device_id)
+            && (u8::from(self.command_format) == ALL_TYPES_FORMAT
+                || self.command_format == command_format)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::show_control::*;
+
+    #[test]
+    fn test_round_trip() {
+        let cue = CueNumber::new("1", Some("2"), Some("3.5")).unwrap();
+        let commands = [
+            ShowCommand::Go(None),
+            ShowCommand::Stop(Some(cue)),
+            ShowCommand::Resume(None),
+            ShowCommand::Load(cue),
+            ShowCommand::Fire(u7::new(12)),
+            ShowCommand::AllOff,
+            ShowCommand::Restore,
+            ShowCommand::Reset,
+            ShowCommand::GoOff(Some(cue)),
+        ];
+        for command in commands.iter() {
+            let message = ShowControlMessage {
+                device_id: u7::new(5),
+                command_format: u7::new(SOUND_FORMAT),
+                command: *command,
+            };
+            let mut buffer = [0u8; 32];
+            let data = message.encode(&mut buffer).unwrap();
+            assert_eq!(message.encoded_len(), data.len());
+            assert_eq!(message, ShowControlMessage::parse(data).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(CueNumber::new("1a", None, None).is_none());
+        assert!(CueNumber::new("1", None, Some("2")).is_none());
+        assert!(ShowControlMessage::parse(&[0x7f, 0x7f, 0x02, 0x01, 0x05]).is_err());
+        assert!(ShowControlMessage::parse(&[0x7f, 0x7f, 0x02, 0x01, 0x01, b'1', 0]).is_err());
+        assert!(ShowControlMessage::parse(&[0x7f, 0x7f, 0x02, 0x01, 0x08, 0]).is_err());
+
+        let message = ShowControlMessage {
+            device_id: u7::new(1),
+            command_format: u7::new(ALL_TYPES_FORMAT),
+            command: ShowCommand::Go(Some(CueNumber::new("100", None, None).unwrap())),
+        };
+        assert!(message.encode(&mut [0u8; 7]).is_err());
+        assert!(message.addresses(u7::new(1), u7::new(PYRO_FORMAT)));
+        assert!(!message.addresses(u7::new(2), u7::new(PYRO_FORMAT)));
+    }
+}
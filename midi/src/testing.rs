@@ -0,0 +1,169 @@
+//! Property-based test generators.
+//!
+//! This module is only available with the `testing` feature. It contains
+//! [`proptest`](https://docs.rs/proptest/) strategies for the integer types and the MIDI messages of
+//! this crate, as well as for their raw byte encodings, so that you can fuzz your MIDI handling
+//! code:
+//!
+//!     extern crate lv2rs_midi as midi;
+//!     #[macro_use]
+//!     extern crate proptest;
+//!
+//!     use midi::MidiMessage;
+//!
+//!     proptest! {
+//!         fn test_round_trip(message: MidiMessage) {
+//!             let (bytes, len) = message.to_bytes();
+//!             prop_assert_eq!(message, MidiMessage::try_from(&bytes[..len]).unwrap());
+//!         }
+//!     }
+//!     # fn main() { test_round_trip(); }
+//!
+//! Since the integer types are defined in the `ux` crate, they don't implement `Arbitrary`.
+//! Instead, there are strategy functions like [`any_u7`](fn.any_u7.html) for them.
+use crate::message::MidiMessage;
+use crate::prelude::*;
+use proptest::arbitrary::Arbitrary;
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::strategy::BoxedStrategy;
+
+/// Strategy for arbitrary three-bit numbers.
+pub fn any_u3() -> impl Strategy<Value = u3> {
+    (0u8..8).prop_map(u3::new)
+}
+
+/// Strategy for arbitrary four-bit numbers, like channels.
+pub fn any_u4() -> impl Strategy<Value = u4> {
+    (0u8..16).prop_map(u4::new)
+}
+
+/// Strategy for arbitrary seven-bit numbers, like notes or velocities.
+pub fn any_u7() -> impl Strategy<Value = u7> {
+    (0u8..128).prop_map(u7::new)
+}
+
+/// Strategy for arbitrary fourteen-bit numbers, like pitch bends.
+pub fn any_u14() -> impl Strategy<Value = u14> {
+    (0u16..16384).prop_map(u14::new)
+}
+
+impl Arbitrary for MidiMessage {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        prop_oneof![
+            (any_u4(), any_u7(), any_u7()).prop_map(|(channel, note, velocity)| {
+                MidiMessage::NoteOff {
+                    channel,
+                    note,
+                    velocity,
+                }
+            }),
+            (any_u4(), any_u7(), any_u7()).prop_map(|(channel, note, velocity)| {
+                MidiMessage::NoteOn {
+                    channel,
+                    note,
+                    velocity,
+                }
+            }),
+            (any_u4(), any_u7(), any_u7()).prop_map(|(channel, note, pressure)| {
+                MidiMessage::PolyKeyPressure {
+                    channel,
+                    note,
+                    pressure,
+                }
+            }),
+            (any_u4(), any_u7(), any_u7()).prop_map(|(channel, control_number, control_value)| {
+                MidiMessage::ControlChange {
+                    channel,
+                    control_number,
+                    control_value,
+                }
+            }),
+            (any_u4(), any_u7()).prop_map(|(channel, program_number)| {
+                MidiMessage::ProgramChange {
+                    channel,
+                    program_number,
+                }
+            }),
+            (any_u4(), any_u7())
+                .prop_map(|(channel, pressure)| MidiMessage::ChannelPressure { channel, pressure }),
+            (any_u4(), any_u14())
+                .prop_map(|(channel, value)| MidiMessage::PitchBendChange { channel, value }),
+            (any_u3(), any_u4()).prop_map(|(message_type, value)| {
+                MidiMessage::TimeCodeQuarterFrame {
+                    message_type,
+                    value,
+                }
+            }),
+            any_u14().prop_map(|position| MidiMessage::SongPositionPointer { position }),
+            any_u7().prop_map(|song| MidiMessage::SongSelect { song }),
+            Just(MidiMessage::TuneRequest),
+            Just(MidiMessage::TimingClock),
+            Just(MidiMessage::Start),
+            Just(MidiMessage::Continue),
+            Just(MidiMessage::Stop),
+            Just(MidiMessage::ActiveSensing),
+            Just(MidiMessage::SystemReset),
+        ]
+        .boxed()
+    }
+}
+
+/// Strategy for the raw bytes of valid messages.
+pub fn message_bytes() -> impl Strategy<Value = Vec<u8>> {
+    any::<MidiMessage>().prop_map(|message| {
+        let (bytes, len) = message.to_bytes();
+        bytes[..len].to_vec()
+    })
+}
+
+/// Strategy for the raw bytes of valid system-exclusive messages, including the start and end
+/// status bytes.
+pub fn system_exclusive_bytes(max_data_len: usize) -> impl Strategy<Value = Vec<u8>> {
+    vec(0u8..128, 0..=max_data_len).prop_map(|data| {
+        let mut bytes = Vec::with_capacity(data.len() + 2);
+        bytes.push(0xF0);
+        bytes.extend(data);
+        bytes.push(0xF7);
+        bytes
+    })
+}
+
+/// Strategy for arbitrary bytes of up to `max_len` bytes.
+///
+/// Most of these are not valid messages, which makes them useful to test the error handling of
+/// parsers.
+pub fn raw_bytes(max_len: usize) -> impl Strategy<Value = Vec<u8>> {
+    vec(any::<u8>(), 0..=max_len)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::testing::*;
+    use crate::validate::validate_message;
+
+    proptest! {
+        #[test]
+        fn test_round_trip(message: MidiMessage) {
+            let (bytes, len) = message.to_bytes();
+            prop_assert_eq!(message, MidiMessage::try_from(&bytes[..len]).unwrap());
+        }
+
+        #[test]
+        fn test_valid_bytes(bytes in message_bytes()) {
+            prop_assert!(validate_message(&bytes).is_ok());
+        }
+
+        #[test]
+        fn test_raw_bytes(bytes in raw_bytes(8)) {
+            // Parsing arbitrary bytes may fail, but must never panic, and everything that is
+            // accepted has to be valid.
+            if MidiMessage::try_from(&bytes).is_ok() {
+                prop_assert!(validate_message(&bytes).is_ok());
+            }
+        }
+    }
+}
@@ -0,0 +1,276 @@
+//! Universal system-exclusive messages.
+//!
+//! Universal system-exclusive messages are system-exclusive messages that aren't specific to a
+//! manufacturer, but are standardized by the MIDI specification. They start with either the
+//! non-real-time ID or the real-time ID, followed by the ID of the targeted device and two sub-IDs
+//! identifying the message.
+//!
+//! All types in this module work on the data bytes of system-exclusive messages, without the start
+//! and end status bytes. This means that you can parse a message by passing the result of
+//! [`SystemExclusiveMessage::get_data`](../struct.SystemExclusiveMessage.html#method.get_data)
+//! and write a message by passing the encoded data as the initialization parameter of a
+//! `SystemExclusiveMessage`:
+//!
+//!     extern crate lv2rs_midi as midi;
+//!
+//!     use midi::prelude::*;
+//!     use midi::universal::*;
+//!
+//!     // A plugin that emulates a piece of hardware.
+//!     let identity = IdentityReply {
+//!         device_id: u7::new(0),
+//!         manufacturer: ManufacturerId::Extended(u7::new(0x21), u7::new(0x42)),
+//!         family: u14::new(1),
+//!         model: u14::new(2),
+//!         version: [u7::new(1), u7::new(0), u7::new(0), u7::new(0)],
+//!     };
+//!
+//!     // The data of an incoming system-exclusive message.
+//!     let incoming: &[u8] = &[0x7e, 0x7f, 0x06, 0x01];
+//!     if let Ok(request) = IdentityRequest::parse(incoming) {
+//!         if request.addresses(identity.device_id) {
+//!             let mut buffer = [0u8; IdentityReply::MAX_DATA_LEN];
+//!             let reply: &[u8] = identity.encode(&mut buffer);
+//!             // Now, `reply` can be written to an output sequence.
+//!             assert_eq!(identity, IdentityReply::parse(reply).unwrap());
+//!         }
+//!     }
+use crate::prelude::*;
+
+/// The ID of universal non-real-time messages.
+pub const NON_REALTIME_ID: u8 = 0x7e;
+
+/// The ID of universal real-time messages.
+pub const REALTIME_ID: u8 = 0x7f;
+
+/// The device ID that addresses all devices.
+pub const ALL_CALL: u8 = 0x7f;
+
+/// The sub-ID of general information messages.
+pub const GENERAL_INFORMATION_SUB_ID: u8 = 0x06;
+
+/// The second sub-ID of identity requests.
+pub const IDENTITY_REQUEST_SUB_ID: u8 = 0x01;
+
+/// The second sub-ID of identity replies.
+pub const IDENTITY_REPLY_SUB_ID: u8 = 0x02;
+
+/// Split a 14-bit value into its least and most significant 7-bit halves.
+pub(crate) fn split_u14(value: u14) -> (u8, u8) {
+    let value: u16 = value.into();
+    ((value & 0x7f) as u8, (value >> 7) as u8)
+}
+
+/// Join the least and most significant 7-bit halves of a 14-bit value.
+pub(crate) fn join_u14(lsb: u8, msb: u8) -> u14 {
+    u14::new(((msb as u16 & 0x7f) << 7) | (lsb as u16 & 0x7f))
+}
+
+/// Check that none of the bytes is a status byte.
+pub(crate) fn all_data_bytes(data: &[u8]) -> bool {
+    data.iter().all(|byte| byte & 0b1000_0000 == 0)
+}
+
+/// The ID of a manufacturer.
+///
+/// Manufacturer IDs are either one byte long or, if the first byte is zero, three bytes long.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ManufacturerId {
+    /// A one-byte ID.
+    Short(u7),
+    /// A three-byte ID. The leading zero byte is omitted.
+    Extended(u7, u7),
+}
+
+impl ManufacturerId {
+    /// Try to parse a manufacturer ID from the beginning of the data.
+    ///
+    /// If successful, the ID and the number of bytes it occupies are returned.
+    pub fn parse(data: &[u8]) -> Result<(Self, usize), ()> {
+        match data {
+            [0, first, second, ..] if all_data_bytes(&[*first, *second]) => Ok((
+                ManufacturerId::Extended(u7::new(*first), u7::new(*second)),
+                3,
+            )),
+            [0, ..] => Err(()),
+            [id, ..] if all_data_bytes(&[*id]) => Ok((ManufacturerId::Short(u7::new(*id)), 1)),
+            _ => Err(()),
+        }
+    }
+
+    /// Return the number of bytes the ID occupies.
+    pub fn encoded_len(&self) -> usize {
+        match self {
+            ManufacturerId::Short(_) => 1,
+            ManufacturerId::Extended(_, _) => 3,
+        }
+    }
+
+    /// Write the ID to the beginning of the buffer and return the number of written bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer is shorter than [`encoded_len`](#method.encoded_len).
+    pub fn encode(&self, buffer: &mut [u8]) -> usize {
+        match self {
+            ManufacturerId::Short(id) => {
+                buffer[0] = u8::from(*id);
+            }
+            ManufacturerId::Extended(first, second) => {
+                buffer[0] = 0;
+                buffer[1] = u8::from(*first);
+                buffer[2] = u8::from(*second);
+            }
+        }
+        self.encoded_len()
+    }
+}
+
+/// A request for the identity of a device.
+///
+/// See the [module documentation](index.html) for more information.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct IdentityRequest {
+    /// The ID of the requested device, or [`ALL_CALL`](constant.ALL_CALL.html).
+    pub device_id: u7,
+}
+
+impl IdentityRequest {
+    /// Try to parse the data of a system-exclusive message as an identity request.
+    pub fn parse(data: &[u8]) -> Result<Self, ()> {
+        match data {
+            [NON_REALTIME_ID, device_id, GENERAL_INFORMATION_SUB_ID, IDENTITY_REQUEST_SUB_ID]
+                if all_data_bytes(&[*device_id]) =>
+            {
+                Ok(Self {
+                    device_id: u7::new(*device_id),
+                })
+            }
+            _ => Err(()),
+        }
+    }
+
+    /// Return the data of the system-exclusive message.
+    pub fn encode(&self) -> [u8; 4] {
+        [
+            NON_REALTIME_ID,
+            u8::from(self.device_id),
+            GENERAL_INFORMATION_SUB_ID,
+            IDENTITY_REQUEST_SUB_ID,
+        ]
+    }
+
+    /// Return `true` if a device with the given ID should answer the request.
+    pub fn addresses(&self, device_id: u7) -> bool {
+        u8::from(self.device_id) == ALL_CALL || self.device_id == device_id
+    }
+}
+
+/// The reply to an [`IdentityRequest`](struct.IdentityRequest.html).
+///
+/// See the [module documentation](index.html) for more information.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct IdentityReply {
+    /// The ID of the replying device.
+    pub device_id: u7,
+    /// The manufacturer of the device.
+    pub manufacturer: ManufacturerId,
+    /// The family code of the device.
+    pub family: u14,
+    /// The model number of the device, within its family.
+    pub model: u14,
+    /// The firmware version of the device.
+    pub version: [u7; 4],
+}
+
+impl IdentityReply {
+    /// The maximal length of the encoded data.
+    pub const MAX_DATA_LEN: usize = 15;
+
+    /// Try to parse the data of a system-exclusive message as an identity reply.
+    pub fn parse(data: &[u8]) -> Result<Self, ()> {
+        if !all_data_bytes(data) {
+            return Err(());
+        }
+        let device_id = match data {
+            [NON_REALTIME_ID, device_id, GENERAL_INFORMATION_SUB_ID, IDENTITY_REPLY_SUB_ID, ..] => {
+                u7::new(*device_id)
+            }
+            _ => return Err(()),
+        };
+        let (manufacturer, manufacturer_len) = ManufacturerId::parse(&data[4..])?;
+        let body = &data[4 + manufacturer_len..];
+        if body.len() != 8 {
+            return Err(());
+        }
+        Ok(Self {
+            device_id,
+            manufacturer,
+            family: join_u14(body[0], body[1]),
+            model: join_u14(body[2], body[3]),
+            version: [
+                u7::new(body[4]),
+                u7::new(body[5]),
+                u7::new(body[6]),
+                u7::new(body[7]),
+            ],
+        })
+    }
+
+    /// Write the data of the system-exclusive message to the buffer.
+    ///
+    /// The returned slice is the part of the buffer that contains the data.
+    pub fn encode<'a>(&self, buffer: &'a mut [u8; Self::MAX_DATA_LEN]) -> &'a [u8] {
+        buffer[0] = NON_REALTIME_ID;
+        buffer[1] = u8::from(self.device_id);
+        buffer[2] = GENERAL_INFORMATION_SUB_ID;
+        buffer[3] = IDENTITY_REPLY_SUB_ID;
+        let mut len = 4 + self.manufacturer.encode(&mut buffer[4..]);
+        let (family_lsb, family_msb) = split_u14(self.family);
+        let (model_lsb, model_msb) = split_u14(self.model);
+        for byte in [family_lsb, family_msb, model_lsb, model_msb].iter() {
+            buffer[len] = *byte;
+            len += 1;
+        }
+        for byte in self.version.iter() {
+            buffer[len] = u8::from(*byte);
+            len += 1;
+        }
+        &buffer[..len]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::universal::*;
+
+    #[test]
+    fn test_identity_request() {
+        let request = IdentityRequest {
+            device_id: u7::new(ALL_CALL),
+        };
+        assert_eq!([0x7e, 0x7f, 0x06, 0x01], request.encode());
+        assert_eq!(request, IdentityRequest::parse(&request.encode()).unwrap());
+        assert!(request.addresses(u7::new(3)));
+        assert!(IdentityRequest::parse(&[0x7e, 0x7f, 0x06, 0x02]).is_err());
+    }
+
+    #[test]
+    fn test_identity_reply() {
+        let reply = IdentityReply {
+            device_id: u7::new(1),
+            manufacturer: ManufacturerId::Short(u7::new(0x41)),
+            family: u14::new(0x1234),
+            model: u14::new(7),
+            version: [u7::new(1), u7::new(2), u7::new(3), u7::new(4)],
+        };
+        let mut buffer = [0u8; IdentityReply::MAX_DATA_LEN];
+        let data = reply.encode(&mut buffer);
+        assert_eq!(
+            [0x7e, 0x01, 0x06, 0x02, 0x41, 0x34, 0x24, 0x07, 0x00, 1, 2, 3, 4],
+            data
+        );
+        assert_eq!(reply, IdentityReply::parse(data).unwrap());
+        assert!(IdentityReply::parse(&data[..12]).is_err());
+    }
+}
@@ -1,43 +1,95 @@
 //! URIs of the LV2 MIDI extension.
-pub const URI: &[u8] = b"http://lv2plug.in/ns/ext/midi\0";
-pub const ACTIVE_SENSE_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#ActiveSense\0";
-pub const AFTERTOUCH_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#Aftertouch\0";
-pub const BENDER_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#Bender\0";
-pub const CHANNEL_PRESSURE_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#ChannelPressure\0";
-pub const CHUNK_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#Chunk\0";
-pub const CLOCK_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#Clock\0";
-pub const CONTINUE_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#Continue\0";
-pub const CONTROLLER_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#Controller\0";
-pub const EVENT_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#MidiEvent\0";
-pub const NOTE_OFF_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#NoteOff\0";
-pub const NOTE_ON_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#NoteOn\0";
-pub const PROGRAM_CHANGE_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#ProgramChange\0";
-pub const QUARTER_FRAME_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#QuarterFrame\0";
-pub const RESET_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#Reset\0";
-pub const SONG_POSITION_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#SongPosition\0";
-pub const SONG_SELECT_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#SongSelect\0";
-pub const START_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#Start\0";
-pub const STOP_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#Stop\0";
-pub const SYSTEM_COMMON_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#SystemCommon\0";
-pub const SYSTEM_EXCLUSIVE_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#SystemExclusive\0";
-pub const SYSTEM_MESSAGE_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#SystemMessage\0";
-pub const SYSTEM_REALTIME_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#SystemRealtime\0";
-pub const TICK_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#Tick\0";
-pub const TUNE_REQUEST_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#TuneRequest\0";
-pub const VOICE_MESSAGE_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#VoiceMessage\0";
-pub const BENDER_VALUE_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#benderValue\0";
-pub const BINDING_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#binding\0";
-pub const BYTE_NUMBER_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#byteNumber\0";
-pub const CHANNEL_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#channel\0";
-pub const LOWER_CHUNK_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#chunk\0";
-pub const CONTROLLER_NUMBER_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#controllerNumber\0";
-pub const CONTROLLER_VALUE_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#controllerValue\0";
-pub const NOTE_NUMBER_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#noteNumber\0";
-pub const PRESSURE_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#pressure\0";
-pub const PROGRAM_NUMBER_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#programNumber\0";
-pub const PROPERTY_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#property\0";
-pub const SONG_NUMBER_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#songNumber\0";
-pub const LOWER_SONG_POSITION_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#songPosition\0";
-pub const STATUS_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#status\0";
-pub const STATUS_MASK_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#statusMask\0";
-pub const VELOCITY_URI: &[u8] = b"http://lv2plug.in/ns/ext/midi#velocity\0";
+use lv2rs_core::uris::Uri;
+
+pub const URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi\0");
+pub const ACTIVE_SENSE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#ActiveSense\0");
+pub const AFTERTOUCH_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#Aftertouch\0");
+pub const BENDER_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#Bender\0");
+pub const CHANNEL_PRESSURE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#ChannelPressure\0");
+pub const CHUNK_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#Chunk\0");
+pub const CLOCK_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#Clock\0");
+pub const CONTINUE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#Continue\0");
+pub const CONTROLLER_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#Controller\0");
+pub const EVENT_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#MidiEvent\0");
+pub const NOTE_OFF_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#NoteOff\0");
+pub const NOTE_ON_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#NoteOn\0");
+pub const PROGRAM_CHANGE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#ProgramChange\0");
+pub const QUARTER_FRAME_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#QuarterFrame\0");
+pub const RESET_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#Reset\0");
+pub const SONG_POSITION_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#SongPosition\0");
+pub const SONG_SELECT_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#SongSelect\0");
+pub const START_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#Start\0");
+pub const STOP_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#Stop\0");
+pub const SYSTEM_COMMON_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#SystemCommon\0");
+pub const SYSTEM_EXCLUSIVE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#SystemExclusive\0");
+pub const SYSTEM_MESSAGE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#SystemMessage\0");
+pub const SYSTEM_REALTIME_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#SystemRealtime\0");
+pub const TICK_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#Tick\0");
+pub const TUNE_REQUEST_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#TuneRequest\0");
+pub const VOICE_MESSAGE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#VoiceMessage\0");
+pub const BENDER_VALUE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#benderValue\0");
+pub const BINDING_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#binding\0");
+pub const BYTE_NUMBER_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#byteNumber\0");
+pub const CHANNEL_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#channel\0");
+pub const LOWER_CHUNK_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#chunk\0");
+pub const CONTROLLER_NUMBER_URI: Uri =
+    Uri::new(b"http://lv2plug.in/ns/ext/midi#controllerNumber\0");
+pub const CONTROLLER_VALUE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#controllerValue\0");
+pub const NOTE_NUMBER_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#noteNumber\0");
+pub const PRESSURE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#pressure\0");
+pub const PROGRAM_NUMBER_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#programNumber\0");
+pub const PROPERTY_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#property\0");
+pub const SONG_NUMBER_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#songNumber\0");
+pub const LOWER_SONG_POSITION_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#songPosition\0");
+pub const STATUS_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#status\0");
+pub const STATUS_MASK_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#statusMask\0");
+pub const VELOCITY_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/midi#velocity\0");
+
+lv2rs_urid::urid_cache! {
+    /// The URIDs of the MIDI vocabulary.
+    ///
+    /// Map them with `MidiURIDs::new(&mut cached_map)` in `instantiate`.
+    pub struct MidiURIDs {
+        pub active_sense: ACTIVE_SENSE_URI,
+        pub aftertouch: AFTERTOUCH_URI,
+        pub bender: BENDER_URI,
+        pub channel_pressure: CHANNEL_PRESSURE_URI,
+        pub chunk: CHUNK_URI,
+        pub clock: CLOCK_URI,
+        pub r#continue: CONTINUE_URI,
+        pub controller: CONTROLLER_URI,
+        pub event: EVENT_URI,
+        pub note_off: NOTE_OFF_URI,
+        pub note_on: NOTE_ON_URI,
+        pub program_change: PROGRAM_CHANGE_URI,
+        pub quarter_frame: QUARTER_FRAME_URI,
+        pub reset: RESET_URI,
+        pub song_position: SONG_POSITION_URI,
+        pub song_select: SONG_SELECT_URI,
+        pub start: START_URI,
+        pub stop: STOP_URI,
+        pub system_common: SYSTEM_COMMON_URI,
+        pub system_exclusive: SYSTEM_EXCLUSIVE_URI,
+        pub system_message: SYSTEM_MESSAGE_URI,
+        pub system_realtime: SYSTEM_REALTIME_URI,
+        pub tick: TICK_URI,
+        pub tune_request: TUNE_REQUEST_URI,
+        pub voice_message: VOICE_MESSAGE_URI,
+        pub bender_value: BENDER_VALUE_URI,
+        pub binding: BINDING_URI,
+        pub byte_number: BYTE_NUMBER_URI,
+        pub channel: CHANNEL_URI,
+        pub lower_chunk: LOWER_CHUNK_URI,
+        pub controller_number: CONTROLLER_NUMBER_URI,
+        pub controller_value: CONTROLLER_VALUE_URI,
+        pub note_number: NOTE_NUMBER_URI,
+        pub pressure: PRESSURE_URI,
+        pub program_number: PROGRAM_NUMBER_URI,
+        pub property: PROPERTY_URI,
+        pub song_number: SONG_NUMBER_URI,
+        pub lower_song_position: LOWER_SONG_POSITION_URI,
+        pub status: STATUS_URI,
+        pub status_mask: STATUS_MASK_URI,
+        pub velocity: VELOCITY_URI,
+    }
+}
@@ -0,0 +1,294 @@
+//! Strict validation of MIDI events.
+//!
+//! The readers of this crate silently ignore MIDI events they can not interpret. This is the right
+//! thing to do in a plugin, but hosts and test harnesses usually want to know exactly what is
+//! wrong with the events a plugin or a device has produced. The functions in this module check
+//! MIDI events against the rules of the LV2 MIDI specification:
+//!
+//! * Every event contains exactly one message, starting with a status byte.
+//! * The length of the message matches its status byte.
+//! * There are no status bytes within the message, except for the terminating byte of a
+//!   system-exclusive message.
+//!
+//! An example:
+//!
+//!     extern crate lv2rs_midi as midi;
+//!
+//!     use midi::validate::{validate_message, Violation};
+//!
+//!     assert!(validate_message(&[0x90, 60, 100]).is_ok());
+//!     assert_eq!(
+//!         Err(Violation::WrongLength { expected: 3, found: 2 }),
+//!         validate_message(&[0x90, 60])
+//!     );
+//!     assert_eq!(
+//!         Err(Violation::InteriorStatusByte { position: 2 }),
+//!         validate_message(&[0xF0, 1, 0xF8, 2, 0xF7])
+//!     );
+use crate::atom::RawMidiMessage;
+use crate::status_bytes::*;
+use lv2rs_atom::prelude::*;
+use lv2rs_urid::CachedMap;
+use std::fmt;
+
+/// A violation of the MIDI specification.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Violation {
+    /// The event doesn't contain any bytes.
+    Empty,
+    /// The first byte of the event is not a status byte.
+    NoStatusByte,
+    /// The status byte is undefined or may not start a message.
+    UnknownStatus(u8),
+    /// The length of the message doesn't match its status byte.
+    WrongLength { expected: usize, found: usize },
+    /// There is a status byte at the given position within the message.
+    InteriorStatusByte { position: usize },
+    /// The system-exclusive message is not terminated by an end-of-exclusive byte.
+    UnterminatedSystemExclusive,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Violation::Empty => write!(f, "empty event"),
+            Violation::NoStatusByte => write!(f, "missing status byte"),
+            Violation::UnknownStatus(status) => write!(f, "unknown status byte {:#04X}", status),
+            Violation::WrongLength { expected, found } => write!(
+                f,
+                "wrong length: expected {} bytes, found {}",
+                expected, found
+            ),
+            Violation::InteriorStatusByte { position } => {
+                write!(f, "interior status byte at position {}", position)
+            }
+            Violation::UnterminatedSystemExclusive => {
+                write!(f, "unterminated system-exclusive message")
+            }
+        }
+    }
+}
+
+/// A violation of an event in a sequence.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct EventViolation {
+    /// The index of the event in the sequence, counting all events, not only MIDI events.
+    pub index: usize,
+    /// What is wrong with the event.
+    pub violation: Violation,
+}
+
+/// The result of validating a sequence.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct ValidationReport {
+    /// The number of events in the sequence.
+    pub n_events: usize,
+    /// The number of MIDI events in the sequence.
+    pub n_midi_events: usize,
+    /// All violations, in the order of the events.
+    pub violations: Vec<EventViolation>,
+}
+
+impl ValidationReport {
+    /// Return `true` if no violations were found.
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    /// Write one line per violation, or a single line if the sequence is valid.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_valid() {
+            return writeln!(f, "{} MIDI events, no violations", self.n_midi_events);
+        }
+        for violation in self.violations.iter() {
+            writeln!(f, "event {}: {}", violation.index, violation.violation)?;
+        }
+        Ok(())
+    }
+}
+
+/// Return the length of a message with the given status byte, including the status byte.
+///
+/// `None` is returned for system-exclusive messages, which don't have a fixed length, and for
+/// bytes that may not start a message.
+pub fn expected_length(status: u8) -> Option<usize> {
+    if status & 0b1000_0000 == 0 {
+        return None;
+    }
+    match status & 0b1111_0000 {
+        NOTE_OFF_STATUS
+        | NOTE_ON_STATUS
+        | POLY_KEY_PRESSURE_STATUS
+        | CONTROL_CHANGE_STATUS
+        | PITCH_BEND_CHANGE_STATUS => return Some(3),
+        PROGRAM_CHANGE_STATUS | CHANNEL_PRESSURE_STATUS => return Some(2),
+        _ => (),
+    }
+    match status {
+        SONG_POSITION_POINTER_STATUS => Some(3),
+        TIME_CODE_QUARTER_FRAME_STATUS | SONG_SELECT_STATUS => Some(2),
+        TUNE_REQUEST_STATUS
+        | TIMING_CLOCK_STATUS
+        | START_STATUS
+        | CONTINUE_STATUS
+        | STOP_STATUS
+        | ACTIVE_SENSING_STATUS
+        | SYSTEM_RESET_STATUS => Some(1),
+        _ => None,
+    }
+}
+
+/// Check the bytes of a single MIDI event.
+///
+/// Only the first violation is returned.
+pub fn validate_message(bytes: &[u8]) -> Result<(), Violation> {
+    let status = *bytes.first().ok_or(Violation::Empty)?;
+    if status & 0b1000_0000 == 0 {
+        return Err(Violation::NoStatusByte);
+    }
+
+    let data_end = if status == START_OF_SYSTEM_EXCLUSIVE_STATUS {
+        if bytes.len() < 2 || *bytes.last().unwrap() != END_OF_SYSTEM_EXCLUSICE_STATUS {
+            // Check all bytes for interior status bytes first, since they are the more specific
+            // violation.
+            bytes.len()
+        } else {
+            bytes.len() - 1
+        }
+    } else {
+        let expected = expected_length(status).ok_or(Violation::UnknownStatus(status))?;
+        if bytes.len() != expected {
+            return Err(Violation::WrongLength {
+                expected,
+                found: bytes.len(),
+            });
+        }
+        bytes.len()
+    };
+
+    if let Some(position) = bytes[1..data_end]
+        .iter()
+        .position(|byte| byte & 0b1000_0000 != 0)
+    {
+        return Err(Violation::InteriorStatusByte {
+            position: position + 1,
+        });
+    }
+
+    if status == START_OF_SYSTEM_EXCLUSIVE_STATUS && data_end == bytes.len() {
+        return Err(Violation::UnterminatedSystemExclusive);
+    }
+    Ok(())
+}
+
+/// Check every MIDI event of a sequence.
+///
+/// Events that aren't MIDI events are counted, but not checked.
+pub fn validate_sequence(sequence: &Sequence, urids: &mut CachedMap) -> ValidationReport {
+    let midi_urid = urids.map(<RawMidiMessage as AtomBody>::get_uri());
+    let mut report = ValidationReport::default();
+    for (index, (_, atom)) in sequence.iter(urids).enumerate() {
+        report.n_events += 1;
+        if atom.atom_type() != midi_urid {
+            continue;
+        }
+        report.n_midi_events += 1;
+        if let Err(violation) = validate_message(atom.get_raw_body()) {
+            report.violations.push(EventViolation { index, violation });
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod test {
+    use crate::atom::SystemExclusiveMessage;
+    use crate::message::*;
+    use crate::prelude::*;
+    use crate::validate::*;
+    use lv2rs_atom::ports::*;
+    use lv2rs_atom::sequence::{TimeStamp, TimeUnit};
+    use lv2rs_urid::debug::DebugMap;
+
+    #[test]
+    fn test_validate_message() {
+        assert_eq!(Err(Violation::Empty), validate_message(&[]));
+        assert_eq!(Err(Violation::NoStatusByte), validate_message(&[60, 100]));
+        assert_eq!(
+            Err(Violation::UnknownStatus(0xF4)),
+            validate_message(&[0xF4])
+        );
+        assert_eq!(
+            Err(Violation::WrongLength {
+                expected: 1,
+                found: 2
+            }),
+            validate_message(&[0xF8, 0])
+        );
+        assert_eq!(
+            Err(Violation::InteriorStatusByte { position: 1 }),
+            validate_message(&[0xB0, 0x80, 0])
+        );
+        assert_eq!(
+            Err(Violation::UnterminatedSystemExclusive),
+            validate_message(&[0xF0, 1, 2])
+        );
+        assert!(validate_message(&[0xC3, 5]).is_ok());
+        assert!(validate_message(&[0xF0, 0xF7]).is_ok());
+    }
+
+    #[test]
+    fn test_validate_sequence() {
+        let mut debug_map = DebugMap::new();
+        let mut urids = unsafe { debug_map.create_cached_map() };
+
+        // Creating the atom space and the ports.
+        let mut space = vec![0u64; 16];
+        let atom = unsafe { (space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+        *(atom.mut_size()) = 128 - 8;
+        let mut out_port: AtomOutputPort<Sequence> = AtomOutputPort::new();
+        out_port.connect_port(atom);
+        let mut in_port: AtomInputPort<Sequence> = AtomInputPort::new();
+        in_port.connect_port(atom as &Atom);
+
+        // Writing a valid MIDI event, a non-MIDI event and a malformed MIDI event.
+        {
+            let mut frame =
+                unsafe { out_port.write_atom_body(&TimeUnit::Frames, &mut urids) }.unwrap();
+            let note_on = MidiMessage::NoteOn {
+                channel: u4::new(0),
+                note: u7::new(60),
+                velocity: u7::new(100),
+            };
+            frame
+                .push_event::<RawMidiMessage>(TimeStamp::Frames(0), &note_on, &mut urids)
+                .unwrap();
+            frame
+                .push_event::<i32>(TimeStamp::Frames(1), &42, &mut urids)
+                .unwrap();
+            frame
+                .push_event::<SystemExclusiveMessage>(
+                    TimeStamp::Frames(2),
+                    &[1, 0xF8, 2],
+                    &mut urids,
+                )
+                .unwrap();
+        }
+
+        let context = lv2rs_core::RunContext::new(3, 0, 44100.0, None);
+        let sequence = unsafe { in_port.read(&context, &mut urids) }.unwrap();
+        let report = validate_sequence(sequence, &mut urids);
+        assert_eq!(3, report.n_events);
+        assert_eq!(2, report.n_midi_events);
+        assert!(!report.is_valid());
+        assert_eq!(
+            vec![EventViolation {
+                index: 2,
+                violation: Violation::InteriorStatusByte { position: 2 },
+            }],
+            report.violations
+        );
+    }
+}
@@ -0,0 +1,278 @@
+//! Keyboard splits and layers.
+//!
+//! A split plugin plays different sounds on different parts of the keyboard, and a layer plugin
+//! plays several sounds with every key. Both can be described with a list of
+//! [`KeyZone`s](struct.KeyZone.html): Every zone covers a range of notes and sends these notes,
+//! transposed and on its own channel, to one of the plugin's outputs. Zones may overlap, in which
+//! case a note is sent to every zone covering it.
+//!
+//! The [`ZoneRouter`](struct.ZoneRouter.html) does the actual routing:
+//!
+//!     extern crate lv2rs_midi as midi;
+//!
+//!     use midi::prelude::*;
+//!     use midi::zone::{KeyZone, ZoneRouter};
+//!     use midi::MidiMessage;
+//!
+//!     // A bass on output 0 below C4 and a piano, one octave up, on output 1 from C4 upwards.
+//!     let router = ZoneRouter::new(vec![
+//!         KeyZone::new(u7::new(0), u7::new(59), u4::new(0), 0, 0),
+//!         KeyZone::new(u7::new(60), u7::new(127), u4::new(1), 12, 1),
+//!     ]);
+//!
+//!     let mut output = Vec::new();
+//!     let note = MidiMessage::NoteOn {
+//!         channel: u4::new(0),
+//!         note: u7::new(64),
+//!         velocity: u7::new(100),
+//!     };
+//!     router.route(note, |index, message| output.push((index, message)));
+//!
+//!     let expected = MidiMessage::NoteOn {
+//!         channel: u4::new(1),
+//!         note: u7::new(76),
+//!         velocity: u7::new(100),
+//!     };
+//!     assert_eq!(vec![(1, expected)], output);
+use crate::message::MidiMessage;
+use crate::prelude::*;
+
+/// A range of notes that is routed to an output.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct KeyZone {
+    /// The lowest note of the zone, inclusive.
+    pub low: u7,
+    /// The highest note of the zone, inclusive.
+    pub high: u7,
+    /// The channel the messages of the zone are sent on.
+    pub channel: u4,
+    /// The number of semitones the notes of the zone are transposed by.
+    pub transpose: i8,
+    /// The index of the output the messages of the zone are sent to.
+    pub output: usize,
+}
+
+impl KeyZone {
+    /// Create a new zone.
+    pub fn new(low: u7, high: u7, channel: u4, transpose: i8, output: usize) -> Self {
+        Self {
+            low,
+            high,
+            channel,
+            transpose,
+            output,
+        }
+    }
+
+    /// Return `true` if the note lies within the zone.
+    pub fn contains(&self, note: u7) -> bool {
+        self.low <= note && note <= self.high
+    }
+
+    /// Return the transposed note, or `None` if it would be out of the MIDI note range.
+    pub fn transposed(&self, note: u7) -> Option<u7> {
+        let note = u8::from(note) as i16 + self.transpose as i16;
+        if (0..128).contains(&note) {
+            Some(u7::new(note as u8))
+        } else {
+            None
+        }
+    }
+}
+
+/// Router that distributes messages over a list of key zones.
+///
+/// Note ons, note offs and key pressures are sent to every zone containing the note, with their
+/// note transposed and their channel replaced by the channel of the zone. Notes that would be
+/// transposed out of the MIDI note range are dropped.
+///
+/// All other channel voice messages, like controllers or the pitch bend, are sent to every zone
+/// with the channel replaced. System messages are sent once to every output that is used by a
+/// zone.
+///
+/// See the [module documentation](index.html) for more information.
+pub struct ZoneRouter {
+    zones: Vec<KeyZone>,
+}
+
+impl ZoneRouter {
+    /// Create a new router for the given zones.
+    pub fn new(zones: Vec<KeyZone>) -> Self {
+        Self { zones }
+    }
+
+    /// Return the zones of the router.
+    pub fn zones(&self) -> &[KeyZone] {
+        &self.zones
+    }
+
+    /// Route a message.
+    ///
+    /// Every routed message is passed to `emit`, together with the index of the output it should
+    /// be sent to. The messages are emitted in the order of the zones.
+    pub fn route<F: FnMut(usize, MidiMessage)>(&self, message: MidiMessage, mut emit: F) {
+        match message {
+            MidiMessage::NoteOn { note, .. }
+            | MidiMessage::NoteOff { note, .. }
+            | MidiMessage::PolyKeyPressure { note, .. } => {
+                for zone in self.zones.iter().filter(|zone| zone.contains(note)) {
+                    if let Some(transposed) = zone.transposed(note) {
+                        let message = with_channel(message, zone.channel).unwrap();
+                        emit(zone.output, with_note(message, transposed));
+                    }
+                }
+            }
+            _ => {
+                if let Some(message) = with_channel(message, u4::new(0)) {
+                    for zone in self.zones.iter() {
+                        emit(zone.output, with_channel(message, zone.channel).unwrap());
+                    }
+                } else {
+                    for (index, zone) in self.zones.iter().enumerate() {
+                        let first_use = self.zones[..index]
+                            .iter()
+                            .all(|previous| previous.output != zone.output);
+                        if first_use {
+                            emit(zone.output, message);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Replace the channel of a channel voice message.
+///
+/// Returns `None` if the message is not a channel voice message.
+fn with_channel(message: MidiMessage, channel: u4) -> Option<MidiMessage> {
+    match message {
+        MidiMessage::NoteOff { note, velocity, .. } => Some(MidiMessage::NoteOff {
+            channel,
+            note,
+            velocity,
+        }),
+        MidiMessage::NoteOn { note, velocity, .. } => Some(MidiMessage::NoteOn {
+            channel,
+            note,
+            velocity,
+        }),
+        MidiMessage::PolyKeyPressure { note, pressure, .. } => Some(MidiMessage::PolyKeyPressure {
+            channel,
+            note,
+            pressure,
+        }),
+        MidiMessage::ControlChange {
+            control_number,
+            control_value,
+            ..
+        } => Some(MidiMessage::ControlChange {
+            channel,
+            control_number,
+            control_value,
+        }),
+        MidiMessage::ProgramChange { program_number, .. } => Some(MidiMessage::ProgramChange {
+            channel,
+            program_number,
+        }),
+        MidiMessage::ChannelPressure { pressure, .. } => {
+            Some(MidiMessage::ChannelPressure { channel, pressure })
+        }
+        MidiMessage::PitchBendChange { value, .. } => {
+            Some(MidiMessage::PitchBendChange { channel, value })
+        }
+        _ => None,
+    }
+}
+
+/// Replace the note of a note on, note off or key pressure message.
+fn with_note(message: MidiMessage, note: u7) -> MidiMessage {
+    match message {
+        MidiMessage::NoteOff {
+            channel, velocity, ..
+        } => MidiMessage::NoteOff {
+            channel,
+            note,
+            velocity,
+        },
+        MidiMessage::NoteOn {
+            channel, velocity, ..
+        } => MidiMessage::NoteOn {
+            channel,
+            note,
+            velocity,
+        },
+        MidiMessage::PolyKeyPressure {
+            channel, pressure, ..
+        } => MidiMessage::PolyKeyPressure {
+            channel,
+            note,
+            pressure,
+        },
+        message => message,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::zone::*;
+
+    fn route(router: &ZoneRouter, message: MidiMessage) -> Vec<(usize, MidiMessage)> {
+        let mut output = Vec::new();
+        router.route(message, |index, message| output.push((index, message)));
+        output
+    }
+
+    #[test]
+    fn test_layers() {
+        let router = ZoneRouter::new(vec![
+            KeyZone::new(u7::new(0), u7::new(127), u4::new(0), 0, 0),
+            KeyZone::new(u7::new(60), u7::new(127), u4::new(1), 120, 0),
+            KeyZone::new(u7::new(0), u7::new(63), u4::new(2), -12, 1),
+        ]);
+
+        let output = route(
+            &router,
+            MidiMessage::NoteOff {
+                channel: u4::new(5),
+                note: u7::new(62),
+                velocity: u7::new(0),
+            },
+        );
+        // The second zone would transpose the note out of range.
+        assert_eq!(2, output.len());
+        assert_eq!(
+            (
+                1,
+                MidiMessage::NoteOff {
+                    channel: u4::new(2),
+                    note: u7::new(50),
+                    velocity: u7::new(0),
+                }
+            ),
+            output[1]
+        );
+
+        let bend = MidiMessage::PitchBendChange {
+            channel: u4::new(5),
+            value: u14::new(0),
+        };
+        let channels: Vec<(usize, MidiMessage)> = route(&router, bend);
+        assert_eq!(3, channels.len());
+        assert_eq!(
+            (
+                0,
+                MidiMessage::PitchBendChange {
+                    channel: u4::new(1),
+                    value: u14::new(0),
+                }
+            ),
+            channels[1]
+        );
+
+        assert_eq!(
+            vec![(0, MidiMessage::Start), (1, MidiMessage::Start)],
+            route(&router, MidiMessage::Start)
+        );
+    }
+}
@@ -0,0 +1,98 @@
+//! The trait of plugins with morph ports and the handling of the `currentType` option.
+use crate::port::MorphType;
+use crate::uris::MorphURIDs;
+use options::{OptionContext, OptionData, OptionValue, OptionsErr, OptionsOption};
+use urid::URID;
+
+/// A plugin with morph ports.
+///
+/// The host changes the type of a port by setting the `morph:currentType` option of the port via
+/// the options interface. The plugin delegates its options to [`set_option`](fn.set_option.html)
+/// and [`get_option`](fn.get_option.html), which check the options and call the methods of this
+/// trait with the decoded port types.
+pub trait MorphHandler: core::Plugin {
+    /// Return the URIDs of the port types and the option.
+    fn morph_urids(&self) -> &MorphURIDs;
+
+    /// Return the current type of a morph port, or `None` if there is no such port.
+    ///
+    /// For auto-morph ports, this is the type that follows from the types of the other ports.
+    fn port_type(&self, port: u32) -> Option<MorphType>;
+
+    /// Change the type of a morph port.
+    ///
+    /// This is where the plugin updates its port wrappers, for example with
+    /// [`MorphInputPort::set_type`](struct.MorphInputPort.html#method.set_type). It is called
+    /// in the instantiation threading class and only for ports for which
+    /// [`port_type`](#tymethod.port_type) returns `Some`. Return `OptionsErr::BadValue` if the
+    /// port doesn't support the type.
+    fn morph_port(&mut self, port: u32, port_type: MorphType) -> Result<(), OptionsErr>;
+}
+
+/// Handle an option if it is the `morph:currentType` option.
+///
+/// `None` is returned if the option has another key, so the plugin can handle its other options
+/// afterwards. The same function may be used to apply the options that are passed to
+/// `instantiate`.
+pub fn set_option<P: MorphHandler>(
+    plugin: &mut P,
+    option: &OptionsOption,
+) -> Option<Result<(), OptionsErr>> {
+    let urids = *plugin.morph_urids();
+    if option.key != urids.current_type {
+        return None;
+    }
+    Some(set_current_type(plugin, option, &urids))
+}
+
+fn set_current_type<P: MorphHandler>(
+    plugin: &mut P,
+    option: &OptionsOption,
+    urids: &MorphURIDs,
+) -> Result<(), OptionsErr> {
+    if option.option_context() != Some(OptionContext::Port) {
+        return Err(OptionsErr::BadSubject);
+    }
+    if plugin.port_type(option.subject).is_none() {
+        return Err(OptionsErr::BadSubject);
+    }
+    if option.value_type != urids.urid {
+        return Err(OptionsErr::BadValue);
+    }
+    let port_type = URID::from_bytes(unsafe { option.raw_value() })
+        .and_then(|urid| MorphType::from_urid(urid, urids))
+        .ok_or(OptionsErr::BadValue)?;
+    plugin.morph_port(option.subject, port_type)
+}
+
+/// Answer a request of the `morph:currentType` option.
+///
+/// Just like [`set_option`](fn.set_option.html), `None` is returned if the key is another one.
+pub fn get_option<P: MorphHandler>(
+    plugin: &P,
+    context: OptionContext,
+    subject: u32,
+    key: URID,
+) -> Option<Result<OptionData<'_>, OptionsErr>> {
+    let urids = plugin.morph_urids();
+    if key != urids.current_type {
+        return None;
+    }
+    if context != OptionContext::Port {
+        return Some(Err(OptionsErr::BadSubject));
+    }
+    let port_type = match plugin.port_type(subject) {
+        Some(port_type) => port_type,
+        None => return Some(Err(OptionsErr::BadSubject)),
+    };
+    let value = port_type.urid(urids);
+    Some(Ok(OptionData {
+        value_type: urids.urid,
+        value: unsafe {
+            std::slice::from_raw_parts(
+                value as *const URID as *const u8,
+                std::mem::size_of::<URID>(),
+            )
+        },
+    }))
+}
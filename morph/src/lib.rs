@@ -0,0 +1,132 @@
+//! Ports that switch between control and CV.
+//!
+//! This crate implements the [LV2 morph extension](http://lv2plug.in/ns/ext/morph/morph.html):
+//! A morph port is declared as a control port that also supports the CV port type. The host may
+//! switch its type by setting the `morph:currentType` option of the port. A plugin that
+//! implements [`MorphHandler`](trait.MorphHandler.html) forwards its options to
+//! [`set_option`](fn.set_option.html) and [`get_option`](fn.get_option.html), which decode the
+//! port types and call the trait's methods. The port wrappers
+//! [`MorphInputPort`](struct.MorphInputPort.html) and
+//! [`MorphOutputPort`](struct.MorphOutputPort.html) then provide the data according to the
+//! current type.
+//!
+//! Audio ports may be morph ports, too: Hosts that process audio in double precision may switch
+//! them to the [double-precision audio type](uris/constant.DOUBLE_AUDIO_PORT_URI.html), which
+//! isn't part of LV2. The wrappers [`MorphAudioInputPort`](struct.MorphAudioInputPort.html) and
+//! [`MorphAudioOutputPort`](struct.MorphAudioOutputPort.html) provide the samples in either
+//! precision and a plugin that implements [`PrecisionHandler`](trait.PrecisionHandler.html)
+//! writes its processing once, generic over the [`Sample`](trait.Sample.html) type.
+//!
+//! The options are handled by the options interface of the
+//! [`lv2rs-options`](https://docs.rs/lv2rs-options) crate, which the plugin has to return in its
+//! `extension_data` function:
+//!
+//!     extern crate lv2rs_core as core;
+//!     extern crate lv2rs_morph as morph;
+//!     extern crate lv2rs_options as options;
+//!     extern crate lv2rs_urid as urid;
+//!
+//!     use morph::*;
+//!     use options::*;
+//!     use std::ffi::CStr;
+//!     use urid::{CachedMap, URID};
+//!
+//!     struct Modulator {
+//!         morph_urids: MorphURIDs,
+//!         depth: MorphInputPort,
+//!     }
+//!
+//!     impl core::Plugin for Modulator {
+//!         fn instantiate(
+//!             _context: &core::InstantiationContext,
+//!             features: Option<&core::FeaturesList>,
+//!         ) -> Result<Self, core::InstantiateError> {
+//!             let missing_map = core::InstantiateError::missing_feature::<urid::Map>;
+//!             let features = features.ok_or_else(missing_map)?;
+//!             let mut urids = CachedMap::try_from_features(features).ok_or_else(missing_map)?;
+//!             let mut plugin = Self {
+//!                 morph_urids: MorphURIDs::new(&mut urids),
+//!                 depth: MorphInputPort::new(),
+//!             };
+//!             // The host may already set the port types at instantiation.
+//!             for option in OptionsOption::list_from_features(features).unwrap_or(&[]) {
+//!                 morph::set_option(&mut plugin, option);
+//!             }
+//!             Ok(plugin)
+//!         }
+//!
+//!         fn connect_port(&mut self, port: u32, data: *mut ()) {
+//!             if port == 0 {
+//!                 self.depth.connect(data as *const f32);
+//!             }
+//!         }
+//!
+//!         fn run(&mut self, context: &mut core::RunContext) {
+//!             if let Some(depth) = unsafe { self.depth.get(context.n_samples()) } {
+//!                 for frame in 0..context.n_samples() as usize {
+//!                     let _depth = depth.value_at(frame);
+//!                 }
+//!             }
+//!         }
+//!
+//!         fn extension_data(uri: &CStr) -> Option<&'static core::ExtensionData> {
+//!             core::extension_data!(uri, [(options::uris::INTERFACE_URI, &OPTIONS_INTERFACE)])
+//!         }
+//!     }
+//!
+//!     impl MorphHandler for Modulator {
+//!         fn morph_urids(&self) -> &MorphURIDs {
+//!             &self.morph_urids
+//!         }
+//!
+//!         fn port_type(&self, port: u32) -> Option<MorphType> {
+//!             if port == 0 {
+//!                 Some(self.depth.port_type())
+//!             } else {
+//!                 None
+//!             }
+//!         }
+//!
+//!         fn morph_port(&mut self, _port: u32, port_type: MorphType) -> Result<(), OptionsErr> {
+//!             match port_type {
+//!                 MorphType::Control | MorphType::CV => {
+//!                     self.depth.set_type(port_type);
+//!                     Ok(())
+//!                 }
+//!                 _ => Err(OptionsErr::BadValue),
+//!             }
+//!         }
+//!     }
+//!
+//!     impl OptionsHandler for Modulator {
+//!         fn get_option(
+//!             &self,
+//!             context: OptionContext,
+//!             subject: u32,
+//!             key: URID,
+//!         ) -> Result<OptionData<'_>, OptionsErr> {
+//!             morph::get_option(self, context, subject, key).unwrap_or(Err(OptionsErr::BadKey))
+//!         }
+//!
+//!         fn set_option(&mut self, option: &OptionsOption) -> Result<(), OptionsErr> {
+//!             morph::set_option(self, option).unwrap_or(Err(OptionsErr::BadKey))
+//!         }
+//!     }
+//!
+//!     static OPTIONS_INTERFACE: OptionsInterface = OptionsInterface::new::<Modulator>();
+//!
+//!     # fn main() {}
+extern crate lv2rs_atom as atom;
+extern crate lv2rs_core as core;
+extern crate lv2rs_options as options;
+extern crate lv2rs_urid as urid;
+
+mod handler;
+mod port;
+mod precision;
+pub mod uris;
+
+pub use handler::*;
+pub use port::*;
+pub use precision::*;
+pub use uris::MorphURIDs;
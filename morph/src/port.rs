@@ -0,0 +1,291 @@
+//! Port types and the wrappers of morph ports.
+use crate::uris::MorphURIDs;
+use core::ports::{PortHandle, PortKind};
+use std::ptr::{null, null_mut};
+use urid::URID;
+
+/// The type of a morph port.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MorphType {
+    /// The port is a control port and its buffer holds a single value.
+    Control,
+    /// The port is a CV port and its buffer holds a value for every frame.
+    CV,
+    /// The port is an audio port with `f32` samples.
+    Audio,
+    /// The port is an audio port with `f64` samples.
+    DoubleAudio,
+}
+
+impl MorphType {
+    /// Interpret the URID of a port type.
+    ///
+    /// `None` is returned for types other than `lv2:ControlPort`, `lv2:CVPort`, `lv2:AudioPort`
+    /// and the [double-precision audio port](uris/constant.DOUBLE_AUDIO_PORT_URI.html).
+    pub fn from_urid(urid: URID, urids: &MorphURIDs) -> Option<Self> {
+        if urid == urids.control_port {
+            Some(MorphType::Control)
+        } else if urid == urids.cv_port {
+            Some(MorphType::CV)
+        } else if urid == urids.audio_port {
+            Some(MorphType::Audio)
+        } else if urid == urids.double_audio_port {
+            Some(MorphType::DoubleAudio)
+        } else {
+            None
+        }
+    }
+
+    /// Return a reference to the URID of the port type.
+    pub fn urid(self, urids: &MorphURIDs) -> &URID {
+        match self {
+            MorphType::Control => &urids.control_port,
+            MorphType::CV => &urids.cv_port,
+            MorphType::Audio => &urids.audio_port,
+            MorphType::DoubleAudio => &urids.double_audio_port,
+        }
+    }
+}
+
+/// The data of a morph input port.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MorphInput<'a> {
+    /// The value of a control port.
+    Control(f32),
+    /// The values of a CV port.
+    CV(&'a [f32]),
+}
+
+impl<'a> MorphInput<'a> {
+    /// Return the value at a frame.
+    ///
+    /// Control ports have the same value for every frame. `None` is returned if the frame is out
+    /// of the bounds of a CV buffer.
+    pub fn value_at(&self, frame: usize) -> Option<f32> {
+        match self {
+            MorphInput::Control(value) => Some(*value),
+            MorphInput::CV(values) => values.get(frame).cloned(),
+        }
+    }
+}
+
+/// The data of a morph output port.
+#[derive(PartialEq, Debug)]
+pub enum MorphOutput<'a> {
+    /// The value of a control port.
+    Control(&'a mut f32),
+    /// The values of a CV port.
+    CV(&'a mut [f32]),
+}
+
+impl<'a> MorphOutput<'a> {
+    /// Set the value at a frame.
+    ///
+    /// Since a control port only has one value, the last value that is set is the one the host
+    /// reads. `false` is returned if the frame is out of the bounds of a CV buffer.
+    pub fn set(&mut self, frame: usize, value: f32) -> bool {
+        match self {
+            MorphOutput::Control(output) => **output = value,
+            MorphOutput::CV(outputs) => match outputs.get_mut(frame) {
+                Some(output) => *output = value,
+                None => return false,
+            },
+        }
+        true
+    }
+
+    /// Set the value of all frames.
+    pub fn fill(&mut self, value: f32) {
+        match self {
+            MorphOutput::Control(output) => **output = value,
+            MorphOutput::CV(outputs) => {
+                for output in outputs.iter_mut() {
+                    *output = value;
+                }
+            }
+        }
+    }
+}
+
+/// Wrapper for morph inputs, which are either control or CV ports.
+///
+/// The port starts as a control port, which is how morph ports should be declared in the
+/// plugin's Turtle description, and is switched to another type with
+/// [`set_type`](#method.set_type), usually in
+/// [`MorphHandler::morph_port`](trait.MorphHandler.html#tymethod.morph_port).
+pub struct MorphInputPort {
+    raw: *const f32,
+    port_type: MorphType,
+    max_block_length: Option<u32>,
+}
+
+impl MorphInputPort {
+    /// Create a new control port that points to null.
+    pub fn new() -> Self {
+        Self {
+            raw: null(),
+            port_type: MorphType::Control,
+            max_block_length: None,
+        }
+    }
+
+    /// Set the internal data pointer.
+    ///
+    /// This function should only be called by a plugin's `connect_port` function.
+    pub fn connect(&mut self, raw: *const f32) {
+        self.raw = raw;
+    }
+
+    /// Return the current type of the port.
+    pub fn port_type(&self) -> MorphType {
+        self.port_type
+    }
+
+    /// Change the type of the port.
+    ///
+    /// The host connects the port to a new buffer after it changed the type.
+    pub fn set_type(&mut self, port_type: MorphType) {
+        self.port_type = port_type;
+    }
+
+    /// Set the maximal number of samples the host passes to `run`.
+    ///
+    /// If it is set, [`get`](#method.get) refuses to create longer CV slices.
+    pub fn set_max_block_length(&mut self, max_block_length: Option<u32>) {
+        self.max_block_length = max_block_length;
+    }
+
+    /// Return the data of the port, according to its current type.
+    ///
+    /// `None` is returned if the port isn't connected, has an audio type or the sample count of
+    /// a CV port exceeds the [maximal block length](#method.set_max_block_length). Audio morph
+    /// ports are wrapped by [`MorphAudioInputPort`](struct.MorphAudioInputPort.html).
+    ///
+    /// # Safety
+    ///
+    /// Invalid slices can be created by passing an invalid sample count. Therefore, only a
+    /// plugin's `run` function should use this function and must pass the sample count it received
+    /// from the host.
+    pub unsafe fn get(&self, n_samples: u32) -> Option<MorphInput<'_>> {
+        if self.raw.is_null() {
+            return None;
+        }
+        match self.port_type {
+            MorphType::Control => Some(MorphInput::Control(*self.raw)),
+            MorphType::CV => {
+                if self.max_block_length.is_some_and(|max| n_samples > max) {
+                    return None;
+                }
+                Some(MorphInput::CV(std::slice::from_raw_parts(
+                    self.raw,
+                    n_samples as usize,
+                )))
+            }
+            MorphType::Audio | MorphType::DoubleAudio => None,
+        }
+    }
+}
+
+impl Default for MorphInputPort {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PortHandle for MorphInputPort {
+    const KIND: PortKind = PortKind::ControlInput;
+
+    fn connect_raw(&mut self, data: *mut ()) {
+        self.connect(data as *const f32);
+    }
+}
+
+/// Wrapper for morph outputs, which are either control or CV ports.
+///
+/// Just like the [`MorphInputPort`](struct.MorphInputPort.html), it starts as a control port.
+pub struct MorphOutputPort {
+    raw: *mut f32,
+    port_type: MorphType,
+    max_block_length: Option<u32>,
+}
+
+impl MorphOutputPort {
+    /// Create a new control port that points to null.
+    pub fn new() -> Self {
+        Self {
+            raw: null_mut(),
+            port_type: MorphType::Control,
+            max_block_length: None,
+        }
+    }
+
+    /// Set the internal data pointer.
+    ///
+    /// This function should only be called by a plugin's `connect_port` function.
+    pub fn connect(&mut self, raw: *mut f32) {
+        self.raw = raw;
+    }
+
+    /// Return the current type of the port.
+    pub fn port_type(&self) -> MorphType {
+        self.port_type
+    }
+
+    /// Change the type of the port.
+    ///
+    /// The host connects the port to a new buffer after it changed the type.
+    pub fn set_type(&mut self, port_type: MorphType) {
+        self.port_type = port_type;
+    }
+
+    /// Set the maximal number of samples the host passes to `run`.
+    ///
+    /// If it is set, [`get`](#method.get) refuses to create longer CV slices.
+    pub fn set_max_block_length(&mut self, max_block_length: Option<u32>) {
+        self.max_block_length = max_block_length;
+    }
+
+    /// Return the data of the port, according to its current type.
+    ///
+    /// `None` is returned if the port isn't connected, has an audio type or the sample count of
+    /// a CV port exceeds the [maximal block length](#method.set_max_block_length). Audio morph
+    /// ports are wrapped by [`MorphAudioOutputPort`](struct.MorphAudioOutputPort.html).
+    ///
+    /// # Safety
+    ///
+    /// Invalid slices can be created by passing an invalid sample count. Therefore, only a
+    /// plugin's `run` function should use this function and must pass the sample count it received
+    /// from the host.
+    pub unsafe fn get(&mut self, n_samples: u32) -> Option<MorphOutput<'_>> {
+        if self.raw.is_null() {
+            return None;
+        }
+        match self.port_type {
+            MorphType::Control => Some(MorphOutput::Control(&mut *self.raw)),
+            MorphType::CV => {
+                if self.max_block_length.is_some_and(|max| n_samples > max) {
+                    return None;
+                }
+                Some(MorphOutput::CV(std::slice::from_raw_parts_mut(
+                    self.raw,
+                    n_samples as usize,
+                )))
+            }
+            MorphType::Audio | MorphType::DoubleAudio => None,
+        }
+    }
+}
+
+impl Default for MorphOutputPort {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PortHandle for MorphOutputPort {
+    const KIND: PortKind = PortKind::ControlOutput;
+
+    fn connect_raw(&mut self, data: *mut ()) {
+        self.connect(data as *mut f32);
+    }
+}
@@ -0,0 +1,295 @@
+//! Audio morph ports with single or double precision and the processing in either precision.
+use crate::handler::MorphHandler;
+use crate::port::MorphType;
+use core::ports::{PortHandle, PortKind};
+use std::fmt::Debug;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::ptr::{null, null_mut};
+
+/// The precision of audio samples.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Precision {
+    /// The samples are `f32` values, like the ones of normal audio ports.
+    Single,
+    /// The samples are `f64` values.
+    Double,
+}
+
+impl Precision {
+    /// Return the precision of an audio port type.
+    ///
+    /// `None` is returned for control and CV ports.
+    pub fn from_type(port_type: MorphType) -> Option<Self> {
+        match port_type {
+            MorphType::Audio => Some(Precision::Single),
+            MorphType::DoubleAudio => Some(Precision::Double),
+            MorphType::Control | MorphType::CV => None,
+        }
+    }
+
+    /// Return the type of an audio port with this precision.
+    pub fn port_type(self) -> MorphType {
+        match self {
+            Precision::Single => MorphType::Audio,
+            Precision::Double => MorphType::DoubleAudio,
+        }
+    }
+}
+
+/// A floating point type that audio samples may have.
+///
+/// It's implemented for `f32` and `f64`, which lets plugins write their processing code once
+/// and run it in either precision.
+pub trait Sample:
+    Copy
+    + Default
+    + PartialEq
+    + PartialOrd
+    + Debug
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+    + 'static
+{
+    /// The precision of this type.
+    const PRECISION: Precision;
+
+    /// Convert a value to a sample, which may round it.
+    fn from_f64(value: f64) -> Self;
+
+    /// Convert the sample to a value.
+    fn to_f64(self) -> f64;
+}
+
+impl Sample for f32 {
+    const PRECISION: Precision = Precision::Single;
+
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+}
+
+impl Sample for f64 {
+    const PRECISION: Precision = Precision::Double;
+
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
+
+/// Wrapper for audio morph inputs, whose samples are either `f32` or `f64` values.
+///
+/// The port starts with single precision, which is how it should be declared in the plugin's
+/// Turtle description, and is switched with [`set_precision`](#method.set_precision), usually
+/// in [`MorphHandler::morph_port`](trait.MorphHandler.html#tymethod.morph_port).
+pub struct MorphAudioInputPort {
+    raw: *const (),
+    precision: Precision,
+    max_block_length: Option<u32>,
+}
+
+impl MorphAudioInputPort {
+    /// Create a new single-precision port that points to null.
+    pub fn new() -> Self {
+        Self {
+            raw: null(),
+            precision: Precision::Single,
+            max_block_length: None,
+        }
+    }
+
+    /// Set the internal data pointer.
+    ///
+    /// This function should only be called by a plugin's `connect_port` function.
+    pub fn connect(&mut self, raw: *const ()) {
+        self.raw = raw;
+    }
+
+    /// Return the current precision of the port.
+    pub fn precision(&self) -> Precision {
+        self.precision
+    }
+
+    /// Change the precision of the port.
+    ///
+    /// The host connects the port to a new buffer after it changed the type.
+    pub fn set_precision(&mut self, precision: Precision) {
+        self.precision = precision;
+    }
+
+    /// Set the maximal number of samples the host passes to `run`.
+    ///
+    /// If it is set, [`as_slice`](#method.as_slice) refuses to create longer slices.
+    pub fn set_max_block_length(&mut self, max_block_length: Option<u32>) {
+        self.max_block_length = max_block_length;
+    }
+
+    /// Try to create an immutable slice of the audio data with the given length.
+    ///
+    /// `None` is returned if the port isn't connected, `S` doesn't have the current precision of
+    /// the port or the sample count exceeds the
+    /// [maximal block length](#method.set_max_block_length).
+    ///
+    /// # Safety
+    ///
+    /// Invalid slices can be created by passing an invalid sample count. Therefore, only a
+    /// plugin's `run` function should use this function and must pass the sample count it received
+    /// from the host.
+    pub unsafe fn as_slice<S: Sample>(&self, n_samples: u32) -> Option<&[S]> {
+        if self.raw.is_null()
+            || S::PRECISION != self.precision
+            || self.max_block_length.is_some_and(|max| n_samples > max)
+        {
+            None
+        } else {
+            Some(std::slice::from_raw_parts(
+                self.raw as *const S,
+                n_samples as usize,
+            ))
+        }
+    }
+}
+
+impl Default for MorphAudioInputPort {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PortHandle for MorphAudioInputPort {
+    const KIND: PortKind = PortKind::AudioInput;
+
+    fn connect_raw(&mut self, data: *mut ()) {
+        self.connect(data);
+    }
+}
+
+/// Wrapper for audio morph outputs, whose samples are either `f32` or `f64` values.
+///
+/// Just like the [`MorphAudioInputPort`](struct.MorphAudioInputPort.html), it starts with single
+/// precision.
+pub struct MorphAudioOutputPort {
+    raw: *mut (),
+    precision: Precision,
+    max_block_length: Option<u32>,
+}
+
+impl MorphAudioOutputPort {
+    /// Create a new single-precision port that points to null.
+    pub fn new() -> Self {
+        Self {
+            raw: null_mut(),
+            precision: Precision::Single,
+            max_block_length: None,
+        }
+    }
+
+    /// Set the internal data pointer.
+    ///
+    /// This function should only be called by a plugin's `connect_port` function.
+    pub fn connect(&mut self, raw: *mut ()) {
+        self.raw = raw;
+    }
+
+    /// Return the current precision of the port.
+    pub fn precision(&self) -> Precision {
+        self.precision
+    }
+
+    /// Change the precision of the port.
+    ///
+    /// The host connects the port to a new buffer after it changed the type.
+    pub fn set_precision(&mut self, precision: Precision) {
+        self.precision = precision;
+    }
+
+    /// Set the maximal number of samples the host passes to `run`.
+    ///
+    /// If it is set, [`as_slice`](#method.as_slice) refuses to create longer slices.
+    pub fn set_max_block_length(&mut self, max_block_length: Option<u32>) {
+        self.max_block_length = max_block_length;
+    }
+
+    /// Try to create a mutable slice of the audio data with the given length.
+    ///
+    /// `None` is returned if the port isn't connected, `S` doesn't have the current precision of
+    /// the port or the sample count exceeds the
+    /// [maximal block length](#method.set_max_block_length).
+    ///
+    /// # Safety
+    ///
+    /// Invalid slices can be created by passing an invalid sample count. Therefore, only a
+    /// plugin's `run` function should use this function and must pass the sample count it received
+    /// from the host.
+    pub unsafe fn as_slice<S: Sample>(&mut self, n_samples: u32) -> Option<&mut [S]> {
+        if self.raw.is_null()
+            || S::PRECISION != self.precision
+            || self.max_block_length.is_some_and(|max| n_samples > max)
+        {
+            None
+        } else {
+            Some(std::slice::from_raw_parts_mut(
+                self.raw as *mut S,
+                n_samples as usize,
+            ))
+        }
+    }
+}
+
+impl Default for MorphAudioOutputPort {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PortHandle for MorphAudioOutputPort {
+    const KIND: PortKind = PortKind::AudioOutput;
+
+    fn connect_raw(&mut self, data: *mut ()) {
+        self.connect(data);
+    }
+}
+
+/// A plugin that processes audio in the precision the host has chosen.
+///
+/// The plugin switches the precision of its audio morph ports in
+/// [`MorphHandler::morph_port`](trait.MorphHandler.html#tymethod.morph_port), before the host
+/// connects the new buffers, and its `run` function calls [`run`](fn.run.html), which calls
+/// [`run_f32`](#method.run_f32) or [`run_f64`](#method.run_f64), depending on the current
+/// [`precision`](#tymethod.precision). Both of them call [`process`](#tymethod.process) by
+/// default, so the processing only needs to be written once.
+pub trait PrecisionHandler: MorphHandler {
+    /// Return the precision the audio ports currently have.
+    fn precision(&self) -> Precision;
+
+    /// Process a cycle with samples of type `S`.
+    fn process<S: Sample>(&mut self, context: &mut core::RunContext);
+
+    /// Process a cycle in single precision.
+    fn run_f32(&mut self, context: &mut core::RunContext) {
+        self.process::<f32>(context);
+    }
+
+    /// Process a cycle in double precision.
+    fn run_f64(&mut self, context: &mut core::RunContext) {
+        self.process::<f64>(context);
+    }
+}
+
+/// Process a cycle in the current precision of the plugin.
+pub fn run<P: PrecisionHandler>(plugin: &mut P, context: &mut core::RunContext) {
+    match plugin.precision() {
+        Precision::Single => plugin.run_f32(context),
+        Precision::Double => plugin.run_f64(context),
+    }
+}
@@ -0,0 +1,29 @@
+//! URIs of the LV2 morph extension.
+use core::uris::Uri;
+
+pub const URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/morph\0");
+pub const AUTO_MORPH_PORT_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/morph#AutoMorphPort\0");
+pub const MORPH_PORT_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/morph#MorphPort\0");
+pub const CURRENT_TYPE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/morph#currentType\0");
+/// The type of audio ports whose samples are `f64` values.
+///
+/// It isn't part of LV2, but hosts that process audio in double precision may offer it as a type
+/// of audio morph ports.
+pub const DOUBLE_AUDIO_PORT_URI: Uri =
+    Uri::new(b"https://github.com/Janonard/lv2rs#DoubleAudioPort\0");
+pub const SUPPORTS_TYPE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/morph#supportsType\0");
+
+urid::urid_cache! {
+    /// The URIDs needed to handle morph options.
+    ///
+    /// Besides the `currentType` option, it contains the URIDs of the port types and of the URID
+    /// atom type, which is the type of the option's value.
+    pub struct MorphURIDs {
+        pub current_type: CURRENT_TYPE_URI,
+        pub control_port: core::uris::CONTROL_PORT_URI,
+        pub cv_port: core::uris::CV_PORT_URI,
+        pub audio_port: core::uris::AUDIO_PORT_URI,
+        pub double_audio_port: DOUBLE_AUDIO_PORT_URI,
+        pub urid: atom::uris::URID_TYPE_URI,
+    }
+}
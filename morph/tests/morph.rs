@@ -0,0 +1,238 @@
+extern crate lv2rs_core as core;
+extern crate lv2rs_morph as morph;
+extern crate lv2rs_options as options;
+extern crate lv2rs_urid as urid;
+
+use core::uris::Uri;
+use morph::*;
+use options::*;
+use std::ffi::CStr;
+use std::os::raw::*;
+use std::ptr::null;
+use urid::host::HostMap;
+use urid::{CachedMap, URID};
+
+/// A plugin that scales a morph input by two and writes it to a morph output.
+///
+/// The output is an auto-morph port: It always has the type of the input.
+struct Doubler {
+    morph_urids: MorphURIDs,
+    input: MorphInputPort,
+    output: MorphOutputPort,
+    morphs: u32,
+}
+
+impl core::Plugin for Doubler {
+    fn instantiate(
+        _context: &core::InstantiationContext,
+        features: Option<&core::FeaturesList>,
+    ) -> Result<Self, core::InstantiateError> {
+        let missing_map = core::InstantiateError::missing_feature::<urid::Map>;
+        let features = features.ok_or_else(missing_map)?;
+        let mut urids = CachedMap::try_from_features(features).ok_or_else(missing_map)?;
+        let mut plugin = Self {
+            morph_urids: MorphURIDs::new(&mut urids),
+            input: MorphInputPort::new(),
+            output: MorphOutputPort::new(),
+            morphs: 0,
+        };
+        for option in OptionsOption::list_from_features(features).unwrap_or(&[]) {
+            morph::set_option(&mut plugin, option);
+        }
+        Ok(plugin)
+    }
+
+    fn connect_port(&mut self, port: u32, data: *mut ()) {
+        match port {
+            0 => self.input.connect(data as *const f32),
+            1 => self.output.connect(data as *mut f32),
+            _ => (),
+        }
+    }
+
+    fn run(&mut self, context: &mut core::RunContext) {
+        let input = unsafe { self.input.get(context.n_samples()) }.unwrap();
+        let mut output = unsafe { self.output.get(context.n_samples()) }.unwrap();
+        for frame in 0..context.n_samples() as usize {
+            output.set(frame, input.value_at(frame).unwrap() * 2.0);
+        }
+    }
+
+    fn extension_data(uri: &CStr) -> Option<&'static dyn core::ExtensionData> {
+        core::extension_data!(uri, [(options::uris::INTERFACE_URI, &OPTIONS_INTERFACE)])
+    }
+}
+
+impl MorphHandler for Doubler {
+    fn morph_urids(&self) -> &MorphURIDs {
+        &self.morph_urids
+    }
+
+    fn port_type(&self, port: u32) -> Option<MorphType> {
+        match port {
+            0 => Some(self.input.port_type()),
+            1 => Some(self.output.port_type()),
+            _ => None,
+        }
+    }
+
+    fn morph_port(&mut self, port: u32, port_type: MorphType) -> Result<(), OptionsErr> {
+        if port != 0 {
+            // The type of the output can't be set directly.
+            return Err(OptionsErr::BadValue);
+        }
+        if port_type != MorphType::Control && port_type != MorphType::CV {
+            return Err(OptionsErr::BadValue);
+        }
+        self.input.set_type(port_type);
+        self.output.set_type(port_type);
+        self.morphs += 1;
+        Ok(())
+    }
+}
+
+impl OptionsHandler for Doubler {
+    fn get_option(
+        &self,
+        context: OptionContext,
+        subject: u32,
+        key: URID,
+    ) -> Result<OptionData<'_>, OptionsErr> {
+        morph::get_option(self, context, subject, key).unwrap_or(Err(OptionsErr::BadKey))
+    }
+
+    fn set_option(&mut self, option: &OptionsOption) -> Result<(), OptionsErr> {
+        morph::set_option(self, option).unwrap_or(Err(OptionsErr::BadKey))
+    }
+}
+
+static OPTIONS_INTERFACE: OptionsInterface = OptionsInterface::new::<Doubler>();
+
+core::lv2_main!(core, Doubler, b"http://example.org/Doubler\0");
+
+fn map(host_map: &HostMap, uri: Uri) -> URID {
+    host_map.map(uri.as_cstr())
+}
+
+/// Create a `currentType` option for a port.
+fn current_type(host_map: &HostMap, port: u32, port_type: &URID) -> OptionsOption {
+    OptionsOption {
+        context: OptionContext::Port as u32,
+        subject: port,
+        key: map(host_map, morph::uris::CURRENT_TYPE_URI),
+        size: std::mem::size_of::<URID>() as u32,
+        value_type: map(host_map, Uri::new(b"http://lv2plug.in/ns/ext/atom#URID\0")),
+        value: port_type as *const URID as *const c_void,
+    }
+}
+
+#[test]
+fn test_morph() {
+    let host_map = HostMap::new();
+    let control_port = map(&host_map, core::uris::CONTROL_PORT_URI);
+    let cv_port = map(&host_map, core::uris::CV_PORT_URI);
+
+    // The input starts as a CV port.
+    let instantiate_options = [
+        current_type(&host_map, 0, &cv_port),
+        OptionsOption::terminator(),
+    ];
+    let urid_features = host_map.features();
+    let [map_feature, unmap_feature] = &*urid_features;
+    let options_feature = core::Feature::new(
+        options::uris::OPTIONS_URI.as_ptr(),
+        instantiate_options.as_ptr() as *mut c_void,
+    );
+    let features: [*const core::Feature; 4] =
+        [map_feature, unmap_feature, &options_feature, null()];
+
+    let descriptor = unsafe { lv2_descriptor(0) };
+    let descriptor_ref = unsafe { descriptor.as_ref() }.unwrap();
+    let handle = unsafe {
+        (descriptor_ref.instantiate)(
+            descriptor,
+            44100.0,
+            b"/\0".as_ptr() as *const c_char,
+            features.as_ptr(),
+        )
+    };
+    assert!(!handle.is_null());
+    let interface =
+        unsafe { (descriptor_ref.extension_data)(options::uris::INTERFACE_URI.as_ptr()) };
+    let interface = unsafe { (interface as *const OptionsInterface).as_ref() }.unwrap();
+
+    let mut input = [1.0f32, 2.0, 3.0];
+    let mut output = [0.0f32; 3];
+    unsafe {
+        (descriptor_ref.connect_port)(handle, 0, input.as_mut_ptr() as *mut c_void);
+        (descriptor_ref.connect_port)(handle, 1, output.as_mut_ptr() as *mut c_void);
+        (descriptor_ref.run)(handle, 3);
+    }
+    assert_eq!([2.0, 4.0, 6.0], output);
+
+    // The output follows the input.
+    let mut requests = [
+        OptionsOption::request(
+            OptionContext::Port,
+            1,
+            map(&host_map, morph::uris::CURRENT_TYPE_URI),
+        ),
+        OptionsOption::terminator(),
+    ];
+    assert_eq!(0, unsafe { (interface.get)(handle, requests.as_mut_ptr()) });
+    assert_eq!(
+        cv_port,
+        URID::from_bytes(unsafe { requests[0].raw_value() }).unwrap()
+    );
+
+    // Switching the input back to a control port.
+    let options = [
+        current_type(&host_map, 0, &control_port),
+        OptionsOption::terminator(),
+    ];
+    assert_eq!(0, unsafe { (interface.set)(handle, options.as_ptr()) });
+    let mut control_input = 0.25f32;
+    output = [0.0; 3];
+    unsafe {
+        (descriptor_ref.connect_port)(handle, 0, &mut control_input as *mut f32 as *mut c_void);
+        (descriptor_ref.connect_port)(handle, 1, output.as_mut_ptr() as *mut c_void);
+        (descriptor_ref.run)(handle, 3);
+    }
+    assert_eq!([0.5, 0.0, 0.0], output);
+
+    // Invalid options are rejected.
+    let audio_port = map(&host_map, core::uris::AUDIO_PORT_URI);
+    let options = [
+        current_type(&host_map, 0, &audio_port),
+        current_type(&host_map, 1, &cv_port),
+        current_type(&host_map, 2, &cv_port),
+        OptionsOption::terminator(),
+    ];
+    let status = unsafe { (interface.set)(handle, options.as_ptr()) };
+    assert_eq!(
+        OptionsErr::BadValue as u32 | OptionsErr::BadSubject as u32,
+        status
+    );
+    let plugin = unsafe { core::get_instance::<Doubler>(handle) };
+    assert_eq!(2, plugin.morphs);
+    assert_eq!(MorphType::Control, plugin.output.port_type());
+
+    unsafe { (descriptor_ref.cleanup)(handle) };
+}
+
+#[test]
+fn test_morph_output() {
+    let mut values = [0.0f32; 4];
+    let mut port = MorphOutputPort::new();
+    port.connect(values.as_mut_ptr());
+    port.set_type(MorphType::CV);
+    port.set_max_block_length(Some(4));
+    assert!(unsafe { port.get(5) }.is_none());
+    {
+        let mut output = unsafe { port.get(4) }.unwrap();
+        output.fill(1.0);
+        assert!(output.set(3, 2.0));
+        assert!(!output.set(4, 2.0));
+    }
+    assert_eq!([1.0, 1.0, 1.0, 2.0], values);
+}
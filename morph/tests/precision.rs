@@ -0,0 +1,240 @@
+extern crate lv2rs_core as core;
+extern crate lv2rs_morph as morph;
+extern crate lv2rs_options as options;
+extern crate lv2rs_urid as urid;
+
+use core::uris::Uri;
+use morph::*;
+use options::*;
+use std::ffi::CStr;
+use std::os::raw::*;
+use std::ptr::null;
+use urid::host::HostMap;
+use urid::{CachedMap, URID};
+
+/// A plugin that halves its input in the precision the host has chosen.
+///
+/// The output is an auto-morph port: It always has the precision of the input.
+struct Attenuator {
+    morph_urids: MorphURIDs,
+    input: MorphAudioInputPort,
+    output: MorphAudioOutputPort,
+    runs: Vec<Precision>,
+}
+
+impl core::Plugin for Attenuator {
+    fn instantiate(
+        _context: &core::InstantiationContext,
+        features: Option<&core::FeaturesList>,
+    ) -> Result<Self, core::InstantiateError> {
+        let missing_map = core::InstantiateError::missing_feature::<urid::Map>;
+        let features = features.ok_or_else(missing_map)?;
+        let mut urids = CachedMap::try_from_features(features).ok_or_else(missing_map)?;
+        let mut plugin = Self {
+            morph_urids: MorphURIDs::new(&mut urids),
+            input: MorphAudioInputPort::new(),
+            output: MorphAudioOutputPort::new(),
+            runs: Vec::new(),
+        };
+        for option in OptionsOption::list_from_features(features).unwrap_or(&[]) {
+            morph::set_option(&mut plugin, option);
+        }
+        Ok(plugin)
+    }
+
+    fn connect_port(&mut self, port: u32, data: *mut ()) {
+        match port {
+            0 => self.input.connect(data),
+            1 => self.output.connect(data),
+            _ => (),
+        }
+    }
+
+    fn run(&mut self, context: &mut core::RunContext) {
+        morph::run(self, context);
+    }
+
+    fn extension_data(uri: &CStr) -> Option<&'static dyn core::ExtensionData> {
+        core::extension_data!(uri, [(options::uris::INTERFACE_URI, &OPTIONS_INTERFACE)])
+    }
+}
+
+impl MorphHandler for Attenuator {
+    fn morph_urids(&self) -> &MorphURIDs {
+        &self.morph_urids
+    }
+
+    fn port_type(&self, port: u32) -> Option<MorphType> {
+        match port {
+            0 => Some(self.input.precision().port_type()),
+            1 => Some(self.output.precision().port_type()),
+            _ => None,
+        }
+    }
+
+    fn morph_port(&mut self, port: u32, port_type: MorphType) -> Result<(), OptionsErr> {
+        if port != 0 {
+            return Err(OptionsErr::BadValue);
+        }
+        let precision = Precision::from_type(port_type).ok_or(OptionsErr::BadValue)?;
+        self.input.set_precision(precision);
+        self.output.set_precision(precision);
+        Ok(())
+    }
+}
+
+impl PrecisionHandler for Attenuator {
+    fn precision(&self) -> Precision {
+        self.input.precision()
+    }
+
+    fn process<S: Sample>(&mut self, context: &mut core::RunContext) {
+        self.runs.push(S::PRECISION);
+        let input = unsafe { self.input.as_slice::<S>(context.n_samples()) }.unwrap();
+        let output = unsafe { self.output.as_slice::<S>(context.n_samples()) }.unwrap();
+        for (input, output) in input.iter().zip(output.iter_mut()) {
+            *output = *input * S::from_f64(0.5);
+        }
+    }
+}
+
+impl OptionsHandler for Attenuator {
+    fn get_option(
+        &self,
+        context: OptionContext,
+        subject: u32,
+        key: URID,
+    ) -> Result<OptionData<'_>, OptionsErr> {
+        morph::get_option(self, context, subject, key).unwrap_or(Err(OptionsErr::BadKey))
+    }
+
+    fn set_option(&mut self, option: &OptionsOption) -> Result<(), OptionsErr> {
+        morph::set_option(self, option).unwrap_or(Err(OptionsErr::BadKey))
+    }
+}
+
+static OPTIONS_INTERFACE: OptionsInterface = OptionsInterface::new::<Attenuator>();
+
+core::lv2_main!(core, Attenuator, b"http://example.org/Attenuator\0");
+
+fn map(host_map: &HostMap, uri: Uri) -> URID {
+    host_map.map(uri.as_cstr())
+}
+
+/// Create a `currentType` option for a port.
+fn current_type(host_map: &HostMap, port: u32, port_type: &URID) -> OptionsOption {
+    OptionsOption {
+        context: OptionContext::Port as u32,
+        subject: port,
+        key: map(host_map, morph::uris::CURRENT_TYPE_URI),
+        size: std::mem::size_of::<URID>() as u32,
+        value_type: map(host_map, Uri::new(b"http://lv2plug.in/ns/ext/atom#URID\0")),
+        value: port_type as *const URID as *const c_void,
+    }
+}
+
+#[test]
+fn test_double_precision() {
+    let host_map = HostMap::new();
+    let audio_port = map(&host_map, core::uris::AUDIO_PORT_URI);
+    let double_audio_port = map(&host_map, morph::uris::DOUBLE_AUDIO_PORT_URI);
+    let cv_port = map(&host_map, core::uris::CV_PORT_URI);
+
+    // The host offers double precision at instantiation.
+    let instantiate_options = [
+        current_type(&host_map, 0, &double_audio_port),
+        OptionsOption::terminator(),
+    ];
+    let urid_features = host_map.features();
+    let [map_feature, unmap_feature] = &*urid_features;
+    let options_feature = core::Feature::new(
+        options::uris::OPTIONS_URI.as_ptr(),
+        instantiate_options.as_ptr() as *mut c_void,
+    );
+    let features: [*const core::Feature; 4] =
+        [map_feature, unmap_feature, &options_feature, null()];
+
+    let descriptor = unsafe { lv2_descriptor(0) };
+    let descriptor_ref = unsafe { descriptor.as_ref() }.unwrap();
+    let handle = unsafe {
+        (descriptor_ref.instantiate)(
+            descriptor,
+            44100.0,
+            b"/\0".as_ptr() as *const c_char,
+            features.as_ptr(),
+        )
+    };
+    assert!(!handle.is_null());
+    let interface =
+        unsafe { (descriptor_ref.extension_data)(options::uris::INTERFACE_URI.as_ptr()) };
+    let interface = unsafe { (interface as *const OptionsInterface).as_ref() }.unwrap();
+
+    // A value that can't be represented as `f32`.
+    let precise = 1.0 + 1.0e-12;
+    let mut input = [2.0f64, precise, -4.0];
+    let mut output = [0.0f64; 3];
+    unsafe {
+        (descriptor_ref.connect_port)(handle, 0, input.as_mut_ptr() as *mut c_void);
+        (descriptor_ref.connect_port)(handle, 1, output.as_mut_ptr() as *mut c_void);
+        (descriptor_ref.run)(handle, 3);
+    }
+    assert_eq!([1.0, precise * 0.5, -2.0], output);
+
+    // The output follows the input.
+    let mut requests = [
+        OptionsOption::request(
+            OptionContext::Port,
+            1,
+            map(&host_map, morph::uris::CURRENT_TYPE_URI),
+        ),
+        OptionsOption::terminator(),
+    ];
+    assert_eq!(0, unsafe { (interface.get)(handle, requests.as_mut_ptr()) });
+    assert_eq!(
+        double_audio_port,
+        URID::from_bytes(unsafe { requests[0].raw_value() }).unwrap()
+    );
+
+    // Switching back to single precision.
+    let options = [
+        current_type(&host_map, 0, &audio_port),
+        OptionsOption::terminator(),
+    ];
+    assert_eq!(0, unsafe { (interface.set)(handle, options.as_ptr()) });
+    let mut input = [2.0f32, 3.0];
+    let mut output = [0.0f32; 2];
+    unsafe {
+        (descriptor_ref.connect_port)(handle, 0, input.as_mut_ptr() as *mut c_void);
+        (descriptor_ref.connect_port)(handle, 1, output.as_mut_ptr() as *mut c_void);
+        (descriptor_ref.run)(handle, 2);
+    }
+    assert_eq!([1.0, 1.5], output);
+
+    // Audio ports can't become CV ports.
+    let options = [
+        current_type(&host_map, 0, &cv_port),
+        OptionsOption::terminator(),
+    ];
+    let status = unsafe { (interface.set)(handle, options.as_ptr()) };
+    assert_eq!(OptionsErr::BadValue as u32, status);
+
+    let plugin = unsafe { core::get_instance::<Attenuator>(handle) };
+    assert_eq!(vec![Precision::Double, Precision::Single], plugin.runs);
+
+    unsafe { (descriptor_ref.cleanup)(handle) };
+}
+
+#[test]
+fn test_precision_mismatch() {
+    let mut values = [1.0f64; 4];
+    let mut port = MorphAudioOutputPort::new();
+    port.connect(values.as_mut_ptr() as *mut ());
+    port.set_max_block_length(Some(4));
+    assert!(unsafe { port.as_slice::<f64>(4) }.is_none());
+
+    port.set_precision(Precision::Double);
+    assert!(unsafe { port.as_slice::<f32>(4) }.is_none());
+    assert!(unsafe { port.as_slice::<f64>(5) }.is_none());
+    unsafe { port.as_slice::<f64>(4) }.unwrap()[3] = 2.0;
+    assert_eq!([1.0, 1.0, 1.0, 2.0], values);
+}
@@ -0,0 +1,74 @@
+//! Guarantees of the host about the block lengths.
+use crate::host::HostOptions;
+use crate::uris;
+use core::uris::Uri;
+use urid::CachedMap;
+
+/// The buffer sizes and block length guarantees of the host.
+///
+/// This struct combines the options of the
+/// [buf-size extension](http://lv2plug.in/ns/ext/buf-size/buf-size.html) with its features, which
+/// tell how the host calls `run`. It is created in `instantiate` with
+/// [`from_features`](#method.from_features) and tells the plugin how large its scratch buffers
+/// have to be.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct BufferConstraints {
+    /// The minimal number of frames passed to `run`.
+    pub min_block_length: Option<u32>,
+    /// The maximal number of frames passed to `run`.
+    pub max_block_length: Option<u32>,
+    /// The usual number of frames passed to `run`.
+    pub nominal_block_length: Option<u32>,
+    /// The size of sequence buffers in bytes.
+    pub sequence_size: Option<u32>,
+    /// The host provides the minimal and the maximal block length and respects them.
+    pub bounded: bool,
+    /// The host always passes the same number of frames to `run`.
+    pub fixed: bool,
+    /// The number of frames passed to `run` is always a power of two.
+    pub power_of_two: bool,
+    /// The host doesn't split blocks at events or parameter changes.
+    pub coarse: bool,
+}
+
+impl BufferConstraints {
+    /// Read the block length options and features of the host.
+    ///
+    /// The options are parsed with
+    /// [`HostOptions::from_features`](struct.HostOptions.html#method.from_features). Block
+    /// lengths that aren't given or are negative are `None`.
+    pub fn from_features(features: &core::FeaturesList, urids: &mut CachedMap) -> Self {
+        let options = HostOptions::from_features(features, urids).unwrap_or_default();
+        Self::from_options(&options, features)
+    }
+
+    /// Combine already parsed options with the block length features of the host.
+    pub fn from_options(options: &HostOptions, features: &core::FeaturesList) -> Self {
+        let features = core::Features::new(features);
+        let supports = |uri: Uri| features.contains(uri.as_cstr());
+        let length = |value: Option<i32>| value.filter(|value| *value >= 0).map(|v| v as u32);
+        Self {
+            min_block_length: length(options.min_block_length),
+            max_block_length: length(options.max_block_length),
+            nominal_block_length: length(options.nominal_block_length),
+            sequence_size: length(options.sequence_size),
+            bounded: supports(uris::BOUNDED_BLOCK_LENGTH_URI),
+            fixed: supports(uris::FIXED_BLOCK_LENGTH_URI),
+            power_of_two: supports(uris::POWER_OF_2_BLOCK_LENGTH_URI),
+            coarse: supports(uris::COARSE_BLOCK_LENGTH_URI),
+        }
+    }
+
+    /// Return the number of frames scratch buffers have to hold.
+    ///
+    /// This is the maximal block length or, if the block length is fixed, the nominal block
+    /// length. `None` is returned if the host didn't give any guarantee, in which case the plugin
+    /// has to be prepared for blocks of any length.
+    pub fn scratch_length(&self) -> Option<usize> {
+        match (self.max_block_length, self.fixed) {
+            (Some(max), _) => Some(max as usize),
+            (None, true) => self.nominal_block_length.map(|length| length as usize),
+            (None, false) => None,
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! The common options of hosts.
+use crate::option::*;
+use crate::uris::{self, OptionsURIDs};
+use atom::uris::AtomURIDs;
+use core::uris::Uri;
+use urid::CachedMap;
+
+/// The common options a host may pass to a plugin.
+///
+/// Hosts pass options like the block lengths or the sample rate via the options feature when
+/// the plugin is instantiated. [`from_features`](#method.from_features) parses them and
+/// [`apply`](#method.apply) updates them when the host sets options at runtime. Options the host
+/// did not provide are `None`.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct HostOptions {
+    /// The maximal number of frames passed to `run`.
+    pub max_block_length: Option<i32>,
+    /// The minimal number of frames passed to `run`.
+    pub min_block_length: Option<i32>,
+    /// The usual number of frames passed to `run`.
+    pub nominal_block_length: Option<i32>,
+    /// The size of sequence buffers in bytes.
+    pub sequence_size: Option<i32>,
+    /// The sample rate.
+    pub sample_rate: Option<f32>,
+    /// The scale factor of user interfaces.
+    pub scale_factor: Option<f32>,
+}
+
+impl HostOptions {
+    /// Parse the options passed with the options feature.
+    ///
+    /// The URIDs of the options and the atom types are mapped with the `CachedMap` and options
+    /// that aren't instance options, are unknown or have invalid values are ignored. `None` is
+    /// returned if the host does not provide the options feature.
+    pub fn from_features(features: &core::FeaturesList, urids: &mut CachedMap) -> Option<Self> {
+        let options = OptionsOption::list_from_features(features)?;
+        OptionsURIDs::new(urids);
+        AtomURIDs::new(urids);
+        let mut host_options = Self::default();
+        for option in options {
+            let _ = host_options.apply(option, urids);
+        }
+        Some(host_options)
+    }
+
+    /// Update the options with a new option.
+    ///
+    /// The URIDs of the options and the atom types have to be mapped in advance, which is done by
+    /// `from_features`. `OptionsErr::BadSubject` is returned if the option isn't an instance
+    /// option, `OptionsErr::BadKey` if it isn't a common option and `OptionsErr::BadValue` if it
+    /// has a wrong type.
+    pub fn apply(&mut self, option: &OptionsOption, urids: &CachedMap) -> Result<(), OptionsErr> {
+        if option.option_context() != Some(OptionContext::Instance) {
+            return Err(OptionsErr::BadSubject);
+        }
+        let key = Some(option.key);
+        let get = |uri: Uri| urids.get(uri.as_cstr());
+        unsafe {
+            if key == get(uris::MAX_BLOCK_LENGTH_URI) {
+                self.max_block_length = Some(option.read(urids)?);
+            } else if key == get(uris::MIN_BLOCK_LENGTH_URI) {
+                self.min_block_length = Some(option.read(urids)?);
+            } else if key == get(uris::NOMINAL_BLOCK_LENGTH_URI) {
+                self.nominal_block_length = Some(option.read(urids)?);
+            } else if key == get(uris::SEQUENCE_SIZE_URI) {
+                self.sequence_size = Some(option.read(urids)?);
+            } else if key == get(uris::SAMPLE_RATE_URI) {
+                self.sample_rate = Some(option.read(urids)?);
+            } else if key == get(uris::SCALE_FACTOR_URI) {
+                self.scale_factor = Some(option.read(urids)?);
+            } else {
+                return Err(OptionsErr::BadKey);
+            }
+        }
+        Ok(())
+    }
+}
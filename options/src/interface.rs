@@ -0,0 +1,98 @@
+//! The options interface and the trait to implement it.
+use crate::option::*;
+use urid::URID;
+
+/// A plugin whose options can be read and changed by the host at runtime.
+///
+/// Both methods are in the instantiation threading class, so they are never called concurrently
+/// with `run` or any other method of the plugin.
+pub trait OptionsHandler: core::Plugin {
+    /// Get the value of an option.
+    ///
+    /// The returned data is borrowed from the plugin; The host copies it before it calls another
+    /// method of the plugin.
+    fn get_option(
+        &self,
+        context: OptionContext,
+        subject: u32,
+        key: URID,
+    ) -> Result<OptionData<'_>, OptionsErr>;
+
+    /// Set the value of an option.
+    fn set_option(&mut self, option: &OptionsOption) -> Result<(), OptionsErr>;
+}
+
+/// The raw options interface.
+///
+/// An instance of this struct, created with [`new`](#method.new), has to be returned by the
+/// plugin's `extension_data` function for the [interface URI](uris/constant.INTERFACE_URI.html).
+#[repr(C)]
+pub struct OptionsInterface {
+    /// Fill the values of a terminated array of requested options.
+    pub get: unsafe extern "C" fn(instance: core::Handle, options: *mut OptionsOption) -> u32,
+    /// Set the options of a terminated array.
+    pub set: unsafe extern "C" fn(instance: core::Handle, options: *const OptionsOption) -> u32,
+}
+
+impl core::ExtensionData for OptionsInterface {}
+
+impl OptionsInterface {
+    /// Create the options interface for a plugin type.
+    pub const fn new<P: OptionsHandler>() -> Self {
+        Self {
+            get: get::<P>,
+            set: set::<P>,
+        }
+    }
+}
+
+unsafe extern "C" fn get<P: OptionsHandler>(
+    instance: core::Handle,
+    options: *mut OptionsOption,
+) -> u32 {
+    let mut status = 0;
+    let mut option = options;
+    while !(*option).is_terminator() {
+        let request = &mut *option;
+        let result = match request.option_context() {
+            Some(context) => core::guard(
+                instance,
+                "get_option",
+                Err(OptionsErr::Unknown),
+                |plugin: &mut P| plugin.get_option(context, request.subject, request.key),
+            ),
+            None => Err(OptionsErr::BadSubject),
+        };
+        match result {
+            Ok(data) => {
+                request.size = data.value.len() as u32;
+                request.value_type = data.value_type;
+                request.value = data.value.as_ptr() as *const _;
+            }
+            Err(error) => status |= error as u32,
+        }
+        option = option.add(1);
+    }
+    status
+}
+
+unsafe extern "C" fn set<P: OptionsHandler>(
+    instance: core::Handle,
+    options: *const OptionsOption,
+) -> u32 {
+    OptionsOption::list_from_raw(options)
+        .iter()
+        .map(|option| {
+            core::guard(
+                instance,
+                "set_option",
+                Err(OptionsErr::Unknown),
+                |plugin: &mut P| plugin.set_option(option),
+            )
+        })
+        .map(|result| match result {
+            Ok(()) => 0,
+            Err(error) => error as u32,
+        })
+        .fold(0, |status, error| status | error)
+}
@@ -0,0 +1,100 @@
+//! Options of plugins and hosts.
+//!
+//! This crate implements the [LV2 options extension](http://lv2plug.in/ns/ext/options/options.html):
+//! Hosts pass options, like the maximal block length or the sample rate, to plugins via the
+//! options feature. These are parsed into a [`HostOptions`](struct.HostOptions.html) struct and,
+//! together with the block length features of the host, into
+//! [`BufferConstraints`](struct.BufferConstraints.html). A
+//! plugin that implements the [`OptionsHandler`](trait.OptionsHandler.html) trait also lets the
+//! host get and set its options at runtime.
+//!
+//! The plugin has to return an [`OptionsInterface`](struct.OptionsInterface.html) for the
+//! [interface URI](uris/constant.INTERFACE_URI.html) in its `extension_data` function:
+//!
+//!     extern crate lv2rs_core as core;
+//!     extern crate lv2rs_options as options;
+//!     extern crate lv2rs_urid as urid;
+//!
+//!     use options::*;
+//!     use std::ffi::CStr;
+//!     use urid::{CachedMap, URID};
+//!
+//!     struct Resampler {
+//!         urids: CachedMap,
+//!         host_options: HostOptions,
+//!         buffer: Vec<f32>,
+//!     }
+//!
+//!     impl core::Plugin for Resampler {
+//!         fn instantiate(
+//!             _context: &core::InstantiationContext,
+//!             features: Option<&core::FeaturesList>,
+//!         ) -> Result<Self, core::InstantiateError> {
+//!             let missing_map = core::InstantiateError::missing_feature::<urid::Map>;
+//!             let features = features.ok_or_else(missing_map)?;
+//!             let mut urids = CachedMap::try_from_features(features).ok_or_else(missing_map)?;
+//!             let host_options = HostOptions::from_features(features, &mut urids)
+//!                 .ok_or_else(core::InstantiateError::missing_feature::<OptionsOption>)?;
+//!             // Allocate the buffer in advance.
+//!             let max_block_length = host_options.max_block_length.ok_or_else(|| {
+//!                 core::InstantiateError::Other("Unknown maximum block length".into())
+//!             })?;
+//!             let buffer = vec![0.0; max_block_length as usize];
+//!             Ok(Self {
+//!                 urids,
+//!                 host_options,
+//!                 buffer,
+//!             })
+//!         }
+//!
+//!         fn connect_port(&mut self, _port: u32, _data: *mut ()) {}
+//!
+//!         fn run(&mut self, _context: &mut core::RunContext) {}
+//!
+//!         fn extension_data(uri: &CStr) -> Option<&'static core::ExtensionData> {
+//!             core::extension_data!(uri, [(uris::INTERFACE_URI, &OPTIONS_INTERFACE)])
+//!         }
+//!     }
+//!
+//!     impl OptionsHandler for Resampler {
+//!         fn get_option(
+//!             &self,
+//!             _context: OptionContext,
+//!             _subject: u32,
+//!             key: URID,
+//!         ) -> Result<OptionData<'_>, OptionsErr> {
+//!             let sample_rate_uri = uris::SAMPLE_RATE_URI.as_cstr();
+//!             match self.host_options.sample_rate {
+//!                 Some(ref sample_rate) if Some(key) == self.urids.get(sample_rate_uri) => {
+//!                     OptionData::scalar(sample_rate, &self.urids)
+//!                 }
+//!                 _ => Err(OptionsErr::BadKey),
+//!             }
+//!         }
+//!
+//!         fn set_option(&mut self, option: &OptionsOption) -> Result<(), OptionsErr> {
+//!             self.host_options.apply(option, &self.urids)?;
+//!             if let Some(max_block_length) = self.host_options.max_block_length {
+//!                 self.buffer.resize(max_block_length as usize, 0.0);
+//!             }
+//!             Ok(())
+//!         }
+//!     }
+//!
+//!     static OPTIONS_INTERFACE: OptionsInterface = OptionsInterface::new::<Resampler>();
+//!
+//!     # fn main() {}
+extern crate lv2rs_atom as atom;
+extern crate lv2rs_core as core;
+extern crate lv2rs_urid as urid;
+
+mod buf_size;
+mod host;
+mod interface;
+mod option;
+pub mod uris;
+
+pub use buf_size::*;
+pub use host::*;
+pub use interface::*;
+pub use option::*;
@@ -0,0 +1,218 @@
+//! Options and their values.
+use crate::uris;
+use atom::prelude::*;
+use core::uris::Uri;
+use std::os::raw::*;
+use urid::{CachedMap, URID};
+
+/// The context of an option, which specifies what the subject of the option is.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OptionContext {
+    /// The option applies to the whole instance; The subject is ignored.
+    Instance = 0,
+    /// The option applies to a resource; The subject is its URID.
+    Resource = 1,
+    /// The option applies to a blank node; The subject is its ID.
+    Blank = 2,
+    /// The option applies to a port; The subject is its index.
+    Port = 3,
+}
+
+impl OptionContext {
+    /// Interpret a raw context value.
+    pub fn from_raw(context: u32) -> Option<Self> {
+        match context {
+            0 => Some(OptionContext::Instance),
+            1 => Some(OptionContext::Resource),
+            2 => Some(OptionContext::Blank),
+            3 => Some(OptionContext::Port),
+            _ => None,
+        }
+    }
+}
+
+/// Errors that may occur when getting or setting options.
+///
+/// The discriminants are the status flags of the LV2 options extension. Since an operation may
+/// involve several options, the status returned to the host is the combination of all errors.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OptionsErr {
+    /// An unknown error occurred.
+    Unknown = 1,
+    /// The subject of an option is invalid.
+    BadSubject = 2,
+    /// The key of an option is unknown.
+    BadKey = 4,
+    /// The value of an option is invalid.
+    BadValue = 8,
+}
+
+/// Scalar values that options can have.
+///
+/// This trait is implemented for all scalar atom types.
+pub trait OptionValue: ScalarAtomBody + Copy {
+    /// Interpret the raw bytes of a value.
+    ///
+    /// `None` is returned if the bytes don't form a valid value.
+    fn from_bytes(bytes: &[u8]) -> Option<Self>;
+}
+
+macro_rules! impl_option_value {
+    ($($t:ty),*) => {
+        $(
+            impl OptionValue for $t {
+                fn from_bytes(bytes: &[u8]) -> Option<Self> {
+                    let mut value = [0u8; std::mem::size_of::<$t>()];
+                    if bytes.len() != value.len() {
+                        return None;
+                    }
+                    value.copy_from_slice(bytes);
+                    Some(<$t>::from_ne_bytes(value))
+                }
+            }
+        )*
+    };
+}
+
+impl_option_value!(i32, i64, f32, f64, URID);
+
+impl OptionValue for bool {
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        <bool as ScalarAtomBody>::from_body(bytes).ok().cloned()
+    }
+}
+
+/// A raw option, as it is passed between host and plugin.
+///
+/// The values are only borrowed: The memory they point to is owned by whoever created the option.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct OptionsOption {
+    /// The raw [context](enum.OptionContext.html) of the option.
+    pub context: u32,
+    /// The subject of the option, which is interpreted according to the context.
+    pub subject: u32,
+    /// The URID of the option.
+    pub key: URID,
+    /// The size of the value in bytes.
+    pub size: u32,
+    /// The URID of the value's type.
+    pub value_type: URID,
+    /// Pointer to the value.
+    pub value: *const c_void,
+}
+
+unsafe impl core::FeatureData for OptionsOption {
+    const URI: Uri = uris::OPTIONS_URI;
+}
+
+impl OptionsOption {
+    /// Create an option with a value.
+    pub fn new(context: OptionContext, subject: u32, key: URID, value: OptionData) -> Self {
+        Self {
+            context: context as u32,
+            subject,
+            key,
+            size: value.value.len() as u32,
+            value_type: value.value_type,
+            value: value.value.as_ptr() as *const c_void,
+        }
+    }
+
+    /// Create an option without a value, as it is used to request options from plugins.
+    pub fn request(context: OptionContext, subject: u32, key: URID) -> Self {
+        Self {
+            context: context as u32,
+            subject,
+            key,
+            size: 0,
+            value_type: 0,
+            value: std::ptr::null(),
+        }
+    }
+
+    /// Create the option that terminates option arrays.
+    pub fn terminator() -> Self {
+        Self::request(OptionContext::Instance, 0, 0)
+    }
+
+    /// Return `true` if this option terminates an option array.
+    pub fn is_terminator(&self) -> bool {
+        self.key == 0 && self.value.is_null()
+    }
+
+    /// Try to find the options feature in the features list.
+    ///
+    /// The options are returned without the terminating option. `None` is returned if the host
+    /// does not provide options.
+    pub fn list_from_features(features: &core::FeaturesList) -> Option<&'static [OptionsOption]> {
+        let first = unsafe { core::Features::new(features).get::<OptionsOption>() }?;
+        Some(unsafe { Self::list_from_raw(first) })
+    }
+
+    /// Read a terminated array of options.
+    ///
+    /// This function is unsafe since the array can not be checked: It has to be terminated and
+    /// has to outlive the returned slice.
+    pub unsafe fn list_from_raw<'a>(first: *const OptionsOption) -> &'a [OptionsOption] {
+        let mut length = 0;
+        while !(*first.add(length)).is_terminator() {
+            length += 1;
+        }
+        std::slice::from_raw_parts(first, length)
+    }
+
+    /// Return the context of the option, or `None` if it is invalid.
+    pub fn option_context(&self) -> Option<OptionContext> {
+        OptionContext::from_raw(self.context)
+    }
+
+    /// Return the raw value of the option.
+    ///
+    /// This method is unsafe since the value pointer and size can not be checked.
+    pub unsafe fn raw_value(&self) -> &[u8] {
+        if self.value.is_null() {
+            return &[];
+        }
+        std::slice::from_raw_parts(self.value as *const u8, self.size as usize)
+    }
+
+    /// Read the value of the option.
+    ///
+    /// The URID of the value's type is looked up in the given `CachedMap` without mapping it, so
+    /// the types have to be mapped in advance, for example with an
+    /// [`AtomURIDs`](../lv2rs_atom/uris/struct.AtomURIDs.html) struct. `OptionsErr::BadValue`
+    /// is returned if the option has a different type or an invalid value.
+    ///
+    /// This method is unsafe since the value pointer and size can not be checked.
+    pub unsafe fn read<T: OptionValue>(&self, urids: &CachedMap) -> Result<T, OptionsErr> {
+        let value_type = urids.get(T::get_uri()).ok_or(OptionsErr::BadValue)?;
+        if self.value_type != value_type {
+            return Err(OptionsErr::BadValue);
+        }
+        T::from_bytes(self.raw_value()).ok_or(OptionsErr::BadValue)
+    }
+}
+
+/// The borrowed value of an option, together with its type.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct OptionData<'a> {
+    /// The URID of the value's type.
+    pub value_type: URID,
+    /// The raw value.
+    pub value: &'a [u8],
+}
+
+impl<'a> OptionData<'a> {
+    /// Borrow a scalar value.
+    ///
+    /// Just like [`OptionsOption::read`](struct.OptionsOption.html#method.read), the URID of the
+    /// type has to be mapped in advance, or `OptionsErr::BadValue` is returned.
+    pub fn scalar<T: OptionValue>(value: &'a T, urids: &CachedMap) -> Result<Self, OptionsErr> {
+        let value_type = urids.get(T::get_uri()).ok_or(OptionsErr::BadValue)?;
+        Ok(Self {
+            value_type,
+            value: value.as_bytes(),
+        })
+    }
+}
@@ -0,0 +1,46 @@
+//! URIs of the LV2 options extension and of common options.
+use core::uris::Uri;
+
+pub const URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/options\0");
+pub const INTERFACE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/options#interface\0");
+pub const OPTIONS_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/options#options\0");
+pub const REQUIRED_OPTION_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/options#requiredOption\0");
+pub const SUPPORTED_OPTION_URI: Uri =
+    Uri::new(b"http://lv2plug.in/ns/ext/options#supportedOption\0");
+
+// Options of the buf-size extension.
+pub const MAX_BLOCK_LENGTH_URI: Uri =
+    Uri::new(b"http://lv2plug.in/ns/ext/buf-size#maxBlockLength\0");
+pub const MIN_BLOCK_LENGTH_URI: Uri =
+    Uri::new(b"http://lv2plug.in/ns/ext/buf-size#minBlockLength\0");
+pub const NOMINAL_BLOCK_LENGTH_URI: Uri =
+    Uri::new(b"http://lv2plug.in/ns/ext/buf-size#nominalBlockLength\0");
+pub const SEQUENCE_SIZE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/buf-size#sequenceSize\0");
+
+// Features of the buf-size extension.
+pub const BOUNDED_BLOCK_LENGTH_URI: Uri =
+    Uri::new(b"http://lv2plug.in/ns/ext/buf-size#boundedBlockLength\0");
+pub const COARSE_BLOCK_LENGTH_URI: Uri =
+    Uri::new(b"http://lv2plug.in/ns/ext/buf-size#coarseBlockLength\0");
+pub const FIXED_BLOCK_LENGTH_URI: Uri =
+    Uri::new(b"http://lv2plug.in/ns/ext/buf-size#fixedBlockLength\0");
+pub const POWER_OF_2_BLOCK_LENGTH_URI: Uri =
+    Uri::new(b"http://lv2plug.in/ns/ext/buf-size#powerOf2BlockLength\0");
+
+// Options of the parameters and the UI extensions.
+pub const SAMPLE_RATE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/parameters#sampleRate\0");
+pub const SCALE_FACTOR_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/ui#scaleFactor\0");
+
+urid::urid_cache! {
+    /// The URIDs of the common options.
+    ///
+    /// Map them with `OptionsURIDs::new(&mut cached_map)` in `instantiate`.
+    pub struct OptionsURIDs {
+        pub max_block_length: MAX_BLOCK_LENGTH_URI,
+        pub min_block_length: MIN_BLOCK_LENGTH_URI,
+        pub nominal_block_length: NOMINAL_BLOCK_LENGTH_URI,
+        pub sequence_size: SEQUENCE_SIZE_URI,
+        pub sample_rate: SAMPLE_RATE_URI,
+        pub scale_factor: SCALE_FACTOR_URI,
+    }
+}
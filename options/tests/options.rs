@@ -0,0 +1,249 @@
+extern crate lv2rs_atom as atom;
+extern crate lv2rs_core as core;
+extern crate lv2rs_options as options;
+extern crate lv2rs_urid as urid;
+
+use core::uris::Uri;
+use options::*;
+use std::ffi::CStr;
+use std::os::raw::*;
+use std::ptr::{null, null_mut};
+use urid::host::{HostMap, HostMapFeatures};
+use urid::{CachedMap, URID};
+
+/// A plugin that exposes the options of the host.
+struct OptionsPlugin {
+    urids: CachedMap,
+    host_options: HostOptions,
+}
+
+impl core::Plugin for OptionsPlugin {
+    fn instantiate(
+        _context: &core::InstantiationContext,
+        features: Option<&core::FeaturesList>,
+    ) -> Result<Self, core::InstantiateError> {
+        let missing_map = core::InstantiateError::missing_feature::<urid::Map>;
+        let features = features.ok_or_else(missing_map)?;
+        let mut urids = CachedMap::try_from_features(features).ok_or_else(missing_map)?;
+        let host_options = HostOptions::from_features(features, &mut urids)
+            .ok_or_else(core::InstantiateError::missing_feature::<OptionsOption>)?;
+        Ok(Self {
+            urids,
+            host_options,
+        })
+    }
+
+    fn connect_port(&mut self, _port: u32, _data: *mut ()) {}
+
+    fn run(&mut self, _context: &mut core::RunContext) {}
+
+    fn extension_data(uri: &CStr) -> Option<&'static dyn core::ExtensionData> {
+        core::extension_data!(uri, [(options::uris::INTERFACE_URI, &OPTIONS_INTERFACE)])
+    }
+}
+
+impl OptionsHandler for OptionsPlugin {
+    fn get_option(
+        &self,
+        context: OptionContext,
+        _subject: u32,
+        key: URID,
+    ) -> Result<OptionData<'_>, OptionsErr> {
+        if context != OptionContext::Instance {
+            return Err(OptionsErr::BadSubject);
+        }
+        let max_block_length_uri = options::uris::MAX_BLOCK_LENGTH_URI.as_cstr();
+        if Some(key) != self.urids.get(max_block_length_uri) {
+            return Err(OptionsErr::BadKey);
+        }
+        match self.host_options.max_block_length {
+            Some(ref max_block_length) => OptionData::scalar(max_block_length, &self.urids),
+            None => Err(OptionsErr::Unknown),
+        }
+    }
+
+    fn set_option(&mut self, option: &OptionsOption) -> Result<(), OptionsErr> {
+        self.host_options.apply(option, &self.urids)
+    }
+}
+
+static OPTIONS_INTERFACE: OptionsInterface = OptionsInterface::new::<OptionsPlugin>();
+
+core::lv2_main!(core, OptionsPlugin, b"http://example.org/OptionsPlugin\0");
+
+fn map(host_map: &HostMap, uri: Uri) -> URID {
+    host_map.map(uri.as_cstr())
+}
+
+#[test]
+fn test_options() {
+    let host_map = HostMap::new();
+    let int_type = map(&host_map, atom::uris::INT_TYPE_URI);
+    let float_type = map(&host_map, atom::uris::FLOAT_TYPE_URI);
+    let max_block_length_key = map(&host_map, options::uris::MAX_BLOCK_LENGTH_URI);
+    let sample_rate_key = map(&host_map, options::uris::SAMPLE_RATE_URI);
+    let unknown_key = map(&host_map, Uri::new(b"http://example.org/unknownOption\0"));
+
+    let max_block_length: i32 = 512;
+    let sample_rate: f32 = 48000.0;
+    let raw_option = |key, size, value_type, value: *const c_void| OptionsOption {
+        context: OptionContext::Instance as u32,
+        subject: 0,
+        key,
+        size,
+        value_type,
+        value,
+    };
+    let host_options = [
+        raw_option(
+            max_block_length_key,
+            4,
+            int_type,
+            &max_block_length as *const i32 as *const c_void,
+        ),
+        raw_option(
+            sample_rate_key,
+            4,
+            float_type,
+            &sample_rate as *const f32 as *const c_void,
+        ),
+        // Options with wrong types are ignored.
+        raw_option(
+            sample_rate_key,
+            4,
+            int_type,
+            &max_block_length as *const i32 as *const c_void,
+        ),
+        OptionsOption::terminator(),
+    ];
+
+    let urid_features = host_map.features();
+    let [map_feature, unmap_feature] = &*urid_features;
+    let options_feature = core::Feature::new(
+        options::uris::OPTIONS_URI.as_ptr(),
+        host_options.as_ptr() as *mut c_void,
+    );
+    let features: [*const core::Feature; 4] =
+        [map_feature, unmap_feature, &options_feature, null()];
+
+    let descriptor = unsafe { lv2_descriptor(0) };
+    let descriptor_ref = unsafe { descriptor.as_ref() }.unwrap();
+    let handle = unsafe {
+        (descriptor_ref.instantiate)(
+            descriptor,
+            48000.0,
+            b"/\0".as_ptr() as *const c_char,
+            features.as_ptr(),
+        )
+    };
+    assert_ne!(null_mut(), handle);
+
+    {
+        let plugin = unsafe { core::get_instance::<OptionsPlugin>(handle) };
+        assert_eq!(Some(512), plugin.host_options.max_block_length);
+        assert_eq!(Some(48000.0), plugin.host_options.sample_rate);
+        assert_eq!(None, plugin.host_options.min_block_length);
+        assert_eq!(None, plugin.host_options.scale_factor);
+    }
+
+    let interface =
+        unsafe { (descriptor_ref.extension_data)(options::uris::INTERFACE_URI.as_ptr()) };
+    let interface = unsafe { (interface as *const OptionsInterface).as_ref() }.unwrap();
+
+    // Setting options.
+    let new_max_block_length: i32 = 1024;
+    let new_options = [
+        raw_option(
+            max_block_length_key,
+            4,
+            int_type,
+            &new_max_block_length as *const i32 as *const c_void,
+        ),
+        raw_option(
+            unknown_key,
+            4,
+            int_type,
+            &new_max_block_length as *const i32 as *const c_void,
+        ),
+        OptionsOption::terminator(),
+    ];
+    let status = unsafe { (interface.set)(handle, new_options.as_ptr()) };
+    assert_eq!(OptionsErr::BadKey as u32, status);
+
+    // Getting options.
+    let mut requests = [
+        OptionsOption::request(OptionContext::Instance, 0, max_block_length_key),
+        OptionsOption::request(OptionContext::Port, 0, max_block_length_key),
+        OptionsOption::request(OptionContext::Instance, 0, unknown_key),
+        OptionsOption::terminator(),
+    ];
+    let status = unsafe { (interface.get)(handle, requests.as_mut_ptr()) };
+    assert_eq!(
+        OptionsErr::BadSubject as u32 | OptionsErr::BadKey as u32,
+        status
+    );
+    assert_eq!(int_type, requests[0].value_type);
+    assert_eq!(4, requests[0].size);
+    assert_eq!(1024, unsafe { *(requests[0].value as *const i32) });
+    assert!(requests[1].value.is_null());
+
+    unsafe { (descriptor_ref.cleanup)(handle) };
+}
+
+#[test]
+fn test_buffer_constraints() {
+    let host_map: &'static HostMap = Box::leak(Box::new(HostMap::new()));
+    let int_type = map(&host_map, atom::uris::INT_TYPE_URI);
+    let max_block_length_key = map(&host_map, options::uris::MAX_BLOCK_LENGTH_URI);
+    let min_block_length_key = map(&host_map, options::uris::MIN_BLOCK_LENGTH_URI);
+
+    let max_block_length: i32 = 256;
+    let min_block_length: i32 = -1;
+    let raw_option = |key, value: &i32| OptionsOption {
+        context: OptionContext::Instance as u32,
+        subject: 0,
+        key,
+        size: 4,
+        value_type: int_type,
+        value: value as *const i32 as *const c_void,
+    };
+    let host_options = [
+        raw_option(max_block_length_key, &max_block_length),
+        // Negative block lengths are ignored.
+        raw_option(min_block_length_key, &min_block_length),
+        OptionsOption::terminator(),
+    ];
+
+    let urid_features: &'static HostMapFeatures = Box::leak(Box::new(host_map.features()));
+    let options_feature = core::Feature::new(
+        options::uris::OPTIONS_URI.as_ptr(),
+        host_options.as_ptr() as *mut c_void,
+    );
+    let bounded_feature =
+        core::Feature::new(options::uris::BOUNDED_BLOCK_LENGTH_URI.as_ptr(), null_mut());
+    let features: Vec<&'static core::Feature> = urid_features
+        .iter()
+        .chain(
+            vec![options_feature, bounded_feature]
+                .into_iter()
+                .map(|feature| &*Box::leak(Box::new(feature))),
+        )
+        .collect();
+
+    let mut urids = CachedMap::try_from_features(&features).unwrap();
+    let constraints = BufferConstraints::from_features(&features, &mut urids);
+    assert_eq!(Some(256), constraints.max_block_length);
+    assert_eq!(None, constraints.min_block_length);
+    assert!(constraints.bounded);
+    assert!(!constraints.fixed);
+    assert_eq!(Some(256), constraints.scratch_length());
+
+    // Without options, only a fixed block length gives a guarantee.
+    let fixed = BufferConstraints {
+        fixed: true,
+        nominal_block_length: Some(64),
+        ..BufferConstraints::default()
+    };
+    assert_eq!(Some(64), fixed.scratch_length());
+    assert_eq!(None, BufferConstraints::default().scratch_length());
+}
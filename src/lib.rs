@@ -14,21 +14,46 @@
 //!
 //! ## What is supported, what isn't?
 //!
-//! Currently 4 out of 22 [official and stable LV2 specifications](http://lv2plug.in/ns/) are
+//! Currently 14 out of 22 [official and stable LV2 specifications](http://lv2plug.in/ns/) are
 //! supported. These are:
 //! 
 //! * Atom
+//! * Dynamic Manifest
+//! * Log
 //! * LV2
 //! * MIDI
+//! * Morph
+//! * Options
+//! * Port Groups
+//! * Port Properties
+//! * State
+//! * UI
+//! * Units
 //! * URID
+//! * Worker
+//!
+//! Additionally, the inline display extension of Ardour is supported.
+//! Plugins can be integration-tested with the in-process host of the
+//! [`test-host`](https://docs.rs/lv2rs-test-host) crate.
 //! 
 //! This is a frozen prototype and therefore, development of this crate will not continue here. Further
 //! development continues as [rust-lv2](https://github.com/rust-dsp/rust-lv2).
 
 pub extern crate lv2rs_atom as atom;
 pub extern crate lv2rs_core as core;
+pub extern crate lv2rs_dynmanifest as dynmanifest;
+pub extern crate lv2rs_inline_display as inline_display;
+pub extern crate lv2rs_log as log;
 pub extern crate lv2rs_midi as midi;
+pub extern crate lv2rs_morph as morph;
+pub extern crate lv2rs_options as options;
+#[cfg(feature = "lv2rs-standalone")]
+pub extern crate lv2rs_standalone as standalone;
+pub extern crate lv2rs_state as state;
+pub extern crate lv2rs_test_host as test_host;
+pub extern crate lv2rs_ui as ui;
 pub extern crate lv2rs_urid as urid;
+pub extern crate lv2rs_worker as worker;
 
 /// Re-export module intended for wildcard use
 ///
@@ -0,0 +1,128 @@
+//! The JACK client that hosts a plugin.
+use crate::runner::Runner;
+use crate::tui::run_tui;
+use test_host::TestHost;
+
+/// An error that stops the standalone runner.
+#[derive(Debug)]
+pub enum Error {
+    /// The JACK server refused a request, for example because it isn't running.
+    Jack(jack::Error),
+    /// The plugin doesn't provide its metadata or refused to instantiate.
+    Instantiate,
+    /// Reading from the standard input or writing to the standard output failed.
+    Io(std::io::Error),
+}
+
+impl From<jack::Error> for Error {
+    fn from(error: jack::Error) -> Self {
+        Error::Jack(error)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::Jack(error) => write!(f, "JACK error: {}", error),
+            Error::Instantiate => write!(f, "The plugin could not be instantiated"),
+            Error::Io(error) => write!(f, "I/O error: {}", error),
+        }
+    }
+}
+
+/// The runner, moved to JACK's process thread.
+struct ProcessRunner<P: core::Plugin>(Runner<'static, P>);
+
+// The runner contains raw pointers to the plugin instance and the URID map, which is why it isn't
+// `Send` by itself. After it has been moved to the process thread, it's only used there and the
+// parameters it shares with the user interface are atomics.
+unsafe impl<P: core::Plugin> Send for ProcessRunner<P> {}
+
+/// Run a plugin in a JACK client until the user quits.
+///
+/// The client is named after the first argument and the URI is the null-terminated URI of the
+/// plugin. Every audio port and every MIDI port of the plugin is registered as a JACK port with
+/// the symbol of the plugin's port as its name. The control inputs are set with the commands of
+/// the user interface on the standard input, which is described in
+/// [`run_tui`](fn.run_tui.html).
+///
+/// The plugin is run once per JACK cycle. Since the buffer size of the client at activation is
+/// passed to the plugin as its maximal block length, larger cycles are truncated.
+pub fn run_jack<P: core::Plugin + 'static>(name: &str, uri: &'static [u8]) -> Result<(), Error> {
+    let (client, _status) = jack::Client::new(name, jack::ClientOptions::NO_START_SERVER)?;
+
+    // The host has to outlive the runner, which is moved to the process thread.
+    let host: &'static mut TestHost =
+        Box::leak(Box::new(TestHost::new(client.sample_rate() as f64)));
+    let runner = Runner::<P>::new(host, uri, client.buffer_size()).ok_or(Error::Instantiate)?;
+    let parameters = runner.parameters().clone();
+
+    let layout = runner.layout();
+    let audio_inputs = layout
+        .audio_inputs
+        .iter()
+        .map(|port| client.register_port(port.symbol, jack::AudioIn))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut audio_outputs = layout
+        .audio_outputs
+        .iter()
+        .map(|port| client.register_port(port.symbol, jack::AudioOut))
+        .collect::<Result<Vec<_>, _>>()?;
+    let midi_inputs = layout
+        .midi_inputs
+        .iter()
+        .map(|port| client.register_port(port.symbol, jack::MidiIn))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut midi_outputs = layout
+        .midi_outputs
+        .iter()
+        .map(|port| client.register_port(port.symbol, jack::MidiOut))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut runner = ProcessRunner(runner);
+    let process = move |_: &jack::Client, scope: &jack::ProcessScope| -> jack::Control {
+        let runner = &mut runner.0;
+        let n_frames = scope.n_frames().min(runner.max_block_length());
+        let n_samples = n_frames as usize;
+
+        for (index, port) in audio_inputs.iter().enumerate() {
+            if let Some(buffer) = runner.audio_input(index) {
+                buffer[..n_samples].copy_from_slice(&port.as_slice(scope)[..n_samples]);
+            }
+        }
+        for (index, port) in midi_inputs.iter().enumerate() {
+            let events = port
+                .iter(scope)
+                .filter(|event| event.time < n_frames)
+                .map(|event| (event.time, event.bytes));
+            let _ = runner.write_midi(index, events);
+        }
+
+        runner.run(n_frames);
+
+        for (index, port) in audio_outputs.iter_mut().enumerate() {
+            if let Some(buffer) = runner.audio_output(index) {
+                port.as_mut_slice(scope)[..n_samples].copy_from_slice(&buffer[..n_samples]);
+            }
+        }
+        for (index, port) in midi_outputs.iter_mut().enumerate() {
+            let mut writer = port.writer(scope);
+            let _ = runner.read_midi(index, |time, bytes| {
+                let _ = writer.write(&jack::RawMidi { time, bytes });
+            });
+        }
+        jack::Control::Continue
+    };
+
+    let client = client.activate_async((), jack::ClosureProcessHandler::new(process))?;
+    let stdin = std::io::stdin();
+    let result = run_tui(&parameters, stdin.lock(), std::io::stdout());
+    client.deactivate()?;
+    result.map_err(Error::from)
+}
@@ -0,0 +1,48 @@
+//! The assignment of the plugin's ports to the ports of the runner.
+use core::ports::PortKind;
+use core::ttl::{PluginInfo, PortInfo};
+
+/// The ports of a plugin, sorted by how the runner connects them.
+///
+/// Audio ports become audio ports of the runner, atom ports that support MIDI events become MIDI
+/// ports and control inputs become parameters. The other ports, like CV ports and atom ports
+/// without MIDI support, are connected to buffers of the runner, but aren't exposed.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PortLayout {
+    pub audio_inputs: Vec<&'static PortInfo>,
+    pub audio_outputs: Vec<&'static PortInfo>,
+    pub midi_inputs: Vec<&'static PortInfo>,
+    pub midi_outputs: Vec<&'static PortInfo>,
+    pub control_inputs: Vec<&'static PortInfo>,
+    pub control_outputs: Vec<&'static PortInfo>,
+}
+
+impl PortLayout {
+    /// Sort the ports of a plugin.
+    pub fn new(info: &'static PluginInfo) -> Self {
+        let of_kind = |kind: PortKind| -> Vec<&'static PortInfo> {
+            info.ports.iter().filter(|port| port.kind == kind).collect()
+        };
+        let midi = |kind: PortKind| -> Vec<&'static PortInfo> {
+            info.ports
+                .iter()
+                .filter(|port| port.kind == kind && supports_midi(port))
+                .collect()
+        };
+        Self {
+            audio_inputs: of_kind(PortKind::AudioInput),
+            audio_outputs: of_kind(PortKind::AudioOutput),
+            midi_inputs: midi(PortKind::AtomInput),
+            midi_outputs: midi(PortKind::AtomOutput),
+            control_inputs: of_kind(PortKind::ControlInput),
+            control_outputs: of_kind(PortKind::ControlOutput),
+        }
+    }
+}
+
+/// Return whether an atom port supports MIDI events.
+pub fn supports_midi(port: &PortInfo) -> bool {
+    port.supports
+        .iter()
+        .any(|supported| *supported == midi::uris::EVENT_URI.as_str())
+}
@@ -0,0 +1,46 @@
+//! A standalone runner for plugins built with lv2rs.
+//!
+//! Usually, a plugin has to be built as a library, installed into a bundle and loaded by a host
+//! before it can be heard. This crate runs a plugin type directly in a small program instead,
+//! which makes it easy to try and profile a plugin during its development.
+//!
+//! The plugin has to provide its metadata in
+//! [`Plugin::info`](../lv2rs_core/trait.Plugin.html#method.info), which tells the runner which
+//! ports it has. The [`Runner`](struct.Runner.html) instantiates the plugin in a
+//! [test host](../lv2rs_test_host/index.html) and connects all of its ports to buffers. Audio ports
+//! and atom ports that support MIDI events are exposed by the runner and control inputs become
+//! [`Parameters`](struct.Parameters.html), which can be changed from another thread while the
+//! plugin is running.
+//!
+//! With the `jack` feature, [`run_jack`](fn.run_jack.html) hosts the plugin in a JACK client and
+//! reads the parameters from the standard input. A program that runs a plugin only needs to call
+//! it in its main function, for example with `run_jack::<Amp>("amp", b"http://example.org/Amp\0")`.
+//!
+//! The parameters are listed at the start. Typing the symbol of a parameter followed by a value
+//! sets it, `list` lists them again and `quit` stops the runner.
+//!
+//! Plugins can also be previewed in web-based tooling: Built for `wasm32-unknown-unknown`, the
+//! [`wasm_exports`](macro.wasm_exports.html) macro turns a plugin into a WebAssembly module with a
+//! flat interface for JavaScript, which is implemented by the [`WasmShim`](struct.WasmShim.html).
+extern crate lv2rs_atom as atom;
+extern crate lv2rs_core as core;
+extern crate lv2rs_midi as midi;
+extern crate lv2rs_options as options;
+extern crate lv2rs_test_host as test_host;
+extern crate lv2rs_urid as urid;
+
+#[cfg(feature = "jack")]
+mod client;
+mod layout;
+mod parameters;
+mod runner;
+mod tui;
+mod wasm;
+
+#[cfg(feature = "jack")]
+pub use client::*;
+pub use layout::*;
+pub use parameters::*;
+pub use runner::*;
+pub use tui::*;
+pub use wasm::*;
@@ -0,0 +1,77 @@
+//! The values of the control inputs, which are shared between the user interface and the audio
+//! thread.
+use core::ttl::PortInfo;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// The values of a plugin's control inputs.
+///
+/// The values are stored in atomics, which lets the user interface set them while the audio
+/// thread reads them, without locking. Every value is clamped to the range of its port when it's
+/// set.
+pub struct Parameters {
+    ports: Vec<&'static PortInfo>,
+    values: Vec<AtomicU32>,
+}
+
+impl Parameters {
+    /// Create the parameters of control inputs, which start with their default values.
+    ///
+    /// Ports without a default start with their minimum, or zero if they don't have a minimum
+    /// either.
+    pub fn new(ports: &[&'static PortInfo]) -> Self {
+        let values = ports
+            .iter()
+            .map(|port| {
+                let value = port.default.or(port.minimum).unwrap_or(0.0);
+                AtomicU32::new(port.clamp(value).to_bits())
+            })
+            .collect();
+        Self {
+            ports: ports.to_vec(),
+            values,
+        }
+    }
+
+    /// Return the number of parameters.
+    pub fn len(&self) -> usize {
+        self.ports.len()
+    }
+
+    /// Return whether there are no parameters.
+    pub fn is_empty(&self) -> bool {
+        self.ports.is_empty()
+    }
+
+    /// Return the index of the parameter with the given symbol.
+    pub fn index(&self, symbol: &str) -> Option<usize> {
+        self.ports.iter().position(|port| port.symbol == symbol)
+    }
+
+    /// Return the port of a parameter.
+    pub fn port(&self, index: usize) -> Option<&'static PortInfo> {
+        self.ports.get(index).cloned()
+    }
+
+    /// Return the value of a parameter.
+    pub fn get(&self, index: usize) -> Option<f32> {
+        let value = self.values.get(index)?.load(Ordering::Relaxed);
+        Some(f32::from_bits(value))
+    }
+
+    /// Set the value of a parameter and return the value it was clamped to.
+    ///
+    /// `None` is returned if there is no parameter with the given index.
+    pub fn set(&self, index: usize, value: f32) -> Option<f32> {
+        let value = self.ports.get(index)?.clamp(value);
+        self.values[index].store(value.to_bits(), Ordering::Relaxed);
+        Some(value)
+    }
+
+    /// Iterate over the ports and the values of the parameters.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static PortInfo, f32)> + '_ {
+        self.ports
+            .iter()
+            .zip(self.values.iter())
+            .map(|(port, value)| (*port, f32::from_bits(value.load(Ordering::Relaxed))))
+    }
+}
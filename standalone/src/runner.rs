@@ -0,0 +1,199 @@
+//! A plugin instance with buffers for all of its ports.
+use crate::layout::PortLayout;
+use crate::parameters::Parameters;
+use atom::ports::AtomOutputPort;
+use atom::prelude::*;
+use atom::sequence::{TimeStamp, TimeUnit};
+use core::ports::PortKind;
+use midi::{MidiMessage, RawMidiMessage};
+use std::sync::Arc;
+use test_host::{TestHost, TestInstance};
+use urid::CachedMap;
+
+/// The capacity of the atom buffers, in bytes.
+const ATOM_CAPACITY: usize = 8192;
+
+/// A plugin instance that is run in blocks, independent of an audio backend.
+///
+/// The runner connects every port of the plugin, as declared by its
+/// [`PluginInfo`](../lv2rs_core/ttl/struct.PluginInfo.html), to a buffer with the maximal block
+/// length. Before every cycle, a backend fills the buffers of the inputs and writes the MIDI
+/// events, then calls [`run`](#method.run) and reads the outputs afterwards. The ports are
+/// addressed by their index in the lists of the [`PortLayout`](struct.PortLayout.html).
+pub struct Runner<'a, P: core::Plugin> {
+    instance: TestInstance<'a, P>,
+    layout: PortLayout,
+    parameters: Arc<Parameters>,
+    urids: CachedMap,
+    max_block_length: u32,
+}
+
+impl<'a, P: core::Plugin> Runner<'a, P> {
+    /// Instantiate a plugin and connect its ports.
+    ///
+    /// The URI is the null-terminated URI of the plugin. The maximal block length is passed to
+    /// the plugin as an option and has to be at least as big as the sample counts passed to
+    /// [`run`](#method.run). `None` is returned if the plugin doesn't provide its metadata in
+    /// [`Plugin::info`](../lv2rs_core/trait.Plugin.html#method.info) or refuses to instantiate.
+    pub fn new(host: &'a mut TestHost, uri: &'static [u8], max_block_length: u32) -> Option<Self> {
+        let info = P::info()?;
+        host.set_option(options::uris::MAX_BLOCK_LENGTH_URI, max_block_length as i32);
+        let host: &'a TestHost = host;
+        let mut instance = host.instantiate::<P>(uri)?;
+        let layout = PortLayout::new(info);
+        let parameters = Arc::new(Parameters::new(&layout.control_inputs));
+        for port in info.ports {
+            let index = port.index;
+            match port.kind {
+                PortKind::AudioInput
+                | PortKind::AudioOutput
+                | PortKind::CVInput
+                | PortKind::CVOutput => {
+                    instance.connect_audio(index, vec![0.0; max_block_length as usize])
+                }
+                PortKind::ControlInput => {
+                    instance.connect_control(index, port.clamp(port.default.unwrap_or(0.0)))
+                }
+                PortKind::ControlOutput => instance.connect_control(index, 0.0),
+                PortKind::AtomInput => instance.connect_atom_input(index, ATOM_CAPACITY),
+                PortKind::AtomOutput => instance.connect_atom_output(index, ATOM_CAPACITY),
+            }
+        }
+
+        let mut urids = unsafe { host.create_cached_map() };
+        // Map the URIs of the events in advance, which keeps the cycles free of allocations.
+        for uri in &[
+            atom::uris::SEQUENCE_TYPE_URI,
+            atom::uris::CHUNK_TYPE_URI,
+            atom::uris::FRAME_TIME_URI,
+            midi::uris::EVENT_URI,
+        ] {
+            urids.map(*uri);
+        }
+
+        Some(Self {
+            instance,
+            layout,
+            parameters,
+            urids,
+            max_block_length,
+        })
+    }
+
+    /// Return the ports of the plugin.
+    pub fn layout(&self) -> &PortLayout {
+        &self.layout
+    }
+
+    /// Return the values of the control inputs.
+    ///
+    /// They may be shared with another thread, which sets them while the plugin is run.
+    pub fn parameters(&self) -> &Arc<Parameters> {
+        &self.parameters
+    }
+
+    /// Return the maximal number of samples that may be passed to [`run`](#method.run).
+    pub fn max_block_length(&self) -> u32 {
+        self.max_block_length
+    }
+
+    /// Return the plugin instance.
+    pub fn instance(&mut self) -> &mut TestInstance<'a, P> {
+        &mut self.instance
+    }
+
+    /// Return the buffer of an audio input.
+    pub fn audio_input(&mut self, index: usize) -> Option<&mut [f32]> {
+        let port = self.layout.audio_inputs.get(index)?;
+        self.instance.audio_mut(port.index)
+    }
+
+    /// Return the buffer of an audio output.
+    pub fn audio_output(&self, index: usize) -> Option<&[f32]> {
+        let port = self.layout.audio_outputs.get(index)?;
+        self.instance.audio(port.index)
+    }
+
+    /// Return the value of a control output.
+    pub fn control_output(&self, index: usize) -> Option<f32> {
+        let port = self.layout.control_outputs.get(index)?;
+        self.instance.control(port.index)
+    }
+
+    /// Write the MIDI events of the next cycle to a MIDI input.
+    ///
+    /// The events are pairs of their frame in the cycle and their raw bytes, in chronological
+    /// order. Messages that aren't valid MIDI messages, like system exclusive messages, are
+    /// skipped. An error is returned if the input doesn't exist or its buffer is full; The events
+    /// written before are delivered anyway.
+    pub fn write_midi<'b, I>(&mut self, index: usize, events: I) -> Result<(), ()>
+    where
+        I: IntoIterator<Item = (u32, &'b [u8])>,
+    {
+        let port = self.layout.midi_inputs.get(index).ok_or(())?;
+        let mut writer: AtomOutputPort<Sequence> = AtomOutputPort::new();
+        writer.connect_port(self.instance.atom_input(port.index).ok_or(())?);
+        let mut sequence = unsafe { writer.write_atom_body(&TimeUnit::Frames, &mut self.urids) }
+            .map_err(|_| ())?;
+        for (frame, bytes) in events {
+            if let Ok(message) = MidiMessage::try_from(bytes) {
+                sequence.push_event::<RawMidiMessage>(
+                    TimeStamp::Frames(i64::from(frame)),
+                    &message,
+                    &mut self.urids,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read the MIDI events the plugin has written to a MIDI output in the last cycle.
+    ///
+    /// The handler is called with the frame and the raw bytes of every event. Events of other
+    /// types are skipped. An error is returned if the output doesn't exist or doesn't contain a
+    /// sequence.
+    pub fn read_midi<F: FnMut(u32, &[u8])>(
+        &mut self,
+        index: usize,
+        mut handler: F,
+    ) -> Result<(), ()> {
+        let port = self.layout.midi_outputs.get(index).ok_or(())?;
+        let atom = self.instance.atom(port.index).ok_or(())?;
+        let sequence = atom.get_body::<Sequence>(&mut self.urids).map_err(|_| ())?;
+        for (time, event) in sequence.iter(&mut self.urids) {
+            let frame = match time {
+                TimeStamp::Frames(frame) => frame.max(0) as u32,
+                TimeStamp::Beats(_) => 0,
+            };
+            if let Ok(message) = event.get_body::<RawMidiMessage>(&mut self.urids) {
+                handler(frame, message.as_bytes());
+            }
+        }
+        Ok(())
+    }
+
+    /// Run the plugin for a number of samples.
+    ///
+    /// The current values of the [parameters](#method.parameters) are copied to the control
+    /// inputs before the plugin is run. The sample count is limited to the maximal block length.
+    pub fn run(&mut self, n_samples: u32) {
+        for (port, value) in self
+            .layout
+            .control_inputs
+            .iter()
+            .zip(self.parameters.iter())
+        {
+            self.instance.set_control(port.index, value.1);
+        }
+        self.instance.run(n_samples.min(self.max_block_length));
+    }
+}
+
+impl<'a, P: core::Plugin> std::fmt::Debug for Runner<'a, P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Runner")
+            .field("layout", &self.layout)
+            .field("max_block_length", &self.max_block_length)
+            .finish()
+    }
+}
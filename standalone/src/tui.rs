@@ -0,0 +1,78 @@
+//! A line-based user interface for the parameters.
+use crate::parameters::Parameters;
+use std::io::{BufRead, Write};
+
+/// A command of the user interface.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Command {
+    /// List the parameters and their values.
+    List,
+    /// Set a parameter, given by its symbol, to a value.
+    Set(String, f32),
+    /// Stop the runner.
+    Quit,
+}
+
+impl Command {
+    /// Parse a line of input.
+    ///
+    /// `list` and `quit` are commands by themselves and a symbol followed by a value sets a
+    /// parameter. An empty line lists the parameters, too.
+    pub fn parse(line: &str) -> Result<Self, ()> {
+        let mut words = line.split_whitespace();
+        let command = match (words.next(), words.next()) {
+            (None, _) | (Some("list"), None) => Command::List,
+            (Some("quit"), None) => Command::Quit,
+            (Some(symbol), Some(value)) => {
+                Command::Set(symbol.to_owned(), value.parse().map_err(|_| ())?)
+            }
+            (Some(_), None) => return Err(()),
+        };
+        if words.next().is_some() {
+            return Err(());
+        }
+        Ok(command)
+    }
+}
+
+/// Write the parameters and their values, one per line.
+fn list<W: Write>(parameters: &Parameters, output: &mut W) -> std::io::Result<()> {
+    for (port, value) in parameters.iter() {
+        write!(output, "{} ({}) = {}", port.symbol, port.name, value)?;
+        if let Some(label) = port.scale_point_label(value) {
+            write!(output, " [{}]", label)?;
+        }
+        if let Some(unit) = port.unit {
+            write!(output, " {}", unit)?;
+        }
+        writeln!(output)?;
+    }
+    Ok(())
+}
+
+/// Read commands until the input ends or the user quits.
+///
+/// This is the user interface of the standalone runner, which reads from the standard input and
+/// writes to the standard output, but it works with any input and output.
+pub fn run_tui<R: BufRead, W: Write>(
+    parameters: &Parameters,
+    input: R,
+    mut output: W,
+) -> std::io::Result<()> {
+    list(parameters, &mut output)?;
+    for line in input.lines() {
+        match Command::parse(&line?) {
+            Ok(Command::List) => list(parameters, &mut output)?,
+            Ok(Command::Set(symbol, value)) => match parameters.index(&symbol) {
+                Some(index) => {
+                    let value = parameters.set(index, value).unwrap();
+                    writeln!(output, "{} = {}", symbol, value)?;
+                }
+                None => writeln!(output, "Unknown parameter {}", symbol)?,
+            },
+            Ok(Command::Quit) => break,
+            Err(()) => writeln!(output, "Commands: list, quit or <symbol> <value>")?,
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,227 @@
+//! A host shim for running plugins in a browser.
+use crate::runner::Runner;
+use std::mem::ManuallyDrop;
+use test_host::TestHost;
+
+/// The maximal number of MIDI events per input and cycle.
+const MIDI_CAPACITY: usize = 256;
+
+/// A plugin instance that is controlled through a flat, C-compatible interface.
+///
+/// WebAssembly modules can only exchange numbers with JavaScript, which is why the shim hands
+/// out raw pointers to the audio buffers, which are views into the module's memory, and takes
+/// MIDI messages byte by byte. The events of a MIDI input are collected until the next call of
+/// [`run`](#method.run). The [`wasm_exports`](macro.wasm_exports.html) macro exports the shim
+/// of a plugin as functions of the module.
+///
+/// The shim doesn't depend on WebAssembly and can be used by other embeddings, too.
+pub struct WasmShim<P: core::Plugin + 'static> {
+    runner: ManuallyDrop<Runner<'static, P>>,
+    host: *mut TestHost,
+    midi: Vec<Vec<(u32, [u8; 3], usize)>>,
+}
+
+impl<P: core::Plugin + 'static> WasmShim<P> {
+    /// Instantiate a plugin, see [`Runner::new`](struct.Runner.html#method.new).
+    pub fn new(uri: &'static [u8], sample_rate: f64, max_block_length: u32) -> Option<Self> {
+        let host = Box::into_raw(Box::new(TestHost::new(sample_rate)));
+        // The host is freed after the runner when the shim is dropped.
+        let runner = match Runner::new(unsafe { &mut *host }, uri, max_block_length) {
+            Some(runner) => runner,
+            None => {
+                drop(unsafe { Box::from_raw(host) });
+                return None;
+            }
+        };
+        let midi = runner
+            .layout()
+            .midi_inputs
+            .iter()
+            .map(|_| Vec::with_capacity(MIDI_CAPACITY))
+            .collect();
+        Some(Self {
+            runner: ManuallyDrop::new(runner),
+            host,
+            midi,
+        })
+    }
+
+    /// Return the runner of the plugin.
+    pub fn runner(&mut self) -> &mut Runner<'static, P> {
+        &mut self.runner
+    }
+
+    /// Return a pointer to the buffer of an audio input, or null if there is no such input.
+    ///
+    /// The buffer is as long as the maximal block length.
+    pub fn audio_input_ptr(&mut self, index: usize) -> *mut f32 {
+        match self.runner.audio_input(index) {
+            Some(buffer) => buffer.as_mut_ptr(),
+            None => std::ptr::null_mut(),
+        }
+    }
+
+    /// Return a pointer to the buffer of an audio output, or null if there is no such output.
+    pub fn audio_output_ptr(&self, index: usize) -> *const f32 {
+        match self.runner.audio_output(index) {
+            Some(buffer) => buffer.as_ptr(),
+            None => std::ptr::null(),
+        }
+    }
+
+    /// Queue a MIDI message of up to three bytes for the next cycle.
+    ///
+    /// `false` is returned if the input doesn't exist, the message is too long or the queue of
+    /// the input is full.
+    pub fn push_midi(&mut self, index: usize, frame: u32, bytes: &[u8]) -> bool {
+        let queue = match self.midi.get_mut(index) {
+            Some(queue) => queue,
+            None => return false,
+        };
+        if bytes.len() > 3 || queue.len() >= MIDI_CAPACITY {
+            return false;
+        }
+        let mut message = [0; 3];
+        message[..bytes.len()].copy_from_slice(bytes);
+        queue.push((frame, message, bytes.len()));
+        true
+    }
+
+    /// Deliver the queued MIDI messages and run the plugin for a number of samples.
+    pub fn run(&mut self, n_samples: u32) {
+        for (index, queue) in self.midi.iter_mut().enumerate() {
+            queue.sort_by_key(|event| event.0);
+            let events = queue
+                .iter()
+                .map(|(frame, message, length)| (*frame, &message[..*length]));
+            let _ = self.runner.write_midi(index, events);
+            queue.clear();
+        }
+        self.runner.run(n_samples);
+    }
+}
+
+impl<P: core::Plugin + 'static> Drop for WasmShim<P> {
+    fn drop(&mut self) {
+        unsafe {
+            ManuallyDrop::drop(&mut self.runner);
+            drop(Box::from_raw(self.host));
+        }
+    }
+}
+
+/// Export the shim of a plugin as functions of a WebAssembly module.
+///
+/// The first argument is the plugin type and the second one its null-terminated URI. The module
+/// hosts a single instance of the plugin, which is controlled with the following functions:
+///
+/// * `lv2rs_create(sample_rate: f64, max_block_length: u32) -> bool` instantiates the plugin and
+///   replaces an existing instance.
+/// * `lv2rs_destroy()` drops the instance.
+/// * `lv2rs_audio_input(index: u32) -> *mut f32` and `lv2rs_audio_output(index: u32) -> *const f32`
+///   return the buffers of the audio ports, see
+///   [`WasmShim::audio_input_ptr`](struct.WasmShim.html#method.audio_input_ptr).
+/// * `lv2rs_parameter_count() -> u32`, `lv2rs_get_parameter(index: u32) -> f32` and
+///   `lv2rs_set_parameter(index: u32, value: f32) -> f32` access the parameters. Setting a
+///   parameter returns the clamped value. For unknown parameters, `NaN` is returned.
+/// * `lv2rs_push_midi(index: u32, frame: u32, status: u8, data1: u8, data2: u8, length: u32) ->
+///   bool` queues a MIDI message.
+/// * `lv2rs_run(n_samples: u32)` runs the plugin.
+///
+/// Without an instance, the functions return null, zero, `NaN` or `false`. The module can be
+/// built with `cargo build --target wasm32-unknown-unknown` and used from JavaScript with
+/// `WebAssembly.instantiate`.
+#[macro_export]
+macro_rules! wasm_exports {
+    ($plugin:ty, $uri:expr) => {
+        thread_local! {
+            static LV2RS_SHIM: ::std::cell::RefCell<Option<$crate::WasmShim<$plugin>>> =
+                ::std::cell::RefCell::new(None);
+        }
+
+        fn lv2rs_with_shim<R, F: FnOnce(&mut $crate::WasmShim<$plugin>) -> R>(
+            default: R,
+            f: F,
+        ) -> R {
+            LV2RS_SHIM.with(|shim| match shim.borrow_mut().as_mut() {
+                Some(shim) => f(shim),
+                None => default,
+            })
+        }
+
+        #[no_mangle]
+        pub extern "C" fn lv2rs_create(sample_rate: f64, max_block_length: u32) -> bool {
+            let shim = $crate::WasmShim::<$plugin>::new($uri, sample_rate, max_block_length);
+            let created = shim.is_some();
+            LV2RS_SHIM.with(|cell| *cell.borrow_mut() = shim);
+            created
+        }
+
+        #[no_mangle]
+        pub extern "C" fn lv2rs_destroy() {
+            LV2RS_SHIM.with(|cell| *cell.borrow_mut() = None);
+        }
+
+        #[no_mangle]
+        pub extern "C" fn lv2rs_audio_input(index: u32) -> *mut f32 {
+            lv2rs_with_shim(::std::ptr::null_mut(), |shim| {
+                shim.audio_input_ptr(index as usize)
+            })
+        }
+
+        #[no_mangle]
+        pub extern "C" fn lv2rs_audio_output(index: u32) -> *const f32 {
+            lv2rs_with_shim(::std::ptr::null(), |shim| {
+                shim.audio_output_ptr(index as usize)
+            })
+        }
+
+        #[no_mangle]
+        pub extern "C" fn lv2rs_parameter_count() -> u32 {
+            lv2rs_with_shim(0, |shim| shim.runner().parameters().len() as u32)
+        }
+
+        #[no_mangle]
+        pub extern "C" fn lv2rs_get_parameter(index: u32) -> f32 {
+            lv2rs_with_shim(::std::f32::NAN, |shim| {
+                shim.runner()
+                    .parameters()
+                    .get(index as usize)
+                    .unwrap_or(::std::f32::NAN)
+            })
+        }
+
+        #[no_mangle]
+        pub extern "C" fn lv2rs_set_parameter(index: u32, value: f32) -> f32 {
+            lv2rs_with_shim(::std::f32::NAN, |shim| {
+                shim.runner()
+                    .parameters()
+                    .set(index as usize, value)
+                    .unwrap_or(::std::f32::NAN)
+            })
+        }
+
+        #[no_mangle]
+        pub extern "C" fn lv2rs_push_midi(
+            index: u32,
+            frame: u32,
+            status: u8,
+            data1: u8,
+            data2: u8,
+            length: u32,
+        ) -> bool {
+            let message = [status, data1, data2];
+            match message.get(..length as usize) {
+                Some(bytes) => {
+                    lv2rs_with_shim(false, |shim| shim.push_midi(index as usize, frame, bytes))
+                }
+                None => false,
+            }
+        }
+
+        #[no_mangle]
+        pub extern "C" fn lv2rs_run(n_samples: u32) {
+            lv2rs_with_shim((), |shim| shim.run(n_samples))
+        }
+    };
+}
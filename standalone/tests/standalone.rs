@@ -0,0 +1,285 @@
+extern crate lv2rs_atom as atom;
+extern crate lv2rs_core as core;
+extern crate lv2rs_midi as midi;
+extern crate lv2rs_standalone as standalone;
+extern crate lv2rs_test_host as test_host;
+extern crate lv2rs_urid as urid;
+
+use atom::ports::{AtomInputPort, AtomOutputPort};
+use atom::prelude::*;
+use atom::sequence::TimeUnit;
+use core::ports::*;
+use core::ttl::*;
+use midi::prelude::u7;
+use midi::{MidiMessage, RawMidiMessage};
+use standalone::*;
+use std::io::Cursor;
+use test_host::TestHost;
+use urid::CachedMap;
+
+#[derive(Ports)]
+struct TransposerPorts {
+    #[port(name = "Gain", default = 1.0, minimum = 0.0, maximum = 2.0)]
+    gain: ParameterInputPort,
+    #[port(name = "Input")]
+    input: AudioInputPort,
+    #[port(name = "Output")]
+    output: AudioOutputPort,
+    #[port(
+        name = "Notes in",
+        buffer_type = "Sequence",
+        supports = "http://lv2plug.in/ns/ext/midi#MidiEvent"
+    )]
+    notes_in: AtomInputPort<Sequence>,
+    #[port(
+        name = "Notes out",
+        buffer_type = "Sequence",
+        supports = "http://lv2plug.in/ns/ext/midi#MidiEvent"
+    )]
+    notes_out: AtomOutputPort<Sequence>,
+    #[port(name = "Events", buffer_type = "Sequence")]
+    events: AtomInputPort<Sequence>,
+}
+
+const TRANSPOSER: PluginInfo = PluginInfo::new(
+    "http://example.org/Transposer",
+    "Transposer",
+    TransposerPorts::PORTS,
+);
+
+/// A plugin that amplifies its audio input and transposes its notes by an octave.
+struct Transposer {
+    ports: TransposerPorts,
+    urids: CachedMap,
+}
+
+impl core::Plugin for Transposer {
+    fn instantiate(
+        _context: &core::InstantiationContext,
+        features: Option<&core::FeaturesList>,
+    ) -> Result<Self, core::InstantiateError> {
+        let urids = features
+            .and_then(CachedMap::try_from_features)
+            .ok_or_else(core::InstantiateError::missing_feature::<urid::Map>)?;
+        Ok(Self {
+            ports: TransposerPorts {
+                gain: ParameterInputPort::new(),
+                input: AudioInputPort::new(),
+                output: AudioOutputPort::new(),
+                notes_in: AtomInputPort::new(),
+                notes_out: AtomOutputPort::new(),
+                events: AtomInputPort::new(),
+            },
+            urids,
+        })
+    }
+
+    fn info() -> Option<&'static PluginInfo> {
+        Some(&TRANSPOSER)
+    }
+
+    fn connect_port(&mut self, port: u32, data: *mut ()) {
+        self.ports.connect_port(port, data);
+    }
+
+    fn run(&mut self, context: &mut core::RunContext) {
+        let gain = *unsafe { self.ports.gain.get() }.unwrap();
+        let input = unsafe { self.ports.input.as_slice(context.n_samples()) }.unwrap();
+        let output = unsafe { self.ports.output.as_slice(context.n_samples()) }.unwrap();
+        for (output, input) in output.iter_mut().zip(input) {
+            *output = input * gain;
+        }
+
+        let urids = &mut self.urids;
+        let notes_in = unsafe { self.ports.notes_in.read(context, urids) }.unwrap();
+        let mut notes_out = unsafe {
+            self.ports
+                .notes_out
+                .write_atom_body(&TimeUnit::Frames, urids)
+        }
+        .unwrap();
+        for (time, event) in notes_in.iter(urids) {
+            let message = match event.get_body::<RawMidiMessage>(urids) {
+                Ok(message) => message.interpret().unwrap(),
+                Err(_) => continue,
+            };
+            let message = match message {
+                MidiMessage::NoteOn {
+                    channel,
+                    note,
+                    velocity,
+                } => MidiMessage::NoteOn {
+                    channel,
+                    note: note + u7::new(12),
+                    velocity,
+                },
+                message => message,
+            };
+            notes_out
+                .push_event::<RawMidiMessage>(time, &message, urids)
+                .unwrap();
+        }
+    }
+}
+
+#[test]
+fn test_layout() {
+    let layout = PortLayout::new(&TRANSPOSER);
+    let symbols =
+        |ports: &[&PortInfo]| -> Vec<&str> { ports.iter().map(|port| port.symbol).collect() };
+    assert_eq!(vec!["input"], symbols(&layout.audio_inputs));
+    assert_eq!(vec!["output"], symbols(&layout.audio_outputs));
+    assert_eq!(vec!["notes_in"], symbols(&layout.midi_inputs));
+    assert_eq!(vec!["notes_out"], symbols(&layout.midi_outputs));
+    assert_eq!(vec!["gain"], symbols(&layout.control_inputs));
+    assert!(layout.control_outputs.is_empty());
+    assert!(!supports_midi(&TRANSPOSER.ports[5]));
+}
+
+#[test]
+fn test_parameters() {
+    let layout = PortLayout::new(&TRANSPOSER);
+    let parameters = Parameters::new(&layout.control_inputs);
+    assert_eq!(1, parameters.len());
+    assert_eq!(Some(0), parameters.index("gain"));
+    assert_eq!(None, parameters.index("input"));
+    assert_eq!(Some(1.0), parameters.get(0));
+
+    assert_eq!(Some(2.0), parameters.set(0, 3.0));
+    assert_eq!(Some(2.0), parameters.get(0));
+    assert_eq!(None, parameters.set(1, 3.0));
+}
+
+#[test]
+fn test_tui() {
+    assert_eq!(Ok(Command::List), Command::parse(""));
+    assert_eq!(Ok(Command::Quit), Command::parse(" quit "));
+    assert_eq!(
+        Ok(Command::Set("gain".to_owned(), 0.5)),
+        Command::parse("gain 0.5")
+    );
+    assert_eq!(Err(()), Command::parse("gain"));
+    assert_eq!(Err(()), Command::parse("gain loud"));
+    assert_eq!(Err(()), Command::parse("gain 0.5 1.0"));
+
+    let layout = PortLayout::new(&TRANSPOSER);
+    let parameters = Parameters::new(&layout.control_inputs);
+    let input = Cursor::new("gain 0.5\nvolume 1\ngain\nlist\nquit\ngain 1.5\n");
+    let mut output = Vec::new();
+    run_tui(&parameters, input, &mut output).unwrap();
+    assert_eq!(
+        "gain (Gain) = 1\n\
+         gain = 0.5\n\
+         Unknown parameter volume\n\
+         Commands: list, quit or <symbol> <value>\n\
+         gain (Gain) = 0.5\n",
+        String::from_utf8(output).unwrap()
+    );
+    assert_eq!(Some(0.5), parameters.get(0));
+}
+
+#[test]
+fn test_runner() {
+    let mut host = TestHost::new(44100.0);
+    let mut runner =
+        Runner::<Transposer>::new(&mut host, b"http://example.org/Transposer\0", 4).unwrap();
+    assert_eq!(4, runner.max_block_length());
+
+    runner
+        .audio_input(0)
+        .unwrap()
+        .copy_from_slice(&[1.0, 2.0, 3.0, 4.0]);
+    runner.parameters().set(0, 0.5);
+    let events: [(u32, &[u8]); 3] = [
+        (1, &[0x90, 60, 100]),
+        (2, &[0xf0, 0x01, 0xf7]),
+        (3, &[0x80, 60, 0]),
+    ];
+    runner.write_midi(0, events.iter().cloned()).unwrap();
+    assert!(runner.write_midi(1, Vec::new()).is_err());
+    runner.run(4);
+
+    assert_eq!(&[0.5, 1.0, 1.5, 2.0], runner.audio_output(0).unwrap());
+    let mut received = Vec::new();
+    runner
+        .read_midi(0, |time, bytes| received.push((time, bytes.to_owned())))
+        .unwrap();
+    assert_eq!(
+        vec![(1, vec![0x90, 72, 100]), (3, vec![0x80, 60, 0])],
+        received
+    );
+    assert_eq!(Some(0.5), runner.instance().control(TransposerPorts::GAIN));
+}
+
+#[test]
+fn test_runner_without_info() {
+    struct Silent;
+
+    impl core::Plugin for Silent {
+        fn instantiate(
+            _context: &core::InstantiationContext,
+            _features: Option<&core::FeaturesList>,
+        ) -> Result<Self, core::InstantiateError> {
+            Ok(Silent)
+        }
+
+        fn connect_port(&mut self, _port: u32, _data: *mut ()) {}
+
+        fn run(&mut self, _context: &mut core::RunContext) {}
+    }
+
+    let mut host = TestHost::new(44100.0);
+    assert!(Runner::<Silent>::new(&mut host, b"http://example.org/Silent\0", 4).is_none());
+}
+
+standalone::wasm_exports!(Transposer, b"http://example.org/Transposer\0");
+
+#[test]
+fn test_wasm_exports() {
+    assert!(!lv2rs_push_midi(0, 0, 0x90, 60, 100, 3));
+    assert!(lv2rs_audio_input(0).is_null());
+    assert!(lv2rs_create(44100.0, 4));
+    assert_eq!(1, lv2rs_parameter_count());
+    assert_eq!(1.0, lv2rs_get_parameter(0));
+    assert_eq!(2.0, lv2rs_set_parameter(0, 5.0));
+    assert!(lv2rs_set_parameter(1, 5.0).is_nan());
+    assert!(lv2rs_audio_input(1).is_null());
+
+    let input = unsafe { std::slice::from_raw_parts_mut(lv2rs_audio_input(0), 4) };
+    input.copy_from_slice(&[1.0, 2.0, 3.0, 4.0]);
+    assert!(lv2rs_push_midi(0, 2, 0x90, 64, 100, 3));
+    assert!(!lv2rs_push_midi(0, 2, 0x90, 64, 100, 4));
+    assert!(!lv2rs_push_midi(1, 2, 0x90, 64, 100, 3));
+    lv2rs_run(4);
+    let output = unsafe { std::slice::from_raw_parts(lv2rs_audio_output(0), 4) };
+    assert_eq!(&[2.0, 4.0, 6.0, 8.0], output);
+
+    lv2rs_destroy();
+    assert!(lv2rs_get_parameter(0).is_nan());
+}
+
+#[test]
+fn test_wasm_shim() {
+    let mut shim =
+        WasmShim::<Transposer>::new(b"http://example.org/Transposer\0", 44100.0, 4).unwrap();
+    assert!(shim.push_midi(0, 3, &[0x80, 60, 0]));
+    assert!(shim.push_midi(0, 1, &[0x90, 60, 100]));
+    shim.run(4);
+
+    let mut received = Vec::new();
+    shim.runner()
+        .read_midi(0, |time, bytes| received.push((time, bytes.to_owned())))
+        .unwrap();
+    assert_eq!(
+        vec![(1, vec![0x90, 72, 100]), (3, vec![0x80, 60, 0])],
+        received
+    );
+
+    // The queue is emptied by every cycle.
+    shim.run(4);
+    received.clear();
+    shim.runner()
+        .read_midi(0, |time, bytes| received.push((time, bytes.to_owned())))
+        .unwrap();
+    assert!(received.is_empty());
+}
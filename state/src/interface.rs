@@ -0,0 +1,157 @@
+//! The state interface and the trait to implement it.
+use crate::property::*;
+
+/// A plugin that can save and restore its state.
+///
+/// `save` may be called concurrently with `run`, which is why it only takes a shared reference to
+/// the plugin: Everything that needs to be saved should be accessible without mutation, for
+/// example as atomics or behind a lock. `restore` on the other hand is called in the instantiation
+/// threading class, so it may mutate the plugin freely.
+///
+/// Both methods receive the flags of the operation and the features the host provides for it,
+/// like the [map path feature](path/struct.MapPath.html).
+pub trait StateHandler: core::Plugin {
+    /// Save the state of the plugin.
+    fn save(
+        &self,
+        store: &mut StoreHandle,
+        flags: StateFlags,
+        features: Option<&core::FeaturesList>,
+    ) -> Result<(), StateErr>;
+
+    /// Restore the state of the plugin.
+    fn restore(
+        &mut self,
+        retrieve: &RetrieveHandle,
+        flags: StateFlags,
+        features: Option<&core::FeaturesList>,
+    ) -> Result<(), StateErr>;
+}
+
+/// A plugin whose state can be restored concurrently with `run`.
+///
+/// Hosts usually have to stop processing to restore the state of a plugin. If a plugin lists the
+/// [thread-safe restore feature](uris/constant.THREAD_SAFE_RESTORE_URI.html) as an optional
+/// feature and returns a state interface created with
+/// [`with_thread_safe_restore`](struct.StateInterface.html#method.with_thread_safe_restore),
+/// hosts may call [`restore_concurrently`](#tymethod.restore_concurrently) from any thread while
+/// the plugin is running instead. Just like `save`, it only takes a shared reference; The plugin
+/// has to hand the restored state over to the audio thread itself, for example with a
+/// [`FileRef`](path/struct.FileRef.html) behind a lock or via the worker.
+pub trait ThreadSafeRestore: StateHandler {
+    /// Restore the state of the plugin while it may be running.
+    fn restore_concurrently(
+        &self,
+        retrieve: &RetrieveHandle,
+        flags: StateFlags,
+        features: Option<&core::FeaturesList>,
+    ) -> Result<(), StateErr>;
+}
+
+/// The raw state interface.
+///
+/// An instance of this struct, created with [`new`](#method.new), has to be returned by the
+/// plugin's `extension_data` function for the [interface URI](uris/constant.INTERFACE_URI.html).
+#[repr(C)]
+pub struct StateInterface {
+    /// Save the state of an instance.
+    pub save: unsafe extern "C" fn(
+        instance: core::Handle,
+        store: StoreFunction,
+        handle: StateHandle,
+        flags: u32,
+        features: *const *const core::Feature,
+    ) -> u32,
+    /// Restore the state of an instance.
+    pub restore: unsafe extern "C" fn(
+        instance: core::Handle,
+        retrieve: RetrieveFunction,
+        handle: StateHandle,
+        flags: u32,
+        features: *const *const core::Feature,
+    ) -> u32,
+}
+
+impl core::ExtensionData for StateInterface {}
+
+impl StateInterface {
+    /// Create the state interface for a plugin type.
+    pub const fn new<P: StateHandler>() -> Self {
+        Self {
+            save: save::<P>,
+            restore: restore::<P>,
+        }
+    }
+
+    /// Create the state interface for a plugin type that supports thread-safe restore.
+    ///
+    /// The host's restore calls are forwarded to
+    /// [`restore_concurrently`](trait.ThreadSafeRestore.html#tymethod.restore_concurrently).
+    pub const fn with_thread_safe_restore<P: ThreadSafeRestore>() -> Self {
+        Self {
+            save: save::<P>,
+            restore: restore_concurrently::<P>,
+        }
+    }
+}
+
+unsafe extern "C" fn save<P: StateHandler>(
+    instance: core::Handle,
+    store: StoreFunction,
+    handle: StateHandle,
+    flags: u32,
+    features: *const *const core::Feature,
+) -> u32 {
+    let mut store = StoreHandle::new(store, handle);
+    let features = core::Features::from_raw(features).map(|features| features.as_list());
+    core::guard(
+        instance,
+        "save",
+        StateErr::Unknown as u32,
+        |plugin: &mut P| {
+            StateErr::into_status(plugin.save(&mut store, StateFlags(flags), features))
+        },
+    )
+}
+
+unsafe extern "C" fn restore<P: StateHandler>(
+    instance: core::Handle,
+    retrieve: RetrieveFunction,
+    handle: StateHandle,
+    flags: u32,
+    features: *const *const core::Feature,
+) -> u32 {
+    let retrieve = RetrieveHandle::new(retrieve, handle);
+    let features = core::Features::from_raw(features).map(|features| features.as_list());
+    core::guard(
+        instance,
+        "restore",
+        StateErr::Unknown as u32,
+        |plugin: &mut P| {
+            StateErr::into_status(plugin.restore(&retrieve, StateFlags(flags), features))
+        },
+    )
+}
+
+unsafe extern "C" fn restore_concurrently<P: ThreadSafeRestore>(
+    instance: core::Handle,
+    retrieve: RetrieveFunction,
+    handle: StateHandle,
+    flags: u32,
+    features: *const *const core::Feature,
+) -> u32 {
+    let retrieve = RetrieveHandle::new(retrieve, handle);
+    let features = core::Features::from_raw(features).map(|features| features.as_list());
+    core::guard(
+        instance,
+        "restore",
+        StateErr::Unknown as u32,
+        |plugin: &mut P| {
+            StateErr::into_status(plugin.restore_concurrently(
+                &retrieve,
+                StateFlags(flags),
+                features,
+            ))
+        },
+    )
+}
@@ -0,0 +1,101 @@
+//! Saving and restoring the state of plugins.
+//!
+//! This crate implements the [LV2 state extension](http://lv2plug.in/ns/ext/state/state.html):
+//! A plugin that implements the [`StateHandler`](trait.StateHandler.html) trait can save its
+//! internal state as a set of properties and restore it again, for example when a session is
+//! loaded. Properties are stored via a [`StoreHandle`](struct.StoreHandle.html) and retrieved via
+//! a [`RetrieveHandle`](struct.RetrieveHandle.html); Both have typed methods for scalars, strings,
+//! atoms and file paths. The latter are mapped to portable paths with a
+//! [`PathMapper`](path/struct.PathMapper.html). Plugins that can restore their state while they
+//! are running implement [`ThreadSafeRestore`](trait.ThreadSafeRestore.html) as well.
+//! Presets, which combine a saved state with port values, are handled by the
+//! [`preset`](preset/index.html) module and parameters, which are controlled with patch messages,
+//! by the [`parameters`](parameters/index.html) module. The [`morph`](morph/index.html) module
+//! interpolates parameters between two saved states, for example to morph between presets.
+//!
+//! The plugin has to return a [`StateInterface`](struct.StateInterface.html) for the
+//! [interface URI](uris/constant.INTERFACE_URI.html) in its `extension_data` function:
+//!
+//!     extern crate lv2rs_atom as atom;
+//!     extern crate lv2rs_core as core;
+//!     extern crate lv2rs_state as state;
+//!     extern crate lv2rs_urid as urid;
+//!
+//!     use atom::uris::AtomURIDs;
+//!     use state::*;
+//!     use std::ffi::CStr;
+//!     use urid::{CachedMap, URID};
+//!
+//!     struct Delay {
+//!         urids: CachedMap,
+//!         delay_key: URID,
+//!         delay: f32,
+//!     }
+//!
+//!     impl core::Plugin for Delay {
+//!         fn instantiate(
+//!             _context: &core::InstantiationContext,
+//!             features: Option<&core::FeaturesList>,
+//!         ) -> Result<Self, core::InstantiateError> {
+//!             let mut urids = features
+//!                 .and_then(CachedMap::try_from_features)
+//!                 .ok_or_else(core::InstantiateError::missing_feature::<urid::Map>)?;
+//!             // The types of the stored properties have to be mapped in advance.
+//!             AtomURIDs::new(&mut urids);
+//!             let delay_key = urids.map(
+//!                 CStr::from_bytes_with_nul(b"https://example.org/delay#delay\0").unwrap(),
+//!             );
+//!             Ok(Self {
+//!                 urids,
+//!                 delay_key,
+//!                 delay: 0.5,
+//!             })
+//!         }
+//!
+//!         fn connect_port(&mut self, _port: u32, _data: *mut ()) {}
+//!
+//!         fn run(&mut self, _context: &mut core::RunContext) {}
+//!
+//!         fn extension_data(uri: &CStr) -> Option<&'static core::ExtensionData> {
+//!             core::extension_data!(uri, [(uris::INTERFACE_URI, &STATE_INTERFACE)])
+//!         }
+//!     }
+//!
+//!     impl StateHandler for Delay {
+//!         fn save(
+//!             &self,
+//!             store: &mut StoreHandle,
+//!             _flags: StateFlags,
+//!             _features: Option<&core::FeaturesList>,
+//!         ) -> Result<(), StateErr> {
+//!             store.store_scalar(self.delay_key, self.delay, &self.urids)
+//!         }
+//!
+//!         fn restore(
+//!             &mut self,
+//!             retrieve: &RetrieveHandle,
+//!             _flags: StateFlags,
+//!             _features: Option<&core::FeaturesList>,
+//!         ) -> Result<(), StateErr> {
+//!             self.delay = retrieve.retrieve_scalar(self.delay_key, &self.urids)?;
+//!             Ok(())
+//!         }
+//!     }
+//!
+//!     static STATE_INTERFACE: StateInterface = StateInterface::new::<Delay>();
+//!
+//!     # fn main() {}
+extern crate lv2rs_atom as atom;
+extern crate lv2rs_core as core;
+extern crate lv2rs_urid as urid;
+
+mod interface;
+pub mod morph;
+pub mod parameters;
+pub mod path;
+pub mod preset;
+mod property;
+pub mod uris;
+
+pub use interface::*;
+pub use property::*;
@@ -0,0 +1,208 @@
+//! Morphing between two sets of parameter values.
+//!
+//! A [`Morph`](struct.Morph.html) interpolates the [parameters](../parameters/index.html) of a
+//! plugin from one saved state to another over a number of frames, for example from the current
+//! values to the values of a [preset](../preset/index.html). The plugin advances the morph in
+//! `run` and either applies the interpolated values to its parameters directly, which also
+//! schedules their notifications, or writes them as `patch:Set` messages:
+//!
+//!     extern crate lv2rs_atom as atom;
+//!     extern crate lv2rs_core as core;
+//!     extern crate lv2rs_state as state;
+//!     extern crate lv2rs_urid as urid;
+//!
+//!     use core::uris::Uri;
+//!     use state::morph::Morph;
+//!     use state::parameters::*;
+//!     use urid::debug::DebugMap;
+//!
+//!     const PARAMETERS: &[ParameterInfo] = &[ParameterInfo::new(
+//!         Uri::new(b"http://example.org/Amp#gain\0"),
+//!         "Gain",
+//!         ParameterValue::Float(0.0),
+//!     )];
+//!
+//!     let mut debug_map = DebugMap::new();
+//!     let mut urids = unsafe { debug_map.create_cached_map() };
+//!     let parameters = Parameters::new(PARAMETERS, &mut urids);
+//!
+//!     // The target state has a gain of -12 dB.
+//!     parameters.set(0, ParameterValue::Float(-12.0));
+//!     let target = parameters.values();
+//!     parameters.reset();
+//!
+//!     // Morph from the current values to the target within 100 frames.
+//!     let mut morph = Morph::new(&parameters, parameters.values(), target, 100).unwrap();
+//!     morph.advance(25);
+//!     assert!(morph.apply(&parameters));
+//!     assert_eq!(Some(ParameterValue::Float(-3.0)), parameters.get(0));
+//!
+//!     morph.advance(100);
+//!     morph.apply(&parameters);
+//!     assert!(morph.is_finished());
+//!     assert_eq!(Some(ParameterValue::Float(-12.0)), parameters.get(0));
+//!
+//! The values of a preset are read with
+//! [`Parameters::preset_values`](../parameters/struct.Parameters.html#method.preset_values) and
+//! the values of any other saved state with
+//! [`Parameters::retrieve_values`](../parameters/struct.Parameters.html#method.retrieve_values).
+use crate::parameters::*;
+use atom::patch::PatchURIDs;
+use atom::prelude::*;
+use atom::sequence::{SequenceWritingFrame, TimeStamp};
+use urid::{CachedMap, URID};
+
+/// An interpolation of parameter values over time.
+///
+/// The position of the morph is counted in frames, from zero to the duration. Every parameter is
+/// interpolated with [`ParameterValue::interpolate`](../parameters/enum.ParameterValue.html#method.interpolate),
+/// which means that integers are rounded and booleans switch halfway.
+///
+/// The morph remembers the values it applied or wrote last, so only changed values are applied
+/// or written again.
+pub struct Morph {
+    from: Vec<ParameterValue>,
+    to: Vec<ParameterValue>,
+    keys: Vec<URID>,
+    last: Vec<Option<ParameterValue>>,
+    duration: u64,
+    position: u64,
+    patch_urids: PatchURIDs,
+}
+
+impl Morph {
+    /// Create a morph between two sets of values of the given parameters.
+    ///
+    /// The sets have to contain one value of the right type for every parameter, as they are
+    /// returned by [`Parameters::values`](../parameters/struct.Parameters.html#method.values)
+    /// or [`Parameters::preset_values`](../parameters/struct.Parameters.html#method.preset_values).
+    /// Otherwise, `None` is returned. A duration of zero jumps to the target values immediately.
+    pub fn new(
+        parameters: &Parameters,
+        from: Vec<ParameterValue>,
+        to: Vec<ParameterValue>,
+        duration: u64,
+    ) -> Option<Self> {
+        let infos = parameters.infos();
+        if from.len() != infos.len() || to.len() != infos.len() {
+            return None;
+        }
+        let types_match = infos
+            .iter()
+            .zip(from.iter().zip(to.iter()))
+            .all(|(info, (from, to))| info.default.same_type(*from) && info.default.same_type(*to));
+        if !types_match {
+            return None;
+        }
+
+        Some(Self {
+            keys: (0..infos.len())
+                .map(|index| parameters.key(index).unwrap())
+                .collect(),
+            last: vec![None; infos.len()],
+            from,
+            to,
+            duration,
+            position: 0,
+            patch_urids: *parameters.patch_urids(),
+        })
+    }
+
+    /// Return the duration of the morph, in frames.
+    pub fn duration(&self) -> u64 {
+        self.duration
+    }
+
+    /// Return the current position of the morph, in frames.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Set the position of the morph, for example to go back to the start.
+    ///
+    /// The position is limited to the duration.
+    pub fn set_position(&mut self, position: u64) {
+        self.position = position.min(self.duration);
+    }
+
+    /// Move the position of the morph forward, usually by the number of frames of a `run` call.
+    pub fn advance(&mut self, n_frames: u64) {
+        self.set_position(self.position.saturating_add(n_frames));
+    }
+
+    /// Return the progress of the morph, between `0.0` at the start and `1.0` at the end.
+    pub fn progress(&self) -> f64 {
+        if self.duration == 0 {
+            1.0
+        } else {
+            self.position as f64 / self.duration as f64
+        }
+    }
+
+    /// Return `true` if the morph reached its target values.
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.duration
+    }
+
+    /// Return the interpolated value of a parameter at the current position.
+    pub fn value(&self, index: usize) -> Option<ParameterValue> {
+        let from = *self.from.get(index)?;
+        Some(from.interpolate(self.to[index], self.progress()))
+    }
+
+    /// Return the interpolated values of all parameters at the current position.
+    pub fn values(&self) -> Vec<ParameterValue> {
+        (0..self.from.len())
+            .map(|index| self.value(index).unwrap())
+            .collect()
+    }
+
+    /// Set the parameters to the interpolated values.
+    ///
+    /// Only values that changed since the last call are set, which also schedules their
+    /// notifications. `true` is returned if a value was set.
+    pub fn apply(&mut self, parameters: &Parameters) -> bool {
+        let mut changed = false;
+        for index in 0..self.from.len() {
+            let value = self.value(index).unwrap();
+            if self.last[index] != Some(value) && parameters.set(index, value) {
+                self.last[index] = Some(value);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Write a `patch:Set` message for every interpolated value that changed since the last
+    /// call.
+    ///
+    /// The messages are written as events with the given time stamp, for example to the control
+    /// port of a plugin or to the notify port, to update the UI. If the sequence is full, the
+    /// remaining values are written by the next call and `Err` is returned.
+    pub fn write_messages<'a, W: SequenceWritingFrame<'a>>(
+        &mut self,
+        frame: &mut W,
+        time: TimeStamp,
+        urids: &mut CachedMap,
+    ) -> Result<(), ()> {
+        for index in 0..self.from.len() {
+            let value = self.value(index).unwrap();
+            if self.last[index] == Some(value) {
+                continue;
+            }
+            {
+                let mut object =
+                    frame.push_event::<Object>(time.clone(), &(0, self.patch_urids.set), urids)?;
+                object.push_property::<URID>(
+                    self.patch_urids.property,
+                    0,
+                    &self.keys[index],
+                    urids,
+                )?;
+                value.push_property(&mut object, self.patch_urids.value, urids)?;
+            }
+            self.last[index] = Some(value);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,573 @@
+//! Plugin parameters that are controlled with patch messages and saved with the state.
+//!
+//! Instead of control ports, a plugin may expose parameters as properties that are written with
+//! the messages of the [patch extension](http://lv2plug.in/ns/ext/patch/patch.html). This module
+//! does the bookkeeping for them: The plugin declares its parameters as a slice of
+//! [`ParameterInfo`](struct.ParameterInfo.html)s and creates a
+//! [`Parameters`](struct.Parameters.html) struct in `instantiate`. In `run`, this struct applies
+//! the `patch:Set` messages of the control port, answers `patch:Get` messages and writes the
+//! resulting notifications to the notify port. In `save` and `restore`, it stores and retrieves
+//! the values of all parameters.
+//!
+//!     extern crate lv2rs_atom as atom;
+//!     extern crate lv2rs_core as core;
+//!     extern crate lv2rs_state as state;
+//!     extern crate lv2rs_urid as urid;
+//!
+//!     use atom::ports::AtomOutputPort;
+//!     use atom::prelude::*;
+//!     use atom::sequence::{TimeStamp, TimeUnit};
+//!     use core::uris::Uri;
+//!     use state::parameters::*;
+//!     use urid::debug::DebugMap;
+//!
+//!     const GAIN_URI: Uri = Uri::new(b"http://example.org/Amp#gain\0");
+//!     const BYPASS_URI: Uri = Uri::new(b"http://example.org/Amp#bypass\0");
+//!     const GAIN: usize = 0;
+//!     const BYPASS: usize = 1;
+//!
+//!     const PARAMETERS: &[ParameterInfo] = &[
+//!         ParameterInfo {
+//!             minimum: Some(ParameterValue::Float(-90.0)),
+//!             maximum: Some(ParameterValue::Float(24.0)),
+//!             ..ParameterInfo::new(GAIN_URI, "Gain", ParameterValue::Float(0.0))
+//!         },
+//!         ParameterInfo::new(BYPASS_URI, "Bypass", ParameterValue::Bool(false)),
+//!     ];
+//!
+//!     let mut debug_map = DebugMap::new();
+//!     let mut urids = unsafe { debug_map.create_cached_map() };
+//!     let parameters = Parameters::new(PARAMETERS, &mut urids);
+//!
+//!     // Values are clamped to the range of the parameter.
+//!     parameters.set(GAIN, ParameterValue::Float(48.0));
+//!     assert_eq!(Some(ParameterValue::Float(24.0)), parameters.get(GAIN));
+//!     assert_eq!(Some(false), parameters.get(BYPASS).and_then(ParameterValue::as_bool));
+//!
+//!     // The new value is reported on the notify port.
+//!     let mut notify_port: AtomOutputPort<Sequence> = AtomOutputPort::new();
+//!     let mut space = vec![0u8; 256];
+//!     let atom = unsafe { (space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+//!     *(atom.mut_size()) = 256 - 8;
+//!     notify_port.connect_port(atom);
+//!
+//!     assert!(parameters.has_notifications());
+//!     let mut frame =
+//!         unsafe { notify_port.write_atom_body(&TimeUnit::Frames, &mut urids) }.unwrap();
+//!     parameters.write_notifications(&mut frame, TimeStamp::Frames(0), &mut urids).unwrap();
+//!     assert!(!parameters.has_notifications());
+//!
+//! The URIs of the parameters also have to be declared as `patch:writable` properties in the
+//! plugin's Turtle description.
+use crate::preset::Preset;
+use crate::property::*;
+use atom::object::ObjectWritingFrame;
+use atom::patch::PatchURIDs;
+use atom::prelude::*;
+use atom::sequence::{SequenceWritingFrame, TimeStamp};
+use atom::uris::AtomURIDs;
+use core::uris::Uri;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use urid::{CachedMap, URID};
+
+/// The value of a parameter.
+///
+/// The variant of a parameter's default value is the type of the parameter. Values of other
+/// types are rejected.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ParameterValue {
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    Bool(bool),
+}
+
+impl ParameterValue {
+    /// Return the value if it is a float.
+    pub fn as_float(self) -> Option<f32> {
+        match self {
+            ParameterValue::Float(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Return the value if it is a double.
+    pub fn as_double(self) -> Option<f64> {
+        match self {
+            ParameterValue::Double(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Return the value if it is an int.
+    pub fn as_int(self) -> Option<i32> {
+        match self {
+            ParameterValue::Int(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Return the value if it is a long.
+    pub fn as_long(self) -> Option<i64> {
+        match self {
+            ParameterValue::Long(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Return the value if it is a bool.
+    pub fn as_bool(self) -> Option<bool> {
+        match self {
+            ParameterValue::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Return `true` if both values have the same type.
+    pub fn same_type(self, other: Self) -> bool {
+        std::mem::discriminant(&self) == std::mem::discriminant(&other)
+    }
+
+    /// Clamp the value to a range.
+    ///
+    /// Bounds of another type are ignored.
+    pub fn clamp(self, minimum: Option<Self>, maximum: Option<Self>) -> Self {
+        let mut value = self;
+        if let Some(minimum) = minimum {
+            if value.less_than(minimum) {
+                value = minimum;
+            }
+        }
+        if let Some(maximum) = maximum {
+            if maximum.less_than(value) {
+                value = maximum;
+            }
+        }
+        value
+    }
+
+    /// Interpolate linearly between two values of the same type.
+    ///
+    /// An `amount` of `0.0` returns `self` and an `amount` of `1.0` returns `other`; The amount
+    /// is clamped to this range. Integers are rounded to the nearest value and booleans switch
+    /// halfway. If the values have different types, `self` is returned.
+    pub fn interpolate(self, other: Self, amount: f64) -> Self {
+        let amount = amount.clamp(0.0, 1.0);
+        match (self, other) {
+            (ParameterValue::Int(a), ParameterValue::Int(b)) => {
+                ParameterValue::Int((a as f64 + (b as f64 - a as f64) * amount).round() as i32)
+            }
+            (ParameterValue::Long(a), ParameterValue::Long(b)) => {
+                ParameterValue::Long((a as f64 + (b as f64 - a as f64) * amount).round() as i64)
+            }
+            (ParameterValue::Float(a), ParameterValue::Float(b)) => {
+                ParameterValue::Float(a + (b - a) * amount as f32)
+            }
+            (ParameterValue::Double(a), ParameterValue::Double(b)) => {
+                ParameterValue::Double(a + (b - a) * amount)
+            }
+            (ParameterValue::Bool(a), ParameterValue::Bool(b)) => {
+                ParameterValue::Bool(if amount < 0.5 { a } else { b })
+            }
+            _ => self,
+        }
+    }
+
+    fn less_than(self, other: Self) -> bool {
+        match (self, other) {
+            (ParameterValue::Int(a), ParameterValue::Int(b)) => a < b,
+            (ParameterValue::Long(a), ParameterValue::Long(b)) => a < b,
+            (ParameterValue::Float(a), ParameterValue::Float(b)) => a < b,
+            (ParameterValue::Double(a), ParameterValue::Double(b)) => a < b,
+            (ParameterValue::Bool(a), ParameterValue::Bool(b)) => !a & b,
+            _ => false,
+        }
+    }
+
+    fn to_bits(self) -> u64 {
+        match self {
+            ParameterValue::Int(value) => value as u32 as u64,
+            ParameterValue::Long(value) => value as u64,
+            ParameterValue::Float(value) => value.to_bits() as u64,
+            ParameterValue::Double(value) => value.to_bits(),
+            ParameterValue::Bool(value) => value as u64,
+        }
+    }
+
+    /// Interpret bits as a value of the same type as `self`.
+    fn with_bits(self, bits: u64) -> Self {
+        match self {
+            ParameterValue::Int(_) => ParameterValue::Int(bits as u32 as i32),
+            ParameterValue::Long(_) => ParameterValue::Long(bits as i64),
+            ParameterValue::Float(_) => ParameterValue::Float(f32::from_bits(bits as u32)),
+            ParameterValue::Double(_) => ParameterValue::Double(f64::from_bits(bits)),
+            ParameterValue::Bool(_) => ParameterValue::Bool(bits != 0),
+        }
+    }
+
+    /// Read a value of the same type as `self` from an atom.
+    fn read_atom(self, atom: &Atom, urids: &mut CachedMap) -> Option<Self> {
+        let value = match self {
+            ParameterValue::Int(_) => ParameterValue::Int(*atom.get_body::<i32>(urids).ok()?),
+            ParameterValue::Long(_) => ParameterValue::Long(*atom.get_body::<i64>(urids).ok()?),
+            ParameterValue::Float(_) => ParameterValue::Float(*atom.get_body::<f32>(urids).ok()?),
+            ParameterValue::Double(_) => ParameterValue::Double(*atom.get_body::<f64>(urids).ok()?),
+            ParameterValue::Bool(_) => ParameterValue::Bool(*atom.get_body::<bool>(urids).ok()?),
+        };
+        Some(value)
+    }
+
+    /// Retrieve a value of the same type as `self`.
+    fn retrieve(
+        self,
+        retrieve: &RetrieveHandle,
+        key: URID,
+        urids: &CachedMap,
+    ) -> Result<Self, StateErr> {
+        let value = match self {
+            ParameterValue::Int(_) => ParameterValue::Int(retrieve.retrieve_scalar(key, urids)?),
+            ParameterValue::Long(_) => ParameterValue::Long(retrieve.retrieve_scalar(key, urids)?),
+            ParameterValue::Float(_) => {
+                ParameterValue::Float(retrieve.retrieve_scalar(key, urids)?)
+            }
+            ParameterValue::Double(_) => {
+                ParameterValue::Double(retrieve.retrieve_scalar(key, urids)?)
+            }
+            ParameterValue::Bool(_) => ParameterValue::Bool(retrieve.retrieve_scalar(key, urids)?),
+        };
+        Ok(value)
+    }
+
+    fn store(self, store: &mut StoreHandle, key: URID, urids: &CachedMap) -> Result<(), StateErr> {
+        match self {
+            ParameterValue::Int(value) => store.store_scalar(key, value, urids),
+            ParameterValue::Long(value) => store.store_scalar(key, value, urids),
+            ParameterValue::Float(value) => store.store_scalar(key, value, urids),
+            ParameterValue::Double(value) => store.store_scalar(key, value, urids),
+            ParameterValue::Bool(value) => store.store_scalar(key, value, urids),
+        }
+    }
+
+    pub(crate) fn push_property<'a, W: ObjectWritingFrame<'a>>(
+        self,
+        frame: &mut W,
+        key: URID,
+        urids: &mut CachedMap,
+    ) -> Result<(), ()> {
+        match self {
+            ParameterValue::Int(value) => {
+                frame.push_property::<i32>(key, 0, &value, urids)?;
+            }
+            ParameterValue::Long(value) => {
+                frame.push_property::<i64>(key, 0, &value, urids)?;
+            }
+            ParameterValue::Float(value) => {
+                frame.push_property::<f32>(key, 0, &value, urids)?;
+            }
+            ParameterValue::Double(value) => {
+                frame.push_property::<f64>(key, 0, &value, urids)?;
+            }
+            ParameterValue::Bool(value) => {
+                frame.push_property::<bool>(key, 0, &value, urids)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<bool> for ParameterValue {
+    fn from(value: bool) -> Self {
+        ParameterValue::Bool(value)
+    }
+}
+
+/// The declaration of a parameter.
+///
+/// Create it with [`new`](#method.new) and use the struct update syntax to set the range.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ParameterInfo {
+    /// The URI of the parameter.
+    pub uri: Uri,
+    /// The human-readable name of the parameter.
+    pub label: &'static str,
+    /// The initial value, which also defines the type of the parameter.
+    pub default: ParameterValue,
+    pub minimum: Option<ParameterValue>,
+    pub maximum: Option<ParameterValue>,
+}
+
+impl ParameterInfo {
+    /// Declare a parameter without a range.
+    pub const fn new(uri: Uri, label: &'static str, default: ParameterValue) -> Self {
+        Self {
+            uri,
+            label,
+            default,
+            minimum: None,
+            maximum: None,
+        }
+    }
+}
+
+/// The current values of a plugin's parameters.
+///
+/// The parameters are identified by their index in the declaration slice. The values are stored
+/// in atomics, which is why all methods only need a shared reference: `save` may be called
+/// concurrently with `run`.
+///
+/// All URIDs are mapped in [`new`](#method.new), including the URIDs of the atom types, so
+/// handling messages and saving the state doesn't map anything.
+pub struct Parameters {
+    infos: &'static [ParameterInfo],
+    keys: Vec<URID>,
+    values: Vec<AtomicU64>,
+    pending: Vec<AtomicBool>,
+    patch_urids: PatchURIDs,
+}
+
+impl Parameters {
+    /// Map the URIs of the parameters and set them to their default values.
+    ///
+    /// This should be called in `instantiate`.
+    pub fn new(infos: &'static [ParameterInfo], urids: &mut CachedMap) -> Self {
+        AtomURIDs::new(urids);
+        let keys = infos.iter().map(|info| urids.map(info.uri)).collect();
+        Self {
+            infos,
+            keys,
+            values: infos
+                .iter()
+                .map(|info| AtomicU64::new(info.default.to_bits()))
+                .collect(),
+            pending: infos.iter().map(|_| AtomicBool::new(false)).collect(),
+            patch_urids: PatchURIDs::new(urids),
+        }
+    }
+
+    /// Return the declarations of the parameters.
+    pub fn infos(&self) -> &'static [ParameterInfo] {
+        self.infos
+    }
+
+    /// Return the number of parameters.
+    pub fn len(&self) -> usize {
+        self.infos.len()
+    }
+
+    /// Return `true` if there are no parameters.
+    pub fn is_empty(&self) -> bool {
+        self.infos.is_empty()
+    }
+
+    /// Return the index of the parameter with the given URID.
+    pub fn index_of(&self, key: URID) -> Option<usize> {
+        self.keys.iter().position(|other| *other == key)
+    }
+
+    /// Return the URID of a parameter.
+    pub fn key(&self, index: usize) -> Option<URID> {
+        self.keys.get(index).cloned()
+    }
+
+    /// Return the current value of a parameter.
+    pub fn get(&self, index: usize) -> Option<ParameterValue> {
+        let bits = self.values.get(index)?.load(Ordering::Relaxed);
+        Some(self.infos[index].default.with_bits(bits))
+    }
+
+    /// Return the current values of all parameters.
+    pub fn values(&self) -> Vec<ParameterValue> {
+        (0..self.len())
+            .map(|index| self.get(index).unwrap())
+            .collect()
+    }
+
+    /// Set the value of a parameter and schedule a notification.
+    ///
+    /// The value is clamped to the range of the parameter. `false` is returned if there is no
+    /// such parameter or the value has the wrong type.
+    pub fn set(&self, index: usize, value: ParameterValue) -> bool {
+        let info = match self.infos.get(index) {
+            Some(info) => info,
+            None => return false,
+        };
+        if !info.default.same_type(value) {
+            return false;
+        }
+        let value = value.clamp(info.minimum, info.maximum);
+        self.values[index].store(value.to_bits(), Ordering::Relaxed);
+        self.notify(index);
+        true
+    }
+
+    /// Set all parameters to their default values.
+    pub fn reset(&self) {
+        for (index, info) in self.infos.iter().enumerate() {
+            self.set(index, info.default);
+        }
+    }
+
+    /// Schedule a notification for a parameter, for example after the plugin changed it itself.
+    pub fn notify(&self, index: usize) {
+        if let Some(pending) = self.pending.get(index) {
+            pending.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Schedule notifications for all parameters.
+    pub fn notify_all(&self) {
+        for pending in self.pending.iter() {
+            pending.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Return `true` if there are notifications to write.
+    pub fn has_notifications(&self) -> bool {
+        self.pending
+            .iter()
+            .any(|pending| pending.load(Ordering::Relaxed))
+    }
+
+    /// Handle a patch message.
+    ///
+    /// A `patch:Set` message sets the value of a parameter, whose index is returned. A `patch:Get`
+    /// message schedules a notification for the requested parameter or, if there is no
+    /// `patch:property`, for all parameters. Other atoms and messages for unknown properties are
+    /// ignored.
+    pub fn handle_message(&self, message: &Atom, urids: &mut CachedMap) -> Option<usize> {
+        let object = message.get_body::<Object>(urids).ok()?;
+        let mut property: Option<URID> = None;
+        let mut value: Option<&Atom> = None;
+        for (header, atom) in object.iter() {
+            if header.key == self.patch_urids.property {
+                property = atom.get_body::<URID>(urids).ok().cloned();
+            } else if header.key == self.patch_urids.value {
+                value = Some(atom);
+            }
+        }
+
+        if object.header.otype == self.patch_urids.set {
+            let index = self.index_of(property?)?;
+            let value = self.infos[index].default.read_atom(value?, urids)?;
+            if self.set(index, value) {
+                Some(index)
+            } else {
+                None
+            }
+        } else if object.header.otype == self.patch_urids.get {
+            match property {
+                Some(property) => self.notify(self.index_of(property)?),
+                None => self.notify_all(),
+            }
+            None
+        } else {
+            None
+        }
+    }
+
+    /// Handle all patch messages of a sequence, usually the one of the control port.
+    ///
+    /// `true` is returned if a parameter was changed.
+    pub fn handle_sequence(&self, sequence: &Sequence, urids: &mut CachedMap) -> bool {
+        let mut changed = false;
+        for (_, atom) in sequence.iter(urids) {
+            changed |= self.handle_message(atom, urids).is_some();
+        }
+        changed
+    }
+
+    /// Write a `patch:Set` message for every scheduled notification.
+    ///
+    /// The messages are written as events with the given time stamp. If the sequence is full, the
+    /// remaining notifications stay scheduled and `Err` is returned.
+    pub fn write_notifications<'a, W: SequenceWritingFrame<'a>>(
+        &self,
+        frame: &mut W,
+        time: TimeStamp,
+        urids: &mut CachedMap,
+    ) -> Result<(), ()> {
+        for (index, pending) in self.pending.iter().enumerate() {
+            if !pending.load(Ordering::Relaxed) {
+                continue;
+            }
+            {
+                let mut object =
+                    frame.push_event::<Object>(time.clone(), &(0, self.patch_urids.set), urids)?;
+                object.push_property::<URID>(
+                    self.patch_urids.property,
+                    0,
+                    &self.keys[index],
+                    urids,
+                )?;
+                self.get(index).unwrap().push_property(
+                    &mut object,
+                    self.patch_urids.value,
+                    urids,
+                )?;
+            }
+            pending.store(false, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    /// Store the values of all parameters.
+    pub fn save(&self, store: &mut StoreHandle, urids: &CachedMap) -> Result<(), StateErr> {
+        for index in 0..self.len() {
+            self.get(index)
+                .unwrap()
+                .store(store, self.keys[index], urids)?;
+        }
+        Ok(())
+    }
+
+    /// Retrieve the values of all parameters and schedule notifications for them.
+    ///
+    /// Parameters that weren't stored are set to their default values.
+    pub fn restore(&self, retrieve: &RetrieveHandle, urids: &CachedMap) -> Result<(), StateErr> {
+        for (index, value) in self
+            .retrieve_values(retrieve, urids)?
+            .into_iter()
+            .enumerate()
+        {
+            self.set(index, value);
+        }
+        Ok(())
+    }
+
+    /// Retrieve the values of all parameters without setting them.
+    ///
+    /// Parameters that weren't stored have their default values.
+    pub fn retrieve_values(
+        &self,
+        retrieve: &RetrieveHandle,
+        urids: &CachedMap,
+    ) -> Result<Vec<ParameterValue>, StateErr> {
+        self.infos
+            .iter()
+            .zip(self.keys.iter())
+            .map(
+                |(info, key)| match info.default.retrieve(retrieve, *key, urids) {
+                    Err(StateErr::NoProperty) => Ok(info.default),
+                    result => result,
+                },
+            )
+            .collect()
+    }
+
+    /// Return the values of all parameters that are saved in a preset.
+    ///
+    /// Parameters that aren't part of the preset have their default values.
+    pub fn preset_values(
+        &self,
+        preset: &Preset,
+        urids: &CachedMap,
+    ) -> Result<Vec<ParameterValue>, StateErr> {
+        self.retrieve_values(&preset.retrieve_handle(), urids)
+    }
+
+    /// Return the URIDs of the patch messages.
+    pub(crate) fn patch_urids(&self) -> &PatchURIDs {
+        &self.patch_urids
+    }
+}
@@ -0,0 +1,237 @@
+//! Mapping of file paths.
+//!
+//! Plugins that use files, like samples or impulse responses, have to store the paths of these
+//! files in their state. However, absolute paths aren't portable: A session may be moved to
+//! another directory or another machine. Therefore, the host provides the map path feature, which
+//! turns absolute paths into abstract paths, which should be stored, and back.
+//!
+//! The strings returned by the host have to be freed again, either with the free path feature or
+//! with the `free` function of the C library. A [`PathMapper`](struct.PathMapper.html) takes care
+//! of this and returns owned strings.
+//!
+//! Plugins that create new files, like recordings, ask the host for a path in the session's
+//! directory with the make path feature, which is wrapped by a [`PathMaker`](struct.PathMaker.html).
+//!
+//! Files that are referenced by the plugin, for example by several voices of a sampler, are best
+//! held as [`FileRef`](struct.FileRef.html)s: They are cheap to clone and can be stored and
+//! retrieved with [`store_file`](../struct.StoreHandle.html#method.store_file) and
+//! [`retrieve_file`](../struct.RetrieveHandle.html#method.retrieve_file).
+use crate::uris;
+use core::uris::Uri;
+use std::ffi::{CStr, CString};
+use std::os::raw::*;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Type to describe pointers to path mapping handles.
+pub type MapPathHandle = *mut c_void;
+
+/// Type to describe pointers to path freeing handles.
+pub type FreePathHandle = *mut c_void;
+
+/// Type to describe pointers to path making handles.
+pub type MakePathHandle = *mut c_void;
+
+/// The map path feature.
+#[repr(C)]
+pub struct MapPath {
+    /// Pointer to a host-specific handle.
+    pub handle: MapPathHandle,
+    /// Map an absolute path to an abstract path.
+    pub abstract_path:
+        extern "C" fn(handle: MapPathHandle, absolute_path: *const c_char) -> *mut c_char,
+    /// Map an abstract path to an absolute path.
+    pub absolute_path:
+        extern "C" fn(handle: MapPathHandle, abstract_path: *const c_char) -> *mut c_char,
+}
+
+unsafe impl core::FeatureData for MapPath {
+    const URI: Uri = uris::MAP_PATH_URI;
+}
+
+/// The free path feature.
+#[repr(C)]
+pub struct FreePath {
+    /// Pointer to a host-specific handle.
+    pub handle: FreePathHandle,
+    /// Free a path that was returned by the host.
+    pub free_path: extern "C" fn(handle: FreePathHandle, path: *mut c_char),
+}
+
+unsafe impl core::FeatureData for FreePath {
+    const URI: Uri = uris::FREE_PATH_URI;
+}
+
+/// The make path feature.
+#[repr(C)]
+pub struct MakePath {
+    /// Pointer to a host-specific handle.
+    pub handle: MakePathHandle,
+    /// Return the absolute path of a new file, given a path relative to the plugin's directory.
+    pub path: extern "C" fn(handle: MakePathHandle, path: *const c_char) -> *mut c_char,
+}
+
+unsafe impl core::FeatureData for MakePath {
+    const URI: Uri = uris::MAKE_PATH_URI;
+}
+
+extern "C" {
+    fn free(ptr: *mut c_void);
+}
+
+/// Copy a string returned by the host and free it.
+fn take_path(free_path: Option<&FreePath>, path: *mut c_char) -> Option<CString> {
+    if path.is_null() {
+        return None;
+    }
+    let owned_path = CString::from(unsafe { CStr::from_ptr(path) });
+    match free_path {
+        Some(free_path) => (free_path.free_path)(free_path.handle, path),
+        None => unsafe { free(path as *mut c_void) },
+    }
+    Some(owned_path)
+}
+
+/// Safe wrapper around the map path and the free path features.
+///
+/// See the [module documentation](index.html) for more information.
+pub struct PathMapper<'a> {
+    map_path: &'a MapPath,
+    free_path: Option<&'a FreePath>,
+}
+
+impl<'a> PathMapper<'a> {
+    /// Create a new path mapper.
+    ///
+    /// If the host doesn't provide the free path feature, the returned strings are freed with the
+    /// `free` function of the C library.
+    pub fn new(map_path: &'a MapPath, free_path: Option<&'a FreePath>) -> Self {
+        Self {
+            map_path,
+            free_path,
+        }
+    }
+
+    /// Try to find the map path and the free path features in the features list.
+    ///
+    /// `None` is returned if the host does not support path mapping.
+    pub fn try_from_features(features: &core::FeaturesList) -> Option<PathMapper<'static>> {
+        let features = core::Features::new(features);
+        let map_path = unsafe { features.get::<MapPath>() }?;
+        let free_path = unsafe { features.get::<FreePath>() }.map(|free_path| &*free_path);
+        Some(PathMapper::new(map_path, free_path))
+    }
+
+    /// Map an absolute path to an abstract path, which can be stored.
+    pub fn abstract_path(&self, absolute_path: &CStr) -> Option<CString> {
+        let path = (self.map_path.abstract_path)(self.map_path.handle, absolute_path.as_ptr());
+        take_path(self.free_path, path)
+    }
+
+    /// Map a stored, abstract path to an absolute path.
+    pub fn absolute_path(&self, abstract_path: &CStr) -> Option<CString> {
+        let path = (self.map_path.absolute_path)(self.map_path.handle, abstract_path.as_ptr());
+        take_path(self.free_path, path)
+    }
+}
+
+/// Safe wrapper around the make path and the free path features.
+///
+/// Hosts usually pass the make path feature when the plugin is instantiated, so the path maker
+/// should be created in `instantiate`.
+pub struct PathMaker<'a> {
+    make_path: &'a MakePath,
+    free_path: Option<&'a FreePath>,
+}
+
+impl<'a> PathMaker<'a> {
+    /// Create a new path maker.
+    ///
+    /// If the host doesn't provide the free path feature, the returned strings are freed with the
+    /// `free` function of the C library.
+    pub fn new(make_path: &'a MakePath, free_path: Option<&'a FreePath>) -> Self {
+        Self {
+            make_path,
+            free_path,
+        }
+    }
+
+    /// Try to find the make path and the free path features in the features list.
+    ///
+    /// `None` is returned if the host does not support making paths.
+    pub fn try_from_features(features: &core::FeaturesList) -> Option<PathMaker<'static>> {
+        let features = core::Features::new(features);
+        let make_path = unsafe { features.get::<MakePath>() }?;
+        let free_path = unsafe { features.get::<FreePath>() }.map(|free_path| &*free_path);
+        Some(PathMaker::new(make_path, free_path))
+    }
+
+    /// Return the absolute path of a new file.
+    ///
+    /// The path is relative to a directory the host reserves for the plugin instance. The host
+    /// creates all missing parent directories, so the file can be created right away.
+    pub fn make_path(&self, path: &CStr) -> Option<CString> {
+        let path = (self.make_path.path)(self.make_path.handle, path.as_ptr());
+        take_path(self.free_path, path)
+    }
+}
+
+/// A reference-counted handle to a file.
+///
+/// Cloning a file reference only increases its reference count, which makes it possible to share
+/// one file between many users, like the voices of a sampler, and to pass it to the audio thread
+/// without allocating. Two file references are equal if they point to the same path, which
+/// can be used to find out whether a restored file is already loaded.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct FileRef {
+    path: Arc<CString>,
+}
+
+impl FileRef {
+    /// Create a new reference to the file at an absolute path.
+    pub fn new(path: CString) -> Self {
+        Self {
+            path: Arc::new(path),
+        }
+    }
+
+    /// Return the absolute path of the file.
+    pub fn path(&self) -> &CStr {
+        &self.path
+    }
+
+    /// Return the absolute path as a path of the standard library.
+    ///
+    /// On systems other than Unix, an error is returned if the path is not valid UTF-8.
+    pub fn to_path_buf(&self) -> Result<PathBuf, ()> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+            Ok(PathBuf::from(std::ffi::OsStr::from_bytes(
+                self.path.to_bytes(),
+            )))
+        }
+        #[cfg(not(unix))]
+        {
+            self.path.to_str().map(PathBuf::from).map_err(|_| ())
+        }
+    }
+
+    /// Return the number of references to the file, including this one.
+    pub fn ref_count(&self) -> usize {
+        Arc::strong_count(&self.path)
+    }
+
+    /// Return whether both references share the same allocation.
+    ///
+    /// Unlike the comparison operator, this is only true for clones of the same reference.
+    pub fn ptr_eq(&self, other: &FileRef) -> bool {
+        Arc::ptr_eq(&self.path, &other.path)
+    }
+}
+
+impl From<CString> for FileRef {
+    fn from(path: CString) -> Self {
+        Self::new(path)
+    }
+}
@@ -0,0 +1,459 @@
+//! Presets of plugins.
+//!
+//! A preset, as defined by the [LV2 presets extension](http://lv2plug.in/ns/ext/presets/presets.html),
+//! is a named set of port values and a saved state. A [`Preset`](struct.Preset.html) holds them in
+//! memory: The state can be saved from and loaded into a plugin that implements
+//! [`StateHandler`](../trait.StateHandler.html), and the port values are set by symbol.
+//!
+//! Presets are installed as Turtle files in a bundle. A
+//! [`PresetBundle`](struct.PresetBundle.html) collects the presets of a plugin, for example its
+//! factory presets, lets you enumerate and find them, and generates the files of the bundle:
+//!
+//!     extern crate lv2rs_state as state;
+//!
+//!     use state::preset::*;
+//!
+//!     let mut soft = Preset::new("http://example.org/Amp#soft", "Soft");
+//!     soft.set_port_value("gain", -12.0);
+//!
+//!     let mut bundle = PresetBundle::new("http://example.org/Amp");
+//!     bundle.add(soft);
+//!     assert_eq!(Some(-12.0), bundle.find_by_label("Soft").unwrap().port_value("gain"));
+//!
+//!     let manifest = bundle.manifest();
+//!     assert!(manifest.contains("<http://example.org/Amp#soft>"));
+//!     assert!(manifest.contains("rdfs:seeAlso <Soft.ttl>"));
+//!
+//! The Turtle descriptions of the presets are generated with [`Preset::to_turtle`](struct.Preset.html#method.to_turtle)
+//! or [`PresetBundle::files`](struct.PresetBundle.html#method.files). Since the state is stored
+//! with URIDs, they need the unmap feature to write the URIs of the keys and types.
+use crate::property::*;
+use crate::StateHandler;
+use atom::uris;
+use core::ttl::{file_name, literal};
+use std::ffi::{CStr, CString};
+use std::fmt::Write;
+use std::os::raw::*;
+use std::ptr::null;
+use urid::{Unmap, URID};
+
+/// A property of a preset's state.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct StoredProperty {
+    /// The key of the property.
+    pub key: URID,
+    /// The raw value of the property.
+    pub value: Vec<u8>,
+    /// The URID of the value's type.
+    pub value_type: URID,
+    /// The flags of the value.
+    pub flags: StateFlags,
+}
+
+/// A named set of port values and a saved state.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Preset {
+    uri: String,
+    label: String,
+    port_values: Vec<(String, f32)>,
+    properties: Vec<StoredProperty>,
+}
+
+impl Preset {
+    /// Create an empty preset with a URI and a human-readable label.
+    pub fn new(uri: &str, label: &str) -> Self {
+        Self {
+            uri: uri.to_owned(),
+            label: label.to_owned(),
+            port_values: Vec::new(),
+            properties: Vec::new(),
+        }
+    }
+
+    /// Return the URI of the preset.
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// Return the label of the preset.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Return the port values of the preset, as pairs of port symbols and values.
+    pub fn port_values(&self) -> &[(String, f32)] {
+        &self.port_values
+    }
+
+    /// Return the value of the port with the given symbol.
+    pub fn port_value(&self, symbol: &str) -> Option<f32> {
+        self.port_values
+            .iter()
+            .find(|(port, _)| port == symbol)
+            .map(|(_, value)| *value)
+    }
+
+    /// Set the value of the port with the given symbol.
+    pub fn set_port_value(&mut self, symbol: &str, value: f32) {
+        match self.port_values.iter_mut().find(|(port, _)| port == symbol) {
+            Some((_, old_value)) => *old_value = value,
+            None => self.port_values.push((symbol.to_owned(), value)),
+        }
+    }
+
+    /// Return the properties of the preset's state, in the order they were stored.
+    pub fn properties(&self) -> &[StoredProperty] {
+        &self.properties
+    }
+
+    /// Return the property with the given key.
+    pub fn property(&self, key: URID) -> Option<&StoredProperty> {
+        self.properties.iter().find(|property| property.key == key)
+    }
+
+    /// Set a property of the preset's state, replacing a property with the same key.
+    pub fn set_property(&mut self, property: StoredProperty) {
+        set_property(&mut self.properties, property);
+    }
+
+    /// Replace the preset's state with the state of a plugin.
+    ///
+    /// The plugin is saved with the `POD` and `PORTABLE` flags and the given features, which
+    /// should contain the map path feature if the plugin stores files. Properties that aren't
+    /// plain old data are rejected with `StateErr::BadFlags`. If saving fails, the previous state
+    /// of the preset is kept.
+    pub fn save<P: StateHandler>(
+        &mut self,
+        plugin: &P,
+        features: Option<&core::FeaturesList>,
+    ) -> Result<(), StateErr> {
+        let mut properties: Vec<StoredProperty> = Vec::new();
+        let mut store = StoreHandle::new(
+            store_property,
+            &mut properties as *mut Vec<StoredProperty> as StateHandle,
+        );
+        plugin.save(&mut store, StateFlags::POD | StateFlags::PORTABLE, features)?;
+        self.properties = properties;
+        Ok(())
+    }
+
+    /// Restore the preset's state in a plugin.
+    ///
+    /// The port values are not affected, since the ports belong to the host. Set them separately
+    /// with the values of [`port_values`](#method.port_values).
+    pub fn load<P: StateHandler>(
+        &self,
+        plugin: &mut P,
+        features: Option<&core::FeaturesList>,
+    ) -> Result<(), StateErr> {
+        plugin.restore(
+            &self.retrieve_handle(),
+            StateFlags::POD | StateFlags::PORTABLE,
+            features,
+        )
+    }
+
+    /// Return a handle that retrieves the properties of the preset's state.
+    pub(crate) fn retrieve_handle(&self) -> RetrieveHandle {
+        RetrieveHandle::new(
+            retrieve_property,
+            &self.properties as *const Vec<StoredProperty> as StateHandle,
+        )
+    }
+
+    /// Return the name of the preset's Turtle file.
+    ///
+    /// It is derived from the label, see [`core::ttl::file_name`](../../lv2rs_core/ttl/fn.file_name.html).
+    pub fn file_name(&self) -> String {
+        file_name(&self.label)
+    }
+
+    /// Generate the Turtle description of the preset.
+    ///
+    /// Scalars, strings, URIDs and paths are written as Turtle literals and URIs, all other
+    /// values as Base64-encoded literals with the URI of their type as the datatype. Paths are
+    /// written as relative URIs, which are resolved relative to the preset's file.
+    ///
+    /// `StateErr::Unknown` is returned if a key can't be unmapped and `StateErr::BadType` if a
+    /// type can't be unmapped or a value doesn't match its type.
+    pub fn to_turtle(&self, plugin_uri: &str, unmap: &mut Unmap) -> Result<String, StateErr> {
+        let mut ttl = String::new();
+        ttl.push_str(PREFIXES);
+        let _ = write!(
+            ttl,
+            "\n<{}>\n    a pset:Preset ;\n    lv2:appliesTo <{}> ;\n    rdfs:label {}",
+            self.uri,
+            plugin_uri,
+            literal(&self.label)
+        );
+        for (symbol, value) in self.port_values.iter() {
+            let _ = write!(
+                ttl,
+                " ;\n    lv2:port [\n        lv2:symbol {} ;\n        pset:value {:?}\n    ]",
+                literal(symbol),
+                value
+            );
+        }
+        if !self.properties.is_empty() {
+            ttl.push_str(" ;\n    state:state [");
+            for (i, property) in self.properties.iter().enumerate() {
+                if i > 0 {
+                    ttl.push_str(" ;");
+                }
+                let key = unmap_uri(unmap, property.key).ok_or(StateErr::Unknown)?;
+                let key = key.to_str().map_err(|_| StateErr::Unknown)?;
+                let value = turtle_value(property, unmap)?;
+                let _ = write!(ttl, "\n        <{}> {}", key, value);
+            }
+            ttl.push_str("\n    ]");
+        }
+        ttl.push_str(" .\n");
+        Ok(ttl)
+    }
+}
+
+/// The presets of a plugin, which are installed in one bundle.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PresetBundle {
+    plugin_uri: String,
+    presets: Vec<Preset>,
+}
+
+impl PresetBundle {
+    /// Create an empty bundle for the plugin with the given URI.
+    pub fn new(plugin_uri: &str) -> Self {
+        Self {
+            plugin_uri: plugin_uri.to_owned(),
+            presets: Vec::new(),
+        }
+    }
+
+    /// Return the URI of the plugin the presets apply to.
+    pub fn plugin_uri(&self) -> &str {
+        &self.plugin_uri
+    }
+
+    /// Add a preset, replacing a preset with the same URI.
+    pub fn add(&mut self, preset: Preset) {
+        match self.presets.iter_mut().find(|old| old.uri == preset.uri) {
+            Some(old) => *old = preset,
+            None => self.presets.push(preset),
+        }
+    }
+
+    /// Remove the preset with the given URI and return it.
+    pub fn remove(&mut self, uri: &str) -> Option<Preset> {
+        let index = self.presets.iter().position(|preset| preset.uri == uri)?;
+        Some(self.presets.remove(index))
+    }
+
+    /// Return the presets, in the order they were added.
+    pub fn presets(&self) -> &[Preset] {
+        &self.presets
+    }
+
+    /// Return the preset with the given URI.
+    pub fn find(&self, uri: &str) -> Option<&Preset> {
+        self.presets.iter().find(|preset| preset.uri == uri)
+    }
+
+    /// Return the first preset with the given label.
+    pub fn find_by_label(&self, label: &str) -> Option<&Preset> {
+        self.presets.iter().find(|preset| preset.label == label)
+    }
+
+    /// Generate the `manifest.ttl` of the bundle.
+    pub fn manifest(&self) -> String {
+        let mut ttl = String::new();
+        ttl.push_str("@prefix lv2: <http://lv2plug.in/ns/lv2core#> .\n");
+        ttl.push_str("@prefix pset: <http://lv2plug.in/ns/ext/presets#> .\n");
+        ttl.push_str("@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .\n");
+        for preset in self.presets.iter() {
+            let _ = write!(
+                ttl,
+                "\n<{}>\n    a pset:Preset ;\n    lv2:appliesTo <{}> ;\n    rdfs:seeAlso <{}> .\n",
+                preset.uri,
+                self.plugin_uri,
+                preset.file_name()
+            );
+        }
+        ttl
+    }
+
+    /// Generate all files of the bundle, together with their file names.
+    pub fn files(&self, unmap: &mut Unmap) -> Result<Vec<(String, String)>, StateErr> {
+        let mut files = Vec::with_capacity(self.presets.len() + 1);
+        files.push((String::from("manifest.ttl"), self.manifest()));
+        for preset in self.presets.iter() {
+            files.push((
+                preset.file_name(),
+                preset.to_turtle(&self.plugin_uri, unmap)?,
+            ));
+        }
+        Ok(files)
+    }
+}
+
+const PREFIXES: &str = "@prefix atom: <http://lv2plug.in/ns/ext/atom#> .
+@prefix lv2: <http://lv2plug.in/ns/lv2core#> .
+@prefix pset: <http://lv2plug.in/ns/ext/presets#> .
+@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+@prefix state: <http://lv2plug.in/ns/ext/state#> .
+@prefix xsd: <http://www.w3.org/2001/XMLSchema#> .
+";
+
+fn set_property(properties: &mut Vec<StoredProperty>, property: StoredProperty) {
+    match properties.iter_mut().find(|old| old.key == property.key) {
+        Some(old) => *old = property,
+        None => properties.push(property),
+    }
+}
+
+extern "C" fn store_property(
+    handle: StateHandle,
+    key: URID,
+    value: *const c_void,
+    size: usize,
+    value_type: URID,
+    flags: u32,
+) -> u32 {
+    let flags = StateFlags(flags);
+    if !flags.contains(StateFlags::POD) {
+        return StateErr::BadFlags as u32;
+    }
+    let properties = unsafe { (handle as *mut Vec<StoredProperty>).as_mut() }.unwrap();
+    let value = unsafe { std::slice::from_raw_parts(value as *const u8, size) };
+    set_property(
+        properties,
+        StoredProperty {
+            key,
+            value: value.to_owned(),
+            value_type,
+            flags,
+        },
+    );
+    0
+}
+
+extern "C" fn retrieve_property(
+    handle: StateHandle,
+    key: URID,
+    size: *mut usize,
+    value_type: *mut URID,
+    flags: *mut u32,
+) -> *const c_void {
+    let properties = unsafe { (handle as *const Vec<StoredProperty>).as_ref() }.unwrap();
+    match properties.iter().find(|property| property.key == key) {
+        Some(property) => {
+            unsafe {
+                *size = property.value.len();
+                *value_type = property.value_type;
+                *flags = property.flags.0;
+            }
+            property.value.as_ptr() as *const c_void
+        }
+        None => null(),
+    }
+}
+
+fn unmap_uri(unmap: &mut Unmap, urid: URID) -> Option<CString> {
+    unmap.unmap(urid).map(CStr::to_owned)
+}
+
+/// Format the value of a property as a Turtle object.
+fn turtle_value(property: &StoredProperty, unmap: &mut Unmap) -> Result<String, StateErr> {
+    let value_type = unmap_uri(unmap, property.value_type).ok_or(StateErr::BadType)?;
+    let value = property.value.as_slice();
+    let typed = |value: String, datatype: &str| format!("\"{}\"^^xsd:{}", value, datatype);
+    let string = || {
+        CStr::from_bytes_with_nul(value)
+            .ok()
+            .and_then(|value| value.to_str().ok())
+            .ok_or(StateErr::BadType)
+    };
+    let turtle = match value_type.as_c_str() {
+        uri if uri == uris::INT_TYPE_URI => typed(scalar::<i32>(value)?.to_string(), "int"),
+        uri if uri == uris::LONG_TYPE_URI => typed(scalar::<i64>(value)?.to_string(), "long"),
+        uri if uri == uris::FLOAT_TYPE_URI => {
+            typed(format!("{:?}", scalar::<f32>(value)?), "float")
+        }
+        uri if uri == uris::DOUBLE_TYPE_URI => {
+            typed(format!("{:?}", scalar::<f64>(value)?), "double")
+        }
+        uri if uri == uris::BOOL_TYPE_URI => typed(scalar::<bool>(value)?.to_string(), "boolean"),
+        uri if uri == uris::STRING_TYPE_URI => literal(string()?),
+        uri if uri == uris::URID_TYPE_URI => {
+            let uri = unmap_uri(unmap, scalar::<URID>(value)?).ok_or(StateErr::BadType)?;
+            format!("<{}>", uri.to_str().map_err(|_| StateErr::BadType)?)
+        }
+        uri if uri == uris::URI_TYPE_URI || uri == uris::PATH_TYPE_URI => {
+            format!("<{}>", escape_iri(string()?))
+        }
+        _ => format!(
+            "\"{}\"^^<{}>",
+            base64(value),
+            value_type.to_str().map_err(|_| StateErr::BadType)?
+        ),
+    };
+    Ok(turtle)
+}
+
+fn scalar<T: StateScalar>(value: &[u8]) -> Result<T, StateErr> {
+    T::from_bytes(value).ok_or(StateErr::BadType)
+}
+
+/// Percent-encode the characters that aren't allowed in Turtle IRIs.
+fn escape_iri(iri: &str) -> String {
+    let mut escaped = String::with_capacity(iri.len());
+    for c in iri.chars() {
+        match c {
+            '\u{0}'..=' ' | '<' | '>' | '"' | '{' | '}' | '|' | '\\' | '^' | '`' => {
+                let _ = write!(escaped, "%{:02X}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Encode bytes with the standard Base64 alphabet and padding.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, byte)| {
+            group | (*byte as u32) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(group >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod test {
+    use crate::preset::*;
+
+    #[test]
+    fn test_base64() {
+        assert_eq!("", base64(b""));
+        assert_eq!("Zg==", base64(b"f"));
+        assert_eq!("Zm8=", base64(b"fo"));
+        assert_eq!("Zm9v", base64(b"foo"));
+        assert_eq!("Zm9vYmFy", base64(b"foobar"));
+    }
+
+    #[test]
+    fn test_escape_iri() {
+        assert_eq!("samples/a%20b.wav", escape_iri("samples/a b.wav"));
+        assert_eq!(
+            "http://example.org/a#b",
+            escape_iri("http://example.org/a#b")
+        );
+    }
+}
@@ -0,0 +1,366 @@
+//! Storing and retrieving properties.
+use crate::path::{FileRef, PathMapper};
+use atom::prelude::*;
+use atom::uris;
+use std::ffi::{CStr, CString};
+use std::os::raw::*;
+use urid::{CachedMap, URID};
+
+/// Type to describe pointers to the host's state handles.
+pub type StateHandle = *mut c_void;
+
+/// The host's function to store a property.
+pub type StoreFunction = extern "C" fn(
+    handle: StateHandle,
+    key: URID,
+    value: *const c_void,
+    size: usize,
+    value_type: URID,
+    flags: u32,
+) -> u32;
+
+/// The host's function to retrieve a property.
+pub type RetrieveFunction = extern "C" fn(
+    handle: StateHandle,
+    key: URID,
+    size: *mut usize,
+    value_type: *mut URID,
+    flags: *mut u32,
+) -> *const c_void;
+
+/// Flags that describe a stored value.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct StateFlags(pub u32);
+
+impl StateFlags {
+    /// The value is plain old data: It doesn't contain pointers or other references to memory
+    /// and may be copied freely.
+    pub const POD: Self = StateFlags(1);
+    /// The value can be used on any machine, regardless of its architecture or endianness.
+    pub const PORTABLE: Self = StateFlags(1 << 1);
+    /// The state is saved natively, for example for a copy of the plugin instance, and doesn't
+    /// need to be portable.
+    pub const NATIVE: Self = StateFlags(1 << 2);
+
+    /// Return `true` if all flags of `other` are set.
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for StateFlags {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        StateFlags(self.0 | other.0)
+    }
+}
+
+/// Errors that may occur when saving or restoring state.
+///
+/// The discriminants are the status codes of the LV2 state extension.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StateErr {
+    /// An unknown error occurred.
+    Unknown = 1,
+    /// A property has a type that can't be handled.
+    BadType = 2,
+    /// A property has flags that can't be handled.
+    BadFlags = 3,
+    /// A required feature is missing.
+    NoFeature = 4,
+    /// A required property is missing.
+    NoProperty = 5,
+    /// There is not enough space to store a property.
+    NoSpace = 6,
+}
+
+impl StateErr {
+    /// Interpret a status code of the state extension.
+    pub fn from_status(status: u32) -> Result<(), StateErr> {
+        match status {
+            0 => Ok(()),
+            2 => Err(StateErr::BadType),
+            3 => Err(StateErr::BadFlags),
+            4 => Err(StateErr::NoFeature),
+            5 => Err(StateErr::NoProperty),
+            6 => Err(StateErr::NoSpace),
+            _ => Err(StateErr::Unknown),
+        }
+    }
+
+    /// Convert a result to a status code of the state extension.
+    pub fn into_status(result: Result<(), StateErr>) -> u32 {
+        match result {
+            Ok(()) => 0,
+            Err(error) => error as u32,
+        }
+    }
+}
+
+/// Scalar values that can be stored as properties.
+///
+/// This trait is implemented for all scalar atom types. Scalars are stored in the native byte
+/// order, but since the type of the value is stored too, they are still considered portable.
+pub trait StateScalar: ScalarAtomBody + Copy {
+    /// Interpret the stored bytes of a value.
+    ///
+    /// `None` is returned if the bytes don't form a valid value.
+    fn from_bytes(bytes: &[u8]) -> Option<Self>;
+}
+
+macro_rules! impl_state_scalar {
+    ($($t:ty),*) => {
+        $(
+            impl StateScalar for $t {
+                fn from_bytes(bytes: &[u8]) -> Option<Self> {
+                    let mut value = [0u8; std::mem::size_of::<$t>()];
+                    if bytes.len() != value.len() {
+                        return None;
+                    }
+                    value.copy_from_slice(bytes);
+                    Some(<$t>::from_ne_bytes(value))
+                }
+            }
+        )*
+    };
+}
+
+impl_state_scalar!(i32, i64, f32, f64, URID);
+
+impl StateScalar for bool {
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        <bool as ScalarAtomBody>::from_body(bytes).ok().cloned()
+    }
+}
+
+/// Look up the URID of a type, which has to be mapped already.
+fn type_urid(uri: &CStr, urids: &CachedMap) -> Result<URID, StateErr> {
+    urids.get(uri).ok_or(StateErr::BadType)
+}
+
+/// A handle to store the properties of a plugin's state.
+///
+/// Every property has a key, which is a URID, a value, the URID of the value's type and flags.
+/// The typed methods look up the URIDs of the types in a [`CachedMap`](../lv2rs_urid/struct.CachedMap.html)
+/// without mapping them, since `save` may be called concurrently with `run`. Therefore, the atom
+/// types have to be mapped in advance, for example with an
+/// [`AtomURIDs`](../lv2rs_atom/uris/struct.AtomURIDs.html) struct in `instantiate`. If a type isn't
+/// mapped, `StateErr::BadType` is returned.
+pub struct StoreHandle {
+    store: StoreFunction,
+    handle: StateHandle,
+}
+
+impl StoreHandle {
+    /// Create a new store handle from the host's function and handle.
+    pub fn new(store: StoreFunction, handle: StateHandle) -> Self {
+        Self { store, handle }
+    }
+
+    /// Store a property as raw bytes.
+    pub fn store_raw(
+        &mut self,
+        key: URID,
+        value: &[u8],
+        value_type: URID,
+        flags: StateFlags,
+    ) -> Result<(), StateErr> {
+        let status = (self.store)(
+            self.handle,
+            key,
+            value.as_ptr() as *const c_void,
+            value.len(),
+            value_type,
+            flags.0,
+        );
+        StateErr::from_status(status)
+    }
+
+    /// Store a scalar value, like a float or an integer.
+    pub fn store_scalar<T: StateScalar>(
+        &mut self,
+        key: URID,
+        value: T,
+        urids: &CachedMap,
+    ) -> Result<(), StateErr> {
+        let value_type = type_urid(T::get_uri(), urids)?;
+        self.store_raw(
+            key,
+            value.as_bytes(),
+            value_type,
+            StateFlags::POD | StateFlags::PORTABLE,
+        )
+    }
+
+    /// Store the body of an atom, with the atom's type.
+    ///
+    /// Only atoms without references to other memory should be stored; You have to decide if
+    /// they are portable.
+    pub fn store_atom(
+        &mut self,
+        key: URID,
+        atom: &Atom,
+        flags: StateFlags,
+    ) -> Result<(), StateErr> {
+        self.store_raw(key, atom.get_raw_body(), atom.atom_type(), flags)
+    }
+
+    /// Store a string.
+    pub fn store_string(
+        &mut self,
+        key: URID,
+        value: &CStr,
+        urids: &CachedMap,
+    ) -> Result<(), StateErr> {
+        let string_type = type_urid(uris::STRING_TYPE_URI.as_cstr(), urids)?;
+        self.store_raw(
+            key,
+            value.to_bytes_with_nul(),
+            string_type,
+            StateFlags::POD | StateFlags::PORTABLE,
+        )
+    }
+
+    /// Store the path of a file.
+    ///
+    /// The absolute path is mapped to an abstract path by the host, which is stored as a path
+    /// atom.
+    pub fn store_path(
+        &mut self,
+        key: URID,
+        path: &CStr,
+        mapper: &PathMapper,
+        urids: &CachedMap,
+    ) -> Result<(), StateErr> {
+        let path_type = type_urid(uris::PATH_TYPE_URI.as_cstr(), urids)?;
+        let abstract_path = mapper.abstract_path(path).ok_or(StateErr::Unknown)?;
+        self.store_raw(
+            key,
+            abstract_path.as_bytes_with_nul(),
+            path_type,
+            StateFlags::POD,
+        )
+    }
+
+    /// Store the path of a referenced file.
+    ///
+    /// The path is stored just like with [`store_path`](#method.store_path).
+    pub fn store_file(
+        &mut self,
+        key: URID,
+        file: &FileRef,
+        mapper: &PathMapper,
+        urids: &CachedMap,
+    ) -> Result<(), StateErr> {
+        self.store_path(key, file.path(), mapper, urids)
+    }
+}
+
+/// A property that was retrieved from the host.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Property<'a> {
+    /// The raw value of the property.
+    pub value: &'a [u8],
+    /// The URID of the value's type.
+    pub value_type: URID,
+    /// The flags of the value.
+    pub flags: StateFlags,
+}
+
+/// A handle to retrieve the properties of a plugin's state.
+///
+/// Just like the [`StoreHandle`](struct.StoreHandle.html), the typed methods look up the URIDs of
+/// the types in a `CachedMap` and return `StateErr::BadType` if they aren't mapped or don't match
+/// the stored type.
+pub struct RetrieveHandle {
+    retrieve: RetrieveFunction,
+    handle: StateHandle,
+}
+
+impl RetrieveHandle {
+    /// Create a new retrieve handle from the host's function and handle.
+    pub fn new(retrieve: RetrieveFunction, handle: StateHandle) -> Self {
+        Self { retrieve, handle }
+    }
+
+    /// Retrieve a property.
+    ///
+    /// If the property wasn't stored, `StateErr::NoProperty` is returned.
+    pub fn retrieve(&self, key: URID) -> Result<Property<'_>, StateErr> {
+        let mut size: usize = 0;
+        let mut value_type: URID = 0;
+        let mut flags: u32 = 0;
+        let value = (self.retrieve)(self.handle, key, &mut size, &mut value_type, &mut flags);
+        if value.is_null() {
+            return Err(StateErr::NoProperty);
+        }
+        Ok(Property {
+            value: unsafe { std::slice::from_raw_parts(value as *const u8, size) },
+            value_type,
+            flags: StateFlags(flags),
+        })
+    }
+
+    /// Retrieve a property and check its type.
+    fn retrieve_typed(&self, key: URID, uri: &CStr, urids: &CachedMap) -> Result<&[u8], StateErr> {
+        let value_type = type_urid(uri, urids)?;
+        let property = self.retrieve(key)?;
+        if property.value_type != value_type {
+            return Err(StateErr::BadType);
+        }
+        Ok(property.value)
+    }
+
+    /// Retrieve a scalar value, like a float or an integer.
+    pub fn retrieve_scalar<T: StateScalar>(
+        &self,
+        key: URID,
+        urids: &CachedMap,
+    ) -> Result<T, StateErr> {
+        let value = self.retrieve_typed(key, T::get_uri(), urids)?;
+        T::from_bytes(value).ok_or(StateErr::BadType)
+    }
+
+    /// Retrieve a string.
+    pub fn retrieve_string(&self, key: URID, urids: &CachedMap) -> Result<&CStr, StateErr> {
+        let uri = uris::STRING_TYPE_URI.as_cstr();
+        let value = self.retrieve_typed(key, uri, urids)?;
+        CStr::from_bytes_with_nul(value).map_err(|_| StateErr::BadType)
+    }
+
+    /// Retrieve the path of a file.
+    ///
+    /// The stored, abstract path is mapped back to an absolute path by the host.
+    pub fn retrieve_path(
+        &self,
+        key: URID,
+        mapper: &PathMapper,
+        urids: &CachedMap,
+    ) -> Result<CString, StateErr> {
+        let uri = uris::PATH_TYPE_URI.as_cstr();
+        let value = self.retrieve_typed(key, uri, urids)?;
+        let abstract_path = CStr::from_bytes_with_nul(value).map_err(|_| StateErr::BadType)?;
+        mapper.absolute_path(abstract_path).ok_or(StateErr::Unknown)
+    }
+
+    /// Retrieve a referenced file.
+    ///
+    /// If the retrieved path is the path of one of the `loaded` files, a clone of that reference
+    /// is returned, so the file doesn't have to be loaded again. Otherwise, a new reference is
+    /// created.
+    pub fn retrieve_file(
+        &self,
+        key: URID,
+        mapper: &PathMapper,
+        urids: &CachedMap,
+        loaded: &[FileRef],
+    ) -> Result<FileRef, StateErr> {
+        let path = self.retrieve_path(key, mapper, urids)?;
+        Ok(loaded
+            .iter()
+            .find(|file| file.path() == path.as_c_str())
+            .cloned()
+            .unwrap_or_else(|| FileRef::new(path)))
+    }
+}
@@ -0,0 +1,20 @@
+//! URIs of the LV2 state and presets extensions.
+use core::uris::Uri;
+
+pub const URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/state\0");
+pub const INTERFACE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/state#interface\0");
+pub const LOAD_DEFAULT_STATE_URI: Uri =
+    Uri::new(b"http://lv2plug.in/ns/ext/state#loadDefaultState\0");
+pub const FREE_PATH_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/state#freePath\0");
+pub const MAKE_PATH_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/state#makePath\0");
+pub const MAP_PATH_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/state#mapPath\0");
+pub const STATE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/state#state\0");
+pub const STATE_CHANGED_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/state#StateChanged\0");
+pub const THREAD_SAFE_RESTORE_URI: Uri =
+    Uri::new(b"http://lv2plug.in/ns/ext/state#threadSafeRestore\0");
+
+pub const PRESETS_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/presets\0");
+pub const BANK_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/presets#Bank\0");
+pub const PRESET_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/presets#Preset\0");
+pub const PRESET_BANK_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/presets#bank\0");
+pub const PRESET_VALUE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/presets#value\0");
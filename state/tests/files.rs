@@ -0,0 +1,344 @@
+extern crate lv2rs_atom as atom;
+extern crate lv2rs_core as core;
+extern crate lv2rs_state as state;
+extern crate lv2rs_urid as urid;
+
+use atom::uris::AtomURIDs;
+use core::uris::Uri;
+use state::path::*;
+use state::*;
+use std::collections::BTreeMap;
+use std::ffi::{CStr, CString};
+use std::os::raw::*;
+use std::ptr::{null, null_mut};
+use std::sync::Mutex;
+use urid::host::HostMap;
+use urid::{CachedMap, URID};
+
+const VOICE_KEY_URIS: [Uri; 2] = [
+    Uri::new(b"http://example.org/Sampler#voice1\0"),
+    Uri::new(b"http://example.org/Sampler#voice2\0"),
+];
+const RECORDING_KEY_URI: Uri = Uri::new(b"http://example.org/Sampler#recording\0");
+
+/// A sampler with two voices, which share their samples, and a recording.
+struct Sampler {
+    urids: CachedMap,
+    voice_keys: [URID; 2],
+    recording_key: URID,
+    voices: Mutex<Vec<FileRef>>,
+    recording: FileRef,
+}
+
+impl core::Plugin for Sampler {
+    fn instantiate(
+        _context: &core::InstantiationContext,
+        features: Option<&core::FeaturesList>,
+    ) -> Result<Self, core::InstantiateError> {
+        let missing_make_path = core::InstantiateError::missing_feature::<MakePath>;
+        let features = features.ok_or_else(missing_make_path)?;
+        let recording = PathMaker::try_from_features(features)
+            .ok_or_else(missing_make_path)?
+            .make_path(CStr::from_bytes_with_nul(b"recording.wav\0").unwrap())
+            .ok_or_else(|| core::InstantiateError::Other("Failed to make a path".into()))?;
+        let mut urids = CachedMap::try_from_features(features)
+            .ok_or_else(core::InstantiateError::missing_feature::<urid::Map>)?;
+        AtomURIDs::new(&mut urids);
+        let voice_keys = [urids.map(VOICE_KEY_URIS[0]), urids.map(VOICE_KEY_URIS[1])];
+        let recording_key = urids.map(RECORDING_KEY_URI);
+        let default = FileRef::new(CString::new("/samples/default.wav").unwrap());
+        Ok(Self {
+            urids,
+            voice_keys,
+            recording_key,
+            voices: Mutex::new(vec![default.clone(), default]),
+            recording: FileRef::new(recording),
+        })
+    }
+
+    fn connect_port(&mut self, _port: u32, _data: *mut ()) {}
+
+    fn run(&mut self, _context: &mut core::RunContext) {}
+
+    fn extension_data(uri: &CStr) -> Option<&'static dyn core::ExtensionData> {
+        core::extension_data!(uri, [(state::uris::INTERFACE_URI, &STATE_INTERFACE)])
+    }
+}
+
+impl StateHandler for Sampler {
+    fn save(
+        &self,
+        store: &mut StoreHandle,
+        _flags: StateFlags,
+        features: Option<&core::FeaturesList>,
+    ) -> Result<(), StateErr> {
+        let mapper = PathMapper::try_from_features(features.ok_or(StateErr::NoFeature)?)
+            .ok_or(StateErr::NoFeature)?;
+        let voices = self.voices.lock().unwrap();
+        for (key, file) in self.voice_keys.iter().zip(voices.iter()) {
+            store.store_file(*key, file, &mapper, &self.urids)?;
+        }
+        store.store_file(self.recording_key, &self.recording, &mapper, &self.urids)
+    }
+
+    fn restore(
+        &mut self,
+        retrieve: &RetrieveHandle,
+        flags: StateFlags,
+        features: Option<&core::FeaturesList>,
+    ) -> Result<(), StateErr> {
+        self.restore_concurrently(retrieve, flags, features)
+    }
+}
+
+impl ThreadSafeRestore for Sampler {
+    fn restore_concurrently(
+        &self,
+        retrieve: &RetrieveHandle,
+        _flags: StateFlags,
+        features: Option<&core::FeaturesList>,
+    ) -> Result<(), StateErr> {
+        let mapper = PathMapper::try_from_features(features.ok_or(StateErr::NoFeature)?)
+            .ok_or(StateErr::NoFeature)?;
+        // Files that are already loaded or were restored before are reused.
+        let mut loaded = self.voices.lock().unwrap().clone();
+        let mut voices = Vec::with_capacity(self.voice_keys.len());
+        for key in self.voice_keys.iter() {
+            let file = retrieve.retrieve_file(*key, &mapper, &self.urids, &loaded)?;
+            loaded.push(file.clone());
+            voices.push(file);
+        }
+        *self.voices.lock().unwrap() = voices;
+        Ok(())
+    }
+}
+
+static STATE_INTERFACE: StateInterface = StateInterface::with_thread_safe_restore::<Sampler>();
+
+core::lv2_main!(core, Sampler, b"http://example.org/Sampler\0");
+
+/// The properties stored by the test host.
+type Properties = BTreeMap<URID, (Vec<u8>, URID, u32)>;
+
+extern "C" fn store(
+    handle: StateHandle,
+    key: URID,
+    value: *const c_void,
+    size: usize,
+    value_type: URID,
+    flags: u32,
+) -> u32 {
+    let properties = unsafe { (handle as *mut Properties).as_mut() }.unwrap();
+    let value = unsafe { std::slice::from_raw_parts(value as *const u8, size) };
+    properties.insert(key, (value.to_owned(), value_type, flags));
+    0
+}
+
+extern "C" fn retrieve(
+    handle: StateHandle,
+    key: URID,
+    size: *mut usize,
+    value_type: *mut URID,
+    flags: *mut u32,
+) -> *const c_void {
+    let properties = unsafe { (handle as *mut Properties).as_ref() }.unwrap();
+    match properties.get(&key) {
+        Some((value, stored_type, stored_flags)) => {
+            unsafe {
+                *size = value.len();
+                *value_type = *stored_type;
+                *flags = *stored_flags;
+            }
+            value.as_ptr() as *const c_void
+        }
+        None => null(),
+    }
+}
+
+const SAMPLE_DIRECTORY: &str = "/samples/";
+const SESSION_DIRECTORY: &str = "/session/";
+
+extern "C" fn abstract_path(_handle: MapPathHandle, absolute_path: *const c_char) -> *mut c_char {
+    let absolute_path = unsafe { CStr::from_ptr(absolute_path) }.to_str().unwrap();
+    let abstract_path = absolute_path.trim_start_matches(SAMPLE_DIRECTORY);
+    CString::new(abstract_path).unwrap().into_raw()
+}
+
+extern "C" fn absolute_path(_handle: MapPathHandle, abstract_path: *const c_char) -> *mut c_char {
+    let abstract_path = unsafe { CStr::from_ptr(abstract_path) }.to_str().unwrap();
+    if abstract_path.starts_with('/') {
+        return CString::new(abstract_path).unwrap().into_raw();
+    }
+    CString::new(format!("{}{}", SAMPLE_DIRECTORY, abstract_path))
+        .unwrap()
+        .into_raw()
+}
+
+extern "C" fn make_path(_handle: MakePathHandle, path: *const c_char) -> *mut c_char {
+    let path = unsafe { CStr::from_ptr(path) }.to_str().unwrap();
+    CString::new(format!("{}{}", SESSION_DIRECTORY, path))
+        .unwrap()
+        .into_raw()
+}
+
+extern "C" fn free_path(_handle: FreePathHandle, path: *mut c_char) {
+    drop(unsafe { CString::from_raw(path) });
+}
+
+#[test]
+fn test_file_refs() {
+    let first = FileRef::new(CString::new("/samples/kick.wav").unwrap());
+    let second = first.clone();
+    let third = FileRef::new(CString::new("/samples/kick.wav").unwrap());
+    assert_eq!(2, first.ref_count());
+    assert_eq!(first, third);
+    assert!(first.ptr_eq(&second));
+    assert!(!first.ptr_eq(&third));
+    #[cfg(unix)]
+    assert_eq!(
+        Ok(std::path::PathBuf::from("/samples/kick.wav")),
+        first.to_path_buf()
+    );
+}
+
+#[test]
+fn test_thread_safe_restore() {
+    let host_map = HostMap::new();
+    let mut map_path = MapPath {
+        handle: null_mut(),
+        abstract_path,
+        absolute_path,
+    };
+    let mut make_path = MakePath {
+        handle: null_mut(),
+        path: make_path,
+    };
+    let mut free_path = FreePath {
+        handle: null_mut(),
+        free_path,
+    };
+    let urid_features = host_map.features();
+    let [map_feature, unmap_feature] = &*urid_features;
+    let map_path_feature = core::Feature::new(
+        state::uris::MAP_PATH_URI.as_ptr(),
+        &mut map_path as *mut MapPath as *mut c_void,
+    );
+    let make_path_feature = core::Feature::new(
+        state::uris::MAKE_PATH_URI.as_ptr(),
+        &mut make_path as *mut MakePath as *mut c_void,
+    );
+    let free_path_feature = core::Feature::new(
+        state::uris::FREE_PATH_URI.as_ptr(),
+        &mut free_path as *mut FreePath as *mut c_void,
+    );
+    let instance_features: [*const core::Feature; 5] = [
+        map_feature,
+        unmap_feature,
+        &make_path_feature,
+        &free_path_feature,
+        null(),
+    ];
+    let state_features: [*const core::Feature; 3] = [&map_path_feature, &free_path_feature, null()];
+
+    let descriptor = unsafe { lv2_descriptor(0) };
+    let descriptor_ref = unsafe { descriptor.as_ref() }.unwrap();
+
+    // The plugin requires the make path feature.
+    let handle = unsafe {
+        (descriptor_ref.instantiate)(
+            descriptor,
+            44100.0,
+            b"/\0".as_ptr() as *const c_char,
+            instance_features[2..].as_ptr(),
+        )
+    };
+    assert_eq!(handle, null_mut());
+
+    let handle = unsafe {
+        (descriptor_ref.instantiate)(
+            descriptor,
+            44100.0,
+            b"/\0".as_ptr() as *const c_char,
+            instance_features.as_ptr(),
+        )
+    };
+    assert_ne!(handle, null_mut());
+
+    let interface = unsafe { (descriptor_ref.extension_data)(state::uris::INTERFACE_URI.as_ptr()) };
+    let interface = unsafe { (interface as *const StateInterface).as_ref() }.unwrap();
+
+    {
+        let plugin = unsafe { core::get_instance::<Sampler>(handle) };
+        assert_eq!(
+            CStr::from_bytes_with_nul(b"/session/recording.wav\0").unwrap(),
+            plugin.recording.path()
+        );
+        let kick = FileRef::new(CString::new("/samples/kick.wav").unwrap());
+        *plugin.voices.lock().unwrap() = vec![kick.clone(), kick];
+    }
+
+    let mut properties = Properties::new();
+    let properties_handle = &mut properties as *mut Properties as StateHandle;
+    let status =
+        unsafe { (interface.save)(handle, store, properties_handle, 0, state_features.as_ptr()) };
+    assert_eq!(0, status);
+
+    let voice_key = host_map.get(VOICE_KEY_URIS[1].as_cstr());
+    assert_eq!(b"kick.wav\0", properties[&voice_key.unwrap()].0.as_slice());
+    let recording_key = host_map.get(RECORDING_KEY_URI.as_cstr());
+    assert_eq!(
+        b"/session/recording.wav\0",
+        properties[&recording_key.unwrap()].0.as_slice()
+    );
+
+    // Restoring the same files keeps the loaded references.
+    let loaded = unsafe { core::get_instance::<Sampler>(handle) }
+        .voices
+        .lock()
+        .unwrap()[0]
+        .clone();
+    let status = unsafe {
+        (interface.restore)(
+            handle,
+            retrieve,
+            properties_handle,
+            0,
+            state_features.as_ptr(),
+        )
+    };
+    assert_eq!(0, status);
+    {
+        let plugin = unsafe { core::get_instance::<Sampler>(handle) };
+        let voices = plugin.voices.lock().unwrap();
+        assert!(voices[0].ptr_eq(&loaded));
+        assert!(voices[1].ptr_eq(&loaded));
+        assert_eq!(3, loaded.ref_count());
+    }
+
+    // New files are shared between the voices, too.
+    properties.get_mut(&voice_key.unwrap()).unwrap().0 = b"snare.wav\0".to_vec();
+    let voice_key = host_map.get(VOICE_KEY_URIS[0].as_cstr());
+    properties.get_mut(&voice_key.unwrap()).unwrap().0 = b"snare.wav\0".to_vec();
+    let status = unsafe {
+        (interface.restore)(
+            handle,
+            retrieve,
+            properties_handle,
+            0,
+            state_features.as_ptr(),
+        )
+    };
+    assert_eq!(0, status);
+    {
+        let plugin = unsafe { core::get_instance::<Sampler>(handle) };
+        let voices = plugin.voices.lock().unwrap();
+        assert_eq!(
+            CStr::from_bytes_with_nul(b"/samples/snare.wav\0").unwrap(),
+            voices[0].path()
+        );
+        assert!(voices[0].ptr_eq(&voices[1]));
+        assert_eq!(1, loaded.ref_count());
+    }
+
+    unsafe { (descriptor_ref.cleanup)(handle) };
+}
@@ -0,0 +1,321 @@
+extern crate lv2rs_atom as atom;
+extern crate lv2rs_core as core;
+extern crate lv2rs_state as state;
+extern crate lv2rs_urid as urid;
+
+use atom::patch::PatchURIDs;
+use atom::ports::*;
+use atom::prelude::*;
+use atom::sequence::{TimeStamp, TimeUnit};
+use core::uris::Uri;
+use state::morph::Morph;
+use state::parameters::*;
+use state::preset::Preset;
+use state::*;
+use urid::debug::DebugMap;
+use urid::{CachedMap, URID};
+
+const GAIN: usize = 0;
+const MODE: usize = 1;
+
+const PARAMETERS: &[ParameterInfo] = &[
+    ParameterInfo {
+        minimum: Some(ParameterValue::Float(-90.0)),
+        maximum: Some(ParameterValue::Float(24.0)),
+        ..ParameterInfo::new(
+            Uri::new(b"http://example.org/Params#gain\0"),
+            "Gain",
+            ParameterValue::Float(0.0),
+        )
+    },
+    ParameterInfo {
+        minimum: Some(ParameterValue::Int(0)),
+        maximum: Some(ParameterValue::Int(3)),
+        ..ParameterInfo::new(
+            Uri::new(b"http://example.org/Params#mode\0"),
+            "Mode",
+            ParameterValue::Int(1),
+        )
+    },
+];
+
+/// A plugin that only has parameters.
+struct ParameterPlugin {
+    urids: CachedMap,
+    parameters: Parameters,
+}
+
+impl core::Plugin for ParameterPlugin {
+    fn instantiate(
+        _context: &core::InstantiationContext,
+        _features: Option<&core::FeaturesList>,
+    ) -> Result<Self, core::InstantiateError> {
+        Err(core::InstantiateError::Other("Not instantiated".into()))
+    }
+
+    fn connect_port(&mut self, _port: u32, _data: *mut ()) {}
+
+    fn run(&mut self, _context: &mut core::RunContext) {}
+}
+
+impl StateHandler for ParameterPlugin {
+    fn save(
+        &self,
+        store: &mut StoreHandle,
+        _flags: StateFlags,
+        _features: Option<&core::FeaturesList>,
+    ) -> Result<(), StateErr> {
+        self.parameters.save(store, &self.urids)
+    }
+
+    fn restore(
+        &mut self,
+        retrieve: &RetrieveHandle,
+        _flags: StateFlags,
+        _features: Option<&core::FeaturesList>,
+    ) -> Result<(), StateErr> {
+        self.parameters.restore(retrieve, &self.urids)
+    }
+}
+
+/// Create an atom buffer of the given size.
+fn atom_space(size: usize) -> Vec<u64> {
+    let mut space = vec![0u64; size / 8];
+    let atom = unsafe { (space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+    *(atom.mut_size()) = size as i32 - 8;
+    space
+}
+
+#[test]
+fn test_parameters() {
+    let mut debug_map = DebugMap::new();
+    let mut urids = unsafe { debug_map.create_cached_map() };
+    let parameters = Parameters::new(PARAMETERS, &mut urids);
+    let patch = PatchURIDs::new(&mut urids);
+    let gain_key = parameters.key(GAIN).unwrap();
+    let mode_key = parameters.key(MODE).unwrap();
+
+    assert_eq!(2, parameters.len());
+    assert_eq!(Some(MODE), parameters.index_of(mode_key));
+    assert_eq!(Some(ParameterValue::Int(1)), parameters.get(MODE));
+    assert!(!parameters.has_notifications());
+    assert!(!parameters.set(GAIN, ParameterValue::Int(2)));
+    assert!(!parameters.set(2, ParameterValue::Int(2)));
+
+    // Writing the messages of the control port.
+    let mut control_space = atom_space(512);
+    let control_atom = unsafe { (control_space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+    {
+        let mut port: AtomOutputPort<Sequence> = AtomOutputPort::new();
+        port.connect_port(control_atom);
+        let mut frame = unsafe { port.write_atom_body(&TimeUnit::Frames, &mut urids) }.unwrap();
+        {
+            let mut set = frame
+                .push_event::<Object>(TimeStamp::Frames(0), &(0, patch.set), &mut urids)
+                .unwrap();
+            set.push_property::<URID>(patch.property, 0, &gain_key, &mut urids)
+                .unwrap();
+            set.push_property::<f32>(patch.value, 0, &-120.0, &mut urids)
+                .unwrap();
+        }
+        {
+            // Values of the wrong type are ignored.
+            let mut set = frame
+                .push_event::<Object>(TimeStamp::Frames(1), &(0, patch.set), &mut urids)
+                .unwrap();
+            set.push_property::<URID>(patch.property, 0, &mode_key, &mut urids)
+                .unwrap();
+            set.push_property::<f32>(patch.value, 0, &2.0, &mut urids)
+                .unwrap();
+        }
+        frame
+            .push_event::<i32>(TimeStamp::Frames(2), &42, &mut urids)
+            .unwrap();
+    }
+
+    let mut port: AtomInputPort<Sequence> = AtomInputPort::new();
+    port.connect_port(control_atom);
+    let context = core::RunContext::new(1, 0, 44100.0, None);
+    let sequence = unsafe { port.read(&context, &mut urids) }.unwrap();
+    assert!(parameters.handle_sequence(sequence, &mut urids));
+    assert_eq!(Some(-90.0), parameters.get(GAIN).unwrap().as_float());
+    assert_eq!(Some(1), parameters.get(MODE).unwrap().as_int());
+    assert!(parameters.has_notifications());
+
+    // Writing the notifications.
+    let mut notify_space = atom_space(512);
+    let notify_atom = notify_space.as_mut_ptr() as *mut Atom;
+    let write_notifications = |parameters: &Parameters, urids: &mut CachedMap| {
+        // Like a host, reset the capacity of the buffer before every cycle.
+        let atom = unsafe { notify_atom.as_mut() }.unwrap();
+        *(atom.mut_size()) = 512 - 8;
+        let mut port: AtomOutputPort<Sequence> = AtomOutputPort::new();
+        port.connect_port(atom);
+        let mut frame = unsafe { port.write_atom_body(&TimeUnit::Frames, urids) }.unwrap();
+        parameters
+            .write_notifications(&mut frame, TimeStamp::Frames(0), urids)
+            .unwrap();
+    };
+    let read_notifications = |urids: &mut CachedMap| -> Vec<(URID, f32)> {
+        let atom = unsafe { notify_atom.as_ref() }.unwrap();
+        let sequence = atom.get_body::<Sequence>(urids).unwrap();
+        let mut notifications = Vec::new();
+        for (_, event) in sequence.iter(urids) {
+            let object = event.get_body::<Object>(urids).unwrap();
+            assert_eq!(patch.set, object.header.otype);
+            let mut properties = object.iter();
+            let (header, key) = properties.next().unwrap();
+            assert_eq!(patch.property, header.key);
+            let key = *key.get_body::<URID>(urids).unwrap();
+            let (header, value) = properties.next().unwrap();
+            assert_eq!(patch.value, header.key);
+            let value = match value.get_body::<f32>(urids) {
+                Ok(value) => *value,
+                Err(_) => *value.get_body::<i32>(urids).unwrap() as f32,
+            };
+            notifications.push((key, value));
+        }
+        notifications
+    };
+
+    write_notifications(&parameters, &mut urids);
+    assert!(!parameters.has_notifications());
+    assert_eq!(vec![(gain_key, -90.0)], read_notifications(&mut urids));
+
+    // A `patch:Get` without a property requests all parameters.
+    let mut get_space = atom_space(64);
+    let get_atom = unsafe { (get_space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap();
+    {
+        let mut port: AtomOutputPort<Object> = AtomOutputPort::new();
+        port.connect_port(get_atom);
+        unsafe { port.write_atom_body(&(0, patch.get), &mut urids) }.unwrap();
+    }
+    assert_eq!(None, parameters.handle_message(get_atom, &mut urids));
+    write_notifications(&parameters, &mut urids);
+    assert_eq!(
+        vec![(gain_key, -90.0), (mode_key, 1.0)],
+        read_notifications(&mut urids)
+    );
+
+    // Saving and restoring the values.
+    let mut plugin = ParameterPlugin { urids, parameters };
+    let mut preset = Preset::new("http://example.org/Params#preset", "Preset");
+    preset.save(&plugin, None).unwrap();
+    assert_eq!(2, preset.properties().len());
+
+    plugin.parameters.set(GAIN, ParameterValue::Float(6.0));
+    plugin.parameters.set(MODE, ParameterValue::Int(5));
+    assert_eq!(Some(ParameterValue::Int(3)), plugin.parameters.get(MODE));
+    write_notifications(&plugin.parameters, &mut plugin.urids);
+
+    preset.load(&mut plugin, None).unwrap();
+    assert_eq!(
+        Some(ParameterValue::Float(-90.0)),
+        plugin.parameters.get(GAIN)
+    );
+    assert_eq!(Some(ParameterValue::Int(1)), plugin.parameters.get(MODE));
+    assert!(plugin.parameters.has_notifications());
+
+    // Missing properties are reset to the default values.
+    Preset::new("http://example.org/Params#empty", "Empty")
+        .load(&mut plugin, None)
+        .unwrap();
+    assert_eq!(
+        Some(ParameterValue::Float(0.0)),
+        plugin.parameters.get(GAIN)
+    );
+}
+
+#[test]
+fn test_morph() {
+    let mut debug_map = DebugMap::new();
+    let mut urids = unsafe { debug_map.create_cached_map() };
+    let parameters = Parameters::new(PARAMETERS, &mut urids);
+    let patch = PatchURIDs::new(&mut urids);
+    let gain_key = parameters.key(GAIN).unwrap();
+
+    // The target values are saved in a preset.
+    parameters.set(GAIN, ParameterValue::Float(-20.0));
+    parameters.set(MODE, ParameterValue::Int(3));
+    let plugin = ParameterPlugin { urids, parameters };
+    let mut preset = Preset::new("http://example.org/Params#target", "Target");
+    preset.save(&plugin, None).unwrap();
+    plugin.parameters.reset();
+    let ParameterPlugin {
+        mut urids,
+        parameters,
+    } = plugin;
+
+    let target = parameters.preset_values(&preset, &urids).unwrap();
+    assert_eq!(
+        vec![ParameterValue::Float(-20.0), ParameterValue::Int(3)],
+        target
+    );
+    let empty = Preset::new("http://example.org/Params#empty", "Empty");
+    assert_eq!(
+        vec![ParameterValue::Float(0.0), ParameterValue::Int(1)],
+        parameters.preset_values(&empty, &urids).unwrap()
+    );
+    assert!(Morph::new(&parameters, vec![], target.clone(), 100).is_none());
+    assert!(Morph::new(
+        &parameters,
+        vec![ParameterValue::Int(0), ParameterValue::Int(1)],
+        target.clone(),
+        100
+    )
+    .is_none());
+
+    let mut morph = Morph::new(&parameters, parameters.values(), target.clone(), 100).unwrap();
+    assert_eq!(0.0, morph.progress());
+    assert_eq!(parameters.values(), morph.values());
+    morph.advance(40);
+    assert_eq!(
+        vec![ParameterValue::Float(-8.0), ParameterValue::Int(2)],
+        morph.values()
+    );
+
+    // Applying the values to the parameters.
+    assert!(morph.apply(&parameters));
+    assert_eq!(morph.values(), parameters.values());
+    assert!(!morph.apply(&parameters));
+    morph.advance(1000);
+    assert!(morph.is_finished());
+    assert_eq!(100, morph.position());
+    assert!(morph.apply(&parameters));
+    assert_eq!(target, parameters.values());
+
+    // Writing the values as messages, which are understood by the parameters.
+    parameters.reset();
+    let receiver = Parameters::new(PARAMETERS, &mut urids);
+    let mut morph = Morph::new(&parameters, parameters.values(), target, 4).unwrap();
+    let mut space = atom_space(512);
+    let atom = space.as_mut_ptr() as *mut Atom;
+    let mut n_messages = Vec::new();
+    for _ in 0..3 {
+        morph.advance(2);
+        {
+            let atom = unsafe { atom.as_mut() }.unwrap();
+            *(atom.mut_size()) = 512 - 8;
+            let mut port: AtomOutputPort<Sequence> = AtomOutputPort::new();
+            port.connect_port(atom);
+            let mut frame = unsafe { port.write_atom_body(&TimeUnit::Frames, &mut urids) }.unwrap();
+            morph
+                .write_messages(&mut frame, TimeStamp::Frames(0), &mut urids)
+                .unwrap();
+        }
+        let atom = unsafe { atom.as_ref() }.unwrap();
+        let sequence = atom.get_body::<Sequence>(&mut urids).unwrap();
+        let events: Vec<&Atom> = sequence.iter(&mut urids).map(|(_, event)| event).collect();
+        n_messages.push(events.len());
+        for event in events {
+            let object = event.get_body::<Object>(&mut urids).unwrap();
+            assert_eq!(patch.set, object.header.otype);
+            receiver.handle_message(event, &mut urids).unwrap();
+        }
+        assert_eq!(morph.values(), receiver.values());
+    }
+    // Only changed values are written, so nothing is written after the end of the morph.
+    assert_eq!(vec![2, 2, 0], n_messages);
+    assert_eq!(Some(ParameterValue::Float(-20.0)), receiver.get(GAIN));
+    assert_eq!(Some(GAIN), receiver.index_of(gain_key));
+}
@@ -0,0 +1,377 @@
+extern crate lv2rs_atom as atom;
+extern crate lv2rs_core as core;
+extern crate lv2rs_state as state;
+extern crate lv2rs_urid as urid;
+
+use atom::uris::AtomURIDs;
+use core::uris::Uri;
+use state::path::*;
+use state::preset::*;
+use state::*;
+use std::collections::BTreeMap;
+use std::ffi::{CStr, CString};
+use std::os::raw::*;
+use std::ptr::{null, null_mut};
+use urid::host::HostMap;
+use urid::{CachedMap, URID};
+
+const GAIN_KEY_URI: Uri = Uri::new(b"http://example.org/StatePlugin#gain\0");
+const NAME_KEY_URI: Uri = Uri::new(b"http://example.org/StatePlugin#name\0");
+const SAMPLE_KEY_URI: Uri = Uri::new(b"http://example.org/StatePlugin#sample\0");
+
+/// A plugin with a gain, a name and a sample file as its state.
+struct StatePlugin {
+    urids: CachedMap,
+    gain_key: URID,
+    name_key: URID,
+    sample_key: URID,
+    gain: f32,
+    name: CString,
+    sample: CString,
+}
+
+impl core::Plugin for StatePlugin {
+    fn instantiate(
+        _context: &core::InstantiationContext,
+        features: Option<&core::FeaturesList>,
+    ) -> Result<Self, core::InstantiateError> {
+        let mut urids = features
+            .and_then(CachedMap::try_from_features)
+            .ok_or_else(core::InstantiateError::missing_feature::<urid::Map>)?;
+        AtomURIDs::new(&mut urids);
+        let mut map = |uri: Uri| urids.map(uri);
+        let gain_key = map(GAIN_KEY_URI);
+        let name_key = map(NAME_KEY_URI);
+        let sample_key = map(SAMPLE_KEY_URI);
+        Ok(Self {
+            urids,
+            gain_key,
+            name_key,
+            sample_key,
+            gain: 1.0,
+            name: CString::new("default").unwrap(),
+            sample: CString::new("/samples/default.wav").unwrap(),
+        })
+    }
+
+    fn connect_port(&mut self, _port: u32, _data: *mut ()) {}
+
+    fn run(&mut self, _context: &mut core::RunContext) {}
+
+    fn extension_data(uri: &CStr) -> Option<&'static dyn core::ExtensionData> {
+        core::extension_data!(uri, [(state::uris::INTERFACE_URI, &STATE_INTERFACE)])
+    }
+}
+
+impl StateHandler for StatePlugin {
+    fn save(
+        &self,
+        store: &mut StoreHandle,
+        _flags: StateFlags,
+        features: Option<&core::FeaturesList>,
+    ) -> Result<(), StateErr> {
+        let mapper = PathMapper::try_from_features(features.ok_or(StateErr::NoFeature)?)
+            .ok_or(StateErr::NoFeature)?;
+        store.store_scalar(self.gain_key, self.gain, &self.urids)?;
+        store.store_string(self.name_key, &self.name, &self.urids)?;
+        store.store_path(self.sample_key, &self.sample, &mapper, &self.urids)
+    }
+
+    fn restore(
+        &mut self,
+        retrieve: &RetrieveHandle,
+        _flags: StateFlags,
+        features: Option<&core::FeaturesList>,
+    ) -> Result<(), StateErr> {
+        let mapper = PathMapper::try_from_features(features.ok_or(StateErr::NoFeature)?)
+            .ok_or(StateErr::NoFeature)?;
+        self.gain = retrieve.retrieve_scalar(self.gain_key, &self.urids)?;
+        self.name = retrieve
+            .retrieve_string(self.name_key, &self.urids)?
+            .to_owned();
+        self.sample = retrieve.retrieve_path(self.sample_key, &mapper, &self.urids)?;
+        Ok(())
+    }
+}
+
+static STATE_INTERFACE: StateInterface = StateInterface::new::<StatePlugin>();
+
+core::lv2_main!(core, StatePlugin, b"http://example.org/StatePlugin\0");
+
+/// The properties stored by the test host.
+type Properties = BTreeMap<URID, (Vec<u8>, URID, u32)>;
+
+extern "C" fn store(
+    handle: StateHandle,
+    key: URID,
+    value: *const c_void,
+    size: usize,
+    value_type: URID,
+    flags: u32,
+) -> u32 {
+    let properties = unsafe { (handle as *mut Properties).as_mut() }.unwrap();
+    let value = unsafe { std::slice::from_raw_parts(value as *const u8, size) };
+    properties.insert(key, (value.to_owned(), value_type, flags));
+    0
+}
+
+extern "C" fn retrieve(
+    handle: StateHandle,
+    key: URID,
+    size: *mut usize,
+    value_type: *mut URID,
+    flags: *mut u32,
+) -> *const c_void {
+    let properties = unsafe { (handle as *mut Properties).as_ref() }.unwrap();
+    match properties.get(&key) {
+        Some((value, stored_type, stored_flags)) => {
+            unsafe {
+                *size = value.len();
+                *value_type = *stored_type;
+                *flags = *stored_flags;
+            }
+            value.as_ptr() as *const c_void
+        }
+        None => null(),
+    }
+}
+
+const SAMPLE_DIRECTORY: &str = "/samples/";
+
+extern "C" fn abstract_path(_handle: MapPathHandle, absolute_path: *const c_char) -> *mut c_char {
+    let absolute_path = unsafe { CStr::from_ptr(absolute_path) }.to_str().unwrap();
+    let abstract_path = absolute_path.trim_start_matches(SAMPLE_DIRECTORY);
+    CString::new(abstract_path).unwrap().into_raw()
+}
+
+extern "C" fn absolute_path(_handle: MapPathHandle, abstract_path: *const c_char) -> *mut c_char {
+    let abstract_path = unsafe { CStr::from_ptr(abstract_path) }.to_str().unwrap();
+    CString::new(format!("{}{}", SAMPLE_DIRECTORY, abstract_path))
+        .unwrap()
+        .into_raw()
+}
+
+extern "C" fn free_path(_handle: FreePathHandle, path: *mut c_char) {
+    drop(unsafe { CString::from_raw(path) });
+}
+
+#[test]
+fn test_save_and_restore() {
+    let host_map = HostMap::new();
+    let mut map_path = MapPath {
+        handle: null_mut(),
+        abstract_path,
+        absolute_path,
+    };
+    let mut free_path = FreePath {
+        handle: null_mut(),
+        free_path,
+    };
+    let urid_features = host_map.features();
+    let [map_feature, unmap_feature] = &*urid_features;
+    let map_path_feature = core::Feature::new(
+        state::uris::MAP_PATH_URI.as_ptr(),
+        &mut map_path as *mut MapPath as *mut c_void,
+    );
+    let free_path_feature = core::Feature::new(
+        state::uris::FREE_PATH_URI.as_ptr(),
+        &mut free_path as *mut FreePath as *mut c_void,
+    );
+    let instance_features: [*const core::Feature; 3] = [map_feature, unmap_feature, null()];
+    let state_features: [*const core::Feature; 3] = [&map_path_feature, &free_path_feature, null()];
+
+    let descriptor = unsafe { lv2_descriptor(0) };
+    let descriptor_ref = unsafe { descriptor.as_ref() }.unwrap();
+    let handle = unsafe {
+        (descriptor_ref.instantiate)(
+            descriptor,
+            44100.0,
+            b"/\0".as_ptr() as *const c_char,
+            instance_features.as_ptr(),
+        )
+    };
+    assert_ne!(handle, null_mut());
+
+    let interface = unsafe { (descriptor_ref.extension_data)(state::uris::INTERFACE_URI.as_ptr()) };
+    let interface = unsafe { (interface as *const StateInterface).as_ref() }.unwrap();
+
+    // Restoring without properties fails.
+    let mut properties = Properties::new();
+    let properties_handle = &mut properties as *mut Properties as StateHandle;
+    let status = unsafe {
+        (interface.restore)(
+            handle,
+            retrieve,
+            properties_handle,
+            0,
+            state_features.as_ptr(),
+        )
+    };
+    assert_eq!(StateErr::NoProperty as u32, status);
+
+    // Saving requires the path features.
+    let status = unsafe { (interface.save)(handle, store, properties_handle, 0, null()) };
+    assert_eq!(StateErr::NoFeature as u32, status);
+
+    {
+        let plugin = unsafe { core::get_instance::<StatePlugin>(handle) };
+        plugin.gain = 0.25;
+        plugin.name = CString::new("kick").unwrap();
+        plugin.sample = CString::new("/samples/kick.wav").unwrap();
+    }
+    let status =
+        unsafe { (interface.save)(handle, store, properties_handle, 0, state_features.as_ptr()) };
+    assert_eq!(0, status);
+
+    let sample_key = host_map.get(SAMPLE_KEY_URI.as_cstr());
+    let path_type = host_map.get(atom::uris::PATH_TYPE_URI.as_cstr());
+    let (value, value_type, flags) = &properties[&sample_key.unwrap()];
+    assert_eq!(b"kick.wav\0", value.as_slice());
+    assert_eq!(path_type.unwrap(), *value_type);
+    assert!(StateFlags(*flags).contains(StateFlags::POD));
+
+    {
+        let plugin = unsafe { core::get_instance::<StatePlugin>(handle) };
+        plugin.gain = 1.0;
+        plugin.name = CString::new("snare").unwrap();
+        plugin.sample = CString::new("/samples/snare.wav").unwrap();
+    }
+    let status = unsafe {
+        (interface.restore)(
+            handle,
+            retrieve,
+            properties_handle,
+            0,
+            state_features.as_ptr(),
+        )
+    };
+    assert_eq!(0, status);
+
+    {
+        let plugin = unsafe { core::get_instance::<StatePlugin>(handle) };
+        assert_eq!(0.25, plugin.gain);
+        assert_eq!(CString::new("kick").unwrap(), plugin.name);
+        assert_eq!(CString::new("/samples/kick.wav").unwrap(), plugin.sample);
+    }
+
+    // Values of the wrong type are rejected.
+    let gain_key = host_map.get(GAIN_KEY_URI.as_cstr());
+    properties.get_mut(&gain_key.unwrap()).unwrap().1 = path_type.unwrap();
+    let status = unsafe {
+        (interface.restore)(
+            handle,
+            retrieve,
+            properties_handle,
+            0,
+            state_features.as_ptr(),
+        )
+    };
+    assert_eq!(StateErr::BadType as u32, status);
+
+    unsafe { (descriptor_ref.cleanup)(handle) };
+}
+
+#[test]
+fn test_presets() {
+    let host_map = HostMap::new();
+    let mut map_path = MapPath {
+        handle: null_mut(),
+        abstract_path,
+        absolute_path,
+    };
+    let mut free_path = FreePath {
+        handle: null_mut(),
+        free_path,
+    };
+    let urid_features = host_map.features();
+    let [map_feature, unmap_feature] = &*urid_features;
+    let map_path_feature = core::Feature::new(
+        state::uris::MAP_PATH_URI.as_ptr(),
+        &mut map_path as *mut MapPath as *mut c_void,
+    );
+    let free_path_feature = core::Feature::new(
+        state::uris::FREE_PATH_URI.as_ptr(),
+        &mut free_path as *mut FreePath as *mut c_void,
+    );
+    let instance_features: [*const core::Feature; 3] = [map_feature, unmap_feature, null()];
+    let state_features: [*const core::Feature; 3] = [&map_path_feature, &free_path_feature, null()];
+    let state_features = unsafe { core::Features::from_raw(state_features.as_ptr()) }
+        .unwrap()
+        .as_list();
+
+    let descriptor = unsafe { lv2_descriptor(0) };
+    let descriptor_ref = unsafe { descriptor.as_ref() }.unwrap();
+    let handle = unsafe {
+        (descriptor_ref.instantiate)(
+            descriptor,
+            44100.0,
+            b"/\0".as_ptr() as *const c_char,
+            instance_features.as_ptr(),
+        )
+    };
+    assert_ne!(handle, null_mut());
+    let plugin = unsafe { core::get_instance::<StatePlugin>(handle) };
+
+    let mut preset = Preset::new("http://example.org/StatePlugin#kick", "Kick");
+    preset.set_port_value("level", 0.5);
+    preset.set_port_value("level", 0.75);
+    assert_eq!(&[(String::from("level"), 0.75)], preset.port_values());
+
+    // Saving a preset requires the path features.
+    assert_eq!(Err(StateErr::NoFeature), preset.save(plugin, None));
+    assert!(preset.properties().is_empty());
+
+    plugin.gain = 0.25;
+    plugin.name = CString::new("kick \"808\"").unwrap();
+    plugin.sample = CString::new("/samples/kick 808.wav").unwrap();
+    preset.save(plugin, Some(state_features)).unwrap();
+    assert_eq!(3, preset.properties().len());
+
+    plugin.gain = 1.0;
+    plugin.name = CString::new("snare").unwrap();
+    plugin.sample = CString::new("/samples/snare.wav").unwrap();
+    preset.load(plugin, Some(state_features)).unwrap();
+    assert_eq!(0.25, plugin.gain);
+    assert_eq!(CString::new("kick \"808\"").unwrap(), plugin.name);
+    assert_eq!(
+        CString::new("/samples/kick 808.wav").unwrap(),
+        plugin.sample
+    );
+
+    let mut bundle = PresetBundle::new("http://example.org/StatePlugin");
+    bundle.add(Preset::new("http://example.org/StatePlugin#snare", "Snare"));
+    bundle.add(preset.clone());
+    assert_eq!(2, bundle.presets().len());
+    assert_eq!(
+        Some(&preset),
+        bundle.find("http://example.org/StatePlugin#kick")
+    );
+    assert_eq!(None, bundle.find_by_label("Hi-Hat"));
+
+    let unmap = urid::Unmap::try_from_features(unsafe {
+        core::Features::from_raw(instance_features.as_ptr())
+            .unwrap()
+            .as_list()
+    })
+    .unwrap();
+    let files = bundle.files(unmap).unwrap();
+    assert_eq!(
+        vec!["manifest.ttl", "Snare.ttl", "Kick.ttl"],
+        files
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>()
+    );
+    assert!(files[0]
+        .1
+        .contains("lv2:appliesTo <http://example.org/StatePlugin>"));
+    let kick = &files[2].1;
+    assert!(kick.contains("rdfs:label \"Kick\""));
+    assert!(kick.contains("lv2:symbol \"level\" ;\n        pset:value 0.75"));
+    assert!(kick.contains("<http://example.org/StatePlugin#gain> \"0.25\"^^xsd:float"));
+    assert!(kick.contains("<http://example.org/StatePlugin#name> \"kick \\\"808\\\"\""));
+    assert!(kick.contains("<http://example.org/StatePlugin#sample> <kick%20808.wav>"));
+    assert!(!files[1].1.contains("state:state"));
+
+    unsafe { (descriptor_ref.cleanup)(handle) };
+}
@@ -0,0 +1,174 @@
+//! The host sides of the log and the worker feature.
+use log::{Log, LogHandle, PrintfFunction};
+use std::ffi::CStr;
+use std::os::raw::*;
+use std::sync::Mutex;
+use urid::URID;
+use worker::{RespondHandle, ScheduleHandle, WorkerInterface, WorkerSchedule};
+
+/// A log that records the printed entries.
+///
+/// Just like the [`HostMap`](../../lv2rs_urid/host/struct.HostMap.html), the feature contains a
+/// pointer to the log itself, which is why it lives in a box.
+pub(crate) struct HostLog {
+    pub(crate) feature: Log,
+    entries: Mutex<Vec<(URID, String)>>,
+}
+
+impl HostLog {
+    pub(crate) fn new() -> Box<Self> {
+        let mut log = Box::new(Self {
+            feature: Log {
+                handle: std::ptr::null_mut(),
+                printf: unsafe {
+                    std::mem::transmute::<*const (), PrintfFunction>(printf as *const ())
+                },
+                vprintf,
+            },
+            entries: Mutex::new(Vec::new()),
+        });
+        log.feature.handle = log.as_ref() as *const Self as LogHandle;
+        log
+    }
+
+    /// Remove and return all recorded entries.
+    pub(crate) fn take(&self) -> Vec<(URID, String)> {
+        match self.entries.lock() {
+            Ok(mut entries) => entries.drain(..).collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+/// The printf function of the log.
+///
+/// Variadic functions can't be defined in Rust. Both the `lv2rs-log` crate and `lv2rs-core` pass
+/// exactly one string argument, so this function is called with a compatible signature and
+/// replaces the first `%s` of the format with the argument.
+unsafe extern "C" fn printf(
+    handle: LogHandle,
+    entry_type: URID,
+    format: *const c_char,
+    argument: *const c_char,
+) -> c_int {
+    let log = match (handle as *const HostLog).as_ref() {
+        Some(log) => log,
+        None => return -1,
+    };
+    let format = CStr::from_ptr(format).to_string_lossy();
+    let message = if argument.is_null() {
+        format.into_owned()
+    } else {
+        format.replacen("%s", &CStr::from_ptr(argument).to_string_lossy(), 1)
+    };
+    let length = message.len() as c_int;
+    match log.entries.lock() {
+        Ok(mut entries) => {
+            entries.push((entry_type, message));
+            length
+        }
+        Err(_) => -1,
+    }
+}
+
+unsafe extern "C" fn vprintf(
+    _handle: LogHandle,
+    _entry_type: URID,
+    _format: *const c_char,
+    _arguments: *mut c_void,
+) -> c_int {
+    -1
+}
+
+/// A worker that does the scheduled work in the host's thread after every cycle.
+pub(crate) struct HostWorker {
+    pub(crate) feature: WorkerSchedule,
+    work: Mutex<Vec<Vec<u8>>>,
+    responses: Mutex<Vec<Vec<u8>>>,
+}
+
+impl HostWorker {
+    pub(crate) fn new() -> Box<Self> {
+        let mut worker = Box::new(Self {
+            feature: WorkerSchedule {
+                handle: std::ptr::null_mut(),
+                schedule_work,
+            },
+            work: Mutex::new(Vec::new()),
+            responses: Mutex::new(Vec::new()),
+        });
+        worker.feature.handle = worker.as_ref() as *const Self as ScheduleHandle;
+        worker
+    }
+
+    /// Do all scheduled work and deliver the responses.
+    ///
+    /// Without an interface, the scheduled work is dropped. This function is unsafe since the
+    /// interface has to belong to the plugin of the instance handle.
+    pub(crate) unsafe fn process(
+        &self,
+        instance: core::Handle,
+        interface: Option<&WorkerInterface>,
+    ) {
+        let work = take(&self.work);
+        let interface = match interface {
+            Some(interface) => interface,
+            None => return,
+        };
+        let respond_handle = self as *const Self as RespondHandle;
+        for data in work {
+            (interface.work)(
+                instance,
+                respond,
+                respond_handle,
+                data.len() as u32,
+                data.as_ptr() as *const c_void,
+            );
+        }
+        for data in take(&self.responses) {
+            (interface.work_response)(instance, data.len() as u32, data.as_ptr() as *const c_void);
+        }
+        if let Some(end_run) = interface.end_run {
+            end_run(instance);
+        }
+    }
+}
+
+fn take(queue: &Mutex<Vec<Vec<u8>>>) -> Vec<Vec<u8>> {
+    match queue.lock() {
+        Ok(mut queue) => queue.drain(..).collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Copy the data of a request into a queue.
+unsafe fn push(queue: &Mutex<Vec<Vec<u8>>>, size: u32, data: *const c_void) -> u32 {
+    let data = if size == 0 {
+        Vec::new()
+    } else if data.is_null() {
+        return worker::WorkerErr::Unknown as u32;
+    } else {
+        std::slice::from_raw_parts(data as *const u8, size as usize).to_owned()
+    };
+    match queue.lock() {
+        Ok(mut queue) => {
+            queue.push(data);
+            0
+        }
+        Err(_) => worker::WorkerErr::Unknown as u32,
+    }
+}
+
+unsafe extern "C" fn schedule_work(handle: ScheduleHandle, size: u32, data: *const c_void) -> u32 {
+    match (handle as *const HostWorker).as_ref() {
+        Some(worker) => push(&worker.work, size, data),
+        None => worker::WorkerErr::Unknown as u32,
+    }
+}
+
+unsafe extern "C" fn respond(handle: RespondHandle, size: u32, data: *const c_void) -> u32 {
+    match (handle as *const HostWorker).as_ref() {
+        Some(worker) => push(&worker.responses, size, data),
+        None => worker::WorkerErr::Unknown as u32,
+    }
+}
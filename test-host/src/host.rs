@@ -0,0 +1,156 @@
+//! The test host and its features.
+use crate::features::HostLog;
+use crate::instance::{PluginInstance, TestInstance};
+use crate::library::PluginLibrary;
+use core::uris::Uri;
+use log::EntryType;
+use options::OptionValue;
+use std::ffi::{CStr, CString};
+use std::pin::Pin;
+use urid::host::HostMap;
+use urid::{CachedMap, URID};
+
+/// A host that runs plugins in the same process.
+///
+/// The host provides the URID map and unmap, the log, the options and the worker features to the
+/// plugins it instantiates. The options have to be set before the plugins are instantiated, since
+/// the instances borrow the host.
+pub struct TestHost {
+    sample_rate: f64,
+    bundle_path: CString,
+    urids: Pin<Box<HostMap>>,
+    log: Box<HostLog>,
+    options: Vec<(URID, URID, Vec<u8>)>,
+}
+
+impl TestHost {
+    /// Create a new host with the given sample rate.
+    ///
+    /// The bundle path passed to the plugins is `/`.
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            sample_rate,
+            bundle_path: CString::new("/").unwrap(),
+            urids: HostMap::new(),
+            log: HostLog::new(),
+            options: Vec::new(),
+        }
+    }
+
+    /// Return the sample rate passed to the plugins.
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// Set the bundle path passed to the plugins.
+    ///
+    /// The path has to end with a directory separator. This method fails if the path contains a
+    /// null byte.
+    pub fn set_bundle_path(&mut self, bundle_path: &str) -> Result<(), ()> {
+        self.bundle_path = CString::new(bundle_path).map_err(|_| ())?;
+        Ok(())
+    }
+
+    /// Return the bundle path passed to the plugins.
+    pub fn bundle_path(&self) -> &CStr {
+        &self.bundle_path
+    }
+
+    /// Return the URID map of the host.
+    pub fn urids(&self) -> &HostMap {
+        &self.urids
+    }
+
+    /// Map a URI, like the URI constants of the lv2rs crates.
+    pub fn map(&self, uri: Uri) -> URID {
+        self.urids.map(uri.as_cstr())
+    }
+
+    /// Create a cached map that uses the host's URID map.
+    ///
+    /// It is needed to read and write atoms in the buffers of the instances.
+    ///
+    /// This function is unsafe since the cached map references the host's map without a lifetime
+    /// and therefore must not outlive the host.
+    pub unsafe fn create_cached_map(&self) -> CachedMap {
+        let mut features = self.urids.features();
+        let map = features[0].data::<urid::Map>().unwrap() as *mut urid::Map;
+        CachedMap::new(&mut *map)
+    }
+
+    /// Set an instance option that is passed to the plugins with the options feature.
+    ///
+    /// The key is a URI, like
+    /// [`MAX_BLOCK_LENGTH_URI`](../lv2rs_options/uris/constant.MAX_BLOCK_LENGTH_URI.html). An
+    /// existing option with the same key is replaced.
+    pub fn set_option<T: OptionValue>(&mut self, key: Uri, value: T) {
+        let key = self.map(key);
+        let value_type = self.urids.map(T::get_uri());
+        let value = value.as_bytes().to_owned();
+        self.options.retain(|(option_key, _, _)| *option_key != key);
+        self.options.push((key, value_type, value));
+    }
+
+    /// Set an option from a `(key, type, value)` triple, replacing an option with the same key.
+    pub(crate) fn set_raw_option(&mut self, option: (URID, URID, Vec<u8>)) {
+        self.options.retain(|(key, _, _)| *key != option.0);
+        self.options.push(option);
+    }
+
+    /// Return the options as `(key, type, value)` triples.
+    pub(crate) fn options(&self) -> &[(URID, URID, Vec<u8>)] {
+        &self.options
+    }
+
+    /// Return the log feature.
+    pub(crate) fn log_feature(&self) -> &log::Log {
+        &self.log.feature
+    }
+
+    /// Remove and return the entries the plugins printed to the log.
+    ///
+    /// Entries with an unknown type are returned as notes.
+    pub fn take_log(&self) -> Vec<(EntryType, String)> {
+        let error = self.map(log::uris::ERROR_URI);
+        let trace = self.map(log::uris::TRACE_URI);
+        let warning = self.map(log::uris::WARNING_URI);
+        self.log
+            .take()
+            .into_iter()
+            .map(|(entry_type, message)| {
+                let entry_type = if entry_type == error {
+                    EntryType::Error
+                } else if entry_type == trace {
+                    EntryType::Trace
+                } else if entry_type == warning {
+                    EntryType::Warning
+                } else {
+                    EntryType::Note
+                };
+                (entry_type, message)
+            })
+            .collect()
+    }
+
+    /// Instantiate a plugin.
+    ///
+    /// The URI is the null-terminated URI of the plugin, which is passed to the plugin with its
+    /// descriptor. `None` is returned if the plugin refuses to instantiate, for example because
+    /// it requires a feature the test host doesn't provide.
+    pub fn instantiate<P: core::Plugin>(&self, uri: &'static [u8]) -> Option<TestInstance<'_, P>> {
+        TestInstance::new(self, uri)
+    }
+
+    /// Instantiate a plugin of a library.
+    ///
+    /// The plugin is identified by its null-terminated URI and receives the bundle path of the
+    /// library. `None` is returned if the library doesn't contain the plugin or if the plugin
+    /// refuses to instantiate.
+    pub fn instantiate_from<'a>(
+        &'a self,
+        library: &'a PluginLibrary,
+        uri: &[u8],
+    ) -> Option<PluginInstance<'a>> {
+        PluginInstance::new(self, library.find(uri)?, library.bundle_path())
+    }
+}
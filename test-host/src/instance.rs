@@ -0,0 +1,498 @@
+//! Plugin instances and their port buffers.
+use crate::features::HostWorker;
+use crate::host::TestHost;
+use crate::recording::{Cycle, PortData, PortSetup, Recording};
+use atom::prelude::*;
+use core::uris::Uri;
+use options::{OptionContext, OptionData, OptionsOption};
+use std::collections::BTreeMap;
+use std::ffi::CStr;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::os::raw::*;
+use urid::host::HostMapFeatures;
+use urid::URID;
+
+/// The buffer a port is connected to.
+enum PortBuffer {
+    Control(Box<f32>),
+    Audio(Vec<f32>),
+    AtomInput(Vec<u64>),
+    AtomOutput(Vec<u64>),
+}
+
+impl PortBuffer {
+    fn as_mut_ptr(&mut self) -> *mut c_void {
+        match self {
+            PortBuffer::Control(value) => value.as_mut() as *mut f32 as *mut c_void,
+            PortBuffer::Audio(samples) => samples.as_mut_ptr() as *mut c_void,
+            PortBuffer::AtomInput(space) | PortBuffer::AtomOutput(space) => {
+                space.as_mut_ptr() as *mut c_void
+            }
+        }
+    }
+}
+
+/// Return the atom at the start of an atom buffer.
+fn atom_header(space: &mut [u64]) -> &mut Atom {
+    unsafe { (space.as_mut_ptr() as *mut Atom).as_mut() }.unwrap()
+}
+
+/// Write an empty sequence to an atom buffer.
+fn clear_sequence(space: &mut [u64], sequence: URID) {
+    for word in space.iter_mut().take(2) {
+        *word = 0;
+    }
+    let atom = atom_header(space);
+    *atom.mut_size() = 8;
+    *atom.mut_atom_type() = sequence;
+}
+
+/// Return the bytes of the atom at the start of an atom buffer, including its header.
+fn atom_bytes(space: &[u64]) -> Vec<u8> {
+    let bytes = unsafe { std::slice::from_raw_parts(space.as_ptr() as *const u8, space.len() * 8) };
+    let size = unsafe { (space.as_ptr() as *const Atom).as_ref() }
+        .unwrap()
+        .size()
+        + 8;
+    bytes[..size.min(bytes.len())].to_owned()
+}
+
+/// Reset an atom buffer to a chunk that spans the whole buffer.
+fn reset_chunk(space: &mut [u64], chunk: URID) -> &mut Atom {
+    let size = space.len() * 8 - 8;
+    let atom = atom_header(space);
+    *atom.mut_size() = size as i32;
+    *atom.mut_atom_type() = chunk;
+    atom
+}
+
+/// A plugin instance, created by a [`TestHost`](struct.TestHost.html).
+///
+/// The instance owns the buffers its ports are connected to. After every call to
+/// [`run`](#method.run), the outputs can be read from these buffers and the inputs can be changed
+/// for the next cycle. The plugin is cleaned up when the instance is dropped.
+///
+/// Instances of plugins that were loaded from a [`PluginLibrary`](struct.PluginLibrary.html) are
+/// used directly, while instances of plugin types are wrapped in a
+/// [`TestInstance`](struct.TestInstance.html), which also gives access to the plugin.
+pub struct PluginInstance<'a> {
+    host: &'a TestHost,
+    descriptor: *const core::Descriptor,
+    _descriptor: Option<Box<core::Descriptor>>,
+    handle: core::Handle,
+    worker: Box<HostWorker>,
+    _options: Vec<OptionsOption>,
+    _urid_features: Box<HostMapFeatures<'a>>,
+    _features: Box<[core::Feature]>,
+    _feature_list: Vec<*const core::Feature>,
+    ports: BTreeMap<u32, PortBuffer>,
+    recording: Option<Recording>,
+}
+
+impl<'a> PluginInstance<'a> {
+    /// Instantiate the plugin of a descriptor that outlives the instance.
+    pub(crate) fn new(
+        host: &'a TestHost,
+        descriptor: &'a core::Descriptor,
+        bundle_path: &CStr,
+    ) -> Option<Self> {
+        Self::instantiate(host, descriptor, bundle_path, None)
+    }
+
+    /// Instantiate the plugin of a descriptor that is owned by the instance.
+    pub(crate) fn with_descriptor(
+        host: &'a TestHost,
+        descriptor: Box<core::Descriptor>,
+    ) -> Option<Self> {
+        let pointer = descriptor.as_ref() as *const core::Descriptor;
+        Self::instantiate(host, pointer, host.bundle_path(), Some(descriptor))
+    }
+
+    fn instantiate(
+        host: &'a TestHost,
+        descriptor: *const core::Descriptor,
+        bundle_path: &CStr,
+        owned_descriptor: Option<Box<core::Descriptor>>,
+    ) -> Option<Self> {
+        let worker = HostWorker::new();
+
+        let mut options: Vec<OptionsOption> = host
+            .options()
+            .iter()
+            .map(|(key, value_type, value)| {
+                let value = OptionData {
+                    value_type: *value_type,
+                    value,
+                };
+                OptionsOption::new(OptionContext::Instance, 0, *key, value)
+            })
+            .collect();
+        options.push(OptionsOption::terminator());
+
+        let urid_features = Box::new(host.urids().features());
+        let features: Box<[core::Feature]> = Box::new([
+            core::Feature::new(
+                log::uris::LOG_URI.as_ptr(),
+                host.log_feature() as *const log::Log as *mut c_void,
+            ),
+            core::Feature::new(
+                options::uris::OPTIONS_URI.as_ptr(),
+                options.as_ptr() as *mut c_void,
+            ),
+            core::Feature::new(
+                worker::uris::SCHEDULE_URI.as_ptr(),
+                &worker.feature as *const worker::WorkerSchedule as *mut c_void,
+            ),
+        ]);
+        let mut feature_list: Vec<*const core::Feature> = urid_features
+            .iter()
+            .chain(features.iter())
+            .map(|feature| feature as *const _)
+            .collect();
+        feature_list.push(std::ptr::null());
+
+        let handle = unsafe {
+            ((*descriptor).instantiate)(
+                descriptor,
+                host.sample_rate(),
+                bundle_path.as_ptr(),
+                feature_list.as_ptr(),
+            )
+        };
+        if handle.is_null() {
+            return None;
+        }
+        Some(Self {
+            host,
+            descriptor,
+            _descriptor: owned_descriptor,
+            handle,
+            worker,
+            _options: options,
+            _urid_features: urid_features,
+            _features: features,
+            _feature_list: feature_list,
+            ports: BTreeMap::new(),
+            recording: None,
+        })
+    }
+
+    /// Return the host of the instance.
+    pub fn host(&self) -> &'a TestHost {
+        self.host
+    }
+
+    /// Return the raw handle of the instance, which is passed to extension interfaces.
+    pub fn handle(&self) -> core::Handle {
+        self.handle
+    }
+
+    /// Return the descriptor of the plugin.
+    pub fn descriptor(&self) -> &core::Descriptor {
+        unsafe { &*self.descriptor }
+    }
+
+    /// Return the extension data of the plugin for a URI, cast to `T`.
+    ///
+    /// This function is unsafe since it can not check that the data has type `T`. It's your
+    /// responsibility to pass the type the extension's specification defines.
+    pub unsafe fn extension_data<T>(&self, uri: Uri) -> Option<&'static T> {
+        ((self.descriptor().extension_data)(uri.as_ptr()) as *const T).as_ref()
+    }
+
+    /// Connect a port to a buffer and store the buffer.
+    fn connect(&mut self, port: u32, mut buffer: PortBuffer) {
+        let data = buffer.as_mut_ptr();
+        self.ports.insert(port, buffer);
+        unsafe { (self.descriptor().connect_port)(self.handle, port, data) };
+    }
+
+    /// Connect a control port to a new buffer with the given value.
+    pub fn connect_control(&mut self, port: u32, value: f32) {
+        self.connect(port, PortBuffer::Control(Box::new(value)));
+    }
+
+    /// Return the value of a control port, or `None` if it isn't connected to a control buffer.
+    pub fn control(&self, port: u32) -> Option<f32> {
+        match self.ports.get(&port) {
+            Some(PortBuffer::Control(value)) => Some(**value),
+            _ => None,
+        }
+    }
+
+    /// Set the value of a control port.
+    ///
+    /// `false` is returned if the port isn't connected to a control buffer.
+    pub fn set_control(&mut self, port: u32, value: f32) -> bool {
+        match self.ports.get_mut(&port) {
+            Some(PortBuffer::Control(buffer)) => {
+                **buffer = value;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Connect an audio or CV port to a new buffer with the given samples.
+    ///
+    /// The buffer has to be at least as long as the sample counts passed to
+    /// [`run`](#method.run). Outputs are usually connected to a buffer of zeros.
+    pub fn connect_audio(&mut self, port: u32, samples: Vec<f32>) {
+        self.connect(port, PortBuffer::Audio(samples));
+    }
+
+    /// Return the samples of an audio or CV port.
+    pub fn audio(&self, port: u32) -> Option<&[f32]> {
+        match self.ports.get(&port) {
+            Some(PortBuffer::Audio(samples)) => Some(samples),
+            _ => None,
+        }
+    }
+
+    /// Return the samples of an audio or CV port mutably, for example to fill an input.
+    pub fn audio_mut(&mut self, port: u32) -> Option<&mut [f32]> {
+        match self.ports.get_mut(&port) {
+            Some(PortBuffer::Audio(samples)) => Some(samples),
+            _ => None,
+        }
+    }
+
+    /// Connect an atom input to a new buffer with a capacity in bytes.
+    ///
+    /// The input contains an empty sequence until something is written with
+    /// [`atom_input`](#method.atom_input).
+    pub fn connect_atom_input(&mut self, port: u32, capacity: usize) {
+        let mut space = vec![0u64; capacity.div_ceil(8).max(2)];
+        clear_sequence(&mut space, self.host.map(atom::uris::SEQUENCE_TYPE_URI));
+        self.connect(port, PortBuffer::AtomInput(space));
+    }
+
+    /// Connect an atom output to a new buffer with a capacity in bytes.
+    pub fn connect_atom_output(&mut self, port: u32, capacity: usize) {
+        let mut space = vec![0u64; capacity.div_ceil(8).max(1)];
+        reset_chunk(&mut space, self.host.map(atom::uris::CHUNK_TYPE_URI));
+        self.connect(port, PortBuffer::AtomOutput(space));
+    }
+
+    /// Prepare an atom input for writing and return its atom.
+    ///
+    /// The atom is reset to a chunk that spans the whole buffer, which is what an
+    /// [`AtomOutputPort`](../lv2rs_atom/ports/struct.AtomOutputPort.html) expects. Just like a
+    /// real host, the test host clears atom inputs after every cycle, which means that the
+    /// written events are only delivered to the next call of [`run`](#method.run).
+    pub fn atom_input(&mut self, port: u32) -> Option<&mut Atom> {
+        let chunk = self.host.map(atom::uris::CHUNK_TYPE_URI);
+        match self.ports.get_mut(&port) {
+            Some(PortBuffer::AtomInput(space)) => Some(reset_chunk(space, chunk)),
+            _ => None,
+        }
+    }
+
+    /// Return the atom of an atom port.
+    ///
+    /// After a call to [`run`](#method.run), this is the atom the plugin has written to an
+    /// output.
+    pub fn atom(&self, port: u32) -> Option<&Atom> {
+        match self.ports.get(&port) {
+            Some(PortBuffer::AtomInput(space)) | Some(PortBuffer::AtomOutput(space)) => unsafe {
+                (space.as_ptr() as *const Atom).as_ref()
+            },
+            _ => None,
+        }
+    }
+
+    /// Disconnect a port by connecting it to null and drop its buffer.
+    pub fn disconnect(&mut self, port: u32) {
+        self.ports.remove(&port);
+        unsafe { (self.descriptor().connect_port)(self.handle, port, std::ptr::null_mut()) };
+    }
+
+    /// Activate the plugin, if it isn't active already.
+    pub fn activate(&mut self) {
+        unsafe { (self.descriptor().activate)(self.handle) };
+    }
+
+    /// Deactivate the plugin, if it is active.
+    pub fn deactivate(&mut self) {
+        unsafe { (self.descriptor().deactivate)(self.handle) };
+    }
+
+    /// Run the plugin for a number of samples.
+    ///
+    /// The plugin is activated before its first cycle. Before the plugin is run, the atom
+    /// outputs are reset to their full capacity. Afterwards, the work the plugin scheduled is
+    /// done in the current thread, the responses are delivered to the plugin and the atom inputs
+    /// are cleared.
+    pub fn run(&mut self, n_samples: u32) {
+        self.activate();
+        let chunk = self.host.map(atom::uris::CHUNK_TYPE_URI);
+        let sequence = self.host.map(atom::uris::SEQUENCE_TYPE_URI);
+        for buffer in self.ports.values_mut() {
+            if let PortBuffer::AtomOutput(space) = buffer {
+                reset_chunk(space, chunk);
+            }
+        }
+        if self.recording.is_some() {
+            let inputs = self.record_ports(true, n_samples);
+            if let Some(recording) = self.recording.as_mut() {
+                recording.cycles.push(Cycle {
+                    n_samples,
+                    inputs,
+                    outputs: BTreeMap::new(),
+                });
+            }
+        }
+        unsafe {
+            (self.descriptor().run)(self.handle, n_samples);
+            let interface =
+                self.extension_data::<worker::WorkerInterface>(worker::uris::INTERFACE_URI);
+            self.worker.process(self.handle, interface);
+        }
+        if self.recording.is_some() {
+            let outputs = self.record_ports(false, n_samples);
+            if let Some(cycle) = self
+                .recording
+                .as_mut()
+                .and_then(|recording| recording.cycles.last_mut())
+            {
+                cycle.outputs = outputs;
+            }
+        }
+        for buffer in self.ports.values_mut() {
+            if let PortBuffer::AtomInput(space) = buffer {
+                clear_sequence(space, sequence);
+            }
+        }
+    }
+
+    /// Start recording the inputs and outputs of every cycle.
+    ///
+    /// The ports have to be connected before the recording starts, since the recording stores
+    /// how they are connected. A running recording is restarted.
+    pub fn start_recording(&mut self) {
+        let ports = self
+            .ports
+            .iter()
+            .map(|(port, buffer)| {
+                let setup = match buffer {
+                    PortBuffer::Control(_) => PortSetup::Control,
+                    PortBuffer::Audio(samples) => PortSetup::Audio(samples.len()),
+                    PortBuffer::AtomInput(space) => PortSetup::AtomInput(space.len() * 8),
+                    PortBuffer::AtomOutput(space) => PortSetup::AtomOutput(space.len() * 8),
+                };
+                (*port, setup)
+            })
+            .collect();
+        self.recording = Some(Recording {
+            sample_rate: self.host.sample_rate(),
+            urids: String::new(),
+            options: self.host.options().to_vec(),
+            ports,
+            cycles: Vec::new(),
+        });
+    }
+
+    /// Return whether the instance is recording.
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// Stop recording and return the recording.
+    ///
+    /// The recording contains the URID table of the host at this point, which covers the URIDs
+    /// of all recorded atoms. `None` is returned if the instance isn't recording.
+    pub fn stop_recording(&mut self) -> Option<Recording> {
+        let mut recording = self.recording.take()?;
+        recording.urids = self.host.urids().to_string();
+        Some(recording)
+    }
+
+    /// Return the content of all ports that are recorded before or after a cycle.
+    fn record_ports(&self, input: bool, n_samples: u32) -> BTreeMap<u32, PortData> {
+        self.ports
+            .keys()
+            .filter_map(|port| Some((*port, self.port_data(*port, input, n_samples)?)))
+            .collect()
+    }
+
+    /// Return the content of a port, as it is recorded before or after a cycle.
+    pub(crate) fn port_data(&self, port: u32, input: bool, n_samples: u32) -> Option<PortData> {
+        match (self.ports.get(&port)?, input) {
+            (PortBuffer::Control(value), _) => Some(PortData::Control(**value)),
+            (PortBuffer::Audio(samples), _) => Some(PortData::Audio(
+                samples.iter().take(n_samples as usize).cloned().collect(),
+            )),
+            (PortBuffer::AtomInput(space), true) | (PortBuffer::AtomOutput(space), false) => {
+                Some(PortData::Atom(atom_bytes(space)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Load recorded data into the buffer of a port.
+    ///
+    /// Data that doesn't fit into the buffer is truncated.
+    pub(crate) fn load_port(&mut self, port: u32, data: &PortData) {
+        match (self.ports.get_mut(&port), data) {
+            (Some(PortBuffer::Control(buffer)), PortData::Control(value)) => **buffer = *value,
+            (Some(PortBuffer::Audio(buffer)), PortData::Audio(samples)) => {
+                for (sample, value) in buffer.iter_mut().zip(samples) {
+                    *sample = *value;
+                }
+            }
+            (Some(PortBuffer::AtomInput(space)), PortData::Atom(bytes)) => {
+                let space = unsafe {
+                    std::slice::from_raw_parts_mut(space.as_mut_ptr() as *mut u8, space.len() * 8)
+                };
+                let length = bytes.len().min(space.len());
+                space[..length].copy_from_slice(&bytes[..length]);
+            }
+            _ => (),
+        }
+    }
+}
+
+impl<'a> Drop for PluginInstance<'a> {
+    fn drop(&mut self) {
+        unsafe { (self.descriptor().cleanup)(self.handle) };
+    }
+}
+
+/// An instance of a plugin type, created by a [`TestHost`](struct.TestHost.html).
+///
+/// It dereferences to a [`PluginInstance`](struct.PluginInstance.html), which connects and runs
+/// the plugin, and additionally gives access to the plugin itself.
+pub struct TestInstance<'a, P: core::Plugin> {
+    instance: PluginInstance<'a>,
+    phantom: PhantomData<P>,
+}
+
+impl<'a, P: core::Plugin> TestInstance<'a, P> {
+    pub(crate) fn new(host: &'a TestHost, uri: &'static [u8]) -> Option<Self> {
+        let descriptor = Box::new(core::Descriptor::new::<P>(uri));
+        Some(Self {
+            instance: PluginInstance::with_descriptor(host, descriptor)?,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Return the plugin.
+    pub fn plugin(&mut self) -> &mut P {
+        unsafe { core::get_instance::<P>(self.instance.handle) }
+    }
+}
+
+impl<'a, P: core::Plugin> Deref for TestInstance<'a, P> {
+    type Target = PluginInstance<'a>;
+
+    fn deref(&self) -> &PluginInstance<'a> {
+        &self.instance
+    }
+}
+
+impl<'a, P: core::Plugin> DerefMut for TestInstance<'a, P> {
+    fn deref_mut(&mut self) -> &mut PluginInstance<'a> {
+        &mut self.instance
+    }
+}
@@ -0,0 +1,115 @@
+//! An in-process host for integration tests.
+//!
+//! Plugins built with lv2rs are usually tested by loading their library in a real host. The
+//! [`TestHost`](struct.TestHost.html) instead instantiates a plugin type directly through its
+//! descriptor, so that plugins can be tested with `cargo test`. It provides the URID map and
+//! unmap, log, options and worker features. The [`TestInstance`](struct.TestInstance.html)s it
+//! creates own the buffers of their ports, run the plugin and do the scheduled work
+//! synchronously after every cycle, which makes the tests deterministic:
+//!
+//!     extern crate lv2rs_core as core;
+//!     extern crate lv2rs_options as options;
+//!     extern crate lv2rs_test_host as test_host;
+//!
+//!     use std::ffi::CStr;
+//!     use test_host::TestHost;
+//!
+//!     struct Amp {
+//!         gain: core::ports::ParameterInputPort,
+//!         input: core::ports::AudioInputPort,
+//!         output: core::ports::AudioOutputPort,
+//!     }
+//!
+//!     impl core::Plugin for Amp {
+//!         fn instantiate(
+//!             _context: &core::InstantiationContext,
+//!             _features: Option<&core::FeaturesList>,
+//!         ) -> Result<Self, core::InstantiateError> {
+//!             Ok(Self {
+//!                 gain: core::ports::ParameterInputPort::new(),
+//!                 input: core::ports::AudioInputPort::new(),
+//!                 output: core::ports::AudioOutputPort::new(),
+//!             })
+//!         }
+//!
+//!         fn connect_port(&mut self, port: u32, data: *mut ()) {
+//!             match port {
+//!                 0 => self.gain.connect(data as *const f32),
+//!                 1 => self.input.connect(data as *const f32),
+//!                 2 => self.output.connect(data as *mut f32),
+//!                 _ => (),
+//!             }
+//!         }
+//!
+//!         fn run(&mut self, context: &mut core::RunContext) {
+//!             let gain = *unsafe { self.gain.get() }.unwrap();
+//!             let input = unsafe { self.input.as_slice(context.n_samples()) }.unwrap();
+//!             let output = unsafe { self.output.as_slice(context.n_samples()) }.unwrap();
+//!             for (output, input) in output.iter_mut().zip(input) {
+//!                 *output = input * gain;
+//!             }
+//!         }
+//!     }
+//!
+//!     let mut host = TestHost::new(44100.0);
+//!     host.set_option(options::uris::MAX_BLOCK_LENGTH_URI, 4i32);
+//!
+//!     let mut amp = host.instantiate::<Amp>(b"http://example.org/Amp\0").unwrap();
+//!     amp.connect_control(0, 2.0);
+//!     amp.connect_audio(1, vec![1.0, 2.0, 3.0, 4.0]);
+//!     amp.connect_audio(2, vec![0.0; 4]);
+//!     amp.run(4);
+//!     assert_eq!(&[2.0, 4.0, 6.0, 8.0], amp.audio(2).unwrap());
+//!
+//!     amp.set_control(0, 0.5);
+//!     amp.run(2);
+//!     assert_eq!(&[0.5, 1.0, 6.0, 8.0], amp.audio(2).unwrap());
+//!
+//! For plugins with complex behaviour, an instance can record the inputs and outputs of its
+//! cycles. The [`Recording`](struct.Recording.html) can be saved as a golden file and replayed
+//! later, which runs the plugin again and reports the first output that differs.
+//!
+//! The host can also load plugin binaries, no matter which toolkit they were written with, which
+//! makes it usable for lightweight hosts written in Rust. A
+//! [`PluginLibrary`](struct.PluginLibrary.html) resolves the descriptors of a binary and
+//! [`TestHost::instantiate_from`](struct.TestHost.html#method.instantiate_from) creates a
+//! [`PluginInstance`](struct.PluginInstance.html) of one of its plugins, which is connected and
+//! run just like the instance of a plugin type:
+//!
+//! ```no_run
+//! # extern crate lv2rs_test_host as test_host;
+//! use test_host::{PluginLibrary, TestHost};
+//!
+//! let host = TestHost::new(48000.0);
+//! let library = unsafe { PluginLibrary::load("/usr/lib/lv2/amp.lv2/amp.so") }.unwrap();
+//! for uri in library.uris() {
+//!     println!("{}", uri.to_string_lossy());
+//! }
+//!
+//! let mut amp = host
+//!     .instantiate_from(&library, b"http://lv2plug.in/plugins/eg-amp\0")
+//!     .unwrap();
+//! amp.connect_control(0, 0.0);
+//! amp.connect_audio(1, vec![0.5; 64]);
+//! amp.connect_audio(2, vec![0.0; 64]);
+//! amp.run(64);
+//! ```
+#[cfg(any(unix, windows))]
+extern crate libloading;
+extern crate lv2rs_atom as atom;
+extern crate lv2rs_core as core;
+extern crate lv2rs_log as log;
+extern crate lv2rs_options as options;
+extern crate lv2rs_urid as urid;
+extern crate lv2rs_worker as worker;
+
+mod features;
+mod host;
+mod instance;
+mod library;
+mod recording;
+
+pub use host::*;
+pub use instance::*;
+pub use library::*;
+pub use recording::*;
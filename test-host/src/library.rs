@@ -0,0 +1,203 @@
+//! Plugin libraries that are loaded at runtime.
+//!
+//! Loading shared objects is only supported on Unix and Windows. On other platforms, like
+//! WebAssembly, libraries can only be created from the descriptor functions of plugins that are
+//! linked into the host.
+#[cfg(any(unix, windows))]
+use libloading::Library;
+use std::ffi::{CStr, CString};
+use std::os::raw::*;
+#[cfg(any(unix, windows))]
+use std::path::Path;
+
+/// The signature of the `lv2_descriptor` function that plugin libraries export.
+pub type DescriptorFunction = unsafe extern "C" fn(index: u32) -> *const core::Descriptor;
+
+/// The signature of the `lv2_lib_descriptor` function that plugin libraries may export.
+pub type LibDescriptorFunction = unsafe extern "C" fn(
+    bundle_path: *const c_char,
+    features: *const *const core::Feature,
+) -> *const core::LibDescriptor;
+
+/// The descriptors of a plugin library.
+///
+/// A library is either loaded from a shared object with [`load`](#method.load) or created from
+/// the `lv2_descriptor` function of plugins that are linked into the host with
+/// [`from_function`](#method.from_function). Its plugins are instantiated with
+/// [`TestHost::instantiate_from`](struct.TestHost.html#method.instantiate_from), which provides
+/// the same features as for plugin types. The instances borrow the library, since the code of
+/// their plugins is unloaded when the library is dropped.
+pub struct PluginLibrary {
+    descriptors: Vec<*const core::Descriptor>,
+    lib_descriptor: *const core::LibDescriptor,
+    bundle_path: CString,
+    #[cfg(any(unix, windows))]
+    _library: Option<Library>,
+}
+
+impl PluginLibrary {
+    /// Load a plugin library from the path of its binary.
+    ///
+    /// The bundle path of the library is the directory that contains the binary, or the current
+    /// directory if the path is a bare file name. If the library exports `lv2_lib_descriptor`, it
+    /// is preferred over `lv2_descriptor`. This method fails if the directory is not valid UTF-8,
+    /// the library can't be loaded or it exports neither of these functions.
+    ///
+    /// This function is only available on Unix and Windows.
+    ///
+    /// # Safety
+    ///
+    /// Loading a library runs its initialisation code and the exported functions are trusted to
+    /// have the signatures of the LV2 specification.
+    #[cfg(any(unix, windows))]
+    pub unsafe fn load<T: AsRef<Path>>(path: T) -> Result<Self, ()> {
+        let path = path.as_ref();
+        let bundle_path = bundle_path_of(path)?;
+
+        let library = Library::new(path).map_err(|_| ())?;
+        let lib_descriptor_function = library
+            .get::<LibDescriptorFunction>(b"lv2_lib_descriptor\0")
+            .map(|symbol| *symbol);
+        let mut plugin_library = if let Ok(function) = lib_descriptor_function {
+            let features: [*const core::Feature; 1] = [std::ptr::null()];
+            let lib_descriptor = function(bundle_path.as_ptr(), features.as_ptr());
+            let lib_descriptor_ref = lib_descriptor.as_ref().ok_or(())?;
+            let mut descriptors = Vec::new();
+            loop {
+                let descriptor = (lib_descriptor_ref.get_plugin)(
+                    lib_descriptor_ref.handle,
+                    descriptors.len() as u32,
+                );
+                if descriptor.is_null() {
+                    break;
+                }
+                descriptors.push(descriptor);
+            }
+            Self {
+                descriptors,
+                lib_descriptor,
+                bundle_path,
+                _library: None,
+            }
+        } else {
+            let function = *library
+                .get::<DescriptorFunction>(b"lv2_descriptor\0")
+                .map_err(|_| ())?;
+            Self::from_function(function, bundle_path)
+        };
+        plugin_library._library = Some(library);
+        Ok(plugin_library)
+    }
+
+    /// Create a library from the `lv2_descriptor` function of plugins that are linked into the
+    /// host, for example one that was exported with the `lv2_descriptors` macro of `lv2rs-core`.
+    ///
+    /// The bundle path is passed to the plugins when they are instantiated and has to end with a
+    /// directory separator.
+    ///
+    /// This function is unsafe since the function is trusted to return valid descriptors.
+    pub unsafe fn from_function(function: DescriptorFunction, bundle_path: CString) -> Self {
+        let mut descriptors = Vec::new();
+        loop {
+            let descriptor = function(descriptors.len() as u32);
+            if descriptor.is_null() {
+                break;
+            }
+            descriptors.push(descriptor);
+        }
+        Self {
+            descriptors,
+            lib_descriptor: std::ptr::null(),
+            bundle_path,
+            #[cfg(any(unix, windows))]
+            _library: None,
+        }
+    }
+
+    /// Return the bundle path of the library.
+    pub fn bundle_path(&self) -> &CStr {
+        &self.bundle_path
+    }
+
+    /// Return the number of plugins in the library.
+    pub fn len(&self) -> usize {
+        self.descriptors.len()
+    }
+
+    /// Return `true` if the library contains no plugins.
+    pub fn is_empty(&self) -> bool {
+        self.descriptors.is_empty()
+    }
+
+    /// Return the descriptor of the plugin with the given index.
+    pub fn descriptor(&self, index: usize) -> Option<&core::Descriptor> {
+        self.descriptors
+            .get(index)
+            .and_then(|descriptor| unsafe { descriptor.as_ref() })
+    }
+
+    /// Iterate over the descriptors of all plugins in the library.
+    pub fn descriptors(&self) -> impl Iterator<Item = &core::Descriptor> {
+        (0..self.len()).filter_map(move |index| self.descriptor(index))
+    }
+
+    /// Return the URIs of all plugins in the library.
+    pub fn uris(&self) -> Vec<&CStr> {
+        self.descriptors()
+            .map(|descriptor| unsafe { CStr::from_ptr(descriptor.uri) })
+            .collect()
+    }
+
+    /// Return the descriptor of the plugin with the given null-terminated URI.
+    pub fn find(&self, uri: &[u8]) -> Option<&core::Descriptor> {
+        let uri = CStr::from_bytes_with_nul(uri).ok()?;
+        self.descriptors()
+            .find(|descriptor| unsafe { CStr::from_ptr(descriptor.uri) } == uri)
+    }
+}
+
+/// Return the bundle path of a binary, which is the directory that contains it.
+#[cfg(any(unix, windows))]
+fn bundle_path_of(path: &Path) -> Result<CString, ()> {
+    let directory = match path.parent() {
+        Some(directory) => directory.to_str().ok_or(())?,
+        None => "",
+    };
+    let mut bundle_path = if directory.is_empty() {
+        String::from(".")
+    } else {
+        directory.to_owned()
+    };
+    if !bundle_path.ends_with(std::path::MAIN_SEPARATOR) {
+        bundle_path.push(std::path::MAIN_SEPARATOR);
+    }
+    CString::new(bundle_path).map_err(|_| ())
+}
+
+impl Drop for PluginLibrary {
+    fn drop(&mut self) {
+        if let Some(lib_descriptor) = unsafe { self.lib_descriptor.as_ref() } {
+            unsafe { (lib_descriptor.cleanup)(lib_descriptor.handle) };
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use crate::library::*;
+
+    #[test]
+    fn test_bundle_path() {
+        let bundle_path = |path: &str| bundle_path_of(Path::new(path)).unwrap();
+        assert_eq!(
+            CString::new("/usr/lib/lv2/amp.lv2/").unwrap(),
+            bundle_path("/usr/lib/lv2/amp.lv2/amp.so")
+        );
+        assert_eq!(CString::new("./").unwrap(), bundle_path("amp.so"));
+        assert_eq!(CString::new("/").unwrap(), bundle_path("/amp.so"));
+
+        use std::os::unix::ffi::OsStrExt;
+        let invalid = std::ffi::OsStr::from_bytes(b"/\xff/amp.so");
+        assert!(bundle_path_of(Path::new(invalid)).is_err());
+    }
+}
@@ -0,0 +1,360 @@
+//! Recordings of the port I/O of instances, for golden-file regression tests.
+use crate::host::TestHost;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use urid::URID;
+
+/// How a port was connected when the recording started.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PortSetup {
+    /// A control port.
+    Control,
+    /// An audio or CV port, with the length of its buffer.
+    Audio(usize),
+    /// An atom input, with the capacity of its buffer in bytes.
+    AtomInput(usize),
+    /// An atom output, with the capacity of its buffer in bytes.
+    AtomOutput(usize),
+}
+
+/// The content of a port in a cycle.
+#[derive(Clone, PartialEq, Debug)]
+pub enum PortData {
+    /// The value of a control port.
+    Control(f32),
+    /// The samples of an audio or CV port, as many as the cycle is long.
+    Audio(Vec<f32>),
+    /// An atom, including its header.
+    Atom(Vec<u8>),
+}
+
+/// The inputs and outputs of a single call to `run`.
+///
+/// Since the test host can't tell control and audio inputs from outputs, these ports are recorded
+/// as inputs before the plugin is run and as outputs afterwards. Atom inputs are only recorded
+/// as inputs and atom outputs only as outputs.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Cycle {
+    pub n_samples: u32,
+    pub inputs: BTreeMap<u32, PortData>,
+    pub outputs: BTreeMap<u32, PortData>,
+}
+
+/// The recorded port I/O of an instance.
+///
+/// A recording is created by a [`PluginInstance`](struct.PluginInstance.html) between the calls to
+/// [`start_recording`](struct.PluginInstance.html#method.start_recording) and
+/// [`stop_recording`](struct.PluginInstance.html#method.stop_recording). It contains everything
+/// that is needed to run the plugin again in the same way: The sample rate, the options, the URID
+/// table of the host, which is needed to interpret the atoms, the connected ports and the inputs
+/// of every cycle. [`replay`](#method.replay) runs a plugin against a recording and compares the
+/// outputs with the recorded ones, which makes recordings usable as golden files for regression
+/// tests. They are stored in a line-based text format with [`save`](#method.save) and loaded with
+/// [`load`](#method.load).
+#[derive(Clone, PartialEq, Debug)]
+pub struct Recording {
+    pub sample_rate: f64,
+    pub urids: String,
+    pub options: Vec<(URID, URID, Vec<u8>)>,
+    pub ports: BTreeMap<u32, PortSetup>,
+    pub cycles: Vec<Cycle>,
+}
+
+/// The reason why a replay failed.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ReplayError {
+    /// The URID table of the recording is malformed.
+    BadUridTable,
+    /// The plugin refused to instantiate.
+    Instantiation,
+    /// An output of the plugin differs from the recorded one.
+    ///
+    /// `actual` is `None` if the port doesn't provide this kind of data.
+    Mismatch {
+        cycle: usize,
+        port: u32,
+        expected: PortData,
+        actual: Option<PortData>,
+    },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::BadUridTable => write!(f, "The URID table is malformed"),
+            ReplayError::Instantiation => write!(f, "The plugin refused to instantiate"),
+            ReplayError::Mismatch {
+                cycle,
+                port,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Output {} differs in cycle {}: Expected {:?}, got {:?}",
+                port, cycle, expected, actual
+            ),
+        }
+    }
+}
+
+/// The first line of a recording file.
+const HEADER: &str = "lv2rs-recording 1";
+
+fn invalid_data<E: fmt::Display>(message: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn write_hex<W: Write>(output: &mut W, bytes: &[u8]) -> io::Result<()> {
+    write!(output, " ")?;
+    for byte in bytes {
+        write!(output, "{:02x}", byte)?;
+    }
+    Ok(())
+}
+
+fn parse_hex(word: &str) -> io::Result<Vec<u8>> {
+    if word.len() % 2 == 1 {
+        return Err(invalid_data("Odd number of hex digits"));
+    }
+    (0..word.len())
+        .step_by(2)
+        .map(|index| {
+            word.get(index..index + 2)
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                .ok_or_else(|| invalid_data(format!("Invalid hex byte in {}", word)))
+        })
+        .collect()
+}
+
+fn parse<T: std::str::FromStr>(word: Option<&str>) -> io::Result<T> {
+    let word = word.ok_or_else(|| invalid_data("Unexpected end of line"))?;
+    word.parse()
+        .map_err(|_| invalid_data(format!("Invalid value {}", word)))
+}
+
+fn write_port_data<W: Write>(output: &mut W, port: u32, data: &PortData) -> io::Result<()> {
+    match data {
+        PortData::Control(value) => write!(output, " {} control {}", port, value)?,
+        PortData::Audio(samples) => {
+            write!(output, " {} audio", port)?;
+            for sample in samples {
+                write!(output, " {}", sample)?;
+            }
+        }
+        PortData::Atom(bytes) => {
+            write!(output, " {} atom", port)?;
+            write_hex(output, bytes)?;
+        }
+    }
+    writeln!(output)
+}
+
+fn parse_port_data<'a, I: Iterator<Item = &'a str>>(mut words: I) -> io::Result<(u32, PortData)> {
+    let port = parse(words.next())?;
+    let data = match words.next() {
+        Some("control") => PortData::Control(parse(words.next())?),
+        Some("audio") => PortData::Audio(
+            words
+                .map(|word| parse(Some(word)))
+                .collect::<io::Result<_>>()?,
+        ),
+        Some("atom") => PortData::Atom(parse_hex(words.next().unwrap_or(""))?),
+        _ => return Err(invalid_data("Unknown port data")),
+    };
+    Ok((port, data))
+}
+
+/// Compare a recorded output with the actual output.
+fn matches(expected: &PortData, actual: &PortData, tolerance: f32) -> bool {
+    match (expected, actual) {
+        (PortData::Control(expected), PortData::Control(actual)) => {
+            (expected - actual).abs() <= tolerance || expected == actual
+        }
+        (PortData::Audio(expected), PortData::Audio(actual)) => {
+            expected.len() == actual.len()
+                && expected.iter().zip(actual).all(|(expected, actual)| {
+                    (expected - actual).abs() <= tolerance || expected == actual
+                })
+        }
+        (PortData::Atom(expected), PortData::Atom(actual)) => expected == actual,
+        _ => false,
+    }
+}
+
+impl Recording {
+    /// Run a plugin against the recording and compare its outputs with the recorded ones.
+    ///
+    /// The plugin is instantiated by a new host with the recorded sample rate, options and URID
+    /// table, its ports are connected like they were connected during the recording and it is
+    /// run for every recorded cycle. The samples of audio outputs and the values of control
+    /// outputs may differ from the recorded ones by the tolerance, atoms have to be equal.
+    ///
+    /// The URI is the null-terminated URI of the plugin. The first mismatch is returned as an
+    /// error.
+    pub fn replay<P: core::Plugin>(
+        &self,
+        uri: &'static [u8],
+        tolerance: f32,
+    ) -> Result<(), ReplayError> {
+        let mut host = TestHost::new(self.sample_rate);
+        host.urids()
+            .load_table(&self.urids)
+            .map_err(|_| ReplayError::BadUridTable)?;
+        for option in &self.options {
+            host.set_raw_option(option.clone());
+        }
+        let mut instance = host
+            .instantiate::<P>(uri)
+            .ok_or(ReplayError::Instantiation)?;
+        for (port, setup) in &self.ports {
+            match *setup {
+                PortSetup::Control => instance.connect_control(*port, 0.0),
+                PortSetup::Audio(length) => instance.connect_audio(*port, vec![0.0; length]),
+                PortSetup::AtomInput(capacity) => instance.connect_atom_input(*port, capacity),
+                PortSetup::AtomOutput(capacity) => instance.connect_atom_output(*port, capacity),
+            }
+        }
+
+        for (index, cycle) in self.cycles.iter().enumerate() {
+            for (port, data) in &cycle.inputs {
+                instance.load_port(*port, data);
+            }
+            instance.run(cycle.n_samples);
+            for (port, expected) in &cycle.outputs {
+                let actual = instance.port_data(*port, false, cycle.n_samples);
+                let matching = actual
+                    .as_ref()
+                    .map(|actual| matches(expected, actual, tolerance))
+                    .unwrap_or(false);
+                if !matching {
+                    return Err(ReplayError::Mismatch {
+                        cycle: index,
+                        port: *port,
+                        expected: expected.clone(),
+                        actual,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the recording in its text format.
+    pub fn write<W: Write>(&self, mut output: W) -> io::Result<()> {
+        writeln!(output, "{}", HEADER)?;
+        writeln!(output, "sample-rate {}", self.sample_rate)?;
+        for line in self.urids.lines() {
+            writeln!(output, "urid {}", line)?;
+        }
+        for (key, value_type, value) in &self.options {
+            write!(output, "option {} {}", key, value_type)?;
+            write_hex(&mut output, value)?;
+            writeln!(output)?;
+        }
+        for (port, setup) in &self.ports {
+            match setup {
+                PortSetup::Control => writeln!(output, "port {} control", port)?,
+                PortSetup::Audio(length) => writeln!(output, "port {} audio {}", port, length)?,
+                PortSetup::AtomInput(capacity) => {
+                    writeln!(output, "port {} atom-input {}", port, capacity)?
+                }
+                PortSetup::AtomOutput(capacity) => {
+                    writeln!(output, "port {} atom-output {}", port, capacity)?
+                }
+            }
+        }
+        for cycle in &self.cycles {
+            writeln!(output, "cycle {}", cycle.n_samples)?;
+            for (port, data) in &cycle.inputs {
+                write!(output, "input")?;
+                write_port_data(&mut output, *port, data)?;
+            }
+            for (port, data) in &cycle.outputs {
+                write!(output, "output")?;
+                write_port_data(&mut output, *port, data)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a recording in its text format.
+    ///
+    /// An error of the kind `InvalidData` is returned if the input isn't a valid recording.
+    pub fn read<R: BufRead>(input: R) -> io::Result<Self> {
+        let mut lines = input.lines();
+        match lines.next() {
+            Some(Ok(ref line)) if line == HEADER => (),
+            Some(Err(error)) => return Err(error),
+            _ => return Err(invalid_data("Not a recording")),
+        }
+
+        let mut recording = Recording {
+            sample_rate: 0.0,
+            urids: String::new(),
+            options: Vec::new(),
+            ports: BTreeMap::new(),
+            cycles: Vec::new(),
+        };
+        for line in lines {
+            let line = line?;
+            let mut words = line.split_whitespace();
+            match words.next() {
+                None => (),
+                Some("sample-rate") => recording.sample_rate = parse(words.next())?,
+                Some("urid") => {
+                    recording.urids.push_str(line["urid".len()..].trim());
+                    recording.urids.push('\n');
+                }
+                Some("option") => {
+                    let key = parse(words.next())?;
+                    let value_type = parse(words.next())?;
+                    let value = parse_hex(words.next().unwrap_or(""))?;
+                    recording.options.push((key, value_type, value));
+                }
+                Some("port") => {
+                    let port = parse(words.next())?;
+                    let setup = match words.next() {
+                        Some("control") => PortSetup::Control,
+                        Some("audio") => PortSetup::Audio(parse(words.next())?),
+                        Some("atom-input") => PortSetup::AtomInput(parse(words.next())?),
+                        Some("atom-output") => PortSetup::AtomOutput(parse(words.next())?),
+                        _ => return Err(invalid_data("Unknown port setup")),
+                    };
+                    recording.ports.insert(port, setup);
+                }
+                Some("cycle") => recording.cycles.push(Cycle {
+                    n_samples: parse(words.next())?,
+                    ..Cycle::default()
+                }),
+                Some(direction @ "input") | Some(direction @ "output") => {
+                    let cycle = recording
+                        .cycles
+                        .last_mut()
+                        .ok_or_else(|| invalid_data("Port data outside of a cycle"))?;
+                    let (port, data) = parse_port_data(words)?;
+                    if direction == "input" {
+                        cycle.inputs.insert(port, data);
+                    } else {
+                        cycle.outputs.insert(port, data);
+                    }
+                }
+                Some(word) => return Err(invalid_data(format!("Unknown entry {}", word))),
+            }
+        }
+        Ok(recording)
+    }
+
+    /// Save the recording to a file.
+    pub fn save<T: AsRef<Path>>(&self, path: T) -> io::Result<()> {
+        let mut file = io::BufWriter::new(std::fs::File::create(path)?);
+        self.write(&mut file)?;
+        file.flush()
+    }
+
+    /// Load a recording from a file.
+    pub fn load<T: AsRef<Path>>(path: T) -> io::Result<Self> {
+        Self::read(io::BufReader::new(std::fs::File::open(path)?))
+    }
+}
@@ -0,0 +1,138 @@
+extern crate lv2rs_core as core;
+extern crate lv2rs_test_host as test_host;
+extern crate lv2rs_urid as urid;
+
+use std::ffi::{CStr, CString};
+use test_host::*;
+use urid::CachedMap;
+
+/// A plugin that amplifies its input.
+struct Amp {
+    gain: core::ports::ParameterInputPort,
+    input: core::ports::AudioInputPort,
+    output: core::ports::AudioOutputPort,
+}
+
+impl core::Plugin for Amp {
+    fn instantiate(
+        _context: &core::InstantiationContext,
+        _features: Option<&core::FeaturesList>,
+    ) -> Result<Self, core::InstantiateError> {
+        Ok(Self {
+            gain: core::ports::ParameterInputPort::new(),
+            input: core::ports::AudioInputPort::new(),
+            output: core::ports::AudioOutputPort::new(),
+        })
+    }
+
+    fn connect_port(&mut self, port: u32, data: *mut ()) {
+        match port {
+            0 => self.gain.connect(data as *const f32),
+            1 => self.input.connect(data as *const f32),
+            2 => self.output.connect(data as *mut f32),
+            _ => (),
+        }
+    }
+
+    fn run(&mut self, context: &mut core::RunContext) {
+        let gain = *unsafe { self.gain.get() }.unwrap();
+        let input = unsafe { self.input.as_slice(context.n_samples()) }.unwrap();
+        let output = unsafe { self.output.as_slice(context.n_samples()) }.unwrap();
+        for (output, input) in output.iter_mut().zip(input) {
+            *output = input * gain;
+        }
+    }
+}
+
+/// A plugin that outputs the length of its bundle path and requires the URID map.
+struct BundleLength {
+    length: usize,
+    output: core::ports::ParameterOutputPort,
+}
+
+impl core::Plugin for BundleLength {
+    fn instantiate(
+        context: &core::InstantiationContext,
+        features: Option<&core::FeaturesList>,
+    ) -> Result<Self, core::InstantiateError> {
+        features
+            .and_then(CachedMap::try_from_features)
+            .ok_or_else(core::InstantiateError::missing_feature::<urid::Map>)?;
+        Ok(Self {
+            length: context.bundle_path_raw().to_bytes().len(),
+            output: core::ports::ParameterOutputPort::new(),
+        })
+    }
+
+    fn connect_port(&mut self, port: u32, data: *mut ()) {
+        if port == 0 {
+            self.output.connect(data as *mut f32);
+        }
+    }
+
+    fn run(&mut self, _context: &mut core::RunContext) {
+        if let Some(output) = unsafe { self.output.get_mut() } {
+            *output = self.length as f32;
+        }
+    }
+}
+
+core::lv2_descriptors!(core, [
+    Amp: b"http://example.org/Amp\0",
+    BundleLength: b"http://example.org/BundleLength\0",
+]);
+
+#[test]
+fn test_descriptors() {
+    let library =
+        unsafe { PluginLibrary::from_function(lv2_descriptor, CString::new("/lv2/").unwrap()) };
+    assert_eq!(2, library.len());
+    assert!(!library.is_empty());
+    assert_eq!(
+        vec![
+            CStr::from_bytes_with_nul(b"http://example.org/Amp\0").unwrap(),
+            CStr::from_bytes_with_nul(b"http://example.org/BundleLength\0").unwrap(),
+        ],
+        library.uris()
+    );
+
+    let descriptor = library.find(b"http://example.org/BundleLength\0").unwrap();
+    assert_eq!(
+        library.descriptor(1).unwrap() as *const core::Descriptor,
+        descriptor as *const core::Descriptor
+    );
+    assert!(library.find(b"http://example.org/Delay\0").is_none());
+    assert!(library.find(b"http://example.org/Amp").is_none());
+    assert!(library.descriptor(2).is_none());
+
+    #[cfg(any(unix, windows))]
+    assert!(unsafe { PluginLibrary::load("/nonexistent/plugin.so") }.is_err());
+}
+
+#[test]
+fn test_instantiate_from() {
+    let host = TestHost::new(44100.0);
+    let library =
+        unsafe { PluginLibrary::from_function(lv2_descriptor, CString::new("/lv2/").unwrap()) };
+
+    let mut amp = host
+        .instantiate_from(&library, b"http://example.org/Amp\0")
+        .unwrap();
+    amp.connect_control(0, 2.0);
+    amp.connect_audio(1, vec![1.0, 2.0, 3.0]);
+    amp.connect_audio(2, vec![0.0; 3]);
+    amp.run(3);
+    assert_eq!(&[2.0, 4.0, 6.0], amp.audio(2).unwrap());
+
+    // The plugin receives the bundle path of the library and the features of the host.
+    let mut bundle_length = host
+        .instantiate_from(&library, b"http://example.org/BundleLength\0")
+        .unwrap();
+    bundle_length.connect_control(0, 0.0);
+    bundle_length.run(1);
+    assert_eq!(Some(5.0), bundle_length.control(0));
+
+    assert!(host
+        .instantiate_from(&library, b"http://example.org/Delay\0")
+        .is_none());
+}
@@ -0,0 +1,125 @@
+extern crate lv2rs_core as core;
+extern crate lv2rs_log as lv2log;
+extern crate lv2rs_test_host as test_host;
+
+use core::ports::*;
+use lv2log::EntryType;
+use test_host::TestHost;
+
+/// A gain that panics if the gain is negative.
+struct Fragile {
+    gain: ParameterInputPort,
+    input: AudioInputPort,
+    output: AudioOutputPort,
+    bypassed_samples: u32,
+}
+
+impl core::Plugin for Fragile {
+    fn instantiate(
+        _context: &core::InstantiationContext,
+        _features: Option<&core::FeaturesList>,
+    ) -> Result<Self, core::InstantiateError> {
+        Ok(Self {
+            gain: ParameterInputPort::new(),
+            input: AudioInputPort::new(),
+            output: AudioOutputPort::new(),
+            bypassed_samples: 0,
+        })
+    }
+
+    fn connect_port(&mut self, port: u32, data: *mut ()) {
+        match port {
+            0 => self.gain.connect(data as *const f32),
+            1 => self.input.connect(data as *const f32),
+            2 => self.output.connect(data as *mut f32),
+            _ => panic!("there is no port {}", port),
+        }
+    }
+
+    fn run(&mut self, context: &mut core::RunContext) {
+        let gain = *unsafe { self.gain.get() }.unwrap();
+        assert!(gain >= 0.0, "negative gain");
+        let input = unsafe { self.input.as_slice(context.n_samples()) }.unwrap();
+        let output = unsafe { self.output.as_slice(context.n_samples()) }.unwrap();
+        for (output, input) in output.iter_mut().zip(input) {
+            *output = input * gain;
+        }
+    }
+
+    fn bypass(&mut self, context: &mut core::RunContext) {
+        self.bypassed_samples += context.n_samples();
+        let input = unsafe { self.input.as_slice(context.n_samples()) }.unwrap();
+        let output = unsafe { self.output.as_slice(context.n_samples()) }.unwrap();
+        output.copy_from_slice(input);
+    }
+}
+
+/// A plugin that panics while it's instantiated.
+struct Exploding;
+
+impl core::Plugin for Exploding {
+    fn instantiate(
+        _context: &core::InstantiationContext,
+        _features: Option<&core::FeaturesList>,
+    ) -> Result<Self, core::InstantiateError> {
+        panic!("boom")
+    }
+
+    fn connect_port(&mut self, _port: u32, _data: *mut ()) {}
+
+    fn run(&mut self, _context: &mut core::RunContext) {}
+}
+
+#[test]
+fn test_panics() {
+    let host = TestHost::new(44100.0);
+
+    let mut instance = host
+        .instantiate::<Fragile>(b"http://example.org/Fragile\0")
+        .unwrap();
+    instance.connect_control(0, 2.0);
+    instance.connect_audio(1, vec![1.0, 2.0]);
+    instance.connect_audio(2, vec![0.0; 2]);
+    instance.run(2);
+    assert_eq!(&[2.0, 4.0], instance.audio(2).unwrap());
+    assert!(host.take_log().is_empty());
+
+    // The panic is logged and the plugin is bypassed.
+    instance.set_control(0, -1.0);
+    instance.run(2);
+    assert_eq!(
+        vec![(
+            EntryType::Error,
+            "Plugin panicked in run: negative gain\n".to_owned()
+        )],
+        host.take_log()
+    );
+    assert_eq!(0, instance.plugin().bypassed_samples);
+
+    // The plugin stays bypassed, even if the gain is valid again.
+    instance.set_control(0, 0.5);
+    instance.run(2);
+    assert_eq!(&[1.0, 2.0], instance.audio(2).unwrap());
+    assert_eq!(2, instance.plugin().bypassed_samples);
+
+    instance.connect_control(3, 0.0);
+    assert_eq!(
+        vec![(
+            EntryType::Error,
+            "Plugin panicked in connect_port: there is no port 3\n".to_owned()
+        )],
+        host.take_log()
+    );
+    drop(instance);
+
+    assert!(host
+        .instantiate::<Exploding>(b"http://example.org/Exploding\0")
+        .is_none());
+    assert_eq!(
+        vec![(
+            EntryType::Error,
+            "Plugin panicked in instantiate: boom\n".to_owned()
+        )],
+        host.take_log()
+    );
+}
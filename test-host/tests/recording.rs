@@ -0,0 +1,198 @@
+extern crate lv2rs_atom as atom;
+extern crate lv2rs_core as core;
+extern crate lv2rs_options as options;
+extern crate lv2rs_test_host as test_host;
+extern crate lv2rs_urid as urid;
+
+use atom::ports::*;
+use atom::prelude::*;
+use atom::sequence::{TimeStamp, TimeUnit};
+use core::uris::Uri;
+use test_host::*;
+use urid::CachedMap;
+
+/// A plugin that amplifies its audio input and outputs the running sum of the integers it
+/// receives.
+///
+/// The broken version forgets its sum in every cycle.
+struct Accumulator<const BROKEN: bool> {
+    urids: CachedMap,
+    gain: core::ports::ParameterInputPort,
+    input: core::ports::AudioInputPort,
+    output: core::ports::AudioOutputPort,
+    events_in: AtomInputPort<Sequence>,
+    events_out: AtomOutputPort<Sequence>,
+    sum: i32,
+}
+
+impl<const BROKEN: bool> core::Plugin for Accumulator<BROKEN> {
+    fn instantiate(
+        _context: &core::InstantiationContext,
+        features: Option<&core::FeaturesList>,
+    ) -> Result<Self, core::InstantiateError> {
+        let urids = features
+            .and_then(CachedMap::try_from_features)
+            .ok_or_else(core::InstantiateError::missing_feature::<urid::Map>)?;
+        Ok(Self {
+            urids,
+            gain: core::ports::ParameterInputPort::new(),
+            input: core::ports::AudioInputPort::new(),
+            output: core::ports::AudioOutputPort::new(),
+            events_in: AtomInputPort::new(),
+            events_out: AtomOutputPort::new(),
+            sum: 0,
+        })
+    }
+
+    fn connect_port(&mut self, port: u32, data: *mut ()) {
+        match port {
+            0 => self.gain.connect(data as *const f32),
+            1 => self.input.connect(data as *const f32),
+            2 => self.output.connect(data as *mut f32),
+            3 => self.events_in.connect_port(data as *const Atom),
+            4 => self.events_out.connect_port(data as *mut Atom),
+            _ => (),
+        }
+    }
+
+    fn run(&mut self, context: &mut core::RunContext) {
+        let gain = *unsafe { self.gain.get() }.unwrap();
+        let input = unsafe { self.input.as_slice(context.n_samples()) }.unwrap();
+        let output = unsafe { self.output.as_slice(context.n_samples()) }.unwrap();
+        for (output, input) in output.iter_mut().zip(input) {
+            *output = input * gain;
+        }
+
+        if BROKEN {
+            self.sum = 0;
+        }
+        let events_in = unsafe { self.events_in.read(context, &mut self.urids) }.unwrap();
+        let mut events_out = unsafe {
+            self.events_out
+                .write_atom_body(&TimeUnit::Frames, &mut self.urids)
+        }
+        .unwrap();
+        for (time, event) in events_in.iter(&mut self.urids) {
+            if let Ok(value) = event.get_body::<i32>(&mut self.urids) {
+                self.sum += *value;
+                events_out
+                    .push_event::<i32>(time, &self.sum, &mut self.urids)
+                    .unwrap();
+            }
+        }
+    }
+}
+
+const URI: Uri = Uri::new(b"http://example.org/Accumulator\0");
+
+/// Run the working plugin for three cycles and record them.
+fn record() -> Recording {
+    let mut host = TestHost::new(44100.0);
+    host.set_option(options::uris::MAX_BLOCK_LENGTH_URI, 4i32);
+    let mut urids = unsafe { host.create_cached_map() };
+    let mut instance = host
+        .instantiate::<Accumulator<false>>(URI.as_bytes_with_nul())
+        .unwrap();
+    instance.connect_control(0, 2.0);
+    instance.connect_audio(1, vec![0.0; 4]);
+    instance.connect_audio(2, vec![0.0; 4]);
+    instance.connect_atom_input(3, 256);
+    instance.connect_atom_output(4, 256);
+    assert!(instance.stop_recording().is_none());
+    instance.start_recording();
+    assert!(instance.is_recording());
+
+    for (cycle, value) in [1, 2, 3].iter().enumerate() {
+        instance
+            .audio_mut(1)
+            .unwrap()
+            .copy_from_slice(&[*value as f32; 4]);
+        instance.set_control(0, 2.0 - cycle as f32 * 0.5);
+        {
+            let mut port: AtomOutputPort<Sequence> = AtomOutputPort::new();
+            port.connect_port(instance.atom_input(3).unwrap());
+            let mut frame = unsafe { port.write_atom_body(&TimeUnit::Frames, &mut urids) }.unwrap();
+            frame
+                .push_event::<i32>(TimeStamp::Frames(*value), &(*value as i32 * 10), &mut urids)
+                .unwrap();
+        }
+        instance.run(if cycle == 2 { 2 } else { 4 });
+    }
+    let recording = instance.stop_recording().unwrap();
+    assert!(!instance.is_recording());
+    recording
+}
+
+#[test]
+fn test_recording() {
+    let recording = record();
+    assert_eq!(44100.0, recording.sample_rate);
+    assert_eq!(1, recording.options.len());
+    assert_eq!(Some(&PortSetup::Control), recording.ports.get(&0));
+    assert_eq!(Some(&PortSetup::Audio(4)), recording.ports.get(&2));
+    assert_eq!(Some(&PortSetup::AtomOutput(256)), recording.ports.get(&4));
+    assert_eq!(3, recording.cycles.len());
+
+    let cycle = &recording.cycles[2];
+    assert_eq!(2, cycle.n_samples);
+    assert_eq!(Some(&PortData::Control(1.0)), cycle.inputs.get(&0));
+    assert_eq!(
+        Some(&PortData::Audio(vec![3.0, 3.0])),
+        cycle.outputs.get(&2)
+    );
+    assert!(!cycle.outputs.contains_key(&3));
+    assert!(!cycle.inputs.contains_key(&4));
+    match cycle.outputs.get(&4) {
+        // The header of the sequence, its time unit and one event with an integer.
+        Some(PortData::Atom(bytes)) => assert_eq!(8 + 8 + 16 + 8, bytes.len()),
+        data => panic!("Unexpected output {:?}", data),
+    }
+
+    // The text format contains everything.
+    let mut file = Vec::new();
+    recording.write(&mut file).unwrap();
+    assert!(file.starts_with(b"lv2rs-recording 1\nsample-rate 44100\n"));
+    assert_eq!(recording, Recording::read(file.as_slice()).unwrap());
+    assert!(Recording::read(&b"lv2rs-recording 2\n"[..]).is_err());
+    assert!(Recording::read(&b"lv2rs-recording 1\ninput 0 control 1\n"[..]).is_err());
+}
+
+#[test]
+fn test_replay() {
+    let recording = record();
+    assert_eq!(
+        Ok(()),
+        recording.replay::<Accumulator<false>>(URI.as_bytes_with_nul(), 0.0)
+    );
+
+    // The broken plugin outputs the same first cycle, but another sum in the second one.
+    match recording.replay::<Accumulator<true>>(URI.as_bytes_with_nul(), 0.0) {
+        Err(ReplayError::Mismatch {
+            cycle: 1,
+            port: 4,
+            expected: PortData::Atom(_),
+            actual: Some(PortData::Atom(_)),
+        }) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+
+    // Audio outputs are compared with a tolerance.
+    let mut changed = recording.clone();
+    changed.cycles[0]
+        .outputs
+        .insert(2, PortData::Audio(vec![2.0, 2.0, 2.0, 2.01]));
+    assert!(changed
+        .replay::<Accumulator<false>>(URI.as_bytes_with_nul(), 0.0)
+        .is_err());
+    assert_eq!(
+        Ok(()),
+        changed.replay::<Accumulator<false>>(URI.as_bytes_with_nul(), 0.1)
+    );
+
+    let mut changed = recording.clone();
+    changed.urids = "0 http://example.org\n".to_owned();
+    assert_eq!(
+        Err(ReplayError::BadUridTable),
+        changed.replay::<Accumulator<false>>(URI.as_bytes_with_nul(), 0.0)
+    );
+}
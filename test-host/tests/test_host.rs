@@ -0,0 +1,218 @@
+extern crate lv2rs_atom as atom;
+extern crate lv2rs_core as core;
+extern crate lv2rs_log as lv2log;
+extern crate lv2rs_options as options;
+extern crate lv2rs_test_host as test_host;
+extern crate lv2rs_urid as urid;
+extern crate lv2rs_worker as worker;
+
+use atom::ports::*;
+use atom::prelude::*;
+use atom::sequence::{TimeStamp, TimeUnit};
+use core::uris::Uri;
+use lv2log::{EntryType, Log, Logger};
+use options::{HostOptions, OptionsOption};
+use std::ffi::CStr;
+use test_host::TestHost;
+use urid::CachedMap;
+use worker::*;
+
+/// A plugin that sums the integers it receives in a worker and echoes them.
+struct Summer {
+    urids: CachedMap,
+    logger: Logger<'static>,
+    schedule: Schedule<'static, Summer>,
+    max_block_length: Option<i32>,
+    input: AtomInputPort<Sequence>,
+    output: AtomOutputPort<Sequence>,
+    sum: i32,
+    end_runs: u32,
+}
+
+impl core::Plugin for Summer {
+    fn instantiate(
+        _context: &core::InstantiationContext,
+        features: Option<&core::FeaturesList>,
+    ) -> Result<Self, core::InstantiateError> {
+        let missing_map = core::InstantiateError::missing_feature::<urid::Map>;
+        let features = features.ok_or_else(missing_map)?;
+        let mut urids = CachedMap::try_from_features(features).ok_or_else(missing_map)?;
+        let host_options = HostOptions::from_features(features, &mut urids)
+            .ok_or_else(core::InstantiateError::missing_feature::<OptionsOption>)?;
+        let logger = Logger::try_from_features(features, &mut urids)
+            .ok_or_else(core::InstantiateError::missing_feature::<Log>)?;
+        logger
+            .note("Summer instantiated\n")
+            .map_err(|_| core::InstantiateError::Other("Failed to log".into()))?;
+        Ok(Self {
+            urids,
+            logger,
+            schedule: Schedule::try_from_features(features)
+                .ok_or_else(core::InstantiateError::missing_feature::<WorkerSchedule>)?,
+            max_block_length: host_options.max_block_length,
+            input: AtomInputPort::new(),
+            output: AtomOutputPort::new(),
+            sum: 0,
+            end_runs: 0,
+        })
+    }
+
+    fn connect_port(&mut self, port: u32, data: *mut ()) {
+        match port {
+            0 => self.input.connect_port(data as *const Atom),
+            1 => self.output.connect_port(data as *mut Atom),
+            _ => (),
+        }
+    }
+
+    fn run(&mut self, context: &mut core::RunContext) {
+        let input = unsafe { self.input.read(context, &mut self.urids) }.unwrap();
+        let mut frame = unsafe {
+            self.output
+                .write_atom_body(&TimeUnit::Frames, &mut self.urids)
+        }
+        .unwrap();
+        for (time, event) in input.iter(&mut self.urids) {
+            if let Ok(value) = event.get_body::<i32>(&mut self.urids) {
+                self.schedule.schedule_work(*value).unwrap();
+                frame
+                    .push_event::<i32>(time, value, &mut self.urids)
+                    .unwrap();
+            }
+        }
+    }
+
+    fn extension_data(uri: &CStr) -> Option<&'static dyn core::ExtensionData> {
+        core::extension_data!(uri, [(worker::uris::INTERFACE_URI, &WORKER_INTERFACE)])
+    }
+}
+
+impl Worker for Summer {
+    type WorkData = i32;
+    type ResponseData = i32;
+
+    fn work(
+        &self,
+        response_handler: &mut ResponseHandler<Self>,
+        value: i32,
+    ) -> Result<(), WorkerErr> {
+        response_handler.respond(value)
+    }
+
+    fn work_response(&mut self, value: i32) -> Result<(), WorkerErr> {
+        self.sum += value;
+        Ok(())
+    }
+
+    fn end_run(&mut self) -> Result<(), WorkerErr> {
+        self.end_runs += 1;
+        self.logger
+            .error("end of run\n")
+            .map_err(|_| WorkerErr::Unknown)
+    }
+}
+
+static WORKER_INTERFACE: WorkerInterface = WorkerInterface::new::<Summer>();
+
+const UNSUPPORTED_URI: Uri = Uri::new(b"http://example.org/Unsupported\0");
+
+/// A plugin that requires a feature the test host doesn't provide.
+struct Demanding;
+
+impl core::Plugin for Demanding {
+    fn instantiate(
+        _context: &core::InstantiationContext,
+        _features: Option<&core::FeaturesList>,
+    ) -> Result<Self, core::InstantiateError> {
+        Ok(Demanding)
+    }
+
+    fn required_features() -> &'static [Uri] {
+        &[UNSUPPORTED_URI]
+    }
+
+    fn connect_port(&mut self, _port: u32, _data: *mut ()) {}
+
+    fn run(&mut self, _context: &mut core::RunContext) {}
+}
+
+#[test]
+fn test_host() {
+    let mut host = TestHost::new(48000.0);
+    host.set_option(options::uris::MAX_BLOCK_LENGTH_URI, 256i32);
+    host.set_option(options::uris::MAX_BLOCK_LENGTH_URI, 512i32);
+    let mut urids = unsafe { host.create_cached_map() };
+
+    let mut instance = host
+        .instantiate::<Summer>(b"http://example.org/Summer\0")
+        .unwrap();
+    assert_eq!(Some(512), instance.plugin().max_block_length);
+    assert_eq!(
+        vec![(EntryType::Note, "Summer instantiated\n".to_owned())],
+        host.take_log()
+    );
+
+    instance.connect_atom_input(0, 256);
+    instance.connect_atom_output(1, 256);
+
+    // Without events.
+    instance.run(64);
+    let read_output = |instance: &test_host::TestInstance<Summer>, urids: &mut CachedMap| {
+        let atom = instance.atom(1).unwrap();
+        let sequence = atom.get_body::<Sequence>(urids).unwrap();
+        sequence
+            .iter(urids)
+            .map(|(time, event)| (time, *event.get_body::<i32>(urids).unwrap()))
+            .collect::<Vec<(TimeStamp, i32)>>()
+    };
+    assert!(read_output(&instance, &mut urids).is_empty());
+    assert_eq!(1, instance.plugin().end_runs);
+
+    // Writing events.
+    {
+        let mut port: AtomOutputPort<Sequence> = AtomOutputPort::new();
+        port.connect_port(instance.atom_input(0).unwrap());
+        let mut frame = unsafe { port.write_atom_body(&TimeUnit::Frames, &mut urids) }.unwrap();
+        frame
+            .push_event::<i32>(TimeStamp::Frames(3), &17, &mut urids)
+            .unwrap();
+        frame
+            .push_event::<i32>(TimeStamp::Frames(10), &25, &mut urids)
+            .unwrap();
+    }
+    instance.run(64);
+    assert_eq!(
+        vec![(TimeStamp::Frames(3), 17), (TimeStamp::Frames(10), 25)],
+        read_output(&instance, &mut urids)
+    );
+    assert_eq!(42, instance.plugin().sum);
+
+    // The inputs are cleared after every cycle.
+    instance.run(64);
+    assert!(read_output(&instance, &mut urids).is_empty());
+    assert_eq!(42, instance.plugin().sum);
+    assert_eq!(
+        vec![(EntryType::Error, "end of run\n".to_owned()); 3],
+        host.take_log()
+    );
+
+    // Wrong buffer types.
+    assert_eq!(None, instance.control(0));
+    assert!(!instance.set_control(1, 0.0));
+    assert!(instance.audio(0).is_none());
+    instance.disconnect(1);
+    assert!(instance.atom(1).is_none());
+    drop(instance);
+
+    // Missing features are reported.
+    assert!(host
+        .instantiate::<Demanding>(b"http://example.org/Demanding\0")
+        .is_none());
+    assert_eq!(
+        vec![(
+            EntryType::Error,
+            "Missing required feature http://example.org/Unsupported\n".to_owned()
+        )],
+        host.take_log()
+    );
+}
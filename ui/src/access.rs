@@ -0,0 +1,128 @@
+//! Direct access to the plugin instance and its extension data.
+//!
+//! Usually, a UI only communicates with the plugin through the host. If the UI is shipped in the
+//! same binary as the plugin, the host may also pass the instance access and the data access
+//! features, which let the UI use the plugin directly. These features break the separation of
+//! UI and plugin, which is why they should only be used by UIs that can't work otherwise, and
+//! why both have to be declared as optional features of the UI.
+//!
+//! Using the plugin directly is inherently unsafe: The host calls the plugin's `run` function
+//! and the UI's functions from different threads. Therefore, the UI must only use the plugin in
+//! ways that are sound while `run` is executed concurrently, for example by only touching
+//! atomic values or by calling extension interfaces whose threading rules allow it.
+use crate::resources::SharedResources;
+use crate::uris;
+use core::uris::Uri;
+use std::ffi::CStr;
+use std::os::raw::*;
+use std::sync::Arc;
+
+/// The instance access feature.
+///
+/// The data of this feature is the handle of the plugin instance the UI belongs to.
+pub struct InstanceAccess {
+    handle: core::Handle,
+}
+
+impl InstanceAccess {
+    /// Try to find the instance access feature in the features.
+    ///
+    /// `None` is returned if the host doesn't provide it or if the handle is null.
+    pub fn try_from_features(features: &core::FeaturesList) -> Option<Self> {
+        let uri = uris::INSTANCE_ACCESS_URI.as_cstr();
+        let handle = core::Feature::get_feature_raw(features, uri)?;
+        if handle.is_null() {
+            None
+        } else {
+            Some(Self { handle })
+        }
+    }
+
+    /// Return the raw handle of the plugin instance.
+    pub fn handle(&self) -> core::Handle {
+        self.handle
+    }
+
+    /// Return a reference to the plugin.
+    ///
+    /// # Safety
+    ///
+    /// The plugin has to be created by `lv2rs-core` with the type `P`, which is only the case if
+    /// the UI is in the same binary as the plugin. Also, the plugin is used by the audio thread at
+    /// the same time, so the UI may only access the parts of it that are synchronized, like
+    /// atomic values.
+    pub unsafe fn instance<P: core::Plugin>(&self) -> &P {
+        core::get_instance::<P>(self.handle)
+    }
+
+    /// Return a mutable reference to the plugin.
+    ///
+    /// # Safety
+    ///
+    /// The requirements of [`instance`](#method.instance) apply. In addition, the UI must make
+    /// sure that the plugin is not used by any other thread while the reference is alive, which
+    /// is usually only the case if the host is known to not run the plugin.
+    pub unsafe fn instance_mut<P: core::Plugin>(&mut self) -> &mut P {
+        core::get_instance::<P>(self.handle)
+    }
+
+    /// Return a new reference to the resources the plugin shares with its UI.
+    ///
+    /// The returned pointer keeps the resources alive, even after the host has cleaned up the
+    /// plugin.
+    ///
+    /// # Safety
+    ///
+    /// The plugin has to be created by `lv2rs-core` with the type `P`, just like in
+    /// [`instance`](#method.instance).
+    pub unsafe fn shared_resources<P: SharedResources>(&self) -> Arc<P::Resources> {
+        self.instance::<P>().shared_resources()
+    }
+}
+
+/// The data access feature.
+///
+/// It contains the `extension_data` function of the plugin the UI belongs to, which gives the UI
+/// access to the plugin's extension interfaces, like the state interface. Since the interfaces
+/// take the instance handle as their first argument, this feature is usually used together with
+/// the [`InstanceAccess`](struct.InstanceAccess.html).
+#[repr(C)]
+pub struct DataAccess {
+    /// The `extension_data` function of the plugin.
+    pub data_access: unsafe extern "C" fn(uri: *const c_char) -> *const c_void,
+}
+
+unsafe impl core::FeatureData for DataAccess {
+    const URI: Uri = uris::DATA_ACCESS_URI;
+}
+
+impl DataAccess {
+    /// Try to find the data access feature in the features.
+    pub fn try_from_features(features: &core::FeaturesList) -> Option<&'static mut Self> {
+        unsafe { core::Features::new(features).get::<Self>() }
+    }
+
+    /// Return the raw extension data of the plugin for the given URI.
+    ///
+    /// `None` is returned if the plugin doesn't provide data for this URI.
+    pub fn extension_data_raw(&self, uri: &CStr) -> Option<*const c_void> {
+        let data = unsafe { (self.data_access)(uri.as_ptr()) };
+        if data.is_null() {
+            None
+        } else {
+            Some(data)
+        }
+    }
+
+    /// Return the extension data of the plugin for the given URI, cast to `T`.
+    ///
+    /// # Safety
+    ///
+    /// This function can not check that the data has type `T`. It's your responsibility to pass
+    /// the type the extension's specification defines, for example the
+    /// `StateInterface` of `lv2rs-state` for the state interface URI. The threading rules of the
+    /// extension still apply when the UI calls the functions of the interface.
+    pub unsafe fn extension_data<T>(&self, uri: &CStr) -> Option<&'static T> {
+        (self.extension_data_raw(uri)? as *const T).as_ref()
+    }
+}
@@ -0,0 +1,191 @@
+//! The UI descriptor and the trait to implement UIs.
+use crate::features::Widget;
+use crate::port::*;
+use crate::uris::ProtocolURIDs;
+use std::ffi::CStr;
+use std::os::raw::*;
+
+/// Type to describe pointers to UI instances.
+pub type UIHandle = *mut c_void;
+
+/// UI Descriptor.
+///
+/// This structure provides the functions necessary to instantiate and use a UI. It is returned by
+/// the `lv2ui_descriptor` function, which is created by the
+/// [`lv2ui_descriptors`](macro.lv2ui_descriptors.html) macro.
+#[repr(C)]
+pub struct UIDescriptor {
+    /// The URI of the UI, which has to be unique.
+    pub uri: *const c_char,
+    /// Create a new UI instance for the plugin with the given URI.
+    ///
+    /// The UI has to store its widget in `widget` and write to the plugin's ports with the write
+    /// function and the controller handle.
+    pub instantiate: unsafe extern "C" fn(
+        descriptor: *const UIDescriptor,
+        plugin_uri: *const c_char,
+        bundle_path: *const c_char,
+        write_function: WriteFunction,
+        controller: ControllerHandle,
+        widget: *mut Widget,
+        features: *const *const core::Feature,
+    ) -> UIHandle,
+    /// Destroy a UI instance.
+    pub cleanup: unsafe extern "C" fn(ui: UIHandle),
+    /// Tell the UI that something happened at a port of the plugin.
+    ///
+    /// The format is `0` for the float protocol or the URID of another protocol.
+    pub port_event: Option<
+        unsafe extern "C" fn(
+            ui: UIHandle,
+            port_index: u32,
+            buffer_size: u32,
+            format: u32,
+            buffer: *const c_void,
+        ),
+    >,
+    /// Return the data of an extension, like the idle interface.
+    pub extension_data: Option<unsafe extern "C" fn(uri: *const c_char) -> *const c_void>,
+}
+
+impl UIDescriptor {
+    /// Create the descriptor of a UI type.
+    ///
+    /// The URI has to be a null-terminated byte string. This function is used by the
+    /// [`lv2ui_descriptors`](macro.lv2ui_descriptors.html) macro.
+    pub const fn new<U: PluginUI>(uri: &'static [u8]) -> Self {
+        assert!(
+            !uri.is_empty() && uri[uri.len() - 1] == 0,
+            "UI URIs have to be null-terminated"
+        );
+        Self {
+            uri: uri.as_ptr() as *const c_char,
+            instantiate: instantiate::<U>,
+            cleanup: cleanup::<U>,
+            port_event: Some(port_event::<U>),
+            extension_data: Some(extension_data::<U>),
+        }
+    }
+}
+
+/// LV2 plugin UI trait.
+///
+/// This trait is the UI counterpart of [`Plugin`](../lv2rs_core/trait.Plugin.html): Implement it
+/// and export the UI with the [`lv2ui_descriptors`](macro.lv2ui_descriptors.html) macro. All
+/// methods are called from the UI thread of the host.
+pub trait PluginUI: Sized + 'static {
+    /// Create a new instance of the UI.
+    ///
+    /// The UI receives the URI of the plugin it controls, the path of its bundle, a
+    /// [`Controller`](struct.Controller.html) to write to the plugin's ports and the features
+    /// supported by the host. It should create its widget here, using the
+    /// [parent widget](fn.parent.html) if the host provides one. Return `None` if the UI can't be
+    /// created.
+    fn instantiate(
+        descriptor: &UIDescriptor,
+        plugin_uri: &CStr,
+        bundle_path: &CStr,
+        controller: Controller,
+        features: Option<&core::FeaturesList>,
+    ) -> Option<Self>;
+
+    /// Return the widget of the UI.
+    ///
+    /// This method is called right after `instantiate` and the widget is given to the host. UIs
+    /// that only use the show interface may return a null pointer.
+    fn widget(&mut self) -> Widget;
+
+    /// Handle an update of a port of the plugin.
+    ///
+    /// The host calls this method for new values of control ports and for all events of ports the
+    /// UI has [subscribed](struct.PortSubscribe.html) to.
+    fn port_event(&mut self, _port_index: u32, _event: PortEvent) {}
+
+    /// Return the data of an extension.
+    ///
+    /// Use the [`extension_data`](../lv2rs_core/macro.extension_data.html) macro to implement
+    /// this function, for example to return an [`IdleInterface`](struct.IdleInterface.html).
+    fn extension_data(_uri: &CStr) -> Option<&'static dyn core::ExtensionData> {
+        None
+    }
+}
+
+/// A UI instance, as it is referenced by the handle.
+struct UIInstance<U: PluginUI> {
+    ui: U,
+    urids: Option<ProtocolURIDs>,
+}
+
+/// Return the UI of an instance handle.
+///
+/// Extension interfaces, like the idle interface, receive the instance handle that was created by
+/// the descriptor and use this function to retrieve the UI.
+///
+/// # Safety
+///
+/// The handle has to be created by the descriptor of the same UI type and must not be cleaned up
+/// yet. The returned reference must not outlive the UI instance and there must not be another
+/// reference to the UI at the same time.
+pub unsafe fn get_ui<'a, U: PluginUI>(handle: UIHandle) -> &'a mut U {
+    &mut (handle as *mut UIInstance<U>).as_mut().unwrap().ui
+}
+
+unsafe extern "C" fn instantiate<U: PluginUI>(
+    descriptor: *const UIDescriptor,
+    plugin_uri: *const c_char,
+    bundle_path: *const c_char,
+    write_function: WriteFunction,
+    controller: ControllerHandle,
+    widget: *mut Widget,
+    features: *const *const core::Feature,
+) -> UIHandle {
+    let descriptor = match descriptor.as_ref() {
+        Some(descriptor) => descriptor,
+        None => return std::ptr::null_mut(),
+    };
+    if plugin_uri.is_null() || bundle_path.is_null() {
+        return std::ptr::null_mut();
+    }
+    let plugin_uri = CStr::from_ptr(plugin_uri);
+    let bundle_path = CStr::from_ptr(bundle_path);
+
+    let features = core::Features::from_raw(features).map(|features| features.as_list());
+    let urids = features
+        .and_then(urid::CachedMap::try_from_features)
+        .map(|mut map| ProtocolURIDs::new(&mut map));
+    let controller = Controller::new(write_function, controller, urids);
+
+    let ui = match U::instantiate(descriptor, plugin_uri, bundle_path, controller, features) {
+        Some(ui) => ui,
+        None => return std::ptr::null_mut(),
+    };
+    let mut instance = Box::new(UIInstance { ui, urids });
+    if let Some(widget) = widget.as_mut() {
+        *widget = instance.ui.widget();
+    }
+    Box::into_raw(instance) as UIHandle
+}
+
+unsafe extern "C" fn cleanup<U: PluginUI>(ui: UIHandle) {
+    drop(Box::from_raw(ui as *mut UIInstance<U>));
+}
+
+unsafe extern "C" fn port_event<U: PluginUI>(
+    ui: UIHandle,
+    port_index: u32,
+    buffer_size: u32,
+    format: u32,
+    buffer: *const c_void,
+) {
+    let instance = (ui as *mut UIInstance<U>).as_mut().unwrap();
+    if let Some(event) = PortEvent::from_raw(buffer_size, format, buffer, instance.urids.as_ref()) {
+        instance.ui.port_event(port_index, event);
+    }
+}
+
+unsafe extern "C" fn extension_data<U: PluginUI>(uri: *const c_char) -> *const c_void {
+    match U::extension_data(CStr::from_ptr(uri)) {
+        Some(data) => data as *const dyn core::ExtensionData as *const c_void,
+        None => std::ptr::null(),
+    }
+}
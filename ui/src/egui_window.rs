@@ -0,0 +1,388 @@
+//! UIs drawn with [`egui`](https://docs.rs/egui/).
+//!
+//! This module is only available with the `egui-window` feature. An
+//! [`EguiWindow`](struct.EguiWindow.html) opens an OpenGL window with the
+//! [event loop adapter](../event_loop/index.html), embeds it in the parent widget of the host and
+//! draws an [`EguiUI`](trait.EguiUI.html) in it. The port events of the host are converted to
+//! the [messages](../trait.PortMessage.html) of the UI and delivered before the next frame is
+//! drawn. Everything a UI type has to do is to forward the calls of the host to the window:
+//!
+//! ```no_run
+//! extern crate lv2rs_core as core;
+//! extern crate lv2rs_ui as ui;
+//!
+//! use std::ffi::CStr;
+//! use ui::egui_window::*;
+//! use ui::*;
+//!
+//! const GAIN_PORT: u32 = 0;
+//!
+//! struct GainMessage(f32);
+//!
+//! impl PortMessage for GainMessage {
+//!     fn from_port_event(port_index: u32, event: PortEvent) -> Option<Self> {
+//!         match (port_index, event) {
+//!             (GAIN_PORT, PortEvent::Float(gain)) => Some(GainMessage(gain)),
+//!             _ => None,
+//!         }
+//!     }
+//! }
+//!
+//! struct Gain(f32);
+//!
+//! impl EguiUI for Gain {
+//!     type Message = GainMessage;
+//!
+//!     fn message(&mut self, message: GainMessage) {
+//!         self.0 = message.0;
+//!     }
+//!
+//!     fn update(&mut self, context: &egui::Context, controller: &Controller) {
+//!         egui::CentralPanel::default().show(context, |ui| {
+//!             if ui.add(egui::Slider::new(&mut self.0, 0.0..=1.0)).changed() {
+//!                 controller.write_float(GAIN_PORT, self.0);
+//!             }
+//!         });
+//!     }
+//! }
+//!
+//! struct GainUI {
+//!     window: EguiWindow<Gain>,
+//! }
+//!
+//! impl PluginUI for GainUI {
+//!     fn instantiate(
+//!         _descriptor: &UIDescriptor,
+//!         _plugin_uri: &CStr,
+//!         _bundle_path: &CStr,
+//!         controller: Controller,
+//!         features: Option<&core::FeaturesList>,
+//!     ) -> Option<Self> {
+//!         let parent = features.and_then(parent);
+//!         let attributes = winit::window::Window::default_attributes()
+//!             .with_inner_size(winit::dpi::LogicalSize::new(300.0, 100.0));
+//!         let window = EguiWindow::new(Gain(0.0), controller, attributes, parent).ok()?;
+//!         Some(Self { window })
+//!     }
+//!
+//!     fn widget(&mut self) -> Widget {
+//!         self.window.widget()
+//!     }
+//!
+//!     fn port_event(&mut self, port_index: u32, event: PortEvent) {
+//!         self.window.port_event(port_index, event);
+//!     }
+//!
+//!     fn extension_data(uri: &CStr) -> Option<&'static dyn core::ExtensionData> {
+//!         core::extension_data!(uri, [(uris::IDLE_INTERFACE_URI, &IDLE_INTERFACE)])
+//!     }
+//! }
+//!
+//! impl Idle for GainUI {
+//!     fn idle(&mut self) -> Result<(), UIClosedError> {
+//!         self.window.idle()
+//!     }
+//! }
+//!
+//! static IDLE_INTERFACE: IdleInterface = IdleInterface::new::<GainUI>();
+//!
+//! ui::lv2ui_descriptors!(ui, [GainUI: b"http://example.org/Gain#ui\0"]);
+//!
+//! # fn main() {}
+//! ```
+use crate::event_loop::{embed, widget_of, EventLoopAdapter};
+use crate::features::Widget;
+use crate::interface::{ShowError, UIClosedError};
+use crate::messages::*;
+use crate::port::{Controller, PortEvent};
+use egui_glow::EguiGlow;
+use glutin::config::ConfigTemplateBuilder;
+use glutin::context::{
+    ContextApi, ContextAttributesBuilder, NotCurrentGlContext, PossiblyCurrentContext,
+};
+use glutin::display::{GetGlDisplay, GlDisplay};
+use glutin::surface::{GlSurface, Surface, SurfaceAttributesBuilder, WindowSurface};
+use glutin_winit::{ApiPreference, DisplayBuilder};
+use std::ffi::CString;
+use std::num::NonZeroU32;
+use std::sync::Arc;
+use winit::dpi::PhysicalSize;
+use winit::event::WindowEvent;
+use winit::event_loop::ActiveEventLoop;
+use winit::raw_window_handle::HasWindowHandle;
+use winit::window::{Window, WindowAttributes};
+
+pub use egui;
+pub use winit;
+
+/// A UI that is drawn with egui.
+pub trait EguiUI {
+    /// The type of the messages that are created from port events.
+    type Message: PortMessage;
+
+    /// Handle a message that was created from a port event.
+    ///
+    /// The messages are delivered in the order of the port events, before the next frame is
+    /// drawn.
+    fn message(&mut self, message: Self::Message);
+
+    /// Draw a frame of the UI.
+    ///
+    /// The controller can be used to write the values the user changed to the ports of the
+    /// plugin.
+    fn update(&mut self, context: &egui::Context, controller: &Controller);
+}
+
+/// An OpenGL window that draws an egui UI.
+///
+/// The window is drawn and its events are processed by [`idle`](#method.idle), which has to be
+/// called by the UI's [`Idle`](../trait.Idle.html) implementation. Frames are only drawn if the
+/// window received input, a message was delivered or egui requested it, for example for an
+/// animation.
+pub struct EguiWindow<U: EguiUI> {
+    ui: U,
+    controller: Controller,
+    messages: MessageQueue<U::Message>,
+    painter: EguiGlow,
+    gl_window: GlWindow,
+    adapter: EventLoopAdapter,
+    repaint: bool,
+}
+
+impl<U: EguiUI> EguiWindow<U> {
+    /// Open a window for a UI.
+    ///
+    /// If a parent widget is given, usually the one returned by
+    /// [`parent`](../fn.parent.html), the window is embedded in it. Otherwise, the window is a
+    /// top-level window that is shown according to the visibility in the attributes.
+    ///
+    /// This method fails if the event loop isn't available or if no OpenGL context could be
+    /// created.
+    pub fn new(
+        ui: U,
+        controller: Controller,
+        attributes: WindowAttributes,
+        parent: Option<Widget>,
+    ) -> Result<Self, ()> {
+        let attributes = match parent {
+            Some(parent) => embed(attributes, parent),
+            None => attributes,
+        };
+        let mut adapter = EventLoopAdapter::new();
+        let (gl_window, painter) =
+            adapter.with_event_loop(|event_loop| GlWindow::new(event_loop, attributes))??;
+        adapter.register(gl_window.window.id());
+
+        Ok(Self {
+            ui,
+            controller,
+            messages: MessageQueue::new(),
+            painter,
+            gl_window,
+            adapter,
+            repaint: true,
+        })
+    }
+
+    /// Return the UI that is drawn in the window.
+    pub fn ui(&self) -> &U {
+        &self.ui
+    }
+
+    /// Return the UI that is drawn in the window, mutably.
+    pub fn ui_mut(&mut self) -> &mut U {
+        &mut self.ui
+    }
+
+    /// Return the window.
+    pub fn window(&self) -> &Window {
+        &self.gl_window.window
+    }
+
+    /// Return the widget of the window, which is returned to the host by
+    /// [`PluginUI::widget`](../trait.PluginUI.html#tymethod.widget).
+    pub fn widget(&self) -> Widget {
+        widget_of(&self.gl_window.window)
+    }
+
+    /// Convert a port event to a message of the UI and queue it for the next frame.
+    pub fn port_event(&mut self, port_index: u32, event: PortEvent) {
+        self.messages.push_event(port_index, event);
+    }
+
+    /// Show the window, if it isn't embedded.
+    pub fn show(&mut self) -> Result<(), ShowError> {
+        self.gl_window.window.set_visible(true);
+        self.repaint = true;
+        Ok(())
+    }
+
+    /// Hide the window, if it isn't embedded.
+    pub fn hide(&mut self) -> Result<(), ShowError> {
+        self.gl_window.window.set_visible(false);
+        Ok(())
+    }
+
+    /// Process the events of the window, deliver the queued messages and draw a new frame if
+    /// necessary.
+    ///
+    /// `Err(UIClosedError)` is returned if the user closed the window, the event loop exited or
+    /// the frame couldn't be drawn, which is what [`Idle::idle`](../trait.Idle.html#tymethod.idle)
+    /// expects.
+    pub fn idle(&mut self) -> Result<(), UIClosedError> {
+        for (_, event) in self.adapter.pump().map_err(|_| UIClosedError)? {
+            match event {
+                WindowEvent::CloseRequested | WindowEvent::Destroyed => return Err(UIClosedError),
+                WindowEvent::RedrawRequested => self.repaint = true,
+                WindowEvent::Resized(size) => {
+                    self.gl_window.resize(size);
+                    self.repaint = true;
+                }
+                _ => (),
+            }
+            if self
+                .painter
+                .on_window_event(&self.gl_window.window, &event)
+                .repaint
+            {
+                self.repaint = true;
+            }
+        }
+
+        for message in self.messages.drain() {
+            self.ui.message(message);
+            self.repaint = true;
+        }
+
+        if self.repaint || self.painter.egui_ctx.has_requested_repaint() {
+            self.paint().map_err(|_| UIClosedError)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Draw a frame of the UI.
+    fn paint(&mut self) -> Result<(), ()> {
+        self.repaint = false;
+        self.gl_window.make_current()?;
+
+        let ui = &mut self.ui;
+        let controller = &self.controller;
+        self.painter.run(&self.gl_window.window, |context| {
+            ui.update(context, controller)
+        });
+
+        let size: [u32; 2] = self.gl_window.window.inner_size().into();
+        let background = self.painter.egui_ctx.style().visuals.panel_fill;
+        self.painter
+            .painter
+            .clear(size, egui::Rgba::from(background).to_array());
+        self.painter.paint(&self.gl_window.window);
+        self.gl_window.swap_buffers()
+    }
+}
+
+impl<U: EguiUI> Drop for EguiWindow<U> {
+    fn drop(&mut self) {
+        // The textures and buffers of the painter belong to the context of this window.
+        if self.gl_window.make_current().is_ok() {
+            self.painter.destroy();
+        }
+    }
+}
+
+/// A window with an OpenGL context.
+struct GlWindow {
+    surface: Surface<WindowSurface>,
+    context: PossiblyCurrentContext,
+    window: Window,
+}
+
+impl GlWindow {
+    /// Create a window with a context and the painter that draws to it.
+    fn new(
+        event_loop: &ActiveEventLoop,
+        attributes: WindowAttributes,
+    ) -> Result<(Self, EguiGlow), ()> {
+        let template = ConfigTemplateBuilder::new()
+            .prefer_hardware_accelerated(None)
+            .with_depth_size(0)
+            .with_stencil_size(0)
+            .with_transparency(false);
+        let (window, config) = DisplayBuilder::new()
+            .with_preference(ApiPreference::FallbackEgl)
+            .with_window_attributes(Some(attributes.clone()))
+            .build(event_loop, template, |mut configs| configs.next().unwrap())
+            .map_err(|_| ())?;
+        let display = config.display();
+        let window = match window {
+            Some(window) => window,
+            None => {
+                glutin_winit::finalize_window(event_loop, attributes, &config).map_err(|_| ())?
+            }
+        };
+        let handle = window.window_handle().map_err(|_| ())?.as_raw();
+
+        // Try a desktop OpenGL context first and fall back to OpenGL ES.
+        let context = unsafe {
+            display
+                .create_context(
+                    &config,
+                    &ContextAttributesBuilder::new().build(Some(handle)),
+                )
+                .or_else(|_| {
+                    let attributes = ContextAttributesBuilder::new()
+                        .with_context_api(ContextApi::Gles(None))
+                        .build(Some(handle));
+                    display.create_context(&config, &attributes)
+                })
+        }
+        .map_err(|_| ())?;
+
+        let (width, height) = non_zero_size(window.inner_size());
+        let attributes =
+            SurfaceAttributesBuilder::<WindowSurface>::new().build(handle, width, height);
+        let surface =
+            unsafe { display.create_window_surface(&config, &attributes) }.map_err(|_| ())?;
+        let context = context.make_current(&surface).map_err(|_| ())?;
+
+        let gl = unsafe {
+            egui_glow::glow::Context::from_loader_function(|symbol| match CString::new(symbol) {
+                Ok(symbol) => display.get_proc_address(&symbol),
+                Err(_) => std::ptr::null(),
+            })
+        };
+        let painter = EguiGlow::new(event_loop, Arc::new(gl), None, None, true);
+
+        Ok((
+            Self {
+                surface,
+                context,
+                window,
+            },
+            painter,
+        ))
+    }
+
+    fn make_current(&self) -> Result<(), ()> {
+        use glutin::context::PossiblyCurrentGlContext;
+        self.context.make_current(&self.surface).map_err(|_| ())
+    }
+
+    fn resize(&self, size: PhysicalSize<u32>) {
+        let (width, height) = non_zero_size(size);
+        self.surface.resize(&self.context, width, height);
+    }
+
+    fn swap_buffers(&self) -> Result<(), ()> {
+        self.surface.swap_buffers(&self.context).map_err(|_| ())
+    }
+}
+
+/// Return the size of a window, with a minimum of one pixel in each dimension.
+fn non_zero_size(size: PhysicalSize<u32>) -> (NonZeroU32, NonZeroU32) {
+    (
+        NonZeroU32::new(size.width).unwrap_or(NonZeroU32::MIN),
+        NonZeroU32::new(size.height).unwrap_or(NonZeroU32::MIN),
+    )
+}
@@ -0,0 +1,293 @@
+//! A [`winit`](https://docs.rs/winit/) event loop driven by the idle interface.
+//!
+//! This module is only available with the `event-loop` feature. A UI can't run an event loop of
+//! its own, since the host keeps control of the thread and calls
+//! [`Idle::idle`](../trait.Idle.html#tymethod.idle) regularly instead. An
+//! [`EventLoopAdapter`](struct.EventLoopAdapter.html) pumps the pending events of a winit event
+//! loop on every call and returns the events of the UI's windows.
+//!
+//! winit only allows one event loop per process, which is why all adapters of a thread share the
+//! same event loop. It is created on first use and the events of the windows of other UIs are
+//! kept until these UIs pump the event loop themselves. Since the host calls all UIs from the
+//! same thread, this lets several instances of a UI run side by side. On Linux, the event loop
+//! always uses X11, since hosts provide X11 windows as parents, even if they run on Wayland.
+//!
+//! Windows are embedded in the parent widget of the host with
+//! [`embed`](fn.embed.html) and the widget of a window, which the UI returns from
+//! [`PluginUI::widget`](../trait.PluginUI.html#tymethod.widget), is retrieved with
+//! [`widget_of`](fn.widget_of.html).
+use crate::features::Widget;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::time::Duration;
+use winit::application::ApplicationHandler;
+use winit::event::WindowEvent;
+use winit::event_loop::{ActiveEventLoop, EventLoop, EventLoopBuilder};
+use winit::platform::pump_events::{EventLoopExtPumpEvents, PumpStatus};
+use winit::raw_window_handle::{HasWindowHandle, RawWindowHandle};
+use winit::window::{Window, WindowAttributes, WindowId};
+
+pub use winit;
+
+thread_local! {
+    static EVENT_LOOP: RefCell<Option<EventLoop<()>>> = const { RefCell::new(None) };
+    static PENDING_EVENTS: RefCell<HashMap<WindowId, Vec<WindowEvent>>> =
+        RefCell::new(HashMap::new());
+}
+
+/// Access to the event loop of the thread for the windows of a UI.
+///
+/// The events of a window are only returned by [`pump`](#method.pump) if the window was created
+/// with [`create_window`](#method.create_window) or [`register`](#method.register)ed. They are
+/// discarded when the adapter is dropped.
+pub struct EventLoopAdapter {
+    windows: Vec<WindowId>,
+}
+
+impl EventLoopAdapter {
+    /// Create a new adapter without any windows.
+    pub fn new() -> Self {
+        Self {
+            windows: Vec::new(),
+        }
+    }
+
+    /// Run a function with the active event loop, for example to create a window.
+    ///
+    /// Many functions of winit and the toolkits that build upon it require an active event loop,
+    /// which only exists while the event loop is pumped. Therefore, the event loop is pumped once
+    /// and the function is called at the end of the iteration.
+    ///
+    /// This method fails if the event loop can't be created, for example because another thread
+    /// already created one, or if it is called from within a function passed to this method.
+    pub fn with_event_loop<R, F>(&mut self, function: F) -> Result<R, ()>
+    where
+        F: FnOnce(&ActiveEventLoop) -> R,
+    {
+        pump_event_loop(Some(function))?.ok_or(())
+    }
+
+    /// Create a window and register it with the adapter.
+    pub fn create_window(&mut self, attributes: WindowAttributes) -> Result<Window, ()> {
+        let window = self
+            .with_event_loop(|event_loop| event_loop.create_window(attributes))?
+            .map_err(|_| ())?;
+        self.register(window.id());
+        Ok(window)
+    }
+
+    /// Register a window that was created with the active event loop.
+    pub fn register(&mut self, window: WindowId) {
+        if !self.windows.contains(&window) {
+            self.windows.push(window);
+        }
+    }
+
+    /// Process the pending events of the event loop without waiting for new ones.
+    ///
+    /// The events of the windows of this adapter are returned in the order they were received.
+    /// This method fails if the event loop exited or if it is called from within
+    /// [`with_event_loop`](#method.with_event_loop).
+    pub fn pump(&mut self) -> Result<Vec<(WindowId, WindowEvent)>, ()> {
+        pump_event_loop::<(), fn(&ActiveEventLoop)>(None)?;
+        PENDING_EVENTS.with(|pending| {
+            let mut pending = pending.borrow_mut();
+            let mut events = Vec::new();
+            for window in self.windows.iter() {
+                if let Some(window_events) = pending.remove(window) {
+                    events.extend(window_events.into_iter().map(|event| (*window, event)));
+                }
+            }
+            Ok(events)
+        })
+    }
+}
+
+impl Default for EventLoopAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for EventLoopAdapter {
+    fn drop(&mut self) {
+        let _ = PENDING_EVENTS.try_with(|pending| {
+            let mut pending = pending.borrow_mut();
+            for window in self.windows.iter() {
+                pending.remove(window);
+            }
+        });
+    }
+}
+
+/// Return the window attributes to embed a window in a parent widget.
+///
+/// On Linux and other Unix systems, the parent is an X11 window ID and the window is embedded
+/// with the XEmbed protocol. On Windows, the parent is a `HWND` and on macOS, it's an `NSView`
+/// pointer. The attributes are returned unchanged if the parent is null.
+pub fn embed(attributes: WindowAttributes, parent: Widget) -> WindowAttributes {
+    if parent.is_null() {
+        attributes
+    } else {
+        embed_in_parent(attributes, parent)
+    }
+}
+
+/// Return the widget of a window, as it is expected by the host.
+///
+/// This is the X11 window ID on Linux and other Unix systems, the `HWND` on Windows and the
+/// `NSView` pointer on macOS. A null pointer is returned if the platform isn't supported.
+pub fn widget_of(window: &Window) -> Widget {
+    match window.window_handle().map(|handle| handle.as_raw()) {
+        Ok(RawWindowHandle::Xlib(handle)) => handle.window as usize as Widget,
+        Ok(RawWindowHandle::Xcb(handle)) => handle.window.get() as usize as Widget,
+        Ok(RawWindowHandle::Win32(handle)) => handle.hwnd.get() as Widget,
+        Ok(RawWindowHandle::AppKit(handle)) => handle.ns_view.as_ptr(),
+        _ => std::ptr::null_mut(),
+    }
+}
+
+#[cfg(all(
+    unix,
+    not(any(
+        target_os = "android",
+        target_os = "emscripten",
+        target_os = "ios",
+        target_os = "macos"
+    ))
+))]
+fn embed_in_parent(attributes: WindowAttributes, parent: Widget) -> WindowAttributes {
+    use winit::platform::x11::{WindowAttributesExtX11, XWindow};
+    attributes.with_embed_parent_window(parent as usize as XWindow)
+}
+
+#[cfg(windows)]
+fn embed_in_parent(attributes: WindowAttributes, parent: Widget) -> WindowAttributes {
+    use std::num::NonZeroIsize;
+    use winit::raw_window_handle::Win32WindowHandle;
+    match NonZeroIsize::new(parent as isize) {
+        Some(hwnd) => unsafe {
+            attributes.with_parent_window(Some(Win32WindowHandle::new(hwnd).into()))
+        },
+        None => attributes,
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn embed_in_parent(attributes: WindowAttributes, parent: Widget) -> WindowAttributes {
+    use std::ptr::NonNull;
+    use winit::raw_window_handle::AppKitWindowHandle;
+    match NonNull::new(parent) {
+        Some(ns_view) => unsafe {
+            attributes.with_parent_window(Some(AppKitWindowHandle::new(ns_view).into()))
+        },
+        None => attributes,
+    }
+}
+
+#[cfg(not(any(
+    all(
+        unix,
+        not(any(
+            target_os = "android",
+            target_os = "emscripten",
+            target_os = "ios",
+            target_os = "macos"
+        ))
+    ),
+    windows,
+    target_os = "macos"
+)))]
+fn embed_in_parent(attributes: WindowAttributes, _parent: Widget) -> WindowAttributes {
+    attributes
+}
+
+#[cfg(all(
+    unix,
+    not(any(
+        target_os = "android",
+        target_os = "emscripten",
+        target_os = "ios",
+        target_os = "macos"
+    ))
+))]
+fn configure(builder: &mut EventLoopBuilder<()>) {
+    use winit::platform::x11::EventLoopBuilderExtX11;
+    builder.with_x11().with_any_thread(true);
+}
+
+#[cfg(windows)]
+fn configure(builder: &mut EventLoopBuilder<()>) {
+    use winit::platform::windows::EventLoopBuilderExtWindows;
+    builder.with_any_thread(true);
+}
+
+#[cfg(not(all(
+    any(unix, windows),
+    not(any(
+        target_os = "android",
+        target_os = "emscripten",
+        target_os = "ios",
+        target_os = "macos"
+    ))
+)))]
+fn configure(_builder: &mut EventLoopBuilder<()>) {}
+
+/// Pump the event loop of the thread once and call the function at the end of the iteration.
+///
+/// The event loop is created if it doesn't exist yet and the window events are added to the
+/// pending events.
+fn pump_event_loop<R, F>(function: Option<F>) -> Result<Option<R>, ()>
+where
+    F: FnOnce(&ActiveEventLoop) -> R,
+{
+    EVENT_LOOP.with(|event_loop| {
+        let mut event_loop = event_loop.try_borrow_mut().map_err(|_| ())?;
+        if event_loop.is_none() {
+            let mut builder = EventLoop::builder();
+            configure(&mut builder);
+            *event_loop = Some(builder.build().map_err(|_| ())?);
+        }
+        let event_loop = event_loop.as_mut().ok_or(())?;
+
+        let mut dispatcher = Dispatcher {
+            function,
+            result: None,
+        };
+        match event_loop.pump_app_events(Some(Duration::ZERO), &mut dispatcher) {
+            PumpStatus::Continue => Ok(dispatcher.result),
+            PumpStatus::Exit(_) => Err(()),
+        }
+    })
+}
+
+/// The application handler that collects the window events.
+struct Dispatcher<R, F: FnOnce(&ActiveEventLoop) -> R> {
+    function: Option<F>,
+    result: Option<R>,
+}
+
+impl<R, F: FnOnce(&ActiveEventLoop) -> R> ApplicationHandler for Dispatcher<R, F> {
+    fn resumed(&mut self, _event_loop: &ActiveEventLoop) {}
+
+    fn window_event(
+        &mut self,
+        _event_loop: &ActiveEventLoop,
+        window: WindowId,
+        event: WindowEvent,
+    ) {
+        PENDING_EVENTS.with(|pending| {
+            pending
+                .borrow_mut()
+                .entry(window)
+                .or_insert_with(Vec::new)
+                .push(event)
+        });
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        if let Some(function) = self.function.take() {
+            self.result = Some(function(event_loop));
+        }
+    }
+}
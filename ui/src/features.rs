@@ -0,0 +1,236 @@
+//! Features the host provides to UIs.
+use crate::uris;
+use core::uris::Uri;
+use std::ffi::CStr;
+use std::fmt;
+use std::os::raw::*;
+
+/// Type to describe pointers to widgets of the UI toolkit.
+pub type Widget = *mut c_void;
+
+/// The index the port map returns for unknown symbols.
+pub const INVALID_PORT_INDEX: u32 = u32::MAX;
+
+/// Return the parent widget the UI should embed itself in.
+///
+/// The data of the parent feature is the widget itself, for example an X11 window ID. `None` is
+/// returned if the host doesn't provide one.
+pub fn parent(features: &core::FeaturesList) -> Option<Widget> {
+    let uri = uris::PARENT_URI.as_cstr();
+    core::Feature::get_feature_raw(features, uri)
+}
+
+/// The port map feature, which maps port symbols to indices.
+#[repr(C)]
+pub struct PortMap {
+    /// Pointer to a host-specific handle.
+    pub handle: *mut c_void,
+    /// Return the index of the port with the given symbol.
+    pub port_index: unsafe extern "C" fn(handle: *mut c_void, symbol: *const c_char) -> u32,
+}
+
+unsafe impl core::FeatureData for PortMap {
+    const URI: Uri = uris::PORT_MAP_URI;
+}
+
+impl PortMap {
+    /// Try to find the port map in the features.
+    pub fn try_from_features(features: &core::FeaturesList) -> Option<&'static mut Self> {
+        unsafe { core::Features::new(features).get::<Self>() }
+    }
+
+    /// Return the index of the port with the given symbol, or `None` if there is no such port.
+    pub fn port_index(&self, symbol: &CStr) -> Option<u32> {
+        match unsafe { (self.port_index)(self.handle, symbol.as_ptr()) } {
+            INVALID_PORT_INDEX => None,
+            index => Some(index),
+        }
+    }
+}
+
+/// The port subscription feature.
+///
+/// By default, hosts only send events of control ports to the UI. With this feature, the UI can
+/// request events of other ports with a specific protocol, like the atom transfers of a
+/// sequence port, and cancel these requests again.
+#[repr(C)]
+pub struct PortSubscribe {
+    /// Pointer to a host-specific handle.
+    pub handle: *mut c_void,
+    /// Subscribe to the events of a port.
+    pub subscribe: unsafe extern "C" fn(
+        handle: *mut c_void,
+        port_index: u32,
+        port_protocol: u32,
+        features: *const *const core::Feature,
+    ) -> u32,
+    /// Cancel a subscription.
+    pub unsubscribe: unsafe extern "C" fn(
+        handle: *mut c_void,
+        port_index: u32,
+        port_protocol: u32,
+        features: *const *const core::Feature,
+    ) -> u32,
+}
+
+unsafe impl core::FeatureData for PortSubscribe {
+    const URI: Uri = uris::PORT_SUBSCRIBE_URI;
+}
+
+impl PortSubscribe {
+    /// Try to find the port subscription feature in the features.
+    pub fn try_from_features(features: &core::FeaturesList) -> Option<&'static mut Self> {
+        unsafe { core::Features::new(features).get::<Self>() }
+    }
+
+    /// Subscribe to the events of a port with the given protocol.
+    ///
+    /// The protocol is `0` for the float protocol or the URID of another protocol, like the
+    /// [event transfer protocol](struct.ProtocolURIDs.html#structfield.event_transfer). An error
+    /// is returned if the host rejects the subscription.
+    pub fn subscribe(&self, port_index: u32, protocol: u32) -> Result<(), SubscriptionError> {
+        let features: [*const core::Feature; 1] = [std::ptr::null()];
+        let status =
+            unsafe { (self.subscribe)(self.handle, port_index, protocol, features.as_ptr()) };
+        if status == 0 {
+            Ok(())
+        } else {
+            Err(SubscriptionError)
+        }
+    }
+
+    /// Cancel the subscription to the events of a port with the given protocol.
+    ///
+    /// An error is returned if the host rejects the cancellation.
+    pub fn unsubscribe(&self, port_index: u32, protocol: u32) -> Result<(), SubscriptionError> {
+        let features: [*const core::Feature; 1] = [std::ptr::null()];
+        let status =
+            unsafe { (self.unsubscribe)(self.handle, port_index, protocol, features.as_ptr()) };
+        if status == 0 {
+            Ok(())
+        } else {
+            Err(SubscriptionError)
+        }
+    }
+}
+
+/// The error that is returned if the host rejects a subscription or its cancellation.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SubscriptionError;
+
+impl fmt::Display for SubscriptionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "The host rejected the port subscription")
+    }
+}
+
+/// The resize feature, which lets the UI request a new size of its widget.
+#[repr(C)]
+pub struct Resize {
+    /// Pointer to a host-specific handle.
+    pub handle: *mut c_void,
+    /// Request a new size.
+    pub ui_resize: unsafe extern "C" fn(handle: *mut c_void, width: c_int, height: c_int) -> c_int,
+}
+
+unsafe impl core::FeatureData for Resize {
+    const URI: Uri = uris::RESIZE_URI;
+}
+
+impl Resize {
+    /// Try to find the resize feature in the features.
+    pub fn try_from_features(features: &core::FeaturesList) -> Option<&'static mut Self> {
+        unsafe { core::Features::new(features).get::<Self>() }
+    }
+
+    /// Request a new size of the widget.
+    ///
+    /// An error is returned if the host rejects the size.
+    pub fn resize(&self, width: i32, height: i32) -> Result<(), ResizeError> {
+        if unsafe { (self.ui_resize)(self.handle, width, height) } == 0 {
+            Ok(())
+        } else {
+            Err(ResizeError)
+        }
+    }
+}
+
+/// The error that is returned if the host rejects a new size of the widget.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ResizeError;
+
+impl fmt::Display for ResizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "The host rejected the size of the widget")
+    }
+}
+
+/// The touch feature, which tells the host when the user grabs and releases a control.
+///
+/// Hosts use these gestures to write automation correctly: While a control is grabbed, the
+/// values written by the UI overwrite the automation of the port and once it's released, the
+/// host goes back to playing the automation.
+#[repr(C)]
+pub struct Touch {
+    /// Pointer to a host-specific handle.
+    pub handle: *mut c_void,
+    /// Notify the host that a control was grabbed or released.
+    pub touch: unsafe extern "C" fn(handle: *mut c_void, port_index: u32, grabbed: bool),
+}
+
+unsafe impl core::FeatureData for Touch {
+    const URI: Uri = uris::TOUCH_URI;
+}
+
+impl Touch {
+    /// Try to find the touch feature in the features.
+    pub fn try_from_features(features: &core::FeaturesList) -> Option<&'static mut Self> {
+        unsafe { core::Features::new(features).get::<Self>() }
+    }
+
+    /// Notify the host that the user grabbed the control of a port.
+    pub fn grab(&self, port_index: u32) {
+        unsafe { (self.touch)(self.handle, port_index, true) }
+    }
+
+    /// Notify the host that the user released the control of a port.
+    pub fn release(&self, port_index: u32) {
+        unsafe { (self.touch)(self.handle, port_index, false) }
+    }
+
+    /// Grab the control of a port until the returned gesture is dropped.
+    ///
+    /// The gesture doesn't borrow the feature, which means that it can be stored in the UI while
+    /// the user drags the control.
+    pub fn gesture(&self, port_index: u32) -> Gesture {
+        self.grab(port_index);
+        Gesture {
+            handle: self.handle,
+            touch: self.touch,
+            port_index,
+        }
+    }
+}
+
+/// A control that is grabbed by the user.
+///
+/// It's created by [`Touch::gesture`](struct.Touch.html#method.gesture) and releases the control
+/// when it's dropped, which makes sure that no release is missed.
+pub struct Gesture {
+    handle: *mut c_void,
+    touch: unsafe extern "C" fn(handle: *mut c_void, port_index: u32, grabbed: bool),
+    port_index: u32,
+}
+
+impl Gesture {
+    /// Return the index of the grabbed port.
+    pub fn port_index(&self) -> u32 {
+        self.port_index
+    }
+}
+
+impl Drop for Gesture {
+    fn drop(&mut self) {
+        unsafe { (self.touch)(self.handle, self.port_index, false) }
+    }
+}
@@ -0,0 +1,112 @@
+//! The idle and show interfaces and the traits to implement them.
+use crate::descriptor::*;
+use std::fmt;
+use std::os::raw::*;
+
+/// A UI that needs to be called regularly, for example to process the events of its toolkit.
+pub trait Idle: PluginUI {
+    /// Run a single iteration of the UI's event loop.
+    ///
+    /// Return `Err(UIClosedError)` if the UI was closed by the user. The host will then stop
+    /// calling this method and clean up the UI.
+    fn idle(&mut self) -> Result<(), UIClosedError>;
+}
+
+/// The error that is returned by [`Idle::idle`](trait.Idle.html#tymethod.idle) if the UI was
+/// closed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct UIClosedError;
+
+impl fmt::Display for UIClosedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "The UI was closed")
+    }
+}
+
+/// The raw idle interface.
+///
+/// An instance of this struct, created with [`new`](#method.new), has to be returned by the
+/// UI's `extension_data` function for the
+/// [idle interface URI](uris/constant.IDLE_INTERFACE_URI.html).
+#[repr(C)]
+pub struct IdleInterface {
+    /// Run a single iteration of the UI's event loop.
+    ///
+    /// Returns a non-zero value if the UI was closed.
+    pub idle: unsafe extern "C" fn(ui: UIHandle) -> c_int,
+}
+
+impl core::ExtensionData for IdleInterface {}
+
+impl IdleInterface {
+    /// Create the idle interface for a UI type.
+    pub const fn new<U: Idle>() -> Self {
+        Self { idle: idle::<U> }
+    }
+}
+
+/// A UI that opens a window of its own instead of providing a widget.
+pub trait Show: Idle {
+    /// Show the window of the UI.
+    fn show(&mut self) -> Result<(), ShowError>;
+
+    /// Hide the window of the UI.
+    fn hide(&mut self) -> Result<(), ShowError>;
+}
+
+/// The error that is returned by the [`Show`](trait.Show.html) methods if the window of the UI
+/// couldn't be shown or hidden.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ShowError;
+
+impl fmt::Display for ShowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "The window of the UI couldn't be shown or hidden")
+    }
+}
+
+/// The raw show interface.
+///
+/// An instance of this struct, created with [`new`](#method.new), has to be returned by the
+/// UI's `extension_data` function for the
+/// [show interface URI](uris/constant.SHOW_INTERFACE_URI.html). Since the host drives the window
+/// with the idle interface, the UI has to provide it too.
+#[repr(C)]
+pub struct ShowInterface {
+    /// Show the window of the UI.
+    pub show: unsafe extern "C" fn(ui: UIHandle) -> c_int,
+    /// Hide the window of the UI.
+    pub hide: unsafe extern "C" fn(ui: UIHandle) -> c_int,
+}
+
+impl core::ExtensionData for ShowInterface {}
+
+impl ShowInterface {
+    /// Create the show interface for a UI type.
+    pub const fn new<U: Show>() -> Self {
+        Self {
+            show: show::<U>,
+            hide: hide::<U>,
+        }
+    }
+}
+
+/// Convert a result to a status code of the UI interfaces.
+fn into_status<E>(result: Result<(), E>) -> c_int {
+    match result {
+        Ok(()) => 0,
+        Err(_) => 1,
+    }
+}
+
+unsafe extern "C" fn idle<U: Idle>(ui: UIHandle) -> c_int {
+    into_status(get_ui::<U>(ui).idle())
+}
+
+unsafe extern "C" fn show<U: Show>(ui: UIHandle) -> c_int {
+    into_status(get_ui::<U>(ui).show())
+}
+
+unsafe extern "C" fn hide<U: Show>(ui: UIHandle) -> c_int {
+    into_status(get_ui::<U>(ui).hide())
+}
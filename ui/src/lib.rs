@@ -0,0 +1,149 @@
+//! Plugin user interfaces.
+//!
+//! This crate implements the [LV2 UI extension](http://lv2plug.in/ns/extensions/ui/ui.html):
+//! UIs live in a separate library and communicate with the plugin only through the host. The
+//! host sends the values of the plugin's ports to the UI's
+//! [`port_event`](trait.PluginUI.html#method.port_event) method and the UI writes new values
+//! with its [`Controller`](struct.Controller.html). By default, only control ports are
+//! delivered; Other ports, like atom sequences, have to be subscribed to with the
+//! [`PortSubscribe`](struct.PortSubscribe.html) feature. When the user drags a control, the UI
+//! should report the gesture with the [`Touch`](struct.Touch.html) feature, which lets the host
+//! record automation correctly. UIs that are shipped in the same binary as their plugin may also
+//! use the plugin directly with the [`InstanceAccess`](struct.InstanceAccess.html) and
+//! [`DataAccess`](struct.DataAccess.html) features, if the host provides them, and share
+//! resources with the plugin that implements [`SharedResources`](trait.SharedResources.html).
+//!
+//! A UI implements the [`PluginUI`](trait.PluginUI.html) trait and is exported with the
+//! [`lv2ui_descriptors`](macro.lv2ui_descriptors.html) macro. UIs that need to run an event loop
+//! or open a window of their own provide the [`IdleInterface`](struct.IdleInterface.html) and
+//! the [`ShowInterface`](struct.ShowInterface.html) in their `extension_data` function:
+//!
+//!     extern crate lv2rs_core as core;
+//!     extern crate lv2rs_ui as ui;
+//!
+//!     use std::ffi::CStr;
+//!     use ui::*;
+//!
+//!     const GAIN_PORT: u32 = 0;
+//!
+//!     struct AmpUI {
+//!         controller: Controller,
+//!         gain: f32,
+//!         visible: bool,
+//!     }
+//!
+//!     impl PluginUI for AmpUI {
+//!         fn instantiate(
+//!             _descriptor: &UIDescriptor,
+//!             _plugin_uri: &CStr,
+//!             _bundle_path: &CStr,
+//!             controller: Controller,
+//!             _features: Option<&core::FeaturesList>,
+//!         ) -> Option<Self> {
+//!             Some(Self {
+//!                 controller,
+//!                 gain: 0.0,
+//!                 visible: false,
+//!             })
+//!         }
+//!
+//!         fn widget(&mut self) -> Widget {
+//!             // This UI opens its own window.
+//!             std::ptr::null_mut()
+//!         }
+//!
+//!         fn port_event(&mut self, port_index: u32, event: PortEvent) {
+//!             if let (GAIN_PORT, PortEvent::Float(gain)) = (port_index, event) {
+//!                 self.gain = gain;
+//!             }
+//!         }
+//!
+//!         fn extension_data(uri: &CStr) -> Option<&'static dyn core::ExtensionData> {
+//!             core::extension_data!(uri, [
+//!                 (uris::IDLE_INTERFACE_URI, &IDLE_INTERFACE),
+//!                 (uris::SHOW_INTERFACE_URI, &SHOW_INTERFACE),
+//!             ])
+//!         }
+//!     }
+//!
+//!     impl Idle for AmpUI {
+//!         fn idle(&mut self) -> Result<(), UIClosedError> {
+//!             // Process the events of the window and write the gain if the user changed it.
+//!             if self.gain > 1.0 {
+//!                 self.controller.write_float(GAIN_PORT, 1.0);
+//!             }
+//!             Ok(())
+//!         }
+//!     }
+//!
+//!     impl Show for AmpUI {
+//!         fn show(&mut self) -> Result<(), ShowError> {
+//!             self.visible = true;
+//!             Ok(())
+//!         }
+//!
+//!         fn hide(&mut self) -> Result<(), ShowError> {
+//!             self.visible = false;
+//!             Ok(())
+//!         }
+//!     }
+//!
+//!     static IDLE_INTERFACE: IdleInterface = IdleInterface::new::<AmpUI>();
+//!     static SHOW_INTERFACE: ShowInterface = ShowInterface::new::<AmpUI>();
+//!
+//!     ui::lv2ui_descriptors!(ui, [AmpUI: b"http://example.org/Amp#ui\0"]);
+//!
+//!     # fn main() {}
+//!
+//! Toolkits usually handle events in their own event loop, after the host delivered them. The
+//! [`PortMessage`](trait.PortMessage.html) trait converts port events to owned messages, which
+//! are kept in a [`MessageQueue`](struct.MessageQueue.html) until the UI handles them. With the
+//! `event-loop` feature, the [`event_loop`](event_loop/index.html) module runs a
+//! [`winit`](https://docs.rs/winit/) event loop from the idle interface and embeds windows in the
+//! parent widget of the host. The `egui-window` feature builds upon it and draws UIs with
+//! [`egui`](https://docs.rs/egui/), see the [`egui_window`](egui_window/index.html) module.
+extern crate lv2rs_atom as atom;
+extern crate lv2rs_core as core;
+extern crate lv2rs_urid as urid;
+
+mod access;
+mod descriptor;
+#[cfg(feature = "egui-window")]
+pub mod egui_window;
+#[cfg(feature = "event-loop")]
+pub mod event_loop;
+mod features;
+mod interface;
+mod messages;
+mod port;
+mod resources;
+pub mod uris;
+
+pub use access::*;
+pub use descriptor::*;
+pub use features::*;
+pub use interface::*;
+pub use messages::*;
+pub use port::*;
+pub use resources::*;
+pub use uris::ProtocolURIDs;
+
+/// Create the UI export function.
+///
+/// This macro works like the `lv2_descriptors` macro of `lv2rs-core`: It takes the namespace of
+/// this crate and a list of types that implement [`PluginUI`](trait.PluginUI.html), together with
+/// their null-terminated URIs. The exported `lv2ui_descriptor` function returns the descriptors of
+/// the UIs in the order of the list.
+#[macro_export]
+macro_rules! lv2ui_descriptors {
+    ($c:ident, [$($s:ty : $u:expr),* $(,)*]) => {
+        #[no_mangle]
+        pub unsafe extern "C" fn lv2ui_descriptor(index: u32) -> *const $c::UIDescriptor {
+            const DESCRIPTORS: &[$c::UIDescriptor] = &[$($c::UIDescriptor::new::<$s>($u)),*];
+            match DESCRIPTORS.get(index as usize) {
+                Some(descriptor) => descriptor,
+                None => std::ptr::null(),
+            }
+        }
+    };
+}
@@ -0,0 +1,122 @@
+//! Typed messages from port events.
+use crate::port::PortEvent;
+use std::collections::VecDeque;
+
+/// A message of a UI that is created from port events.
+///
+/// Port events may borrow data from the host, which is only valid during the call of
+/// [`PluginUI::port_event`](trait.PluginUI.html#method.port_event). Toolkits usually handle
+/// their events later, in their own event loop, which is why the events are converted to owned
+/// messages first.
+pub trait PortMessage: Sized {
+    /// Convert a port event to a message.
+    ///
+    /// `None` is returned if the UI isn't interested in the event.
+    fn from_port_event(port_index: u32, event: PortEvent) -> Option<Self>;
+}
+
+/// A queue of messages that were created from port events.
+///
+/// The queue is filled by [`PluginUI::port_event`](trait.PluginUI.html#method.port_event) and
+/// drained by the event loop of the UI, which is usually run by [`Idle::idle`](trait.Idle.html).
+pub struct MessageQueue<M: PortMessage> {
+    messages: VecDeque<M>,
+}
+
+impl<M: PortMessage> MessageQueue<M> {
+    /// Create a new, empty queue.
+    pub fn new() -> Self {
+        Self {
+            messages: VecDeque::new(),
+        }
+    }
+
+    /// Convert a port event and add the message to the queue.
+    ///
+    /// `true` is returned if a message was added.
+    pub fn push_event(&mut self, port_index: u32, event: PortEvent) -> bool {
+        match M::from_port_event(port_index, event) {
+            Some(message) => {
+                self.messages.push_back(message);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Add a message to the queue.
+    pub fn push(&mut self, message: M) {
+        self.messages.push_back(message);
+    }
+
+    /// Remove the oldest message from the queue.
+    pub fn pop(&mut self) -> Option<M> {
+        self.messages.pop_front()
+    }
+
+    /// Remove all messages from the queue, oldest first.
+    pub fn drain(&mut self) -> impl Iterator<Item = M> + '_ {
+        self.messages.drain(..)
+    }
+
+    /// Return the number of messages in the queue.
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Return `true` if the queue contains no messages.
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+}
+
+impl<M: PortMessage> Default for MessageQueue<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::messages::*;
+    use crate::port::PeakData;
+
+    #[derive(Debug, PartialEq)]
+    enum Message {
+        Gain(f32),
+        Peak(f32),
+    }
+
+    impl PortMessage for Message {
+        fn from_port_event(port_index: u32, event: PortEvent) -> Option<Self> {
+            match (port_index, event) {
+                (0, PortEvent::Float(gain)) => Some(Message::Gain(gain)),
+                (1, PortEvent::Peak(data)) => Some(Message::Peak(data.peak)),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn test_message_queue() {
+        let mut queue: MessageQueue<Message> = MessageQueue::new();
+        assert!(queue.is_empty());
+
+        assert!(queue.push_event(0, PortEvent::Float(0.5)));
+        assert!(!queue.push_event(1, PortEvent::Float(0.5)));
+        let peak = PeakData {
+            period_start: 0,
+            period_size: 64,
+            peak: 0.25,
+        };
+        assert!(queue.push_event(1, PortEvent::Peak(peak)));
+        queue.push(Message::Gain(1.0));
+        assert_eq!(3, queue.len());
+
+        assert_eq!(Some(Message::Gain(0.5)), queue.pop());
+        let messages: Vec<Message> = queue.drain().collect();
+        assert_eq!(vec![Message::Peak(0.25), Message::Gain(1.0)], messages);
+        assert!(queue.is_empty());
+        assert_eq!(None, queue.pop());
+    }
+}
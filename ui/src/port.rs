@@ -0,0 +1,211 @@
+//! Communication with the ports of the plugin.
+use crate::uris::ProtocolURIDs;
+use atom::Atom;
+use std::fmt;
+use std::os::raw::*;
+use urid::URID;
+
+/// Type to describe pointers to the host's controller handles.
+pub type ControllerHandle = *mut c_void;
+
+/// The host's function to write data to a port of the plugin.
+///
+/// The protocol is `0` for the float protocol or the URID of another protocol.
+pub type WriteFunction = unsafe extern "C" fn(
+    controller: ControllerHandle,
+    port_index: u32,
+    buffer_size: u32,
+    port_protocol: u32,
+    buffer: *const c_void,
+);
+
+/// Peak data of an audio port, as it is delivered with the peak protocol.
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PeakData {
+    /// The start of the measurement period, in frames since an arbitrary point in time.
+    pub period_start: u32,
+    /// The size of the measurement period, in frames.
+    pub period_size: u32,
+    /// The peak value of the measurement period.
+    pub peak: f32,
+}
+
+/// An update of a port, delivered to [`PluginUI::port_event`](trait.PluginUI.html#method.port_event).
+#[derive(Clone, Copy)]
+pub enum PortEvent<'a> {
+    /// A new value of a control port, sent with the float protocol.
+    Float(f32),
+    /// An atom sent with the atom transfer protocol, usually the value of an atom port.
+    Atom(&'a Atom),
+    /// An event sent with the event transfer protocol, usually from a sequence port.
+    Event(&'a Atom),
+    /// Peak data of an audio port.
+    Peak(PeakData),
+    /// Data sent with a protocol this crate doesn't know.
+    Other { protocol: URID, data: &'a [u8] },
+}
+
+impl<'a> PortEvent<'a> {
+    /// Interpret the raw parameters of a port event.
+    ///
+    /// `None` is returned if the buffer is null or too small for the protocol. Atoms are only
+    /// recognized if the protocol URIDs are known, otherwise they are delivered as `Other`.
+    ///
+    /// # Safety
+    ///
+    /// Unless it's null, the buffer has to point to `buffer_size` readable bytes that live for
+    /// `'a`. If the protocol is an atom protocol, these bytes have to contain a valid atom.
+    pub unsafe fn from_raw(
+        buffer_size: u32,
+        format: u32,
+        buffer: *const c_void,
+        urids: Option<&ProtocolURIDs>,
+    ) -> Option<PortEvent<'a>> {
+        use std::mem::size_of;
+
+        if buffer.is_null() {
+            return None;
+        }
+        let data = std::slice::from_raw_parts(buffer as *const u8, buffer_size as usize);
+
+        if format == 0 {
+            if data.len() != size_of::<f32>() {
+                return None;
+            }
+            return Some(PortEvent::Float(std::ptr::read_unaligned(
+                buffer as *const f32,
+            )));
+        }
+
+        let urids = match urids {
+            Some(urids) => urids,
+            None => {
+                return Some(PortEvent::Other {
+                    protocol: format,
+                    data,
+                })
+            }
+        };
+        if format == urids.atom_transfer || format == urids.event_transfer {
+            if data.len() < size_of::<Atom>() {
+                return None;
+            }
+            let atom = (buffer as *const Atom).as_ref()?;
+            if data.len() < size_of::<Atom>() + atom.size() {
+                return None;
+            }
+            if format == urids.atom_transfer {
+                Some(PortEvent::Atom(atom))
+            } else {
+                Some(PortEvent::Event(atom))
+            }
+        } else if format == urids.peak_protocol {
+            if data.len() != size_of::<PeakData>() {
+                return None;
+            }
+            Some(PortEvent::Peak(std::ptr::read_unaligned(
+                buffer as *const PeakData,
+            )))
+        } else {
+            Some(PortEvent::Other {
+                protocol: format,
+                data,
+            })
+        }
+    }
+}
+
+/// Handle to write to the ports of the plugin.
+///
+/// The controller is passed to [`PluginUI::instantiate`](trait.PluginUI.html#tymethod.instantiate)
+/// and may be used as long as the UI exists. Atoms can only be written if the host supports the
+/// URID map feature, since the protocols are identified by URIDs.
+#[derive(Clone, Copy)]
+pub struct Controller {
+    write_function: WriteFunction,
+    handle: ControllerHandle,
+    urids: Option<ProtocolURIDs>,
+}
+
+impl Controller {
+    /// Create a new controller from the parameters of the host.
+    pub fn new(
+        write_function: WriteFunction,
+        handle: ControllerHandle,
+        urids: Option<ProtocolURIDs>,
+    ) -> Self {
+        Self {
+            write_function,
+            handle,
+            urids,
+        }
+    }
+
+    /// Return the URIDs of the port protocols, if the host supports the URID map feature.
+    pub fn urids(&self) -> Option<&ProtocolURIDs> {
+        self.urids.as_ref()
+    }
+
+    /// Write a new value to a control port.
+    pub fn write_float(&self, port_index: u32, value: f32) {
+        unsafe { self.write_raw(port_index, 0, &value.to_ne_bytes()) }
+    }
+
+    /// Write an atom to an atom port, using the atom transfer protocol.
+    ///
+    /// This method fails if the URIDs of the protocols are unknown.
+    pub fn write_atom(&self, port_index: u32, atom: &Atom) -> Result<(), UnknownProtocolError> {
+        let protocol = self.urids.ok_or(UnknownProtocolError)?.atom_transfer;
+        unsafe { self.write_raw(port_index, protocol, atom_bytes(atom)) };
+        Ok(())
+    }
+
+    /// Write an event to a sequence port, using the event transfer protocol.
+    ///
+    /// This method fails if the URIDs of the protocols are unknown.
+    pub fn write_event(&self, port_index: u32, atom: &Atom) -> Result<(), UnknownProtocolError> {
+        let protocol = self.urids.ok_or(UnknownProtocolError)?.event_transfer;
+        unsafe { self.write_raw(port_index, protocol, atom_bytes(atom)) };
+        Ok(())
+    }
+
+    /// Write raw data to a port.
+    ///
+    /// # Safety
+    ///
+    /// The host interprets the data according to the protocol, which has to be `0` for the float
+    /// protocol or the URID of a protocol supported by the port, and the data has to be valid for
+    /// this protocol.
+    pub unsafe fn write_raw(&self, port_index: u32, protocol: u32, data: &[u8]) {
+        (self.write_function)(
+            self.handle,
+            port_index,
+            data.len() as u32,
+            protocol,
+            data.as_ptr() as *const c_void,
+        );
+    }
+}
+
+/// The error that is returned if an atom is written, but the URIDs of the protocols are unknown.
+///
+/// The URIDs are only known if the host supports the URID map feature.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct UnknownProtocolError;
+
+impl fmt::Display for UnknownProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "The URIDs of the port protocols are unknown")
+    }
+}
+
+/// Return the bytes of an atom, including the header.
+fn atom_bytes(atom: &Atom) -> &[u8] {
+    unsafe {
+        std::slice::from_raw_parts(
+            atom as *const Atom as *const u8,
+            std::mem::size_of::<Atom>() + atom.size(),
+        )
+    }
+}
@@ -0,0 +1,26 @@
+//! Resources that are shared by a plugin and its UI.
+//!
+//! Plugins often create large resources when they're instantiated, like sample banks or
+//! wavetables, which their UI wants to display too. Instead of loading them twice or storing them
+//! in global variables, the plugin can implement the [`SharedResources`](trait.SharedResources.html)
+//! trait and hand out reference-counted pointers to them. A UI in the same binary retrieves them
+//! with [`InstanceAccess::shared_resources`](struct.InstanceAccess.html#method.shared_resources).
+//!
+//! Since the resources are reference-counted, they stay alive as long as either the plugin or the
+//! UI holds them, regardless of the order in which the host cleans them up.
+use std::sync::Arc;
+
+/// A plugin that shares resources with its UI.
+///
+/// The resources are accessed concurrently by the audio thread and the UI thread, which is why
+/// they have to be `Send` and `Sync`. Resources that change after instantiation have to be
+/// synchronized, for example with atomic values.
+pub trait SharedResources: core::Plugin {
+    /// The type of the shared resources.
+    ///
+    /// Plugins with several resources usually collect them in a struct.
+    type Resources: Send + Sync + 'static;
+
+    /// Return a new reference to the shared resources.
+    fn shared_resources(&self) -> Arc<Self::Resources>;
+}
@@ -0,0 +1,26 @@
+//! URIs of the LV2 UI extension and the instance and data access features.
+use core::uris::Uri;
+
+pub const URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/ui\0");
+pub const DATA_ACCESS_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/data-access\0");
+pub const FLOAT_PROTOCOL_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/ui#floatProtocol\0");
+pub const IDLE_INTERFACE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/ui#idleInterface\0");
+pub const INSTANCE_ACCESS_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/instance-access\0");
+pub const PARENT_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/ui#parent\0");
+pub const PEAK_PROTOCOL_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/ui#peakProtocol\0");
+pub const PORT_MAP_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/ui#portMap\0");
+pub const PORT_SUBSCRIBE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/ui#portSubscribe\0");
+pub const RESIZE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/ui#resize\0");
+pub const SHOW_INTERFACE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/ui#showInterface\0");
+pub const TOUCH_URI: Uri = Uri::new(b"http://lv2plug.in/ns/extensions/ui#touch\0");
+
+urid::urid_cache! {
+    /// The URIDs of the port protocols.
+    ///
+    /// The float protocol has no URID, it is always denoted by the format `0`.
+    pub struct ProtocolURIDs {
+        pub atom_transfer: atom::uris::ATOM_TRANSFER_URI,
+        pub event_transfer: atom::uris::EVENT_TRANSFER_URI,
+        pub peak_protocol: PEAK_PROTOCOL_URI,
+    }
+}
@@ -0,0 +1,187 @@
+extern crate lv2rs_core as core;
+extern crate lv2rs_ui as ui;
+
+use core::uris::Uri;
+use std::ffi::CStr;
+use std::os::raw::*;
+use std::ptr::{null, null_mut};
+use std::sync::atomic::{AtomicU32, Ordering};
+use ui::*;
+
+const COUNTER_URI: Uri = Uri::new(b"http://example.org/Counter#counter\0");
+
+/// A plugin that counts the samples it has processed.
+struct Counter {
+    samples: AtomicU32,
+}
+
+impl core::Plugin for Counter {
+    fn instantiate(
+        _context: &core::InstantiationContext,
+        _features: Option<&core::FeaturesList>,
+    ) -> Result<Self, core::InstantiateError> {
+        Ok(Self {
+            samples: AtomicU32::new(0),
+        })
+    }
+
+    fn connect_port(&mut self, _port: u32, _data: *mut ()) {}
+
+    fn run(&mut self, context: &mut core::RunContext) {
+        self.samples
+            .fetch_add(context.n_samples(), Ordering::Relaxed);
+    }
+
+    fn extension_data(uri: &CStr) -> Option<&'static dyn core::ExtensionData> {
+        core::extension_data!(uri, [(COUNTER_URI, &COUNTER_INTERFACE)])
+    }
+}
+
+/// A custom interface to read the counter.
+#[repr(C)]
+struct CounterInterface {
+    samples: unsafe extern "C" fn(instance: core::Handle) -> u32,
+}
+
+impl core::ExtensionData for CounterInterface {}
+
+unsafe extern "C" fn samples(instance: core::Handle) -> u32 {
+    core::get_instance::<Counter>(instance)
+        .samples
+        .load(Ordering::Relaxed)
+}
+
+static COUNTER_INTERFACE: CounterInterface = CounterInterface { samples };
+
+core::lv2_main!(core, Counter, b"http://example.org/Counter\0");
+
+/// A UI that reads the counter directly.
+struct CounterUI {
+    instance: Option<InstanceAccess>,
+    data: Option<&'static mut DataAccess>,
+}
+
+impl PluginUI for CounterUI {
+    fn instantiate(
+        _descriptor: &UIDescriptor,
+        _plugin_uri: &CStr,
+        _bundle_path: &CStr,
+        _controller: Controller,
+        features: Option<&core::FeaturesList>,
+    ) -> Option<Self> {
+        let features = features?;
+        Some(Self {
+            instance: InstanceAccess::try_from_features(features),
+            data: DataAccess::try_from_features(features),
+        })
+    }
+
+    fn widget(&mut self) -> Widget {
+        null_mut()
+    }
+
+    fn port_event(&mut self, _port_index: u32, _event: PortEvent) {}
+}
+
+impl CounterUI {
+    fn samples(&self) -> Option<u32> {
+        let instance = self.instance.as_ref()?;
+        Some(
+            unsafe { instance.instance::<Counter>() }
+                .samples
+                .load(Ordering::Relaxed),
+        )
+    }
+
+    fn samples_via_interface(&self) -> Option<u32> {
+        let instance = self.instance.as_ref()?;
+        let uri = COUNTER_URI.as_cstr();
+        let interface = unsafe { self.data.as_ref()?.extension_data::<CounterInterface>(uri) }?;
+        Some(unsafe { (interface.samples)(instance.handle()) })
+    }
+}
+
+ui::lv2ui_descriptors!(ui, [CounterUI: b"http://example.org/Counter#ui\0"]);
+
+unsafe extern "C" fn write(
+    _controller: ControllerHandle,
+    _port_index: u32,
+    _buffer_size: u32,
+    _port_protocol: u32,
+    _buffer: *const c_void,
+) {
+}
+
+/// Instantiate the UI with the given features.
+fn instantiate_ui(features: &[*const core::Feature]) -> UIHandle {
+    let descriptor = unsafe { lv2ui_descriptor(0).as_ref() }.unwrap();
+    let mut widget: Widget = null_mut();
+    let instance = unsafe {
+        (descriptor.instantiate)(
+            descriptor,
+            b"http://example.org/Counter\0".as_ptr() as *const c_char,
+            b"/\0".as_ptr() as *const c_char,
+            write,
+            null_mut(),
+            &mut widget,
+            features.as_ptr(),
+        )
+    };
+    assert!(!instance.is_null());
+    instance
+}
+
+#[test]
+fn test_access() {
+    let plugin_descriptor = unsafe { lv2_descriptor(0).as_ref() }.unwrap();
+    let plugin = unsafe {
+        (plugin_descriptor.instantiate)(
+            plugin_descriptor,
+            44100.0,
+            b"/\0".as_ptr() as *const c_char,
+            [null()].as_ptr(),
+        )
+    };
+    assert!(!plugin.is_null());
+    unsafe { (plugin_descriptor.run)(plugin, 64) };
+
+    let ui_descriptor = unsafe { lv2ui_descriptor(0).as_ref() }.unwrap();
+
+    // Without the features, the UI can't access the plugin.
+    let ui_instance = instantiate_ui(&[null()]);
+    {
+        let ui = unsafe { get_ui::<CounterUI>(ui_instance) };
+        assert_eq!(None, ui.samples());
+        assert_eq!(None, ui.samples_via_interface());
+    }
+    unsafe { (ui_descriptor.cleanup)(ui_instance) };
+
+    // With the features.
+    let instance_feature =
+        core::Feature::new(uris::INSTANCE_ACCESS_URI.as_ptr(), plugin as *mut c_void);
+    let mut data_access = DataAccess {
+        data_access: plugin_descriptor.extension_data,
+    };
+    let data_feature = core::Feature::new(
+        uris::DATA_ACCESS_URI.as_ptr(),
+        &mut data_access as *mut DataAccess as *mut c_void,
+    );
+    let ui_instance = instantiate_ui(&[&instance_feature, &data_feature, null()]);
+    {
+        let ui = unsafe { get_ui::<CounterUI>(ui_instance) };
+        assert_eq!(Some(64), ui.samples());
+        unsafe { (plugin_descriptor.run)(plugin, 32) };
+        assert_eq!(Some(96), ui.samples_via_interface());
+
+        let unknown = CStr::from_bytes_with_nul(b"http://example.org/Unknown\0").unwrap();
+        assert!(ui
+            .data
+            .as_ref()
+            .unwrap()
+            .extension_data_raw(unknown)
+            .is_none());
+    }
+    unsafe { (ui_descriptor.cleanup)(ui_instance) };
+
+    unsafe { (plugin_descriptor.cleanup)(plugin) };
+}
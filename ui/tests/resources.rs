@@ -0,0 +1,131 @@
+extern crate lv2rs_core as core;
+extern crate lv2rs_ui as ui;
+
+use std::ffi::CStr;
+use std::os::raw::*;
+use std::ptr::{null, null_mut};
+use std::sync::Arc;
+use ui::*;
+
+/// A wavetable that is computed when the plugin is instantiated.
+struct Wavetable {
+    samples: Vec<f32>,
+}
+
+/// A plugin that shares its wavetable with the UI.
+struct Oscillator {
+    wavetable: Arc<Wavetable>,
+}
+
+impl core::Plugin for Oscillator {
+    fn instantiate(
+        _context: &core::InstantiationContext,
+        _features: Option<&core::FeaturesList>,
+    ) -> Result<Self, core::InstantiateError> {
+        let samples = (0..4).map(|index| index as f32 / 4.0).collect();
+        Ok(Self {
+            wavetable: Arc::new(Wavetable { samples }),
+        })
+    }
+
+    fn connect_port(&mut self, _port: u32, _data: *mut ()) {}
+
+    fn run(&mut self, _context: &mut core::RunContext) {}
+}
+
+impl SharedResources for Oscillator {
+    type Resources = Wavetable;
+
+    fn shared_resources(&self) -> Arc<Wavetable> {
+        self.wavetable.clone()
+    }
+}
+
+core::lv2_main!(core, Oscillator, b"http://example.org/Oscillator\0");
+
+/// A UI that draws the wavetable of the plugin.
+struct OscillatorUI {
+    wavetable: Option<Arc<Wavetable>>,
+}
+
+impl PluginUI for OscillatorUI {
+    fn instantiate(
+        _descriptor: &UIDescriptor,
+        _plugin_uri: &CStr,
+        _bundle_path: &CStr,
+        _controller: Controller,
+        features: Option<&core::FeaturesList>,
+    ) -> Option<Self> {
+        let instance = InstanceAccess::try_from_features(features?);
+        Some(Self {
+            wavetable: instance
+                .map(|instance| unsafe { instance.shared_resources::<Oscillator>() }),
+        })
+    }
+
+    fn widget(&mut self) -> Widget {
+        null_mut()
+    }
+
+    fn port_event(&mut self, _port_index: u32, _event: PortEvent) {}
+}
+
+ui::lv2ui_descriptors!(ui, [OscillatorUI: b"http://example.org/Oscillator#ui\0"]);
+
+unsafe extern "C" fn write(
+    _controller: ControllerHandle,
+    _port_index: u32,
+    _buffer_size: u32,
+    _port_protocol: u32,
+    _buffer: *const c_void,
+) {
+}
+
+#[test]
+fn test_shared_resources() {
+    let plugin_descriptor = unsafe { lv2_descriptor(0).as_ref() }.unwrap();
+    let plugin = unsafe {
+        (plugin_descriptor.instantiate)(
+            plugin_descriptor,
+            44100.0,
+            b"/\0".as_ptr() as *const c_char,
+            [null()].as_ptr(),
+        )
+    };
+    assert!(!plugin.is_null());
+
+    let instance_feature =
+        core::Feature::new(uris::INSTANCE_ACCESS_URI.as_ptr(), plugin as *mut c_void);
+    let features: [*const core::Feature; 2] = [&instance_feature, null()];
+    let ui_descriptor = unsafe { lv2ui_descriptor(0).as_ref() }.unwrap();
+    let mut widget: Widget = null_mut();
+    let ui_instance = unsafe {
+        (ui_descriptor.instantiate)(
+            ui_descriptor,
+            b"http://example.org/Oscillator\0".as_ptr() as *const c_char,
+            b"/\0".as_ptr() as *const c_char,
+            write,
+            null_mut(),
+            &mut widget,
+            features.as_ptr(),
+        )
+    };
+    assert!(!ui_instance.is_null());
+
+    let wavetable = {
+        let ui = unsafe { get_ui::<OscillatorUI>(ui_instance) };
+        ui.wavetable.clone().unwrap()
+    };
+    assert_eq!(vec![0.0, 0.25, 0.5, 0.75], wavetable.samples);
+    {
+        let plugin = unsafe { core::get_instance::<Oscillator>(plugin) };
+        assert!(Arc::ptr_eq(&plugin.wavetable, &wavetable));
+    }
+    assert_eq!(3, Arc::strong_count(&wavetable));
+
+    // The wavetable outlives the plugin and the UI.
+    unsafe { (plugin_descriptor.cleanup)(plugin) };
+    assert_eq!(2, Arc::strong_count(&wavetable));
+    unsafe { (ui_descriptor.cleanup)(ui_instance) };
+    assert_eq!(1, Arc::strong_count(&wavetable));
+}
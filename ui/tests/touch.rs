@@ -0,0 +1,55 @@
+extern crate lv2rs_core as core;
+extern crate lv2rs_ui as ui;
+
+use std::os::raw::*;
+use std::ptr::null;
+use ui::*;
+
+type Gestures = Vec<(u32, bool)>;
+
+unsafe extern "C" fn touch(handle: *mut c_void, port_index: u32, grabbed: bool) {
+    (handle as *mut Gestures)
+        .as_mut()
+        .unwrap()
+        .push((port_index, grabbed));
+}
+
+#[test]
+fn test_touch() {
+    let mut gestures: Gestures = Vec::new();
+    let mut touch_data = Touch {
+        handle: &mut gestures as *mut Gestures as *mut c_void,
+        touch,
+    };
+    let touch_feature = core::Feature::new(
+        uris::TOUCH_URI.as_ptr(),
+        &mut touch_data as *mut Touch as *mut c_void,
+    );
+    let features: [*const core::Feature; 2] = [&touch_feature, null()];
+    let features = unsafe { core::Features::from_raw(features.as_ptr()) }.unwrap();
+    let empty: [*const core::Feature; 1] = [null()];
+    let empty = unsafe { core::Features::from_raw(empty.as_ptr()) }.unwrap();
+
+    assert!(Touch::try_from_features(empty.as_list()).is_none());
+    let touch = Touch::try_from_features(features.as_list()).unwrap();
+
+    touch.grab(2);
+    touch.release(2);
+    let gesture = touch.gesture(3);
+    assert_eq!(3, gesture.port_index());
+    touch.grab(4);
+    drop(gesture);
+    touch.release(4);
+
+    assert_eq!(
+        vec![
+            (2, true),
+            (2, false),
+            (3, true),
+            (4, true),
+            (3, false),
+            (4, false)
+        ],
+        gestures
+    );
+}
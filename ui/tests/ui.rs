@@ -0,0 +1,232 @@
+extern crate lv2rs_atom as atom;
+extern crate lv2rs_core as core;
+extern crate lv2rs_ui as ui;
+extern crate lv2rs_urid as urid;
+
+use std::ffi::CStr;
+use std::os::raw::*;
+use std::ptr::{null, null_mut};
+use ui::*;
+use urid::host::HostMap;
+
+const WIDGET: usize = 0x1234;
+
+/// A UI that echoes the events it receives.
+struct EchoUI {
+    controller: Controller,
+    events: Vec<(u32, String)>,
+    idle_calls: u32,
+    visible: bool,
+}
+
+impl PluginUI for EchoUI {
+    fn instantiate(
+        _descriptor: &UIDescriptor,
+        plugin_uri: &CStr,
+        _bundle_path: &CStr,
+        controller: Controller,
+        _features: Option<&core::FeaturesList>,
+    ) -> Option<Self> {
+        if plugin_uri.to_bytes() != b"http://example.org/Echo" {
+            return None;
+        }
+        Some(Self {
+            controller,
+            events: Vec::new(),
+            idle_calls: 0,
+            visible: false,
+        })
+    }
+
+    fn widget(&mut self) -> Widget {
+        WIDGET as Widget
+    }
+
+    fn port_event(&mut self, port_index: u32, event: PortEvent) {
+        match event {
+            PortEvent::Float(value) => {
+                self.events.push((port_index, format!("float {}", value)));
+                self.controller.write_float(port_index, value * 2.0);
+            }
+            PortEvent::Event(atom) => {
+                self.events
+                    .push((port_index, format!("event {}", atom.size())));
+                self.controller.write_event(port_index, atom).unwrap();
+            }
+            PortEvent::Atom(atom) => {
+                self.events
+                    .push((port_index, format!("atom {}", atom.size())));
+            }
+            PortEvent::Peak(peak) => {
+                self.events
+                    .push((port_index, format!("peak {}", peak.peak)));
+            }
+            PortEvent::Other { data, .. } => {
+                self.events
+                    .push((port_index, format!("other {}", data.len())));
+            }
+        }
+    }
+
+    fn extension_data(uri: &CStr) -> Option<&'static dyn core::ExtensionData> {
+        core::extension_data!(
+            uri,
+            [
+                (uris::IDLE_INTERFACE_URI, &IDLE_INTERFACE),
+                (uris::SHOW_INTERFACE_URI, &SHOW_INTERFACE),
+            ]
+        )
+    }
+}
+
+impl Idle for EchoUI {
+    fn idle(&mut self) -> Result<(), UIClosedError> {
+        self.idle_calls += 1;
+        if self.idle_calls < 2 {
+            Ok(())
+        } else {
+            Err(UIClosedError)
+        }
+    }
+}
+
+impl Show for EchoUI {
+    fn show(&mut self) -> Result<(), ShowError> {
+        self.visible = true;
+        Ok(())
+    }
+
+    fn hide(&mut self) -> Result<(), ShowError> {
+        self.visible = false;
+        Ok(())
+    }
+}
+
+static IDLE_INTERFACE: IdleInterface = IdleInterface::new::<EchoUI>();
+static SHOW_INTERFACE: ShowInterface = ShowInterface::new::<EchoUI>();
+
+ui::lv2ui_descriptors!(ui, [EchoUI: b"http://example.org/Echo#ui\0"]);
+
+/// The writes of the UI, as recorded by the test host.
+type Writes = Vec<(u32, u32, Vec<u8>)>;
+
+unsafe extern "C" fn write(
+    controller: ControllerHandle,
+    port_index: u32,
+    buffer_size: u32,
+    port_protocol: u32,
+    buffer: *const c_void,
+) {
+    let writes = (controller as *mut Writes).as_mut().unwrap();
+    let data = std::slice::from_raw_parts(buffer as *const u8, buffer_size as usize);
+    writes.push((port_index, port_protocol, data.to_owned()));
+}
+
+#[test]
+fn test_ui() {
+    let host_map = HostMap::new();
+    let urid_features = host_map.features();
+    let [map_feature, unmap_feature] = &*urid_features;
+    let features: [*const core::Feature; 3] = [map_feature, unmap_feature, null()];
+    let event_transfer = host_map.map(atom::uris::EVENT_TRANSFER_URI.as_cstr());
+    let int_type = host_map.map(atom::uris::INT_TYPE_URI.as_cstr());
+
+    let descriptor = unsafe { lv2ui_descriptor(0).as_ref() }.unwrap();
+    assert_eq!(
+        b"http://example.org/Echo#ui",
+        unsafe { CStr::from_ptr(descriptor.uri) }.to_bytes()
+    );
+    assert!(unsafe { lv2ui_descriptor(1) }.is_null());
+
+    // Instantiating the UI.
+    let mut writes: Writes = Vec::new();
+    let mut widget: Widget = null_mut();
+    let instance = unsafe {
+        (descriptor.instantiate)(
+            descriptor,
+            b"http://example.org/Echo\0".as_ptr() as *const c_char,
+            b"/\0".as_ptr() as *const c_char,
+            write,
+            &mut writes as *mut Writes as ControllerHandle,
+            &mut widget,
+            features.as_ptr(),
+        )
+    };
+    assert!(!instance.is_null());
+    assert_eq!(WIDGET as Widget, widget);
+
+    // Sending port events.
+    let port_event = descriptor.port_event.unwrap();
+    let value = 0.5f32;
+    let int_atom: [u32; 3] = [4, int_type, 42];
+    unsafe {
+        port_event(instance, 0, 4, 0, &value as *const f32 as *const c_void);
+        port_event(
+            instance,
+            1,
+            12,
+            event_transfer,
+            int_atom.as_ptr() as *const c_void,
+        );
+        // Too small for the atom.
+        port_event(
+            instance,
+            1,
+            8,
+            event_transfer,
+            int_atom.as_ptr() as *const c_void,
+        );
+    }
+
+    let ui = unsafe { get_ui::<EchoUI>(instance) };
+    assert_eq!(
+        vec![(0, "float 0.5".to_owned()), (1, "event 4".to_owned())],
+        ui.events
+    );
+    assert_eq!(2, writes.len());
+    assert_eq!((0, 0, 1.0f32.to_ne_bytes().to_vec()), writes[0]);
+    let (port_index, protocol, data) = &writes[1];
+    assert_eq!((1, event_transfer), (*port_index, *protocol));
+    assert_eq!(
+        int_atom
+            .iter()
+            .flat_map(|i| i.to_ne_bytes().to_vec())
+            .collect::<Vec<u8>>(),
+        *data
+    );
+
+    // Using the interfaces.
+    let extension_data = descriptor.extension_data.unwrap();
+    let idle_interface = unsafe {
+        (extension_data(uris::IDLE_INTERFACE_URI.as_ptr()) as *const IdleInterface).as_ref()
+    }
+    .unwrap();
+    let show_interface = unsafe {
+        (extension_data(uris::SHOW_INTERFACE_URI.as_ptr()) as *const ShowInterface).as_ref()
+    }
+    .unwrap();
+    assert!(unsafe { extension_data(uris::RESIZE_URI.as_ptr()) }.is_null());
+
+    assert_eq!(0, unsafe { (show_interface.show)(instance) });
+    assert!(unsafe { get_ui::<EchoUI>(instance) }.visible);
+    assert_eq!(0, unsafe { (idle_interface.idle)(instance) });
+    assert_ne!(0, unsafe { (idle_interface.idle)(instance) });
+    assert_eq!(0, unsafe { (show_interface.hide)(instance) });
+    assert!(!unsafe { get_ui::<EchoUI>(instance) }.visible);
+
+    unsafe { (descriptor.cleanup)(instance) };
+
+    // Instantiating the UI for another plugin fails.
+    let instance = unsafe {
+        (descriptor.instantiate)(
+            descriptor,
+            b"http://example.org/Other\0".as_ptr() as *const c_char,
+            b"/\0".as_ptr() as *const c_char,
+            write,
+            &mut writes as *mut Writes as ControllerHandle,
+            &mut widget,
+            features.as_ptr(),
+        )
+    };
+    assert!(instance.is_null());
+}
@@ -0,0 +1,97 @@
+//! Structs of pre-mapped URIDs.
+//!
+//! Most plugins need a handful of URIDs in their `run` method and mapping them there is neither
+//! fast nor real-time-safe. Instead, all URIDs should be mapped once in `instantiate` and stored
+//! in the plugin. The [`urid_cache`](../macro.urid_cache.html) macro creates a struct for this
+//! purpose: Every field holds the URID of a URI and the generated `new` method maps all of them.
+//!
+//!     extern crate lv2rs_core as core;
+//!     #[macro_use]
+//!     extern crate lv2rs_urid as urid;
+//!
+//!     use core::uris::Uri;
+//!     use urid::debug::DebugMap;
+//!
+//!     const MIDI_EVENT_URI: Uri = uri!("http://lv2plug.in/ns/ext/midi#MidiEvent");
+//!
+//!     urid_cache! {
+//!         /// The URIDs my plugin needs.
+//!         pub struct PluginURIDs {
+//!             /// The type of MIDI events.
+//!             pub midi_event: MIDI_EVENT_URI,
+//!             pub gain: uri!("https://example.org/plugin#gain"),
+//!         }
+//!     }
+//!
+//!     let mut debug_map = DebugMap::new();
+//!     let mut cached_map = unsafe { debug_map.create_cached_map() };
+//!
+//!     let urids = PluginURIDs::new(&mut cached_map);
+//!     assert_ne!(urids.midi_event, urids.gain);
+
+/// Create a struct of pre-mapped URIDs.
+///
+/// Every field of the struct is given as a name and a URI. The URI has to be a
+/// [`Uri`](../lv2rs_core/uris/struct.Uri.html), like the URI constants of the LV2 crates and the
+/// URIs created by the [`uri`](macro.uri.html) macro, or a `&'static CStr`. The generated struct has a field of the type [`URID`](type.URID.html) for every URI and a
+/// `new` method that maps all URIs with a [`CachedMap`](struct.CachedMap.html).
+///
+/// See the [module documentation](cache/index.html) for an example.
+#[macro_export]
+macro_rules! urid_cache {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $(#[$field_meta:meta])*
+                $field_vis:vis $field:ident : $uri:expr
+            ),* $(,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+        $vis struct $name {
+            $(
+                $(#[$field_meta])*
+                $field_vis $field: $crate::URID,
+            )*
+        }
+
+        impl $name {
+            /// Map all URIs of the struct.
+            $vis fn new(map: &mut $crate::CachedMap) -> Self {
+                Self {
+                    $(
+                        $field: map.map($uri),
+                    )*
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use crate::debug::DebugMap;
+    use lv2rs_core::uris::Uri;
+
+    const GITHUB_URI: Uri = crate::uri!("https://github.com");
+
+    urid_cache! {
+        struct TestURIDs {
+            github: GITHUB_URI,
+            gitlab: crate::uri!("https://gitlab.com")
+        }
+    }
+
+    #[test]
+    fn test_urid_cache() {
+        let mut debug_map = DebugMap::new();
+        let mut cached_map = unsafe { debug_map.create_cached_map() };
+        let github_urid = cached_map.map(GITHUB_URI);
+
+        let urids = TestURIDs::new(&mut cached_map);
+        assert_eq!(github_urid, urids.github);
+        assert_ne!(urids.github, urids.gitlab);
+    }
+}
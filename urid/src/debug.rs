@@ -4,54 +4,219 @@
 //! testing. However, testing can (and should) not be done within a running host. This is where
 //! these utilities come in hand: They map URIs to unique URIDs and backwards without needing an
 //! external host.
-use crate::{MapHandle, URID};
-use std::collections::HashMap;
-use std::ffi::{CStr, CString};
-use std::marker::PhantomPinned;
-use std::os::raw::*;
+use crate::{MapHandle, UnmapHandle, URID};
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::ffi::CString;
+use alloc::vec::Vec;
+use core::ffi::*;
+use core::fmt;
+use core::marker::PhantomPinned;
 
-/// Container holding both the mapping feature and a storage for URID mappings.
+/// The mappings of a debug or host map, in both directions.
+pub(crate) struct Storage {
+    pub(crate) forward: BTreeMap<CString, URID>,
+    pub(crate) reverse: BTreeMap<URID, CString>,
+    next_urid: URID,
+}
+
+impl Storage {
+    pub(crate) fn new() -> Self {
+        Self {
+            forward: BTreeMap::new(),
+            reverse: BTreeMap::new(),
+            next_urid: 1,
+        }
+    }
+
+    pub(crate) fn map(&mut self, uri: &CStr) -> URID {
+        if let Some(urid) = self.forward.get(uri) {
+            return *urid;
+        }
+        // Skip URIDs that were taken by seeded mappings.
+        while self.reverse.contains_key(&self.next_urid) {
+            self.next_urid += 1;
+        }
+        let urid = self.next_urid;
+        self.next_urid += 1;
+        self.forward.insert(uri.to_owned(), urid);
+        self.reverse.insert(urid, uri.to_owned());
+        urid
+    }
+
+    pub(crate) fn seed(&mut self, uri: &CStr, urid: URID) -> Result<(), ()> {
+        if urid == 0 {
+            return Err(());
+        }
+        match (self.forward.get(uri), self.reverse.get(&urid)) {
+            (None, None) => {
+                self.forward.insert(uri.to_owned(), urid);
+                self.reverse.insert(urid, uri.to_owned());
+                Ok(())
+            }
+            (Some(mapped_urid), _) if *mapped_urid == urid => Ok(()),
+            _ => Err(()),
+        }
+    }
+
+    pub(crate) fn load(&mut self, table: &str) -> Result<(), ()> {
+        for line in table.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (urid, uri) = line.split_once(char::is_whitespace).ok_or(())?;
+            let urid: URID = urid.parse().map_err(|_| ())?;
+            let uri = CString::new(uri.trim_start()).map_err(|_| ())?;
+            self.seed(uri.as_c_str(), urid)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Storage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (urid, uri) in self.reverse.iter() {
+            writeln!(f, "{}\t{}", urid, uri.to_string_lossy())?;
+        }
+        Ok(())
+    }
+}
+
+/// Container holding the mapping and unmapping features and a storage for URID mappings.
+///
+/// URIDs are assigned in the order the URIs are mapped, starting with 1, which makes the mapping
+/// deterministic: The same sequence of `map` calls always leads to the same URIDs. URIs that should
+/// have a certain URID, for example to replay recorded atoms, can be pre-seeded with
+/// [`seed`](#method.seed), and the whole table can be dumped by formatting the map. Such a dump
+/// can be loaded again with [`from_table`](#method.from_table), for example to replay recorded
+/// atoms in tests with the same URIDs:
 ///
-/// Since the mapping feature contains a raw pointer to the storage, this struct must be pinned.
-/// This means that it cannot be moved.
+///     extern crate lv2rs_urid as urid;
+///
+///     use std::ffi::CStr;
+///     use urid::debug::DebugMap;
+///
+///     let uri = CStr::from_bytes_with_nul(b"https://example.org\0").unwrap();
+///     let mut recording_map = DebugMap::new();
+///     let urid = recording_map.get_map_mut().map(uri);
+///     let table = recording_map.to_string();
+///
+///     let replaying_map = DebugMap::from_table(&table).unwrap();
+///     assert_eq!(Some(urid), replaying_map.get(uri));
+///
+/// Since the features contain a raw pointer to the storage, this struct must be pinned. This
+/// means that it cannot be moved.
 pub struct DebugMap {
-    storage: HashMap<CString, URID>,
+    storage: Storage,
     feature: crate::Map,
+    unmap_feature: crate::Unmap,
     _pin: PhantomPinned,
 }
 
 extern "C" fn mapping_fn(handle: MapHandle, uri: *const c_char) -> URID {
-    let map = unsafe { (handle as *mut HashMap<CString, URID>).as_mut() }.unwrap();
-    let uri = unsafe { CStr::from_ptr(uri) }.to_owned();
+    let storage = unsafe { (handle as *mut Storage).as_mut() }.unwrap();
+    storage.map(unsafe { CStr::from_ptr(uri) })
+}
 
-    if !map.contains_key(&uri) {
-        let biggest_urid = map.values().map(|urid: &URID| -> URID { *urid }).max();
-        let new_urid = match biggest_urid {
-            Some(urid) => urid + 1,
-            None => 0,
-        };
-        map.insert(uri.clone(), new_urid);
+extern "C" fn unmapping_fn(handle: UnmapHandle, urid: URID) -> *const c_char {
+    let storage = unsafe { (handle as *const Storage).as_ref() }.unwrap();
+    // The content of a `CString` lives on the heap and therefore, the returned pointer stays
+    // valid even if the map is re-organized.
+    match storage.reverse.get(&urid) {
+        Some(uri) => uri.as_ptr(),
+        None => core::ptr::null(),
     }
-
-    map[&uri]
 }
 
 impl DebugMap {
     /// Create a new debug map in a box.
     pub fn new() -> Box<Self> {
         let mut debug_map = Box::new(Self {
-            storage: HashMap::new(),
+            storage: Storage::new(),
             feature: crate::Map {
-                handle: std::ptr::null_mut(),
+                handle: core::ptr::null_mut(),
                 map: mapping_fn,
             },
+            unmap_feature: crate::Unmap {
+                handle: core::ptr::null_mut(),
+                unmap: unmapping_fn,
+            },
             _pin: PhantomPinned,
         });
-        debug_map.feature.handle =
-            &mut debug_map.storage as *mut HashMap<CString, URID> as *mut c_void;
+        let handle = &mut debug_map.storage as *mut Storage as *mut c_void;
+        debug_map.feature.handle = handle;
+        debug_map.unmap_feature.handle = handle;
         debug_map
     }
 
+    /// Create a new debug map that is pre-seeded with the given mappings.
+    ///
+    /// An `Err` is returned if the mappings collide; See [`seed`](#method.seed).
+    pub fn with_mappings(mappings: &[(&CStr, URID)]) -> Result<Box<Self>, ()> {
+        let mut debug_map = Self::new();
+        for (uri, urid) in mappings {
+            debug_map.seed(uri, *urid)?;
+        }
+        Ok(debug_map)
+    }
+
+    /// Create a new debug map from a table that was created by formatting a map.
+    ///
+    /// See [`load_table`](#method.load_table) for the format of the table.
+    pub fn from_table(table: &str) -> Result<Box<Self>, ()> {
+        let mut debug_map = Self::new();
+        debug_map.load_table(table)?;
+        Ok(debug_map)
+    }
+
+    /// Seed the map with all mappings of a table.
+    ///
+    /// Every line of the table contains a URID and a URI, separated by whitespace. Empty lines and
+    /// lines that start with `#` are ignored. This is the format that is written when the map is
+    /// formatted.
+    ///
+    /// An `Err` is returned if a line can not be parsed or if a mapping collides with an existing
+    /// one; See [`seed`](#method.seed). In this case, the mappings of the preceding lines are
+    /// kept.
+    pub fn load_table(&mut self, table: &str) -> Result<(), ()> {
+        self.storage.load(table)
+    }
+
+    /// Map a URI to a fixed URID.
+    ///
+    /// Seeding an existing mapping again is fine, but if the URI is already mapped to another
+    /// URID, if the URID is already used by another URI or if the URID is 0, which is not a valid
+    /// URID, an `Err` is returned and the map is not changed.
+    pub fn seed(&mut self, uri: &CStr, urid: URID) -> Result<(), ()> {
+        self.storage.seed(uri, urid)
+    }
+
+    /// Return the URID of a URI, without mapping it.
+    pub fn get(&self, uri: &CStr) -> Option<URID> {
+        self.storage.forward.get(uri).cloned()
+    }
+
+    /// Return the number of mapped URIs.
+    pub fn len(&self) -> usize {
+        self.storage.forward.len()
+    }
+
+    /// Return `true` if no URIs are mapped.
+    pub fn is_empty(&self) -> bool {
+        self.storage.forward.is_empty()
+    }
+
+    /// Return all mappings, ordered by their URID.
+    pub fn table(&self) -> Vec<(URID, &CStr)> {
+        self.storage
+            .reverse
+            .iter()
+            .map(|(urid, uri)| (*urid, uri.as_c_str()))
+            .collect()
+    }
+
     /// Return a reference to the mapping feature.
     pub fn get_map_ref(&self) -> &crate::Map {
         &self.feature
@@ -64,7 +229,7 @@ impl DebugMap {
 
     /// Create a cached map.
     ///
-    /// Technically, this is useless since this debug map already contains the URIDs in a `HashMap`,
+    /// Technically, this is useless since this debug map already stores the URIDs in ordered maps,
     /// but many LV2 libraries use the `CachedMap` and therefore need such an object.
     ///
     /// This method is unsafe since it has to fake the lifetime of the mapping feature. In reality,
@@ -76,21 +241,52 @@ impl DebugMap {
             (self.get_map_mut() as *mut crate::Map).as_mut().unwrap();
         crate::CachedMap::new(faked_map)
     }
+
+    /// Return a reference to the unmapping feature.
+    pub fn get_unmap_ref(&self) -> &crate::Unmap {
+        &self.unmap_feature
+    }
+
+    /// Return a mutable reference to the unmapping feature.
+    pub fn get_unmap_mut(&mut self) -> &mut crate::Unmap {
+        &mut self.unmap_feature
+    }
+
+    /// Create a cached unmap.
+    ///
+    /// This method is unsafe for the same reasons as
+    /// [`create_cached_map`](#method.create_cached_map).
+    pub unsafe fn create_cached_unmap(&mut self) -> crate::CachedUnmap {
+        let faked_unmap: &'static mut crate::Unmap = (self.get_unmap_mut() as *mut crate::Unmap)
+            .as_mut()
+            .unwrap();
+        crate::CachedUnmap::new(faked_unmap)
+    }
+}
+
+impl fmt::Display for DebugMap {
+    /// Write the mapping table, one mapping per line, ordered by the URIDs.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.storage.fmt(f)
+    }
 }
+
 #[cfg(test)]
 mod test {
     use crate::debug::*;
+    use crate::{CacheStats, MapError};
+    use lv2rs_core::uris::Uri;
 
-    const GITHUB_URI: &[u8] = b"https://github.com\0";
-    const GITLAB_URI: &[u8] = b"https://gitlab.com\0";
+    const GITHUB_URI: Uri = Uri::new(b"https://github.com\0");
+    const GITLAB_URI: Uri = Uri::new(b"https://gitlab.com\0");
 
     #[test]
     fn test_mapping() {
         let mut debug_map = DebugMap::new();
         let map = debug_map.get_map_mut();
 
-        let github_urid = map.map(CStr::from_bytes_with_nul(GITHUB_URI).unwrap());
-        let gitlab_urid = map.map(CStr::from_bytes_with_nul(GITLAB_URI).unwrap());
+        let github_urid = map.map(GITHUB_URI);
+        let gitlab_urid = map.map(GITLAB_URI);
 
         assert_ne!(github_urid, gitlab_urid);
     }
@@ -100,9 +296,240 @@ mod test {
         let mut debug_map = DebugMap::new();
         let mut cached_map = unsafe { debug_map.create_cached_map() };
 
-        let github_urid = cached_map.map(CStr::from_bytes_with_nul(GITHUB_URI).unwrap());
-        let gitlab_urid = cached_map.map(CStr::from_bytes_with_nul(GITLAB_URI).unwrap());
+        let github_urid = cached_map.map(GITHUB_URI);
+        let gitlab_urid = cached_map.map(GITLAB_URI);
 
         assert_ne!(github_urid, gitlab_urid);
     }
+
+    #[test]
+    fn test_cached_unmapping() {
+        let mut debug_map = DebugMap::new();
+        let mut cached_map = unsafe { debug_map.create_cached_map() };
+        let cached_unmap = unsafe { debug_map.create_cached_unmap() };
+
+        let github_uri = GITHUB_URI.as_cstr();
+        let gitlab_uri = GITLAB_URI.as_cstr();
+        let github_urid = cached_map.map(github_uri);
+        let gitlab_urid = cached_map.map(gitlab_uri);
+
+        assert_eq!(
+            github_uri,
+            cached_unmap.unmap(github_urid).unwrap().as_c_str()
+        );
+        assert_eq!(
+            gitlab_uri,
+            cached_unmap.unmap(gitlab_urid).unwrap().as_c_str()
+        );
+        assert!(cached_unmap.unmap(gitlab_urid + 1).is_none());
+
+        // Previously returned URIs stay valid while new ones are unmapped.
+        let github_unmapped = cached_unmap.get(github_urid).unwrap();
+        for index in 0..64 {
+            let uri = CString::new(format!("urn:test:{}", index)).unwrap();
+            let urid = debug_map.get_map_mut().map(uri.as_c_str());
+            assert_eq!(uri.as_c_str(), cached_unmap.unmap(urid).unwrap().as_c_str());
+        }
+        assert_eq!(github_uri, github_unmapped.as_c_str());
+        assert_eq!(66, cached_unmap.len());
+        assert_eq!(
+            Some(&github_unmapped),
+            cached_unmap.cache().get(&github_urid)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_shared_mapping() {
+        use crate::SharedCachedMap;
+        use std::sync::Arc;
+
+        let mut debug_map = DebugMap::new();
+        let shared_map = Arc::new(SharedCachedMap::from(unsafe {
+            debug_map.create_cached_map()
+        }));
+        let github_uri = GITHUB_URI.as_cstr();
+        let gitlab_uri = GITLAB_URI.as_cstr();
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let shared_map = shared_map.clone();
+                std::thread::spawn(move || (shared_map.map(github_uri), shared_map.map(gitlab_uri)))
+            })
+            .collect();
+        let urids: Vec<(URID, URID)> = threads
+            .into_iter()
+            .map(|thread| thread.join().unwrap())
+            .collect();
+
+        assert!(urids.iter().all(|pair| *pair == urids[0]));
+        assert_ne!(urids[0].0, urids[0].1);
+        assert_eq!(Some(urids[0].0), shared_map.get(github_uri));
+        assert_eq!(2, shared_map.len());
+    }
+
+    #[test]
+    fn test_determinism() {
+        let github_uri = GITHUB_URI.as_cstr();
+        let gitlab_uri = GITLAB_URI.as_cstr();
+        let example_uri = CStr::from_bytes_with_nul(b"https://example.org\0").unwrap();
+
+        let mut debug_map = DebugMap::with_mappings(&[(gitlab_uri, 1)]).unwrap();
+        assert!(debug_map.seed(gitlab_uri, 1).is_ok());
+        assert!(debug_map.seed(gitlab_uri, 2).is_err());
+        assert!(debug_map.seed(github_uri, 1).is_err());
+        assert!(debug_map.seed(github_uri, 0).is_err());
+        assert!(debug_map.seed(example_uri, 3).is_ok());
+
+        let map = debug_map.get_map_mut();
+        assert_eq!(2, map.map(github_uri));
+        assert_eq!(1, map.map(gitlab_uri));
+        let other_uri = CStr::from_bytes_with_nul(b"https://example.com\0").unwrap();
+        assert_eq!(4, map.map(other_uri));
+
+        assert_eq!(Some(3), debug_map.get(example_uri));
+        assert_eq!(4, debug_map.len());
+        assert_eq!(
+            "1\thttps://gitlab.com\n2\thttps://github.com\n\
+             3\thttps://example.org\n4\thttps://example.com\n",
+            debug_map.to_string()
+        );
+    }
+
+    #[test]
+    fn test_premapping() {
+        let github_uri = GITHUB_URI.as_cstr();
+        let gitlab_uri = GITLAB_URI.as_cstr();
+
+        let mut debug_map = DebugMap::new();
+        let mut cached_map = unsafe { debug_map.create_cached_map() };
+        cached_map.premap(&[github_uri]);
+
+        let realtime_map: &crate::CachedMap = &cached_map;
+        assert_eq!(Some(1), realtime_map.get(github_uri));
+        assert_eq!(None, realtime_map.get(gitlab_uri));
+        assert_eq!(1, debug_map.len());
+    }
+
+    #[test]
+    fn test_bulk_mapping() {
+        let github_uri = GITHUB_URI.as_cstr();
+        let gitlab_uri = GITLAB_URI.as_cstr();
+
+        let mut debug_map = DebugMap::new();
+        let mut cached_map = unsafe { debug_map.create_cached_map() };
+        let gitlab_urid = cached_map.map(gitlab_uri);
+
+        let urids = cached_map.map_all(&[github_uri, gitlab_uri, github_uri]);
+        assert_eq!(vec![2, gitlab_urid, 2], urids);
+
+        let mut urids = [0; 2];
+        cached_map.map_into(&[gitlab_uri, github_uri], &mut urids);
+        assert_eq!([gitlab_urid, 2], urids);
+    }
+
+    #[test]
+    fn test_str_mapping() {
+        let mut debug_map = DebugMap::new();
+        let mut cached_map = unsafe { debug_map.create_cached_map() };
+
+        let github_urid = cached_map.map(crate::uri!("https://github.com"));
+        let github_uri = String::from("https://github.com");
+        assert_eq!(Ok(github_urid), cached_map.map_str(&github_uri));
+        assert_eq!(Ok(github_urid), cached_map.map_str("https://github.com"));
+        assert_ne!(Ok(github_urid), cached_map.map_str("https://gitlab.com"));
+        assert_eq!(
+            Err(MapError::InvalidUri),
+            cached_map.map_str("https://github.com\0")
+        );
+    }
+
+    #[test]
+    fn test_cache_capacity() {
+        let mut debug_map = DebugMap::new();
+        let mut cached_map = unsafe { debug_map.create_cached_map() };
+        cached_map.map(GITHUB_URI);
+        cached_map.set_capacity(Some(2));
+
+        let a_urid = cached_map.map_str("urn:test:a").unwrap();
+        cached_map.map_str("urn:test:b").unwrap();
+        assert_eq!(Ok(a_urid), cached_map.map_str("urn:test:a"));
+        // "urn:test:b" is the least recently used URI and is evicted.
+        cached_map.map_str("urn:test:c").unwrap();
+        assert_eq!(3, cached_map.len());
+        assert_eq!(
+            CacheStats {
+                hits: 1,
+                misses: 4,
+                evictions: 1,
+            },
+            cached_map.stats()
+        );
+
+        // The evicted URI is mapped to the same URID again.
+        assert_eq!(Ok(3), cached_map.map_str("urn:test:b"));
+        assert_eq!(2, cached_map.stats().evictions);
+        cached_map.set_capacity(Some(0));
+        assert_eq!(1, cached_map.len());
+        assert_eq!(4, cached_map.stats().evictions);
+
+        cached_map.reset_stats();
+        assert_eq!(CacheStats::default(), cached_map.stats());
+        assert_eq!(Some(0), cached_map.capacity());
+    }
+
+    #[test]
+    fn test_table() {
+        let github_uri = GITHUB_URI.as_cstr();
+        let gitlab_uri = GITLAB_URI.as_cstr();
+        let table = "# Recorded mappings\n\n7\thttps://github.com\n2 https://gitlab.com\n";
+
+        let mut debug_map = DebugMap::from_table(table).unwrap();
+        assert_eq!(Some(7), debug_map.get(github_uri));
+        assert_eq!(Some(2), debug_map.get(gitlab_uri));
+        let exported = debug_map.to_string();
+        assert_eq!("2\thttps://gitlab.com\n7\thttps://github.com\n", exported);
+        assert_eq!(
+            exported,
+            DebugMap::from_table(&exported).unwrap().to_string()
+        );
+
+        assert!(debug_map.load_table("7\thttps://gitlab.com").is_err());
+        assert!(debug_map.load_table("seven\thttps://example.org").is_err());
+        assert!(debug_map.load_table("https://example.org").is_err());
+        assert_eq!(2, debug_map.len());
+    }
+
+    extern "C" fn failing_mapping_fn(handle: MapHandle, uri: *const c_char) -> URID {
+        let storage = unsafe { (handle as *mut Storage).as_mut() }.unwrap();
+        let uri = unsafe { CStr::from_ptr(uri) };
+        if uri.to_bytes().starts_with(b"urn:fail") {
+            0
+        } else {
+            storage.map(uri)
+        }
+    }
+
+    #[test]
+    fn test_failures() {
+        let mut debug_map = DebugMap::new();
+        debug_map.get_map_mut().map = failing_mapping_fn;
+        let mut cached_map = unsafe { debug_map.create_cached_map() };
+        let failing_uri = CStr::from_bytes_with_nul(b"urn:fail\0").unwrap();
+        let github_uri = GITHUB_URI.as_cstr();
+
+        assert_eq!(Err(MapError::Failed), cached_map.try_map(failing_uri));
+        assert_eq!(0, cached_map.map(failing_uri));
+        assert_eq!(Ok(1), cached_map.try_map(github_uri));
+        assert_eq!(None, cached_map.get(failing_uri));
+        assert_eq!(Err(MapError::Failed), cached_map.map_str("urn:fail:str"));
+        assert_eq!(Err(MapError::Failed), cached_map.map_str("urn:fail:str"));
+        // Failures are cached and the host is only asked once.
+        assert_eq!(3, cached_map.stats().misses);
+
+        cached_map.forget_failures();
+        assert_eq!(1, cached_map.len());
+        assert_eq!(Err(MapError::Failed), cached_map.try_map(failing_uri));
+        assert_eq!(4, cached_map.stats().misses);
+    }
 }
@@ -0,0 +1,293 @@
+//! URID mapping for hosts.
+//!
+//! A host has to provide the mapping and unmapping features to every plugin it loads. The
+//! [`HostMap`](struct.HostMap.html) implements both of them on top of a shared, thread-safe
+//! interning table, which lives as long as the map itself. This makes it usable by Rust hosts as
+//! well as by test harnesses that want to hand real features to plugins:
+//!
+//!     extern crate lv2rs_core as core;
+//!     extern crate lv2rs_urid as urid;
+//!
+//!     use std::ffi::CStr;
+//!     use urid::host::{HostMap, HostMapFeatures};
+//!     use urid::UridFeatures;
+//!
+//!     // The features have to live as long as the plugin instances, which is the whole program
+//!     // in this example.
+//!     let host_map: &'static HostMap = Box::leak(Box::new(HostMap::new()));
+//!
+//!     // Construct the feature array for a plugin.
+//!     let features: &'static HostMapFeatures = Box::leak(Box::new(host_map.features()));
+//!     let feature_refs: Vec<&'static lv2rs_core::Feature> = features.iter().collect();
+//!
+//!     // The plugin retrieves and uses the features.
+//!     let mut cached_map = core::Features::new(&feature_refs).cached_map().unwrap();
+//!     let uri = CStr::from_bytes_with_nul(b"https://example.org\0").unwrap();
+//!     let urid = cached_map.map(uri);
+//!
+//!     // The host sees the same mapping.
+//!     assert_eq!(urid, host_map.map(uri));
+//!     assert_eq!(Some(uri), host_map.unmap(urid));
+use crate::debug::Storage;
+use crate::{MapHandle, UnmapHandle, URID};
+use std::cell::UnsafeCell;
+use std::ffi::CStr;
+use std::fmt;
+use std::marker::{PhantomData, PhantomPinned};
+use std::ops::{Deref, DerefMut};
+use std::os::raw::*;
+use std::pin::Pin;
+use std::sync::{Mutex, MutexGuard};
+
+/// A thread-safe URID map for hosts, providing the mapping and unmapping features.
+///
+/// URIDs are assigned in the order the URIs are mapped, starting with 1. The unmapped URI
+/// strings are never moved or freed while the map exists, which means that the pointers returned
+/// by the unmapping feature stay valid for the whole lifetime of the map.
+///
+/// Since the features contain a raw pointer to the map, it's created in a pinned box and the
+/// [`features`](#method.features) borrow it. It has to outlive all plugin instances it was passed
+/// to.
+///
+/// ```compile_fail
+/// # extern crate lv2rs_urid as urid;
+/// # use urid::host::HostMap;
+/// // The features can't outlive the map.
+/// let features = {
+///     let host_map = HostMap::new();
+///     host_map.features()
+/// };
+/// ```
+pub struct HostMap {
+    storage: Mutex<Storage>,
+    // Plugins receive mutable pointers to the features.
+    map_feature: UnsafeCell<crate::Map>,
+    unmap_feature: UnsafeCell<crate::Unmap>,
+    _pin: PhantomPinned,
+}
+
+unsafe impl Send for HostMap {}
+unsafe impl Sync for HostMap {}
+
+extern "C" fn mapping_fn(handle: MapHandle, uri: *const c_char) -> URID {
+    if uri.is_null() {
+        return 0;
+    }
+    let host_map = unsafe { (handle as *const HostMap).as_ref() }.unwrap();
+    host_map.map(unsafe { CStr::from_ptr(uri) })
+}
+
+extern "C" fn unmapping_fn(handle: UnmapHandle, urid: URID) -> *const c_char {
+    let host_map = unsafe { (handle as *const HostMap).as_ref() }.unwrap();
+    match host_map.unmap(urid) {
+        Some(uri) => uri.as_ptr(),
+        None => std::ptr::null(),
+    }
+}
+
+impl HostMap {
+    /// Create a new, empty host map in a pinned box.
+    pub fn new() -> Pin<Box<Self>> {
+        let mut host_map = Box::new(Self {
+            storage: Mutex::new(Storage::new()),
+            map_feature: UnsafeCell::new(crate::Map {
+                handle: std::ptr::null_mut(),
+                map: mapping_fn,
+            }),
+            unmap_feature: UnsafeCell::new(crate::Unmap {
+                handle: std::ptr::null_mut(),
+                unmap: unmapping_fn,
+            }),
+            _pin: PhantomPinned,
+        });
+        let handle = host_map.as_mut() as *mut HostMap as *mut c_void;
+        host_map.map_feature.get_mut().handle = handle;
+        host_map.unmap_feature.get_mut().handle = handle;
+        Box::into_pin(host_map)
+    }
+
+    /// Create a new host map from a table that was created by formatting a map.
+    ///
+    /// See [`load_table`](#method.load_table) for the format of the table.
+    pub fn from_table(table: &str) -> Result<Pin<Box<Self>>, ()> {
+        let host_map = Self::new();
+        host_map.load_table(table)?;
+        Ok(host_map)
+    }
+
+    /// Add all mappings of a table to the map.
+    ///
+    /// The table has the same format as the one of the
+    /// [`DebugMap`](../debug/struct.DebugMap.html#method.load_table): Every line contains a URID
+    /// and a URI, separated by whitespace. An `Err` is returned if a line can not be parsed or if
+    /// a mapping collides with an existing one.
+    pub fn load_table(&self, table: &str) -> Result<(), ()> {
+        self.lock().load(table)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Storage> {
+        // A panic while mapping can not leave the storage in an inconsistent state.
+        match self.storage.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    /// Map a URI to a URID.
+    pub fn map(&self, uri: &CStr) -> URID {
+        self.lock().map(uri)
+    }
+
+    /// Return the URI of a URID, or `None` if the URID is not mapped.
+    pub fn unmap(&self, urid: URID) -> Option<&CStr> {
+        let storage = self.lock();
+        // The content of a `CString` lives on the heap and is never removed from the storage.
+        // Therefore, the reference stays valid as long as the map exists.
+        storage
+            .reverse
+            .get(&urid)
+            .map(|uri| unsafe { CStr::from_ptr(uri.as_ptr()) })
+    }
+
+    /// Return the URID of a URI, without mapping it.
+    pub fn get(&self, uri: &CStr) -> Option<URID> {
+        self.lock().forward.get(uri).cloned()
+    }
+
+    /// Return the number of mapped URIs.
+    pub fn len(&self) -> usize {
+        self.lock().forward.len()
+    }
+
+    /// Return `true` if no URIs are mapped.
+    pub fn is_empty(&self) -> bool {
+        self.lock().forward.is_empty()
+    }
+
+    /// Return a reference to the mapping feature.
+    pub fn map_feature(&self) -> &crate::Map {
+        unsafe { &*self.map_feature.get() }
+    }
+
+    /// Return a reference to the unmapping feature.
+    pub fn unmap_feature(&self) -> &crate::Unmap {
+        unsafe { &*self.unmap_feature.get() }
+    }
+
+    /// Return the feature descriptors of the mapping and the unmapping feature.
+    ///
+    /// The descriptors point to the features of this map, which is why they borrow it.
+    pub fn features(&self) -> HostMapFeatures<'_> {
+        HostMapFeatures {
+            features: [
+                lv2rs_core::Feature::new(
+                    crate::uris::MAP_URI.as_ptr(),
+                    self.map_feature.get() as *mut c_void,
+                ),
+                lv2rs_core::Feature::new(
+                    crate::uris::UNMAP_URI.as_ptr(),
+                    self.unmap_feature.get() as *mut c_void,
+                ),
+            ],
+            host_map: PhantomData,
+        }
+    }
+}
+
+/// The feature descriptors of a [`HostMap`](struct.HostMap.html).
+///
+/// They dereference to an array of the mapping and the unmapping feature, in this order.
+/// Mutable access is needed to retrieve the data of a feature with
+/// [`Feature::data`](../../lv2rs_core/struct.Feature.html#method.data).
+pub struct HostMapFeatures<'a> {
+    features: [lv2rs_core::Feature; 2],
+    host_map: PhantomData<&'a HostMap>,
+}
+
+impl<'a> Deref for HostMapFeatures<'a> {
+    type Target = [lv2rs_core::Feature; 2];
+
+    fn deref(&self) -> &[lv2rs_core::Feature; 2] {
+        &self.features
+    }
+}
+
+impl<'a> DerefMut for HostMapFeatures<'a> {
+    fn deref_mut(&mut self) -> &mut [lv2rs_core::Feature; 2] {
+        &mut self.features
+    }
+}
+
+impl fmt::Display for HostMap {
+    /// Write the mapping table, one mapping per line, ordered by the URIDs.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.lock().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::host::*;
+    use crate::UridFeatures;
+    use lv2rs_core::uris::Uri;
+
+    const GITHUB_URI: Uri = Uri::new(b"https://github.com\0");
+    const GITLAB_URI: Uri = Uri::new(b"https://gitlab.com\0");
+
+    #[test]
+    fn test_features() {
+        let host_map: &'static HostMap = Box::leak(Box::new(HostMap::new()));
+        let features: &'static HostMapFeatures = Box::leak(Box::new(host_map.features()));
+        let feature_refs: Vec<&'static lv2rs_core::Feature> = features.iter().collect();
+        let features = lv2rs_core::Features::new(&feature_refs);
+
+        let github_uri = GITHUB_URI.as_cstr();
+        let gitlab_uri = GITLAB_URI.as_cstr();
+        let mut cached_map = features.cached_map().unwrap();
+        let cached_unmap = features.cached_unmap().unwrap();
+        let github_urid = cached_map.map(github_uri);
+        assert_eq!(1, github_urid);
+        assert_eq!(2, host_map.map(gitlab_uri));
+        assert_eq!(Some(2), host_map.get(gitlab_uri));
+        assert_eq!(2, host_map.len());
+
+        assert_eq!(
+            github_uri,
+            cached_unmap.unmap(github_urid).unwrap().as_c_str()
+        );
+        assert_eq!(Some(gitlab_uri), host_map.unmap(2));
+        assert_eq!(None, host_map.unmap(3));
+
+        let map = host_map.map_feature();
+        assert_eq!(0, (map.map)(map.handle, std::ptr::null()));
+
+        let replaying_map = HostMap::from_table(&host_map.to_string()).unwrap();
+        assert_eq!(Some(2), replaying_map.get(gitlab_uri));
+        assert_eq!(
+            3,
+            replaying_map.map(CStr::from_bytes_with_nul(b"urn:test\0").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_threads() {
+        let host_map: &'static HostMap = Box::leak(Box::new(HostMap::new()));
+        let threads: Vec<_> = (0..4)
+            .map(|index| {
+                std::thread::spawn(move || {
+                    let uri = std::ffi::CString::new(format!("urn:test:{}", index % 2)).unwrap();
+                    let map = host_map.map_feature();
+                    (map.map)(map.handle, uri.as_ptr())
+                })
+            })
+            .collect();
+        let mut urids: Vec<URID> = threads
+            .into_iter()
+            .map(|thread| thread.join().unwrap())
+            .collect();
+        urids.sort();
+        urids.dedup();
+        assert_eq!(vec![1, 2], urids);
+        assert_eq!(2, host_map.len());
+    }
+}
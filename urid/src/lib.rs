@@ -1,7 +1,7 @@
 //! A Rust re-implementation of the LV2 URID library.
 //!
 //! This LV2 feature enables you to map URIs to numbers and reverse.
-//! 
+//!
 //! This is a frozen prototype and therefore, development of this crate will not continue here. Further
 //! development continues as [rust-lv2](https://github.com/rust-dsp/rust-lv2).
 //!
@@ -21,39 +21,77 @@
 //!
 //!     impl core::Plugin for UridPlugin {
 //!         fn instantiate(
-//!             descriptor: &core::Descriptor,
-//!             rate: f64,
-//!             bundle_path: &CStr,
-//!             features: Option<&core::FeaturesList>
-//!         ) -> Option<Self> where Self: Sized {
+//!             context: &core::InstantiationContext,
+//!             features: Option<&lv2rs_core::FeaturesList>
+//!         ) -> Result<Self, core::InstantiateError> where Self: Sized {
 //!
-//!             // Return `None` if there are no features.
-//!             let features = features?;
+//!             // Fail if there are no features.
+//!             let missing_map = core::InstantiateError::missing_feature::<urid::Map>;
+//!             let features = features.ok_or_else(missing_map)?;
 //!
 //!             // Try to get the mapper and the un-mapper from the features list.
-//!             let map = urid::Map::try_from_features(features)?;
-//!             let unmap = urid::Unmap::try_from_features(features)?;
+//!             let map = urid::Map::try_from_features(features).ok_or_else(missing_map)?;
+//!             let unmap = urid::Unmap::try_from_features(features)
+//!                 .ok_or_else(core::InstantiateError::missing_feature::<urid::Unmap>)?;
 //!
 //!             // Create a URI, map it, and un-map it.
 //!             let github_uri = CStr::from_bytes_with_nul(b"https://github.com\0").unwrap();
 //!             let github_urid = map.map(github_uri);
 //!             let github_uri = unmap.unmap(github_urid);
 //!
-//!             Some(Self {})
+//!             Ok(Self {})
 //!         }
 //!
 //!         // Blank implementations to keep the compiler quiet.
 //!         fn connect_port(&mut self, _port: u32, _data: *mut ()) {}
-//!         fn run(&mut self, _n_samples: u32) {}
+//!         fn run(&mut self, _context: &mut core::RunContext) {}
 //!     }
-extern crate lv2rs_core as core;
+//!
+//! ## Real-time safety
+//!
+//! Mapping a URI may allocate memory or lock a mutex in the host and therefore, it is not
+//! real-time-safe. The [`CachedMap`](struct.CachedMap.html) makes this distinction visible in
+//! its signatures: All methods that may call the host, like `map`, take `&mut self`, while
+//! lookups in the cache, like `get`, take `&self` and are wait-free. Map all URIs you need with
+//! `premap` or a [`urid_cache`](macro.urid_cache.html) struct in `instantiate`, and code of your
+//! own that runs in the audio thread can take a `&CachedMap`, which can't call the host.
+//!
+//! This is a convention, not a guarantee for the whole stack: The reading and writing functions
+//! of the atom crate still take a `&mut CachedMap`, since they map the URIs of the atom types on
+//! demand. They only call the host for URIs that aren't cached yet, which is why the atom types
+//! should be premapped in `instantiate` too, for example with `AtomURIDs::new`.
+//!
+//! ## `no_std` support
+//!
+//! The crate only depends on `core` and `alloc` if the default `std` feature is disabled. In this
+//! case, the [`host`](host/index.html) module and the
+//! [`SharedCachedMap`](struct.SharedCachedMap.html), which need the synchronization primitives
+//! of the standard library, are not available.
+#![cfg_attr(all(not(feature = "std"), not(test)), no_std)]
+extern crate alloc;
+extern crate lv2rs_core;
 
+#[macro_use]
+pub mod cache;
 pub mod debug;
+#[cfg(feature = "std")]
+pub mod host;
+pub mod uri_map;
 pub mod uris;
 
-use std::collections::HashMap;
-use std::ffi::{CStr, CString};
-use std::os::raw::*;
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::ffi::CString;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::UnsafeCell;
+use core::ffi::*;
+use lv2rs_core::uris::Uri;
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+use uri_map::UriMap;
 
 /// Type to describe pointers to map handles.
 pub type MapHandle = *mut c_void;
@@ -77,13 +115,8 @@ impl Map {
     /// Try to find the mapping feature in the features map.
     ///
     /// If this function returns None if the host does not support mapping.
-    pub fn try_from_features<'a>(features: &core::FeaturesList) -> Option<&'static mut Self> {
-        unsafe {
-            core::Feature::get_feature::<Self>(
-                features,
-                CStr::from_bytes_with_nul(uris::MAP_URI).unwrap(),
-            )
-        }
+    pub fn try_from_features<'a>(features: &lv2rs_core::FeaturesList) -> Option<&'static mut Self> {
+        unsafe { lv2rs_core::Feature::get_feature::<Self>(features, uris::MAP_URI.as_cstr()) }
     }
 
     /// Map a URI to a URID.
@@ -99,11 +132,15 @@ impl Map {
     }
 }
 
+unsafe impl lv2rs_core::FeatureData for Map {
+    const URI: Uri = uris::MAP_URI;
+}
+
 /// Struct for mapping URIDs to URIs.
 #[repr(C)]
 pub struct Unmap {
     /// Pointer to a host-specific handle to map URIDs to URIs.
-    pub handle: MapHandle,
+    pub handle: UnmapHandle,
     /// Function that maps a URID to a URI.
     pub unmap: extern "C" fn(handle: UnmapHandle, urid: URID) -> *const c_char,
 }
@@ -112,13 +149,8 @@ impl Unmap {
     /// Try to find the unmapping feature in the features map.
     ///
     /// If this function returns None if the host does not support unmapping.
-    pub fn try_from_features(features: &core::FeaturesList) -> Option<&'static mut Self> {
-        unsafe {
-            core::Feature::get_feature::<Self>(
-                features,
-                CStr::from_bytes_with_nul(uris::UNMAP_URI).unwrap(),
-            )
-        }
+    pub fn try_from_features(features: &lv2rs_core::FeaturesList) -> Option<&'static mut Self> {
+        unsafe { lv2rs_core::Feature::get_feature::<Self>(features, uris::UNMAP_URI.as_cstr()) }
     }
 
     /// Try to unmap a URID to a URI.
@@ -136,31 +168,179 @@ impl Unmap {
     }
 }
 
+unsafe impl lv2rs_core::FeatureData for Unmap {
+    const URI: Uri = uris::UNMAP_URI;
+}
+
+/// Typed getters for the URID features.
+///
+/// This trait is implemented for [`Features`](../lv2rs_core/struct.Features.html) and lets you
+/// retrieve the URID features without naming their URIs:
+///
+///     extern crate lv2rs_core as core;
+///     extern crate lv2rs_urid as urid;
+///
+///     use urid::UridFeatures;
+///
+///     fn get_map(features: &lv2rs_core::FeaturesList) -> Option<urid::CachedMap> {
+///         lv2rs_core::Features::new(features).cached_map()
+///     }
+pub trait UridFeatures {
+    /// Return the mapping feature, if the host supports it.
+    ///
+    /// # Safety
+    ///
+    /// The same requirements as for
+    /// [`Features::get`](../lv2rs_core/struct.Features.html#method.get) apply: The feature has to
+    /// outlive the reference and the references returned by several calls must not be used at the
+    /// same time.
+    unsafe fn map(&self) -> Option<&'static mut Map>;
+
+    /// Return the unmapping feature, if the host supports it.
+    ///
+    /// # Safety
+    ///
+    /// The same requirements as for [`map`](#tymethod.map) apply.
+    unsafe fn unmap(&self) -> Option<&'static mut Unmap>;
+
+    /// Return the deprecated uri-map feature, if the host supports it.
+    ///
+    /// # Safety
+    ///
+    /// The same requirements as for [`map`](#tymethod.map) apply.
+    unsafe fn uri_map(&self) -> Option<&'static mut UriMap>;
+
+    /// Return a cached map, if the host supports mapping.
+    ///
+    /// If the host doesn't support the urid extension, the deprecated uri-map extension is used
+    /// instead. Like for
+    /// [`CachedMap::try_from_features`](struct.CachedMap.html#method.try_from_features), the
+    /// features have to be the ones the host passed to `instantiate`.
+    fn cached_map(&self) -> Option<CachedMap> {
+        unsafe {
+            self.map()
+                .map(CachedMap::new)
+                .or_else(|| self.uri_map().map(CachedMap::from_uri_map))
+        }
+    }
+
+    /// Return a cached unmap, if the host supports unmapping.
+    fn cached_unmap(&self) -> Option<CachedUnmap> {
+        unsafe { self.unmap() }.map(CachedUnmap::new)
+    }
+}
+
+impl<'a> UridFeatures for lv2rs_core::Features<'a> {
+    unsafe fn map(&self) -> Option<&'static mut Map> {
+        self.get::<Map>()
+    }
+
+    unsafe fn unmap(&self) -> Option<&'static mut Unmap> {
+        self.get::<Unmap>()
+    }
+
+    unsafe fn uri_map(&self) -> Option<&'static mut UriMap> {
+        self.get::<UriMap>()
+    }
+}
+
+/// The host feature a cached map is backed by.
+enum RawMap {
+    Urid(&'static mut Map),
+    UriMap(&'static mut UriMap),
+}
+
+impl RawMap {
+    fn map(&mut self, uri: &CStr) -> URID {
+        match self {
+            RawMap::Urid(map) => map.map(uri),
+            RawMap::UriMap(uri_map) => uri_map.map(uri),
+        }
+    }
+}
+
+/// The reasons why a URI could not be mapped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MapError {
+    /// The host failed to map the URI and returned 0.
+    Failed,
+    /// The URI contains a null byte and can not be passed to the host.
+    InvalidUri,
+}
+
+impl core::fmt::Display for MapError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            MapError::Failed => write!(f, "the host failed to map the URI"),
+            MapError::InvalidUri => write!(f, "the URI contains a null byte"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MapError {}
+
 /// Cached version of [Map](struct.Map.html)
+///
+/// A cached map can also be backed by the deprecated uri-map extension; See the
+/// [`uri_map`](uri_map/index.html) module.
 pub struct CachedMap {
-    raw: &'static mut Map,
-    cache: HashMap<&'static CStr, URID>,
+    raw: RawMap,
+    cache: BTreeMap<&'static CStr, URID>,
+    str_cache: BTreeMap<String, (URID, u64)>,
+    str_capacity: Option<usize>,
+    clock: u64,
+    stats: CacheStats,
+}
+
+/// Statistics on the usage of a [`CachedMap`](struct.CachedMap.html).
+///
+/// Only calls of the mapping methods are counted; Lookups with `get` are not.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// The number of mappings that were answered from the cache.
+    pub hits: u64,
+    /// The number of mappings that had to be requested from the host.
+    pub misses: u64,
+    /// The number of mappings that were evicted from the cache of string slice URIs.
+    pub evictions: u64,
 }
 
 impl CachedMap {
     /// Create a new cached map from a mutable map reference.
     pub fn new(raw: &'static mut Map) -> CachedMap {
+        Self::with_raw(RawMap::Urid(raw))
+    }
+
+    /// Create a new cached map from the deprecated uri-map feature.
+    pub fn from_uri_map(raw: &'static mut UriMap) -> CachedMap {
+        Self::with_raw(RawMap::UriMap(raw))
+    }
+
+    fn with_raw(raw: RawMap) -> CachedMap {
         Self {
-            raw: raw,
-            cache: HashMap::new(),
+            raw,
+            cache: BTreeMap::new(),
+            str_cache: BTreeMap::new(),
+            str_capacity: None,
+            clock: 0,
+            stats: CacheStats::default(),
         }
     }
 
     /// Try to find the mapping feature in the features map.
     ///
-    /// If this function returns `None` if the host does not support mapping.
-    pub fn try_from_features(features: &core::FeaturesList) -> Option<Self> {
-        let raw_map = Map::try_from_features(features)?;
-        Some(Self::new(raw_map))
+    /// If the host does not support the urid extension, the deprecated uri-map extension is used
+    /// instead. This function returns `None` if the host supports neither.
+    pub fn try_from_features(features: &lv2rs_core::FeaturesList) -> Option<Self> {
+        match Map::try_from_features(features) {
+            Some(raw_map) => Some(Self::new(raw_map)),
+            None => UriMap::try_from_features(features).map(Self::from_uri_map),
+        }
     }
 
     /// Return a reference to the cache.
-    pub fn cache(&self) -> &HashMap<&'static CStr, URID> {
+    pub fn cache(&self) -> &BTreeMap<&'static CStr, URID> {
         &self.cache
     }
 
@@ -168,18 +348,385 @@ impl CachedMap {
     ///
     /// The same rules from [Map.map](struct.Map.html#method.map) apply. Additionally, this function
     /// will cache the mappings and short-cut if a requested mapping is already cached.
-    pub fn map(&mut self, uri: &'static CStr) -> URID {
+    ///
+    /// If the host fails to map the URI, 0 is returned, which is not a valid URID. Failures are
+    /// cached too, so that the host isn't asked again; Use [`try_map`](#method.try_map) to handle
+    /// them and [`forget_failures`](#method.forget_failures) to retry them.
+    ///
+    /// The URI is either a `&'static CStr` or a [`Uri`](../lv2rs_core/uris/struct.Uri.html), like
+    /// the URI constants of the LV2 crates.
+    pub fn map<S>(&mut self, uri: S) -> URID
+    where
+        &'static CStr: From<S>,
+    {
+        let uri: &'static CStr = uri.into();
         if !self.cache.contains_key(&uri) {
             let urid = self.raw.map(uri);
             self.cache.insert(uri.clone(), urid);
+            self.stats.misses += 1;
+        } else {
+            self.stats.hits += 1;
         }
         *(self.cache.get(&uri).unwrap())
     }
+
+    /// Map a URI to a URID and report if the host failed to do so.
+    ///
+    /// This does the same as [`map`](#method.map), but returns `Err(MapError::Failed)` instead of
+    /// 0 if the host failed, which lets plugins degrade gracefully, for example by disabling a
+    /// feature that needs the URID.
+    pub fn try_map(&mut self, uri: &'static CStr) -> Result<URID, MapError> {
+        match self.map(uri) {
+            0 => Err(MapError::Failed),
+            urid => Ok(urid),
+        }
+    }
+
+    /// Remove all cached failures, so that the host is asked again the next time these URIs are
+    /// mapped.
+    pub fn forget_failures(&mut self) {
+        self.cache.retain(|_, urid| *urid != 0);
+        self.str_cache.retain(|_, (urid, _)| *urid != 0);
+    }
+
+    /// Map a URI, given as a string slice, to a URID.
+    ///
+    /// This does the same as [`map`](#method.map), but doesn't require a null-terminated,
+    /// static string. Since the URI has to be copied and terminated before it can be passed to the
+    /// host, this method allocates memory when a URI is mapped for the first time. Mappings of
+    /// string slices are cached separately.
+    ///
+    /// An `Err` is returned if the URI contains a null byte or if the host failed to map it, like
+    /// in [`try_map`](#method.try_map). If you map constant URIs, you should use the
+    /// [`uri`](macro.uri.html) macro and [`map`](#method.map) instead.
+    ///
+    /// Since these URIs are often created at runtime, for example from file paths, the number of
+    /// cached string slice URIs can be limited with [`set_capacity`](#method.set_capacity).
+    pub fn map_str(&mut self, uri: &str) -> Result<URID, MapError> {
+        let urid = self.map_str_cached(uri)?;
+        if urid == 0 {
+            Err(MapError::Failed)
+        } else {
+            Ok(urid)
+        }
+    }
+
+    fn map_str_cached(&mut self, uri: &str) -> Result<URID, MapError> {
+        self.clock += 1;
+        if let Some((urid, last_use)) = self.str_cache.get_mut(uri) {
+            *last_use = self.clock;
+            self.stats.hits += 1;
+            return Ok(*urid);
+        }
+        let c_uri = CString::new(uri).map_err(|_| MapError::InvalidUri)?;
+        let urid = self.raw.map(c_uri.as_c_str());
+        self.stats.misses += 1;
+        if let Some(capacity) = self.str_capacity {
+            self.evict(capacity.saturating_sub(1));
+            if capacity == 0 {
+                return Ok(urid);
+            }
+        }
+        self.str_cache.insert(uri.to_owned(), (urid, self.clock));
+        Ok(urid)
+    }
+
+    /// Evict the least recently used string slice URIs until at most `len` are left.
+    fn evict(&mut self, len: usize) {
+        while self.str_cache.len() > len {
+            let oldest = self
+                .str_cache
+                .iter()
+                .min_by_key(|(_, (_, last_use))| *last_use)
+                .map(|(uri, _)| uri.clone())
+                .unwrap();
+            self.str_cache.remove(&oldest);
+            self.stats.evictions += 1;
+        }
+    }
+
+    /// Limit the number of cached string slice URIs.
+    ///
+    /// If the limit is reached, [`map_str`](#method.map_str) evicts the least recently used
+    /// mapping before it caches a new one. Evicted URIs are still mapped by the host and are
+    /// mapped to the same URID again, but this requires another call to the host. `None` removes
+    /// the limit, which is the default. If the cache currently holds more mappings than the new
+    /// limit, the least recently used ones are evicted immediately.
+    ///
+    /// Mappings of static URIs, created with [`map`](#method.map), are never evicted since they are
+    /// needed for real-time lookups and their number is bounded by the plugin's code.
+    pub fn set_capacity(&mut self, capacity: Option<usize>) {
+        self.str_capacity = capacity;
+        if let Some(capacity) = capacity {
+            self.evict(capacity);
+        }
+    }
+
+    /// Return the maximal number of cached string slice URIs, if it is limited.
+    pub fn capacity(&self) -> Option<usize> {
+        self.str_capacity
+    }
+
+    /// Return the number of cached mappings, of both static and string slice URIs.
+    pub fn len(&self) -> usize {
+        self.cache.len() + self.str_cache.len()
+    }
+
+    /// Return `true` if no mappings are cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the usage statistics of the cache.
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Reset the usage statistics of the cache.
+    pub fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+
+    /// Map all given URIs in advance.
+    ///
+    /// Call this method in `instantiate` with all URIs you need in `run`, so that you can look
+    /// them up with [`get`](#method.get) later on.
+    pub fn premap(&mut self, uris: &[&'static CStr]) {
+        for uri in uris {
+            self.map(*uri);
+        }
+    }
+
+    /// Map many URIs at once.
+    ///
+    /// The returned URIDs are in the same order as the URIs. This is a convenience for plugins
+    /// that need many URIDs at startup.
+    pub fn map_all(&mut self, uris: &[&'static CStr]) -> Vec<URID> {
+        let mut urids = vec![0; uris.len()];
+        self.map_into(uris, &mut urids);
+        urids
+    }
+
+    /// Map many URIs at once and write the URIDs to a slice.
+    ///
+    /// This does the same as [`map_all`](#method.map_all), but writes the URIDs to an existing
+    /// slice, for example an array, instead of allocating a vector.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slices have different lengths.
+    pub fn map_into(&mut self, uris: &[&'static CStr], urids: &mut [URID]) {
+        assert_eq!(uris.len(), urids.len());
+        for (uri, urid) in uris.iter().zip(urids.iter_mut()) {
+            *urid = self.map(*uri);
+        }
+    }
+
+    /// Look up the URID of an already mapped URI.
+    ///
+    /// In contrast to [`map`](#method.map), this method never calls the host and never allocates
+    /// memory, which makes it safe to use in the audio thread. If the URI hasn't been mapped yet
+    /// or if the host failed to map it, `None` is returned.
+    pub fn get(&self, uri: &CStr) -> Option<URID> {
+        self.cache.get(uri).cloned().filter(|urid| *urid != 0)
+    }
+}
+
+#[doc(hidden)]
+pub use lv2rs_core::uris::Uri as __Uri;
+
+/// Check a null-terminated URI at compile time.
+///
+/// This function is used by the [`uri`](macro.uri.html) macro and is not part of the public API.
+#[doc(hidden)]
+pub const fn validate_uri(uri: &'static [u8]) -> &'static [u8] {
+    if uri.len() < 2 {
+        panic!("URIs must not be empty");
+    }
+    let mut index = 0;
+    let mut has_scheme = false;
+    while index < uri.len() - 1 {
+        if uri[index] == 0 {
+            panic!("URIs must not contain null bytes");
+        }
+        if uri[index] == b':' {
+            has_scheme = true;
+        }
+        index += 1;
+    }
+    if !has_scheme {
+        panic!("URIs must have a scheme");
+    }
+    uri
+}
+
+/// Create a [`Uri`](../lv2rs_core/uris/struct.Uri.html) from a URI string literal.
+///
+/// The literal is null-terminated and checked at compile time: It must not be empty, must not
+/// contain null bytes and must have a scheme, like `http:`. Since the result is a constant, it can
+/// be used to define URI constants:
+///
+///     extern crate lv2rs_core as core;
+///     #[macro_use]
+///     extern crate lv2rs_urid as urid;
+///
+///     use core::uris::Uri;
+///
+///     const GAIN_URI: Uri = uri!("https://example.org/plugin#gain");
+///
+///     assert_eq!(b"https://example.org/plugin#gain", GAIN_URI.to_bytes());
+///
+/// Invalid URIs are rejected by the compiler:
+///
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate lv2rs_urid as urid;
+///
+/// const INVALID_URI: lv2rs_core::uris::Uri = uri!("no scheme");
+/// # fn main() {}
+/// ```
+#[macro_export]
+macro_rules! uri {
+    ($uri:expr) => {{
+        const URI: $crate::__Uri =
+            $crate::__Uri::new($crate::validate_uri(concat!($uri, "\0").as_bytes()));
+        URI
+    }};
+}
+
+/// Thread-safe version of [CachedMap](struct.CachedMap.html).
+///
+/// The `CachedMap` can only be used by one thread at a time, but a plugin may need to map URIs in
+/// its `run` method, in a worker thread and in its UI. A `SharedCachedMap` can be shared between
+/// these threads, for example with an `Arc`, since the cache and the calls to the host's mapping
+/// function are protected by a mutex.
+///
+/// Locking a mutex is not real-time-safe. Therefore, you should map all URIs you need in the
+/// `run` method in advance, for example in `instantiate`.
+#[cfg(feature = "std")]
+pub struct SharedCachedMap {
+    inner: Mutex<CachedMap>,
+}
+
+// The raw map is only accessed while the mutex is locked and the LV2 specification allows calling
+// the mapping function from any thread.
+#[cfg(feature = "std")]
+unsafe impl Send for SharedCachedMap {}
+#[cfg(feature = "std")]
+unsafe impl Sync for SharedCachedMap {}
+
+#[cfg(feature = "std")]
+impl SharedCachedMap {
+    /// Create a new shared map from a mutable map reference.
+    pub fn new(raw: &'static mut Map) -> Self {
+        Self::from(CachedMap::new(raw))
+    }
+
+    /// Try to find the mapping feature in the features map.
+    ///
+    /// If this function returns `None` if the host does not support mapping.
+    pub fn try_from_features(features: &lv2rs_core::FeaturesList) -> Option<Self> {
+        CachedMap::try_from_features(features).map(Self::from)
+    }
+
+    /// Map a URI to a URID.
+    ///
+    /// This does the same as [CachedMap.map](struct.CachedMap.html#method.map), but may block if
+    /// another thread is mapping at the same time.
+    pub fn map<S>(&self, uri: S) -> URID
+    where
+        &'static CStr: From<S>,
+    {
+        self.lock().map(uri)
+    }
+
+    /// Map many URIs at once.
+    ///
+    /// This does the same as [CachedMap.map_all](struct.CachedMap.html#method.map_all), but locks
+    /// the mutex only once for all URIs.
+    pub fn map_all(&self, uris: &[&'static CStr]) -> Vec<URID> {
+        self.lock().map_all(uris)
+    }
+
+    /// Map a URI to a URID and report if the host failed to do so.
+    ///
+    /// This does the same as [CachedMap.try_map](struct.CachedMap.html#method.try_map), but may
+    /// block if another thread is mapping at the same time.
+    pub fn try_map(&self, uri: &'static CStr) -> Result<URID, MapError> {
+        self.lock().try_map(uri)
+    }
+
+    /// Return the URID of a URI if it is already cached.
+    pub fn get(&self, uri: &CStr) -> Option<URID> {
+        self.lock().get(uri)
+    }
+
+    /// Return the number of cached mappings.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Return the usage statistics of the cache.
+    pub fn stats(&self) -> CacheStats {
+        self.lock().stats()
+    }
+
+    /// Return `true` if no mappings are cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Convert the shared map back into a single-threaded map, keeping the cache.
+    pub fn into_inner(self) -> CachedMap {
+        self.inner
+            .into_inner()
+            .unwrap_or_else(|error| error.into_inner())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CachedMap> {
+        // A panic while mapping can not leave the cache in an inconsistent state, so a poisoned
+        // mutex is still usable.
+        self.inner.lock().unwrap_or_else(|error| error.into_inner())
+    }
 }
+
+#[cfg(feature = "std")]
+impl From<CachedMap> for SharedCachedMap {
+    /// Share a cached map, keeping its cache.
+    fn from(map: CachedMap) -> Self {
+        Self {
+            inner: Mutex::new(map),
+        }
+    }
+}
+
 /// Cached version of [Unmap](struct.Unmap.html)
+///
+/// Every URI that was returned by the host is copied into the cache and never removed or moved
+/// again. Therefore, `unmap` only needs a shared reference and the returned URIs live as long as
+/// the cached unmap, which allows to keep many of them around at once, for example when logging
+/// every event of a sequence:
+///
+///     extern crate lv2rs_urid as urid;
+///
+///     use std::ffi::CStr;
+///     use urid::debug::DebugMap;
+///
+///     let mut debug_map = DebugMap::new();
+///     let mut cached_map = unsafe { debug_map.create_cached_map() };
+///     let cached_unmap = unsafe { debug_map.create_cached_unmap() };
+///
+///     let a_urid = cached_map.map(CStr::from_bytes_with_nul(b"urn:a\0").unwrap());
+///     let b_urid = cached_map.map(CStr::from_bytes_with_nul(b"urn:b\0").unwrap());
+///     let a_uri = cached_unmap.unmap(a_urid).unwrap();
+///     let b_uri = cached_unmap.unmap(b_urid).unwrap();
+///     assert_ne!(a_uri, b_uri);
 pub struct CachedUnmap {
     raw: &'static mut Unmap,
-    cache: HashMap<URID, CString>,
+    // The URIs are leaked boxes that are only freed when the cached unmap is dropped. Raw
+    // pointers are stored since moving a `Box` around in the map would invalidate the references
+    // that were returned by `unmap`.
+    cache: UnsafeCell<BTreeMap<URID, *mut CString>>,
 }
 
 impl CachedUnmap {
@@ -187,38 +734,77 @@ impl CachedUnmap {
     pub fn new(raw_map: &'static mut Unmap) -> Self {
         Self {
             raw: raw_map,
-            cache: HashMap::new(),
+            cache: UnsafeCell::new(BTreeMap::new()),
         }
     }
 
     /// Try to find the unmapping feature in the features map.
     ///
     /// If this function returns `None` if the host does not support unmapping.
-    pub fn try_from_features(features: &core::FeaturesList) -> Option<Self> {
+    pub fn try_from_features(features: &lv2rs_core::FeaturesList) -> Option<Self> {
         let raw_unmap = Unmap::try_from_features(features)?;
         Some(Self::new(raw_unmap))
     }
 
-    /// Return a reference to the cache.
-    pub fn cache(&self) -> &HashMap<URID, CString> {
-        &self.cache
+    /// Return a copy of the cache.
+    ///
+    /// The cache itself can't be borrowed since `unmap` may extend it at any time. Therefore, this
+    /// method collects references to all cached URIs into a new map.
+    pub fn cache(&self) -> BTreeMap<URID, &CString> {
+        let cache = unsafe { &*self.cache.get() };
+        cache
+            .iter()
+            .map(|(urid, uri)| (*urid, unsafe { &**uri }))
+            .collect()
     }
 
     /// Try to map a URID to a URI.
     ///
     /// The same rules from [Unmap.unmap](struct.Unmap.html#method.unmap) apply. Additionally, this
     /// function will cache the mappings and short-cut if a requested mapping is already cached.
-    pub fn unmap(&mut self, urid: URID) -> Option<&CString> {
-        if !self.cache.contains_key(&urid) {
-            let uri = self.raw.unmap(urid);
-            match uri {
-                Some(uri) => {
-                    let uri = CString::from(uri);
-                    self.cache.insert(urid, uri);
-                }
-                None => return None,
-            }
+    /// URIDs that the host doesn't know are not cached since they may be mapped later.
+    pub fn unmap(&self, urid: URID) -> Option<&CString> {
+        if let Some(uri) = self.get(urid) {
+            return Some(uri);
+        }
+        let uri = (self.raw.unmap)(self.raw.handle, urid);
+        if uri.is_null() {
+            return None;
+        }
+        let uri = Box::into_raw(Box::new(CString::from(unsafe { CStr::from_ptr(uri) })));
+        // The cache is only modified here and no reference to the map itself escapes this method.
+        // The strings are only freed in `drop`, which means that the returned reference stays
+        // valid even if the map is modified later on.
+        let cache = unsafe { &mut *self.cache.get() };
+        cache.insert(urid, uri);
+        Some(unsafe { &*uri })
+    }
+
+    /// Look up the URI of an already unmapped URID.
+    ///
+    /// In contrast to [`unmap`](#method.unmap), this method never calls the host and never
+    /// allocates memory, which makes it safe to use in the audio thread.
+    pub fn get(&self, urid: URID) -> Option<&CString> {
+        let cache = unsafe { &*self.cache.get() };
+        let uri = *cache.get(&urid)?;
+        Some(unsafe { &*uri })
+    }
+
+    /// Return the number of cached URIs.
+    pub fn len(&self) -> usize {
+        unsafe { &*self.cache.get() }.len()
+    }
+
+    /// Return `true` if no URIs are cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Drop for CachedUnmap {
+    fn drop(&mut self) {
+        for uri in self.cache.get_mut().values() {
+            drop(unsafe { Box::from_raw(*uri) });
         }
-        Some(self.cache.get(&urid).unwrap())
     }
 }
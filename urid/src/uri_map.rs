@@ -0,0 +1,105 @@
+//! Compatibility with the deprecated uri-map extension.
+//!
+//! Before the urid extension was published, hosts provided the uri-map extension, which serves
+//! the same purpose. Some old hosts still only support this extension. A
+//! [`CachedMap`](../struct.CachedMap.html) can be created from a uri-map feature too and then
+//! behaves just like a map that is backed by the urid extension. If you retrieve your map with
+//! [`CachedMap::try_from_features`](../struct.CachedMap.html#method.try_from_features) or
+//! [`UridFeatures::cached_map`](../trait.UridFeatures.html#method.cached_map), the uri-map feature
+//! is already used as a fallback.
+//!
+//! Note that the uri-map extension does not support unmapping.
+use crate::URID;
+use core::ffi::*;
+use lv2rs_core::uris::Uri;
+
+/// The URI of the uri-map feature.
+pub const URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/uri-map\0");
+
+/// Type to describe pointers to the callback data of the uri-map feature.
+pub type UriMapCallbackData = *mut c_void;
+
+/// Struct for mapping URIs to URIDs with the deprecated uri-map extension.
+#[repr(C)]
+pub struct UriMap {
+    /// Pointer to host-specific data.
+    pub callback_data: UriMapCallbackData,
+    /// Function that maps a URI to an ID.
+    ///
+    /// The `map` parameter is the URI of an extension whose ID space should be used, or null for
+    /// the global ID space.
+    pub uri_to_id: extern "C" fn(
+        callback_data: UriMapCallbackData,
+        map: *const c_char,
+        uri: *const c_char,
+    ) -> u32,
+}
+
+impl UriMap {
+    /// Try to find the uri-map feature in the features map.
+    ///
+    /// If this function returns `None` if the host does not support the uri-map extension.
+    pub fn try_from_features(features: &lv2rs_core::FeaturesList) -> Option<&'static mut Self> {
+        unsafe { lv2rs_core::Feature::get_feature::<Self>(features, URI.as_cstr()) }
+    }
+
+    /// Map a URI to a URID in the global ID space.
+    pub fn map(&mut self, uri: &CStr) -> URID {
+        (self.uri_to_id)(self.callback_data, core::ptr::null(), uri.as_ptr())
+    }
+}
+
+unsafe impl lv2rs_core::FeatureData for UriMap {
+    const URI: Uri = URI;
+}
+
+#[cfg(test)]
+mod test {
+    use crate::uri_map::*;
+    use crate::UridFeatures;
+
+    extern "C" fn uri_to_id(
+        callback_data: UriMapCallbackData,
+        map: *const c_char,
+        uri: *const c_char,
+    ) -> u32 {
+        assert!(map.is_null());
+        let uris = unsafe { (callback_data as *mut Vec<Vec<u8>>).as_mut() }.unwrap();
+        let uri = unsafe { CStr::from_ptr(uri) }.to_bytes().to_owned();
+        match uris.iter().position(|mapped_uri| *mapped_uri == uri) {
+            Some(index) => index as u32 + 1,
+            None => {
+                uris.push(uri);
+                uris.len() as u32
+            }
+        }
+    }
+
+    #[test]
+    fn test_fallback() {
+        let uris: &'static mut Vec<Vec<u8>> = Box::leak(Box::new(Vec::new()));
+        let uri_map: &'static UriMap = Box::leak(Box::new(UriMap {
+            callback_data: uris as *mut Vec<Vec<u8>> as *mut c_void,
+            uri_to_id,
+        }));
+        let feature: &'static lv2rs_core::Feature = Box::leak(Box::new(lv2rs_core::Feature::new(
+            URI.as_ptr(),
+            uri_map as *const UriMap as *mut c_void,
+        )));
+        let feature_list = [feature];
+        let features = lv2rs_core::Features::new(&feature_list);
+        assert!(unsafe { features.map() }.is_none());
+
+        let github_uri = CStr::from_bytes_with_nul(b"https://github.com\0").unwrap();
+        let gitlab_uri = CStr::from_bytes_with_nul(b"https://gitlab.com\0").unwrap();
+        let mut cached_map = features.cached_map().unwrap();
+        assert_eq!(1, cached_map.map(github_uri));
+        assert_eq!(2, cached_map.map(gitlab_uri));
+        assert_eq!(Ok(1), cached_map.map_str("https://github.com"));
+        assert_eq!(Some(2), cached_map.get(gitlab_uri));
+
+        let mut cached_map = crate::CachedMap::try_from_features(&feature_list).unwrap();
+        assert_eq!(2, cached_map.map(gitlab_uri));
+        assert!(features.cached_unmap().is_none());
+    }
+}
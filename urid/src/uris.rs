@@ -1,3 +1,5 @@
-pub const URI: &[u8] = b"http://lv2plug.in/ns/ext/urid\0";
-pub const MAP_URI: &[u8] = b"http://lv2plug.in/ns/ext/urid#map\0";
-pub const UNMAP_URI: &[u8] = b"http://lv2plug.in/ns/ext/urid#unmap\0";
+use lv2rs_core::uris::Uri;
+
+pub const URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/urid\0");
+pub const MAP_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/urid#map\0");
+pub const UNMAP_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/urid#unmap\0");
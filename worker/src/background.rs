@@ -0,0 +1,271 @@
+//! A fallback worker thread for hosts without the worker extension.
+use crate::interface::Worker;
+use crate::schedule::*;
+use std::cell::UnsafeCell;
+use std::mem::MaybeUninit;
+use std::os::raw::*;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+/// A lock-free queue with a single producer and a single consumer.
+struct RingBuffer<T: Copy> {
+    slots: Box<[UnsafeCell<MaybeUninit<T>>]>,
+    /// The number of values that were popped.
+    head: AtomicUsize,
+    /// The number of values that were pushed.
+    tail: AtomicUsize,
+}
+
+impl<T: Copy> RingBuffer<T> {
+    fn new(capacity: usize) -> Self {
+        let slots = (0..capacity.max(1))
+            .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+            .collect();
+        Self {
+            slots,
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+
+    /// Push a value to the queue.
+    ///
+    /// This method may only be called by the producer.
+    fn push(&self, value: T) -> Result<(), WorkerErr> {
+        let tail = self.tail.load(Ordering::Relaxed);
+        if tail.wrapping_sub(self.head.load(Ordering::Acquire)) >= self.slots.len() {
+            return Err(WorkerErr::NoSpace);
+        }
+        let slot = &self.slots[tail % self.slots.len()];
+        unsafe { (*slot.get()).as_mut_ptr().write(value) };
+        self.tail.store(tail.wrapping_add(1), Ordering::Release);
+        Ok(())
+    }
+
+    /// Pop a value from the queue.
+    ///
+    /// This method may only be called by the consumer.
+    fn pop(&self) -> Option<T> {
+        let head = self.head.load(Ordering::Relaxed);
+        if head == self.tail.load(Ordering::Acquire) {
+            return None;
+        }
+        let slot = &self.slots[head % self.slots.len()];
+        let value = unsafe { (*slot.get()).as_ptr().read() };
+        self.head.store(head.wrapping_add(1), Ordering::Release);
+        Some(value)
+    }
+}
+
+/// The state that is shared between the plugin and the worker thread.
+struct Shared<P: Worker> {
+    requests: RingBuffer<P::WorkData>,
+    responses: RingBuffer<P::ResponseData>,
+    stop: AtomicBool,
+}
+
+// The payloads are moved between the threads, just like a host would copy them.
+unsafe impl<P: Worker> Send for Shared<P> {}
+unsafe impl<P: Worker> Sync for Shared<P> {}
+
+/// A pointer to the plugin that is moved to the worker thread.
+struct PluginPointer<P>(*const P);
+
+unsafe impl<P> Send for PluginPointer<P> {}
+
+/// Push a response of the worker thread to the response queue.
+unsafe extern "C" fn respond<P: Worker>(
+    handle: RespondHandle,
+    size: u32,
+    data: *const c_void,
+) -> u32 {
+    let shared = match (handle as *const Shared<P>).as_ref() {
+        Some(shared) => shared,
+        None => return WorkerErr::Unknown as u32,
+    };
+    let result = match read_payload(size, data) {
+        Some(data) => shared.responses.push(data),
+        None => Err(WorkerErr::Unknown),
+    };
+    WorkerErr::into_status(result)
+}
+
+/// A worker thread that is owned by the plugin.
+///
+/// It takes the role of the host's worker: Work that is scheduled with
+/// [`schedule_work`](#method.schedule_work) is passed to the plugin's
+/// [`work`](trait.Worker.html#tymethod.work) method in a dedicated thread and the responses are
+/// collected until the plugin takes them with [`take_response`](#method.take_response). Both
+/// directions use lock-free queues with a fixed capacity, so scheduling and taking responses
+/// never blocks the audio thread.
+pub struct BackgroundWorker<P: Worker> {
+    shared: Arc<Shared<P>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<P: Worker> BackgroundWorker<P> {
+    /// Create a new background worker with queues for `capacity` payloads each.
+    ///
+    /// The thread is not started before [`activate`](#method.activate) is called.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                requests: RingBuffer::new(capacity),
+                responses: RingBuffer::new(capacity),
+                stop: AtomicBool::new(false),
+            }),
+            thread: None,
+        }
+    }
+
+    /// Start the worker thread for the plugin, if it isn't running yet.
+    ///
+    /// This method should be called in the plugin's `activate` method.
+    ///
+    /// This method is unsafe since the thread calls `work` with a shared reference to the plugin,
+    /// concurrently with the audio thread. The plugin must stay at the same address and has to
+    /// outlive the thread, which is stopped by [`deactivate`](#method.deactivate) or when the
+    /// background worker is dropped.
+    pub unsafe fn activate(&mut self, plugin: *const P)
+    where
+        P: 'static,
+    {
+        if self.thread.is_some() {
+            return;
+        }
+        let shared = self.shared.clone();
+        let plugin = PluginPointer(plugin);
+        self.thread = Some(std::thread::spawn(move || {
+            let plugin = plugin;
+            let handle = &*shared as *const Shared<P> as RespondHandle;
+            while !shared.stop.load(Ordering::Acquire) {
+                while let Some(data) = shared.requests.pop() {
+                    let mut response_handler = ResponseHandler::new(respond::<P>, handle);
+                    // There is no host to report errors to.
+                    let _ = (*plugin.0).work(&mut response_handler, data);
+                }
+                std::thread::park();
+            }
+        }));
+    }
+
+    /// Stop the worker thread and wait until it's finished.
+    ///
+    /// Work that was scheduled but not done yet is kept until the thread is started again.
+    pub fn deactivate(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            self.shared.stop.store(true, Ordering::Release);
+            thread.thread().unpark();
+            let _ = thread.join();
+            self.shared.stop.store(false, Ordering::Release);
+        }
+    }
+
+    /// Schedule work.
+    ///
+    /// This method may only be called from the `run` method of the plugin. It fails with
+    /// `WorkerErr::NoSpace` if the queue is full and with `WorkerErr::Unknown` if the thread
+    /// isn't running.
+    pub fn schedule_work(&mut self, data: P::WorkData) -> Result<(), WorkerErr> {
+        let thread = self.thread.as_ref().ok_or(WorkerErr::Unknown)?;
+        self.shared.requests.push(data)?;
+        thread.thread().unpark();
+        Ok(())
+    }
+
+    /// Take the next response of the worker thread, if there is one.
+    pub fn take_response(&mut self) -> Option<P::ResponseData> {
+        self.shared.responses.pop()
+    }
+}
+
+impl<P: Worker> Drop for BackgroundWorker<P> {
+    fn drop(&mut self) {
+        self.deactivate();
+    }
+}
+
+/// A scheduler that uses the host's worker if possible and a background worker otherwise.
+///
+/// Plugins that use a scheduler work with every host: They schedule work with
+/// [`schedule_work`](#method.schedule_work), forward `activate` and `deactivate` to the
+/// scheduler and deliver the responses of [`take_response`](#method.take_response) to their
+/// [`work_response`](trait.Worker.html#tymethod.work_response) method in `run`. With
+/// the host's worker, there are no responses to take, since the host delivers them itself.
+pub enum Scheduler<'a, P: Worker> {
+    /// The host's schedule feature.
+    Host(Schedule<'a, P>),
+    /// A worker thread of the plugin.
+    Background(BackgroundWorker<P>),
+}
+
+impl<P: Worker> Scheduler<'static, P> {
+    /// Use the schedule feature of the host, or create a background worker with queues for
+    /// `capacity` payloads if the host doesn't provide it.
+    pub fn from_features(features: Option<&core::FeaturesList>, capacity: usize) -> Self {
+        match features.and_then(Schedule::try_from_features) {
+            Some(schedule) => Scheduler::Host(schedule),
+            None => Scheduler::Background(BackgroundWorker::new(capacity)),
+        }
+    }
+}
+
+impl<'a, P: Worker> Scheduler<'a, P> {
+    /// Start the background worker, if there is one.
+    ///
+    /// This method is unsafe for the same reasons as
+    /// [`BackgroundWorker::activate`](struct.BackgroundWorker.html#method.activate).
+    pub unsafe fn activate(&mut self, plugin: *const P)
+    where
+        P: 'static,
+    {
+        if let Scheduler::Background(worker) = self {
+            worker.activate(plugin);
+        }
+    }
+
+    /// Stop the background worker, if there is one.
+    pub fn deactivate(&mut self) {
+        if let Scheduler::Background(worker) = self {
+            worker.deactivate();
+        }
+    }
+
+    /// Schedule work.
+    ///
+    /// This method may only be called from the `run` method of the plugin.
+    pub fn schedule_work(&mut self, data: P::WorkData) -> Result<(), WorkerErr> {
+        match self {
+            Scheduler::Host(schedule) => schedule.schedule_work(data),
+            Scheduler::Background(worker) => worker.schedule_work(data),
+        }
+    }
+
+    /// Take the next response of the background worker, if there is one.
+    pub fn take_response(&mut self) -> Option<P::ResponseData> {
+        match self {
+            Scheduler::Host(_) => None,
+            Scheduler::Background(worker) => worker.take_response(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::background::*;
+
+    #[test]
+    fn test_ring_buffer() {
+        let buffer = RingBuffer::<u32>::new(2);
+        assert_eq!(None, buffer.pop());
+        buffer.push(1).unwrap();
+        buffer.push(2).unwrap();
+        assert_eq!(Err(WorkerErr::NoSpace), buffer.push(3));
+        assert_eq!(Some(1), buffer.pop());
+        buffer.push(3).unwrap();
+        assert_eq!(Some(2), buffer.pop());
+        assert_eq!(Some(3), buffer.pop());
+        assert_eq!(None, buffer.pop());
+    }
+}
@@ -0,0 +1,118 @@
+//! The worker interface and the trait to implement it.
+use crate::schedule::*;
+use std::os::raw::*;
+
+/// A plugin that schedules work in a separate thread.
+///
+/// The plugin schedules [`WorkData`](#associatedtype.WorkData) with a
+/// [`Schedule`](struct.Schedule.html) in its `run` method. The host calls [`work`](#tymethod.work)
+/// with this data in a worker thread, where the plugin may block, allocate memory or access the
+/// disk. Results are sent back with a [`ResponseHandler`](struct.ResponseHandler.html) and passed
+/// to [`work_response`](#tymethod.work_response) in the audio thread.
+///
+/// Since `work` may be called concurrently with `run`, it only takes a shared reference to the
+/// plugin. `work_response` and `end_run` are called in the audio thread, just like `run`.
+///
+/// The payloads are copied by the host, which is why they have to be `Copy`. Use owned pointers,
+/// like the raw pointer of a leaked `Box`, to pass more complex data.
+pub trait Worker: core::Plugin {
+    /// The data that is sent to the worker.
+    type WorkData: Copy + 'static;
+    /// The data that is sent back to the plugin.
+    type ResponseData: Copy + 'static;
+
+    /// Do the scheduled work.
+    fn work(
+        &self,
+        response_handler: &mut ResponseHandler<Self>,
+        data: Self::WorkData,
+    ) -> Result<(), WorkerErr>;
+
+    /// Handle a response of the worker.
+    fn work_response(&mut self, data: Self::ResponseData) -> Result<(), WorkerErr>;
+
+    /// Called at the end of every cycle, after all responses were delivered.
+    fn end_run(&mut self) -> Result<(), WorkerErr> {
+        Ok(())
+    }
+}
+
+/// The raw worker interface.
+///
+/// An instance of this struct, created with [`new`](#method.new), has to be returned by the
+/// plugin's `extension_data` function for the [interface URI](uris/constant.INTERFACE_URI.html).
+#[repr(C)]
+pub struct WorkerInterface {
+    /// Do work for an instance.
+    pub work: unsafe extern "C" fn(
+        instance: core::Handle,
+        respond: RespondFunction,
+        handle: RespondHandle,
+        size: u32,
+        data: *const c_void,
+    ) -> u32,
+    /// Deliver a response to an instance.
+    pub work_response:
+        unsafe extern "C" fn(instance: core::Handle, size: u32, body: *const c_void) -> u32,
+    /// Notify an instance that the cycle is over.
+    pub end_run: Option<unsafe extern "C" fn(instance: core::Handle) -> u32>,
+}
+
+impl core::ExtensionData for WorkerInterface {}
+
+impl WorkerInterface {
+    /// Create the worker interface for a plugin type.
+    pub const fn new<P: Worker>() -> Self {
+        Self {
+            work: work::<P>,
+            work_response: work_response::<P>,
+            end_run: Some(end_run::<P>),
+        }
+    }
+}
+
+unsafe extern "C" fn work<P: Worker>(
+    instance: core::Handle,
+    respond: RespondFunction,
+    handle: RespondHandle,
+    size: u32,
+    data: *const c_void,
+) -> u32 {
+    let data = match read_payload(size, data) {
+        Some(data) => data,
+        None => return WorkerErr::Unknown as u32,
+    };
+    let mut response_handler = ResponseHandler::new(respond, handle);
+    core::guard(
+        instance,
+        "work",
+        WorkerErr::Unknown as u32,
+        |plugin: &mut P| WorkerErr::into_status(plugin.work(&mut response_handler, data)),
+    )
+}
+
+unsafe extern "C" fn work_response<P: Worker>(
+    instance: core::Handle,
+    size: u32,
+    body: *const c_void,
+) -> u32 {
+    let data = match read_payload(size, body) {
+        Some(data) => data,
+        None => return WorkerErr::Unknown as u32,
+    };
+    core::guard(
+        instance,
+        "work_response",
+        WorkerErr::Unknown as u32,
+        |plugin: &mut P| WorkerErr::into_status(plugin.work_response(data)),
+    )
+}
+
+unsafe extern "C" fn end_run<P: Worker>(instance: core::Handle) -> u32 {
+    core::guard(
+        instance,
+        "end_run",
+        WorkerErr::Unknown as u32,
+        |plugin: &mut P| WorkerErr::into_status(plugin.end_run()),
+    )
+}
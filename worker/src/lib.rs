@@ -0,0 +1,92 @@
+//! Scheduling work in a non-real-time thread.
+//!
+//! This crate implements the [LV2 worker extension](http://lv2plug.in/ns/ext/worker/worker.html):
+//! Plugins must not block, allocate memory or access the disk in their `run` method. Instead,
+//! they can schedule such work with the host's [`Schedule`](struct.Schedule.html) feature. The
+//! host then calls the plugin's [`work`](trait.Worker.html#tymethod.work) method in a separate
+//! thread, which sends its results back to the plugin with a
+//! [`ResponseHandler`](struct.ResponseHandler.html). The payloads of both directions are typed by
+//! the [`Worker`](trait.Worker.html) trait.
+//!
+//! The plugin has to return a [`WorkerInterface`](struct.WorkerInterface.html) for the
+//! [interface URI](uris/constant.INTERFACE_URI.html) in its `extension_data` function:
+//!
+//!     extern crate lv2rs_core as core;
+//!     extern crate lv2rs_worker as worker;
+//!
+//!     use std::ffi::CStr;
+//!     use worker::*;
+//!
+//!     struct Sampler {
+//!         schedule: Schedule<'static, Sampler>,
+//!         sample: Option<Box<[f32]>>,
+//!         requested: bool,
+//!     }
+//!
+//!     impl core::Plugin for Sampler {
+//!         fn instantiate(
+//!             _context: &core::InstantiationContext,
+//!             features: Option<&core::FeaturesList>,
+//!         ) -> Result<Self, core::InstantiateError> {
+//!             Ok(Self {
+//!                 schedule: features
+//!                     .and_then(Schedule::try_from_features)
+//!                     .ok_or_else(core::InstantiateError::missing_feature::<WorkerSchedule>)?,
+//!                 sample: None,
+//!                 requested: false,
+//!             })
+//!         }
+//!
+//!         fn connect_port(&mut self, _port: u32, _data: *mut ()) {}
+//!
+//!         fn run(&mut self, _context: &mut core::RunContext) {
+//!             if !self.requested {
+//!                 // Load a sample with 44100 frames.
+//!                 self.requested = self.schedule.schedule_work(44100).is_ok();
+//!             }
+//!         }
+//!
+//!         fn extension_data(uri: &CStr) -> Option<&'static core::ExtensionData> {
+//!             core::extension_data!(uri, [(uris::INTERFACE_URI, &WORKER_INTERFACE)])
+//!         }
+//!     }
+//!
+//!     impl Worker for Sampler {
+//!         type WorkData = usize;
+//!         type ResponseData = *mut [f32];
+//!
+//!         fn work(
+//!             &self,
+//!             response_handler: &mut ResponseHandler<Self>,
+//!             n_frames: usize,
+//!         ) -> Result<(), WorkerErr> {
+//!             let sample = vec![0.0; n_frames].into_boxed_slice();
+//!             response_handler.respond(Box::into_raw(sample))
+//!         }
+//!
+//!         fn work_response(&mut self, sample: *mut [f32]) -> Result<(), WorkerErr> {
+//!             // A real plugin would send the old sample back to the worker to free it there.
+//!             self.sample = Some(unsafe { Box::from_raw(sample) });
+//!             Ok(())
+//!         }
+//!     }
+//!
+//!     static WORKER_INTERFACE: WorkerInterface = WorkerInterface::new::<Sampler>();
+//!
+//!     # fn main() {}
+//!
+//! Hosts that don't support the worker extension don't provide the schedule feature. Plugins
+//! that should run there anyway can use a [`Scheduler`](enum.Scheduler.html) instead, which falls
+//! back to a [`BackgroundWorker`](struct.BackgroundWorker.html): A thread owned by the plugin
+//! that calls the plugin's `work` method and queues the responses until the plugin takes them
+//! in `run`.
+extern crate lv2rs_core as core;
+
+mod background;
+mod interface;
+mod schedule;
+pub mod uris;
+
+pub use background::*;
+pub use interface::*;
+pub use schedule::*;
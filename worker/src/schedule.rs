@@ -0,0 +1,185 @@
+//! Scheduling work and responding to it.
+use crate::interface::Worker;
+use crate::uris;
+use core::uris::Uri;
+use std::marker::PhantomData;
+use std::os::raw::*;
+
+/// Type to describe pointers to the host's schedule handles.
+pub type ScheduleHandle = *mut c_void;
+
+/// Type to describe pointers to the host's respond handles.
+pub type RespondHandle = *mut c_void;
+
+/// The host's function to send a response from the worker to the plugin.
+pub type RespondFunction =
+    unsafe extern "C" fn(handle: RespondHandle, size: u32, data: *const c_void) -> u32;
+
+/// Errors that may occur when scheduling work or sending responses.
+///
+/// The discriminants are the status codes of the LV2 worker extension.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WorkerErr {
+    /// An unknown error occurred.
+    Unknown = 1,
+    /// The host or the plugin ran out of space for the data.
+    NoSpace = 2,
+}
+
+impl WorkerErr {
+    /// Interpret a status code of the worker extension.
+    pub fn from_status(status: u32) -> Result<(), WorkerErr> {
+        match status {
+            0 => Ok(()),
+            2 => Err(WorkerErr::NoSpace),
+            _ => Err(WorkerErr::Unknown),
+        }
+    }
+
+    /// Convert a result to a status code of the worker extension.
+    pub fn into_status(result: Result<(), WorkerErr>) -> u32 {
+        match result {
+            Ok(()) => 0,
+            Err(error) => error as u32,
+        }
+    }
+}
+
+/// The schedule feature.
+#[repr(C)]
+pub struct WorkerSchedule {
+    /// Pointer to a host-specific handle.
+    pub handle: ScheduleHandle,
+    /// Request the host to call the worker with the given data.
+    pub schedule_work:
+        unsafe extern "C" fn(handle: ScheduleHandle, size: u32, data: *const c_void) -> u32,
+}
+
+unsafe impl core::FeatureData for WorkerSchedule {
+    const URI: Uri = uris::SCHEDULE_URI;
+}
+
+/// Typed getters for the worker features.
+///
+/// This trait is implemented for [`Features`](../lv2rs_core/struct.Features.html) and lets you
+/// retrieve the schedule feature without naming its URI:
+///
+///     extern crate lv2rs_core as core;
+///     extern crate lv2rs_worker as worker;
+///
+///     use worker::*;
+///
+///     fn get_schedule<P: Worker>(features: &core::FeaturesList) -> Option<Schedule<'static, P>> {
+///         core::Features::new(features).schedule()
+///     }
+pub trait WorkerFeatures {
+    /// Return the schedule feature, if the host supports it.
+    ///
+    /// # Safety
+    ///
+    /// The same requirements as for
+    /// [`Features::get`](../lv2rs_core/struct.Features.html#method.get) apply: The feature has to
+    /// outlive the reference, which is the case for the features the host passes to
+    /// `instantiate`.
+    unsafe fn worker_schedule(&self) -> Option<&'static WorkerSchedule>;
+
+    /// Return a schedule for the work of a plugin, if the host supports the worker extension.
+    ///
+    /// Like for [`Schedule::try_from_features`](struct.Schedule.html#method.try_from_features),
+    /// the features have to be the ones the host passed to `instantiate`.
+    fn schedule<P: Worker>(&self) -> Option<Schedule<'static, P>> {
+        unsafe { self.worker_schedule() }.map(Schedule::new)
+    }
+}
+
+impl<'a> WorkerFeatures for core::Features<'a> {
+    unsafe fn worker_schedule(&self) -> Option<&'static WorkerSchedule> {
+        self.get::<WorkerSchedule>().map(|schedule| &*schedule)
+    }
+}
+
+/// View the bytes of a payload.
+fn payload_bytes<T: Copy>(value: &T) -> (u32, *const c_void) {
+    (
+        std::mem::size_of::<T>() as u32,
+        value as *const T as *const c_void,
+    )
+}
+
+/// Read a payload that was copied by the host.
+///
+/// `None` is returned if the size of the data doesn't match the size of the payload type.
+///
+/// This function is unsafe since the data pointer has to point to `size` readable bytes.
+pub(crate) unsafe fn read_payload<T: Copy>(size: u32, data: *const c_void) -> Option<T> {
+    if data.is_null() || size as usize != std::mem::size_of::<T>() {
+        return None;
+    }
+    Some(std::ptr::read_unaligned(data as *const T))
+}
+
+/// A safe wrapper around the schedule feature.
+///
+/// It can only schedule the [`WorkData`](trait.Worker.html#associatedtype.WorkData) of the
+/// plugin, which is copied by the host and passed to the plugin's
+/// [`work`](trait.Worker.html#tymethod.work) method in the worker thread.
+pub struct Schedule<'a, P: Worker> {
+    schedule: &'a WorkerSchedule,
+    phantom: PhantomData<P>,
+}
+
+impl<'a, P: Worker> Schedule<'a, P> {
+    /// Wrap a reference to the schedule feature.
+    pub fn new(schedule: &'a WorkerSchedule) -> Self {
+        Self {
+            schedule,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Try to find the schedule feature in the features list.
+    ///
+    /// `None` is returned if the host does not support the worker extension.
+    pub fn try_from_features(features: &core::FeaturesList) -> Option<Schedule<'static, P>> {
+        core::Features::new(features).schedule()
+    }
+
+    /// Schedule work.
+    ///
+    /// This method may only be called from the `run` method of the plugin. It fails with
+    /// `WorkerErr::NoSpace` if the host can't queue the data.
+    pub fn schedule_work(&mut self, data: P::WorkData) -> Result<(), WorkerErr> {
+        let (size, data) = payload_bytes(&data);
+        let status = unsafe { (self.schedule.schedule_work)(self.schedule.handle, size, data) };
+        WorkerErr::from_status(status)
+    }
+}
+
+/// A handle to send responses from the worker to the plugin.
+///
+/// The [`ResponseData`](trait.Worker.html#associatedtype.ResponseData) passed to
+/// [`respond`](#method.respond) is copied by the host and passed to the plugin's
+/// [`work_response`](trait.Worker.html#tymethod.work_response) method in the audio thread.
+pub struct ResponseHandler<P: Worker + ?Sized> {
+    respond: RespondFunction,
+    handle: RespondHandle,
+    phantom: PhantomData<P>,
+}
+
+impl<P: Worker + ?Sized> ResponseHandler<P> {
+    /// Create a new response handler from the host's function and handle.
+    pub fn new(respond: RespondFunction, handle: RespondHandle) -> Self {
+        Self {
+            respond,
+            handle,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Send a response to the plugin.
+    pub fn respond(&mut self, data: P::ResponseData) -> Result<(), WorkerErr> {
+        let (size, data) = payload_bytes(&data);
+        let status = unsafe { (self.respond)(self.handle, size, data) };
+        WorkerErr::from_status(status)
+    }
+}
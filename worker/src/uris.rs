@@ -0,0 +1,6 @@
+//! URIs of the LV2 worker extension.
+use core::uris::Uri;
+
+pub const URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/worker\0");
+pub const INTERFACE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/worker#interface\0");
+pub const SCHEDULE_URI: Uri = Uri::new(b"http://lv2plug.in/ns/ext/worker#schedule\0");
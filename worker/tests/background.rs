@@ -0,0 +1,100 @@
+extern crate lv2rs_core as core;
+extern crate lv2rs_worker as worker;
+
+use core::Plugin;
+use std::ffi::CStr;
+use std::time::{Duration, Instant};
+use worker::*;
+
+/// A plugin that lets the worker sum up numbers, with or without the host's worker.
+struct SumPlugin {
+    scheduler: Scheduler<'static, SumPlugin>,
+    sums: Vec<u64>,
+}
+
+impl core::Plugin for SumPlugin {
+    fn instantiate(
+        _context: &core::InstantiationContext,
+        features: Option<&core::FeaturesList>,
+    ) -> Result<Self, core::InstantiateError> {
+        Ok(Self {
+            scheduler: Scheduler::from_features(features, 1),
+            sums: Vec::new(),
+        })
+    }
+
+    fn connect_port(&mut self, _port: u32, _data: *mut ()) {}
+
+    fn activate(&mut self) {
+        let plugin = self as *const Self;
+        unsafe { self.scheduler.activate(plugin) };
+    }
+
+    fn run(&mut self, context: &mut core::RunContext) {
+        while let Some(response) = self.scheduler.take_response() {
+            self.work_response(response).unwrap();
+        }
+        if context.n_samples() > 0 {
+            self.scheduler.schedule_work(context.n_samples()).unwrap();
+        }
+    }
+
+    fn deactivate(&mut self) {
+        self.scheduler.deactivate();
+    }
+}
+
+impl Worker for SumPlugin {
+    type WorkData = u32;
+    type ResponseData = u64;
+
+    fn work(
+        &self,
+        response_handler: &mut ResponseHandler<Self>,
+        data: u32,
+    ) -> Result<(), WorkerErr> {
+        response_handler.respond((0..=u64::from(data)).sum())
+    }
+
+    fn work_response(&mut self, data: u64) -> Result<(), WorkerErr> {
+        self.sums.push(data);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_background_worker() {
+    let descriptor = core::Descriptor::new::<SumPlugin>(b"http://example.org/SumPlugin\0");
+    let bundle_path = CStr::from_bytes_with_nul(b"/\0").unwrap();
+    let context = core::InstantiationContext::new(&descriptor, 44100.0, bundle_path);
+    let mut plugin = Box::new(SumPlugin::instantiate(&context, None).unwrap());
+    match plugin.scheduler {
+        Scheduler::Background(_) => (),
+        Scheduler::Host(_) => panic!("there is no schedule feature"),
+    }
+
+    // Work can't be scheduled before the plugin is activated.
+    assert_eq!(Err(WorkerErr::Unknown), plugin.scheduler.schedule_work(4));
+
+    plugin.activate();
+    plugin.run(&mut core::RunContext::new(4, 0, 44100.0, None));
+    let start = Instant::now();
+    while plugin.sums.is_empty() {
+        assert!(start.elapsed() < Duration::from_secs(10));
+        std::thread::sleep(Duration::from_millis(1));
+        plugin.run(&mut core::RunContext::new(0, 0, 44100.0, None));
+    }
+    assert_eq!(vec![10], plugin.sums);
+
+    // Restarting the thread.
+    plugin.deactivate();
+    plugin.activate();
+    plugin.run(&mut core::RunContext::new(3, 0, 44100.0, None));
+    while plugin.sums.len() < 2 {
+        assert!(start.elapsed() < Duration::from_secs(10));
+        std::thread::sleep(Duration::from_millis(1));
+        plugin.run(&mut core::RunContext::new(0, 0, 44100.0, None));
+    }
+    assert_eq!(vec![10, 6], plugin.sums);
+    plugin.deactivate();
+}
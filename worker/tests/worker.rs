@@ -0,0 +1,182 @@
+extern crate lv2rs_core as core;
+extern crate lv2rs_worker as worker;
+
+use std::collections::VecDeque;
+use std::ffi::CStr;
+use std::os::raw::*;
+use std::ptr::{null, null_mut};
+use worker::*;
+
+/// A plugin that lets the worker sum up numbers.
+struct SumPlugin {
+    schedule: Schedule<'static, SumPlugin>,
+    sums: Vec<u64>,
+    ended_runs: u32,
+}
+
+impl core::Plugin for SumPlugin {
+    fn instantiate(
+        _context: &core::InstantiationContext,
+        features: Option<&core::FeaturesList>,
+    ) -> Result<Self, core::InstantiateError> {
+        Ok(Self {
+            schedule: features
+                .and_then(Schedule::try_from_features)
+                .ok_or_else(core::InstantiateError::missing_feature::<WorkerSchedule>)?,
+            sums: Vec::new(),
+            ended_runs: 0,
+        })
+    }
+
+    fn connect_port(&mut self, _port: u32, _data: *mut ()) {}
+
+    fn run(&mut self, context: &mut core::RunContext) {
+        self.schedule.schedule_work(context.n_samples()).unwrap();
+    }
+
+    fn extension_data(uri: &CStr) -> Option<&'static dyn core::ExtensionData> {
+        core::extension_data!(uri, [(worker::uris::INTERFACE_URI, &WORKER_INTERFACE)])
+    }
+}
+
+impl Worker for SumPlugin {
+    type WorkData = u32;
+    type ResponseData = u64;
+
+    fn work(
+        &self,
+        response_handler: &mut ResponseHandler<Self>,
+        data: u32,
+    ) -> Result<(), WorkerErr> {
+        response_handler.respond((0..=u64::from(data)).sum())
+    }
+
+    fn work_response(&mut self, data: u64) -> Result<(), WorkerErr> {
+        self.sums.push(data);
+        Ok(())
+    }
+
+    fn end_run(&mut self) -> Result<(), WorkerErr> {
+        self.ended_runs += 1;
+        Ok(())
+    }
+}
+
+static WORKER_INTERFACE: WorkerInterface = WorkerInterface::new::<SumPlugin>();
+
+core::lv2_main!(core, SumPlugin, b"http://example.org/SumPlugin\0");
+
+/// A queue of copied payloads, with a limited capacity.
+struct Queue {
+    payloads: VecDeque<Vec<u8>>,
+    capacity: usize,
+}
+
+unsafe extern "C" fn push(handle: *mut c_void, size: u32, data: *const c_void) -> u32 {
+    let queue = (handle as *mut Queue).as_mut().unwrap();
+    if queue.payloads.len() >= queue.capacity {
+        return WorkerErr::NoSpace as u32;
+    }
+    let data = std::slice::from_raw_parts(data as *const u8, size as usize);
+    queue.payloads.push_back(data.to_owned());
+    0
+}
+
+#[test]
+fn test_worker() {
+    let mut work_queue = Queue {
+        payloads: VecDeque::new(),
+        capacity: 1,
+    };
+    let mut response_queue = Queue {
+        payloads: VecDeque::new(),
+        capacity: 16,
+    };
+    let mut schedule = WorkerSchedule {
+        handle: &mut work_queue as *mut Queue as *mut c_void,
+        schedule_work: push,
+    };
+    let schedule_feature = core::Feature::new(
+        worker::uris::SCHEDULE_URI.as_ptr(),
+        &mut schedule as *mut WorkerSchedule as *mut c_void,
+    );
+    let features: [*const core::Feature; 2] = [&schedule_feature, null()];
+
+    let descriptor = unsafe { lv2_descriptor(0) };
+    let descriptor_ref = unsafe { descriptor.as_ref() }.unwrap();
+
+    // The schedule feature is required.
+    let bundle_path = b"/\0".as_ptr() as *const c_char;
+    let empty_features: [*const core::Feature; 1] = [null()];
+    let handle = unsafe {
+        (descriptor_ref.instantiate)(descriptor, 44100.0, bundle_path, empty_features.as_ptr())
+    };
+    assert_eq!(null_mut(), handle);
+
+    let handle = unsafe {
+        (descriptor_ref.instantiate)(descriptor, 44100.0, bundle_path, features.as_ptr())
+    };
+    assert_ne!(null_mut(), handle);
+
+    let interface =
+        unsafe { (descriptor_ref.extension_data)(worker::uris::INTERFACE_URI.as_ptr()) };
+    let interface = unsafe { (interface as *const WorkerInterface).as_ref() }.unwrap();
+
+    // Scheduling work.
+    unsafe { (descriptor_ref.run)(handle, 4) };
+    let work = work_queue.payloads.pop_front().unwrap();
+    assert_eq!(4, work.len());
+
+    // Doing the work.
+    let response_handle = &mut response_queue as *mut Queue as *mut c_void;
+    let status = unsafe {
+        (interface.work)(
+            handle,
+            push,
+            response_handle,
+            work.len() as u32,
+            work.as_ptr() as *const c_void,
+        )
+    };
+    assert_eq!(0, status);
+
+    // Payloads of the wrong size are rejected.
+    let status = unsafe {
+        (interface.work)(
+            handle,
+            push,
+            response_handle,
+            2,
+            work.as_ptr() as *const c_void,
+        )
+    };
+    assert_eq!(WorkerErr::Unknown as u32, status);
+
+    // Delivering the response.
+    let response = response_queue.payloads.pop_front().unwrap();
+    assert!(response_queue.payloads.is_empty());
+    let status = unsafe {
+        (interface.work_response)(
+            handle,
+            response.len() as u32,
+            response.as_ptr() as *const c_void,
+        )
+    };
+    assert_eq!(0, status);
+    assert_eq!(0, unsafe { (interface.end_run.unwrap())(handle) });
+
+    {
+        let plugin = unsafe { core::get_instance::<SumPlugin>(handle) };
+        assert_eq!(vec![10], plugin.sums);
+        assert_eq!(1, plugin.ended_runs);
+    }
+
+    // A full queue is reported.
+    {
+        let plugin = unsafe { core::get_instance::<SumPlugin>(handle) };
+        plugin.schedule.schedule_work(1).unwrap();
+        assert_eq!(Err(WorkerErr::NoSpace), plugin.schedule.schedule_work(2));
+    }
+
+    unsafe { (descriptor_ref.cleanup)(handle) };
+}